@@ -0,0 +1,13 @@
+-- Idempotency keys for POST /api/v1/shorten retries (kept for 24 hours)
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    scope        TEXT NOT NULL,              -- actor that sent the key: `token:<id>` or `user:<id>`
+    key          TEXT NOT NULL,              -- client-supplied Idempotency-Key header
+    request_hash TEXT NOT NULL,              -- SHA-256 of the request body
+    response     JSONB NULL,                 -- NULL while the first request is in flight
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at   TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_expires_at_idx
+    ON idempotency_keys (expires_at);
@@ -0,0 +1,10 @@
+-- Duplicate handling is chosen per request (`dedupe`: reuse | always_new | error),
+-- so the schema must allow several links for one URL. The global UNIQUE (long_url)
+-- from the init migration also predates domains and soft delete: it rejected the same
+-- URL on a second domain and re-shortening a URL after its link was deleted.
+ALTER TABLE links DROP CONSTRAINT IF EXISTS links_long_url_key;
+
+-- Dedupe lookup: live links of a URL on a domain
+CREATE INDEX IF NOT EXISTS idx_links_long_url_domain
+    ON links (long_url, domain_id)
+    WHERE deleted_at IS NULL;
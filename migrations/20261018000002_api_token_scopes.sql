@@ -0,0 +1,8 @@
+-- Token permissions. Existing tokens keep full access.
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS scopes TEXT[] NOT NULL
+        DEFAULT ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin'];
+
+-- Domains a token may act on; NULL means every domain.
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS domain_ids BIGINT[] NULL;
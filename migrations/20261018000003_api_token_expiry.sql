@@ -0,0 +1,8 @@
+-- Optional token lifetime. NULL never expires.
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ NULL;
+
+-- Listing tokens that expire soon
+CREATE INDEX IF NOT EXISTS api_tokens_expires_at_idx
+    ON api_tokens (expires_at)
+    WHERE expires_at IS NOT NULL;
@@ -0,0 +1,10 @@
+-- New tokens get full access, including token management.
+ALTER TABLE api_tokens
+    ALTER COLUMN scopes
+    SET DEFAULT ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin'];
+
+-- Tokens that had every scope before `tokens:admin` existed keep full access.
+UPDATE api_tokens
+SET scopes = array_append(scopes, 'tokens:admin')
+WHERE scopes @> ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin']
+  AND NOT scopes @> ARRAY['tokens:admin'];
@@ -0,0 +1,14 @@
+-- Server-side dashboard sessions. The cookie holds the session ID; only its hash is stored.
+CREATE TABLE IF NOT EXISTS dashboard_sessions (
+    id_hash     TEXT PRIMARY KEY,               -- SHA-256 of the session ID
+    token_id    BIGINT NOT NULL REFERENCES api_tokens (id) ON DELETE CASCADE,
+    csrf_token  TEXT NOT NULL,                  -- expected X-CSRF-Token header value
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS dashboard_sessions_expires_at_idx
+    ON dashboard_sessions (expires_at);
+
+CREATE INDEX IF NOT EXISTS dashboard_sessions_token_id_idx
+    ON dashboard_sessions (token_id);
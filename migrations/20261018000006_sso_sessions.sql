@@ -0,0 +1,20 @@
+-- Users who sign in to the dashboard with single sign-on.
+CREATE TABLE IF NOT EXISTS users (
+    id          BIGSERIAL PRIMARY KEY,
+    -- Only an email the identity provider verified; NULL if it sent none.
+    email       TEXT UNIQUE,
+    sso_subject TEXT UNIQUE,                    -- OIDC `sub`; NULL until the user signs in with SSO
+    role        TEXT NOT NULL DEFAULT 'viewer'
+                CHECK (role IN ('viewer', 'editor', 'admin')),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Dashboard sessions of single sign-on users, who have no API token.
+ALTER TABLE dashboard_sessions
+    ALTER COLUMN token_id DROP NOT NULL,
+    ADD COLUMN user_id BIGINT REFERENCES users (id) ON DELETE CASCADE,
+    ADD CONSTRAINT dashboard_sessions_owner_check CHECK ((token_id IS NULL) <> (user_id IS NULL));
+
+-- Single sign-on sessions are looked up and cascade-deleted by user, like token sessions by token.
+CREATE INDEX IF NOT EXISTS dashboard_sessions_user_id_idx
+    ON dashboard_sessions (user_id);
@@ -0,0 +1,22 @@
+-- Teams group users; non-admin users see the links of their team.
+CREATE TABLE IF NOT EXISTS teams (
+    id          BIGSERIAL PRIMARY KEY,
+    name        TEXT NOT NULL UNIQUE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS team_id BIGINT REFERENCES teams (id) ON DELETE SET NULL;
+
+-- Who created a link, and the team it belongs to. NULL for links created
+-- before ownership existed or by tokens not bound to a user; only admins see those.
+ALTER TABLE links
+    ADD COLUMN IF NOT EXISTS owner_id BIGINT REFERENCES users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS team_id  BIGINT REFERENCES teams (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS links_owner_id_idx ON links (owner_id);
+CREATE INDEX IF NOT EXISTS links_team_id_idx ON links (team_id);
+
+-- A token bound to a user acts as that user; NULL keeps full visibility.
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS user_id BIGINT REFERENCES users (id) ON DELETE CASCADE;
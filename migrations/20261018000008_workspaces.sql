@@ -0,0 +1,43 @@
+-- Workspaces (tenants) own domains, links, tokens and users, with optional quotas.
+-- NULL quotas are unlimited.
+CREATE TABLE IF NOT EXISTS workspaces (
+    id                      BIGSERIAL PRIMARY KEY,
+    name                    TEXT NOT NULL UNIQUE,
+    max_links               BIGINT CHECK (max_links >= 0),          -- non-deleted links
+    max_domains             BIGINT CHECK (max_domains >= 0),        -- non-deleted domains
+    monthly_click_allowance BIGINT CHECK (monthly_click_allowance >= 0),
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Everything created before workspaces existed belongs to the default workspace.
+INSERT INTO workspaces (id, name) VALUES (1, 'default') ON CONFLICT (id) DO NOTHING;
+SELECT setval(pg_get_serial_sequence('workspaces', 'id'), GREATEST((SELECT MAX(id) FROM workspaces), 1));
+
+ALTER TABLE domains
+    ADD COLUMN IF NOT EXISTS workspace_id BIGINT NOT NULL DEFAULT 1 REFERENCES workspaces (id);
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS workspace_id BIGINT NOT NULL DEFAULT 1 REFERENCES workspaces (id);
+-- Single sign-on users are created in the default workspace.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS workspace_id BIGINT NOT NULL DEFAULT 1 REFERENCES workspaces (id);
+
+-- A link always lives in the workspace of its domain.
+ALTER TABLE domains
+    ADD CONSTRAINT domains_id_workspace_key UNIQUE (id, workspace_id);
+ALTER TABLE links
+    ADD COLUMN IF NOT EXISTS workspace_id BIGINT NOT NULL DEFAULT 1,
+    ADD CONSTRAINT links_domain_workspace_fkey
+        FOREIGN KEY (domain_id, workspace_id) REFERENCES domains (id, workspace_id);
+
+CREATE INDEX IF NOT EXISTS links_workspace_id_idx ON links (workspace_id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS domains_workspace_id_idx ON domains (workspace_id);
+CREATE INDEX IF NOT EXISTS api_tokens_workspace_id_idx ON api_tokens (workspace_id);
+
+-- Monthly click allowance: clicks since the start of the month.
+CREATE INDEX IF NOT EXISTS link_clicks_clicked_at_idx ON link_clicks (clicked_at);
+
+-- Each workspace has its own default domain.
+DROP INDEX IF EXISTS idx_domains_single_default;
+CREATE UNIQUE INDEX idx_domains_single_default
+    ON domains (workspace_id)
+    WHERE is_default = TRUE;
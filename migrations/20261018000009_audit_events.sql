@@ -0,0 +1,45 @@
+-- Append-only log of changes to links, domains and tokens.
+CREATE TABLE IF NOT EXISTS audit_events (
+    id             BIGSERIAL PRIMARY KEY,
+    workspace_id   BIGINT NOT NULL REFERENCES workspaces (id),
+    entity         TEXT NOT NULL CHECK (entity IN ('link', 'domain', 'token')),
+    entity_id      BIGINT NOT NULL,
+    action         TEXT NOT NULL CHECK (action IN ('create', 'update', 'delete', 'restore')),
+    -- No foreign keys: events outlive the tokens and users that caused them.
+    actor_token_id BIGINT,
+    actor_user_id  BIGINT,
+    actor_name     TEXT NOT NULL,                   -- token name, user email or `admin-cli`
+    ip             TEXT,
+    before         JSONB,                           -- NULL for `create`
+    after          JSONB,                           -- NULL for `delete`
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_workspace_created_at_idx
+    ON audit_events (workspace_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS audit_events_entity_idx ON audit_events (entity, entity_id);
+
+CREATE OR REPLACE FUNCTION audit_events_reject_change() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION audit_events_reject_change();
+
+CREATE TRIGGER audit_events_no_truncate
+    BEFORE TRUNCATE ON audit_events
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_events_reject_change();
+
+-- New tokens get full access, including the audit log.
+ALTER TABLE api_tokens
+    ALTER COLUMN scopes
+    SET DEFAULT ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin', 'audit:read'];
+
+-- Tokens that had every scope before `audit:read` existed keep full access.
+UPDATE api_tokens
+SET scopes = array_append(scopes, 'audit:read')
+WHERE scopes @> ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin']
+  AND NOT scopes @> ARRAY['audit:read'];
@@ -0,0 +1,21 @@
+-- Destination, expiry and redirect type of every version of a link.
+CREATE TABLE IF NOT EXISTS link_versions (
+    id             BIGSERIAL PRIMARY KEY,
+    link_id        BIGINT NOT NULL REFERENCES links (id) ON DELETE CASCADE,
+    version        INTEGER NOT NULL,                -- 1 for the link as created
+    long_url       TEXT NOT NULL,
+    expires_at     TIMESTAMPTZ,
+    permanent      BOOLEAN NOT NULL,
+    reverted_from  INTEGER,                         -- version this one restored, if any
+    actor_token_id BIGINT,
+    actor_user_id  BIGINT,
+    actor_name     TEXT,                            -- NULL for versions from before history was kept
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT link_versions_link_version_key UNIQUE (link_id, version)
+);
+
+-- Existing links start their history with their current state.
+INSERT INTO link_versions (link_id, version, long_url, expires_at, permanent, created_at)
+SELECT id, 1, long_url, expires_at, permanent, created_at
+FROM links
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,10 @@
+-- New tokens get full access, including cache administration.
+ALTER TABLE api_tokens
+    ALTER COLUMN scopes
+    SET DEFAULT ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin', 'audit:read', 'cache:admin'];
+
+-- Tokens that had every scope before `cache:admin` existed keep full access.
+UPDATE api_tokens
+SET scopes = array_append(scopes, 'cache:admin')
+WHERE scopes @> ARRAY['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin', 'audit:read']
+  AND NOT scopes @> ARRAY['cache:admin'];
@@ -0,0 +1,22 @@
+-- links.clicks is now maintained by the click counter flush, which adds the
+-- clicks recorded after counted_up_to, the highest link_clicks ID already
+-- counted, and moves it past them. Start both from the clicks recorded so far.
+CREATE TABLE IF NOT EXISTS click_counter_watermark (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    counted_up_to BIGINT NOT NULL
+);
+
+-- Waits for clicks being inserted, so none below the watermark is missed.
+LOCK TABLE link_clicks IN SHARE MODE;
+
+UPDATE links
+SET clicks = counts.clicks
+FROM (
+    SELECT link_id, COUNT(*) AS clicks
+    FROM link_clicks
+    GROUP BY link_id
+) AS counts
+WHERE links.id = counts.link_id;
+
+INSERT INTO click_counter_watermark (counted_up_to)
+SELECT COALESCE(MAX(id), 0) FROM link_clicks;
@@ -0,0 +1,18 @@
+-- Teams belong to a workspace, so team names only need to be unique within one.
+-- Existing teams move to the workspace of their first member, or stay in the default one.
+ALTER TABLE teams
+    ADD COLUMN IF NOT EXISTS workspace_id BIGINT NOT NULL DEFAULT 1 REFERENCES workspaces (id);
+
+UPDATE teams
+SET workspace_id = members.workspace_id
+FROM (
+    SELECT DISTINCT ON (team_id) team_id, workspace_id
+    FROM users
+    WHERE team_id IS NOT NULL
+    ORDER BY team_id, id
+) AS members
+WHERE teams.id = members.team_id;
+
+ALTER TABLE teams
+    DROP CONSTRAINT IF EXISTS teams_name_key,
+    ADD CONSTRAINT teams_workspace_id_name_key UNIQUE (workspace_id, name);
@@ -0,0 +1,181 @@
+//! DTOs for the audit log.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{ObjectBuilder, Required, Type};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::dto::pagination::{DateFilterParams, PaginationParams};
+use crate::api::dto::stats_list::PaginationMeta;
+use crate::domain::entities::{AuditAction, AuditActor, AuditEntity, AuditEvent};
+
+/// Kind of record an audit event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntityKind {
+    Link,
+    Domain,
+    Token,
+}
+
+impl From<AuditEntityKind> for AuditEntity {
+    fn from(kind: AuditEntityKind) -> Self {
+        match kind {
+            AuditEntityKind::Link => AuditEntity::Link,
+            AuditEntityKind::Domain => AuditEntity::Domain,
+            AuditEntityKind::Token => AuditEntity::Token,
+        }
+    }
+}
+
+impl From<AuditEntity> for AuditEntityKind {
+    fn from(entity: AuditEntity) -> Self {
+        match entity {
+            AuditEntity::Link => AuditEntityKind::Link,
+            AuditEntity::Domain => AuditEntityKind::Domain,
+            AuditEntity::Token => AuditEntityKind::Token,
+        }
+    }
+}
+
+/// What happened to the record. Revoking a token is a `delete`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditActionKind {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl From<AuditAction> for AuditActionKind {
+    fn from(action: AuditAction) -> Self {
+        match action {
+            AuditAction::Create => AuditActionKind::Create,
+            AuditAction::Update => AuditActionKind::Update,
+            AuditAction::Delete => AuditActionKind::Delete,
+            AuditAction::Restore => AuditActionKind::Restore,
+        }
+    }
+}
+
+/// Query parameters of `GET /api/audit`.
+#[derive(Debug, Deserialize)]
+pub struct AuditQueryParams {
+    #[serde(flatten)]
+    pub pagination: PaginationParams,
+
+    #[serde(flatten)]
+    pub date_filter: DateFilterParams,
+
+    pub entity: Option<AuditEntityKind>,
+
+    pub actor: Option<String>,
+}
+
+/// Lists the flattened parameters individually, like
+/// [`crate::api::dto::pagination::StatsQueryParams`].
+impl IntoParams for AuditQueryParams {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        let mut params = PaginationParams::into_params(&parameter_in_provider);
+        let string_param = |name: &str, description: &str| {
+            ParameterBuilder::new()
+                .name(name)
+                .parameter_in(parameter_in_provider().unwrap_or_default())
+                .required(Required::False)
+                .description(Some(description))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+                .build()
+        };
+        params.push(
+            ParameterBuilder::new()
+                .name("entity")
+                .parameter_in(parameter_in_provider().unwrap_or_default())
+                .required(Required::False)
+                .description(Some("Only include changes to this kind of record."))
+                .schema(Some(
+                    ObjectBuilder::new()
+                        .schema_type(Type::String)
+                        .enum_values(Some(["link", "domain", "token"])),
+                ))
+                .build(),
+        );
+        params.push(string_param(
+            "actor",
+            "Only include changes made by this token name, token ID or user email.",
+        ));
+        params.push(string_param(
+            "from",
+            "Only include changes at or after this RFC 3339 timestamp.",
+        ));
+        params.push(string_param(
+            "to",
+            "Only include changes at or before this RFC 3339 timestamp.",
+        ));
+        params
+    }
+}
+
+/// Who made a change.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditActorItem {
+    /// API token used; `null` for single sign-on users and the admin CLI.
+    pub token_id: Option<i64>,
+    pub user_id: Option<i64>,
+    /// Token name, user email or `admin-cli`.
+    pub name: String,
+}
+
+impl From<AuditActor> for AuditActorItem {
+    fn from(actor: AuditActor) -> Self {
+        Self {
+            token_id: actor.token_id,
+            user_id: actor.user_id,
+            name: actor.name,
+        }
+    }
+}
+
+/// A recorded change to a link, domain or token.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditEventItem {
+    pub id: i64,
+    pub entity: AuditEntityKind,
+    pub entity_id: i64,
+    pub action: AuditActionKind,
+    pub actor: AuditActorItem,
+    /// Client IP of the request; `null` for the admin CLI.
+    pub ip: Option<String>,
+    /// The record before the change; `null` for `create`.
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+    /// The record after the change; `null` for `delete`.
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEvent> for AuditEventItem {
+    fn from(event: AuditEvent) -> Self {
+        Self {
+            id: event.id,
+            entity: event.entity.into(),
+            entity_id: event.entity_id,
+            action: event.action.into(),
+            actor: event.actor.into(),
+            ip: event.ip,
+            before: event.before,
+            after: event.after,
+            created_at: event.created_at,
+        }
+    }
+}
+
+/// Paginated audit log, newest first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditListResponse {
+    pub pagination: PaginationMeta,
+    pub items: Vec<AuditEventItem>,
+}
@@ -0,0 +1,85 @@
+//! DTOs for cache administration.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::infrastructure::cache::{CacheEntry, CacheStats, CachedRedirect};
+
+/// What the cache backend reports about itself; fields it can't tell are `null`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheStatsResponse {
+    /// `redis`, `tiered` (in-process cache in front of Redis) or `none`.
+    #[schema(example = "tiered")]
+    pub backend: String,
+    /// Cached redirects in Redis.
+    #[schema(example = 1520)]
+    pub keys: Option<u64>,
+    /// Entries in the in-process cache of the instance that answered.
+    #[schema(example = 310)]
+    pub local_entries: Option<u64>,
+    /// Lookups Redis answered since it started, for all of its users.
+    pub hits: Option<u64>,
+    /// Lookups Redis could not answer since it started.
+    pub misses: Option<u64>,
+    /// Memory used by Redis.
+    pub used_memory_bytes: Option<u64>,
+}
+
+impl From<CacheStats> for CacheStatsResponse {
+    fn from(stats: CacheStats) -> Self {
+        Self {
+            backend: stats.backend.to_string(),
+            keys: stats.keys,
+            local_entries: stats.local_entries,
+            hits: stats.hits,
+            misses: stats.misses,
+            used_memory_bytes: stats.used_memory_bytes,
+        }
+    }
+}
+
+/// The cached redirect answer of a short code.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheKeyResponse {
+    #[schema(example = "s.example.com:abc123")]
+    pub key: String,
+    /// `found`, `not_found`, `deleted` or `expired`; `unreadable` for entries
+    /// of another format version.
+    #[schema(example = "found")]
+    pub status: String,
+    /// The entry as stored.
+    pub value: String,
+    /// Seconds until the entry expires, if known.
+    #[schema(example = 3540)]
+    pub ttl_seconds: Option<u64>,
+}
+
+impl CacheKeyResponse {
+    /// Describes the entry cached under `key`.
+    pub fn new(key: String, entry: CacheEntry) -> Self {
+        let status = match CachedRedirect::parse(&entry.value) {
+            Some(CachedRedirect::Found(_)) => "found",
+            Some(CachedRedirect::NotFound { .. }) => "not_found",
+            Some(CachedRedirect::Deleted) => "deleted",
+            Some(CachedRedirect::Expired) => "expired",
+            None => "unreadable",
+        };
+
+        Self {
+            key,
+            status: status.to_string(),
+            value: entry.value,
+            ttl_seconds: entry.ttl.map(|ttl| ttl.as_secs()),
+        }
+    }
+}
+
+/// Result of purging the cached redirects of a domain.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PurgeDomainResponse {
+    #[schema(example = "s.example.com")]
+    pub domain: String,
+    /// Entries removed from Redis.
+    #[schema(example = 42)]
+    pub removed: u64,
+}
@@ -2,11 +2,12 @@
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 /// Individual click event information.
 ///
 /// Optional fields are omitted from JSON when `None` for cleaner responses.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ClickInfo {
     pub clicked_at: DateTime<Utc>,
 
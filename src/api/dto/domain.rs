@@ -3,9 +3,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use utoipa::ToSchema;
 
 /// Individual domain information (used in all domain responses).
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DomainItem {
     pub id: i64,
     pub domain: String,
@@ -18,13 +19,14 @@ pub struct DomainItem {
 }
 
 /// Response containing list of domains.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DomainListResponse {
     pub items: Vec<DomainItem>,
 }
 
 /// Request body for `POST /api/domains`.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({ "domain": "go.example.com", "description": "Campaign links" }))]
 pub struct CreateDomainRequest {
     pub domain: String,
     /// When true, this domain becomes the system default. Defaults to false.
@@ -42,11 +44,13 @@ pub struct CreateDomainRequest {
 /// - `null` → clear (set to NULL)
 /// - String value → set to that value
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({ "description": "Primary short domain" }))]
 pub struct UpdateDomainRequest {
     pub domain: Option<String>,
     pub is_default: Option<bool>,
     pub is_active: Option<bool>,
     #[serde(default, with = "::serde_with::rust::double_option")]
+    #[schema(value_type = Option<String>)]
     pub description: Option<Option<String>>,
 }
@@ -1,9 +1,10 @@
 //! DTOs for health check endpoint.
 
 use serde::Serialize;
+use utoipa::ToSchema;
 
 /// Health check response with component status.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
@@ -11,7 +12,7 @@ pub struct HealthResponse {
 }
 
 /// Health status for each system component.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthChecks {
     pub database: CheckStatus,
     pub click_queue: CheckStatus,
@@ -19,7 +20,7 @@ pub struct HealthChecks {
 }
 
 /// Individual component health status.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CheckStatus {
     pub status: String,
 
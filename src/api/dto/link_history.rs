@@ -0,0 +1,45 @@
+//! DTOs for link version history.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::api::dto::audit::AuditActorItem;
+use crate::domain::entities::LinkVersion;
+
+/// One version of a link.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LinkVersionItem {
+    /// 1 for the link as created, increasing by one per change.
+    pub version: i32,
+    pub long_url: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub permanent: bool,
+    /// Version this one restored, if it was created by a revert.
+    pub reverted_from: Option<i32>,
+    /// Who made the change; `null` for versions from before history was kept.
+    pub actor: Option<AuditActorItem>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<LinkVersion> for LinkVersionItem {
+    fn from(version: LinkVersion) -> Self {
+        Self {
+            version: version.version,
+            long_url: version.long_url,
+            expires_at: version.expires_at,
+            permanent: version.permanent,
+            reverted_from: version.reverted_from,
+            actor: version.actor.map(Into::into),
+            created_at: version.created_at,
+        }
+    }
+}
+
+/// Versions of a link, newest first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LinkHistoryResponse {
+    pub code: String,
+    pub domain: Option<String>,
+    pub items: Vec<LinkVersionItem>,
+}
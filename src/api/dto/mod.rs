@@ -3,11 +3,15 @@
 //! All DTOs use Serde for JSON serialization/deserialization and validator
 //! for input validation.
 
+pub mod audit;
+pub mod cache;
 pub mod clicks;
 pub mod domain;
 pub mod health;
+pub mod link_history;
 pub mod pagination;
 pub mod shorten;
 pub mod stats;
 pub mod stats_list;
+pub mod token;
 pub mod update_link;
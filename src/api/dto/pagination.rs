@@ -3,19 +3,27 @@
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{ObjectBuilder, Required, Type};
 
 /// Pagination query parameters.
 ///
 /// Uses `serde_with` to parse page numbers from query strings as integers.
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationParams {
+    /// Page number, starting at 1 (default: 1).
     #[serde_as(as = "Option<DisplayFromStr>")]
     #[serde(default)]
+    #[param(minimum = 1)]
     pub page: Option<u32>,
 
+    /// Items per page, 10–1000 (default: 25).
     #[serde_as(as = "Option<DisplayFromStr>")]
     #[serde(default)]
+    #[param(minimum = 10, maximum = 1000)]
     pub page_size: Option<u32>,
 }
 
@@ -55,11 +63,14 @@ impl PaginationParams {
 }
 
 /// Date range filtering parameters.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct DateFilterParams {
+    /// Only count clicks at or after this RFC 3339 timestamp.
     #[serde(default, with = "optional_rfc3339")]
     pub from: Option<DateTime<Utc>>,
 
+    /// Only count clicks at or before this RFC 3339 timestamp.
     #[serde(default, with = "optional_rfc3339")]
     pub to: Option<DateTime<Utc>>,
 }
@@ -95,6 +106,25 @@ pub struct StatsQueryParams {
     pub domain: Option<String>,
 }
 
+/// Lists the flattened pagination and date filter parameters individually,
+/// since `#[derive(IntoParams)]` does not follow `#[serde(flatten)]`.
+impl IntoParams for StatsQueryParams {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        let mut params = PaginationParams::into_params(&parameter_in_provider);
+        params.extend(DateFilterParams::into_params(&parameter_in_provider));
+        params.push(
+            ParameterBuilder::new()
+                .name("domain")
+                .parameter_in(parameter_in_provider().unwrap_or_default())
+                .required(Required::False)
+                .description(Some("Only include links on this domain."))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+                .build(),
+        );
+        params
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
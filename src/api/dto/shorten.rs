@@ -1,10 +1,12 @@
 //! DTOs for link shortening endpoint.
 
+use crate::domain::entities::DedupePolicy;
 use crate::error::ErrorInfo;
 use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
+use utoipa::ToSchema;
 use validator::Validate;
 
 /// Compiled regex for custom code validation.
@@ -13,17 +15,19 @@ static CUSTOM_CODE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z0
 /// Request to shorten one or more URLs.
 ///
 /// Supports batch processing for efficiency when creating multiple links.
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[schema(example = json!({ "urls": [{ "url": "https://example.com/some/long/path" }] }))]
 pub struct ShortenRequest {
     #[validate(nested)]
     pub urls: Vec<UrlItem>,
 }
 
 /// Individual URL to be shortened.
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct UrlItem {
     /// The original URL to shorten (must be valid HTTP/HTTPS).
     #[validate(url(message = "Invalid URL format"))]
+    #[schema(example = "https://example.com/some/long/path")]
     pub url: String,
 
     /// Optional domain override (otherwise uses default domain).
@@ -32,6 +36,12 @@ pub struct UrlItem {
     /// Optional custom short code (validated for length and characters).
     #[validate(length(min = 4, max = 50))]
     #[validate(regex(path = "*CUSTOM_CODE_REGEX"))]
+    #[schema(
+        min_length = 4,
+        max_length = 50,
+        pattern = "^[a-z0-9-]+$",
+        example = "my-link"
+    )]
     pub custom_code: Option<String>,
 
     /// Optional expiry timestamp. After this time, the link returns 410 Gone.
@@ -39,10 +49,39 @@ pub struct UrlItem {
 
     /// When true, uses 301 Permanent Redirect instead of 307 Temporary.
     pub permanent: Option<bool>,
+
+    /// What to do if the URL is already shortened on the domain (default: `reuse`).
+    #[serde(default)]
+    pub dedupe: Dedupe,
+}
+
+/// Duplicate handling for a URL that is already shortened on the domain.
+///
+/// - `reuse` - return an existing link with the same code (if `custom_code` is set),
+///   `expires_at` and `permanent`, marked `reused: true`; otherwise create a new one
+/// - `always_new` - always create a new link
+/// - `error` - fail the item with `conflict`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Dedupe {
+    #[default]
+    Reuse,
+    AlwaysNew,
+    Error,
+}
+
+impl From<Dedupe> for DedupePolicy {
+    fn from(dedupe: Dedupe) -> Self {
+        match dedupe {
+            Dedupe::Reuse => DedupePolicy::Reuse,
+            Dedupe::AlwaysNew => DedupePolicy::AlwaysNew,
+            Dedupe::Error => DedupePolicy::Error,
+        }
+    }
 }
 
 /// Response containing batch processing results.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ShortenResponse {
     pub summary: BatchSummary,
     pub items: Vec<ShortenResultItem>,
@@ -51,13 +90,15 @@ pub struct ShortenResponse {
 /// Individual result for a URL in the batch.
 ///
 /// Uses untagged enum for cleaner JSON structure (no discriminator field).
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(untagged)]
 pub enum ShortenResultItem {
     Success {
         long_url: String,
         code: String,
         short_url: String,
+        /// `true` when an existing link was returned instead of creating one.
+        reused: bool,
     },
     Error {
         long_url: String,
@@ -66,7 +107,7 @@ pub enum ShortenResultItem {
 }
 
 /// Summary statistics for batch processing.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchSummary {
     pub total: usize,
     pub successful: usize,
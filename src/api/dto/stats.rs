@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use super::clicks::ClickInfo;
 use super::stats_list::PaginationMeta;
@@ -9,7 +10,7 @@ use super::stats_list::PaginationMeta;
 /// Detailed statistics for a specific short link.
 ///
 /// Includes link metadata, total click count, and paginated click records.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StatsResponse {
     pub pagination: PaginationMeta,
     pub code: String,
@@ -2,16 +2,17 @@
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 /// Paginated list of link statistics.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StatsListResponse {
     pub pagination: PaginationMeta,
     pub items: Vec<LinkStatsItem>,
 }
 
 /// Aggregated statistics for a single link.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LinkStatsItem {
     pub code: String,
     pub domain: Option<String>,
@@ -21,7 +22,7 @@ pub struct LinkStatsItem {
 }
 
 /// Pagination metadata for responses.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginationMeta {
     pub page: u32,
     pub page_size: u32,
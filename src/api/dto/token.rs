@@ -0,0 +1,112 @@
+//! DTOs for API token management.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::domain::entities::Scope;
+use crate::domain::repositories::ApiToken;
+
+/// Permission granted to an API token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum TokenScope {
+    #[serde(rename = "links:read")]
+    LinksRead,
+    #[serde(rename = "links:write")]
+    LinksWrite,
+    #[serde(rename = "stats:read")]
+    StatsRead,
+    #[serde(rename = "domains:admin")]
+    DomainsAdmin,
+    #[serde(rename = "tokens:admin")]
+    TokensAdmin,
+    #[serde(rename = "audit:read")]
+    AuditRead,
+    #[serde(rename = "cache:admin")]
+    CacheAdmin,
+}
+
+impl From<TokenScope> for Scope {
+    fn from(scope: TokenScope) -> Self {
+        match scope {
+            TokenScope::LinksRead => Scope::LinksRead,
+            TokenScope::LinksWrite => Scope::LinksWrite,
+            TokenScope::StatsRead => Scope::StatsRead,
+            TokenScope::DomainsAdmin => Scope::DomainsAdmin,
+            TokenScope::TokensAdmin => Scope::TokensAdmin,
+            TokenScope::AuditRead => Scope::AuditRead,
+            TokenScope::CacheAdmin => Scope::CacheAdmin,
+        }
+    }
+}
+
+impl From<Scope> for TokenScope {
+    fn from(scope: Scope) -> Self {
+        match scope {
+            Scope::LinksRead => TokenScope::LinksRead,
+            Scope::LinksWrite => TokenScope::LinksWrite,
+            Scope::StatsRead => TokenScope::StatsRead,
+            Scope::DomainsAdmin => TokenScope::DomainsAdmin,
+            Scope::TokensAdmin => TokenScope::TokensAdmin,
+            Scope::AuditRead => TokenScope::AuditRead,
+            Scope::CacheAdmin => TokenScope::CacheAdmin,
+        }
+    }
+}
+
+/// Token metadata. The token value itself is never returned after creation.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenItem {
+    pub id: i64,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Domains the token is limited to; `null` means every domain.
+    pub domain_ids: Option<Vec<i64>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiToken> for TokenItem {
+    fn from(token: ApiToken) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            scopes: token.scopes.into_iter().map(TokenScope::from).collect(),
+            domain_ids: token.domain_ids,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            revoked_at: token.revoked_at,
+        }
+    }
+}
+
+/// Response containing list of tokens.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenListResponse {
+    pub items: Vec<TokenItem>,
+}
+
+/// Request body for `POST /api/v1/tokens`.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[schema(example = json!({ "name": "CI pipeline", "scopes": ["links:write"] }))]
+pub struct CreateTokenRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(min_length = 1, max_length = 100)]
+    pub name: String,
+    /// Scopes to grant; must be a subset of the caller's scopes. Defaults to the caller's scopes.
+    pub scopes: Option<Vec<TokenScope>>,
+    /// Domain IDs to limit the token to. Defaults to every domain.
+    pub domain_ids: Option<Vec<i64>>,
+    /// When the token stops working. Defaults to never.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `POST /api/v1/tokens`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedTokenResponse {
+    pub token: TokenItem,
+    /// Token value for the `Authorization: Bearer` header. Shown only once.
+    pub plaintext: String,
+}
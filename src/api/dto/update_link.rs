@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_with::serde_as;
+use utoipa::ToSchema;
 use validator::Validate;
 
 /// Request body for `PATCH /api/links/{code}`.
@@ -15,7 +16,8 @@ use validator::Validate;
 /// - **`null`** → clear expiry (link never expires)
 /// - **Timestamp** → set new expiry
 #[serde_as]
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[schema(example = json!({ "permanent": true }))]
 pub struct UpdateLinkRequest {
     /// New destination URL for this link.
     #[validate(url(message = "Invalid URL format"))]
@@ -23,6 +25,7 @@ pub struct UpdateLinkRequest {
 
     /// Expiry timestamp. Absent = no change, null = clear, value = set.
     #[serde(default, with = "::serde_with::rust::double_option")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
     pub expires_at: Option<Option<DateTime<Utc>>>,
 
     /// Change redirect type: true = 301 permanent, false = 307 temporary.
@@ -0,0 +1,90 @@
+//! Handler for the audit log endpoint.
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde_json::json;
+
+use crate::api::dto::audit::{AuditListResponse, AuditQueryParams};
+use crate::api::dto::stats_list::PaginationMeta;
+use crate::domain::entities::{Principal, Scope};
+use crate::domain::repositories::AuditFilter;
+use crate::error::{AppError, ErrorBody};
+use crate::state::AppState;
+
+/// Lists changes to links, domains and tokens of the caller's workspace.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/audit`
+///
+/// # Query Parameters
+///
+/// - `entity` (optional): `link`, `domain` or `token`
+/// - `actor` (optional): Token name, token ID or user email
+/// - `from` / `to` (optional): Time range (RFC3339 format)
+/// - `page` / `page_size` (optional): Pagination (default: 1 / 25)
+///
+/// Requires the `audit:read` scope on a token that is not limited to specific
+/// domains, since the log covers every domain of the workspace.
+///
+/// # Errors
+///
+/// Returns 400 Bad Request if the filters or pagination are invalid.
+/// Returns 403 Forbidden if the token lacks the scope or is domain-restricted.
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit",
+    operation_id = "list_audit_events",
+    tag = "audit",
+    params(AuditQueryParams),
+    responses(
+        (status = 200, description = "Paginated audit events, newest first", body = AuditListResponse),
+        (status = 400, description = "Invalid filter or pagination", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `audit:read` or is limited to specific domains", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn audit_list_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+    Query(params): Query<AuditQueryParams>,
+) -> Result<Json<AuditListResponse>, AppError> {
+    principal.require_scope(Scope::AuditRead)?;
+    if principal.is_domain_restricted() {
+        return Err(AppError::forbidden(
+            "Domain-restricted tokens cannot read the audit log",
+            json!({}),
+        ));
+    }
+
+    let (offset, limit) = params
+        .pagination
+        .validate_and_get_offset_limit()
+        .map_err(|e| AppError::bad_request(e, json!({})))?;
+
+    let page = params.pagination.page.unwrap_or(1);
+    let page_size = params.pagination.page_size.unwrap_or(25);
+
+    let filter = AuditFilter::new(offset, limit)
+        .with_workspace(Some(principal.workspace_id))
+        .with_entity(params.entity.map(Into::into))
+        .with_actor(params.actor.filter(|actor| !actor.is_empty()))
+        .with_date_range(params.date_filter.from, params.date_filter.to);
+
+    let (events, total_items) = state.audit_service.list(filter).await?;
+
+    let total_pages = ((total_items as f64) / (page_size as f64)).ceil() as u32;
+
+    Ok(Json(AuditListResponse {
+        pagination: PaginationMeta {
+            page,
+            page_size,
+            total_items,
+            total_pages,
+        },
+        items: events.into_iter().map(Into::into).collect(),
+    }))
+}
@@ -0,0 +1,197 @@
+//! Handlers for inspecting and purging the redirect cache.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde_json::json;
+
+use crate::api::dto::cache::{CacheKeyResponse, CacheStatsResponse, PurgeDomainResponse};
+use crate::domain::entities::{Principal, Scope};
+use crate::error::{AppError, ErrorBody};
+use crate::infrastructure::cache::CachedRedirect;
+use crate::state::AppState;
+
+/// Returns key counts, hits and memory use of the cache.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/cache/stats`
+///
+/// Requires the `cache:admin` scope on a token that is not limited to specific
+/// domains, since the cache is shared by every domain.
+///
+/// # Errors
+///
+/// Returns 403 Forbidden if the token lacks the scope or is domain-restricted.
+/// Returns 500 Internal Server Error if the cache backend fails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/cache/stats",
+    operation_id = "get_cache_stats",
+    tag = "cache",
+    responses(
+        (status = 200, description = "Cache statistics", body = CacheStatsResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `cache:admin` or is limited to specific domains", body = ErrorBody),
+        (status = 500, description = "Cache backend failed", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn cache_stats_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+) -> Result<Json<CacheStatsResponse>, AppError> {
+    principal.require_scope(Scope::CacheAdmin)?;
+    if principal.is_domain_restricted() {
+        return Err(AppError::forbidden(
+            "Domain-restricted tokens cannot read cache statistics",
+            json!({}),
+        ));
+    }
+
+    let stats = state.cache_admin_service.stats().await?;
+
+    Ok(Json(stats.into()))
+}
+
+/// Returns the cached redirect of a short code.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/cache/keys/{domain}/{code}`
+///
+/// # Errors
+///
+/// Returns 403 Forbidden if the token lacks `cache:admin` or access to the domain.
+/// Returns 404 Not Found if the domain doesn't exist or nothing is cached for the code.
+/// Returns 410 Gone if the domain has been deleted.
+#[utoipa::path(
+    get,
+    path = "/api/v1/cache/keys/{domain}/{code}",
+    operation_id = "get_cache_key",
+    tag = "cache",
+    params(
+        ("domain" = String, Path, description = "Domain of the short link", example = "s.example.com"),
+        ("code" = String, Path, description = "Short code", example = "abc123"),
+    ),
+    responses(
+        (status = 200, description = "Cached entry", body = CacheKeyResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `cache:admin` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Domain not found or nothing cached", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_cache_key_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path((domain, code)): Path<(String, String)>,
+) -> Result<Json<CacheKeyResponse>, AppError> {
+    require_cache_admin(&state, &principal, &domain).await?;
+
+    let key = CachedRedirect::key(&domain, &code);
+    match state.cache_admin_service.inspect(&domain, &code).await? {
+        Some(entry) => Ok(Json(CacheKeyResponse::new(key, entry))),
+        None => Err(AppError::not_found(
+            "Nothing cached for this code",
+            json!({ "key": key }),
+        )),
+    }
+}
+
+/// Removes the cached redirect of a short code on every instance.
+///
+/// # Endpoint
+///
+/// `DELETE /api/v1/cache/keys/{domain}/{code}`
+///
+/// Succeeds whether or not anything was cached.
+///
+/// # Errors
+///
+/// Returns 403 Forbidden if the token lacks `cache:admin` or access to the domain.
+/// Returns 404 Not Found if the domain doesn't exist.
+/// Returns 410 Gone if the domain has been deleted.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cache/keys/{domain}/{code}",
+    operation_id = "purge_cache_key",
+    tag = "cache",
+    params(
+        ("domain" = String, Path, description = "Domain of the short link", example = "s.example.com"),
+        ("code" = String, Path, description = "Short code", example = "abc123"),
+    ),
+    responses(
+        (status = 204, description = "Entry purged"),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `cache:admin` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn purge_cache_key_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path((domain, code)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    require_cache_admin(&state, &principal, &domain).await?;
+
+    state.cache_admin_service.purge(&domain, &code).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Removes the cached redirects of every code on a domain on every instance.
+///
+/// # Endpoint
+///
+/// `DELETE /api/v1/cache/domains/{domain}`
+///
+/// # Errors
+///
+/// Returns 403 Forbidden if the token lacks `cache:admin` or access to the domain.
+/// Returns 404 Not Found if the domain doesn't exist.
+/// Returns 410 Gone if the domain has been deleted.
+/// Returns 500 Internal Server Error if the cache backend fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cache/domains/{domain}",
+    operation_id = "purge_cache_domain",
+    tag = "cache",
+    params(("domain" = String, Path, description = "Domain to purge", example = "s.example.com")),
+    responses(
+        (status = 200, description = "Entries purged", body = PurgeDomainResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `cache:admin` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+        (status = 500, description = "Cache backend failed", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn purge_cache_domain_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(domain): Path<String>,
+) -> Result<Json<PurgeDomainResponse>, AppError> {
+    require_cache_admin(&state, &principal, &domain).await?;
+
+    let removed = state.cache_admin_service.purge_domain(&domain).await?;
+
+    Ok(Json(PurgeDomainResponse { domain, removed }))
+}
+
+/// Fails unless the caller has `cache:admin` and may act on `domain`.
+async fn require_cache_admin(
+    state: &AppState,
+    principal: &Principal,
+    domain: &str,
+) -> Result<(), AppError> {
+    principal.require_scope(Scope::CacheAdmin)?;
+    let domain_entity = state.domain_service.get_domain(domain).await?;
+    principal.require_domain(&domain_entity)
+}
@@ -9,9 +9,11 @@ use axum::{
 use crate::api::dto::domain::{
     CreateDomainRequest, DomainItem, DomainListResponse, UpdateDomainRequest,
 };
-use crate::domain::entities::{Domain, UpdateDomain};
-use crate::error::AppError;
+use crate::api::middleware::client_ip::ClientIp;
+use crate::domain::entities::{AuditActor, AuditContext, Domain, Principal, Scope, UpdateDomain};
+use crate::error::{AppError, ErrorBody};
 use crate::state::AppState;
+use serde_json::json;
 
 fn domain_to_item(d: Domain) -> DomainItem {
     DomainItem {
@@ -26,41 +28,98 @@ fn domain_to_item(d: Domain) -> DomainItem {
     }
 }
 
-/// Lists all non-deleted domains.
+/// Lists all non-deleted domains of the caller's workspace.
 ///
 /// # Endpoint
 ///
 /// `GET /api/domains`
+///
+/// Requires `links:read` or `domains:admin`. Domain-restricted tokens only
+/// see their own domains.
+#[utoipa::path(
+    get,
+    path = "/api/v1/domains",
+    operation_id = "list_domains",
+    tag = "domains",
+    responses(
+        (status = 200, description = "All non-deleted domains the token may use", body = DomainListResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:read` and `domains:admin`", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn domain_list_handler(
     State(state): State<AppState>,
+    principal: Principal,
 ) -> Result<Json<DomainListResponse>, AppError> {
-    let all_domains = state.domain_service.list_domains(false).await?;
+    if !principal.has_scope(Scope::DomainsAdmin) {
+        principal.require_scope(Scope::LinksRead)?;
+    }
+
+    let all_domains = state
+        .domain_service
+        .list_domains(principal.workspace_id, false)
+        .await?;
 
     Ok(Json(DomainListResponse {
-        items: all_domains.into_iter().map(domain_to_item).collect(),
+        items: all_domains
+            .into_iter()
+            .filter(|d| principal.can_access_domain(d.id))
+            .map(domain_to_item)
+            .collect(),
     }))
 }
 
-/// Creates a new domain.
+/// Creates a new domain in the caller's workspace.
 ///
 /// # Endpoint
 ///
 /// `POST /api/domains`
 ///
+/// The workspace's first domain becomes its default.
+///
 /// # Errors
 ///
 /// Returns 400 if domain name is invalid.
+/// Returns 403 if the token lacks `domains:admin`, is domain-restricted, or the
+/// workspace's domain quota is used up.
 /// Returns 409 if domain already exists.
+#[utoipa::path(
+    post,
+    path = "/api/v1/domains",
+    operation_id = "create_domain",
+    tag = "domains",
+    responses(
+        (status = 201, description = "Domain created", body = DomainItem),
+        (status = 400, description = "Invalid domain name", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `domains:admin`, is limited to specific domains, or the workspace's domain quota is used up", body = ErrorBody),
+        (status = 409, description = "Domain already exists", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_domain_handler(
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
     Json(payload): Json<CreateDomainRequest>,
 ) -> Result<(StatusCode, Json<DomainItem>), AppError> {
+    principal.require_scope(Scope::DomainsAdmin)?;
+    if principal.is_domain_restricted() {
+        return Err(AppError::forbidden(
+            "Domain-restricted tokens cannot create domains",
+            json!({}),
+        ));
+    }
+
     let domain = state
         .domain_service
         .create_domain(
+            principal.workspace_id,
             payload.domain,
             payload.is_default.unwrap_or(false),
             payload.description,
+            &AuditContext::new(AuditActor::from(&principal), ip),
         )
         .await?;
 
@@ -81,12 +140,35 @@ pub async fn create_domain_handler(
 ///
 /// Returns 400 if `is_default: false` is requested.
 /// Returns 400 if domain name is invalid.
-/// Returns 404 if domain not found.
+/// Returns 403 if the token lacks `domains:admin` or access to the domain.
+/// Returns 404 if domain not found or belongs to another workspace.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/domains/{id}",
+    operation_id = "update_domain",
+    tag = "domains",
+    params(("id" = i64, Path, description = "Domain ID", example = 1)),
+    responses(
+        (status = 200, description = "Domain updated", body = DomainItem),
+        (status = 400, description = "Invalid update", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `domains:admin` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Domain name already in use", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_domain_handler(
     Path(id): Path<i64>,
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
     Json(payload): Json<UpdateDomainRequest>,
 ) -> Result<Json<DomainItem>, AppError> {
+    principal.require_scope(Scope::DomainsAdmin)?;
+    let domain = state.domain_service.get_domain_by_id(id).await?;
+    principal.require_domain(&domain)?;
+
     let update = UpdateDomain {
         domain: payload.domain,
         is_default: payload.is_default,
@@ -94,9 +176,13 @@ pub async fn update_domain_handler(
         description: payload.description,
     };
 
-    let domain = state.domain_service.update_domain(id, update).await?;
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    let updated = state
+        .domain_service
+        .update_domain(id, update, &context)
+        .await?;
 
-    Ok(Json(domain_to_item(domain)))
+    Ok(Json(domain_to_item(updated)))
 }
 
 /// Soft-deletes a domain.
@@ -111,13 +197,37 @@ pub async fn update_domain_handler(
 ///
 /// # Errors
 ///
-/// Returns 400 if the domain is the system default.
+/// Returns 400 if the domain is its workspace's default.
 /// Returns 400 if the domain has existing links.
-/// Returns 404 if domain not found or already deleted.
+/// Returns 403 if the token lacks `domains:admin` or access to the domain.
+/// Returns 404 if domain not found, already deleted or belongs to another workspace.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/domains/{id}",
+    operation_id = "delete_domain",
+    tag = "domains",
+    params(("id" = i64, Path, description = "Domain ID", example = 1)),
+    responses(
+        (status = 204, description = "Domain soft-deleted"),
+        (status = 400, description = "Domain is the default or still has links", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `domains:admin` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Domain not found or already deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_domain_handler(
     Path(id): Path<i64>,
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
 ) -> Result<StatusCode, AppError> {
-    state.domain_service.delete_domain(id).await?;
+    principal.require_scope(Scope::DomainsAdmin)?;
+    let domain = state.domain_service.get_domain_by_id(id).await?;
+    principal.require_domain(&domain)?;
+
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    state.domain_service.delete_domain(id, &context).await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
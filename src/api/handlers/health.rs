@@ -3,6 +3,7 @@
 use axum::{Json, extract::State, http::StatusCode};
 
 use crate::api::dto::health::{CheckStatus, HealthChecks, HealthResponse};
+use crate::domain::entities::DEFAULT_WORKSPACE_ID;
 use crate::state::AppState;
 
 /// Returns service health status with component checks.
@@ -44,6 +45,16 @@ use crate::state::AppState;
 ///   }
 /// }
 /// ```
+#[utoipa::path(
+    get,
+    path = "/health",
+    operation_id = "health",
+    tag = "health",
+    responses(
+        (status = 200, description = "All components healthy", body = HealthResponse),
+        (status = 503, description = "One or more components degraded", body = HealthResponse),
+    )
+)]
 pub async fn health_handler(
     State(state): State<AppState>,
 ) -> Result<Json<HealthResponse>, (StatusCode, Json<HealthResponse>)> {
@@ -73,9 +84,13 @@ pub async fn health_handler(
     }
 }
 
-/// Checks database connectivity by querying the default domain.
+/// Checks database connectivity by querying the default workspace's default domain.
 async fn check_database(state: &AppState) -> CheckStatus {
-    match state.domain_service.get_default_domain().await {
+    match state
+        .domain_service
+        .get_default_domain(DEFAULT_WORKSPACE_ID)
+        .await
+    {
         Ok(domain) => CheckStatus {
             status: "ok".to_string(),
             message: Some(format!("Connected, default domain: {}", domain.domain)),
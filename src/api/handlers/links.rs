@@ -1,26 +1,38 @@
-//! Handlers for link management endpoints (create, update, delete).
+//! Handlers for link management endpoints (create, update, delete, history, revert).
 
 use axum::{
     Json,
     extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
 };
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use serde_json::json;
+use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::api::dto::link_history::LinkHistoryResponse;
 use crate::api::dto::shorten::{
     BatchSummary, ShortenRequest, ShortenResponse, ShortenResultItem, UrlItem,
 };
 use crate::api::dto::update_link::UpdateLinkRequest;
-use crate::domain::entities::LinkPatch;
-use crate::error::AppError;
+use crate::api::middleware::client_ip::ClientIp;
+use crate::application::services::idempotency_service::{IdempotencyStatus, request_hash};
+use crate::domain::entities::{AuditActor, AuditContext, Link, LinkPatch, Principal, Scope};
+use crate::error::{AppError, ErrorBody};
+use crate::infrastructure::cache::CachedRedirect;
 use crate::state::AppState;
 use crate::utils::extract_domain::extract_domain_from_headers;
 
+/// Request header carrying the client-chosen idempotency key.
+static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Response header set when a stored response is replayed.
+static IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
 /// JSON representation of a link returned after update.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LinkResponse {
     pub code: String,
     pub long_url: String,
@@ -35,7 +47,7 @@ pub struct LinkResponse {
 ///
 /// # Endpoint
 ///
-/// `POST /api/shorten`
+/// `POST /api/v1/shorten`
 ///
 /// # Batch Processing
 ///
@@ -50,22 +62,137 @@ pub struct LinkResponse {
 ///     {
 ///       "url": "https://example.com",
 ///       "domain": "s.example.com",  // optional
-///       "custom_code": "my-link"     // optional
+///       "custom_code": "my-link",    // optional
+///       "dedupe": "always_new"       // optional: reuse (default) | always_new | error
 ///     }
 ///   ]
 /// }
 /// ```
 ///
+/// # Permissions
+///
+/// Requires the `links:write` scope. For a domain-restricted token, items that
+/// target another domain fail individually with a `forbidden` error. Links are
+/// created in the caller's workspace: items targeting another workspace's domain
+/// fail with `not_found`, and new links fail with `forbidden` once the
+/// workspace's link quota is used up.
+///
+/// # Audit
+///
+/// Every new link is recorded in the audit log and as version 1 of its
+/// history; reused links are not.
+///
+/// # Cache
+///
+/// Creating a link invalidates its cache key, which may hold a cached 404.
+///
+/// # Idempotency
+///
+/// With an `Idempotency-Key` header, the response is stored for 24 hours per API
+/// token. A retry with the same key and body returns the stored response (marked
+/// with `Idempotent-Replayed: true`) without creating links again. If the
+/// response cannot be stored, the key is released and 500 is returned, so a
+/// retry processes the batch again.
+///
 /// # Errors
 ///
 /// Returns 400 Bad Request if validation fails.
+/// Returns 403 Forbidden if the token lacks `links:write`.
+/// Returns 409 Conflict if a request with the same key is still in flight.
+/// Returns 422 Unprocessable Entity if the key was used with a different body.
+/// Returns 500 Internal Server Error if the response could not be stored for the key.
 /// Individual URL errors are returned in the response items array.
+#[utoipa::path(
+    post,
+    path = "/api/v1/shorten",
+    operation_id = "shorten_urls",
+    tag = "links",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, max_length = 255,
+            description = "Client-chosen key; retries with the same key and body replay the first response for 24 hours"),
+    ),
+    responses(
+        (status = 200, description = "Batch processed; per-URL failures are reported in `items`", body = ShortenResponse,
+            headers(("Idempotent-Replayed" = bool, description = "Present on responses replayed for a reused `Idempotency-Key`"))),
+        (status = 400, description = "Request validation failed", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:write`", body = ErrorBody),
+        (status = 409, description = "A request with the same `Idempotency-Key` is still in progress", body = ErrorBody),
+        (status = 422, description = "`Idempotency-Key` was already used with a different request body", body = ErrorBody),
+        (status = 500, description = "The response could not be stored for the `Idempotency-Key`; the key was released", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn shorten_handler(
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(payload): Json<ShortenRequest>,
-) -> Result<Json<ShortenResponse>, AppError> {
+) -> Result<Response, AppError> {
+    principal.require_scope(Scope::LinksWrite)?;
     payload.validate()?;
 
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    let Some(key) = idempotency_key(&headers)? else {
+        return Ok(
+            Json(shorten_batch(&state, &principal, &context, payload).await).into_response(),
+        );
+    };
+
+    // Keys of different tokens or users never collide, and a stored response
+    // is only replayed to the actor that created it.
+    let scope = principal.actor_key();
+    let hash = request_hash(&payload);
+
+    if let IdempotencyStatus::Replay(stored) =
+        state.idempotency_service.begin(&scope, &key, &hash).await?
+    {
+        let replayed = [(
+            IDEMPOTENT_REPLAYED.clone(),
+            HeaderValue::from_static("true"),
+        )];
+        return Ok((replayed, Json(stored)).into_response());
+    }
+
+    let response = serde_json::to_value(shorten_batch(&state, &principal, &context, payload).await)
+        .map_err(|e| {
+            AppError::internal(
+                "Failed to serialize response",
+                json!({"reason": e.to_string()}),
+            )
+        })?;
+
+    state
+        .idempotency_service
+        .complete(&scope, &key, &response)
+        .await?;
+
+    Ok(Json(response).into_response())
+}
+
+/// Reads the optional `Idempotency-Key` request header.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    headers
+        .get(&IDEMPOTENCY_KEY)
+        .map(|value| {
+            value.to_str().map(str::to_owned).map_err(|_| {
+                AppError::bad_request(
+                    "Invalid Idempotency-Key",
+                    json!({"reason": "must be visible ASCII"}),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Shortens every URL of the batch, collecting per-item results.
+async fn shorten_batch(
+    state: &AppState,
+    principal: &Principal,
+    context: &AuditContext,
+    payload: ShortenRequest,
+) -> ShortenResponse {
     let total = payload.urls.len();
     let mut results = Vec::with_capacity(total);
     let mut successful = 0;
@@ -74,13 +201,14 @@ pub async fn shorten_handler(
     for item in payload.urls {
         let long_url = item.url.clone();
 
-        match process_single_url(&state, item).await {
-            Ok((code, short_url)) => {
+        match process_single_url(state, principal, context, item).await {
+            Ok((code, short_url, reused)) => {
                 successful += 1;
                 results.push(ShortenResultItem::Success {
                     long_url,
                     code,
                     short_url,
+                    reused,
                 });
             }
             Err(err) => {
@@ -93,25 +221,34 @@ pub async fn shorten_handler(
         }
     }
 
-    Ok(Json(ShortenResponse {
+    ShortenResponse {
         summary: BatchSummary {
             total,
             successful,
             failed,
         },
         items: results,
-    }))
+    }
 }
 
-/// Resolves the target domain, creates the short link, and generates the full URL.
-async fn process_single_url(state: &AppState, item: UrlItem) -> Result<(String, String), AppError> {
+/// Resolves the target domain, creates (or reuses) the short link, and generates the full URL.
+async fn process_single_url(
+    state: &AppState,
+    principal: &Principal,
+    context: &AuditContext,
+    item: UrlItem,
+) -> Result<(String, String, bool), AppError> {
     let domain = if let Some(domain_name) = item.domain {
         state.domain_service.get_domain(&domain_name).await?
     } else {
-        state.domain_service.get_default_domain().await?
+        state
+            .domain_service
+            .get_default_domain(principal.workspace_id)
+            .await?
     };
+    principal.require_domain(&domain)?;
 
-    let link = state
+    let created = state
         .link_service
         .create_short_link_for_domain(
             item.url,
@@ -119,12 +256,21 @@ async fn process_single_url(state: &AppState, item: UrlItem) -> Result<(String,
             item.custom_code,
             item.expires_at,
             item.permanent.unwrap_or(false),
+            item.dedupe.into(),
+            principal,
+            context,
         )
         .await?;
 
-    let short_url = state.link_service.get_short_url(&domain.domain, &link.code);
+    if !created.reused {
+        invalidate_link_cache(state, &domain.domain, &created.link.code).await;
+    }
+
+    let short_url = state
+        .link_service
+        .get_short_url(&domain.domain, &created.link.code);
 
-    Ok((link.code, short_url))
+    Ok((created.link.code, short_url, created.reused))
 }
 
 /// Partially updates a short link.
@@ -151,41 +297,207 @@ async fn process_single_url(state: &AppState, item: UrlItem) -> Result<(String,
 /// The cache entry for this link is invalidated so the next redirect uses the
 /// updated destination and redirect type.
 ///
+/// # Audit
+///
+/// Recorded as `restore` if the link was deleted before, `update` otherwise.
+/// Changing the destination, expiry or redirect type also adds a version to the
+/// link's history.
+///
 /// # Errors
 ///
-/// Returns 404 Not Found if the link doesn't exist for this domain.
+/// Returns 404 Not Found if the link doesn't exist for this domain or belongs
+/// to another user or team.
 /// Returns 400 Bad Request if validation fails.
+/// Returns 403 Forbidden if the token lacks `links:write` or access to the domain,
+/// or if restoring the link would exceed the workspace's link quota.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/links/{code}",
+    operation_id = "update_link",
+    tag = "links",
+    params(("code" = String, Path, description = "Short code on the domain from the `Host` header", example = "abc123")),
+    responses(
+        (status = 200, description = "Link updated", body = LinkResponse),
+        (status = 400, description = "Request validation failed", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:write` or access to the domain, or the workspace's link quota is used up", body = ErrorBody),
+        (status = 404, description = "Link or domain not found", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_link_handler(
     Path(code): Path<String>,
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
     headers: HeaderMap,
     Json(payload): Json<UpdateLinkRequest>,
 ) -> Result<Json<LinkResponse>, AppError> {
+    principal.require_scope(Scope::LinksWrite)?;
     payload.validate()?;
 
     let domain = extract_domain_from_headers(&headers)?;
     let domain_entity = state.domain_service.get_domain(&domain).await?;
+    principal.require_domain(&domain_entity)?;
 
     let patch = LinkPatch {
         url: payload.url,
         expires_at: payload.expires_at,
         permanent: payload.permanent,
         restore: payload.restore,
+        reverted_from: None,
     };
 
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
     let link = state
         .link_service
-        .update_link(&code, domain_entity.id, patch)
+        .update_link(&code, domain_entity.id, patch, &principal, &context)
         .await?;
 
-    let cache_key = format!("{}:{}", domain, code);
+    invalidate_link_cache(&state, &domain, &code).await;
+
+    Ok(Json(link_response(&state, &domain, link)))
+}
+
+/// Lists the versions of a link's destination, expiry and redirect type.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/links/{code}/history`
+///
+/// Version 1 is the link as created (or as it was when history started being
+/// kept); each change to the destination, expiry or redirect type adds one.
+/// Deleted links keep their history.
+///
+/// # Errors
+///
+/// Returns 404 Not Found if the link doesn't exist for this domain or belongs
+/// to another user or team.
+/// Returns 403 Forbidden if the token lacks `links:read` or access to the domain.
+#[utoipa::path(
+    get,
+    path = "/api/v1/links/{code}/history",
+    operation_id = "get_link_history",
+    tag = "links",
+    params(("code" = String, Path, description = "Short code on the domain from the `Host` header", example = "abc123")),
+    responses(
+        (status = 200, description = "Versions of the link, newest first", body = LinkHistoryResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:read` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Link or domain not found", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn link_history_handler(
+    Path(code): Path<String>,
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+) -> Result<Json<LinkHistoryResponse>, AppError> {
+    principal.require_scope(Scope::LinksRead)?;
+
+    let domain = extract_domain_from_headers(&headers)?;
+    let domain_entity = state.domain_service.get_domain(&domain).await?;
+    principal.require_domain(&domain_entity)?;
+
+    let link = state
+        .link_service
+        .get_visible_link(&code, domain_entity.id, &principal)
+        .await?;
+    let versions = state.link_history_service.history(&link).await?;
+
+    Ok(Json(LinkHistoryResponse {
+        code: link.code,
+        domain: link.domain,
+        items: versions.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Sets a link's destination, expiry and redirect type back to an earlier version.
+///
+/// # Endpoint
+///
+/// `POST /api/v1/links/{code}/revert/{version}`
+///
+/// The revert is recorded as a new version pointing at the restored one, so it
+/// can itself be reverted. A deleted link stays deleted; restore it with
+/// `PATCH /api/v1/links/{code}`.
+///
+/// # Cache
+///
+/// The cache entry for this link is invalidated, like after `PATCH`.
+///
+/// # Errors
+///
+/// Returns 404 Not Found if the link or the version doesn't exist, or the link
+/// belongs to another user or team.
+/// Returns 403 Forbidden if the token lacks `links:write` or access to the domain.
+#[utoipa::path(
+    post,
+    path = "/api/v1/links/{code}/revert/{version}",
+    operation_id = "revert_link",
+    tag = "links",
+    params(
+        ("code" = String, Path, description = "Short code on the domain from the `Host` header", example = "abc123"),
+        ("version" = i32, Path, description = "Version to restore", example = 1),
+    ),
+    responses(
+        (status = 200, description = "Link reverted", body = LinkResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:write` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Link, version or domain not found", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revert_link_handler(
+    Path((code, version)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+) -> Result<Json<LinkResponse>, AppError> {
+    principal.require_scope(Scope::LinksWrite)?;
+
+    let domain = extract_domain_from_headers(&headers)?;
+    let domain_entity = state.domain_service.get_domain(&domain).await?;
+    principal.require_domain(&domain_entity)?;
+
+    let link = state
+        .link_service
+        .get_visible_link(&code, domain_entity.id, &principal)
+        .await?;
+    let patch = state
+        .link_history_service
+        .revert_patch(&link, version)
+        .await?;
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    let link = state
+        .link_service
+        .update_link(&code, domain_entity.id, patch, &principal, &context)
+        .await?;
+
+    invalidate_link_cache(&state, &domain, &code).await;
+
+    Ok(Json(link_response(&state, &domain, link)))
+}
+
+/// Drops the cached redirect (or cached 404) of a created or changed link so the
+/// next redirect reads it from the database.
+async fn invalidate_link_cache(state: &AppState, domain: &str, code: &str) {
+    let cache_key = CachedRedirect::key(domain, code);
     if let Err(e) = state.cache.invalidate(&cache_key).await {
-        tracing::warn!(error = ?e, cache_key, "Failed to invalidate cache after update");
+        tracing::warn!(error = ?e, cache_key, "Failed to invalidate cache");
     }
+}
 
-    let short_url = state.link_service.get_short_url(&domain, &link.code);
+/// Builds the response for an updated link.
+fn link_response(state: &AppState, domain: &str, link: Link) -> LinkResponse {
+    let short_url = state.link_service.get_short_url(domain, &link.code);
 
-    Ok(Json(LinkResponse {
+    LinkResponse {
         code: link.code,
         long_url: link.long_url,
         short_url,
@@ -193,7 +505,7 @@ pub async fn update_link_handler(
         expires_at: link.expires_at,
         deleted_at: link.deleted_at,
         created_at: link.created_at,
-    }))
+    }
 }
 
 /// Soft-deletes a short link by setting its `deleted_at` timestamp.
@@ -213,30 +525,58 @@ pub async fn update_link_handler(
 /// The cache entry for this link is invalidated immediately so the next redirect
 /// reflects the deleted state without waiting for TTL expiry.
 ///
+/// # Audit
+///
+/// Recorded as `delete` with the link as it was before deletion.
+///
 /// # Errors
 ///
-/// Returns 404 Not Found if the link doesn't exist or is already deleted.
+/// Returns 404 Not Found if the link doesn't exist, is already deleted, or
+/// belongs to another user or team.
+/// Returns 403 Forbidden if the token lacks `links:write` or access to the domain.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/links/{code}",
+    operation_id = "delete_link",
+    tag = "links",
+    params(("code" = String, Path, description = "Short code on the domain from the `Host` header", example = "abc123")),
+    responses(
+        (status = 204, description = "Link soft-deleted"),
+        (status = 400, description = "Missing or invalid `Host` header", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `links:write` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Link not found or already deleted", body = ErrorBody),
+        (status = 410, description = "Domain has been deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_link_handler(
     Path(code): Path<String>,
     State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
     headers: HeaderMap,
 ) -> Result<StatusCode, AppError> {
+    principal.require_scope(Scope::LinksWrite)?;
+
     let domain = extract_domain_from_headers(&headers)?;
     let domain_entity = state.domain_service.get_domain(&domain).await?;
+    principal.require_domain(&domain_entity)?;
 
-    let deleted = state
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    if state
         .link_service
-        .soft_delete_link(&code, domain_entity.id)
-        .await?;
-
-    if !deleted {
+        .soft_delete_link(&code, domain_entity.id, &principal, &context)
+        .await?
+        .is_none()
+    {
         return Err(AppError::not_found(
             "Link not found or already deleted",
             json!({ "code": code }),
         ));
     }
 
-    let cache_key = format!("{}:{}", domain, code);
+    let cache_key = CachedRedirect::key(&domain, &code);
     if let Err(e) = state.cache.invalidate(&cache_key).await {
         tracing::warn!(error = ?e, cache_key, "Failed to invalidate cache after delete");
     }
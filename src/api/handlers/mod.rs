@@ -2,16 +2,29 @@
 //!
 //! Each handler module corresponds to a logical grouping of endpoints.
 
+pub mod audit;
+pub mod cache;
 pub mod domains;
 pub mod health;
 pub mod links;
+pub mod openapi;
 pub mod redirect;
 pub mod stats;
+pub mod tokens;
 
+pub use audit::audit_list_handler;
+pub use cache::{
+    cache_stats_handler, get_cache_key_handler, purge_cache_domain_handler, purge_cache_key_handler,
+};
 pub use domains::{
     create_domain_handler, delete_domain_handler, domain_list_handler, update_domain_handler,
 };
 pub use health::health_handler;
-pub use links::{delete_link_handler, shorten_handler, update_link_handler};
+pub use links::{
+    delete_link_handler, link_history_handler, revert_link_handler, shorten_handler,
+    update_link_handler,
+};
+pub use openapi::{openapi_docs_handler, openapi_handler};
 pub use redirect::redirect_handler;
 pub use stats::{stats_handler, stats_list_handler};
+pub use tokens::{create_token_handler, list_tokens_handler, revoke_token_handler};
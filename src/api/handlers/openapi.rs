@@ -0,0 +1,52 @@
+//! Handlers serving the OpenAPI document and its viewer.
+
+use axum::Json;
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
+use utoipa::OpenApi;
+
+use crate::api::openapi::ApiDoc;
+
+/// Redoc page rendering the OpenAPI document next to it.
+///
+/// The Redoc bundle is loaded from its CDN; the page has no inline scripts.
+const DOCS_HTML: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>URL Shortener API</title>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+  </head>
+  <body>
+    <redoc spec-url="openapi.json"></redoc>
+    <script src="https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js"></script>
+  </body>
+</html>
+"#;
+
+/// Content Security Policy of the Redoc page: scripts only from the Redoc CDN,
+/// the document only from this server. Redoc injects its styles and runs its
+/// search index in a blob worker.
+const DOCS_CSP: &str = "default-src 'none'; script-src https://cdn.redoc.ly; \
+    style-src 'unsafe-inline'; img-src 'self' data:; connect-src 'self'; worker-src blob:";
+
+/// Returns the OpenAPI 3 document describing the REST API.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/openapi.json` (public)
+pub async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Returns a Redoc page rendering the OpenAPI document.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/docs` (public)
+pub async fn openapi_docs_handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_SECURITY_POLICY, DOCS_CSP)],
+        Html(DOCS_HTML),
+    )
+}
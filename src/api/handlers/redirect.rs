@@ -5,19 +5,22 @@ use axum::{
     http::{HeaderMap, header},
     response::{IntoResponse, Redirect},
 };
-use serde_json::json;
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::error::TrySendError;
 use tracing::{debug, error};
 
 use crate::domain::click_event::ClickEvent;
 use crate::error::AppError;
+use crate::infrastructure::cache::{CachedLink, CachedRedirect};
 use crate::state::AppState;
 use crate::utils::extract_domain::extract_domain_from_headers;
 
-/// Cache value prefix for permanent (301) links.
-const PERMANENT_PREFIX: &str = "1:";
-/// Cache value prefix for temporary (307) links.
-const TEMPORARY_PREFIX: &str = "0:";
+/// Lifetime of a lookup lock, in case its holder dies before releasing it.
+const LOOKUP_LOCK_TTL: Duration = Duration::from_secs(5);
+
+/// How often a waiting instance checks the cache for the lock holder's answer.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(25);
 
 /// Redirects a short code to its original URL.
 ///
@@ -28,24 +31,39 @@ const TEMPORARY_PREFIX: &str = "0:";
 /// # Request Flow
 ///
 /// 1. Extract domain from Host header
-/// 2. Check cache for URL (cache key: `domain:code`)
-/// 3. On cache miss, query database
+/// 2. Check cache for the answer (cache key: `domain:code`)
+/// 3. On cache miss, look the code up in the database (see below)
 /// 4. Check if link is deleted or expired → 410 Gone
-/// 5. Asynchronously update cache with redirect-type prefix
-/// 6. Send click event to background worker
-/// 7. Return 301 Permanent or 307 Temporary redirect based on link's `permanent` flag
+/// 5. Check the workspace's monthly click allowance → 403 Forbidden
+/// 6. Asynchronously update cache with the outcome
+/// 7. Send click event to background worker (see [`enqueue_click`])
+/// 8. Return 301 Permanent or 307 Temporary redirect based on link's `permanent` flag
+///
+/// # Caching
+///
+/// Answers are cached as versioned entries encoded by [`CachedRedirect`];
+/// legacy string entries are still served, and renewed in the background to
+/// the current format. A cached link that has expired since answers 410 Gone.
 ///
-/// # Cache Encoding
+/// Codes that don't redirect are cached for `NEGATIVE_CACHE_TTL_SECONDS`, so
+/// scanners probing random codes don't reach the database. Creating, updating
+/// or deleting a link invalidates its key.
 ///
-/// Cached values are prefixed to preserve the redirect type:
-/// - `"1:{url}"` → 301 Permanent Redirect
-/// - `"0:{url}"` → 307 Temporary Redirect
-/// - No prefix (legacy) → 307 Temporary Redirect
+/// Concurrent misses for the same key on this instance share one database
+/// lookup. With `CACHE_LOCK_WAIT_MS` set, the lookup also takes a Redis lock on
+/// the key, and other instances wait up to that long for its result to be
+/// cached before querying the database themselves.
+///
+/// A redirect served from the cache with less than
+/// `CACHE_REFRESH_AHEAD_SECONDS` of TTL left is looked up again in the
+/// background, so hot links are renewed before their entry expires.
 ///
 /// # Errors
 ///
 /// Returns 404 Not Found if the short code doesn't exist.
 /// Returns 410 Gone if the link has been deleted or has expired.
+/// Returns 403 Forbidden if the workspace used up its monthly click allowance,
+/// whether the link was served from the cache or the database.
 /// Returns 400 Bad Request if the Host header is missing or invalid.
 pub async fn redirect_handler(
     Path(code): Path<String>,
@@ -55,83 +73,43 @@ pub async fn redirect_handler(
 ) -> Result<impl IntoResponse, AppError> {
     let domain = extract_domain_from_headers(&headers)?;
 
-    let cache_key = format!("{}:{}", domain, code);
+    let cache_key = CachedRedirect::key(&domain, &code);
+
+    // Entries of an unknown format version count as misses.
+    let cached = state.cache.get_entry(&cache_key).await.map(|entry| {
+        entry.and_then(|entry| Some((CachedRedirect::parse(&entry.value)?, entry.ttl)))
+    });
 
-    let (long_url, permanent) = match state.cache.get_url(&cache_key).await {
-        Ok(Some(cached_value)) => {
+    let link = match cached {
+        Ok(Some((cached, ttl))) => {
             debug!("Cache HIT for {}", cache_key);
-            parse_cached_value(&cached_value)
+            match &cached {
+                CachedRedirect::Found(link) if needs_refresh(&state, &cache_key, link, ttl) => {
+                    refresh_ahead(&state, cache_key, domain.clone(), code.clone());
+                }
+                CachedRedirect::Found(_) => {}
+                _ => metrics::counter!("redirect_negative_cache_hits_total").increment(1),
+            }
+            let link = cached.into_result(&code)?;
+            check_click_allowance(&state, &domain).await?;
+            link
         }
         Ok(None) => {
             debug!("Cache MISS for {}", cache_key);
 
-            let domain_entity = state.domain_service.get_domain(&domain).await?;
-
-            let link = state
-                .link_service
-                .get_link_by_code(&code, domain_entity.id)
-                .await?;
-
-            // Deleted takes precedence over expired in the error message.
-            if link.is_deleted() {
-                return Err(AppError::gone(
-                    "This link has been deleted",
-                    json!({ "code": code }),
-                ));
-            }
-            if link.is_expired() {
-                return Err(AppError::gone(
-                    "This link has expired",
-                    json!({ "code": code }),
-                ));
-            }
-
-            let url = link.long_url.clone();
-            let permanent = link.permanent;
-
-            // Cache with redirect-type prefix. Use expiry-aware TTL if applicable.
-            let cache_clone = state.cache.clone();
-            let cache_key_clone = cache_key.clone();
-            let ttl = link.expires_at.map(|exp| {
-                let secs = (exp - chrono::Utc::now()).num_seconds();
-                secs.max(1) as usize
-            });
-            let cached_value = encode_cached_value(&url, permanent);
-            tokio::spawn(async move {
-                if let Err(e) = cache_clone
-                    .set_url(&cache_key_clone, &cached_value, ttl)
-                    .await
-                {
-                    error!("Failed to cache URL: {}", e);
-                }
-            });
-
-            (url, permanent)
+            let lookup = load_redirect(state.clone(), domain.clone(), code.clone());
+            state
+                .redirect_lookups
+                .run(&cache_key, lookup)
+                .await?
+                .into_result(&code)?
         }
         Err(e) => {
             error!("Cache error: {}", e);
 
             // Fall back to database on cache error.
-            let domain_entity = state.domain_service.get_domain(&domain).await?;
-            let link = state
-                .link_service
-                .get_link_by_code(&code, domain_entity.id)
-                .await?;
-
-            if link.is_deleted() {
-                return Err(AppError::gone(
-                    "This link has been deleted",
-                    json!({ "code": code }),
-                ));
-            }
-            if link.is_expired() {
-                return Err(AppError::gone(
-                    "This link has expired",
-                    json!({ "code": code }),
-                ));
-            }
-
-            (link.long_url, link.permanent)
+            let (outcome, _) = find_redirect(&state, &domain, &code).await?;
+            outcome.into_result(&code)?
         }
     };
 
@@ -144,36 +122,238 @@ pub async fn redirect_handler(
             .get(header::USER_AGENT)
             .and_then(|v| v.to_str().ok()),
         headers.get(header::REFERER).and_then(|v| v.to_str().ok()),
-    );
+    )
+    .with_link_id(link.link_id);
 
-    let _ = state.click_sender.try_send(click_event);
+    enqueue_click(&state, click_event);
 
-    if permanent {
-        Ok(Redirect::permanent(&long_url))
+    if link.permanent {
+        Ok(Redirect::permanent(&link.url))
     } else {
-        Ok(Redirect::temporary(&long_url))
+        Ok(Redirect::temporary(&link.url))
     }
 }
 
-/// Encodes a URL with a redirect-type prefix for caching.
-fn encode_cached_value(url: &str, permanent: bool) -> String {
-    if permanent {
-        format!("{}{}", PERMANENT_PREFIX, url)
-    } else {
-        format!("{}{}", TEMPORARY_PREFIX, url)
+/// Fails with 403 Forbidden if the workspace of `domain` used up its monthly
+/// click allowance.
+///
+/// Used for cached redirects; [`find_redirect`] checks links read from the
+/// database.
+async fn check_click_allowance(state: &AppState, domain: &str) -> Result<(), AppError> {
+    let domain = state.domain_service.get_domain(domain).await?;
+    state
+        .link_service
+        .check_click_allowance(domain.workspace_id)
+        .await
+}
+
+/// Queues a click event for the background worker without waiting.
+///
+/// If the queue is full or closed, the event is spilled to the click journal,
+/// or dropped and counted in `click_events_dropped_total` without one.
+fn enqueue_click(state: &AppState, event: ClickEvent) {
+    let (event, reason) = match state.click_sender.try_send(event) {
+        Ok(()) => return,
+        Err(TrySendError::Full(event)) => (event, "queue_full"),
+        Err(TrySendError::Closed(event)) => (event, "queue_closed"),
+    };
+
+    match &state.click_journal {
+        Some(journal) => {
+            journal.spill(std::slice::from_ref(&event), reason);
+        }
+        None => {
+            metrics::counter!("click_events_dropped_total", "reason" => reason).increment(1);
+            debug!("Click queue unavailable ({}), dropping click", reason);
+        }
     }
 }
 
-/// Parses a cached value, extracting the URL and redirect type.
+/// Returns `true` if a cached link with `ttl` left should be renewed now.
 ///
-/// Handles both prefixed (new) and legacy (no prefix) entries.
-fn parse_cached_value(value: &str) -> (String, bool) {
-    if let Some(url) = value.strip_prefix(PERMANENT_PREFIX) {
-        (url.to_string(), true)
-    } else if let Some(url) = value.strip_prefix(TEMPORARY_PREFIX) {
-        (url.to_string(), false)
-    } else {
-        // Legacy cached entries without prefix → treat as temporary.
-        (value.to_string(), false)
+/// Legacy entries are always renewed, upgrading them to the current format.
+/// Links expiring within the refresh window are not: their entry's TTL ends
+/// when they expire, so renewing it wouldn't extend it.
+fn needs_refresh(
+    state: &AppState,
+    cache_key: &str,
+    link: &CachedLink,
+    ttl: Option<Duration>,
+) -> bool {
+    if state.redirect_lookups.in_flight(cache_key) {
+        return false;
+    }
+    if link.is_legacy() {
+        return true;
     }
+
+    match (state.redirect_cache.refresh_ahead, ttl) {
+        (Some(threshold), Some(ttl)) => {
+            let expires_soon = link
+                .expires_at
+                .is_some_and(|at| at <= chrono::Utc::now() + threshold);
+            ttl < threshold && !expires_soon
+        }
+        _ => false,
+    }
+}
+
+/// Looks a cached redirect up again in the background, renewing its entry.
+fn refresh_ahead(state: &AppState, cache_key: String, domain: String, code: String) {
+    metrics::counter!("cache_refresh_ahead_total").increment(1);
+    debug!("Refreshing {} ahead of expiry", cache_key);
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        let lookup = load_redirect(state.clone(), domain, code);
+        if let Err(e) = state.redirect_lookups.run(&cache_key, lookup).await {
+            debug!("Failed to refresh {}: {}", cache_key, e);
+        }
+    });
+}
+
+/// Looks a code up in the database and caches the outcome.
+///
+/// With a lock wait configured, first takes the Redis lookup lock of the key.
+/// If another instance holds it, polls the cache for that instance's result
+/// until the wait runs out, then queries the database without the lock.
+///
+/// # Errors
+///
+/// Returns the errors of [`find_redirect`]; these answers are not cached.
+async fn load_redirect(
+    state: AppState,
+    domain: String,
+    code: String,
+) -> Result<CachedRedirect, AppError> {
+    let cache_key = CachedRedirect::key(&domain, &code);
+
+    let lock_token = match state.redirect_cache.lock_wait {
+        Some(wait) => match lock_lookup(&state, &cache_key, wait).await {
+            LookupLock::Held(token) => Some(token),
+            LookupLock::Cached(outcome) => return Ok(outcome),
+            LookupLock::TimedOut => None,
+        },
+        None => None,
+    };
+
+    let result = find_redirect(&state, &domain, &code).await;
+
+    // `None` skips caching; negative answers use the short negative TTL.
+    let ttl = match &result {
+        Ok((CachedRedirect::Found(_), ttl)) => Some(*ttl),
+        Ok(_) => state.redirect_cache.negative_ttl.map(Some),
+        Err(_) => None,
+    };
+    let cached_value = result.as_ref().ok().map(|(outcome, _)| outcome.encode());
+
+    if ttl.is_some() || lock_token.is_some() {
+        let cache = state.cache.clone();
+        tokio::spawn(async move {
+            if let (Some(ttl), Some(value)) = (ttl, cached_value)
+                && let Err(e) = cache.set_url(&cache_key, &value, ttl).await
+            {
+                error!("Failed to cache URL: {}", e);
+            }
+            if let Some(token) = lock_token
+                && let Err(e) = cache.unlock(&cache_key, &token).await
+            {
+                error!("Failed to release lookup lock: {}", e);
+            }
+        });
+    }
+
+    result.map(|(outcome, _)| outcome)
+}
+
+/// Result of trying to take the lookup lock of a key.
+enum LookupLock {
+    /// This instance holds the lock under the token.
+    Held(String),
+    /// Another instance cached the answer while we waited.
+    Cached(CachedRedirect),
+    /// Another instance kept the lock for the whole wait.
+    TimedOut,
+}
+
+/// Takes the lookup lock of `cache_key`, or waits up to `wait` for the
+/// instance holding it to cache its answer.
+async fn lock_lookup(state: &AppState, cache_key: &str, wait: Duration) -> LookupLock {
+    match state.cache.try_lock(cache_key, LOOKUP_LOCK_TTL).await {
+        Ok(Some(token)) => return LookupLock::Held(token),
+        Ok(None) => debug!("Lookup lock of {} is held, waiting", cache_key),
+        Err(e) => {
+            error!("Failed to take lookup lock: {}", e);
+            return LookupLock::TimedOut;
+        }
+    }
+
+    let deadline = Instant::now() + wait;
+    while Instant::now() < deadline {
+        tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+        if let Ok(Some(value)) = state.cache.get_url(cache_key).await
+            && let Some(cached) = CachedRedirect::parse(&value)
+        {
+            return LookupLock::Cached(cached);
+        }
+    }
+
+    metrics::counter!("cache_lock_wait_timeouts_total").increment(1);
+    LookupLock::TimedOut
+}
+
+/// Looks a code up in the database.
+///
+/// Returns the outcome with the TTL of its cache entry: until the link expires,
+/// or the default TTL for links without expiry.
+///
+/// # Errors
+///
+/// Returns an error if the domain doesn't exist, the workspace used up its
+/// monthly click allowance, or the database fails; these answers are not cached.
+async fn find_redirect(
+    state: &AppState,
+    domain: &str,
+    code: &str,
+) -> Result<(CachedRedirect, Option<usize>), AppError> {
+    let domain_entity = state.domain_service.get_domain(domain).await?;
+
+    let link = match state
+        .link_service
+        .get_link_by_code(code, domain_entity.id)
+        .await
+    {
+        Ok(link) => link,
+        Err(AppError::NotFound { .. }) => {
+            return Ok((
+                CachedRedirect::NotFound {
+                    domain_id: domain_entity.id,
+                },
+                None,
+            ));
+        }
+        Err(e) => return Err(e),
+    };
+
+    if link.is_deleted() {
+        return Ok((CachedRedirect::Deleted, None));
+    }
+    if link.is_expired() {
+        return Ok((CachedRedirect::Expired, None));
+    }
+    state
+        .link_service
+        .check_click_allowance(link.workspace_id)
+        .await?;
+
+    let cached = CachedLink {
+        url: link.long_url,
+        permanent: link.permanent,
+        expires_at: link.expires_at,
+        link_id: Some(link.id),
+        domain_id: Some(domain_entity.id),
+    };
+    let ttl = cached.ttl_seconds();
+
+    Ok((CachedRedirect::Found(cached), ttl))
 }
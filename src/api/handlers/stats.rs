@@ -10,8 +10,9 @@ use crate::api::dto::clicks::ClickInfo;
 use crate::api::dto::pagination::StatsQueryParams;
 use crate::api::dto::stats::StatsResponse;
 use crate::api::dto::stats_list::{LinkStatsItem, PaginationMeta, StatsListResponse};
+use crate::domain::entities::{Principal, Scope};
 use crate::domain::repositories::StatsFilter;
-use crate::error::AppError;
+use crate::error::{AppError, ErrorBody};
 use crate::state::AppState;
 
 /// Retrieves aggregated statistics for all links.
@@ -28,6 +29,9 @@ use crate::state::AppState;
 /// - `to` (optional): End date for click filtering (RFC3339 format)
 /// - `domain` (optional): Filter by domain name
 ///
+/// Requires the `stats:read` scope. Domain-restricted tokens only see links
+/// on their domains.
+///
 /// # Performance
 ///
 /// Uses `tokio::try_join!` to parallelize stats query and total count query.
@@ -35,10 +39,28 @@ use crate::state::AppState;
 /// # Errors
 ///
 /// Returns 400 Bad Request if pagination parameters are invalid.
+/// Returns 403 Forbidden if the token lacks the scope or the domain.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    operation_id = "list_stats",
+    tag = "stats",
+    params(StatsQueryParams),
+    responses(
+        (status = 200, description = "Paginated click totals per link", body = StatsListResponse),
+        (status = 400, description = "Invalid pagination or date filter", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `stats:read` or access to the domain", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn stats_list_handler(
     State(state): State<AppState>,
+    principal: Principal,
     Query(params): Query<StatsQueryParams>,
 ) -> Result<Json<StatsListResponse>, AppError> {
+    principal.require_scope(Scope::StatsRead)?;
+
     let (offset, limit) = params
         .pagination
         .validate_and_get_offset_limit()
@@ -47,20 +69,19 @@ pub async fn stats_list_handler(
     let page = params.pagination.page.unwrap_or(1);
     let page_size = params.pagination.page_size.unwrap_or(25);
 
-    let domain_id = if let Some(domain_name) = &params.domain {
-        let domain = state.domain_service.get_domain(domain_name).await?;
-        Some(domain.id)
-    } else {
-        None
-    };
+    let domain_id = domain_filter(&state, &principal, params.domain.as_deref()).await?;
 
-    let filter = StatsFilter::new(offset, limit)
+    let visibility = principal.visibility();
+    let filter = StatsFilter::new(offset, limit, visibility.clone())
         .with_domain(domain_id)
+        .with_allowed_domains(principal.domain_ids.clone())
         .with_date_range(params.date_filter.from, params.date_filter.to);
 
     let (all_stats, total_items) = tokio::try_join!(
         state.stats_service.get_all_stats(filter),
-        state.stats_service.count_all_links()
+        state
+            .stats_service
+            .count_all_links(principal.domain_ids.clone(), &visibility)
     )?;
 
     let items = all_stats
@@ -101,15 +122,40 @@ pub async fn stats_list_handler(
 /// - `to` (optional): End date (RFC3339 format)
 /// - `domain` (optional): Filter by domain name
 ///
+/// Requires the `stats:read` scope. Links outside the domains of a
+/// domain-restricted token are reported as not found.
+///
 /// # Errors
 ///
 /// Returns 404 Not Found if the short code doesn't exist.
 /// Returns 400 Bad Request if pagination parameters are invalid.
+/// Returns 403 Forbidden if the token lacks the scope or the domain.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats/{code}",
+    operation_id = "get_link_stats",
+    tag = "stats",
+    params(
+        ("code" = String, Path, description = "Short code", example = "abc123"),
+        StatsQueryParams,
+    ),
+    responses(
+        (status = 200, description = "Link details with paginated clicks", body = StatsResponse),
+        (status = 400, description = "Invalid pagination or date filter", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `stats:read` or access to the domain", body = ErrorBody),
+        (status = 404, description = "Short code not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn stats_handler(
     State(state): State<AppState>,
+    principal: Principal,
     Path(code): Path<String>,
     Query(params): Query<StatsQueryParams>,
 ) -> Result<Json<StatsResponse>, AppError> {
+    principal.require_scope(Scope::StatsRead)?;
+
     let (offset, limit) = params
         .pagination
         .validate_and_get_offset_limit()
@@ -118,15 +164,11 @@ pub async fn stats_handler(
     let page = params.pagination.page.unwrap_or(1);
     let page_size = params.pagination.page_size.unwrap_or(25);
 
-    let domain_id = if let Some(domain_name) = &params.domain {
-        let domain = state.domain_service.get_domain(domain_name).await?;
-        Some(domain.id)
-    } else {
-        None
-    };
+    let domain_id = domain_filter(&state, &principal, params.domain.as_deref()).await?;
 
-    let filter = StatsFilter::new(offset, limit)
+    let filter = StatsFilter::new(offset, limit, principal.visibility())
         .with_domain(domain_id)
+        .with_allowed_domains(principal.domain_ids.clone())
         .with_date_range(params.date_filter.from, params.date_filter.to);
 
     let detailed_stats = state
@@ -160,3 +202,19 @@ pub async fn stats_handler(
             .collect(),
     }))
 }
+
+/// Resolves the optional `domain` query parameter, checking the token may use it.
+async fn domain_filter(
+    state: &AppState,
+    principal: &Principal,
+    domain_name: Option<&str>,
+) -> Result<Option<i64>, AppError> {
+    let Some(domain_name) = domain_name else {
+        return Ok(None);
+    };
+
+    let domain = state.domain_service.get_domain(domain_name).await?;
+    principal.require_domain(&domain)?;
+
+    Ok(Some(domain.id))
+}
@@ -0,0 +1,206 @@
+//! Handlers for API token management endpoints.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use serde_json::json;
+use validator::Validate;
+
+use crate::api::dto::token::{
+    CreateTokenRequest, CreatedTokenResponse, TokenItem, TokenListResponse,
+};
+use crate::api::middleware::client_ip::ClientIp;
+use crate::domain::entities::{AuditActor, AuditContext, Principal, Scope};
+use crate::error::{AppError, ErrorBody};
+use crate::state::AppState;
+
+/// Fails unless the caller may manage tokens.
+///
+/// Token management requires `tokens:admin` on a token that is not limited to
+/// specific domains, since the endpoints act on every token in the system.
+fn require_token_admin(principal: &Principal) -> Result<(), AppError> {
+    principal.require_scope(Scope::TokensAdmin)?;
+    if principal.is_domain_restricted() {
+        return Err(AppError::forbidden(
+            "Domain-restricted tokens cannot manage tokens",
+            json!({}),
+        ));
+    }
+    Ok(())
+}
+
+/// Lists the API tokens of the caller's workspace, including revoked and expired ones.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/tokens`
+///
+/// Token values are never returned; only their metadata.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    operation_id = "list_tokens",
+    tag = "tokens",
+    responses(
+        (status = 200, description = "Tokens of the caller's workspace, newest first", body = TokenListResponse),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `tokens:admin` or is limited to specific domains", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_tokens_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+) -> Result<Json<TokenListResponse>, AppError> {
+    require_token_admin(&principal)?;
+
+    let tokens = state
+        .auth_service
+        .list_tokens(Some(principal.workspace_id))
+        .await?;
+
+    Ok(Json(TokenListResponse {
+        items: tokens.into_iter().map(TokenItem::from).collect(),
+    }))
+}
+
+/// Creates a new API token in the caller's workspace.
+///
+/// # Endpoint
+///
+/// `POST /api/v1/tokens`
+///
+/// The token value is generated by the server and returned once in `plaintext`;
+/// only its hash is stored. The new token cannot be granted scopes the caller
+/// does not have, nor domains of other workspaces.
+///
+/// # Errors
+///
+/// Returns 400 if the name is empty, a domain does not exist, or `expires_at` is in the past.
+/// Returns 403 if the caller lacks `tokens:admin`, is domain-restricted, or
+/// requests a scope it does not have.
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    operation_id = "create_token",
+    tag = "tokens",
+    responses(
+        (status = 201, description = "Token created; `plaintext` is shown only once", body = CreatedTokenResponse),
+        (status = 400, description = "Invalid name, unknown domain or past expiry", body = ErrorBody),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `tokens:admin`, is limited to specific domains, or lacks a requested scope", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_token_handler(
+    State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<CreatedTokenResponse>), AppError> {
+    require_token_admin(&principal)?;
+    payload.validate()?;
+
+    let scopes: Vec<Scope> = match payload.scopes {
+        Some(scopes) => scopes.into_iter().map(Scope::from).collect(),
+        None => principal.scopes.clone(),
+    };
+    if let Some(scope) = scopes.iter().find(|s| !principal.has_scope(**s)) {
+        return Err(AppError::forbidden(
+            "Cannot grant a scope the token does not have",
+            json!({ "scope": scope.as_str() }),
+        ));
+    }
+
+    if let Some(domain_ids) = &payload.domain_ids {
+        let known = state
+            .domain_service
+            .list_domains(principal.workspace_id, false)
+            .await?;
+        if let Some(missing) = domain_ids
+            .iter()
+            .find(|id| !known.iter().any(|d| d.id == **id))
+        {
+            return Err(AppError::bad_request(
+                "Domain not found",
+                json!({ "domain_id": missing }),
+            ));
+        }
+    }
+
+    if payload.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(AppError::bad_request(
+            "expires_at must be in the future",
+            json!({ "expires_at": payload.expires_at }),
+        ));
+    }
+
+    let issued = state
+        .auth_service
+        .create_token(
+            payload.name.trim(),
+            None,
+            scopes,
+            payload.domain_ids,
+            payload.expires_at,
+            principal.user.map(|user| user.id),
+            principal.workspace_id,
+            &AuditContext::new(AuditActor::from(&principal), ip),
+        )
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedTokenResponse {
+            token: issued.token.into(),
+            plaintext: issued.plaintext,
+        }),
+    ))
+}
+
+/// Revokes an API token.
+///
+/// # Endpoint
+///
+/// `DELETE /api/v1/tokens/{id}`
+///
+/// Revoked tokens stop working immediately. Revoking an already revoked token
+/// succeeds without changes.
+///
+/// # Errors
+///
+/// Returns 403 if the caller lacks `tokens:admin` or is domain-restricted.
+/// Returns 404 if the token does not exist in the caller's workspace.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{id}",
+    operation_id = "revoke_token",
+    tag = "tokens",
+    params(("id" = i64, Path, description = "Token ID", example = 1)),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 401, description = "Missing or invalid Bearer token", body = ErrorBody),
+        (status = 403, description = "Token lacks `tokens:admin` or is limited to specific domains", body = ErrorBody),
+        (status = 404, description = "Token not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_token_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    principal: Principal,
+    ClientIp(ip): ClientIp,
+) -> Result<StatusCode, AppError> {
+    require_token_admin(&principal)?;
+
+    let context = AuditContext::new(AuditActor::from(&principal), ip);
+    state
+        .auth_service
+        .revoke_token(id, Some(principal.workspace_id), &context)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
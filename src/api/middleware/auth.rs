@@ -1,15 +1,23 @@
-//! Bearer token authentication middleware.
+//! Bearer token and dashboard session authentication middleware.
 
 use axum::{
     extract::{FromRequestParts, Request, State},
+    http::{Method, header::AUTHORIZATION, request::Parts},
     middleware::Next,
     response::Response,
 };
 use axum_auth::AuthBearer;
 
-use crate::{error::AppError, state::AppState};
+use crate::{
+    application::services::session_service::{CSRF_HEADER, SESSION_COOKIE, verify_csrf},
+    domain::entities::Principal,
+    error::AppError,
+    state::AppState,
+    utils::cookies::read_cookie,
+};
 
-/// Authenticates requests using Bearer tokens from Authorization header.
+/// Authenticates requests using Bearer tokens from Authorization header, or
+/// the dashboard session cookie.
 ///
 /// # Header Format
 ///
@@ -17,20 +25,33 @@ use crate::{error::AppError, state::AppState};
 /// Authorization: Bearer <token>
 /// ```
 ///
+/// Without an `Authorization` header, the `session` cookie set by
+/// `POST /dashboard/login` is accepted instead. Requests other than
+/// `GET`/`HEAD`/`OPTIONS` authenticated this way must echo the session's CSRF
+/// token in the `X-CSRF-Token` header.
+///
 /// # Authentication Flow
 ///
 /// 1. Extract token from `Authorization` header
 /// 2. Validate token hash against database
-/// 3. Check if token is revoked
+/// 3. Check if token is revoked or expired
 /// 4. Update `last_used_at` timestamp
-/// 5. Continue to next middleware/handler
+/// 5. Store the token's [`Principal`] in the request extensions
+/// 6. Continue to next middleware/handler
+///
+/// Handlers take the [`Principal`] as an extractor to enforce scopes and
+/// domain restrictions, answering `403 Forbidden` when they are not met.
 ///
 /// # Errors
 ///
 /// Returns `401 Unauthorized` if:
-/// - Authorization header is missing
+/// - Both the Authorization header and the session cookie are missing
 /// - Token format is invalid
-/// - Token is not found or revoked
+/// - Token is not found, revoked or expired
+/// - Session is invalid or expired
+///
+/// Returns `403 Forbidden` if a session-authenticated state-changing request
+/// has a missing or wrong CSRF token.
 ///
 /// Adds `WWW-Authenticate: Bearer` header to 401 responses per RFC 6750.
 ///
@@ -51,18 +72,56 @@ pub async fn layer(
 ) -> Result<Response, AppError> {
     let (mut parts, body) = req.into_parts();
 
-    let AuthBearer(token) = AuthBearer::from_request_parts(&mut parts, &())
-        .await
-        .map_err(|_| {
-            AppError::unauthorized(
-                "Unauthorized",
-                serde_json::json!({"reason": "Authorization header is missing or invalid"}),
-            )
-        })?;
+    let session_cookie = read_cookie(&parts.headers, SESSION_COOKIE);
+    let principal = match session_cookie {
+        Some(cookie) if !parts.headers.contains_key(AUTHORIZATION) => {
+            let session = st.session_service.authenticate(&cookie).await?;
+            if !is_safe_method(&parts.method) {
+                let provided = parts
+                    .headers
+                    .get(CSRF_HEADER)
+                    .and_then(|value| value.to_str().ok());
+                verify_csrf(&session, provided)?;
+            }
+            session.principal
+        }
+        _ => {
+            let AuthBearer(token) = AuthBearer::from_request_parts(&mut parts, &())
+                .await
+                .map_err(|_| {
+                    AppError::unauthorized(
+                        "Unauthorized",
+                        serde_json::json!({"reason": "Authorization header is missing or invalid"}),
+                    )
+                })?;
 
-    let req = Request::from_parts(parts, body);
+            st.auth_service.authenticate(&token).await?
+        }
+    };
+    parts.extensions.insert(principal);
 
-    st.auth_service.authenticate(&token).await?;
+    let req = Request::from_parts(parts, body);
 
     Ok(next.run(req).await)
 }
+
+/// Returns true for methods that must not change state and so need no CSRF token.
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Extracts the [`Principal`] stored by [`layer`].
+///
+/// Rejects with `401 Unauthorized` when the route is not behind the auth layer.
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Principal>().cloned().ok_or_else(|| {
+            AppError::unauthorized(
+                "Unauthorized",
+                serde_json::json!({"reason": "Request is not authenticated"}),
+            )
+        })
+    }
+}
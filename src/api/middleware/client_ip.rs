@@ -0,0 +1,54 @@
+//! Client IP of a request, for the audit log.
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+
+use crate::api::middleware::rate_limit::SmartIpExtractor;
+
+/// Client IP address of the request, as seen by the rate limiter.
+///
+/// Stored in the request extensions by [`layer`]; `None` when the layer is not
+/// mounted or the address cannot be determined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientIp(pub Option<String>);
+
+/// Stores the [`ClientIp`] of the request in its extensions.
+///
+/// Uses the same [`SmartIpExtractor`] rules as rate limiting, so proxy headers
+/// are only trusted when the service runs behind a proxy.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api = api.layer(middleware::from_fn_with_state(
+///     SmartIpExtractor { behind_proxy },
+///     client_ip::layer,
+/// ));
+/// ```
+pub async fn layer(
+    State(extractor): State<SmartIpExtractor>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let ip = extractor.client_ip(&req);
+    req.extensions_mut().insert(ClientIp(ip));
+    next.run(req).await
+}
+
+/// Extracts the [`ClientIp`] stored by [`layer`]; never rejects.
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ClientIp>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
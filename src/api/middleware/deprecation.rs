@@ -0,0 +1,105 @@
+//! Deprecation headers for the unversioned `/api` alias.
+//!
+//! Requests served through the legacy `/api/*` prefix behave exactly like
+//! `/api/v1/*`, but every response announces the deprecation:
+//!
+//! ```text
+//! Deprecation: @1792281600
+//! Sunset: Fri, 30 Apr 2027 00:00:00 GMT
+//! Link: </api/v1/shorten>; rel="successor-version"
+//! ```
+//!
+//! - `Deprecation` - RFC 9745, Unix timestamp of [`LEGACY_DEPRECATED_AT`]
+//! - `Sunset` - RFC 8594, date after which the alias may be removed (`API_LEGACY_SUNSET`)
+//! - `Link` - the same resource under `/api/v1`
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+
+use crate::api::routes::V1_PREFIX;
+
+/// Unix timestamp at which the unversioned `/api` alias was deprecated (2026-10-18T00:00:00Z).
+pub const LEGACY_DEPRECATED_AT: i64 = 1_792_281_600;
+
+static DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+static SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// Precomputed header values attached to every legacy alias response.
+#[derive(Debug, Clone)]
+pub struct DeprecationHeaders {
+    deprecation: HeaderValue,
+    sunset: HeaderValue,
+}
+
+impl DeprecationHeaders {
+    /// Builds the headers for an alias that will be removed after `sunset`.
+    pub fn new(sunset: DateTime<Utc>) -> Self {
+        let deprecation = format!("@{LEGACY_DEPRECATED_AT}");
+        let sunset = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+
+        Self {
+            deprecation: HeaderValue::from_str(&deprecation).expect("valid header value"),
+            sunset: HeaderValue::from_str(&sunset).expect("valid header value"),
+        }
+    }
+}
+
+/// Adds `Deprecation`, `Sunset` and a `successor-version` `Link` to the response.
+///
+/// Mounted on the `/api` alias only; `/api/v1` responses are unchanged.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let legacy = api_v1.layer(middleware::from_fn_with_state(
+///     DeprecationHeaders::new(sunset),
+///     deprecation::layer,
+/// ));
+/// ```
+pub async fn layer(
+    State(headers): State<DeprecationHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let successor = req
+        .uri()
+        .path_and_query()
+        .map(|pq| format!("<{V1_PREFIX}{pq}>; rel=\"successor-version\""));
+
+    let mut response = next.run(req).await;
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(DEPRECATION.clone(), headers.deprecation);
+    response_headers.insert(SUNSET.clone(), headers.sunset);
+    if let Some(link) = successor.and_then(|l| HeaderValue::from_str(&l).ok()) {
+        response_headers.append(header::LINK, link);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_headers_use_rfc_formats() {
+        let sunset = Utc.with_ymd_and_hms(2027, 4, 30, 0, 0, 0).unwrap();
+        let headers = DeprecationHeaders::new(sunset);
+
+        assert_eq!(headers.deprecation, "@1792281600");
+        assert_eq!(headers.sunset, "Fri, 30 Apr 2027 00:00:00 GMT");
+    }
+
+    #[test]
+    fn test_deprecated_at_matches_announced_date() {
+        let deprecated_at = Utc.timestamp_opt(LEGACY_DEPRECATED_AT, 0).unwrap();
+        assert_eq!(deprecated_at.to_rfc3339(), "2026-10-18T00:00:00+00:00");
+    }
+}
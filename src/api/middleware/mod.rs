@@ -1,7 +1,9 @@
 //! HTTP middleware for request processing and protection.
 //!
-//! Provides authentication, rate limiting, and observability middleware.
+//! Provides authentication, rate limiting, client IP, deprecation, and observability middleware.
 
 pub mod auth;
+pub mod client_ip;
+pub mod deprecation;
 pub mod rate_limit;
 pub mod tracing;
@@ -30,30 +30,36 @@ pub struct SmartIpExtractor {
     pub behind_proxy: bool,
 }
 
-impl KeyExtractor for SmartIpExtractor {
-    type Key = String;
-
-    fn extract<B>(&self, req: &http::Request<B>) -> Result<String, GovernorError> {
+impl SmartIpExtractor {
+    /// Returns the client IP of the request, or `None` if it cannot be determined.
+    pub fn client_ip<B>(&self, req: &http::Request<B>) -> Option<String> {
         if self.behind_proxy {
             // X-Forwarded-For: client, proxy1, proxy2 — take the leftmost (original client)
             if let Some(xff) = req.headers().get("x-forwarded-for")
                 && let Ok(s) = xff.to_str()
                 && let Some(ip) = s.split(',').next()
             {
-                return Ok(ip.trim().to_string());
+                return Some(ip.trim().to_string());
             }
             // X-Real-IP set by nginx
             if let Some(xri) = req.headers().get("x-real-ip")
                 && let Ok(ip) = xri.to_str()
             {
-                return Ok(ip.trim().to_string());
+                return Some(ip.trim().to_string());
             }
         }
 
         req.extensions()
             .get::<ConnectInfo<SocketAddr>>()
             .map(|ci| ci.0.ip().to_string())
-            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+impl KeyExtractor for SmartIpExtractor {
+    type Key = String;
+
+    fn extract<B>(&self, req: &http::Request<B>) -> Result<String, GovernorError> {
+        self.client_ip(req).ok_or(GovernorError::UnableToExtractKey)
     }
 }
 
@@ -1,8 +1,43 @@
 //! HTTP request/response tracing middleware.
 
+use axum::http::Request;
 use tower_http::LatencyUnit;
-use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
-use tracing::Level;
+use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, MakeSpan, TraceLayer};
+use tracing::{Level, Span};
+
+use crate::telemetry;
+
+/// Request span factory that continues the caller's trace.
+///
+/// Builds the same span as [`DefaultMakeSpan`] and sets its parent from the
+/// incoming W3C `traceparent` / `tracestate` headers, so spans exported over OTLP
+/// are linked to the upstream service's trace.
+#[derive(Debug, Clone)]
+pub struct TraceContextMakeSpan {
+    inner: DefaultMakeSpan,
+}
+
+impl TraceContextMakeSpan {
+    pub fn new() -> Self {
+        Self {
+            inner: DefaultMakeSpan::new().level(Level::INFO),
+        }
+    }
+}
+
+impl Default for TraceContextMakeSpan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> MakeSpan<B> for TraceContextMakeSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let span = self.inner.make_span(request);
+        telemetry::set_parent_from_headers(&span, request.headers());
+        span
+    }
+}
 
 /// Creates a tracing middleware for HTTP requests.
 ///
@@ -13,6 +48,7 @@ use tracing::Level;
 ///   - HTTP method
 ///   - URI path
 ///   - HTTP version
+/// - Continues the trace from the `traceparent` header, if present
 ///
 /// **On Response:**
 /// - Logs at `INFO` level with:
@@ -33,11 +69,12 @@ use tracing::Level;
 ///     .nest("/api", api_routes())
 ///     .layer(tracing::layer());
 /// ```
-pub fn layer()
--> TraceLayer<tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>>
-{
+pub fn layer() -> TraceLayer<
+    tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>,
+    TraceContextMakeSpan,
+> {
     TraceLayer::new_for_http()
-        .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+        .make_span_with(TraceContextMakeSpan::new())
         .on_response(
             DefaultOnResponse::new()
                 .level(Level::INFO)
@@ -8,9 +8,11 @@
 //! - [`dto`] - Data Transfer Objects for request/response serialization
 //! - [`handlers`] - HTTP request handlers
 //! - [`middleware`] - Authentication and request processing middleware
+//! - [`openapi`] - OpenAPI document generated from handlers and DTOs
 //! - [`routes`] - Route configuration and composition
 
 pub mod dto;
 pub mod handlers;
 pub mod middleware;
+pub mod openapi;
 pub mod routes;
@@ -0,0 +1,102 @@
+//! OpenAPI 3 description of the REST API.
+//!
+//! The document is generated at compile time by `utoipa` from the `#[utoipa::path]`
+//! annotations on the handlers and the `ToSchema` / `IntoParams` derives on the DTOs.
+//! Request bodies, path and query parameters are inferred from the handler
+//! signatures (`Json<T>`, `Path<T>`, `Query<T>`), so they cannot silently diverge
+//! from what the handlers actually accept.
+//!
+//! # Serving
+//!
+//! - `GET /api/v1/openapi.json` - the raw document ([`crate::api::handlers::openapi_handler`])
+//! - `GET /api/v1/docs`         - Redoc viewer for the document
+//!
+//! Both are public so SDK generators can fetch the spec without a token.
+
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::api::handlers::{audit, cache, domains, health, links, stats, tokens};
+
+/// Root OpenAPI document for the service.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "URL Shortener API",
+        description = "Create and manage short links, domains, click statistics and API tokens, \
+            read the audit log of changes and manage the redirect cache.\n\n\
+            Every failing request returns the `ErrorBody` envelope."
+    ),
+    paths(
+        links::shorten_handler,
+        links::update_link_handler,
+        links::delete_link_handler,
+        links::link_history_handler,
+        links::revert_link_handler,
+        domains::domain_list_handler,
+        domains::create_domain_handler,
+        domains::update_domain_handler,
+        domains::delete_domain_handler,
+        stats::stats_list_handler,
+        stats::stats_handler,
+        tokens::list_tokens_handler,
+        tokens::create_token_handler,
+        tokens::revoke_token_handler,
+        audit::audit_list_handler,
+        cache::cache_stats_handler,
+        cache::get_cache_key_handler,
+        cache::purge_cache_key_handler,
+        cache::purge_cache_domain_handler,
+        health::health_handler,
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "links", description = "Create, update and delete short links, and revert them to earlier versions"),
+        (name = "domains", description = "Manage short link domains"),
+        (name = "stats", description = "Click statistics"),
+        (name = "tokens", description = "Manage API tokens"),
+        (name = "audit", description = "Audit log of changes to links, domains and tokens"),
+        (name = "cache", description = "Inspect and purge the redirect cache"),
+        (name = "health", description = "Service health"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer_auth` security scheme referenced by protected operations.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_operations_reference_registered_scheme() {
+        let doc = ApiDoc::openapi();
+        let schemes = &doc.components.as_ref().unwrap().security_schemes;
+        assert!(schemes.contains_key("bearer_auth"));
+
+        let shorten = doc.paths.paths["/api/v1/shorten"].post.as_ref().unwrap();
+        assert!(shorten.security.is_some());
+
+        let health = doc.paths.paths["/health"].get.as_ref().unwrap();
+        assert!(health.security.is_none());
+    }
+
+    #[test]
+    fn test_error_envelope_is_a_component() {
+        let doc = ApiDoc::openapi();
+        let schemas = &doc.components.as_ref().unwrap().schemas;
+        assert!(schemas.contains_key("ErrorBody"));
+        assert!(schemas.contains_key("ErrorInfo"));
+    }
+}
@@ -0,0 +1,21 @@
+//! API route configuration, grouped by API version.
+//!
+//! Each version lives in its own module and is mounted under its own prefix by
+//! [`crate::routes::app_router`]:
+//!
+//! - [`v1`] - `/api/v1/*` (current); also served at `/api/*` as a deprecated alias
+//!
+//! # Adding a Version
+//!
+//! A breaking change goes into a new `v2` module with its own prefix constant
+//! (`/api/v2`), handlers and DTOs (e.g. `api::dto::v2`), mounted beside v1 in
+//! [`crate::routes::app_router`]. Existing v1 handlers and DTOs stay untouched so
+//! v1 clients keep working.
+
+pub mod v1;
+
+/// Path prefix of API version 1.
+pub const V1_PREFIX: &str = "/api/v1";
+
+/// Unversioned path prefix, kept as a deprecated alias of [`V1_PREFIX`].
+pub const LEGACY_PREFIX: &str = "/api";
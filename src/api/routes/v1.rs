@@ -0,0 +1,90 @@
+//! Routes of API version 1, mounted at `/api/v1` (and the deprecated `/api` alias).
+//!
+//! The v1 contract is formed by the handlers in [`crate::api::handlers`] and the
+//! DTOs in [`crate::api::dto`]. All endpoints require Bearer token authentication
+//! via [`crate::api::middleware::auth`], except the API documentation in
+//! [`public_routes`].
+
+use crate::api::handlers::{
+    audit_list_handler, cache_stats_handler, create_domain_handler, create_token_handler,
+    delete_domain_handler, delete_link_handler, domain_list_handler, get_cache_key_handler,
+    link_history_handler, list_tokens_handler, openapi_docs_handler, openapi_handler,
+    purge_cache_domain_handler, purge_cache_key_handler, revert_link_handler, revoke_token_handler,
+    shorten_handler, stats_handler, stats_list_handler, update_domain_handler, update_link_handler,
+};
+use crate::state::AppState;
+use axum::{
+    Router,
+    routing::{delete, get, patch, post},
+};
+
+/// All v1 API routes, protected by Bearer token authentication.
+///
+/// # Endpoints
+///
+/// - `GET    /domains`        - List configured domains
+/// - `POST   /domains`        - Create a new domain
+/// - `PATCH  /domains/{id}`   - Update a domain (rename, toggle active/default, etc.)
+/// - `DELETE /domains/{id}`   - Soft-delete a domain
+/// - `GET    /stats`          - Aggregated click statistics (paginated)
+/// - `GET    /stats/{code}`   - Detailed statistics for a specific link
+/// - `POST   /shorten`        - Create shortened URLs (batch-capable)
+/// - `DELETE /links/{code}`   - Soft-delete a link
+/// - `PATCH  /links/{code}`   - Partially update a link
+/// - `GET    /links/{code}/history`           - Versions of a link
+/// - `POST   /links/{code}/revert/{version}`  - Revert a link to a version
+/// - `GET    /tokens`         - List API tokens
+/// - `POST   /tokens`         - Create an API token
+/// - `DELETE /tokens/{id}`    - Revoke an API token
+/// - `GET    /audit`          - Audit log of link, domain and token changes
+/// - `GET    /cache/stats`    - Cache statistics
+/// - `GET    /cache/keys/{domain}/{code}` - Cached redirect of a link
+/// - `DELETE /cache/keys/{domain}/{code}` - Purge the cached redirect of a link
+/// - `DELETE /cache/domains/{domain}`     - Purge the cached redirects of a domain
+pub fn protected_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/domains",
+            get(domain_list_handler).post(create_domain_handler),
+        )
+        .route(
+            "/domains/{id}",
+            patch(update_domain_handler).delete(delete_domain_handler),
+        )
+        .route("/stats", get(stats_list_handler))
+        .route("/stats/{code}", get(stats_handler))
+        .route("/shorten", post(shorten_handler))
+        .route(
+            "/links/{code}",
+            delete(delete_link_handler).patch(update_link_handler),
+        )
+        .route("/links/{code}/history", get(link_history_handler))
+        .route("/links/{code}/revert/{version}", post(revert_link_handler))
+        .route(
+            "/tokens",
+            get(list_tokens_handler).post(create_token_handler),
+        )
+        .route("/tokens/{id}", delete(revoke_token_handler))
+        .route("/audit", get(audit_list_handler))
+        .route("/cache/stats", get(cache_stats_handler))
+        .route(
+            "/cache/keys/{domain}/{code}",
+            get(get_cache_key_handler).delete(purge_cache_key_handler),
+        )
+        .route(
+            "/cache/domains/{domain}",
+            delete(purge_cache_domain_handler),
+        )
+}
+
+/// Public v1 API documentation routes (no authentication).
+///
+/// # Endpoints
+///
+/// - `GET /openapi.json` - OpenAPI 3 document
+/// - `GET /docs`         - Redoc viewer rendering the same document
+pub fn public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi_handler))
+        .route("/docs", get(openapi_docs_handler))
+}
@@ -10,5 +10,6 @@
 //! - [`services::stats_service::StatsService`] - Click tracking and analytics
 //! - [`services::auth_service::AuthService`] - API token authentication
 //! - [`services::domain_service::DomainService`] - Domain management and validation
+//! - [`services::sso_service::SsoService`] - Dashboard single sign-on (OpenID Connect)
 
 pub mod services;
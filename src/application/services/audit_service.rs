@@ -0,0 +1,60 @@
+//! Querying the audit log.
+
+use std::sync::Arc;
+
+use crate::domain::entities::AuditEvent;
+use crate::domain::repositories::{AuditFilter, AuditRepository};
+use crate::error::AppError;
+
+/// Service for reading the audit log of changes to links, domains and tokens.
+///
+/// Events are written by the repositories of the changed records, in the same
+/// transaction as the change.
+pub struct AuditService<R: AuditRepository> {
+    repository: Arc<R>,
+}
+
+impl<R: AuditRepository> AuditService<R> {
+    /// Creates a new audit service.
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Lists events matching the filter, newest first, with their total count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn list(&self, filter: AuditFilter) -> Result<(Vec<AuditEvent>, i64), AppError> {
+        tokio::try_join!(
+            self.repository.list(&filter),
+            self.repository.count(&filter)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::MockAuditRepository;
+
+    #[tokio::test]
+    async fn test_list_returns_total() {
+        let mut mock_repo = MockAuditRepository::new();
+        mock_repo.expect_list().returning(|_| Ok(vec![]));
+        mock_repo
+            .expect_count()
+            .withf(|filter| filter.workspace_id == Some(2))
+            .returning(|_| Ok(12));
+
+        let service = AuditService::new(Arc::new(mock_repo));
+
+        let (events, total) = service
+            .list(AuditFilter::new(0, 10).with_workspace(Some(2)))
+            .await
+            .unwrap();
+
+        assert!(events.is_empty());
+        assert_eq!(total, 12);
+    }
+}
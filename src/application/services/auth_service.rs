@@ -1,23 +1,85 @@
-//! Authentication service for API token validation.
+//! Authentication service for API token validation and management.
 
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
+use rand::Rng;
 use sha2::Sha256;
 use std::sync::Arc;
 
-use crate::domain::repositories::TokenRepository;
+use crate::domain::entities::{AuditContext, Principal, Scope};
+use crate::domain::repositories::{ApiToken, NewApiToken, TokenRepository};
 use crate::error::AppError;
 use serde_json::json;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Length of generated tokens.
+const TOKEN_LEN: usize = 48;
+
+/// Characters of generated tokens.
+const TOKEN_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Length of the hex key ID derived from a signing secret.
+const KEY_ID_LEN: usize = 8;
+
+/// Message MACed with a signing secret to derive its key ID.
+const KEY_ID_LABEL: &str = "url-shortener token signing key id";
+
+/// HMAC key for token hashing, identified by a key ID derived from the secret.
+///
+/// Stored hashes have the form `<key id>:<hex mac>`, so the key that produced a
+/// hash is known without the plaintext token. Hashes created before key IDs were
+/// introduced are a bare hex MAC.
+#[derive(Clone)]
+struct SigningKey {
+    id: String,
+    secret: String,
+}
+
+impl SigningKey {
+    fn new(secret: String) -> Self {
+        Self {
+            id: signing_key_id(&secret),
+            secret,
+        }
+    }
+
+    /// Returns the hex-encoded HMAC-SHA256 of `token`.
+    fn mac(&self, token: &str) -> String {
+        hmac_hex(&self.secret, token)
+    }
+
+    /// Returns the stored form of `token`'s hash under this key.
+    fn hash(&self, token: &str) -> String {
+        format!("{}:{}", self.id, self.mac(token))
+    }
+}
+
+/// A newly stored token together with its plaintext value.
+///
+/// Only the hash is persisted, so this is the only time the plaintext is available.
+#[derive(Debug, Clone)]
+pub struct IssuedToken {
+    pub token: ApiToken,
+    pub plaintext: String,
+}
+
 /// Service for authenticating API requests via Bearer tokens.
 ///
-/// Tokens are hashed with HMAC-SHA256 (keyed by `signing_secret`) before storage
+/// Tokens are hashed with HMAC-SHA256 (keyed by a signing secret) before storage
 /// and comparison. An attacker with read-only access to the database cannot verify
 /// or forge tokens without the server-side secret.
+///
+/// # Secret rotation
+///
+/// Several secrets can be configured, newest first. New tokens are hashed with the
+/// first (primary) secret; tokens hashed with an older secret still authenticate and
+/// are re-hashed with the primary secret on first use. Once no token uses an old
+/// secret any more (see `admin token migrate-hashes`), it can be removed.
 pub struct AuthService<R: TokenRepository> {
     repository: Arc<R>,
-    signing_secret: String,
+    /// Signing keys, primary first. Never empty.
+    keys: Vec<SigningKey>,
 }
 
 impl<R: TokenRepository> AuthService<R> {
@@ -26,70 +88,297 @@ impl<R: TokenRepository> AuthService<R> {
     /// # Arguments
     ///
     /// - `repository` - token repository for DB operations
-    /// - `signing_secret` - HMAC key; must match the value used when tokens were created
-    pub fn new(repository: Arc<R>, signing_secret: String) -> Self {
+    /// - `signing_secrets` - HMAC keys, newest (primary) first; tokens hashed with
+    ///   any of them authenticate
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signing_secrets` is empty.
+    pub fn new(repository: Arc<R>, signing_secrets: Vec<String>) -> Self {
+        assert!(
+            !signing_secrets.is_empty(),
+            "at least one token signing secret is required"
+        );
+
         Self {
             repository,
-            signing_secret,
+            keys: signing_secrets.into_iter().map(SigningKey::new).collect(),
         }
     }
 
-    /// Hashes a raw token with HMAC-SHA256 using the server signing secret.
+    /// Hashes a raw token with HMAC-SHA256 using the primary signing secret.
     ///
-    /// Returns a 64-character lowercase hex-encoded MAC.
-    fn hash_token(&self, token: &str) -> String {
-        let mut mac = HmacSha256::new_from_slice(self.signing_secret.as_bytes())
-            .expect("HMAC accepts any key length");
-        mac.update(token.as_bytes());
-        hex::encode(mac.finalize().into_bytes())
+    /// Returns `<key id>:<hex mac>`: the 8-character ID of the primary key and the
+    /// 64-character lowercase hex-encoded MAC.
+    pub fn hash_token(&self, token: &str) -> String {
+        self.keys[0].hash(token)
+    }
+
+    /// Returns the ID of the primary signing key.
+    pub fn primary_key_id(&self) -> &str {
+        &self.keys[0].id
+    }
+
+    /// Returns the IDs of all configured signing keys, primary first.
+    pub fn key_ids(&self) -> Vec<&str> {
+        self.keys.iter().map(|k| k.id.as_str()).collect()
+    }
+
+    /// Returns every stored form `token` may have: its hash under each key, followed
+    /// by the legacy bare MACs. The first entry is [`Self::hash_token`].
+    fn candidate_hashes(&self, token: &str) -> Vec<String> {
+        let macs: Vec<String> = self.keys.iter().map(|k| k.mac(token)).collect();
+
+        self.keys
+            .iter()
+            .zip(&macs)
+            .map(|(key, mac)| format!("{}:{mac}", key.id))
+            .chain(macs.iter().cloned())
+            .collect()
     }
 
     /// Authenticates a raw token against stored credentials.
     ///
-    /// On successful authentication, updates the `last_used` timestamp for
-    /// monitoring and audit purposes.
+    /// The token is looked up by its hash under every configured key in a single
+    /// query. On successful authentication, updates the `last_used` timestamp for
+    /// monitoring and audit purposes, re-hashes the token with the primary key if
+    /// it was hashed with an older one, and returns the token's [`Principal`].
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Unauthorized`] if:
     /// - Token hash does not match any stored credentials
     /// - Token has been revoked
+    /// - Token has expired
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    pub async fn authenticate(&self, token: &str) -> Result<(), AppError> {
-        let token_hash = self.hash_token(token);
+    pub async fn authenticate(&self, token: &str) -> Result<Principal, AppError> {
+        let candidates = self.candidate_hashes(token);
+
+        let api_token = self
+            .repository
+            .validate_token(&candidates)
+            .await?
+            .ok_or_else(|| {
+                AppError::unauthorized(
+                    "Unauthorized",
+                    json!({"reason": "Invalid, revoked or expired token"}),
+                )
+            })?;
+
+        let _ = self
+            .repository
+            .update_last_used(&api_token.token_hash)
+            .await;
+
+        let primary_hash = &candidates[0];
+        if api_token.token_hash != *primary_hash
+            && let Err(e) = self
+                .repository
+                .rehash_token(api_token.id, &api_token.token_hash, primary_hash)
+                .await
+        {
+            tracing::warn!(token_id = api_token.id, error = %e, "Failed to re-hash token");
+        }
 
-        let is_valid = self.repository.validate_token(&token_hash).await?;
+        Ok(api_token.principal())
+    }
 
-        if !is_valid {
-            return Err(AppError::unauthorized(
-                "Unauthorized",
-                json!({"reason": "Invalid or revoked token"}),
-            ));
-        }
+    /// Creates a token and returns it with its plaintext value.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - human-readable token identifier
+    /// - `plaintext` - token value to store; a random one is generated if `None`
+    /// - `scopes` - permissions granted to the token
+    /// - `domain_ids` - domains the token is limited to, `None` for every domain
+    /// - `expires_at` - when the token stops working, `None` for never
+    /// - `user_id` - user the token acts for, `None` for a token that sees every
+    ///   link of its workspace
+    /// - `workspace_id` - workspace the token acts in
+    /// - `context` - who creates the token, for the audit log
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Conflict`] if a token with the same value already exists.
+    /// Returns [`AppError::Internal`] on database errors.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_token(
+        &self,
+        name: &str,
+        plaintext: Option<String>,
+        scopes: Vec<Scope>,
+        domain_ids: Option<Vec<i64>>,
+        expires_at: Option<DateTime<Utc>>,
+        user_id: Option<i64>,
+        workspace_id: i64,
+        context: &AuditContext,
+    ) -> Result<IssuedToken, AppError> {
+        let plaintext = plaintext.unwrap_or_else(generate_token);
+
+        let new_token = NewApiToken::new(name, self.hash_token(&plaintext))
+            .with_scopes(scopes)
+            .with_domains(domain_ids)
+            .with_expiry(expires_at)
+            .with_user(user_id)
+            .with_workspace(workspace_id);
+        let token = self.repository.create_token(new_token, context).await?;
+
+        Ok(IssuedToken { token, plaintext })
+    }
+
+    /// Replaces a token with a newly generated one.
+    ///
+    /// The old token keeps working until `grace_until`; see
+    /// [`TokenRepository::rotate_token`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the token does not exist, is revoked or expired.
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn rotate_token(
+        &self,
+        id: i64,
+        expires_at: Option<DateTime<Utc>>,
+        grace_until: DateTime<Utc>,
+        context: &AuditContext,
+    ) -> Result<IssuedToken, AppError> {
+        let plaintext = generate_token();
+
+        let token = self
+            .repository
+            .rotate_token(
+                id,
+                &self.hash_token(&plaintext),
+                expires_at,
+                grace_until,
+                context,
+            )
+            .await?;
+
+        Ok(IssuedToken { token, plaintext })
+    }
 
-        let _ = self.repository.update_last_used(&token_hash).await;
+    /// Lists the tokens of a workspace, or of every workspace if `None`, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn list_tokens(&self, workspace_id: Option<i64>) -> Result<Vec<ApiToken>, AppError> {
+        self.repository.list_tokens(workspace_id).await
+    }
 
-        Ok(())
+    /// Finds a token by ID (numeric input) or name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn find_token(&self, name_or_id: &str) -> Result<Option<ApiToken>, AppError> {
+        match name_or_id.parse::<i64>() {
+            Ok(id) => self.repository.find_by_id(id).await,
+            Err(_) => self.repository.find_by_name(name_or_id).await,
+        }
     }
+
+    /// Revokes a token of a workspace, or of any workspace if `workspace_id` is
+    /// `None`, and records it in the audit log for `context`. Revoking an already
+    /// revoked token is a no-op.
+    ///
+    /// Returns the token as it was before revocation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the token does not exist in the workspace.
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn revoke_token(
+        &self,
+        id: i64,
+        workspace_id: Option<i64>,
+        context: &AuditContext,
+    ) -> Result<ApiToken, AppError> {
+        let token = self
+            .repository
+            .find_by_id(id)
+            .await?
+            .filter(|token| workspace_id.is_none_or(|ws| token.workspace_id == ws))
+            .ok_or_else(|| AppError::not_found("Token not found", json!({ "id": id })))?;
+
+        self.repository.revoke_token(id, context).await?;
+        Ok(token)
+    }
+}
+
+/// Returns the key ID of a signing secret: the first 8 hex characters of the
+/// HMAC-SHA256 of a fixed label under the secret.
+///
+/// Unlike a plain hash of the secret, the ID can't be matched against hashes of
+/// the same secret used elsewhere.
+pub fn signing_key_id(secret: &str) -> String {
+    let mut id = hmac_hex(secret, KEY_ID_LABEL);
+    id.truncate(KEY_ID_LEN);
+    id
+}
+
+/// Returns the hex-encoded HMAC-SHA256 of `message` keyed with `secret`.
+fn hmac_hex(secret: &str, message: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Generates a random token.
+///
+/// 48 alphanumeric characters, about 286 bits of entropy.
+pub fn generate_token() -> String {
+    let mut rng = rand::rng();
+
+    (0..TOKEN_LEN)
+        .map(|_| TOKEN_CHARSET[rng.random_range(0..TOKEN_CHARSET.len())] as char)
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::repositories::MockTokenRepository;
+    use crate::domain::entities::{Actor, Role, Scope, UserRef, Visibility};
+    use crate::domain::repositories::{ApiToken, MockTokenRepository};
+    use chrono::Utc;
 
-    fn test_secret() -> String {
-        "test-signing-secret".to_string()
+    fn test_secret() -> Vec<String> {
+        vec!["test-signing-secret".to_string()]
     }
 
-    fn compute_expected_hash(token: &str) -> String {
-        let mut mac = HmacSha256::new_from_slice(test_secret().as_bytes())
-            .expect("HMAC accepts any key length");
+    fn compute_mac(secret: &str, token: &str) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
         mac.update(token.as_bytes());
         hex::encode(mac.finalize().into_bytes())
     }
 
+    fn compute_expected_hash(token: &str) -> String {
+        format!(
+            "{}:{}",
+            signing_key_id("test-signing-secret"),
+            compute_mac("test-signing-secret", token)
+        )
+    }
+
+    fn api_token(scopes: Vec<Scope>, domain_ids: Option<Vec<i64>>) -> ApiToken {
+        ApiToken {
+            id: 7,
+            name: "ci".to_string(),
+            token_hash: "hash".to_string(),
+            scopes,
+            domain_ids,
+            created_at: Utc::now(),
+            revoked_at: None,
+            expires_at: None,
+            user: None,
+            workspace_id: 1,
+        }
+    }
+
     #[tokio::test]
     async fn test_authenticate_success() {
         let mut mock_repo = MockTokenRepository::new();
@@ -97,16 +386,22 @@ mod tests {
         let token = "valid-token";
         let expected_hash = compute_expected_hash(token);
 
+        let stored_hash = expected_hash.clone();
         mock_repo
             .expect_validate_token()
-            .withf(move |hash| hash == &expected_hash)
+            .withf(move |hashes| hashes[0] == expected_hash)
             .times(1)
-            .returning(|_| Ok(true));
+            .returning(move |_| {
+                let mut token = api_token(Scope::ALL.to_vec(), None);
+                token.token_hash = stored_hash.clone();
+                Ok(Some(token))
+            });
 
         mock_repo
             .expect_update_last_used()
             .times(1)
             .returning(|_| Ok(()));
+        mock_repo.expect_rehash_token().times(0);
 
         let service = AuthService::new(Arc::new(mock_repo), test_secret());
 
@@ -115,6 +410,115 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_authenticate_returns_token_principal() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        mock_repo
+            .expect_validate_token()
+            .returning(|_| Ok(Some(api_token(vec![Scope::StatsRead], Some(vec![3])))));
+        mock_repo.expect_update_last_used().returning(|_| Ok(()));
+        mock_repo.expect_rehash_token().returning(|_, _, _| Ok(()));
+
+        let service = AuthService::new(Arc::new(mock_repo), test_secret());
+
+        let principal = service.authenticate("scoped-token").await.unwrap();
+
+        assert_eq!(
+            principal.actor,
+            Actor::Token {
+                id: 7,
+                name: "ci".to_string()
+            }
+        );
+        assert_eq!(principal.scopes, vec![Scope::StatsRead]);
+        assert_eq!(principal.domain_ids, Some(vec![3]));
+        assert_eq!(principal.visibility(), Visibility::Workspace(1));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_limits_user_token_to_role() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        mock_repo.expect_validate_token().returning(|_| {
+            let mut token = api_token(Scope::ALL.to_vec(), None);
+            token.user = Some(UserRef {
+                id: 3,
+                team_id: Some(9),
+                role: Role::Editor,
+            });
+            Ok(Some(token))
+        });
+        mock_repo.expect_update_last_used().returning(|_| Ok(()));
+        mock_repo.expect_rehash_token().returning(|_, _, _| Ok(()));
+
+        let service = AuthService::new(Arc::new(mock_repo), test_secret());
+
+        let principal = service.authenticate("user-token").await.unwrap();
+
+        assert_eq!(principal.scopes, Role::Editor.scopes());
+        assert_eq!(
+            principal.visibility(),
+            Visibility::Owned {
+                workspace_id: 1,
+                user_id: 3,
+                team_id: Some(9)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_checks_every_key_and_legacy_hashes() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        let expected = vec![
+            format!("{}:{}", signing_key_id("new"), compute_mac("new", "token")),
+            format!("{}:{}", signing_key_id("old"), compute_mac("old", "token")),
+            compute_mac("new", "token"),
+            compute_mac("old", "token"),
+        ];
+        mock_repo
+            .expect_validate_token()
+            .withf(move |hashes| hashes == expected.as_slice())
+            .times(1)
+            .returning(|_| Ok(None));
+
+        let service = AuthService::new(
+            Arc::new(mock_repo),
+            vec!["new".to_string(), "old".to_string()],
+        );
+
+        assert!(service.authenticate("token").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_rehashes_token_of_older_key() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        let old_hash = format!("{}:{}", signing_key_id("old"), compute_mac("old", "token"));
+        let new_hash = format!("{}:{}", signing_key_id("new"), compute_mac("new", "token"));
+
+        let stored = old_hash.clone();
+        mock_repo.expect_validate_token().returning(move |_| {
+            let mut token = api_token(Scope::ALL.to_vec(), None);
+            token.token_hash = stored.clone();
+            Ok(Some(token))
+        });
+        mock_repo.expect_update_last_used().returning(|_| Ok(()));
+        mock_repo
+            .expect_rehash_token()
+            .withf(move |id, old, new| *id == 7 && *old == old_hash && *new == new_hash)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let service = AuthService::new(
+            Arc::new(mock_repo),
+            vec!["new".to_string(), "old".to_string()],
+        );
+
+        assert!(service.authenticate("token").await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_authenticate_invalid_token() {
         let mut mock_repo = MockTokenRepository::new();
@@ -122,7 +526,7 @@ mod tests {
         mock_repo
             .expect_validate_token()
             .times(1)
-            .returning(|_| Ok(false));
+            .returning(|_| Ok(None));
 
         let service = AuthService::new(Arc::new(mock_repo), test_secret());
 
@@ -132,6 +536,102 @@ mod tests {
         assert!(matches!(result.unwrap_err(), AppError::Unauthorized { .. }));
     }
 
+    #[tokio::test]
+    async fn test_create_token_stores_hash_of_generated_plaintext() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        mock_repo
+            .expect_create_token()
+            .withf(|new_token, _| {
+                new_token.name == "ci"
+                    && new_token.scopes == vec![Scope::StatsRead]
+                    && new_token.domain_ids.is_none()
+                    && new_token.user_id.is_none()
+            })
+            .times(1)
+            .returning(|new_token, _| {
+                let mut token = api_token(new_token.scopes, new_token.domain_ids);
+                token.token_hash = new_token.token_hash;
+                Ok(token)
+            });
+
+        let service = AuthService::new(Arc::new(mock_repo), test_secret());
+
+        let issued = service
+            .create_token(
+                "ci",
+                None,
+                vec![Scope::StatsRead],
+                None,
+                None,
+                None,
+                1,
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(issued.plaintext.len(), TOKEN_LEN);
+        assert_eq!(
+            issued.token.token_hash,
+            compute_expected_hash(&issued.plaintext)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_token_uses_provided_plaintext() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        let expected_hash = compute_expected_hash("my-token");
+        mock_repo
+            .expect_create_token()
+            .withf(move |new_token, _| new_token.token_hash == expected_hash)
+            .times(1)
+            .returning(|new_token, _| Ok(api_token(new_token.scopes, None)));
+
+        let service = AuthService::new(Arc::new(mock_repo), test_secret());
+
+        let issued = service
+            .create_token(
+                "ci",
+                Some("my-token".into()),
+                Scope::ALL.to_vec(),
+                None,
+                None,
+                None,
+                1,
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(issued.plaintext, "my-token");
+    }
+
+    #[tokio::test]
+    async fn test_revoke_missing_token_is_not_found() {
+        let mut mock_repo = MockTokenRepository::new();
+
+        mock_repo.expect_find_by_id().returning(|_| Ok(None));
+        mock_repo.expect_revoke_token().times(0);
+
+        let service = AuthService::new(Arc::new(mock_repo), test_secret());
+
+        let result = service.revoke_token(99, None, &AuditContext::cli()).await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_generate_token_format() {
+        let a = generate_token();
+        let b = generate_token();
+
+        assert_eq!(a.len(), TOKEN_LEN);
+        assert!(a.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(a, b);
+    }
+
     #[tokio::test]
     async fn test_hash_token_consistency() {
         let mock_repo = MockTokenRepository::new();
@@ -141,7 +641,8 @@ mod tests {
         let hash2 = service.hash_token("test-token");
 
         assert_eq!(hash1, hash2);
-        assert_eq!(hash1.len(), 64);
+        assert_eq!(hash1.len(), KEY_ID_LEN + 1 + 64);
+        assert!(hash1.starts_with(&format!("{}:", service.primary_key_id())));
     }
 
     #[tokio::test]
@@ -160,10 +661,21 @@ mod tests {
         let mock_repo1 = MockTokenRepository::new();
         let mock_repo2 = MockTokenRepository::new();
 
-        let svc1 = AuthService::new(Arc::new(mock_repo1), "secret-a".to_string());
-        let svc2 = AuthService::new(Arc::new(mock_repo2), "secret-b".to_string());
+        let svc1 = AuthService::new(Arc::new(mock_repo1), vec!["secret-a".to_string()]);
+        let svc2 = AuthService::new(Arc::new(mock_repo2), vec!["secret-b".to_string()]);
 
         // Same token, different secrets → different hashes
         assert_ne!(svc1.hash_token("token"), svc2.hash_token("token"));
     }
+
+    #[test]
+    fn test_signing_key_id_is_not_a_hash_of_the_secret() {
+        use sha2::Digest;
+
+        let id = signing_key_id("secret-a");
+
+        assert_eq!(id, compute_mac("secret-a", KEY_ID_LABEL)[..KEY_ID_LEN]);
+        assert!(!hex::encode(Sha256::digest("secret-a")).starts_with(&id));
+        assert_ne!(id, signing_key_id("secret-b"));
+    }
 }
@@ -0,0 +1,237 @@
+//! Cache warm-up and inspection of cached redirects.
+
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::domain::repositories::{PopularLink, StatsRepository};
+use crate::error::AppError;
+use crate::infrastructure::cache::{
+    CacheEntry, CacheService, CacheStats, CachedLink, CachedRedirect, NewCacheEntry,
+};
+
+/// Service for filling, inspecting and purging the redirect cache.
+///
+/// Used by the admin API, the `admin cache` CLI commands and the warm-up at
+/// startup. Unlike redirects, which fail open, purges and statistics report
+/// cache backend errors.
+pub struct CacheAdminService<R: StatsRepository> {
+    cache: Arc<dyn CacheService>,
+    repository: Arc<R>,
+}
+
+impl<R: StatsRepository> CacheAdminService<R> {
+    /// Creates a new cache admin service.
+    pub fn new(cache: Arc<dyn CacheService>, repository: Arc<R>) -> Self {
+        Self { cache, repository }
+    }
+
+    /// Caches the redirects of the `limit` links clicked most since `since`.
+    ///
+    /// Returns the number of links cached. Entries are written as on a cache
+    /// miss, so a warmed link expires with the link itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn warm_up(&self, limit: i64, since: DateTime<Utc>) -> Result<usize, AppError> {
+        let links = self.repository.most_clicked(since, limit).await?;
+        let entries: Vec<NewCacheEntry> = links.into_iter().map(cache_entry).collect();
+
+        self.cache.set_many(&entries).await?;
+
+        metrics::counter!("cache_warmed_links_total").increment(entries.len() as u64);
+        info!("Warmed up cache with {} links", entries.len());
+        Ok(entries.len())
+    }
+
+    /// Returns the cached entry of `code` on `domain`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on cache errors.
+    pub async fn inspect(&self, domain: &str, code: &str) -> Result<Option<CacheEntry>, AppError> {
+        Ok(self
+            .cache
+            .get_entry(&CachedRedirect::key(domain, code))
+            .await?)
+    }
+
+    /// Removes the cached entry of `code` on `domain` on every instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on cache errors.
+    pub async fn purge(&self, domain: &str, code: &str) -> Result<(), AppError> {
+        Ok(self
+            .cache
+            .invalidate(&CachedRedirect::key(domain, code))
+            .await?)
+    }
+
+    /// Removes the cached entries of every code on `domain` on every instance.
+    ///
+    /// Returns the number of entries removed from the shared cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on cache errors.
+    pub async fn purge_domain(&self, domain: &str) -> Result<u64, AppError> {
+        let removed = self
+            .cache
+            .purge_prefix(&CachedRedirect::domain_prefix(domain))
+            .await?;
+
+        info!("Purged {} cached redirects of {}", removed, domain);
+        Ok(removed)
+    }
+
+    /// Returns the statistics reported by the cache backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on cache errors.
+    pub async fn stats(&self) -> Result<CacheStats, AppError> {
+        Ok(self.cache.stats().await?)
+    }
+}
+
+/// Builds the cache entry a redirect of the link would write.
+fn cache_entry(link: PopularLink) -> NewCacheEntry {
+    let cached = CachedLink {
+        url: link.long_url,
+        permanent: link.permanent,
+        expires_at: link.expires_at,
+        link_id: Some(link.link_id),
+        domain_id: Some(link.domain_id),
+    };
+
+    NewCacheEntry {
+        key: CachedRedirect::key(&link.domain, &link.code),
+        ttl_seconds: cached.ttl_seconds(),
+        value: CachedRedirect::Found(cached).encode(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::MockStatsRepository;
+    use crate::infrastructure::cache::{NullCache, TieredCache};
+
+    fn popular(link_id: i64, domain: &str, code: &str) -> PopularLink {
+        PopularLink {
+            link_id,
+            domain_id: 1,
+            domain: domain.to_string(),
+            code: code.to_string(),
+            long_url: format!("https://example.com/{code}"),
+            permanent: false,
+            expires_at: None,
+            clicks: 10,
+        }
+    }
+
+    fn service(repo: MockStatsRepository) -> CacheAdminService<MockStatsRepository> {
+        CacheAdminService::new(
+            Arc::new(TieredCache::new(NullCache::new(), 100, 60)),
+            Arc::new(repo),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_caches_most_clicked_links() {
+        let mut repo = MockStatsRepository::new();
+        repo.expect_most_clicked()
+            .withf(|_, limit| *limit == 2)
+            .times(1)
+            .returning(|_, _| {
+                Ok(vec![
+                    popular(7, "s.example.com", "abc"),
+                    popular(8, "s.example.com", "def"),
+                ])
+            });
+        let service = service(repo);
+
+        let warmed = service.warm_up(2, Utc::now()).await.unwrap();
+
+        assert_eq!(warmed, 2);
+        let entry = service
+            .inspect("s.example.com", "abc")
+            .await
+            .unwrap()
+            .unwrap();
+        let CachedRedirect::Found(link) = CachedRedirect::parse(&entry.value).unwrap() else {
+            panic!("expected a cached link");
+        };
+        assert_eq!(link.url, "https://example.com/abc");
+        assert_eq!(link.link_id, Some(7));
+    }
+
+    #[test]
+    fn test_warm_up_keeps_link_expiry() {
+        let expires_at = Utc::now() + chrono::Duration::minutes(5);
+        let link = PopularLink {
+            expires_at: Some(expires_at),
+            ..popular(7, "s.example.com", "abc")
+        };
+
+        let entry = cache_entry(link);
+
+        assert!(entry.ttl_seconds.unwrap() <= 300);
+    }
+
+    #[tokio::test]
+    async fn test_purge_domain_leaves_other_domains() {
+        let mut repo = MockStatsRepository::new();
+        repo.expect_most_clicked().returning(|_, _| {
+            Ok(vec![
+                popular(7, "a.example.com", "abc"),
+                popular(8, "b.example.com", "abc"),
+            ])
+        });
+        let service = service(repo);
+        service.warm_up(10, Utc::now()).await.unwrap();
+
+        service.purge_domain("a.example.com").await.unwrap();
+
+        assert!(
+            service
+                .inspect("a.example.com", "abc")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            service
+                .inspect("b.example.com", "abc")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_one_code() {
+        let mut repo = MockStatsRepository::new();
+        repo.expect_most_clicked().returning(|_, _| {
+            Ok(vec![
+                popular(7, "s.example.com", "abc"),
+                popular(8, "s.example.com", "def"),
+            ])
+        });
+        let service = service(repo);
+        service.warm_up(10, Utc::now()).await.unwrap();
+
+        service.purge("s.example.com", "abc").await.unwrap();
+
+        assert!(
+            service
+                .inspect("s.example.com", "abc")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(service.stats().await.unwrap().local_entries, Some(1));
+    }
+}
@@ -1,7 +1,7 @@
 //! Domain management service.
 
-use crate::domain::entities::{Domain, NewDomain, UpdateDomain};
-use crate::domain::repositories::DomainRepository;
+use crate::domain::entities::{AuditContext, Domain, NewDomain, UpdateDomain};
+use crate::domain::repositories::{DomainRepository, WorkspaceRepository};
 use crate::error::AppError;
 use serde_json::json;
 use std::sync::Arc;
@@ -10,19 +10,27 @@ use std::sync::Arc;
 ///
 /// Handles domain CRUD operations with validation to ensure:
 /// - Valid DNS-compatible domain names
-/// - Proper default domain management
+/// - Proper default domain management, one default per workspace
 /// - Safe deletion (prevents cascading issues)
-pub struct DomainService<R: DomainRepository> {
+/// - The workspace's custom domain quota
+pub struct DomainService<R: DomainRepository, W: WorkspaceRepository> {
     repository: Arc<R>,
+    workspace_repository: Arc<W>,
 }
 
-impl<R: DomainRepository> DomainService<R> {
+impl<R: DomainRepository, W: WorkspaceRepository> DomainService<R, W> {
     /// Creates a new domain service.
-    pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+    pub fn new(repository: Arc<R>, workspace_repository: Arc<W>) -> Self {
+        Self {
+            repository,
+            workspace_repository,
+        }
     }
 
-    /// Creates a new domain.
+    /// Creates a new domain in a workspace and records it in the audit log for
+    /// `context`.
+    ///
+    /// The first domain of a workspace becomes its default.
     ///
     /// # Validation
     ///
@@ -34,11 +42,14 @@ impl<R: DomainRepository> DomainService<R> {
     ///
     /// Returns [`AppError::Validation`] if validation fails.
     /// Returns [`AppError::Conflict`] if domain already exists.
+    /// Returns [`AppError::Forbidden`] if the workspace's domain quota is used up.
     pub async fn create_domain(
         &self,
+        workspace_id: i64,
         domain: String,
         is_default: bool,
         description: Option<String>,
+        context: &AuditContext,
     ) -> Result<Domain, AppError> {
         self.validate_domain_name(&domain)?;
 
@@ -49,13 +60,32 @@ impl<R: DomainRepository> DomainService<R> {
             ));
         }
 
+        let quotas = self
+            .workspace_repository
+            .find_by_id(workspace_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Workspace not found", json!({"id": workspace_id})))?
+            .quotas;
+        let used = self
+            .workspace_repository
+            .count_domains(workspace_id)
+            .await?;
+        if quotas.domains_exhausted(used) {
+            return Err(AppError::forbidden(
+                "Workspace domain quota exceeded",
+                json!({"quota": "max_domains", "limit": quotas.max_domains}),
+            ));
+        }
+
+        let is_default = is_default || used == 0;
         let new_domain = NewDomain {
             domain,
             is_default,
             description,
+            workspace_id,
         };
 
-        let created = self.repository.create(new_domain).await?;
+        let created = self.repository.create(new_domain, context).await?;
 
         if is_default {
             self.repository.set_default(created.id).await?;
@@ -64,9 +94,25 @@ impl<R: DomainRepository> DomainService<R> {
         Ok(created)
     }
 
-    /// Lists all non-deleted domains, optionally filtered by active status.
-    pub async fn list_domains(&self, only_active: bool) -> Result<Vec<Domain>, AppError> {
-        self.repository.list(only_active).await
+    /// Lists the non-deleted domains of a workspace, optionally filtered by active status.
+    pub async fn list_domains(
+        &self,
+        workspace_id: i64,
+        only_active: bool,
+    ) -> Result<Vec<Domain>, AppError> {
+        self.repository.list(Some(workspace_id), only_active).await
+    }
+
+    /// Retrieves a domain by its database ID, including soft-deleted ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the domain does not exist.
+    pub async fn get_domain_by_id(&self, domain_id: i64) -> Result<Domain, AppError> {
+        self.repository
+            .find_by_id(domain_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Domain not found", json!({"id": domain_id})))
     }
 
     /// Retrieves a domain by name.
@@ -89,23 +135,24 @@ impl<R: DomainRepository> DomainService<R> {
         }
     }
 
-    /// Retrieves the system default domain.
-    pub async fn get_default_domain(&self) -> Result<Domain, AppError> {
-        self.repository.get_default().await
+    /// Retrieves the default domain of a workspace.
+    pub async fn get_default_domain(&self, workspace_id: i64) -> Result<Domain, AppError> {
+        self.repository.get_default(workspace_id).await
     }
 
-    /// Sets a domain as the system default (atomic transaction).
+    /// Sets a domain as its workspace's default (atomic transaction).
     pub async fn set_default(&self, domain_id: i64) -> Result<(), AppError> {
         self.repository.set_default(domain_id).await
     }
 
-    /// Partially updates a domain.
+    /// Partially updates a domain and records the change in the audit log for
+    /// `context`.
     ///
     /// # `is_default` handling
     ///
-    /// Setting `is_default = true` atomically transfers the default flag via
-    /// `set_default()`. Setting `is_default = false` is rejected — to change the
-    /// default, set another domain as default instead.
+    /// Setting `is_default = true` transfers the default flag in the same
+    /// transaction as the update. Setting `is_default = false` is rejected — to
+    /// change the default, set another domain as default instead.
     ///
     /// # Errors
     ///
@@ -116,6 +163,7 @@ impl<R: DomainRepository> DomainService<R> {
         &self,
         domain_id: i64,
         update: UpdateDomain,
+        context: &AuditContext,
     ) -> Result<Domain, AppError> {
         if update.is_default == Some(false) {
             return Err(AppError::bad_request(
@@ -124,18 +172,15 @@ impl<R: DomainRepository> DomainService<R> {
             ));
         }
 
-        if update.is_default == Some(true) {
-            self.repository.set_default(domain_id).await?;
-        }
-
         if let Some(ref name) = update.domain {
             self.validate_domain_name(name)?;
         }
 
-        self.repository.update(domain_id, update).await
+        self.repository.update(domain_id, update, context).await
     }
 
-    /// Soft-deletes a domain after safety checks.
+    /// Soft-deletes a domain after safety checks and records it in the audit log
+    /// for `context`.
     ///
     /// # Safety Checks
     ///
@@ -146,12 +191,12 @@ impl<R: DomainRepository> DomainService<R> {
     ///
     /// Returns [`AppError::NotFound`] if the domain does not exist.
     /// Returns [`AppError::Validation`] if safety checks fail.
-    pub async fn delete_domain(&self, domain_id: i64) -> Result<(), AppError> {
-        let domain = self
-            .repository
-            .find_by_id(domain_id)
-            .await?
-            .ok_or_else(|| AppError::not_found("Domain not found", json!({"id": domain_id})))?;
+    pub async fn delete_domain(
+        &self,
+        domain_id: i64,
+        context: &AuditContext,
+    ) -> Result<(), AppError> {
+        let domain = self.get_domain_by_id(domain_id).await?;
 
         if domain.is_default {
             return Err(AppError::bad_request(
@@ -168,7 +213,7 @@ impl<R: DomainRepository> DomainService<R> {
             ));
         }
 
-        self.repository.delete(domain_id).await
+        self.repository.delete(domain_id, context).await
     }
 
     /// Validates domain name format.
@@ -204,9 +249,30 @@ impl<R: DomainRepository> DomainService<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::repositories::MockDomainRepository;
+    use crate::domain::entities::{Workspace, WorkspaceQuotas};
+    use crate::domain::repositories::{MockDomainRepository, MockWorkspaceRepository};
     use chrono::Utc;
 
+    /// Workspace 1 with `used` domains out of `max_domains`.
+    fn workspaces(max_domains: Option<i64>, used: i64) -> MockWorkspaceRepository {
+        let mut mock_repo = MockWorkspaceRepository::new();
+        mock_repo.expect_find_by_id().returning(move |id| {
+            Ok(Some(Workspace {
+                id,
+                name: "default".to_string(),
+                quotas: WorkspaceQuotas {
+                    max_domains,
+                    ..Default::default()
+                },
+                created_at: Utc::now(),
+            }))
+        });
+        mock_repo
+            .expect_count_domains()
+            .returning(move |_| Ok(used));
+        mock_repo
+    }
+
     fn create_test_domain(id: i64, name: &str, is_default: bool) -> Domain {
         Domain::new(
             id,
@@ -234,12 +300,18 @@ mod tests {
         mock_repo
             .expect_create()
             .times(1)
-            .returning(move |_| Ok(created_domain.clone()));
+            .returning(move |_, _| Ok(created_domain.clone()));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
-            .create_domain("new.example.com".to_string(), false, None)
+            .create_domain(
+                1,
+                "new.example.com".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_ok());
@@ -247,6 +319,68 @@ mod tests {
         assert_eq!(domain.domain, "new.example.com");
     }
 
+    #[tokio::test]
+    async fn test_create_domain_quota_exceeded() {
+        let mut mock_repo = MockDomainRepository::new();
+
+        mock_repo
+            .expect_find_by_name()
+            .times(1)
+            .returning(|_| Ok(None));
+        mock_repo.expect_create().times(0);
+
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(Some(2), 2)));
+
+        let result = service
+            .create_domain(
+                1,
+                "new.example.com".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
+            .await;
+
+        assert!(matches!(result.unwrap_err(), AppError::Forbidden { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_create_first_domain_of_workspace_is_default() {
+        let mut mock_repo = MockDomainRepository::new();
+
+        mock_repo
+            .expect_find_by_name()
+            .times(1)
+            .returning(|_| Ok(None));
+
+        let created_domain = create_test_domain(7, "first.example.com", true).with_workspace(3);
+        mock_repo
+            .expect_create()
+            .withf(|new_domain, _| new_domain.is_default && new_domain.workspace_id == 3)
+            .times(1)
+            .returning(move |_, _| Ok(created_domain.clone()));
+        mock_repo
+            .expect_set_default()
+            .withf(|id| *id == 7)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(Some(2), 0)));
+
+        let domain = service
+            .create_domain(
+                3,
+                "first.example.com".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert!(domain.is_default);
+    }
+
     #[tokio::test]
     async fn test_create_domain_already_exists() {
         let mut mock_repo = MockDomainRepository::new();
@@ -257,10 +391,16 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(Some(existing.clone())));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
-            .create_domain("existing.com".to_string(), false, None)
+            .create_domain(
+                1,
+                "existing.com".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -270,9 +410,11 @@ mod tests {
     #[tokio::test]
     async fn test_create_domain_invalid_empty() {
         let mock_repo = MockDomainRepository::new();
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
-        let result = service.create_domain("".to_string(), false, None).await;
+        let result = service
+            .create_domain(1, "".to_string(), false, None, &AuditContext::cli())
+            .await;
 
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::Validation { .. }));
@@ -281,10 +423,16 @@ mod tests {
     #[tokio::test]
     async fn test_create_domain_invalid_no_dot() {
         let mock_repo = MockDomainRepository::new();
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
-            .create_domain("localhost".to_string(), false, None)
+            .create_domain(
+                1,
+                "localhost".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -294,10 +442,16 @@ mod tests {
     #[tokio::test]
     async fn test_create_domain_invalid_characters() {
         let mock_repo = MockDomainRepository::new();
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
-            .create_domain("bad_domain!.com".to_string(), false, None)
+            .create_domain(
+                1,
+                "bad_domain!.com".to_string(),
+                false,
+                None,
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -315,7 +469,7 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(Some(domain.clone())));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service.get_domain("test.com").await;
 
@@ -332,7 +486,7 @@ mod tests {
             .times(1)
             .returning(|_| Ok(None));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service.get_domain("notfound.com").await;
 
@@ -359,7 +513,7 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(Some(deleted.clone())));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service.get_domain("deleted.com").await;
 
@@ -379,9 +533,9 @@ mod tests {
 
         mock_repo.expect_count_links().times(1).returning(|_| Ok(5));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
-        let result = service.delete_domain(1).await;
+        let result = service.delete_domain(1, &AuditContext::cli()).await;
 
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::Validation { .. }));
@@ -397,9 +551,9 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(Some(domain.clone())));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
-        let result = service.delete_domain(1).await;
+        let result = service.delete_domain(1, &AuditContext::cli()).await;
 
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::Validation { .. }));
@@ -417,11 +571,11 @@ mod tests {
         mock_repo
             .expect_list()
             .times(1)
-            .returning(move |_| Ok(domains.clone()));
+            .returning(move |_, _| Ok(domains.clone()));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
-        let result = service.list_domains(true).await;
+        let result = service.list_domains(1, true).await;
 
         assert!(result.is_ok());
         let list = result.unwrap();
@@ -431,7 +585,7 @@ mod tests {
     #[tokio::test]
     async fn test_update_domain_reject_unset_default() {
         let mock_repo = MockDomainRepository::new();
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
             .update_domain(
@@ -440,6 +594,7 @@ mod tests {
                     is_default: Some(false),
                     ..Default::default()
                 },
+                &AuditContext::cli(),
             )
             .await;
 
@@ -451,19 +606,16 @@ mod tests {
     async fn test_update_domain_set_default() {
         let mut mock_repo = MockDomainRepository::new();
 
-        mock_repo
-            .expect_set_default()
-            .withf(|id| *id == 2)
-            .times(1)
-            .returning(|_| Ok(()));
+        mock_repo.expect_set_default().times(0);
 
         let updated = create_test_domain(2, "new-default.com", true);
         mock_repo
             .expect_update()
+            .withf(|id, update, _| *id == 2 && update.is_default == Some(true))
             .times(1)
-            .returning(move |_, _| Ok(updated.clone()));
+            .returning(move |_, _, _| Ok(updated.clone()));
 
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
             .update_domain(
@@ -472,6 +624,7 @@ mod tests {
                     is_default: Some(true),
                     ..Default::default()
                 },
+                &AuditContext::cli(),
             )
             .await;
 
@@ -482,7 +635,7 @@ mod tests {
     #[tokio::test]
     async fn test_update_domain_rename_invalid_name() {
         let mock_repo = MockDomainRepository::new();
-        let service = DomainService::new(Arc::new(mock_repo));
+        let service = DomainService::new(Arc::new(mock_repo), Arc::new(workspaces(None, 1)));
 
         let result = service
             .update_domain(
@@ -491,6 +644,7 @@ mod tests {
                     domain: Some("no-dot-here".to_string()),
                     ..Default::default()
                 },
+                &AuditContext::cli(),
             )
             .await;
 
@@ -0,0 +1,318 @@
+//! Idempotency key handling for retried requests.
+
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio_retry::Retry;
+use tokio_retry::strategy::FixedInterval;
+
+use crate::domain::repositories::IdempotencyRepository;
+use crate::error::AppError;
+
+/// How long a key and its response are kept.
+pub const IDEMPOTENCY_KEY_TTL: Duration = Duration::hours(24);
+
+/// How long a key may stay in flight before a retry is allowed to take it over.
+///
+/// Covers requests whose handler never finished (client disconnect, crash).
+pub const IN_FLIGHT_TIMEOUT: Duration = Duration::seconds(60);
+
+/// How many times storing a response is retried before the key is released.
+pub const COMPLETE_RETRIES: usize = 2;
+
+/// Delay between attempts to store a response.
+const COMPLETE_RETRY_DELAY_MS: u64 = 100;
+
+/// Maximum accepted length of an `Idempotency-Key` header value.
+pub const MAX_KEY_LENGTH: usize = 255;
+
+/// Outcome of [`IdempotencyService::begin`].
+#[derive(Debug, PartialEq)]
+pub enum IdempotencyStatus {
+    /// The key is reserved; process the request and call [`IdempotencyService::complete`].
+    Started,
+    /// The key was already used with this request; return the stored response as-is.
+    Replay(Value),
+}
+
+/// Service that lets clients retry requests safely with an `Idempotency-Key`.
+///
+/// The first request with a key reserves it, the response is stored for
+/// [`IDEMPOTENCY_KEY_TTL`], and retries with the same body get the stored
+/// response instead of being processed again.
+pub struct IdempotencyService<R: IdempotencyRepository> {
+    repository: Arc<R>,
+}
+
+impl<R: IdempotencyRepository> IdempotencyService<R> {
+    /// Creates a new idempotency service.
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Reserves `key` for a request, or returns the response stored for it.
+    ///
+    /// # Arguments
+    ///
+    /// - `scope` - namespace of the caller (token or user), so keys never collide across clients
+    /// - `key` - client-supplied `Idempotency-Key`
+    /// - `request_hash` - [`request_hash`] of the request body
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Validation`] if the key is empty or too long.
+    /// Returns [`AppError::Unprocessable`] if the key was used with a different request body.
+    /// Returns [`AppError::Conflict`] if a request with the same key is still in flight.
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn begin(
+        &self,
+        scope: &str,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyStatus, AppError> {
+        validate_key(key)?;
+
+        let now = Utc::now();
+        let reserved = self
+            .repository
+            .try_reserve(
+                scope,
+                key,
+                request_hash,
+                now + IDEMPOTENCY_KEY_TTL,
+                now - IN_FLIGHT_TIMEOUT,
+            )
+            .await?;
+
+        if reserved {
+            return Ok(IdempotencyStatus::Started);
+        }
+
+        let record = self.repository.find(scope, key).await?.ok_or_else(|| {
+            AppError::conflict(
+                "Idempotency key is being processed",
+                json!({"idempotency_key": key}),
+            )
+        })?;
+
+        if record.request_hash != request_hash {
+            return Err(AppError::unprocessable(
+                "Idempotency key was already used with a different request",
+                json!({"idempotency_key": key}),
+            ));
+        }
+
+        match record.response {
+            Some(response) => Ok(IdempotencyStatus::Replay(response)),
+            None => Err(AppError::conflict(
+                "A request with this idempotency key is still in progress",
+                json!({"idempotency_key": key, "retry_after_seconds": IN_FLIGHT_TIMEOUT.num_seconds()}),
+            )),
+        }
+    }
+
+    /// Stores the response of a request started with [`begin`](Self::begin).
+    ///
+    /// Retries up to [`COMPLETE_RETRIES`] times. If the response still cannot
+    /// be stored, the key is released, so retries of the request are processed
+    /// again instead of getting 409 until [`IN_FLIGHT_TIMEOUT`] passes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] if the response could not be stored.
+    pub async fn complete(&self, scope: &str, key: &str, response: &Value) -> Result<(), AppError> {
+        let strategy = FixedInterval::from_millis(COMPLETE_RETRY_DELAY_MS).take(COMPLETE_RETRIES);
+        let result =
+            Retry::spawn(strategy, || self.repository.complete(scope, key, response)).await;
+
+        if result.is_err()
+            && let Err(e) = self.repository.release(scope, key).await
+        {
+            tracing::warn!(idempotency_key = %key, "Failed to release idempotency key: {}", e);
+        }
+        result
+    }
+
+    /// Deletes expired keys. Returns the number of removed keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn purge_expired(&self) -> Result<u64, AppError> {
+        self.repository.delete_expired().await
+    }
+}
+
+/// Hashes a request body for comparison between retries.
+///
+/// Hashes the parsed request rather than the raw bytes, so retries that only
+/// differ in whitespace are treated as the same request.
+pub fn request_hash<T: Serialize>(request: &T) -> String {
+    let bytes = serde_json::to_vec(request).expect("request serializes to JSON");
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn validate_key(key: &str) -> Result<(), AppError> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(AppError::bad_request(
+            "Invalid Idempotency-Key",
+            json!({"reason": format!("must be 1-{MAX_KEY_LENGTH} characters")}),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::{IdempotencyRecord, MockIdempotencyRepository};
+
+    fn record(request_hash: &str, response: Option<Value>) -> IdempotencyRecord {
+        IdempotencyRecord {
+            request_hash: request_hash.to_string(),
+            response,
+            created_at: Utc::now(),
+            expires_at: Utc::now() + IDEMPOTENCY_KEY_TTL,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_begin_reserves_new_key() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo
+            .expect_try_reserve()
+            .withf(|scope, key, hash, expires_at, stale_before| {
+                scope == "scope"
+                    && key == "key-1"
+                    && hash == "h1"
+                    && *expires_at > Utc::now() + Duration::hours(23)
+                    && *stale_before < Utc::now()
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(true));
+        mock_repo.expect_find().times(0);
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let status = service.begin("scope", "key-1", "h1").await.unwrap();
+
+        assert_eq!(status, IdempotencyStatus::Started);
+    }
+
+    #[tokio::test]
+    async fn test_begin_replays_stored_response() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo
+            .expect_try_reserve()
+            .returning(|_, _, _, _, _| Ok(false));
+        mock_repo
+            .expect_find()
+            .returning(|_, _| Ok(Some(record("h1", Some(json!({"ok": true}))))));
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let status = service.begin("scope", "key-1", "h1").await.unwrap();
+
+        assert_eq!(status, IdempotencyStatus::Replay(json!({"ok": true})));
+    }
+
+    #[tokio::test]
+    async fn test_begin_rejects_different_request() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo
+            .expect_try_reserve()
+            .returning(|_, _, _, _, _| Ok(false));
+        mock_repo
+            .expect_find()
+            .returning(|_, _| Ok(Some(record("h1", Some(json!({}))))));
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let result = service.begin("scope", "key-1", "h2").await;
+
+        assert!(matches!(result, Err(AppError::Unprocessable { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_begin_conflicts_while_in_flight() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo
+            .expect_try_reserve()
+            .returning(|_, _, _, _, _| Ok(false));
+        mock_repo
+            .expect_find()
+            .returning(|_, _| Ok(Some(record("h1", None))));
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let result = service.begin("scope", "key-1", "h1").await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_begin_rejects_oversized_key() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo.expect_try_reserve().times(0);
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let key = "k".repeat(MAX_KEY_LENGTH + 1);
+        let result = service.begin("scope", &key, "h1").await;
+
+        assert!(matches!(result, Err(AppError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_complete_retries_failed_store() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        let mut seq = mockall::Sequence::new();
+        mock_repo
+            .expect_complete()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Err(AppError::internal("Database error", json!({}))));
+        mock_repo
+            .expect_complete()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        mock_repo.expect_release().times(0);
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let result = service
+            .complete("scope", "key-1", &json!({"ok": true}))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_complete_releases_key_when_store_keeps_failing() {
+        let mut mock_repo = MockIdempotencyRepository::new();
+        mock_repo
+            .expect_complete()
+            .times(COMPLETE_RETRIES + 1)
+            .returning(|_, _, _| Err(AppError::internal("Database error", json!({}))));
+        mock_repo
+            .expect_release()
+            .withf(|scope, key| scope == "scope" && key == "key-1")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = IdempotencyService::new(Arc::new(mock_repo));
+        let result = service
+            .complete("scope", "key-1", &json!({"ok": true}))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Internal { .. })));
+    }
+
+    #[test]
+    fn test_request_hash_is_stable_and_body_sensitive() {
+        let a = request_hash(&json!({"urls": [{"url": "https://a.example"}]}));
+        let b = request_hash(&json!({"urls": [{"url": "https://a.example"}]}));
+        let c = request_hash(&json!({"urls": [{"url": "https://b.example"}]}));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.len(), 64);
+    }
+}
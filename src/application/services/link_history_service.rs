@@ -0,0 +1,96 @@
+//! Reading and reverting the version history of links.
+
+use std::sync::Arc;
+
+use crate::domain::entities::{Link, LinkPatch, LinkVersion};
+use crate::domain::repositories::LinkVersionRepository;
+use crate::error::AppError;
+use serde_json::json;
+
+/// Service for the versions of a link's destination, expiry and redirect type.
+///
+/// Versions are recorded by the link repository in the transaction that creates
+/// or changes the link.
+pub struct LinkHistoryService<V: LinkVersionRepository> {
+    repository: Arc<V>,
+}
+
+impl<V: LinkVersionRepository> LinkHistoryService<V> {
+    /// Creates a new link history service.
+    pub fn new(repository: Arc<V>) -> Self {
+        Self { repository }
+    }
+
+    /// Lists a link's versions, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn history(&self, link: &Link) -> Result<Vec<LinkVersion>, AppError> {
+        self.repository.list(link.id).await
+    }
+
+    /// Returns the patch that sets a link back to one of its versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the link has no such version.
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn revert_patch(&self, link: &Link, version: i32) -> Result<LinkPatch, AppError> {
+        let target = self
+            .repository
+            .find(link.id, version)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    "Link version not found",
+                    json!({ "code": link.code, "version": version }),
+                )
+            })?;
+
+        Ok(LinkPatch {
+            url: Some(target.long_url),
+            expires_at: Some(target.expires_at),
+            permanent: Some(target.permanent),
+            restore: false,
+            reverted_from: Some(version),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::MockLinkVersionRepository;
+    use chrono::Utc;
+
+    fn link(long_url: &str) -> Link {
+        Link::new(
+            3,
+            "abc".to_string(),
+            long_url.to_string(),
+            None,
+            Utc::now(),
+            None,
+            false,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_revert_patch_of_missing_version_is_not_found() {
+        let mut mock_repo = MockLinkVersionRepository::new();
+        mock_repo
+            .expect_find()
+            .withf(|link_id, version| *link_id == 3 && *version == 9)
+            .returning(|_, _| Ok(None));
+
+        let service = LinkHistoryService::new(Arc::new(mock_repo));
+
+        let result = service
+            .revert_patch(&link("https://example.com/a"), 9)
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+}
@@ -2,47 +2,76 @@
 
 use std::sync::Arc;
 
-use crate::domain::entities::{Link, LinkPatch, NewLink};
-use crate::domain::repositories::{DomainRepository, LinkRepository};
+use crate::domain::entities::{
+    AuditContext, DedupePolicy, Link, LinkPatch, NewLink, Principal, WorkspaceQuotas, month_start,
+};
+use crate::domain::repositories::{DomainRepository, LinkRepository, WorkspaceRepository};
 use crate::error::AppError;
 use crate::utils::code_generator::{generate_code, validate_custom_code};
 use crate::utils::url_normalizer::normalize_url;
 use chrono::{DateTime, Utc};
 use serde_json::json;
 
+/// Result of [`LinkService::create_short_link_for_domain`].
+#[derive(Debug, Clone)]
+pub struct CreatedLink {
+    pub link: Link,
+    /// `true` when an existing link was returned instead of creating one.
+    pub reused: bool,
+}
+
 /// Service for creating and managing shortened links.
 ///
 /// Handles URL normalization, code generation/validation, deduplication,
-/// soft-deletion, and partial updates.
-pub struct LinkService<L: LinkRepository, D: DomainRepository> {
+/// soft-deletion, and partial updates. New links are owned by the caller's user
+/// and team; callers may only reuse, update and delete links they can see
+/// (see [`Principal::visibility`]). Creating and restoring links is limited by
+/// the workspace's link quota.
+pub struct LinkService<L: LinkRepository, D: DomainRepository, W: WorkspaceRepository> {
     link_repository: Arc<L>,
     domain_repository: Arc<D>,
+    workspace_repository: Arc<W>,
 }
 
-impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
+impl<L: LinkRepository, D: DomainRepository, W: WorkspaceRepository> LinkService<L, D, W> {
     /// Creates a new link service.
-    pub fn new(link_repository: Arc<L>, domain_repository: Arc<D>) -> Self {
+    pub fn new(
+        link_repository: Arc<L>,
+        domain_repository: Arc<D>,
+        workspace_repository: Arc<W>,
+    ) -> Self {
         Self {
             link_repository,
             domain_repository,
+            workspace_repository,
         }
     }
 
-    /// Creates a short link using the default domain.
+    /// Creates a short link using the default domain of the caller's workspace.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_short_link(
         &self,
         long_url: String,
         custom_code: Option<String>,
         expires_at: Option<DateTime<Utc>>,
         permanent: bool,
-    ) -> Result<Link, AppError> {
-        let default_domain = self.domain_repository.get_default().await?;
+        dedupe: DedupePolicy,
+        principal: &Principal,
+        context: &AuditContext,
+    ) -> Result<CreatedLink, AppError> {
+        let default_domain = self
+            .domain_repository
+            .get_default(principal.workspace_id)
+            .await?;
         self.create_short_link_for_domain(
             long_url,
             default_domain.id,
             custom_code,
             expires_at,
             permanent,
+            dedupe,
+            principal,
+            context,
         )
         .await
     }
@@ -51,14 +80,31 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
     ///
     /// # Deduplication
     ///
-    /// If a non-deleted link for the same normalized URL and domain already exists,
-    /// returns the existing link instead of creating a duplicate.
+    /// Non-deleted links the caller can see for the same normalized URL and domain
+    /// are handled per `dedupe`:
+    ///
+    /// - [`DedupePolicy::Reuse`] - returns an existing link whose code (when `custom_code`
+    ///   is given), expiry and redirect type match the request, with `reused: true`;
+    ///   creates a new link if none matches
+    /// - [`DedupePolicy::AlwaysNew`] - always creates a new link
+    /// - [`DedupePolicy::Error`] - returns a conflict if any link exists
     ///
     /// # Code Generation
     ///
     /// - If `custom_code` is provided, validates and uses it (or returns conflict error)
     /// - Otherwise, generates a cryptographically secure random 12-character code
     /// - Retries up to 10 times on collision before failing
+    ///
+    /// The domain must belong to the caller's workspace; handlers check this with
+    /// [`Principal::require_domain`]. A new link is recorded in the audit log for
+    /// `context`; reusing a link is not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Forbidden`] if the workspace's link quota is used up;
+    /// the link repository checks it in the same transaction as the insert.
+    /// Reusing an existing link is always allowed.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_short_link_for_domain(
         &self,
         long_url: String,
@@ -66,17 +112,40 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
         custom_code: Option<String>,
         expires_at: Option<DateTime<Utc>>,
         permanent: bool,
-    ) -> Result<Link, AppError> {
+        dedupe: DedupePolicy,
+        principal: &Principal,
+        context: &AuditContext,
+    ) -> Result<CreatedLink, AppError> {
         let normalized_url = normalize_url(&long_url).map_err(|e| {
             AppError::bad_request("Invalid URL format", json!({ "reason": e.to_string() }))
         })?;
 
-        if let Some(existing_link) = self
-            .link_repository
-            .find_by_long_url(&normalized_url, domain_id)
-            .await?
-        {
-            return Ok(existing_link);
+        if dedupe != DedupePolicy::AlwaysNew {
+            let visibility = principal.visibility();
+            let existing: Vec<Link> = self
+                .link_repository
+                .find_by_long_url(&normalized_url, domain_id)
+                .await?
+                .into_iter()
+                .filter(|link| visibility.can_see(link))
+                .collect();
+
+            if dedupe == DedupePolicy::Error
+                && let Some(link) = existing.first()
+            {
+                return Err(AppError::conflict(
+                    "URL is already shortened for this domain",
+                    json!({ "url": normalized_url, "code": link.code, "domain_id": domain_id }),
+                ));
+            }
+
+            if let Some(link) = existing.into_iter().find(|l| {
+                custom_code.as_ref().is_none_or(|c| *c == l.code)
+                    && l.expires_at == expires_at
+                    && l.permanent == permanent
+            }) {
+                return Ok(CreatedLink { link, reused: true });
+            }
         }
 
         let code = if let Some(custom) = custom_code {
@@ -105,9 +174,16 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
             domain_id,
             expires_at,
             permanent,
+            owner_id: principal.user.map(|user| user.id),
+            team_id: principal.user.and_then(|user| user.team_id),
         };
 
-        self.link_repository.create(new_link).await
+        let link = self.link_repository.create(new_link, context).await?;
+
+        Ok(CreatedLink {
+            link,
+            reused: false,
+        })
     }
 
     /// Retrieves a link by its short code and domain.
@@ -129,6 +205,23 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
             })
     }
 
+    /// Retrieves a link the caller may see, including deleted and expired ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the link does not exist or is not
+    /// visible to the caller.
+    pub async fn get_visible_link(
+        &self,
+        code: &str,
+        domain_id: i64,
+        principal: &Principal,
+    ) -> Result<Link, AppError> {
+        self.visible_link(code, domain_id, principal)
+            .await?
+            .ok_or_else(|| AppError::not_found("Link not found", json!({ "code": code })))
+    }
+
     /// Constructs the full short URL from a domain and code.
     ///
     /// Always uses HTTPS protocol.
@@ -136,22 +229,111 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
         format!("https://{}/{}", domain.trim_end_matches('/'), code)
     }
 
-    /// Soft-deletes a link (sets `deleted_at`). Returns `false` if not found.
-    pub async fn soft_delete_link(&self, code: &str, domain_id: i64) -> Result<bool, AppError> {
-        self.link_repository.soft_delete(code, domain_id).await
+    /// Soft-deletes a link (sets `deleted_at`) and records it in the audit log
+    /// for `context`.
+    ///
+    /// Returns the link as it was before deletion, or `None` if it was not
+    /// found, already deleted or not visible to the caller.
+    pub async fn soft_delete_link(
+        &self,
+        code: &str,
+        domain_id: i64,
+        principal: &Principal,
+        context: &AuditContext,
+    ) -> Result<Option<Link>, AppError> {
+        let Some(link) = self.visible_link(code, domain_id, principal).await? else {
+            return Ok(None);
+        };
+
+        let deleted = self
+            .link_repository
+            .soft_delete(code, domain_id, context)
+            .await?;
+        Ok(deleted.then_some(link))
     }
 
     /// Partially updates a link.
     ///
     /// Only patch fields that are `Some` are modified. Set `patch.restore = true`
-    /// to restore a previously soft-deleted link.
+    /// to restore a previously soft-deleted link. The change is recorded in the
+    /// audit log for `context`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the link does not exist or is not
+    /// visible to the caller.
+    /// Returns [`AppError::Forbidden`] if restoring a deleted link would exceed the
+    /// workspace's link quota.
     pub async fn update_link(
         &self,
         code: &str,
         domain_id: i64,
         patch: LinkPatch,
+        principal: &Principal,
+        context: &AuditContext,
     ) -> Result<Link, AppError> {
-        self.link_repository.update(code, domain_id, patch).await
+        self.get_visible_link(code, domain_id, principal).await?;
+        self.link_repository
+            .update(code, domain_id, patch, context)
+            .await
+    }
+
+    /// Fails with [`AppError::Forbidden`] if the workspace's redirects this month
+    /// used up its click allowance.
+    ///
+    /// Checked on every redirect, including those served from the cache. The
+    /// server's workspace repository is a
+    /// [`WorkspaceCache`](crate::infrastructure::workspace_cache::WorkspaceCache), so
+    /// quotas and the month's clicks are read from memory most of the time.
+    pub async fn check_click_allowance(&self, workspace_id: i64) -> Result<(), AppError> {
+        let quotas = self.workspace_quotas(workspace_id).await?;
+        let Some(allowance) = quotas.monthly_click_allowance else {
+            return Ok(());
+        };
+
+        let clicks = self
+            .workspace_repository
+            .count_clicks_since(workspace_id, month_start(Utc::now()))
+            .await?;
+
+        if quotas.clicks_exhausted(clicks) {
+            return Err(AppError::forbidden(
+                "Workspace monthly click allowance exhausted",
+                json!({ "quota": "monthly_click_allowance", "limit": allowance }),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Loads a workspace's quotas.
+    async fn workspace_quotas(&self, workspace_id: i64) -> Result<WorkspaceQuotas, AppError> {
+        self.workspace_repository
+            .find_by_id(workspace_id)
+            .await?
+            .map(|workspace| workspace.quotas)
+            .ok_or_else(|| {
+                AppError::internal(
+                    "Workspace not found",
+                    json!({ "workspace_id": workspace_id }),
+                )
+            })
+    }
+
+    /// Returns the link if it exists and the caller may see it.
+    async fn visible_link(
+        &self,
+        code: &str,
+        domain_id: i64,
+        principal: &Principal,
+    ) -> Result<Option<Link>, AppError> {
+        let visibility = principal.visibility();
+
+        Ok(self
+            .link_repository
+            .find_by_code(code, domain_id)
+            .await?
+            .filter(|link| visibility.can_see(link)))
     }
 
     /// Generates a unique short code for a domain with collision retry.
@@ -181,10 +363,29 @@ impl<L: LinkRepository, D: DomainRepository> LinkService<L, D> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::Domain;
-    use crate::domain::repositories::{MockDomainRepository, MockLinkRepository};
+    use crate::domain::entities::{Actor, Domain, Role, Scope, UserRef, Workspace};
+    use crate::domain::repositories::{
+        MockDomainRepository, MockLinkRepository, MockWorkspaceRepository,
+    };
     use chrono::Utc;
 
+    /// Workspaces with `quotas`, each having `used` clicks this month.
+    fn workspaces(quotas: WorkspaceQuotas, used: i64) -> MockWorkspaceRepository {
+        let mut mock_repo = MockWorkspaceRepository::new();
+        mock_repo.expect_find_by_id().returning(move |id| {
+            Ok(Some(Workspace {
+                id,
+                name: "default".to_string(),
+                quotas,
+                created_at: Utc::now(),
+            }))
+        });
+        mock_repo
+            .expect_count_clicks_since()
+            .returning(move |_, _| Ok(used));
+        mock_repo
+    }
+
     fn create_test_domain(id: i64) -> Domain {
         Domain::new(
             id,
@@ -211,6 +412,32 @@ mod tests {
         )
     }
 
+    /// An API token not bound to a user: sees every link.
+    fn token() -> Principal {
+        Principal {
+            actor: Actor::Token {
+                id: 1,
+                name: "test".to_string(),
+            },
+            scopes: Scope::ALL.to_vec(),
+            domain_ids: None,
+            user: None,
+            workspace_id: 1,
+        }
+    }
+
+    /// An editor in team 9.
+    fn editor(id: i64) -> Principal {
+        Principal {
+            user: Some(UserRef {
+                id,
+                team_id: Some(9),
+                role: Role::Editor,
+            }),
+            ..token()
+        }
+    }
+
     #[tokio::test]
     async fn test_create_short_link_success() {
         let mut mock_link_repo = MockLinkRepository::new();
@@ -220,12 +447,12 @@ mod tests {
         mock_domain_repo
             .expect_get_default()
             .times(1)
-            .returning(move || Ok(domain.clone()));
+            .returning(move |_| Ok(domain.clone()));
 
         mock_link_repo
             .expect_find_by_long_url()
             .times(1)
-            .returning(|_, _| Ok(None));
+            .returning(|_, _| Ok(vec![]));
 
         mock_link_repo
             .expect_find_by_code()
@@ -236,17 +463,30 @@ mod tests {
         mock_link_repo
             .expect_create()
             .times(1)
-            .returning(move |_| Ok(created_link.clone()));
+            .returning(move |_, _| Ok(created_link.clone()));
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
-            .create_short_link("https://example.com".to_string(), None, None, false)
+            .create_short_link(
+                "https://example.com".to_string(),
+                None,
+                None,
+                false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_ok());
-        let link = result.unwrap();
-        assert_eq!(link.long_url, "https://example.com");
+        let created = result.unwrap();
+        assert_eq!(created.link.long_url, "https://example.com");
+        assert!(!created.reused);
     }
 
     #[tokio::test]
@@ -258,13 +498,13 @@ mod tests {
         mock_domain_repo
             .expect_get_default()
             .times(1)
-            .returning(move || Ok(domain.clone()));
+            .returning(move |_| Ok(domain.clone()));
 
         mock_link_repo
             .expect_find_by_long_url()
             .withf(|url, _| url == "https://example.com/path")
             .times(1)
-            .returning(|_, _| Ok(None));
+            .returning(|_, _| Ok(vec![]));
 
         mock_link_repo
             .expect_find_by_code()
@@ -275,9 +515,13 @@ mod tests {
         mock_link_repo
             .expect_create()
             .times(1)
-            .returning(move |_| Ok(created_link.clone()));
+            .returning(move |_, _| Ok(created_link.clone()));
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
             .create_short_link(
@@ -285,6 +529,9 @@ mod tests {
                 None,
                 None,
                 false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
             )
             .await;
 
@@ -300,26 +547,160 @@ mod tests {
         mock_domain_repo
             .expect_get_default()
             .times(1)
-            .returning(move || Ok(domain.clone()));
+            .returning(move |_| Ok(domain.clone()));
 
         let existing_link = create_test_link(5, "existing", "https://example.com", 1);
         mock_link_repo
             .expect_find_by_long_url()
             .times(1)
-            .returning(move |_, _| Ok(Some(existing_link.clone())));
+            .returning(move |_, _| Ok(vec![existing_link.clone()]));
 
         mock_link_repo.expect_create().times(0);
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
-            .create_short_link("https://example.com".to_string(), None, None, false)
+            .create_short_link(
+                "https://example.com".to_string(),
+                None,
+                None,
+                false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_ok());
-        let link = result.unwrap();
-        assert_eq!(link.id, 5);
-        assert_eq!(link.code, "existing");
+        let created = result.unwrap();
+        assert_eq!(created.link.id, 5);
+        assert_eq!(created.link.code, "existing");
+        assert!(created.reused);
+    }
+
+    #[tokio::test]
+    async fn test_create_short_link_reuse_skips_links_with_other_settings() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        let existing_link = create_test_link(5, "existing", "https://example.com", 1);
+        mock_link_repo
+            .expect_find_by_long_url()
+            .times(1)
+            .returning(move |_, _| Ok(vec![existing_link.clone()]));
+
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(|_, _| Ok(None));
+
+        let created_link = create_test_link(10, "abc123", "https://example.com", 1);
+        mock_link_repo
+            .expect_create()
+            .withf(|new_link, _| new_link.permanent)
+            .times(1)
+            .returning(move |_, _| Ok(created_link.clone()));
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let created = service
+            .create_short_link_for_domain(
+                "https://example.com".to_string(),
+                1,
+                None,
+                None,
+                true,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(created.link.id, 10);
+        assert!(!created.reused);
+    }
+
+    #[tokio::test]
+    async fn test_create_short_link_always_new_skips_lookup() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        mock_link_repo.expect_find_by_long_url().times(0);
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(|_, _| Ok(None));
+
+        let created_link = create_test_link(10, "abc123", "https://example.com", 1);
+        mock_link_repo
+            .expect_create()
+            .times(1)
+            .returning(move |_, _| Ok(created_link.clone()));
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let created = service
+            .create_short_link_for_domain(
+                "https://example.com".to_string(),
+                1,
+                None,
+                None,
+                false,
+                DedupePolicy::AlwaysNew,
+                &token(),
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert!(!created.reused);
+    }
+
+    #[tokio::test]
+    async fn test_create_short_link_error_policy_conflicts() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        let existing_link = create_test_link(5, "existing", "https://example.com", 1);
+        mock_link_repo
+            .expect_find_by_long_url()
+            .times(1)
+            .returning(move |_, _| Ok(vec![existing_link.clone()]));
+        mock_link_repo.expect_create().times(0);
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let result = service
+            .create_short_link_for_domain(
+                "https://example.com".to_string(),
+                1,
+                None,
+                None,
+                false,
+                DedupePolicy::Error,
+                &token(),
+                &AuditContext::cli(),
+            )
+            .await;
+
+        assert!(matches!(result.unwrap_err(), AppError::Conflict { .. }));
     }
 
     #[tokio::test]
@@ -327,10 +708,23 @@ mod tests {
         let mock_link_repo = MockLinkRepository::new();
         let mock_domain_repo = MockDomainRepository::new();
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
-            .create_short_link_for_domain("not-a-url".to_string(), 1, None, None, false)
+            .create_short_link_for_domain(
+                "not-a-url".to_string(),
+                1,
+                None,
+                None,
+                false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -346,12 +740,12 @@ mod tests {
         mock_domain_repo
             .expect_get_default()
             .times(1)
-            .returning(move || Ok(domain.clone()));
+            .returning(move |_| Ok(domain.clone()));
 
         mock_link_repo
             .expect_find_by_long_url()
             .times(1)
-            .returning(|_, _| Ok(None));
+            .returning(|_, _| Ok(vec![]));
 
         mock_link_repo
             .expect_find_by_code()
@@ -362,11 +756,15 @@ mod tests {
         let created_link = create_test_link(10, "mycode12", "https://example.com", 1);
         mock_link_repo
             .expect_create()
-            .withf(|new_link| new_link.code == "mycode12")
+            .withf(|new_link, _| new_link.code == "mycode12")
             .times(1)
-            .returning(move |_| Ok(created_link.clone()));
+            .returning(move |_, _| Ok(created_link.clone()));
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
             .create_short_link(
@@ -374,12 +772,15 @@ mod tests {
                 Some("mycode12".to_string()),
                 None,
                 false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
             )
             .await;
 
         assert!(result.is_ok());
-        let link = result.unwrap();
-        assert_eq!(link.code, "mycode12");
+        let created = result.unwrap();
+        assert_eq!(created.link.code, "mycode12");
     }
 
     #[tokio::test]
@@ -391,12 +792,12 @@ mod tests {
         mock_domain_repo
             .expect_get_default()
             .times(1)
-            .returning(move || Ok(domain.clone()));
+            .returning(move |_| Ok(domain.clone()));
 
         mock_link_repo
             .expect_find_by_long_url()
             .times(1)
-            .returning(|_, _| Ok(None));
+            .returning(|_, _| Ok(vec![]));
 
         let existing_link = create_test_link(5, "taken123", "https://other.com", 1);
         mock_link_repo
@@ -405,7 +806,11 @@ mod tests {
             .times(1)
             .returning(move |_, _| Ok(Some(existing_link.clone())));
 
-        let service = LinkService::new(Arc::new(mock_link_repo), Arc::new(mock_domain_repo));
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
 
         let result = service
             .create_short_link(
@@ -413,10 +818,191 @@ mod tests {
                 Some("taken123".to_string()),
                 None,
                 false,
+                DedupePolicy::Reuse,
+                &token(),
+                &AuditContext::cli(),
             )
             .await;
 
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::Conflict { .. }));
     }
+
+    #[tokio::test]
+    async fn test_create_short_link_sets_owner_and_team() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(|_, _| Ok(None));
+
+        let created_link = create_test_link(10, "abc123", "https://example.com", 1);
+        mock_link_repo
+            .expect_create()
+            .withf(|new_link, _| new_link.owner_id == Some(3) && new_link.team_id == Some(9))
+            .times(1)
+            .returning(move |_, _| Ok(created_link.clone()));
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let result = service
+            .create_short_link_for_domain(
+                "https://example.com".to_string(),
+                1,
+                None,
+                None,
+                false,
+                DedupePolicy::AlwaysNew,
+                &editor(3),
+                &AuditContext::cli(),
+            )
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_create_short_link_ignores_links_of_other_teams() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        let foreign =
+            create_test_link(5, "foreign", "https://example.com", 1).with_owner(Some(4), Some(8));
+        mock_link_repo
+            .expect_find_by_long_url()
+            .times(1)
+            .returning(move |_, _| Ok(vec![foreign.clone()]));
+
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(|_, _| Ok(None));
+
+        let created_link = create_test_link(10, "abc123", "https://example.com", 1);
+        mock_link_repo
+            .expect_create()
+            .times(1)
+            .returning(move |_, _| Ok(created_link.clone()));
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let created = service
+            .create_short_link_for_domain(
+                "https://example.com".to_string(),
+                1,
+                None,
+                None,
+                false,
+                DedupePolicy::Error,
+                &editor(3),
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(created.link.id, 10);
+        assert!(!created.reused);
+    }
+
+    #[tokio::test]
+    async fn test_update_link_of_other_team_is_not_found() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        let foreign =
+            create_test_link(5, "foreign", "https://example.com", 1).with_owner(Some(4), Some(8));
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(move |_, _| Ok(Some(foreign.clone())));
+        mock_link_repo.expect_update().times(0);
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let result = service
+            .update_link(
+                "foreign",
+                1,
+                LinkPatch {
+                    url: Some("https://example.org".to_string()),
+                    expires_at: None,
+                    permanent: None,
+                    restore: false,
+                    reverted_from: None,
+                },
+                &editor(3),
+                &AuditContext::cli(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_link_of_own_team() {
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mock_domain_repo = MockDomainRepository::new();
+
+        let teammate =
+            create_test_link(5, "team", "https://example.com", 1).with_owner(Some(4), Some(9));
+        mock_link_repo
+            .expect_find_by_code()
+            .times(1)
+            .returning(move |_, _| Ok(Some(teammate.clone())));
+        mock_link_repo
+            .expect_soft_delete()
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+
+        let service = LinkService::new(
+            Arc::new(mock_link_repo),
+            Arc::new(mock_domain_repo),
+            Arc::new(workspaces(WorkspaceQuotas::default(), 0)),
+        );
+
+        let deleted = service
+            .soft_delete_link("team", 1, &editor(3), &AuditContext::cli())
+            .await
+            .unwrap();
+        assert_eq!(deleted.map(|link| link.id), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_check_click_allowance() {
+        let quotas = WorkspaceQuotas {
+            monthly_click_allowance: Some(100),
+            ..Default::default()
+        };
+
+        let service = LinkService::new(
+            Arc::new(MockLinkRepository::new()),
+            Arc::new(MockDomainRepository::new()),
+            Arc::new(workspaces(quotas, 99)),
+        );
+        assert!(service.check_click_allowance(1).await.is_ok());
+
+        let service = LinkService::new(
+            Arc::new(MockLinkRepository::new()),
+            Arc::new(MockDomainRepository::new()),
+            Arc::new(workspaces(quotas, 100)),
+        );
+        assert!(matches!(
+            service.check_click_allowance(1).await,
+            Err(AppError::Forbidden { .. })
+        ));
+    }
 }
@@ -1,11 +1,23 @@
 //! Business logic services for the application layer.
 
+pub mod audit_service;
 pub mod auth_service;
+pub mod cache_admin_service;
 pub mod domain_service;
+pub mod idempotency_service;
+pub mod link_history_service;
 pub mod link_service;
+pub mod session_service;
+pub mod sso_service;
 pub mod stats_service;
 
-pub use auth_service::AuthService;
+pub use audit_service::AuditService;
+pub use auth_service::{AuthService, IssuedToken, generate_token, signing_key_id};
+pub use cache_admin_service::CacheAdminService;
 pub use domain_service::DomainService;
+pub use idempotency_service::IdempotencyService;
+pub use link_history_service::LinkHistoryService;
 pub use link_service::LinkService;
+pub use session_service::{SessionService, StartedSession};
+pub use sso_service::{PendingLogin, SsoPolicy, SsoService};
 pub use stats_service::StatsService;
@@ -0,0 +1,348 @@
+//! Dashboard login sessions.
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::application::services::generate_token;
+use crate::domain::repositories::{DashboardSession, SessionOwner, SessionRepository};
+use crate::error::AppError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Name of the HttpOnly cookie holding the signed session ID.
+pub const SESSION_COOKIE: &str = "session";
+
+/// Name of the cookie holding the CSRF token, readable by the dashboard scripts.
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+/// Header that must echo the CSRF token on state-changing requests.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Prefix of the signed message, so session signatures can never be confused
+/// with token hashes or other values signed with the same secret.
+const SIGNATURE_CONTEXT: &[u8] = b"dashboard-session:";
+
+/// A session created by [`SessionService::start`].
+#[derive(Debug, Clone)]
+pub struct StartedSession {
+    /// Value of the [`SESSION_COOKIE`]: `<session id>.<hex signature>`.
+    pub cookie: String,
+    /// Value of the [`CSRF_COOKIE`].
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Service for server-side dashboard sessions.
+///
+/// Logging in with an API token or through single sign-on yields a random
+/// session ID, stored in an HttpOnly cookie and signed with the token signing
+/// secrets; only the SHA-256 of the ID is stored. The session carries the
+/// token's or the user role's permissions and ends when it expires, the user
+/// logs out, or the token is revoked.
+///
+/// Each session has a CSRF token that state-changing requests authenticated by
+/// the session cookie must send in the [`CSRF_HEADER`].
+pub struct SessionService<R: SessionRepository> {
+    repository: Arc<R>,
+    /// Signing secrets, primary first. Never empty.
+    signing_secrets: Vec<String>,
+    ttl: Duration,
+}
+
+impl<R: SessionRepository> SessionService<R> {
+    /// Creates a new session service.
+    ///
+    /// # Arguments
+    ///
+    /// - `repository` - session repository for DB operations
+    /// - `signing_secrets` - HMAC keys for cookie signatures, newest (primary) first
+    /// - `ttl` - lifetime of a session
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signing_secrets` is empty.
+    pub fn new(repository: Arc<R>, signing_secrets: Vec<String>, ttl: Duration) -> Self {
+        assert!(
+            !signing_secrets.is_empty(),
+            "at least one session signing secret is required"
+        );
+
+        Self {
+            repository,
+            signing_secrets,
+            ttl,
+        }
+    }
+
+    /// Returns the lifetime of new sessions.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Starts a session for an authenticated token or single sign-on user.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn start(&self, owner: SessionOwner) -> Result<StartedSession, AppError> {
+        let session_id = generate_token();
+        let csrf_token = generate_token();
+        let expires_at = Utc::now() + self.ttl;
+
+        self.repository
+            .create_session(
+                &hash_session_id(&session_id),
+                &owner,
+                &csrf_token,
+                expires_at,
+            )
+            .await?;
+
+        Ok(StartedSession {
+            cookie: self.seal(SIGNATURE_CONTEXT, &session_id),
+            csrf_token,
+            expires_at,
+        })
+    }
+
+    /// Resolves a session cookie to its session.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Unauthorized`] if the signature is invalid, or the
+    /// session is unknown, expired, or belongs to a revoked or expired token.
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn authenticate(&self, cookie: &str) -> Result<DashboardSession, AppError> {
+        let session_id = self
+            .unseal(SIGNATURE_CONTEXT, cookie)
+            .ok_or_else(invalid_session)?;
+
+        self.repository
+            .find_session(&hash_session_id(session_id))
+            .await?
+            .ok_or_else(invalid_session)
+    }
+
+    /// Ends the session of a cookie. Unknown or invalid cookies are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn end(&self, cookie: &str) -> Result<(), AppError> {
+        match self.unseal(SIGNATURE_CONTEXT, cookie) {
+            Some(session_id) => {
+                self.repository
+                    .delete_session(&hash_session_id(session_id))
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Deletes expired sessions. Returns the number of removed sessions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn purge_expired(&self) -> Result<u64, AppError> {
+        self.repository.delete_expired().await
+    }
+
+    /// Signs `value` with the primary secret, returning `<value>.<hex signature>`.
+    ///
+    /// `context` separates values signed for different purposes.
+    pub fn seal(&self, context: &[u8], value: &str) -> String {
+        let signature = sign(&self.signing_secrets[0], context, value);
+        format!("{value}.{signature}")
+    }
+
+    /// Returns the value of a [`seal`](Self::seal)ed string whose signature
+    /// matches any signing secret.
+    pub fn unseal<'a>(&self, context: &[u8], sealed: &'a str) -> Option<&'a str> {
+        let (value, signature) = sealed.rsplit_once('.')?;
+        let signature = hex::decode(signature).ok()?;
+
+        self.signing_secrets
+            .iter()
+            .any(|secret| mac(secret, context, value).verify_slice(&signature).is_ok())
+            .then_some(value)
+    }
+}
+
+/// Fails with [`AppError::Forbidden`] unless `provided` matches the session's CSRF token.
+pub fn verify_csrf(session: &DashboardSession, provided: Option<&str>) -> Result<(), AppError> {
+    let valid =
+        provided.is_some_and(|p| constant_time_eq(p.as_bytes(), session.csrf_token.as_bytes()));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "CSRF token missing or invalid",
+            json!({ "header": CSRF_HEADER }),
+        ))
+    }
+}
+
+fn invalid_session() -> AppError {
+    AppError::unauthorized(
+        "Unauthorized",
+        json!({"reason": "Session is invalid or expired"}),
+    )
+}
+
+fn hash_session_id(session_id: &str) -> String {
+    hex::encode(Sha256::digest(session_id.as_bytes()))
+}
+
+fn mac(secret: &str, context: &[u8], value: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(context);
+    mac.update(value.as_bytes());
+    mac
+}
+
+fn sign(secret: &str, context: &[u8], value: &str) -> String {
+    hex::encode(mac(secret, context, value).finalize().into_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{Principal, Role, User};
+    use crate::domain::repositories::MockSessionRepository;
+
+    fn session(id_hash: String, csrf_token: &str) -> DashboardSession {
+        DashboardSession {
+            id_hash,
+            principal: Principal::for_user(&User {
+                id: 1,
+                email: Some("a@example.com".to_string()),
+                sso_subject: Some("sub".to_string()),
+                role: Role::Viewer,
+                team_id: None,
+                workspace_id: 1,
+                created_at: Utc::now(),
+            }),
+            csrf_token: csrf_token.to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+        }
+    }
+
+    fn service(
+        mock_repo: MockSessionRepository,
+        secrets: &[&str],
+    ) -> SessionService<MockSessionRepository> {
+        SessionService::new(
+            Arc::new(mock_repo),
+            secrets.iter().map(|s| s.to_string()).collect(),
+            Duration::hours(12),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_start_stores_hash_of_signed_session_id() {
+        let mut mock_repo = MockSessionRepository::new();
+        mock_repo
+            .expect_create_session()
+            .withf(|id_hash, owner, csrf_token, expires_at| {
+                id_hash.len() == 64
+                    && *owner == SessionOwner::Token(7)
+                    && !csrf_token.is_empty()
+                    && *expires_at > Utc::now() + Duration::hours(11)
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+
+        let started = service(mock_repo, &["secret"])
+            .start(SessionOwner::Token(7))
+            .await
+            .unwrap();
+
+        let (session_id, signature) = started.cookie.split_once('.').unwrap();
+        assert_eq!(signature, sign("secret", SIGNATURE_CONTEXT, session_id));
+        assert_ne!(started.csrf_token, session_id);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_accepts_cookie_signed_with_older_secret() {
+        let cookie = format!("abc.{}", sign("old", SIGNATURE_CONTEXT, "abc"));
+        let expected_hash = hash_session_id("abc");
+
+        let mut mock_repo = MockSessionRepository::new();
+        mock_repo
+            .expect_find_session()
+            .withf(move |id_hash| id_hash == expected_hash)
+            .times(1)
+            .returning(|id_hash| Ok(Some(session(id_hash.to_string(), "csrf"))));
+
+        let result = service(mock_repo, &["new", "old"])
+            .authenticate(&cookie)
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_rejects_tampered_cookie_without_lookup() {
+        let mut mock_repo = MockSessionRepository::new();
+        mock_repo.expect_find_session().times(0);
+        let service = service(mock_repo, &["secret"]);
+
+        let forged = format!("other.{}", sign("secret", SIGNATURE_CONTEXT, "abc"));
+        for cookie in ["abc", "abc.zz", "abc.00", forged.as_str()] {
+            assert!(matches!(
+                service.authenticate(cookie).await,
+                Err(AppError::Unauthorized { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_end_deletes_session() {
+        let expected_hash = hash_session_id("abc");
+
+        let mut mock_repo = MockSessionRepository::new();
+        mock_repo
+            .expect_delete_session()
+            .withf(move |id_hash| id_hash == expected_hash)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = service(mock_repo, &["secret"]);
+        service
+            .end(&format!("abc.{}", sign("secret", SIGNATURE_CONTEXT, "abc")))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_seal_is_bound_to_context() {
+        let service = service(MockSessionRepository::new(), &["new", "old"]);
+        let sealed = service.seal(b"a:", "value.with.dots");
+
+        assert_eq!(service.unseal(b"a:", &sealed), Some("value.with.dots"));
+        assert_eq!(service.unseal(b"b:", &sealed), None);
+    }
+
+    #[test]
+    fn test_verify_csrf() {
+        let session = session("hash".to_string(), "csrf-value");
+
+        assert!(verify_csrf(&session, Some("csrf-value")).is_ok());
+        assert!(matches!(
+            verify_csrf(&session, Some("csrf-other")),
+            Err(AppError::Forbidden { .. })
+        ));
+        assert!(verify_csrf(&session, None).is_err());
+    }
+}
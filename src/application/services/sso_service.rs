@@ -0,0 +1,459 @@
+//! Dashboard single sign-on through an OpenID Connect identity provider.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::application::services::generate_token;
+use crate::config::OidcConfig;
+use crate::domain::entities::Role;
+use crate::domain::identity_provider::{AuthorizationRequest, IdentityClaims, IdentityProvider};
+use crate::domain::repositories::{SessionOwner, UserRepository};
+use crate::error::AppError;
+
+/// How long a user may take at the identity provider before the login expires.
+pub const LOGIN_TIMEOUT: Duration = Duration::minutes(10);
+
+/// Who may sign in and with which role.
+#[derive(Debug, Clone)]
+pub struct SsoPolicy {
+    /// Lowercase email domains whose users may sign in; the email must be verified.
+    pub allowed_email_domains: Vec<String>,
+    /// Groups whose members may sign in.
+    pub allowed_groups: Vec<String>,
+    /// Group to role; a user gets the highest role of their groups.
+    pub role_mapping: Vec<(String, Role)>,
+    /// Role of allowed users matching no mapping.
+    pub default_role: Role,
+}
+
+impl From<&OidcConfig> for SsoPolicy {
+    fn from(config: &OidcConfig) -> Self {
+        Self {
+            allowed_email_domains: config.allowed_email_domains.clone(),
+            allowed_groups: config.allowed_groups.clone(),
+            role_mapping: config.role_mapping.clone(),
+            default_role: config.default_role,
+        }
+    }
+}
+
+impl SsoPolicy {
+    /// Returns the role of an allowed user.
+    ///
+    /// A user is allowed if their verified email is in an allowed domain or
+    /// they belong to an allowed group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Forbidden`] if the user is not allowed.
+    pub fn role_for(&self, claims: &IdentityClaims) -> Result<Role, AppError> {
+        let domain_allowed = claims
+            .email
+            .as_deref()
+            .filter(|_| claims.email_verified)
+            .and_then(|email| email.rsplit_once('@'))
+            .is_some_and(|(_, domain)| {
+                self.allowed_email_domains
+                    .contains(&domain.to_ascii_lowercase())
+            });
+        let group_allowed = claims
+            .groups
+            .iter()
+            .any(|group| self.allowed_groups.contains(group));
+
+        if !domain_allowed && !group_allowed {
+            return Err(AppError::forbidden(
+                "Your account is not allowed to use this dashboard",
+                json!({ "email": claims.email }),
+            ));
+        }
+
+        let role = self
+            .role_mapping
+            .iter()
+            .filter(|(group, _)| claims.groups.contains(group))
+            .map(|(_, role)| *role)
+            .max()
+            .unwrap_or(self.default_role);
+
+        Ok(role)
+    }
+}
+
+/// A login in progress, kept in a signed cookie until the provider redirects back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PendingLogin {
+    /// Serializes the login for the cookie: `state~nonce~verifier~expiry`.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}~{}~{}~{}",
+            self.state,
+            self.nonce,
+            self.code_verifier,
+            self.expires_at.timestamp()
+        )
+    }
+
+    /// Parses a value produced by [`encode`](Self::encode).
+    pub fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.split('~');
+        let (state, nonce, code_verifier, expires_at) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            state: state.to_string(),
+            nonce: nonce.to_string(),
+            code_verifier: code_verifier.to_string(),
+            expires_at: DateTime::from_timestamp(expires_at.parse().ok()?, 0)?,
+        })
+    }
+}
+
+/// Service for dashboard single sign-on.
+///
+/// Runs the OpenID Connect authorization code flow with PKCE: [`begin`](Self::begin)
+/// creates the state, nonce and code verifier and the URL to send the browser
+/// to; [`complete`](Self::complete) checks the state, redeems the code and maps
+/// the user to a dashboard [`Role`] through the [`SsoPolicy`]. The user is
+/// created on first sign-in, and their email and role are refreshed on every one.
+pub struct SsoService<P: IdentityProvider, U: UserRepository> {
+    provider: Arc<P>,
+    user_repository: Arc<U>,
+    policy: SsoPolicy,
+}
+
+impl<P: IdentityProvider, U: UserRepository> SsoService<P, U> {
+    /// Creates a new single sign-on service.
+    pub fn new(provider: Arc<P>, user_repository: Arc<U>, policy: SsoPolicy) -> Self {
+        Self {
+            provider,
+            user_repository,
+            policy,
+        }
+    }
+
+    /// Starts a login.
+    ///
+    /// Returns the identity provider URL to redirect the browser to, and the
+    /// login to keep until the callback.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] if the identity provider cannot be reached.
+    pub async fn begin(&self) -> Result<(String, PendingLogin), AppError> {
+        let pending = PendingLogin {
+            state: generate_token(),
+            nonce: generate_token(),
+            code_verifier: generate_token(),
+            expires_at: Utc::now() + LOGIN_TIMEOUT,
+        };
+
+        let url = self
+            .provider
+            .authorization_url(&AuthorizationRequest {
+                state: pending.state.clone(),
+                nonce: pending.nonce.clone(),
+                code_challenge: code_challenge(&pending.code_verifier),
+            })
+            .await?;
+
+        Ok((url, pending))
+    }
+
+    /// Finishes a login from the provider's callback and returns the session owner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Unauthorized`] if the login expired, the state does
+    /// not match, or the provider rejects the code or returns an invalid ID token.
+    /// Returns [`AppError::Forbidden`] if the user is not allowed to sign in.
+    /// Returns [`AppError::Conflict`] if another user already has the email.
+    /// Returns [`AppError::Internal`] if the identity provider cannot be reached.
+    pub async fn complete(
+        &self,
+        code: &str,
+        state: &str,
+        pending: &PendingLogin,
+    ) -> Result<SessionOwner, AppError> {
+        if pending.expires_at <= Utc::now() || state != pending.state {
+            return Err(AppError::unauthorized(
+                "Login expired or was started elsewhere",
+                json!({ "reason": "state mismatch" }),
+            ));
+        }
+
+        let claims = self
+            .provider
+            .exchange_code(code, &pending.code_verifier, &pending.nonce)
+            .await?;
+
+        let role = self.policy.role_for(&claims)?;
+        let email = claims.email.as_deref().filter(|_| claims.email_verified);
+
+        let user = self
+            .user_repository
+            .upsert_sso_user(&claims.subject, email, role)
+            .await?;
+
+        tracing::info!(subject = %claims.subject, user_id = user.id, %role, "Dashboard single sign-on");
+
+        Ok(SessionOwner::User(user.id))
+    }
+}
+
+/// Returns the PKCE `S256` challenge of a code verifier.
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::User;
+    use crate::domain::identity_provider::MockIdentityProvider;
+    use crate::domain::repositories::MockUserRepository;
+
+    fn policy() -> SsoPolicy {
+        SsoPolicy {
+            allowed_email_domains: vec!["example.com".to_string()],
+            allowed_groups: vec!["contractors".to_string()],
+            role_mapping: vec![
+                ("marketing".to_string(), Role::Editor),
+                ("sre".to_string(), Role::Admin),
+            ],
+            default_role: Role::Viewer,
+        }
+    }
+
+    fn claims(email: &str, verified: bool, groups: &[&str]) -> IdentityClaims {
+        IdentityClaims {
+            subject: "user-1".to_string(),
+            email: Some(email.to_string()),
+            email_verified: verified,
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    fn pending() -> PendingLogin {
+        PendingLogin {
+            state: "state".to_string(),
+            nonce: "nonce".to_string(),
+            code_verifier: "verifier".to_string(),
+            expires_at: Utc::now() + Duration::minutes(5),
+        }
+    }
+
+    #[test]
+    fn test_code_challenge_matches_rfc_7636_example() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_pending_login_round_trip() {
+        let login = PendingLogin {
+            expires_at: DateTime::from_timestamp(1_800_000_000, 0).unwrap(),
+            ..pending()
+        };
+
+        assert_eq!(PendingLogin::decode(&login.encode()), Some(login));
+        assert_eq!(PendingLogin::decode("a~b~c"), None);
+        assert_eq!(PendingLogin::decode("a~b~c~d"), None);
+    }
+
+    #[test]
+    fn test_policy_allows_verified_email_domain() {
+        let policy = policy();
+
+        assert_eq!(
+            policy
+                .role_for(&claims("ada@Example.com", true, &[]))
+                .unwrap(),
+            Role::Viewer
+        );
+        assert!(matches!(
+            policy.role_for(&claims("ada@example.com", false, &[])),
+            Err(AppError::Forbidden { .. })
+        ));
+        assert!(policy.role_for(&claims("ada@evil.com", true, &[])).is_err());
+        assert!(
+            policy
+                .role_for(&claims("ada@example.com.evil.com", true, &[]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_policy_allows_group_members() {
+        let role = policy()
+            .role_for(&claims("bob@agency.test", false, &["contractors"]))
+            .unwrap();
+
+        assert_eq!(role, Role::Viewer);
+    }
+
+    #[test]
+    fn test_policy_picks_highest_mapped_role() {
+        let policy = policy();
+
+        assert_eq!(
+            policy
+                .role_for(&claims("ada@example.com", true, &["marketing"]))
+                .unwrap(),
+            Role::Editor
+        );
+        assert_eq!(
+            policy
+                .role_for(&claims("ada@example.com", true, &["sre", "marketing"]))
+                .unwrap(),
+            Role::Admin
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_sends_s256_challenge_of_verifier() {
+        let mut provider = MockIdentityProvider::new();
+        provider
+            .expect_authorization_url()
+            .times(1)
+            .returning(|request| {
+                Ok(format!(
+                    "https://idp.test/auth?c={}",
+                    request.code_challenge
+                ))
+            });
+
+        let service = SsoService::new(
+            Arc::new(provider),
+            Arc::new(MockUserRepository::new()),
+            policy(),
+        );
+        let (url, pending) = service.begin().await.unwrap();
+
+        assert_eq!(
+            url,
+            format!(
+                "https://idp.test/auth?c={}",
+                code_challenge(&pending.code_verifier)
+            )
+        );
+        assert_ne!(pending.state, pending.nonce);
+        assert!(pending.expires_at > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_complete_rejects_state_mismatch_without_exchange() {
+        let mut provider = MockIdentityProvider::new();
+        provider.expect_exchange_code().times(0);
+
+        let service = SsoService::new(
+            Arc::new(provider),
+            Arc::new(MockUserRepository::new()),
+            policy(),
+        );
+        let result = service.complete("code", "other-state", &pending()).await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_complete_rejects_expired_login() {
+        let mut provider = MockIdentityProvider::new();
+        provider.expect_exchange_code().times(0);
+
+        let expired = PendingLogin {
+            expires_at: Utc::now() - Duration::seconds(1),
+            ..pending()
+        };
+        let service = SsoService::new(
+            Arc::new(provider),
+            Arc::new(MockUserRepository::new()),
+            policy(),
+        );
+
+        assert!(service.complete("code", "state", &expired).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_upserts_user_with_role() {
+        let mut provider = MockIdentityProvider::new();
+        provider
+            .expect_exchange_code()
+            .withf(|code, verifier, nonce| {
+                code == "code" && verifier == "verifier" && nonce == "nonce"
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(claims("ada@example.com", true, &["sre"])));
+
+        let mut users = MockUserRepository::new();
+        users
+            .expect_upsert_sso_user()
+            .withf(|subject, email, role| {
+                subject == "user-1" && *email == Some("ada@example.com") && *role == Role::Admin
+            })
+            .times(1)
+            .returning(|subject, email, role| {
+                Ok(User {
+                    id: 5,
+                    email: email.map(str::to_string),
+                    sso_subject: Some(subject.to_string()),
+                    role,
+                    team_id: None,
+                    workspace_id: 1,
+                    created_at: Utc::now(),
+                })
+            });
+
+        let service = SsoService::new(Arc::new(provider), Arc::new(users), policy());
+        let owner = service.complete("code", "state", &pending()).await.unwrap();
+
+        assert_eq!(owner, SessionOwner::User(5));
+    }
+
+    #[tokio::test]
+    async fn test_complete_drops_unverified_email() {
+        let mut provider = MockIdentityProvider::new();
+        provider
+            .expect_exchange_code()
+            .times(1)
+            .returning(|_, _, _| Ok(claims("bob@example.com", false, &["contractors"])));
+
+        let mut users = MockUserRepository::new();
+        users
+            .expect_upsert_sso_user()
+            .withf(|subject, email, _| subject == "user-1" && email.is_none())
+            .times(1)
+            .returning(|subject, _, role| {
+                Ok(User {
+                    id: 6,
+                    email: None,
+                    sso_subject: Some(subject.to_string()),
+                    role,
+                    team_id: None,
+                    workspace_id: 1,
+                    created_at: Utc::now(),
+                })
+            });
+
+        let service = SsoService::new(Arc::new(provider), Arc::new(users), policy());
+        let owner = service.complete("code", "state", &pending()).await.unwrap();
+
+        assert_eq!(owner, SessionOwner::User(6));
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use crate::domain::entities::{Click, NewClick};
+use crate::domain::entities::{Click, NewClick, Visibility};
 use crate::domain::repositories::{DetailedStats, LinkStats, StatsFilter, StatsRepository};
 use crate::error::AppError;
 use serde_json::json;
@@ -46,6 +46,7 @@ impl<R: StatsRepository> StatsService<R> {
             user_agent,
             referer,
             ip,
+            clicked_at: None,
         };
 
         self.repository.record_click(new_click).await
@@ -83,15 +84,21 @@ impl<R: StatsRepository> StatsService<R> {
         self.repository.get_all_stats(filter).await
     }
 
-    /// Counts the total number of links in the system.
+    /// Counts the links the caller may see, optionally limited to a set of domains.
     ///
     /// Used for pagination metadata.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    pub async fn count_all_links(&self) -> Result<i64, AppError> {
-        self.repository.count_all_links().await
+    pub async fn count_all_links(
+        &self,
+        domain_ids: Option<Vec<i64>>,
+        visibility: &Visibility,
+    ) -> Result<i64, AppError> {
+        self.repository
+            .count_all_links(domain_ids, visibility)
+            .await
     }
 }
 
@@ -131,7 +138,7 @@ mod tests {
 
         let service = StatsService::new(Arc::new(mock_repo));
 
-        let filter = StatsFilter::new(0, 10);
+        let filter = StatsFilter::new(0, 10, Visibility::All);
         let result = service.get_detailed_stats("abc123", filter).await;
 
         assert!(result.is_ok());
@@ -151,7 +158,7 @@ mod tests {
 
         let service = StatsService::new(Arc::new(mock_repo));
 
-        let filter = StatsFilter::new(0, 10);
+        let filter = StatsFilter::new(0, 10, Visibility::All);
         let result = service.get_detailed_stats("notfound", filter).await;
 
         assert!(result.is_err());
@@ -188,7 +195,7 @@ mod tests {
 
         let service = StatsService::new(Arc::new(mock_repo));
 
-        let filter = StatsFilter::new(0, 10);
+        let filter = StatsFilter::new(0, 10, Visibility::All);
         let result = service.get_all_stats(filter).await;
 
         assert!(result.is_ok());
@@ -205,11 +212,11 @@ mod tests {
         mock_repo
             .expect_count_all_links()
             .times(1)
-            .returning(|| Ok(42));
+            .returning(|_, _| Ok(42));
 
         let service = StatsService::new(Arc::new(mock_repo));
 
-        let result = service.count_all_links().await;
+        let result = service.count_all_links(None, &Visibility::All).await;
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 42);
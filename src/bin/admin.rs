@@ -9,15 +9,50 @@
 //! # Create a new API token
 //! cargo run --bin admin -- token create
 //!
+//! # Create a read-only token limited to one domain
+//! cargo run --bin admin -- token create --scope stats:read --domain s.example.com
+//!
 //! # List all tokens
 //! cargo run --bin admin -- token list
 //!
+//! # Replace a token; the old one keeps working for 48 hours
+//! cargo run --bin admin -- token rotate "Production API" --grace-hours 48
+//!
 //! # Revoke a token
 //! cargo run --bin admin -- token revoke "Production API"
 //!
+//! # Show tokens still hashed with an old signing secret
+//! cargo run --bin admin -- token migrate-hashes
+//!
+//! # Create a team and an editor in it, and a token acting for them
+//! cargo run --bin admin -- team create marketing
+//! cargo run --bin admin -- user create ada@example.com --role editor --team marketing
+//! cargo run --bin admin -- token create --user ada@example.com
+//!
+//! # Create a workspace with quotas, a token in it, and show usage
+//! cargo run --bin admin -- workspace create acme --max-links 1000 --max-domains 2
+//! cargo run --bin admin -- token create --workspace acme
+//! cargo run --bin admin -- workspace list
+//! cargo run --bin admin -- workspace set-quota acme --monthly-clicks 100000
+//!
 //! # View statistics
 //! cargo run --bin admin -- stats
 //!
+//! # Recount the click counters of every link from the recorded clicks
+//! cargo run --bin admin -- stats reconcile
+//!
+//! # Show token changes made since the start of October
+//! cargo run --bin admin -- audit --entity token --from 2026-10-01
+//!
+//! # Cache the 500 links clicked most in the last 7 days, e.g. after a Redis flush
+//! cargo run --bin admin -- cache warm --links 500
+//!
+//! # Show and purge the cached redirect of a link, purge a domain, show cache stats
+//! cargo run --bin admin -- cache inspect s.example.com abc123
+//! cargo run --bin admin -- cache purge s.example.com abc123
+//! cargo run --bin admin -- cache purge-domain s.example.com
+//! cargo run --bin admin -- cache stats
+//!
 //! # Check database connection
 //! cargo run --bin admin -- db check
 //! ```
@@ -25,28 +60,53 @@
 //! # Environment Variables
 //!
 //! - `DATABASE_URL` (required): PostgreSQL connection string
+//! - `TOKEN_SIGNING_SECRET` (required): token signing secrets, comma-separated, newest first
+//! - `REDIS_URL` / `REDIS_HOST` (required by `cache` commands): Redis connection
 //!
 //! # Features
 //!
-//! - **Token Management**: Create, list, and revoke API tokens
-//! - **Statistics**: View link and click counts
+//! - **Token Management**: Create, list, rotate, and revoke API tokens
+//! - **Users and Teams**: Create users, set their role and team
+//! - **Workspaces**: Create workspaces, set their quotas and show their usage
+//! - **Audit Log**: Show changes to links, domains and tokens; token changes made
+//!   here are recorded as `admin-cli`
+//! - **Statistics**: View link and click counts; recount the per-link click counters
+//! - **Cache**: Warm up the redirect cache, inspect and purge entries, show statistics;
+//!   purges are published so every server instance evicts its in-process copy
 //! - **Database Tools**: Connection checks and info queries
 //! - **Interactive Prompts**: User-friendly CLI with confirmation dialogs
 //! - **Colored Output**: Terminal-friendly formatting using `colored` crate
 
-use url_shortener::domain::repositories::TokenRepository;
-use url_shortener::infrastructure::persistence::PgTokenRepository;
+use url_shortener::application::services::{
+    AuditService, AuthService, CacheAdminService, generate_token,
+};
+use url_shortener::config::{load_from_env, parse_signing_secrets};
+use url_shortener::domain::entities::{
+    AuditContext, AuditEntity, DEFAULT_WORKSPACE_ID, Role, Scope, User, Workspace, WorkspaceQuotas,
+    month_start,
+};
+use url_shortener::domain::repositories::{
+    ApiToken, AuditFilter, DomainRepository, StatsRepository, UserRepository, WorkspaceRepository,
+};
+use url_shortener::infrastructure::cache::{CachedRedirect, InvalidationBus, RedisCache};
+use url_shortener::infrastructure::persistence::{
+    PgAuditRepository, PgDomainRepository, PgStatsRepository, PgTokenRepository, PgUserRepository,
+    PgWorkspaceRepository,
+};
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
 use colored::*;
 use dialoguer::{Confirm, Input};
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
 use sqlx::PgPool;
 use std::sync::Arc;
 
-type HmacSha256 = Hmac<Sha256>;
+type TokenService = AuthService<PgTokenRepository>;
+type AuditLog = AuditService<PgAuditRepository>;
+
+/// Tokens expiring within this many days are flagged in `token list`.
+const EXPIRY_WARNING_DAYS: i64 = 7;
 
 /// CLI tool for managing url-shortener.
 #[derive(Parser)]
@@ -66,8 +126,66 @@ enum Commands {
         action: TokenAction,
     },
 
+    /// Manage users
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+
+    /// Manage teams
+    Team {
+        #[command(subcommand)]
+        action: TeamAction,
+    },
+
+    /// Manage workspaces and their quotas
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+
     /// Show statistics
-    Stats,
+    Stats {
+        #[command(subcommand)]
+        action: Option<StatsAction>,
+    },
+
+    /// Show changes to links, domains and tokens, newest first
+    Audit {
+        /// Only show changes to this kind of record: link, domain or token
+        #[arg(long, value_parser = parse_entity)]
+        entity: Option<AuditEntity>,
+
+        /// Only show changes by this token name, token ID or user email
+        #[arg(long)]
+        actor: Option<String>,
+
+        /// Only show changes at or after this time (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_from)]
+        from: Option<DateTime<Utc>>,
+
+        /// Only show changes at or before this time (YYYY-MM-DD includes the whole day)
+        #[arg(long, value_parser = parse_to)]
+        to: Option<DateTime<Utc>>,
+
+        /// Workspace name (every workspace if omitted)
+        #[arg(long)]
+        workspace: Option<String>,
+
+        /// Maximum number of changes to show
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+
+        /// Also print the values before and after each change
+        #[arg(long)]
+        values: bool,
+    },
+
+    /// Warm up, inspect and purge the redirect cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
 
     /// Database operations
     Db {
@@ -89,21 +207,234 @@ enum TokenAction {
         #[arg(short, long)]
         token: Option<String>,
 
+        /// Scope to grant (repeatable; all scopes if omitted)
+        #[arg(long = "scope", value_parser = parse_scope)]
+        scopes: Vec<Scope>,
+
+        /// Limit the token to a domain (repeatable; all domains if omitted)
+        #[arg(long = "domain")]
+        domains: Vec<String>,
+
+        /// Expire the token after this many days (never expires if omitted)
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+
+        /// Email of the user the token acts for (sees every link of the workspace if omitted)
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Workspace name (the user's workspace, or the default one, if omitted)
+        #[arg(long)]
+        workspace: Option<String>,
+
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
     },
 
-    /// List all tokens
+    /// List all tokens of every workspace
     List,
 
+    /// Issue a replacement token; the old one keeps working during a grace period
+    Rotate {
+        /// Token name or ID to rotate
+        name_or_id: String,
+
+        /// Hours the old token keeps working
+        #[arg(long, default_value_t = 24)]
+        grace_hours: u32,
+
+        /// Expire the new token after this many days (never expires if omitted)
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
     /// Revoke a token
     Revoke {
         /// Token name or ID to revoke
         name_or_hash: String,
     },
+
+    /// Report tokens not yet re-hashed with the primary signing secret
+    ///
+    /// Tokens are re-hashed automatically when they are used. Once every active
+    /// token uses the primary secret, older secrets can be removed.
+    MigrateHashes {
+        /// Revoke active tokens still hashed with an older or unknown secret
+        #[arg(long)]
+        revoke_remaining: bool,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
 }
 
+/// User management subcommands.
+#[derive(Subcommand)]
+enum UserAction {
+    /// Create a user, e.g. to bind tokens to them before they sign in
+    Create {
+        /// Email; single sign-on claims the user by this email
+        email: String,
+
+        /// Role: viewer, editor or admin
+        #[arg(long, default_value = "viewer", value_parser = parse_role)]
+        role: Role,
+
+        /// Team name
+        #[arg(long)]
+        team: Option<String>,
+
+        /// Workspace name
+        #[arg(long, default_value = "default")]
+        workspace: String,
+    },
+
+    /// List all users
+    List,
+
+    /// Change a user's role (not for single sign-on users, whose role comes from the provider)
+    SetRole {
+        /// User email
+        email: String,
+
+        /// Role: viewer, editor or admin
+        #[arg(value_parser = parse_role)]
+        role: Role,
+    },
+
+    /// Move a user to a team; existing links stay with their team
+    SetTeam {
+        /// User email
+        email: String,
+
+        /// Team name (removes the user from their team if omitted)
+        team: Option<String>,
+    },
+}
+
+/// Team management subcommands.
+#[derive(Subcommand)]
+enum TeamAction {
+    /// Create a team
+    Create {
+        /// Team name, unique within the workspace
+        name: String,
+
+        /// Workspace name
+        #[arg(long, default_value = "default")]
+        workspace: String,
+    },
+
+    /// List all teams
+    List,
+}
+
+/// Workspace management subcommands.
+#[derive(Subcommand)]
+enum WorkspaceAction {
+    /// Create a workspace (quotas are unlimited if omitted)
+    Create {
+        /// Workspace name
+        name: String,
+
+        /// Maximum number of links
+        #[arg(long)]
+        max_links: Option<i64>,
+
+        /// Maximum number of custom domains
+        #[arg(long)]
+        max_domains: Option<i64>,
+
+        /// Redirects allowed per calendar month (UTC)
+        #[arg(long)]
+        monthly_clicks: Option<i64>,
+    },
+
+    /// List all workspaces with their quotas and usage
+    List,
+
+    /// Change a workspace's quotas; omitted quotas are kept, "unlimited" removes one
+    SetQuota {
+        /// Workspace name
+        name: String,
+
+        /// Maximum number of links
+        #[arg(long, value_parser = parse_limit)]
+        max_links: Option<Limit>,
+
+        /// Maximum number of custom domains
+        #[arg(long, value_parser = parse_limit)]
+        max_domains: Option<Limit>,
+
+        /// Redirects allowed per calendar month (UTC)
+        #[arg(long, value_parser = parse_limit)]
+        monthly_clicks: Option<Limit>,
+    },
+}
+
+/// Redirect cache subcommands.
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Cache the links clicked most recently
+    Warm {
+        /// Number of links to cache (CACHE_WARMUP_LINKS, or 1000, if omitted)
+        #[arg(long)]
+        links: Option<u64>,
+
+        /// Rank links by their clicks in this many days (CACHE_WARMUP_DAYS if omitted)
+        #[arg(long)]
+        days: Option<u64>,
+    },
+
+    /// Show the cached redirect of a short code
+    Inspect {
+        /// Domain of the short link
+        domain: String,
+
+        /// Short code
+        code: String,
+    },
+
+    /// Remove the cached redirect of a short code
+    Purge {
+        /// Domain of the short link
+        domain: String,
+
+        /// Short code
+        code: String,
+    },
+
+    /// Remove the cached redirects of every code on a domain
+    PurgeDomain {
+        /// Domain to purge
+        domain: String,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Show key counts, hits and memory use
+    Stats,
+}
+
+/// Statistics subcommands; `stats` alone shows the totals.
+#[derive(Subcommand)]
+enum StatsAction {
+    /// Recount the click counters of every link from the recorded clicks
+    Reconcile,
+}
+
+/// A quota given on the command line; `None` is unlimited.
+#[derive(Clone, Copy)]
+struct Limit(Option<i64>);
+
 /// Database operation subcommands.
 #[derive(Subcommand)]
 enum DbAction {
@@ -123,16 +454,51 @@ async fn main() -> Result<()> {
 
     // Connect to database
     let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
-    let signing_secret =
-        std::env::var("TOKEN_SIGNING_SECRET").context("TOKEN_SIGNING_SECRET must be set")?;
+    let signing_secrets = parse_signing_secrets(
+        &std::env::var("TOKEN_SIGNING_SECRET").context("TOKEN_SIGNING_SECRET must be set")?,
+    );
+    if signing_secrets.is_empty() {
+        anyhow::bail!("TOKEN_SIGNING_SECRET must not be empty");
+    }
 
     let pool = PgPool::connect(&database_url)
         .await
         .context("Failed to connect to database")?;
 
     match cli.command {
-        Commands::Token { action } => handle_token_action(action, &pool, &signing_secret).await?,
-        Commands::Stats => handle_stats(&pool).await?,
+        Commands::Token { action } => handle_token_action(action, &pool, signing_secrets).await?,
+        Commands::User { action } => handle_user_action(action, &pool).await?,
+        Commands::Team { action } => handle_team_action(action, &pool).await?,
+        Commands::Workspace { action } => handle_workspace_action(action, &pool).await?,
+        Commands::Stats { action: None } => handle_stats(&pool).await?,
+        Commands::Stats {
+            action: Some(StatsAction::Reconcile),
+        } => reconcile_click_counters(&pool).await?,
+        Commands::Audit {
+            entity,
+            actor,
+            from,
+            to,
+            workspace,
+            limit,
+            values,
+        } => {
+            let workspace_id = match workspace {
+                Some(name) => Some(
+                    find_workspace(&workspace_repository(&pool), &name)
+                        .await?
+                        .id,
+                ),
+                None => None,
+            };
+            let filter = AuditFilter::new(0, limit)
+                .with_workspace(workspace_id)
+                .with_entity(entity)
+                .with_actor(actor)
+                .with_date_range(from, to);
+            show_audit(&audit_log(&pool), filter, values).await?;
+        }
+        Commands::Cache { action } => handle_cache_action(action, pool).await?,
         Commands::Db { action } => handle_db_action(action, &pool).await?,
     }
 
@@ -140,18 +506,84 @@ async fn main() -> Result<()> {
 }
 
 /// Dispatches token management commands.
-async fn handle_token_action(action: TokenAction, pool: &PgPool, secret: &str) -> Result<()> {
+async fn handle_token_action(
+    action: TokenAction,
+    pool: &PgPool,
+    secrets: Vec<String>,
+) -> Result<()> {
     let repo = Arc::new(PgTokenRepository::new(Arc::new(pool.clone())));
+    let service = AuthService::new(repo, secrets);
 
     match action {
-        TokenAction::Create { name, token, yes } => {
-            create_token(repo, name, token, yes, secret).await?;
+        TokenAction::Create {
+            name,
+            token,
+            scopes,
+            domains,
+            expires_in_days,
+            user,
+            workspace,
+            yes,
+        } => {
+            let scopes = if scopes.is_empty() {
+                Scope::ALL.to_vec()
+            } else {
+                scopes
+            };
+            let expires_at = expiry_from_days(expires_in_days);
+            let user = match user {
+                Some(email) => Some(find_user(&user_repository(pool), &email).await?),
+                None => None,
+            };
+            let workspace_id = match (workspace, &user) {
+                (Some(name), user) => {
+                    let workspace = find_workspace(&workspace_repository(pool), &name).await?;
+                    if user
+                        .as_ref()
+                        .is_some_and(|u| u.workspace_id != workspace.id)
+                    {
+                        anyhow::bail!("User is not in workspace '{}'", name);
+                    }
+                    workspace.id
+                }
+                (None, Some(user)) => user.workspace_id,
+                (None, None) => DEFAULT_WORKSPACE_ID,
+            };
+            let domain_ids = resolve_domains(pool, &domains, workspace_id).await?;
+            create_token(
+                &service,
+                name,
+                token,
+                scopes,
+                domain_ids,
+                expires_at,
+                user,
+                workspace_id,
+                yes,
+            )
+            .await?;
         }
         TokenAction::List => {
-            list_tokens(repo).await?;
+            list_tokens(&service).await?;
+        }
+        TokenAction::Rotate {
+            name_or_id,
+            grace_hours,
+            expires_in_days,
+            yes,
+        } => {
+            let grace_until = Utc::now() + Duration::hours(grace_hours.into());
+            let expires_at = expiry_from_days(expires_in_days);
+            rotate_token(&service, name_or_id, grace_until, expires_at, yes).await?;
         }
         TokenAction::Revoke { name_or_hash } => {
-            revoke_token(repo, name_or_hash).await?;
+            revoke_token(&service, name_or_hash).await?;
+        }
+        TokenAction::MigrateHashes {
+            revoke_remaining,
+            yes,
+        } => {
+            migrate_hashes(&service, revoke_remaining, yes).await?;
         }
     }
 
@@ -166,21 +598,27 @@ async fn handle_token_action(action: TokenAction, pool: &PgPool, secret: &str) -
 /// 2. Generate random token or use provided value
 /// 3. Display token details with warning
 /// 4. Confirm creation (unless `--yes` flag)
-/// 5. Hash token with SHA-256
-/// 6. Store in database
-/// 7. Display usage instructions
+/// 5. Hash token with HMAC-SHA256 and store it via [`AuthService::create_token`],
+///    recording it in the audit log
+/// 6. Display usage instructions
 ///
 /// # Security
 ///
-/// - Only the SHA-256 hash is stored in the database
+/// - Only the HMAC-SHA256 hash is stored in the database
 /// - Raw token is displayed once and cannot be retrieved later
 /// - Tokens are 48 characters (alphanumeric) for high entropy
+/// - A token bound to a user never has more scopes than the user's role
+#[allow(clippy::too_many_arguments)]
 async fn create_token(
-    repo: Arc<PgTokenRepository>,
+    service: &TokenService,
     name: Option<String>,
     token: Option<String>,
+    scopes: Vec<Scope>,
+    domain_ids: Option<Vec<i64>>,
+    expires_at: Option<DateTime<Utc>>,
+    user: Option<User>,
+    workspace_id: i64,
     skip_confirm: bool,
-    secret: &str,
 ) -> Result<()> {
     println!("{}", "🔑 Create API Token".bright_blue().bold());
     println!();
@@ -212,6 +650,16 @@ async fn create_token(
     println!("{}", "Token details:".bright_white().bold());
     println!("  Name:  {}", token_name.cyan());
     println!("  Token: {}", token_value.bright_yellow().bold());
+    println!("  Scopes: {}", format_scopes(&scopes).cyan());
+    println!(
+        "  Domains: {}",
+        format_domain_ids(domain_ids.as_deref()).cyan()
+    );
+    println!("  Expires: {}", format_expiry(expires_at).cyan());
+    println!("  Workspace: {}", workspace_id.to_string().cyan());
+    if let Some(user) = &user {
+        println!("  User:    {} ({})", user_label(user).cyan(), user.role);
+    }
     println!();
     println!(
         "{}",
@@ -234,11 +682,18 @@ async fn create_token(
         }
     }
 
-    // Hash token
-    let token_hash = hash_token(&token_value, secret);
-
-    // Save to database
-    repo.create_token(&token_name, &token_hash)
+    // Hash and save to database
+    service
+        .create_token(
+            &token_name,
+            Some(token_value.clone()),
+            scopes,
+            domain_ids,
+            expires_at,
+            user.map(|u| u.id),
+            workspace_id,
+            &AuditContext::cli(),
+        )
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create token: {}", e))?;
 
@@ -269,17 +724,23 @@ async fn create_token(
 /// ```text
 /// 📋 API Tokens
 ///
-///   ID  Name                           Created              Status
-///   ─────────────────────────────────────────────────────────────────────────
-///   1   Production API                 2024-01-15 10:30     ACTIVE
-///   2   Mobile App                     2024-01-16 14:20     REVOKED
+///   ID  Name                           Created              Expires              Status
+///   ─────────────────────────────────────────────────────────────────────────────────────────
+///   3   Production API                 2024-02-01 09:00     never                ACTIVE
+///   1   Production API                 2024-01-15 10:30     2024-02-02 09:00     EXPIRING
+///   2   Mobile App                     2024-01-16 14:20     never                REVOKED
+///
+///   Total: 3
+///   ⚠️  1 token(s) expire within 7 days
 /// ```
-async fn list_tokens(repo: Arc<PgTokenRepository>) -> Result<()> {
+///
+/// Workspace IDs, scopes and domain restrictions are shown in additional columns.
+async fn list_tokens(service: &TokenService) -> Result<()> {
     println!("{}", "📋 API Tokens".bright_blue().bold());
     println!();
 
-    let tokens = repo
-        .list_tokens()
+    let tokens = service
+        .list_tokens(None)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to list tokens: {}", e))?;
 
@@ -294,23 +755,36 @@ async fn list_tokens(repo: Arc<PgTokenRepository>) -> Result<()> {
     }
 
     println!(
-        "  {:<3} {:<30} {:<20} {:<10}",
+        "  {:<3} {:<30} {:<20} {:<20} {:<10} {:<4} {:<50} {}",
         "ID".bright_white().bold(),
         "Name".bright_white().bold(),
         "Created".bright_white().bold(),
-        "Status".bright_white().bold()
+        "Expires".bright_white().bold(),
+        "Status".bright_white().bold(),
+        "WS".bright_white().bold(),
+        "Scopes".bright_white().bold(),
+        "Domains".bright_white().bold()
     );
-    println!("  {}", "─".repeat(75).bright_black());
+    println!("  {}", "─".repeat(161).bright_black());
+
+    let now = Utc::now();
+    let warn_before = now + Duration::days(EXPIRY_WARNING_DAYS);
+    let mut expiring = 0;
 
     for token in &tokens {
         let status = if token.revoked_at.is_some() {
             "REVOKED".red()
+        } else if token.is_expired(now) {
+            "EXPIRED".red()
+        } else if token.expires_at.is_some_and(|at| at <= warn_before) {
+            expiring += 1;
+            "EXPIRING".yellow()
         } else {
             "ACTIVE".green()
         };
 
         println!(
-            "  {:<3} {:<30} {:<20} {}",
+            "  {:<3} {:<30} {:<20} {:<20} {:<10} {:<4} {:<50} {}",
             token.id.to_string().bright_black(),
             token.name.cyan(),
             token
@@ -318,7 +792,11 @@ async fn list_tokens(repo: Arc<PgTokenRepository>) -> Result<()> {
                 .format("%Y-%m-%d %H:%M")
                 .to_string()
                 .bright_black(),
-            status
+            format_expiry(token.expires_at).bright_black(),
+            status,
+            token.workspace_id,
+            format_scopes(&token.scopes),
+            format_domain_ids(token.domain_ids.as_deref())
         );
     }
 
@@ -327,6 +805,12 @@ async fn list_tokens(repo: Arc<PgTokenRepository>) -> Result<()> {
         "  Total: {}",
         tokens.len().to_string().bright_white().bold()
     );
+    if expiring > 0 {
+        println!(
+            "  {}",
+            format!("⚠️  {expiring} token(s) expire within {EXPIRY_WARNING_DAYS} days").yellow()
+        );
+    }
     println!();
 
     Ok(())
@@ -343,23 +827,11 @@ async fn list_tokens(repo: Arc<PgTokenRepository>) -> Result<()> {
 ///
 /// - Requires confirmation (default: No)
 /// - Prevents double-revocation
-async fn revoke_token(repo: Arc<PgTokenRepository>, name_or_hash: String) -> Result<()> {
+async fn revoke_token(service: &TokenService, name_or_hash: String) -> Result<()> {
     println!("{}", "🔒 Revoke API Token".bright_blue().bold());
     println!();
 
-    // Try to find by name or ID
-    let token = match name_or_hash.parse::<i64>() {
-        Ok(id) => repo
-            .find_by_id(id)
-            .await
-            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?,
-        Err(_) => repo
-            .find_by_name(&name_or_hash)
-            .await
-            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?,
-    };
-
-    let token = token.context("Token not found")?;
+    let token = find_token(service, &name_or_hash).await?;
 
     if token.revoked_at.is_some() {
         println!("{}", "⚠️  This token is already revoked".yellow());
@@ -380,7 +852,8 @@ async fn revoke_token(repo: Arc<PgTokenRepository>, name_or_hash: String) -> Res
         return Ok(());
     }
 
-    repo.revoke_token(token.id)
+    service
+        .revoke_token(token.id, None, &AuditContext::cli())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to revoke token: {}", e))?;
 
@@ -391,6 +864,576 @@ async fn revoke_token(repo: Arc<PgTokenRepository>, name_or_hash: String) -> Res
     Ok(())
 }
 
+/// Replaces a token, keeping the old one valid until `grace_until`.
+///
+/// # Flow
+///
+/// 1. Look up the token by name or ID
+/// 2. Generate the replacement token
+/// 3. Confirm rotation (unless `--yes` flag)
+/// 4. Store the replacement with the same name, scopes and domains, and
+///    shorten the old token's expiry to the end of the grace period, recording
+///    both in the audit log
+/// 5. Display the new token once
+async fn rotate_token(
+    service: &TokenService,
+    name_or_id: String,
+    grace_until: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    skip_confirm: bool,
+) -> Result<()> {
+    println!("{}", "🔄 Rotate API Token".bright_blue().bold());
+    println!();
+
+    let token = find_token(service, &name_or_id).await?;
+
+    if token.revoked_at.is_some() || token.is_expired(Utc::now()) {
+        println!(
+            "{}",
+            "⚠️  This token is revoked or expired; create a new one instead".yellow()
+        );
+        return Ok(());
+    }
+
+    println!("  Token:       {}", token.name.cyan());
+    println!("  ID:          {}", token.id.to_string().bright_black());
+    println!(
+        "  Old expires: {}",
+        format_expiry(Some(
+            token
+                .expires_at
+                .map_or(grace_until, |at| at.min(grace_until))
+        ))
+        .cyan()
+    );
+    println!("  New expires: {}", format_expiry(expires_at).cyan());
+    println!();
+
+    if !skip_confirm {
+        let confirmed = Confirm::new()
+            .with_prompt("Rotate this token?")
+            .default(true)
+            .interact()?;
+
+        if !confirmed {
+            println!("{}", "❌ Cancelled".red());
+            return Ok(());
+        }
+    }
+
+    let issued = service
+        .rotate_token(token.id, expires_at, grace_until, &AuditContext::cli())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to rotate token: {}", e))?;
+
+    println!();
+    println!("{}", "✅ Token rotated successfully!".green().bold());
+    println!();
+    println!(
+        "  New ID:    {}",
+        issued.token.id.to_string().bright_black()
+    );
+    println!("  New token: {}", issued.plaintext.bright_yellow().bold());
+    println!();
+    println!(
+        "{}",
+        "⚠️  IMPORTANT: Save this token now! You won't be able to see it again."
+            .red()
+            .bold()
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Reports the signing key of every active token and optionally revokes the
+/// tokens that still use an older key.
+///
+/// # Output Format
+///
+/// ```text
+/// 🔐 Token Hash Migration
+///
+///   Primary key:  3f9a01c2
+///   Older keys:   7be4d210
+///
+///   On primary key:  4
+///   On older key:    1
+///   Legacy (no key): 1
+///   Unknown key:     0
+///
+///   Not yet migrated:
+///   5   Mobile App                     legacy
+///   2   CI                             7be4d210
+/// ```
+///
+/// Tokens are re-hashed with the primary key on their next use. Tokens on an
+/// unknown key were hashed with a secret that is no longer configured and can no
+/// longer authenticate.
+async fn migrate_hashes(service: &TokenService, revoke_remaining: bool, yes: bool) -> Result<()> {
+    println!("{}", "🔐 Token Hash Migration".bright_blue().bold());
+    println!();
+
+    let key_ids = service.key_ids();
+    let primary = service.primary_key_id();
+
+    println!("  Primary key:  {}", primary.cyan());
+    println!(
+        "  Older keys:   {}",
+        if key_ids.len() > 1 {
+            key_ids[1..].join(", ")
+        } else {
+            "none".to_string()
+        }
+        .cyan()
+    );
+    println!();
+
+    let now = Utc::now();
+    let tokens = service
+        .list_tokens(None)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list tokens: {}", e))?;
+    let active: Vec<&ApiToken> = tokens
+        .iter()
+        .filter(|t| t.revoked_at.is_none() && !t.is_expired(now))
+        .collect();
+
+    let on_primary = active
+        .iter()
+        .filter(|t| t.key_id() == Some(primary))
+        .count();
+    let legacy = active.iter().filter(|t| t.key_id().is_none()).count();
+    let unknown = active
+        .iter()
+        .filter(|t| t.key_id().is_some_and(|id| !key_ids.contains(&id)))
+        .count();
+    let older = active.len() - on_primary - legacy - unknown;
+
+    println!("  On primary key:  {}", on_primary.to_string().green());
+    println!("  On older key:    {}", older.to_string().yellow());
+    println!("  Legacy (no key): {}", legacy.to_string().yellow());
+    println!("  Unknown key:     {}", unknown.to_string().red());
+    println!();
+
+    let remaining: Vec<&ApiToken> = active
+        .into_iter()
+        .filter(|t| t.key_id() != Some(primary))
+        .collect();
+
+    if remaining.is_empty() {
+        println!(
+            "{}",
+            "✅ All active tokens use the primary key; older secrets can be removed."
+                .green()
+                .bold()
+        );
+        println!();
+        return Ok(());
+    }
+
+    println!("  {}", "Not yet migrated:".bright_white().bold());
+    for token in &remaining {
+        println!(
+            "  {:<3} {:<30} {}",
+            token.id.to_string().bright_black(),
+            token.name.cyan(),
+            token.key_id().unwrap_or("legacy")
+        );
+    }
+    println!();
+
+    if !revoke_remaining {
+        println!(
+            "  These tokens are re-hashed on their next use. Run with {} to revoke them.",
+            "--revoke-remaining".bright_cyan()
+        );
+        println!();
+        return Ok(());
+    }
+
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Revoke {} token(s)?", remaining.len()))
+            .default(false)
+            .interact()?;
+
+        if !confirmed {
+            println!("{}", "❌ Cancelled".red());
+            return Ok(());
+        }
+    }
+
+    for token in &remaining {
+        service
+            .revoke_token(token.id, None, &AuditContext::cli())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to revoke token {}: {}", token.id, e))?;
+    }
+
+    println!();
+    println!(
+        "{}",
+        format!("✅ Revoked {} token(s)", remaining.len())
+            .green()
+            .bold()
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Finds a token by ID (numeric input) or name.
+async fn find_token(service: &TokenService, name_or_id: &str) -> Result<ApiToken> {
+    let token = service
+        .find_token(name_or_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    token.context("Token not found")
+}
+
+/// Dispatches user management commands.
+async fn handle_user_action(action: UserAction, pool: &PgPool) -> Result<()> {
+    let repo = user_repository(pool);
+
+    match action {
+        UserAction::Create {
+            email,
+            role,
+            team,
+            workspace,
+        } => {
+            let workspace = find_workspace(&workspace_repository(pool), &workspace).await?;
+            let team_id = match team {
+                Some(name) => Some(find_team_id(&repo, &name, workspace.id).await?),
+                None => None,
+            };
+            let user = repo
+                .create_user(&email, role, team_id, workspace.id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create user: {}", e))?;
+
+            println!(
+                "{} {} ({}, workspace {})",
+                "✅ Created user".green().bold(),
+                user_label(&user).cyan(),
+                user.role,
+                workspace.name
+            );
+        }
+        UserAction::List => list_users(&repo).await?,
+        UserAction::SetRole { email, role } => {
+            let user = find_user(&repo, &email).await?;
+            repo.set_role(user.id, role)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set role: {}", e))?;
+
+            println!(
+                "{} {} is now {}",
+                "✅".green(),
+                user_label(&user).cyan(),
+                role.to_string().bright_white().bold()
+            );
+        }
+        UserAction::SetTeam { email, team } => {
+            let user = find_user(&repo, &email).await?;
+            let team_id = match &team {
+                Some(name) => Some(find_team_id(&repo, name, user.workspace_id).await?),
+                None => None,
+            };
+            repo.set_team(user.id, team_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set team: {}", e))?;
+
+            println!(
+                "{} {} is now in team {}",
+                "✅".green(),
+                user_label(&user).cyan(),
+                team.as_deref().unwrap_or("none").bright_white().bold()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists all users with their role and team.
+async fn list_users(repo: &PgUserRepository) -> Result<()> {
+    println!("{}", "👤 Users".bright_blue().bold());
+    println!();
+
+    let users = repo
+        .list_users()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list users: {}", e))?;
+    let teams = repo
+        .list_teams()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list teams: {}", e))?;
+
+    if users.is_empty() {
+        println!("{}", "  No users found".yellow());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<5} {:<40} {:<8} {:<20} {}",
+        "ID".bright_white().bold(),
+        "Email".bright_white().bold(),
+        "Role".bright_white().bold(),
+        "Team".bright_white().bold(),
+        "SSO".bright_white().bold()
+    );
+    println!("  {}", "─".repeat(80).bright_black());
+
+    for user in &users {
+        let team = user
+            .team_id
+            .and_then(|id| teams.iter().find(|t| t.id == id))
+            .map_or("-", |t| t.name.as_str());
+
+        println!(
+            "  {:<5} {:<40} {:<8} {:<20} {}",
+            user.id.to_string().bright_black(),
+            user_label(user).cyan(),
+            user.role.as_str(),
+            team,
+            if user.sso_subject.is_some() {
+                "yes".green()
+            } else {
+                "no".bright_black()
+            }
+        );
+    }
+
+    println!();
+    println!("  Total: {}", users.len().to_string().bright_white().bold());
+    println!();
+
+    Ok(())
+}
+
+/// Dispatches team management commands.
+async fn handle_team_action(action: TeamAction, pool: &PgPool) -> Result<()> {
+    let repo = user_repository(pool);
+
+    match action {
+        TeamAction::Create { name, workspace } => {
+            let workspace = find_workspace(&workspace_repository(pool), &workspace).await?;
+            let team = repo
+                .create_team(&name, workspace.id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create team: {}", e))?;
+
+            println!(
+                "{} {} (workspace {})",
+                "✅ Created team".green().bold(),
+                team.name.cyan(),
+                workspace.name
+            );
+        }
+        TeamAction::List => {
+            println!("{}", "👥 Teams".bright_blue().bold());
+            println!();
+
+            let teams = repo
+                .list_teams()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to list teams: {}", e))?;
+
+            if teams.is_empty() {
+                println!("{}", "  No teams found".yellow());
+            }
+            for team in &teams {
+                println!(
+                    "  {:<5} {} (workspace {})",
+                    team.id.to_string().bright_black(),
+                    team.name.cyan(),
+                    team.workspace_id
+                );
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Dispatches workspace management commands.
+async fn handle_workspace_action(action: WorkspaceAction, pool: &PgPool) -> Result<()> {
+    let repo = workspace_repository(pool);
+
+    match action {
+        WorkspaceAction::Create {
+            name,
+            max_links,
+            max_domains,
+            monthly_clicks,
+        } => {
+            let quotas = WorkspaceQuotas {
+                max_links,
+                max_domains,
+                monthly_click_allowance: monthly_clicks,
+            };
+            let workspace = repo
+                .create(&name, quotas)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create workspace: {}", e))?;
+
+            println!(
+                "{} {} (ID {})",
+                "✅ Created workspace".green().bold(),
+                workspace.name.cyan(),
+                workspace.id
+            );
+        }
+        WorkspaceAction::List => list_workspaces(&repo).await?,
+        WorkspaceAction::SetQuota {
+            name,
+            max_links,
+            max_domains,
+            monthly_clicks,
+        } => {
+            let workspace = find_workspace(&repo, &name).await?;
+            let current = workspace.quotas;
+            let quotas = WorkspaceQuotas {
+                max_links: max_links.map_or(current.max_links, |l| l.0),
+                max_domains: max_domains.map_or(current.max_domains, |l| l.0),
+                monthly_click_allowance: monthly_clicks
+                    .map_or(current.monthly_click_allowance, |l| l.0),
+            };
+            repo.set_quotas(workspace.id, quotas)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set quotas: {}", e))?;
+
+            println!(
+                "{} {}: links {}, domains {}, clicks/month {}",
+                "✅ Quotas of".green().bold(),
+                workspace.name.cyan(),
+                format_limit(quotas.max_links),
+                format_limit(quotas.max_domains),
+                format_limit(quotas.monthly_click_allowance)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists all workspaces with their usage against their quotas.
+///
+/// # Output Format
+///
+/// ```text
+/// 🏢 Workspaces
+///
+///   ID    Name                 Links            Domains        Clicks this month
+///   ──────────────────────────────────────────────────────────────────────────────
+///   1     default              120 / ∞          2 / ∞          5302 / ∞
+///   2     acme                 998 / 1000       1 / 2          120 / 100000
+/// ```
+async fn list_workspaces(repo: &PgWorkspaceRepository) -> Result<()> {
+    println!("{}", "🏢 Workspaces".bright_blue().bold());
+    println!();
+
+    let workspaces = repo
+        .list()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list workspaces: {}", e))?;
+
+    println!(
+        "  {:<5} {:<20} {:<16} {:<14} {}",
+        "ID".bright_white().bold(),
+        "Name".bright_white().bold(),
+        "Links".bright_white().bold(),
+        "Domains".bright_white().bold(),
+        "Clicks this month".bright_white().bold()
+    );
+    println!("  {}", "─".repeat(78).bright_black());
+
+    let since = month_start(Utc::now());
+    for workspace in &workspaces {
+        let usage = |e| anyhow::anyhow!("Failed to load workspace usage: {}", e);
+        let links = repo.count_links(workspace.id).await.map_err(usage)?;
+        let domains = repo.count_domains(workspace.id).await.map_err(usage)?;
+        let clicks = repo
+            .count_clicks_since(workspace.id, since)
+            .await
+            .map_err(usage)?;
+        let quotas = workspace.quotas;
+
+        println!(
+            "  {:<5} {:<20} {:<16} {:<14} {}",
+            workspace.id.to_string().bright_black(),
+            workspace.name.cyan(),
+            format_usage(links, quotas.max_links, quotas.links_exhausted(links)),
+            format_usage(
+                domains,
+                quotas.max_domains,
+                quotas.domains_exhausted(domains)
+            ),
+            format_usage(
+                clicks,
+                quotas.monthly_click_allowance,
+                quotas.clicks_exhausted(clicks)
+            )
+        );
+    }
+
+    println!();
+    println!(
+        "  Total: {}",
+        workspaces.len().to_string().bright_white().bold()
+    );
+    println!();
+
+    Ok(())
+}
+
+fn workspace_repository(pool: &PgPool) -> PgWorkspaceRepository {
+    PgWorkspaceRepository::new(Arc::new(pool.clone()))
+}
+
+/// Finds a workspace by name.
+async fn find_workspace(repo: &PgWorkspaceRepository, name: &str) -> Result<Workspace> {
+    repo.find_by_name(name)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to look up workspace: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Workspace '{}' not found", name))
+}
+
+fn user_repository(pool: &PgPool) -> PgUserRepository {
+    PgUserRepository::new(Arc::new(pool.clone()))
+}
+
+/// Finds a user by email.
+async fn find_user(repo: &PgUserRepository, email: &str) -> Result<User> {
+    repo.find_by_email(email)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to look up user: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("User '{}' not found", email))
+}
+
+/// Returns the user's email, or `user #<id>` for users without one.
+fn user_label(user: &User) -> String {
+    user.email
+        .clone()
+        .unwrap_or_else(|| format!("user #{}", user.id))
+}
+
+/// Finds the ID of a workspace's team by name.
+async fn find_team_id(repo: &PgUserRepository, name: &str, workspace_id: i64) -> Result<i64> {
+    let team = repo
+        .find_team_by_name(name, workspace_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to look up team: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Team '{}' not found", name))?;
+
+    Ok(team.id)
+}
+
 /// Displays system statistics.
 ///
 /// Shows:
@@ -409,10 +1452,12 @@ async fn handle_stats(pool: &PgPool) -> Result<()> {
         .fetch_one(pool)
         .await?;
 
-    let tokens_count: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM api_tokens WHERE revoked_at IS NULL")
-            .fetch_one(pool)
-            .await?;
+    let tokens_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM api_tokens
+             WHERE revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())",
+    )
+    .fetch_one(pool)
+    .await?;
 
     println!(
         "  Links:         {}",
@@ -431,6 +1476,252 @@ async fn handle_stats(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
+/// Sets `links.clicks` of every link to its number of recorded clicks.
+///
+/// Safe while servers run: the recount moves the flush watermark past every
+/// click it counted, so their next flushes don't count them again. Flushes
+/// wait until the recount is done; click inserts only wait for the moment it
+/// reads the highest committed click.
+async fn reconcile_click_counters(pool: &PgPool) -> Result<()> {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+    let corrected = repo.reconcile_link_clicks().await?;
+
+    if corrected == 0 {
+        println!("{}", "✅ All click counters are exact.".green().bold());
+    } else {
+        println!(
+            "{}",
+            format!("✅ Corrected the click counters of {corrected} link(s)")
+                .green()
+                .bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Dispatches redirect cache commands.
+///
+/// Connects to Redis with the server's settings. Purges are published on the
+/// invalidation bus, so running servers also drop their in-process copies.
+async fn handle_cache_action(action: CacheAction, pool: PgPool) -> Result<()> {
+    let config = load_from_env()?;
+    let Some(redis_url) = config.redis_url.as_deref() else {
+        anyhow::bail!("Redis is not configured; set REDIS_URL or REDIS_HOST");
+    };
+    let redis = RedisCache::connect(redis_url, config.cache_ttl_seconds)
+        .await
+        .context("Failed to connect to Redis")?;
+    let bus = InvalidationBus::connect(redis_url)
+        .await
+        .context("Failed to connect to Redis")?;
+    let service = CacheAdminService::new(
+        Arc::new(redis),
+        Arc::new(PgStatsRepository::new(Arc::new(pool))),
+    );
+
+    match action {
+        CacheAction::Warm { links, days } => {
+            let links = links.unwrap_or(if config.cache_warmup_links > 0 {
+                config.cache_warmup_links
+            } else {
+                1000
+            });
+            let days = days.unwrap_or(config.cache_warmup_days);
+            let since = Utc::now() - Duration::days(days as i64);
+
+            let warmed = service
+                .warm_up(links as i64, since)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to warm up cache: {}", e))?;
+
+            println!(
+                "{} {} links clicked most in the last {} days",
+                "✅ Cached".green().bold(),
+                warmed.to_string().cyan(),
+                days
+            );
+        }
+        CacheAction::Inspect { domain, code } => {
+            let key = CachedRedirect::key(&domain, &code);
+            let entry = service
+                .inspect(&domain, &code)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read cache: {}", e))?;
+
+            match entry {
+                Some(entry) => {
+                    println!("{} {}", "🔑".bright_blue(), key.cyan().bold());
+                    println!("  Value: {}", entry.value);
+                    println!(
+                        "  TTL:   {}",
+                        entry
+                            .ttl
+                            .map_or("unknown".to_string(), |ttl| format!("{}s", ttl.as_secs()))
+                    );
+                }
+                None => println!("{} {}", "Nothing cached for".yellow(), key),
+            }
+        }
+        CacheAction::Purge { domain, code } => {
+            service
+                .purge(&domain, &code)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to purge cache: {}", e))?;
+            let key = CachedRedirect::key(&domain, &code);
+            bus.publish(&key).await;
+
+            println!("{} {}", "✅ Purged".green().bold(), key.cyan());
+        }
+        CacheAction::PurgeDomain { domain, yes } => {
+            if !yes {
+                let confirmed = Confirm::new()
+                    .with_prompt(format!("Purge every cached redirect of {}?", domain))
+                    .default(false)
+                    .interact()?;
+
+                if !confirmed {
+                    println!("{}", "❌ Cancelled".red());
+                    return Ok(());
+                }
+            }
+
+            let removed = service
+                .purge_domain(&domain)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to purge cache: {}", e))?;
+            bus.publish_prefix(&CachedRedirect::domain_prefix(&domain))
+                .await;
+
+            println!(
+                "{} {} cached redirects of {}",
+                "✅ Purged".green().bold(),
+                removed.to_string().cyan(),
+                domain.cyan()
+            );
+        }
+        CacheAction::Stats => {
+            let stats = service
+                .stats()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read cache statistics: {}", e))?;
+            let show = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+
+            println!("{}", "📦 Cache".bright_blue().bold());
+            println!();
+            println!(
+                "  Keys:          {}",
+                show(stats.keys).bright_green().bold()
+            );
+            println!("  Hits:          {}", show(stats.hits));
+            println!("  Misses:        {}", show(stats.misses));
+            println!(
+                "  Memory:        {}",
+                stats.used_memory_bytes.map_or("-".to_string(), |b| format!(
+                    "{:.1} MiB",
+                    b as f64 / 1048576.0
+                ))
+            );
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+fn audit_log(pool: &PgPool) -> AuditLog {
+    AuditService::new(Arc::new(PgAuditRepository::new(Arc::new(pool.clone()))))
+}
+
+/// Lists audit events, newest first.
+///
+/// # Output Format
+///
+/// ```text
+/// 📜 Audit Log
+///
+///   Time                 WS    Entity   ID     Action   Actor                     IP
+///   ──────────────────────────────────────────────────────────────────────────────────────────
+///   2026-10-18 09:12:44  1     token    7      delete   admin-cli
+///   2026-10-18 09:10:02  1     link     42     update   CI pipeline (#3)          203.0.113.9
+///
+///   Showing 2 of 2
+/// ```
+///
+/// With `--values`, the JSON values before and after each change follow its row.
+async fn show_audit(audit: &AuditLog, filter: AuditFilter, values: bool) -> Result<()> {
+    println!("{}", "📜 Audit Log".bright_blue().bold());
+    println!();
+
+    let (events, total) = audit
+        .list(filter)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list audit events: {}", e))?;
+
+    if events.is_empty() {
+        println!("{}", "  No changes found".yellow());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<20} {:<5} {:<8} {:<6} {:<8} {:<25} {}",
+        "Time".bright_white().bold(),
+        "WS".bright_white().bold(),
+        "Entity".bright_white().bold(),
+        "ID".bright_white().bold(),
+        "Action".bright_white().bold(),
+        "Actor".bright_white().bold(),
+        "IP".bright_white().bold()
+    );
+    println!("  {}", "─".repeat(90).bright_black());
+
+    for event in &events {
+        let action = match event.action.as_str() {
+            "create" | "restore" => event.action.as_str().green(),
+            "delete" => event.action.as_str().red(),
+            other => other.yellow(),
+        };
+        let actor = match event.actor.token_id {
+            Some(id) => format!("{} (#{})", event.actor.name, id),
+            None => event.actor.name.clone(),
+        };
+
+        println!(
+            "  {:<20} {:<5} {:<8} {:<6} {:<8} {:<25} {}",
+            event
+                .created_at
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .bright_black(),
+            event.workspace_id,
+            event.entity.as_str(),
+            event.entity_id.to_string().bright_black(),
+            action,
+            actor.cyan(),
+            event.ip.as_deref().unwrap_or("").bright_black()
+        );
+
+        if values {
+            for (label, value) in [("before", &event.before), ("after", &event.after)] {
+                if let Some(value) = value {
+                    println!("      {} {}", format!("{label}:").bright_black(), value);
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "  Showing {} of {}",
+        events.len().to_string().bright_white().bold(),
+        total.to_string().bright_white().bold()
+    );
+    println!();
+
+    Ok(())
+}
+
 /// Handles database diagnostic commands.
 async fn handle_db_action(action: DbAction, pool: &PgPool) -> Result<()> {
     match action {
@@ -457,34 +1748,131 @@ async fn handle_db_action(action: DbAction, pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-/// Generates a cryptographically random token.
-///
-/// # Format
-///
-/// - Length: 48 characters
-/// - Character set: A-Z, a-z, 0-9
-/// - Entropy: ~286 bits
-fn generate_token() -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-    const TOKEN_LEN: usize = 48;
+/// Parses a `--scope` argument.
+fn parse_scope(value: &str) -> Result<Scope, String> {
+    value.parse()
+}
 
-    let mut rng = rand::rng();
+/// Parses an `--entity` argument.
+fn parse_entity(value: &str) -> Result<AuditEntity, String> {
+    value.parse()
+}
 
-    (0..TOKEN_LEN)
-        .map(|_| {
-            let idx = rng.random_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
+/// Parses a `--from` argument; a date means its start (UTC).
+fn parse_from(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_time(value, false)
 }
 
-/// Hashes a token with HMAC-SHA256 keyed by `secret`.
-///
-/// Returns a 64-character lowercase hex-encoded MAC for database storage.
-fn hash_token(token: &str, secret: &str) -> String {
-    let mut mac =
-        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
-    mac.update(token.as_bytes());
-    hex::encode(mac.finalize().into_bytes())
+/// Parses a `--to` argument; a date means its end (UTC).
+fn parse_to(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_time(value, true)
+}
+
+/// Parses an RFC 3339 timestamp or a `YYYY-MM-DD` date.
+fn parse_time(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("'{value}' is not a YYYY-MM-DD date or RFC 3339 timestamp"))?;
+    let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    Ok(if end_of_day {
+        start + Duration::days(1) - Duration::nanoseconds(1)
+    } else {
+        start
+    })
+}
+
+/// Parses a `--role` argument.
+fn parse_role(value: &str) -> Result<Role, String> {
+    value.parse()
+}
+
+/// Parses a quota argument: a non-negative number or `unlimited`.
+fn parse_limit(value: &str) -> Result<Limit, String> {
+    if value == "unlimited" {
+        return Ok(Limit(None));
+    }
+
+    match value.parse::<i64>() {
+        Ok(limit) if limit >= 0 => Ok(Limit(Some(limit))),
+        _ => Err(format!(
+            "'{value}' is not a non-negative number or 'unlimited'"
+        )),
+    }
+}
+
+/// Resolves `--domain` names to domain IDs of the token's workspace; no names
+/// means every domain.
+async fn resolve_domains(
+    pool: &PgPool,
+    names: &[String],
+    workspace_id: i64,
+) -> Result<Option<Vec<i64>>> {
+    if names.is_empty() {
+        return Ok(None);
+    }
+
+    let repo = PgDomainRepository::new(Arc::new(pool.clone()));
+    let mut ids = Vec::with_capacity(names.len());
+
+    for name in names {
+        let domain = repo
+            .find_by_name(name)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to look up domain: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("Domain '{}' not found", name))?;
+        if domain.workspace_id != workspace_id {
+            anyhow::bail!("Domain '{}' belongs to another workspace", name);
+        }
+        ids.push(domain.id);
+    }
+
+    Ok(Some(ids))
+}
+
+/// Formats scopes as a comma-separated list.
+fn format_scopes(scopes: &[Scope]) -> String {
+    scopes
+        .iter()
+        .map(Scope::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Formats a domain restriction for display.
+fn format_domain_ids(domain_ids: Option<&[i64]>) -> String {
+    match domain_ids {
+        None => "all".to_string(),
+        Some(ids) => ids.iter().map(i64::to_string).collect::<Vec<_>>().join(","),
+    }
+}
+
+/// Converts `--expires-in-days` to an absolute expiry.
+fn expiry_from_days(days: Option<u32>) -> Option<DateTime<Utc>> {
+    days.map(|d| Utc::now() + Duration::days(d.into()))
+}
+
+/// Formats a quota for display.
+fn format_limit(limit: Option<i64>) -> String {
+    limit.map_or_else(|| "unlimited".to_string(), |l| l.to_string())
+}
+
+/// Formats quota usage as `used / limit`, red once the quota is used up.
+fn format_usage(used: i64, limit: Option<i64>, exhausted: bool) -> ColoredString {
+    let text = format!(
+        "{} / {}",
+        used,
+        limit.map_or_else(|| "∞".to_string(), |l| l.to_string())
+    );
+    if exhausted { text.red() } else { text.normal() }
+}
+
+/// Formats a token expiry for display.
+fn format_expiry(expires_at: Option<DateTime<Utc>>) -> String {
+    match expires_at {
+        None => "never".to_string(),
+        Some(at) => at.format("%Y-%m-%d %H:%M").to_string(),
+    }
 }
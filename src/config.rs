@@ -40,10 +40,42 @@
 //! - `RUST_LOG` - Log level (default: `info`)
 //! - `LOG_FORMAT` - Log format: `text` or `json` (default: `text`)
 //! - `CLICK_QUEUE_CAPACITY` - Click event buffer size (default: 10000, min: 100)
+//! - `CLICK_BATCH_SIZE` / `CLICK_BATCH_INTERVAL_MS` - Click events written per INSERT
+//!   and how long a batch waits to fill up (default: 100 events, 50 ms)
+//! - `CLICK_JOURNAL_PATH` - Local file that clicks spill into when the queue is full or
+//!   the database is down (enables the journal if set)
+//! - `CLICK_JOURNAL_MAX_MB` / `CLICK_JOURNAL_REPLAY_SECONDS` - Journal size limit and
+//!   how often it is replayed (default: 1024 MB, 10 seconds)
+//! - `CLICK_COUNTER_FLUSH_SECONDS` - How often recorded clicks are added to the
+//!   `links.clicks` counters (default: 10)
+//! - `L1_CACHE_CAPACITY` / `L1_CACHE_TTL_SECONDS` - In-process cache in front of Redis
+//!   (default: 10000 entries for 5 seconds; `0` entries disables it)
+//! - `NEGATIVE_CACHE_TTL_SECONDS` - How long unknown, deleted and expired codes are
+//!   cached (default: 30; `0` disables it)
+//! - `CACHE_REFRESH_AHEAD_SECONDS` - Renew cached redirects with less TTL left
+//!   (default: 60; `0` disables it)
+//! - `CACHE_LOCK_WAIT_MS` - Coalesce cache misses across instances with a Redis lock,
+//!   waiting at most this long for the holder (default: 0, disabled)
+//! - `CACHE_WARMUP_LINKS` / `CACHE_WARMUP_DAYS` - Cache the most clicked links of the
+//!   last days at startup (default: 0 links, disabled; 7 days)
+//! - `DOMAIN_REFRESH_SECONDS` - How often the in-memory domain registry reloads
+//!   domains changed by other instances (default: 60; `0` disables it)
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT` - OTLP/HTTP collector URL (enables trace export if set)
+//! - `OTEL_SERVICE_NAME` - Reported service name (default: `url-shortener`)
+//! - `API_LEGACY_SUNSET` - RFC 3339 date after which the unversioned `/api` alias may be
+//!   removed, announced in its `Sunset` header (default: `2027-04-30T00:00:00Z`)
+//! - `OIDC_ISSUER_URL` - OpenID Connect issuer; enables dashboard single sign-on
+//!   (see [`OidcConfig`] for the related `OIDC_*` variables)
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use std::env;
 
+use crate::domain::entities::Role;
+
+/// Default `Sunset` of the unversioned `/api` alias (2027-04-30T00:00:00Z).
+const DEFAULT_API_LEGACY_SUNSET: i64 = 1_809_043_200;
+
 /// Service configuration loaded from environment variables.
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -59,11 +91,54 @@ pub struct Config {
     /// Default TTL (seconds) for cached URL mappings in Redis.
     /// Has no effect when Redis is not configured.
     pub cache_ttl_seconds: u64,
-    /// Maximum number of click events processed concurrently by the background worker.
+    /// Maximum number of entries in the in-process cache in front of Redis
+    /// (`L1_CACHE_CAPACITY`, default: 10000). `0` disables it.
+    pub l1_cache_capacity: u64,
+    /// How long the in-process cache keeps an entry, in seconds (`L1_CACHE_TTL_SECONDS`,
+    /// default: 5). Bounds how long other instances serve a changed link while the
+    /// pub/sub invalidation subscription is down.
+    pub l1_cache_ttl_seconds: u64,
+    /// How long redirects to unknown, deleted and expired codes are cached, in seconds
+    /// (`NEGATIVE_CACHE_TTL_SECONDS`, default: 30). `0` disables negative caching.
+    pub negative_cache_ttl_seconds: u64,
+    /// Cached redirects with less TTL left are renewed from the database in the
+    /// background (`CACHE_REFRESH_AHEAD_SECONDS`, default: 60). `0` disables it.
+    pub cache_refresh_ahead_seconds: u64,
+    /// How long a cache miss waits for another instance holding the Redis lookup
+    /// lock of the same key (`CACHE_LOCK_WAIT_MS`, default: 0). `0` disables the lock.
+    pub cache_lock_wait_ms: u64,
+    /// How many of the most clicked links are cached at startup
+    /// (`CACHE_WARMUP_LINKS`, default: 0). `0` disables the warm-up.
+    pub cache_warmup_links: u64,
+    /// How many days of clicks rank the links cached at startup
+    /// (`CACHE_WARMUP_DAYS`, default: 7).
+    pub cache_warmup_days: u64,
+    /// How often the in-memory domain registry is reloaded from the database
+    /// (`DOMAIN_REFRESH_SECONDS`, default: 60). `0` disables periodic reloads.
+    pub domain_refresh_seconds: u64,
+    /// Maximum number of click batches written concurrently by the background worker.
     pub click_worker_concurrency: usize,
-    /// HMAC signing secret used to hash API tokens before storage.
-    /// Loaded from `TOKEN_SIGNING_SECRET`. Must be non-empty.
-    pub token_signing_secret: String,
+    /// Maximum number of click events written by one INSERT (`CLICK_BATCH_SIZE`, default: 100).
+    pub click_batch_size: usize,
+    /// How long the first click event of a batch waits for more events
+    /// (`CLICK_BATCH_INTERVAL_MS`, default: 50).
+    pub click_batch_interval_ms: u64,
+    /// Local file clicks spill into when the queue is full or the database is down
+    /// (`CLICK_JOURNAL_PATH`). The journal is disabled when unset.
+    pub click_journal_path: Option<String>,
+    /// Size limit of the click journal in megabytes (`CLICK_JOURNAL_MAX_MB`, default: 1024).
+    pub click_journal_max_mb: u64,
+    /// How often the click journal is replayed (`CLICK_JOURNAL_REPLAY_SECONDS`, default: 10).
+    pub click_journal_replay_seconds: u64,
+    /// How often recorded clicks are added to the `links.clicks` counters
+    /// (`CLICK_COUNTER_FLUSH_SECONDS`, default: 10).
+    pub click_counter_flush_seconds: u64,
+    /// HMAC signing secrets used to hash API tokens before storage, newest first.
+    /// Loaded from `TOKEN_SIGNING_SECRET` (comma-separated). Must be non-empty.
+    /// The first secret hashes new tokens; the others only verify existing ones.
+    pub token_signing_secrets: Vec<String>,
+    /// Lifetime of a dashboard login session in hours (`SESSION_TTL_HOURS`, default: 12).
+    pub session_ttl_hours: u64,
 
     // ── PgPool settings ─────────────────────────────────────────────────────
     /// Maximum number of connections in the pool (`DB_MAX_CONNECTIONS`, default: 10).
@@ -76,6 +151,144 @@ pub struct Config {
     pub db_idle_timeout: u64,
     /// Maximum connection lifetime in seconds (`DB_MAX_LIFETIME`, default: 1800).
     pub db_max_lifetime: u64,
+
+    // ── Tracing ─────────────────────────────────────────────────────────────
+    /// OTLP/HTTP collector base URL (`OTEL_EXPORTER_OTLP_ENDPOINT`).
+    /// Spans are exported to `{endpoint}/v1/traces`; export is disabled when unset.
+    pub otlp_endpoint: Option<String>,
+    /// Service name attached to exported spans (`OTEL_SERVICE_NAME`, default: `url-shortener`).
+    pub otel_service_name: String,
+
+    // ── API versioning ──────────────────────────────────────────────────────
+    /// Date after which the deprecated `/api` alias of `/api/v1` may be removed
+    /// (`API_LEGACY_SUNSET`, RFC 3339, default: `2027-04-30T00:00:00Z`).
+    pub api_legacy_sunset: DateTime<Utc>,
+
+    // ── Single sign-on ──────────────────────────────────────────────────────
+    /// Dashboard login through OpenID Connect; `None` unless `OIDC_ISSUER_URL` is set.
+    pub oidc: Option<OidcConfig>,
+}
+
+/// OpenID Connect settings for dashboard single sign-on.
+///
+/// | Variable | Default | |
+/// |:--|:--|:--|
+/// | `OIDC_ISSUER_URL` | — | Issuer; its `/.well-known/openid-configuration` is fetched on first use |
+/// | `OIDC_CLIENT_ID` | — | Required with `OIDC_ISSUER_URL` |
+/// | `OIDC_CLIENT_SECRET` | — | Omit for public clients (PKCE only) |
+/// | `OIDC_REDIRECT_URL` | — | Public URL of `/dashboard/oidc/callback` |
+/// | `OIDC_SCOPES` | `openid email profile` | Space- or comma-separated |
+/// | `OIDC_ALLOWED_EMAIL_DOMAINS` | — | Comma-separated; verified emails only |
+/// | `OIDC_ALLOWED_GROUPS` | — | Comma-separated |
+/// | `OIDC_GROUPS_CLAIM` | `groups` | ID token claim listing the user's groups |
+/// | `OIDC_ROLE_MAPPING` | — | `group=role` pairs, e.g. `sre=admin,marketing=editor` |
+/// | `OIDC_DEFAULT_ROLE` | `viewer` | Role of allowed users matching no mapping |
+///
+/// At least one allowed email domain or group is required.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub scopes: Vec<String>,
+    /// Lowercase email domains whose users may sign in.
+    pub allowed_email_domains: Vec<String>,
+    /// Groups whose members may sign in.
+    pub allowed_groups: Vec<String>,
+    pub groups_claim: String,
+    /// Group to role; a user gets the highest role of their groups.
+    pub role_mapping: Vec<(String, Role)>,
+    pub default_role: Role,
+}
+
+impl OidcConfig {
+    /// Loads the `OIDC_*` variables; `Ok(None)` if `OIDC_ISSUER_URL` is unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required variable is missing or a role is unknown.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(issuer_url) = env::var("OIDC_ISSUER_URL").ok().filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+
+        let client_id = env::var("OIDC_CLIENT_ID")
+            .context("OIDC_CLIENT_ID must be set when OIDC_ISSUER_URL is set")?;
+        let client_secret = env::var("OIDC_CLIENT_SECRET")
+            .ok()
+            .filter(|v| !v.is_empty());
+        let redirect_url = env::var("OIDC_REDIRECT_URL")
+            .context("OIDC_REDIRECT_URL must be set when OIDC_ISSUER_URL is set")?;
+
+        let scopes = env::var("OIDC_SCOPES")
+            .map(|v| parse_list(&v.replace(' ', ",")))
+            .unwrap_or_else(|_| vec!["openid".into(), "email".into(), "profile".into()]);
+
+        let allowed_email_domains = parse_list(
+            &env::var("OIDC_ALLOWED_EMAIL_DOMAINS")
+                .unwrap_or_default()
+                .to_ascii_lowercase(),
+        );
+        let allowed_groups = parse_list(&env::var("OIDC_ALLOWED_GROUPS").unwrap_or_default());
+        let groups_claim = env::var("OIDC_GROUPS_CLAIM").unwrap_or_else(|_| "groups".to_string());
+
+        let role_mapping = parse_role_mapping(&env::var("OIDC_ROLE_MAPPING").unwrap_or_default())
+            .context("Invalid OIDC_ROLE_MAPPING")?;
+        let default_role = match env::var("OIDC_DEFAULT_ROLE") {
+            Ok(v) => v
+                .parse()
+                .map_err(anyhow::Error::msg)
+                .context("Invalid OIDC_DEFAULT_ROLE")?,
+            Err(_) => Role::Viewer,
+        };
+
+        Ok(Some(Self {
+            issuer_url,
+            client_id,
+            client_secret,
+            redirect_url,
+            scopes,
+            allowed_email_domains,
+            allowed_groups,
+            groups_claim,
+            role_mapping,
+            default_role,
+        }))
+    }
+
+    /// Validates the settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a URL is not `http(s)://`, `OIDC_CLIENT_ID` is empty,
+    /// the `openid` scope is missing, or nobody would be allowed to sign in.
+    pub fn validate(&self) -> Result<()> {
+        for (name, url) in [
+            ("OIDC_ISSUER_URL", &self.issuer_url),
+            ("OIDC_REDIRECT_URL", &self.redirect_url),
+        ] {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                anyhow::bail!("{name} must start with 'http://' or 'https://', got '{url}'");
+            }
+        }
+
+        if self.client_id.is_empty() {
+            anyhow::bail!("OIDC_CLIENT_ID must not be empty");
+        }
+
+        if !self.scopes.iter().any(|s| s == "openid") {
+            anyhow::bail!("OIDC_SCOPES must include 'openid'");
+        }
+
+        if self.allowed_email_domains.is_empty() && self.allowed_groups.is_empty() {
+            anyhow::bail!(
+                "OIDC_ALLOWED_EMAIL_DOMAINS or OIDC_ALLOWED_GROUPS must be set when OIDC_ISSUER_URL is set"
+            );
+        }
+
+        Ok(())
+    }
 }
 
 impl Config {
@@ -111,13 +324,88 @@ impl Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or(3600);
 
+        let l1_cache_capacity = env::var("L1_CACHE_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10_000);
+
+        let l1_cache_ttl_seconds = env::var("L1_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+
+        let negative_cache_ttl_seconds = env::var("NEGATIVE_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+
+        let cache_refresh_ahead_seconds = env::var("CACHE_REFRESH_AHEAD_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+
+        let cache_lock_wait_ms = env::var("CACHE_LOCK_WAIT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let cache_warmup_links = env::var("CACHE_WARMUP_LINKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let cache_warmup_days = env::var("CACHE_WARMUP_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(7);
+
+        let domain_refresh_seconds = env::var("DOMAIN_REFRESH_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+
         let click_worker_concurrency = env::var("CLICK_WORKER_CONCURRENCY")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(4);
 
-        let token_signing_secret =
-            env::var("TOKEN_SIGNING_SECRET").context("TOKEN_SIGNING_SECRET must be set")?;
+        let click_batch_size = env::var("CLICK_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100);
+
+        let click_batch_interval_ms = env::var("CLICK_BATCH_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(50);
+
+        let click_journal_path = env::var("CLICK_JOURNAL_PATH")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        let click_journal_max_mb = env::var("CLICK_JOURNAL_MAX_MB")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1024);
+
+        let click_journal_replay_seconds = env::var("CLICK_JOURNAL_REPLAY_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+
+        let click_counter_flush_seconds = env::var("CLICK_COUNTER_FLUSH_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+
+        let token_signing_secrets = parse_signing_secrets(
+            &env::var("TOKEN_SIGNING_SECRET").context("TOKEN_SIGNING_SECRET must be set")?,
+        );
+
+        let session_ttl_hours = env::var("SESSION_TTL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(12);
 
         let db_max_connections = env::var("DB_MAX_CONNECTIONS")
             .ok()
@@ -139,6 +427,23 @@ impl Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or(1800);
 
+        let otlp_endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        let otel_service_name =
+            env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "url-shortener".to_string());
+
+        let api_legacy_sunset = env::var("API_LEGACY_SUNSET")
+            .ok()
+            .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or_else(|| {
+                DateTime::from_timestamp(DEFAULT_API_LEGACY_SUNSET, 0).expect("valid timestamp")
+            });
+
+        let oidc = OidcConfig::from_env()?;
+
         Ok(Self {
             database_url,
             redis_url,
@@ -148,12 +453,31 @@ impl Config {
             click_queue_capacity,
             behind_proxy,
             cache_ttl_seconds,
+            l1_cache_capacity,
+            l1_cache_ttl_seconds,
+            negative_cache_ttl_seconds,
+            cache_refresh_ahead_seconds,
+            cache_lock_wait_ms,
+            cache_warmup_links,
+            cache_warmup_days,
+            domain_refresh_seconds,
             click_worker_concurrency,
-            token_signing_secret,
+            click_batch_size,
+            click_batch_interval_ms,
+            click_journal_path,
+            click_journal_max_mb,
+            click_journal_replay_seconds,
+            click_counter_flush_seconds,
+            token_signing_secrets,
+            session_ttl_hours,
             db_max_connections,
             db_connect_timeout,
             db_idle_timeout,
             db_max_lifetime,
+            otlp_endpoint,
+            otel_service_name,
+            api_legacy_sunset,
+            oidc,
         })
     }
 
@@ -225,6 +549,7 @@ impl Config {
     /// - `click_queue_capacity` is less than 100
     /// - `log_format` is not `text` or `json`
     /// - `listen_addr` is invalid
+    /// - `otlp_endpoint` is not an `http://` or `https://` URL
     pub fn validate(&self) -> Result<()> {
         // Validate queue capacity
         if self.click_queue_capacity < 100 {
@@ -283,6 +608,52 @@ impl Config {
             anyhow::bail!("CACHE_TTL_SECONDS must be greater than 0");
         }
 
+        // Validate in-process cache TTL (entries are never refreshed from other instances)
+        if self.l1_cache_capacity > 0
+            && (self.l1_cache_ttl_seconds == 0 || self.l1_cache_ttl_seconds > 300)
+        {
+            anyhow::bail!(
+                "L1_CACHE_TTL_SECONDS must be between 1 and 300, got {}",
+                self.l1_cache_ttl_seconds
+            );
+        }
+
+        // Validate negative cache TTL (a link created meanwhile clears its entry)
+        if self.negative_cache_ttl_seconds > 3600 {
+            anyhow::bail!(
+                "NEGATIVE_CACHE_TTL_SECONDS must be at most 3600, got {}",
+                self.negative_cache_ttl_seconds
+            );
+        }
+
+        // Validate refresh-ahead window (entries would be renewed on every hit otherwise)
+        if self.cache_refresh_ahead_seconds >= self.cache_ttl_seconds {
+            anyhow::bail!(
+                "CACHE_REFRESH_AHEAD_SECONDS must be less than CACHE_TTL_SECONDS ({}), got {}",
+                self.cache_ttl_seconds,
+                self.cache_refresh_ahead_seconds
+            );
+        }
+
+        // Validate lock wait (a miss never waits longer than this for another instance)
+        if self.cache_lock_wait_ms > 5000 {
+            anyhow::bail!(
+                "CACHE_LOCK_WAIT_MS must be at most 5000, got {}",
+                self.cache_lock_wait_ms
+            );
+        }
+
+        // Validate warm-up (a single pipeline writes every entry)
+        if self.cache_warmup_links > 100_000 {
+            anyhow::bail!(
+                "CACHE_WARMUP_LINKS must be at most 100000, got {}",
+                self.cache_warmup_links
+            );
+        }
+        if self.cache_warmup_days == 0 {
+            anyhow::bail!("CACHE_WARMUP_DAYS must be at least 1");
+        }
+
         // Validate click worker concurrency
         if self.click_worker_concurrency == 0 || self.click_worker_concurrency > 256 {
             anyhow::bail!(
@@ -291,11 +662,47 @@ impl Config {
             );
         }
 
-        // Validate token signing secret
-        if self.token_signing_secret.is_empty() {
+        // Validate click batching
+        if self.click_batch_size == 0 || self.click_batch_size > 10_000 {
+            anyhow::bail!(
+                "CLICK_BATCH_SIZE must be between 1 and 10000, got {}",
+                self.click_batch_size
+            );
+        }
+
+        if self.click_batch_interval_ms > 10_000 {
+            anyhow::bail!(
+                "CLICK_BATCH_INTERVAL_MS must be at most 10000, got {}",
+                self.click_batch_interval_ms
+            );
+        }
+
+        // Validate click journal
+        if self.click_journal_max_mb == 0 {
+            anyhow::bail!("CLICK_JOURNAL_MAX_MB must be at least 1");
+        }
+
+        if self.click_journal_replay_seconds == 0 {
+            anyhow::bail!("CLICK_JOURNAL_REPLAY_SECONDS must be at least 1");
+        }
+
+        if self.click_counter_flush_seconds == 0 {
+            anyhow::bail!("CLICK_COUNTER_FLUSH_SECONDS must be at least 1");
+        }
+
+        // Validate token signing secrets
+        if self.token_signing_secrets.is_empty() {
             anyhow::bail!("TOKEN_SIGNING_SECRET must not be empty");
         }
 
+        // Validate session lifetime (at most 30 days)
+        if self.session_ttl_hours == 0 || self.session_ttl_hours > 720 {
+            anyhow::bail!(
+                "SESSION_TTL_HOURS must be between 1 and 720, got {}",
+                self.session_ttl_hours
+            );
+        }
+
         // Validate pool settings
         if self.db_max_connections == 0 {
             anyhow::bail!("DB_MAX_CONNECTIONS must be at least 1");
@@ -304,6 +711,25 @@ impl Config {
             anyhow::bail!("DB_CONNECT_TIMEOUT must be greater than 0");
         }
 
+        // Validate OTLP endpoint (if present)
+        if let Some(ref endpoint) = self.otlp_endpoint
+            && !endpoint.starts_with("http://")
+            && !endpoint.starts_with("https://")
+        {
+            anyhow::bail!(
+                "OTEL_EXPORTER_OTLP_ENDPOINT must start with 'http://' or 'https://', got '{}'",
+                endpoint
+            );
+        }
+
+        if self.otel_service_name.is_empty() {
+            anyhow::bail!("OTEL_SERVICE_NAME must not be empty");
+        }
+
+        if let Some(oidc) = &self.oidc {
+            oidc.validate()?;
+        }
+
         Ok(())
     }
 
@@ -320,6 +746,26 @@ impl Config {
 
         if let Some(ref redis_url) = self.redis_url {
             tracing::info!("  Redis: {} (enabled)", mask_connection_string(redis_url));
+            if self.l1_cache_capacity > 0 {
+                tracing::info!(
+                    "  L1 cache: {} entries, {}s TTL",
+                    self.l1_cache_capacity,
+                    self.l1_cache_ttl_seconds
+                );
+            }
+            tracing::info!("  Negative cache TTL: {}s", self.negative_cache_ttl_seconds);
+            tracing::info!(
+                "  Cache refresh-ahead: {}s, lock wait: {}ms",
+                self.cache_refresh_ahead_seconds,
+                self.cache_lock_wait_ms
+            );
+            if self.cache_warmup_links > 0 {
+                tracing::info!(
+                    "  Cache warm-up: {} links clicked most in {} days",
+                    self.cache_warmup_links,
+                    self.cache_warmup_days
+                );
+            }
         } else {
             tracing::info!("  Redis: disabled");
         }
@@ -327,9 +773,84 @@ impl Config {
         tracing::info!("  Log level: {}", self.log_level);
         tracing::info!("  Log format: {}", self.log_format);
         tracing::info!("  Click queue capacity: {}", self.click_queue_capacity);
+        tracing::info!(
+            "  Click batches: up to {} events every {}ms",
+            self.click_batch_size,
+            self.click_batch_interval_ms
+        );
+        match &self.click_journal_path {
+            Some(path) => tracing::info!(
+                "  Click journal: {} (max {} MB, replayed every {}s)",
+                path,
+                self.click_journal_max_mb,
+                self.click_journal_replay_seconds
+            ),
+            None => tracing::info!("  Click journal: disabled"),
+        }
+        tracing::info!(
+            "  Click counter flush: {}s",
+            self.click_counter_flush_seconds
+        );
+        tracing::info!("  Domain refresh: {}s", self.domain_refresh_seconds);
+        tracing::info!(
+            "  Token signing secrets: {}",
+            self.token_signing_secrets.len()
+        );
+
+        if let Some(ref endpoint) = self.otlp_endpoint {
+            tracing::info!("  OTLP export: {} (enabled)", endpoint);
+        } else {
+            tracing::info!("  OTLP export: disabled");
+        }
+
+        tracing::info!(
+            "  Legacy /api sunset: {}",
+            self.api_legacy_sunset.to_rfc3339()
+        );
+
+        if let Some(ref oidc) = self.oidc {
+            tracing::info!("  Dashboard SSO: {} (enabled)", oidc.issuer_url);
+        } else {
+            tracing::info!("  Dashboard SSO: disabled");
+        }
     }
 }
 
+/// Splits a comma-separated `TOKEN_SIGNING_SECRET` value into secrets, newest first.
+///
+/// Whitespace around each secret and empty entries are ignored.
+pub fn parse_signing_secrets(value: &str) -> Vec<String> {
+    parse_list(value)
+}
+
+/// Parses `OIDC_ROLE_MAPPING`: comma-separated `group=role` pairs.
+///
+/// # Errors
+///
+/// Returns an error for an entry without `=` or with an unknown role.
+pub fn parse_role_mapping(value: &str) -> Result<Vec<(String, Role)>> {
+    parse_list(value)
+        .into_iter()
+        .map(|entry| {
+            let (group, role) = entry
+                .rsplit_once('=')
+                .with_context(|| format!("expected 'group=role', got '{entry}'"))?;
+            let role = role.trim().parse().map_err(anyhow::Error::msg)?;
+            Ok((group.trim().to_string(), role))
+        })
+        .collect()
+}
+
+/// Splits a comma-separated value, trimming entries and dropping empty ones.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 /// Masks sensitive information in connection strings for logging.
 ///
 /// Replaces password with `***` in URLs like:
@@ -405,12 +926,31 @@ mod tests {
             click_queue_capacity: 10_000,
             behind_proxy: false,
             cache_ttl_seconds: 3600,
+            l1_cache_capacity: 10_000,
+            l1_cache_ttl_seconds: 5,
+            negative_cache_ttl_seconds: 30,
+            cache_refresh_ahead_seconds: 60,
+            cache_lock_wait_ms: 0,
+            cache_warmup_links: 0,
+            cache_warmup_days: 7,
+            domain_refresh_seconds: 60,
             click_worker_concurrency: 4,
-            token_signing_secret: "test-secret".to_string(),
+            click_batch_size: 100,
+            click_batch_interval_ms: 50,
+            click_journal_path: None,
+            click_journal_max_mb: 1024,
+            click_journal_replay_seconds: 10,
+            click_counter_flush_seconds: 10,
+            token_signing_secrets: vec!["test-secret".to_string()],
+            session_ttl_hours: 12,
             db_max_connections: 10,
             db_connect_timeout: 30,
             db_idle_timeout: 600,
             db_max_lifetime: 1800,
+            otlp_endpoint: None,
+            otel_service_name: "url-shortener".to_string(),
+            api_legacy_sunset: DateTime::from_timestamp(DEFAULT_API_LEGACY_SUNSET, 0).unwrap(),
+            oidc: None,
         };
 
         assert!(config.validate().is_ok());
@@ -555,12 +1095,31 @@ mod tests {
             click_queue_capacity: 10_000,
             behind_proxy: false,
             cache_ttl_seconds: 3600,
+            l1_cache_capacity: 10_000,
+            l1_cache_ttl_seconds: 5,
+            negative_cache_ttl_seconds: 30,
+            cache_refresh_ahead_seconds: 60,
+            cache_lock_wait_ms: 0,
+            cache_warmup_links: 0,
+            cache_warmup_days: 7,
+            domain_refresh_seconds: 60,
             click_worker_concurrency: 4,
-            token_signing_secret: "test-secret".to_string(),
+            click_batch_size: 100,
+            click_batch_interval_ms: 50,
+            click_journal_path: None,
+            click_journal_max_mb: 1024,
+            click_journal_replay_seconds: 10,
+            click_counter_flush_seconds: 10,
+            token_signing_secrets: vec!["test-secret".to_string()],
+            session_ttl_hours: 12,
             db_max_connections: 10,
             db_connect_timeout: 30,
             db_idle_timeout: 600,
             db_max_lifetime: 1800,
+            otlp_endpoint: None,
+            otel_service_name: "url-shortener".to_string(),
+            api_legacy_sunset: DateTime::from_timestamp(DEFAULT_API_LEGACY_SUNSET, 0).unwrap(),
+            oidc: None,
         }
     }
 
@@ -595,6 +1154,63 @@ mod tests {
         assert!(c.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_l1_cache_ttl_bounds() {
+        let mut c = base_config();
+        c.l1_cache_ttl_seconds = 0;
+        assert!(c.validate().is_err());
+
+        c.l1_cache_ttl_seconds = 301;
+        assert!(c.validate().is_err());
+
+        c.l1_cache_capacity = 0;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_negative_cache_ttl_bound() {
+        let mut c = base_config();
+        c.negative_cache_ttl_seconds = 3601;
+        assert!(c.validate().is_err());
+
+        c.negative_cache_ttl_seconds = 0;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_refresh_ahead_below_cache_ttl() {
+        let mut c = base_config();
+        c.cache_refresh_ahead_seconds = 3600;
+        assert!(c.validate().is_err());
+
+        c.cache_refresh_ahead_seconds = 0;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_cache_lock_wait_bound() {
+        let mut c = base_config();
+        c.cache_lock_wait_ms = 5001;
+        assert!(c.validate().is_err());
+
+        c.cache_lock_wait_ms = 200;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_cache_warmup_bounds() {
+        let mut c = base_config();
+        c.cache_warmup_links = 100_001;
+        assert!(c.validate().is_err());
+
+        c.cache_warmup_links = 500;
+        c.cache_warmup_days = 0;
+        assert!(c.validate().is_err());
+
+        c.cache_warmup_days = 1;
+        assert!(c.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_click_worker_concurrency_bounds() {
         let mut c = base_config();
@@ -611,11 +1227,70 @@ mod tests {
         assert!(c.validate().is_ok());
     }
 
+    #[test]
+    fn test_validate_click_batch_bounds() {
+        let mut c = base_config();
+        c.click_batch_size = 0;
+        assert!(c.validate().is_err());
+
+        c.click_batch_size = 10_001;
+        assert!(c.validate().is_err());
+
+        c.click_batch_size = 1;
+        c.click_batch_interval_ms = 10_001;
+        assert!(c.validate().is_err());
+
+        c.click_batch_interval_ms = 0;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_click_journal_settings() {
+        let mut c = base_config();
+        c.click_journal_max_mb = 0;
+        assert!(c.validate().is_err());
+
+        c.click_journal_max_mb = 1;
+        c.click_journal_replay_seconds = 0;
+        assert!(c.validate().is_err());
+
+        c.click_journal_replay_seconds = 1;
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_click_counter_flush_interval() {
+        let mut c = base_config();
+        c.click_counter_flush_seconds = 0;
+        assert!(c.validate().is_err());
+
+        c.click_counter_flush_seconds = 1;
+        assert!(c.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_empty_token_signing_secret() {
         let mut c = base_config();
-        c.token_signing_secret = "".to_string();
+        c.token_signing_secrets = Vec::new();
+        assert!(c.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_signing_secrets() {
+        assert_eq!(parse_signing_secrets("only"), vec!["only"]);
+        assert_eq!(parse_signing_secrets(" new , old,"), vec!["new", "old"]);
+        assert!(parse_signing_secrets(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_validate_session_ttl_bounds() {
+        let mut c = base_config();
+        c.session_ttl_hours = 0;
+        assert!(c.validate().is_err());
+        c.session_ttl_hours = 721;
         assert!(c.validate().is_err());
+        c.session_ttl_hours = 720;
+        assert!(c.validate().is_ok());
     }
 
     #[test]
@@ -632,6 +1307,26 @@ mod tests {
         assert!(c.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_otlp_endpoint_format() {
+        let mut c = base_config();
+        c.otlp_endpoint = Some("http://localhost:4318".to_string());
+        assert!(c.validate().is_ok());
+
+        c.otlp_endpoint = Some("https://otel.example.com".to_string());
+        assert!(c.validate().is_ok());
+
+        c.otlp_endpoint = Some("grpc://localhost:4317".to_string());
+        assert!(c.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_empty_otel_service_name() {
+        let mut c = base_config();
+        c.otel_service_name = "".to_string();
+        assert!(c.validate().is_err());
+    }
+
     #[test]
     fn test_is_cache_enabled() {
         let mut c = base_config();
@@ -640,4 +1335,92 @@ mod tests {
         c.redis_url = Some("redis://localhost:6379/0".to_string());
         assert!(c.is_cache_enabled());
     }
+
+    fn oidc_config() -> OidcConfig {
+        OidcConfig {
+            issuer_url: "https://idp.example.com".to_string(),
+            client_id: "shortener".to_string(),
+            client_secret: None,
+            redirect_url: "https://s.example.com/dashboard/oidc/callback".to_string(),
+            scopes: vec!["openid".to_string(), "email".to_string()],
+            allowed_email_domains: vec!["example.com".to_string()],
+            allowed_groups: vec![],
+            groups_claim: "groups".to_string(),
+            role_mapping: vec![],
+            default_role: Role::Viewer,
+        }
+    }
+
+    #[test]
+    fn test_parse_role_mapping() {
+        let mapping = parse_role_mapping("sre = admin, marketing=editor,").unwrap();
+        assert_eq!(
+            mapping,
+            vec![
+                ("sre".to_string(), Role::Admin),
+                ("marketing".to_string(), Role::Editor)
+            ]
+        );
+        assert!(parse_role_mapping("sre").is_err());
+        assert!(parse_role_mapping("sre=owner").is_err());
+    }
+
+    #[test]
+    fn test_validate_oidc() {
+        let mut c = base_config();
+        c.oidc = Some(oidc_config());
+        assert!(c.validate().is_ok());
+
+        let mut oidc = oidc_config();
+        oidc.allowed_email_domains.clear();
+        c.oidc = Some(oidc);
+        assert!(c.validate().is_err(), "nobody allowed");
+
+        let mut oidc = oidc_config();
+        oidc.scopes = vec!["email".to_string()];
+        c.oidc = Some(oidc);
+        assert!(c.validate().is_err(), "openid scope missing");
+
+        let mut oidc = oidc_config();
+        oidc.redirect_url = "/dashboard/oidc/callback".to_string();
+        c.oidc = Some(oidc);
+        assert!(c.validate().is_err(), "relative redirect URL");
+    }
+
+    #[test]
+    #[serial]
+    fn test_oidc_from_env() {
+        // SAFETY: Tests are run serially
+        unsafe {
+            env::remove_var("OIDC_ISSUER_URL");
+        }
+        assert!(OidcConfig::from_env().unwrap().is_none());
+
+        unsafe {
+            env::set_var("OIDC_ISSUER_URL", "https://idp.example.com");
+            env::set_var("OIDC_CLIENT_ID", "shortener");
+            env::set_var(
+                "OIDC_REDIRECT_URL",
+                "https://s.example.com/dashboard/oidc/callback",
+            );
+            env::set_var("OIDC_ALLOWED_EMAIL_DOMAINS", "Example.COM");
+            env::set_var("OIDC_ROLE_MAPPING", "sre=admin");
+        }
+
+        let oidc = OidcConfig::from_env().unwrap().unwrap();
+        assert_eq!(oidc.scopes, vec!["openid", "email", "profile"]);
+        assert_eq!(oidc.allowed_email_domains, vec!["example.com"]);
+        assert_eq!(oidc.role_mapping, vec![("sre".to_string(), Role::Admin)]);
+        assert_eq!(oidc.default_role, Role::Viewer);
+        assert_eq!(oidc.groups_claim, "groups");
+
+        // Cleanup
+        unsafe {
+            env::remove_var("OIDC_ISSUER_URL");
+            env::remove_var("OIDC_CLIENT_ID");
+            env::remove_var("OIDC_REDIRECT_URL");
+            env::remove_var("OIDC_ALLOWED_EMAIL_DOMAINS");
+            env::remove_var("OIDC_ROLE_MAPPING");
+        }
+    }
 }
@@ -0,0 +1,163 @@
+//! Click counters in `links.clicks`, caught up with `link_clicks` periodically.
+//!
+//! The click worker notes in [`UnflushedClicks`] how many clicks it recorded,
+//! and [`run_counter_flush`] periodically adds the clicks recorded since the
+//! last flush to the `links.clicks` column with a single statement. List views read
+//! that column instead of counting `link_clicks`.
+//!
+//! A flush counts the `link_clicks` rows above a stored watermark, the highest
+//! click ID already counted, and moves the watermark past them in the same
+//! transaction. Servers flushing at the same time, `admin stats reconcile`
+//! while servers run, or a crash before a flush therefore neither count a click
+//! twice nor lose it: the next flush, by any server, counts what a stopped one
+//! left behind.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::domain::repositories::StatsRepository;
+use crate::error::AppError;
+
+/// Number of clicks this process recorded since its last flush.
+///
+/// Only decides whether a flush is due; it holds no per-link counts. The flush
+/// itself counts the clicks in `link_clicks`, including those recorded by
+/// other servers.
+#[derive(Default)]
+pub struct UnflushedClicks {
+    pending: AtomicU64,
+    /// Set by the first successful flush; until then a flush runs even without
+    /// clicks, to count the ones a previous process left behind.
+    flushed: AtomicBool,
+}
+
+impl UnflushedClicks {
+    /// Creates a tracker with no unflushed clicks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notes that `clicks` more clicks were recorded.
+    pub fn add(&self, clicks: u64) {
+        self.pending.fetch_add(clicks, Ordering::Relaxed);
+    }
+
+    /// Number of clicks recorded since the last flush.
+    pub fn len(&self) -> u64 {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if no clicks were recorded since the last flush.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds the clicks recorded since the last flush, by any server, to
+    /// `links.clicks`.
+    ///
+    /// Skipped when this process recorded nothing since its last flush.
+    /// Returns the number of links updated. On failure the clicks stay
+    /// unflushed, so the next flush runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    pub async fn flush<S: StatsRepository>(&self, repository: &S) -> Result<u64, AppError> {
+        let pending = self.pending.swap(0, Ordering::Relaxed);
+        if pending == 0 && self.flushed.load(Ordering::Relaxed) {
+            return Ok(0);
+        }
+
+        match repository.flush_link_clicks().await {
+            Ok(links) => {
+                self.flushed.store(true, Ordering::Relaxed);
+                metrics::counter!("click_counters_flushed_total").increment(links);
+                Ok(links)
+            }
+            Err(e) => {
+                self.pending.fetch_add(pending, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Flushes `unflushed` now and every `interval` afterwards.
+///
+/// Runs until the process exits; the server flushes once more after the click
+/// worker has drained.
+///
+/// # Metrics
+///
+/// - `click_counters_flushed_total` - link counters written
+/// - `click_counter_flush_failures_total` - flushes that failed and were retried later
+pub async fn run_counter_flush<S: StatsRepository>(
+    unflushed: Arc<UnflushedClicks>,
+    repository: Arc<S>,
+    interval: Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        // The first tick completes immediately, catching up with clicks a
+        // previous process recorded but didn't flush.
+        interval.tick().await;
+        if let Err(e) = unflushed.flush(repository.as_ref()).await {
+            metrics::counter!("click_counter_flush_failures_total").increment(1);
+            tracing::warn!("Failed to flush click counters: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::MockStatsRepository;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_flush_runs_once_per_recorded_clicks() {
+        let unflushed = UnflushedClicks::new();
+        unflushed.add(4);
+
+        let mut repo = MockStatsRepository::new();
+        repo.expect_flush_link_clicks().times(1).returning(|| Ok(2));
+
+        assert_eq!(unflushed.flush(&repo).await.unwrap(), 2);
+        assert!(unflushed.is_empty());
+        // Nothing recorded since: no second write.
+        assert_eq!(unflushed.flush(&repo).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_first_flush_runs_without_clicks() {
+        let unflushed = UnflushedClicks::new();
+
+        let mut repo = MockStatsRepository::new();
+        repo.expect_flush_link_clicks().times(1).returning(|| Ok(3));
+
+        assert_eq!(unflushed.flush(&repo).await.unwrap(), 3);
+        assert_eq!(unflushed.flush(&repo).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_clicks_unflushed() {
+        let unflushed = UnflushedClicks::new();
+        unflushed.add(2);
+
+        let mut failing = MockStatsRepository::new();
+        failing
+            .expect_flush_link_clicks()
+            .times(1)
+            .returning(|| Err(AppError::internal("connection refused", json!({}))));
+        assert!(unflushed.flush(&failing).await.is_err());
+        assert_eq!(unflushed.len(), 2);
+
+        unflushed.add(1);
+        let mut repo = MockStatsRepository::new();
+        repo.expect_flush_link_clicks().times(1).returning(|| Ok(1));
+        assert_eq!(unflushed.flush(&repo).await.unwrap(), 1);
+        assert!(unflushed.is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 //! Click event model for asynchronous click tracking.
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
 /// An in-memory representation of a click event for async processing.
 ///
 /// Used to pass click information from HTTP handlers to the background worker
@@ -9,22 +12,38 @@
 /// # Design
 ///
 /// - Contains denormalized data (domain name + code) to avoid lookups in handlers
+/// - Carries the link ID when the redirect knew it, so the worker can skip
+///   resolving domain and code
 /// - All client metadata is optional to handle missing headers gracefully
-/// - Cloneable for sending across async boundaries
+/// - Carries the W3C `traceparent` of the originating request so the worker's
+///   processing span joins the same trace
+/// - Records when the click happened, so events written late (e.g. replayed from
+///   the [`crate::domain::click_journal`]) keep their time
+/// - Cloneable for sending across async boundaries; serializable for the journal,
+///   which leaves out the trace context
 ///
 /// # Usage Flow
 ///
 /// 1. Created in redirect handler with request metadata
 /// 2. Sent to channel (non-blocking)
-/// 3. Processed by [`crate::domain::click_worker::run_click_worker`]
+/// 3. Batched and processed by [`crate::domain::click_worker::run_click_worker`]
 /// 4. Converted to [`crate::domain::entities::NewClick`] for persistence
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClickEvent {
     pub domain: String,
     pub code: String,
+    /// ID of the clicked link; `None` if the redirect was served from a legacy
+    /// cache entry without it.
+    #[serde(default)]
+    pub link_id: Option<i64>,
     pub user_agent: Option<String>,
     pub referer: Option<String>,
     pub ip: Option<String>,
+    /// When the redirect was served.
+    pub clicked_at: DateTime<Utc>,
+    /// `traceparent` of the request that produced the event, if it was traced.
+    #[serde(skip)]
+    pub trace_context: Option<String>,
 }
 
 impl ClickEvent {
@@ -38,6 +57,9 @@ impl ClickEvent {
     /// - `user_agent` - Optional User-Agent header
     /// - `referer` - Optional Referer header
     ///
+    /// The trace context is captured from the current span (see
+    /// [`crate::telemetry::current_traceparent`]).
+    ///
     /// # Examples
     ///
     /// ```ignore
@@ -59,11 +81,20 @@ impl ClickEvent {
         Self {
             domain,
             code,
+            link_id: None,
             ip,
             user_agent: user_agent.map(|s| s.to_string()),
             referer: referer.map(|s| s.to_string()),
+            clicked_at: Utc::now(),
+            trace_context: crate::telemetry::current_traceparent(),
         }
     }
+
+    /// Sets the ID of the clicked link.
+    pub fn with_link_id(mut self, link_id: Option<i64>) -> Self {
+        self.link_id = link_id;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -85,6 +116,15 @@ mod tests {
         assert_eq!(event.ip, Some("192.168.1.1".to_string()));
         assert_eq!(event.user_agent, Some("Mozilla/5.0".to_string()));
         assert_eq!(event.referer, Some("https://google.com".to_string()));
+        assert!(event.link_id.is_none());
+    }
+
+    #[test]
+    fn test_click_event_with_link_id() {
+        let event = ClickEvent::new("s.com".to_string(), "code1".to_string(), None, None, None)
+            .with_link_id(Some(42));
+
+        assert_eq!(event.link_id, Some(42));
     }
 
     #[test]
@@ -102,6 +142,7 @@ mod tests {
         assert!(event.ip.is_none());
         assert!(event.user_agent.is_none());
         assert!(event.referer.is_none());
+        assert!(event.trace_context.is_none());
     }
 
     #[test]
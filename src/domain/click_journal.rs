@@ -0,0 +1,528 @@
+//! Append-only local journal for click events that could not be written in time.
+//!
+//! Click events normally travel from the redirect handler to the worker over an
+//! in-memory channel. When the channel is full, or the worker gives up on a batch
+//! because the database is unavailable, the events are appended to the journal
+//! instead, one JSON object per line. The worker replays the journal on startup
+//! and periodically afterwards (see [`crate::domain::click_worker::run_journal_replay`]).
+//!
+//! # Files
+//!
+//! - `<path>` - the active journal new events are appended to
+//! - `<path>.replay` - events taken out of the active journal for replay
+//! - `<path>.replay.offset` - bytes of the replay file already recorded
+//!
+//! A replay first renames the active journal, so events spilled during the replay
+//! go to a fresh file. The offset is saved after each recorded batch; a crash
+//! between recording a batch and saving its offset replays that batch again.
+//!
+//! # Durability
+//!
+//! Spilled events are handed to a writer thread, so neither the redirect handler
+//! nor the worker waits for the disk. The thread appends everything queued since
+//! its last write and then syncs the file, so events it has written survive a
+//! crash of the machine. Events still waiting in the click queue or in the
+//! writer's queue when the process dies are lost.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::domain::click_event::ClickEvent;
+
+/// Spills waiting for the writer thread; further spills are dropped.
+const WRITER_QUEUE_CAPACITY: usize = 10_000;
+
+/// Errors appending to or replaying the journal.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("click journal is full ({max_bytes} bytes)")]
+    Full { max_bytes: u64 },
+
+    #[error("click journal I/O failed: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("click event could not be encoded: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+/// The active journal file and its size.
+struct ActiveFile {
+    file: File,
+    len: u64,
+}
+
+/// The journal's files, shared with the writer thread.
+struct JournalFiles {
+    path: PathBuf,
+    max_bytes: u64,
+    active: Mutex<ActiveFile>,
+}
+
+/// Work for the writer thread.
+enum WriterRequest {
+    Spill {
+        events: Vec<ClickEvent>,
+        reason: &'static str,
+    },
+    /// Answered once everything queued before it is written and synced.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Append-only journal of click events on local disk.
+pub struct ClickJournal {
+    files: Arc<JournalFiles>,
+    writer: mpsc::Sender<WriterRequest>,
+}
+
+impl ClickJournal {
+    /// Opens or creates the journal at `path` and starts its writer thread.
+    ///
+    /// Appends are refused once the active file reaches `max_bytes`. A line cut
+    /// short by a crash is terminated, so it can't corrupt the next one. The
+    /// writer thread stops once the journal is dropped and its queue is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be opened or created, or the thread
+    /// can't be started.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.into();
+        let mut file = open_append(&path)?;
+        let mut len = file.metadata()?.len();
+
+        if len > 0 && !ends_with_newline(&path, len)? {
+            file.write_all(b"\n")?;
+            len += 1;
+        }
+
+        let files = Arc::new(JournalFiles {
+            path,
+            max_bytes,
+            active: Mutex::new(ActiveFile { file, len }),
+        });
+        let (writer, requests) = mpsc::channel(WRITER_QUEUE_CAPACITY);
+        let writer_files = files.clone();
+        std::thread::Builder::new()
+            .name("click-journal".to_string())
+            .spawn(move || run_writer(&writer_files, requests))?;
+
+        Ok(Self { files, writer })
+    }
+
+    /// Appends `events`, one line each, and syncs the file.
+    ///
+    /// Blocks until the events are on disk; async code uses [`spill`](Self::spill).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JournalError::Full`] if the events don't fit within the size
+    /// limit; nothing is written then.
+    /// Returns [`JournalError::Io`] if the write fails.
+    pub fn append(&self, events: &[ClickEvent]) -> Result<(), JournalError> {
+        self.files.write(events)?;
+        self.files.sync()?;
+        Ok(())
+    }
+
+    /// Queues events that could not be queued or written for the writer thread,
+    /// without waiting for the disk.
+    ///
+    /// Returns `false` if the events were dropped because the writer's queue is
+    /// full or the writer stopped. The writer counts the final outcome.
+    ///
+    /// # Metrics
+    ///
+    /// - `click_events_spilled_total{reason}` - events appended
+    /// - `click_events_dropped_total{reason="journal_busy"|"journal_full"|"journal_error"}` - events lost
+    pub fn spill(&self, events: &[ClickEvent], reason: &'static str) -> bool {
+        let request = WriterRequest::Spill {
+            events: events.to_vec(),
+            reason,
+        };
+        if self.writer.try_send(request).is_ok() {
+            return true;
+        }
+
+        metrics::counter!("click_events_dropped_total", "reason" => "journal_busy")
+            .increment(events.len() as u64);
+        tracing::error!(
+            events = events.len(),
+            reason,
+            "Click journal writer busy, dropping click events"
+        );
+        false
+    }
+
+    /// Waits until every event spilled so far is written and synced.
+    ///
+    /// The server calls it on shutdown, after the click worker has drained.
+    pub async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        if self.writer.send(WriterRequest::Flush(done)).await.is_ok() {
+            written.await.ok();
+        }
+    }
+
+    /// Size of the active journal in bytes.
+    pub fn len(&self) -> u64 {
+        self.files.active().len
+    }
+
+    /// Returns `true` if no events are waiting in the active journal.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the events waiting for replay, if any.
+    ///
+    /// Continues an unfinished replay first. Otherwise moves the active journal
+    /// aside for replay and starts a new one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files can't be read, renamed or created.
+    pub fn take_pending(&self) -> io::Result<Option<PendingReplay>> {
+        let path = &self.files.path;
+        let replay_path = with_suffix(path, ".replay");
+        let offset_path = with_suffix(path, ".replay.offset");
+
+        if !replay_path.exists() {
+            let mut active = self.files.active();
+            if active.len == 0 {
+                return Ok(None);
+            }
+            fs::rename(path, &replay_path)?;
+            // Drop a checkpoint left behind by a replay that finished without
+            // removing it, so the new file is read from the start.
+            remove_if_exists(&offset_path)?;
+            *active = ActiveFile {
+                file: open_append(path)?,
+                len: 0,
+            };
+        }
+
+        let offset = match fs::read_to_string(&offset_path) {
+            Ok(saved) => saved.trim().parse().unwrap_or(0),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        PendingReplay::open(replay_path, offset_path, offset).map(Some)
+    }
+}
+
+impl JournalFiles {
+    fn active(&self) -> std::sync::MutexGuard<'_, ActiveFile> {
+        self.active.lock().expect("click journal lock poisoned")
+    }
+
+    /// Appends `events` without syncing; nothing is written if they don't fit.
+    fn write(&self, events: &[ClickEvent]) -> Result<(), JournalError> {
+        let mut lines = Vec::new();
+        for event in events {
+            serde_json::to_writer(&mut lines, event)?;
+            lines.push(b'\n');
+        }
+
+        let mut active = self.active();
+        if active.len + lines.len() as u64 > self.max_bytes {
+            return Err(JournalError::Full {
+                max_bytes: self.max_bytes,
+            });
+        }
+        active.file.write_all(&lines)?;
+        active.len += lines.len() as u64;
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.active().file.sync_data()
+    }
+}
+
+/// Writes spilled events until every [`ClickJournal`] handle is dropped.
+///
+/// Takes everything queued at once, writes it and syncs the file a single
+/// time, so a burst of spills costs one sync.
+fn run_writer(files: &JournalFiles, mut requests: mpsc::Receiver<WriterRequest>) {
+    while let Some(first) = requests.blocking_recv() {
+        let mut flushes = Vec::new();
+        let mut written = Vec::new();
+        let mut next = Some(first);
+
+        while let Some(request) = next {
+            match request {
+                WriterRequest::Spill { events, reason } => match files.write(&events) {
+                    Ok(()) => written.push((events.len(), reason)),
+                    Err(e) => {
+                        let dropped = match e {
+                            JournalError::Full { .. } => "journal_full",
+                            _ => "journal_error",
+                        };
+                        metrics::counter!("click_events_dropped_total", "reason" => dropped)
+                            .increment(events.len() as u64);
+                        tracing::error!(error = %e, events = events.len(), reason, "Failed to spill click events");
+                    }
+                },
+                WriterRequest::Flush(done) => flushes.push(done),
+            }
+            next = requests.try_recv().ok();
+        }
+
+        if !written.is_empty()
+            && let Err(e) = files.sync()
+        {
+            tracing::error!(error = %e, "Failed to sync click journal");
+        }
+        for (events, reason) in written {
+            metrics::counter!("click_events_spilled_total", "reason" => reason)
+                .increment(events as u64);
+            tracing::debug!(events, reason, "Click events spilled to journal");
+        }
+        for done in flushes {
+            done.send(()).ok();
+        }
+    }
+}
+
+/// Events taken out of the journal, read in batches.
+pub struct PendingReplay {
+    path: PathBuf,
+    offset_path: PathBuf,
+    reader: BufReader<File>,
+    offset: u64,
+}
+
+impl PendingReplay {
+    fn open(path: PathBuf, offset_path: PathBuf, offset: u64) -> io::Result<Self> {
+        let mut file = File::open(&path)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        Ok(Self {
+            path,
+            offset_path,
+            reader: BufReader::new(file),
+            offset,
+        })
+    }
+
+    /// Reads up to `limit` events after the current position.
+    ///
+    /// Returns the events and the offset to [`commit`](Self::commit) once they are
+    /// recorded. Lines that aren't valid events are skipped. An empty batch means
+    /// the replay is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read.
+    pub fn next_batch(&mut self, limit: usize) -> io::Result<(Vec<ClickEvent>, u64)> {
+        let mut events = Vec::with_capacity(limit);
+        let mut offset = self.offset;
+        let mut line = String::new();
+
+        while events.len() < limit {
+            line.clear();
+            let read = self.reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            offset += read as u64;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(event) => events.push(event),
+                Err(e) => {
+                    metrics::counter!("click_journal_corrupt_total").increment(1);
+                    tracing::warn!(error = %e, "Skipping unreadable click journal line");
+                }
+            }
+        }
+
+        Ok((events, offset))
+    }
+
+    /// Records that the events before `offset` are persisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the offset can't be saved.
+    pub fn commit(&mut self, offset: u64) -> io::Result<()> {
+        fs::write(&self.offset_path, offset.to_string())?;
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Removes the replayed file and its offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files can't be removed.
+    pub fn finish(self) -> io::Result<()> {
+        fs::remove_file(&self.path)?;
+        remove_if_exists(&self.offset_path)
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn ends_with_newline(path: &Path, len: u64) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(len - 1))?;
+    let mut last = [0u8; 1];
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A journal path in a fresh temporary directory, removed when dropped.
+    fn journal_path() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("clicks.journal");
+        (dir, path)
+    }
+
+    fn event(code: &str) -> ClickEvent {
+        ClickEvent::new(
+            "s.example.com".to_string(),
+            code.to_string(),
+            Some("10.0.0.1".to_string()),
+            Some("Mozilla/5.0"),
+            None,
+        )
+        .with_link_id(Some(7))
+    }
+
+    #[test]
+    fn test_append_and_replay_in_batches() {
+        let (_dir, path) = journal_path();
+        let journal = ClickJournal::open(&path, 1 << 20).unwrap();
+        journal
+            .append(&[event("a"), event("b"), event("c")])
+            .unwrap();
+
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        assert!(journal.is_empty());
+
+        let (first, offset) = pending.next_batch(2).unwrap();
+        assert_eq!(
+            first.iter().map(|e| e.code.as_str()).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(first[0].link_id, Some(7));
+        assert_eq!(first[0].ip.as_deref(), Some("10.0.0.1"));
+        pending.commit(offset).unwrap();
+
+        let (rest, offset) = pending.next_batch(2).unwrap();
+        assert_eq!(rest.len(), 1);
+        pending.commit(offset).unwrap();
+        assert!(pending.next_batch(2).unwrap().0.is_empty());
+
+        pending.finish().unwrap();
+        assert!(journal.take_pending().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unfinished_replay_resumes_after_committed_offset() {
+        let (_dir, path) = journal_path();
+        let journal = ClickJournal::open(&path, 1 << 20).unwrap();
+        journal.append(&[event("a"), event("b")]).unwrap();
+
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        let (_, offset) = pending.next_batch(1).unwrap();
+        pending.commit(offset).unwrap();
+        drop(pending);
+
+        // Spilled during the replay: waits for the next one.
+        journal.append(&[event("c")]).unwrap();
+
+        let reopened = ClickJournal::open(&path, 1 << 20).unwrap();
+        let mut pending = reopened.take_pending().unwrap().unwrap();
+        let (events, _) = pending.next_batch(10).unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.code.as_str()).collect::<Vec<_>>(),
+            ["b"]
+        );
+        pending.finish().unwrap();
+
+        let mut pending = reopened.take_pending().unwrap().unwrap();
+        assert_eq!(pending.next_batch(10).unwrap().0[0].code, "c");
+    }
+
+    #[tokio::test]
+    async fn test_spilled_events_are_written_by_flush() {
+        let (_dir, path) = journal_path();
+        let journal = ClickJournal::open(&path, 1 << 20).unwrap();
+
+        assert!(journal.spill(&[event("a")], "queue_full"));
+        assert!(journal.spill(&[event("b")], "queue_full"));
+        journal.flush().await;
+
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        let (events, _) = pending.next_batch(10).unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.code.as_str()).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spill_beyond_size_limit_is_dropped() {
+        let (_dir, path) = journal_path();
+        let journal = ClickJournal::open(&path, 10).unwrap();
+
+        // Queued, then refused by the writer.
+        assert!(journal.spill(&[event("a")], "queue_full"));
+        journal.flush().await;
+
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn test_append_refused_when_full() {
+        let (_dir, path) = journal_path();
+        let journal = ClickJournal::open(&path, 10).unwrap();
+
+        let result = journal.append(&[event("a")]);
+
+        assert!(matches!(result, Err(JournalError::Full { max_bytes: 10 })));
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_line_is_skipped() {
+        let (_dir, path) = journal_path();
+        fs::write(&path, br#"{"domain":"s.example.com","co"#).unwrap();
+
+        let journal = ClickJournal::open(&path, 1 << 20).unwrap();
+        journal.append(&[event("a")]).unwrap();
+
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        let (events, _) = pending.next_batch(10).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].code, "a");
+    }
+}
@@ -1,16 +1,97 @@
 //! Background worker for processing click events asynchronously.
-
-use serde_json::json;
+//!
+//! Events are written in batches: the worker collects up to `batch_size` events,
+//! or whatever arrived within `batch_interval` of the first one, resolves their
+//! links with one lookup per domain and one link query, and records them with a
+//! single multi-row INSERT.
+//!
+//! With a [`ClickJournal`], batches the database could not take are spilled to
+//! the journal instead of being dropped, and [`run_journal_replay`] writes them
+//! back once the database is reachable and the queue has room.
+//!
+//! With [`UnflushedClicks`], the worker also notes how many clicks it recorded,
+//! so the counter flush knows there are clicks to add to `links.clicks`.
+
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::task::JoinSet;
+use tokio::time::Instant;
 use tokio_retry::RetryIf;
 use tokio_retry::strategy::ExponentialBackoff;
+use tracing::Instrument;
 
+use crate::domain::click_counters::UnflushedClicks;
 use crate::domain::click_event::ClickEvent;
+use crate::domain::click_journal::ClickJournal;
 use crate::domain::entities::NewClick;
 use crate::domain::repositories::{DomainRepository, LinkRepository, StatsRepository};
 use crate::error::AppError;
+use crate::telemetry;
+
+/// Default maximum number of events written by one INSERT.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Default time the first event of a batch waits for more events.
+pub const DEFAULT_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Retries of a batch after a transient error.
+const MAX_RETRIES: usize = 6;
+
+/// Concurrency, batching, journal and counter settings of [`run_click_worker`].
+#[derive(Clone)]
+pub struct ClickWorkerConfig {
+    /// Maximum number of batches written concurrently.
+    pub concurrency: usize,
+    /// Maximum number of events written by one INSERT.
+    pub batch_size: usize,
+    /// How long the first event of a batch waits for more events.
+    pub batch_interval: Duration,
+    /// Journal that failed batches are spilled to; `None` drops them.
+    pub journal: Option<Arc<ClickJournal>>,
+    /// How often [`run_journal_replay`] checks the journal.
+    pub replay_interval: Duration,
+    /// Where recorded clicks are noted for the counter flush; `None` doesn't note them.
+    pub unflushed_clicks: Option<Arc<UnflushedClicks>>,
+    /// Retries of a batch after a transient error.
+    pub(crate) max_retries: usize,
+}
+
+impl ClickWorkerConfig {
+    /// Creates settings with the default batch size and interval and no journal.
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            concurrency,
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_interval: DEFAULT_BATCH_INTERVAL,
+            journal: None,
+            replay_interval: Duration::from_secs(10),
+            unflushed_clicks: None,
+            max_retries: MAX_RETRIES,
+        }
+    }
+
+    /// Sets the maximum batch size and how long a batch waits to fill up.
+    pub fn with_batching(mut self, batch_size: usize, batch_interval: Duration) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    /// Spills failed batches to `journal` and replays it every `replay_interval`.
+    pub fn with_journal(mut self, journal: Arc<ClickJournal>, replay_interval: Duration) -> Self {
+        self.journal = Some(journal);
+        self.replay_interval = replay_interval;
+        self
+    }
+
+    /// Notes the recorded clicks in `unflushed_clicks`.
+    pub fn with_unflushed_clicks(mut self, unflushed_clicks: Arc<UnflushedClicks>) -> Self {
+        self.unflushed_clicks = Some(unflushed_clicks);
+        self
+    }
+}
 
 /// Returns `true` for transient errors that are worth retrying (e.g. DB connection issues).
 ///
@@ -19,71 +100,142 @@ fn is_transient_error(e: &AppError) -> bool {
     matches!(e, AppError::Internal { .. })
 }
 
-/// Persists a single click event, resolving domain → link → click record.
+/// Creates the `click.batch` span for a batch of events.
 ///
-/// Retries up to 6 times with exponential backoff (100 ms → 3.2 s) on transient errors.
-/// Permanent errors (domain/link not found) are logged and discarded immediately.
-///
-/// # Metrics
+/// The span is linked to the trace of every redirect request in the batch (via
+/// [`ClickEvent::trace_context`]), so each request's trace leads to the
+/// repository spans that recorded its click.
+fn batch_span(events: &[ClickEvent]) -> tracing::Span {
+    let span = tracing::info_span!("click.batch", events = events.len());
+    for traceparent in events.iter().filter_map(|e| e.trace_context.as_deref()) {
+        telemetry::add_link_from_traceparent(&span, traceparent);
+    }
+    span
+}
+
+/// Looks up the IDs of the links clicked in `events`.
 ///
-/// - `click_worker_processed_total` - incremented on success
-/// - `click_worker_retried_total`   - incremented on each retry attempt
-/// - `click_worker_failed_total`    - incremented after exhausting all retries
-/// - `click_worker_dropped_total`   - incremented when the event is discarded
-async fn process_click<S, D, L>(
-    event: ClickEvent,
-    stats_repository: Arc<S>,
-    domain_repository: Arc<D>,
-    link_repository: Arc<L>,
-) where
+/// Events carrying a link ID skip the lookup. The others are resolved with one
+/// domain lookup per distinct domain name and a single query for all links.
+/// The result is in the order of `events`; `None` marks events whose domain or
+/// link doesn't exist.
+async fn resolve_link_ids<D, L>(
+    events: &[ClickEvent],
+    domain_repo: &D,
+    link_repo: &L,
+) -> Result<Vec<Option<i64>>, AppError>
+where
+    D: DomainRepository,
+    L: LinkRepository,
+{
+    let unresolved: Vec<&ClickEvent> = events.iter().filter(|e| e.link_id.is_none()).collect();
+    if unresolved.is_empty() {
+        return Ok(events.iter().map(|e| e.link_id).collect());
+    }
+
+    let names: HashSet<&str> = unresolved.iter().map(|e| e.domain.as_str()).collect();
+    let mut domain_ids = HashMap::with_capacity(names.len());
+    for name in names {
+        if let Some(domain) = domain_repo.find_by_name(name).await? {
+            domain_ids.insert(name, domain.id);
+        }
+    }
+
+    let keys: Vec<(i64, String)> = unresolved
+        .iter()
+        .filter_map(|e| Some((*domain_ids.get(e.domain.as_str())?, e.code.clone())))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let link_ids = if keys.is_empty() {
+        HashMap::new()
+    } else {
+        link_repo.find_ids_by_codes(&keys).await?
+    };
+
+    Ok(events
+        .iter()
+        .map(|e| {
+            e.link_id.or_else(|| {
+                let domain_id = *domain_ids.get(e.domain.as_str())?;
+                link_ids.get(&(domain_id, e.code.clone())).copied()
+            })
+        })
+        .collect())
+}
+
+/// Resolves and records one batch; returns the number of clicks recorded.
+async fn persist_batch<S, D, L>(
+    events: &[ClickEvent],
+    stats_repo: &S,
+    domain_repo: &D,
+    link_repo: &L,
+    unflushed_clicks: Option<&UnflushedClicks>,
+) -> Result<u64, AppError>
+where
     S: StatsRepository,
     D: DomainRepository,
     L: LinkRepository,
 {
-    let strategy = ExponentialBackoff::from_millis(100).take(6);
+    let link_ids = resolve_link_ids(events, domain_repo, link_repo).await?;
+
+    let clicks: Vec<NewClick> = events
+        .iter()
+        .zip(&link_ids)
+        .filter_map(|(event, link_id)| match link_id {
+            Some(link_id) => Some(NewClick {
+                link_id: *link_id,
+                user_agent: event.user_agent.clone(),
+                referer: event.referer.clone(),
+                ip: event.ip.clone(),
+                clicked_at: Some(event.clicked_at),
+            }),
+            None => {
+                tracing::warn!(
+                    domain = &event.domain,
+                    code = &event.code,
+                    "Click worker: domain or link not found, dropping click"
+                );
+                None
+            }
+        })
+        .collect();
+
+    if clicks.is_empty() {
+        return Ok(0);
+    }
+    let recorded = stats_repo.record_clicks(&clicks).await?;
+    if let Some(unflushed_clicks) = unflushed_clicks {
+        unflushed_clicks.add(recorded);
+    }
+    Ok(recorded)
+}
 
-    let stats_repo = stats_repository.clone();
-    let domain_repo = domain_repository.clone();
-    let link_repo = link_repository.clone();
-    let ev = event.clone();
+/// Resolves and records one batch, retrying transient errors.
+///
+/// Retries up to `config.max_retries` times with exponential backoff starting at 100 ms.
+async fn persist_with_retry<S, D, L>(
+    events: &[ClickEvent],
+    stats_repository: &S,
+    domain_repository: &D,
+    link_repository: &L,
+    config: &ClickWorkerConfig,
+) -> Result<u64, AppError>
+where
+    S: StatsRepository,
+    D: DomainRepository,
+    L: LinkRepository,
+{
+    let strategy = ExponentialBackoff::from_millis(100).take(config.max_retries);
 
     let op = || {
-        let stats_repo = stats_repo.clone();
-        let domain_repo = domain_repo.clone();
-        let link_repo = link_repo.clone();
-        let event = ev.clone();
-
-        async move {
-            let domain_entity =
-                domain_repo
-                    .find_by_name(&event.domain)
-                    .await?
-                    .ok_or_else(|| {
-                        AppError::not_found(
-                            format!("Domain not found: {}", event.domain),
-                            json!({ "domain": event.domain.clone() }),
-                        )
-                    })?;
-
-            let link = link_repo
-                .find_by_code(&event.code, domain_entity.id)
-                .await?
-                .ok_or_else(|| {
-                    AppError::not_found(
-                        format!("Link not found: {}", event.code),
-                        json!({ "code": event.code.clone(), "domain_id": domain_entity.id }),
-                    )
-                })?;
-
-            let new_click = NewClick {
-                link_id: link.id,
-                user_agent: event.user_agent,
-                referer: event.referer,
-                ip: event.ip,
-            };
-
-            stats_repo.record_click(new_click).await.map(|_| ())
-        }
+        persist_batch(
+            events,
+            stats_repository,
+            domain_repository,
+            link_repository,
+            config.unflushed_clicks.as_deref(),
+        )
     };
 
     let on_error = |e: &AppError| {
@@ -91,95 +243,283 @@ async fn process_click<S, D, L>(
         if transient {
             metrics::counter!("click_worker_retried_total").increment(1);
             tracing::warn!(
-                domain = &event.domain,
-                code = &event.code,
+                events = events.len(),
                 error = ?e,
-                "Click worker: transient error, retrying"
+                "Click worker: transient error, retrying batch"
             );
         }
         transient
     };
 
-    match RetryIf::spawn(strategy, op, on_error).await {
-        Ok(()) => {
-            metrics::counter!("click_worker_processed_total").increment(1);
-            tracing::debug!(
-                domain = &event.domain,
-                code = &event.code,
-                "Click successfully recorded"
-            );
+    RetryIf::spawn(strategy, op, on_error).await
+}
+
+/// Persists a batch of click events with a single INSERT.
+///
+/// Retries the whole batch on transient errors (see [`persist_with_retry`]).
+/// Events whose domain or link doesn't exist are logged and discarded without
+/// failing the rest of the batch. A batch that still fails with a transient error
+/// is spilled to the journal, if there is one.
+///
+/// # Metrics
+///
+/// - `click_worker_batches_total`   - incremented for each batch
+/// - `click_worker_processed_total` - incremented by the clicks recorded
+/// - `click_worker_retried_total`   - incremented on each retry attempt of a batch
+/// - `click_worker_failed_total`    - incremented by the batch size after exhausting all retries
+/// - `click_worker_dropped_total`   - incremented by the events discarded
+async fn process_batch<S, D, L>(
+    events: Vec<ClickEvent>,
+    stats_repository: Arc<S>,
+    domain_repository: Arc<D>,
+    link_repository: Arc<L>,
+    config: ClickWorkerConfig,
+) where
+    S: StatsRepository,
+    D: DomainRepository,
+    L: LinkRepository,
+{
+    metrics::counter!("click_worker_batches_total").increment(1);
+    let total = events.len() as u64;
+
+    let result = persist_with_retry(
+        &events,
+        stats_repository.as_ref(),
+        domain_repository.as_ref(),
+        link_repository.as_ref(),
+        &config,
+    )
+    .await;
+
+    match result {
+        Ok(recorded) => {
+            metrics::counter!("click_worker_processed_total").increment(recorded);
+            metrics::counter!("click_worker_dropped_total").increment(total - recorded);
+            tracing::debug!(events = total, recorded, "Click batch recorded");
         }
         Err(e) => {
-            metrics::counter!("click_worker_failed_total").increment(1);
-            metrics::counter!("click_worker_dropped_total").increment(1);
+            metrics::counter!("click_worker_failed_total").increment(total);
+            let spilled = is_transient_error(&e)
+                && config
+                    .journal
+                    .as_ref()
+                    .is_some_and(|journal| journal.spill(&events, "db_unavailable"));
+            if !spilled {
+                metrics::counter!("click_worker_dropped_total").increment(total);
+            }
             tracing::error!(
                 error = ?e,
-                domain = &event.domain,
-                code = &event.code,
-                "Click worker: failed to persist click event after retries"
+                events = total,
+                spilled,
+                "Click worker: failed to persist click batch after retries"
             );
         }
     }
 }
 
+/// Records the events waiting in the journal.
+///
+/// Continues an unfinished replay, then replays what was spilled since. Progress
+/// is saved after each batch. Returns the number of clicks recorded.
+///
+/// # Errors
+///
+/// Stops at the first batch that still fails after retries and returns its
+/// error; the remaining events stay in the journal for the next replay.
+pub async fn replay_journal<S, D, L>(
+    journal: &ClickJournal,
+    stats_repository: &S,
+    domain_repository: &D,
+    link_repository: &L,
+    config: &ClickWorkerConfig,
+) -> anyhow::Result<u64>
+where
+    S: StatsRepository,
+    D: DomainRepository,
+    L: LinkRepository,
+{
+    let Some(mut pending) = journal.take_pending()? else {
+        return Ok(0);
+    };
+
+    let mut recorded = 0;
+    loop {
+        let (events, offset) = pending.next_batch(config.batch_size)?;
+        if events.is_empty() {
+            break;
+        }
+
+        let batch = persist_with_retry(
+            &events,
+            stats_repository,
+            domain_repository,
+            link_repository,
+            config,
+        )
+        .await?;
+        pending.commit(offset)?;
+
+        metrics::counter!("click_journal_replayed_total").increment(batch);
+        metrics::counter!("click_worker_dropped_total").increment(events.len() as u64 - batch);
+        recorded += batch;
+    }
+    pending.finish()?;
+
+    tracing::info!(recorded, "Replayed click journal");
+    Ok(recorded)
+}
+
+/// Replays the click journal on startup and every `replay_interval` afterwards.
+///
+/// A round is skipped while the click queue is more than half full, so the
+/// worker catches up with live clicks first. Returns once the click channel is
+/// closed, or at once without a journal in `config`.
+///
+/// # Metrics
+///
+/// - `click_journal_replayed_total` - clicks recorded from the journal
+pub async fn run_journal_replay<S, D, L>(
+    sender: mpsc::WeakSender<ClickEvent>,
+    stats_repository: Arc<S>,
+    domain_repository: Arc<D>,
+    link_repository: Arc<L>,
+    config: ClickWorkerConfig,
+) where
+    S: StatsRepository,
+    D: DomainRepository,
+    L: LinkRepository,
+{
+    let Some(journal) = config.journal.clone() else {
+        return;
+    };
+    let mut interval = tokio::time::interval(config.replay_interval);
+
+    loop {
+        interval.tick().await;
+
+        let Some(sender) = sender.upgrade() else {
+            break;
+        };
+        let busy = sender.capacity() < sender.max_capacity() / 2;
+        drop(sender);
+        if busy {
+            tracing::debug!("Click queue busy, postponing journal replay");
+            continue;
+        }
+
+        if let Err(e) = replay_journal(
+            &journal,
+            stats_repository.as_ref(),
+            domain_repository.as_ref(),
+            link_repository.as_ref(),
+            &config,
+        )
+        .await
+        {
+            tracing::warn!(error = %e, "Click journal replay stopped, retrying later");
+        }
+    }
+}
+
+/// Waits for the next batch of events.
+///
+/// Returns `None` once the channel is closed and empty. Otherwise waits for
+/// one event, then takes whatever else arrives within `batch_interval`, up to
+/// `batch_size` events.
+async fn next_batch(
+    rx: &mut mpsc::Receiver<ClickEvent>,
+    config: &ClickWorkerConfig,
+) -> Option<Vec<ClickEvent>> {
+    let first = rx.recv().await?;
+    let mut batch = Vec::with_capacity(config.batch_size);
+    batch.push(first);
+
+    let deadline = Instant::now() + config.batch_interval;
+    while batch.len() < config.batch_size {
+        let limit = config.batch_size - batch.len();
+        match tokio::time::timeout_at(deadline, rx.recv_many(&mut batch, limit)).await {
+            // 0 means the channel is closed.
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+
+    Some(batch)
+}
+
 /// Runs the background click processing worker with bounded concurrency.
 ///
-/// Reads [`ClickEvent`]s from `rx` and processes up to `concurrency` events in parallel.
-/// Each event is handled by [`process_click`], which retries transient database errors
-/// with exponential backoff.
+/// Reads [`ClickEvent`]s from `rx` in batches (see [`ClickWorkerConfig`]) and
+/// writes up to `concurrency` batches in parallel. Each batch is handled by
+/// [`process_batch`], which retries transient database errors with exponential
+/// backoff.
 ///
 /// # Concurrency
 ///
-/// At most `concurrency` events are in-flight simultaneously. When all slots are busy,
-/// the worker waits for one to finish before accepting the next event. The mpsc channel
+/// At most `concurrency` batches are in-flight simultaneously. When all slots are busy,
+/// the worker waits for one to finish before collecting the next batch. The mpsc channel
 /// buffer (configured via `CLICK_QUEUE_CAPACITY`) absorbs bursts beyond this limit.
 ///
 /// # Graceful Shutdown
 ///
 /// The worker exits when the sending side of the channel is dropped (i.e. after
 /// `axum::serve` completes and [`crate::state::AppState`] is deallocated).
-/// Before returning, all in-flight tasks are drained to avoid losing events.
+/// The last partial batch is written and all in-flight tasks are drained before
+/// returning to avoid losing events.
 ///
 /// # Metrics
 ///
 /// - `click_worker_received_total` - events received from channel
+/// - `click_worker_batches_total` - batches written
 /// - `click_worker_processed_total` - events successfully persisted
 /// - `click_worker_retried_total` - individual retry attempts
 /// - `click_worker_failed_total` - events that exhausted all retries
-/// - `click_worker_dropped_total` - events discarded due to permanent errors
+/// - `click_worker_dropped_total` - events discarded due to permanent errors, or after
+///   retries without a journal
 pub async fn run_click_worker<S, D, L>(
     mut rx: mpsc::Receiver<ClickEvent>,
     stats_repository: Arc<S>,
     domain_repository: Arc<D>,
     link_repository: Arc<L>,
-    concurrency: usize,
+    config: ClickWorkerConfig,
 ) where
     S: StatsRepository + 'static,
     D: DomainRepository + 'static,
     L: LinkRepository + 'static,
 {
-    tracing::info!(concurrency, "Click worker started");
+    tracing::info!(
+        concurrency = config.concurrency,
+        batch_size = config.batch_size,
+        batch_interval_ms = config.batch_interval.as_millis() as u64,
+        "Click worker started"
+    );
 
     let mut join_set: JoinSet<()> = JoinSet::new();
 
-    while let Some(ev) = rx.recv().await {
-        metrics::counter!("click_worker_received_total").increment(1);
+    while let Some(batch) = next_batch(&mut rx, &config).await {
+        metrics::counter!("click_worker_received_total").increment(batch.len() as u64);
 
         // Clean up already-finished tasks to keep join_set size accurate.
         while join_set.try_join_next().is_some() {}
 
         // If at capacity, wait for one slot to free up before spawning more.
-        if join_set.len() >= concurrency {
+        if join_set.len() >= config.concurrency {
             join_set.join_next().await;
         }
 
         let stats_repo = stats_repository.clone();
         let domain_repo = domain_repository.clone();
         let link_repo = link_repository.clone();
+        let batch_config = config.clone();
+
+        let span = batch_span(&batch);
 
-        join_set.spawn(async move {
-            process_click(ev, stats_repo, domain_repo, link_repo).await;
-        });
+        join_set.spawn(
+            async move {
+                process_batch(batch, stats_repo, domain_repo, link_repo, batch_config).await;
+            }
+            .instrument(span),
+        );
     }
 
     // Drain all in-flight tasks before returning so no events are lost on shutdown.
@@ -191,82 +531,125 @@ pub async fn run_click_worker<S, D, L>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::{Click, Domain, Link};
+    use crate::domain::entities::Domain;
     use crate::domain::repositories::{
         MockDomainRepository, MockLinkRepository, MockStatsRepository,
     };
     use chrono::Utc;
-    use tokio::sync::mpsc;
-
-    #[tokio::test]
-    async fn test_click_worker_successful_processing() {
-        let mut mock_domain_repo = MockDomainRepository::new();
-        let mut mock_link_repo = MockLinkRepository::new();
-        let mut mock_stats_repo = MockStatsRepository::new();
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-        let domain = Domain::new(
-            1,
-            "s.example.com".to_string(),
+    fn domain(id: i64, name: &str) -> Domain {
+        Domain::new(
+            id,
+            name.to_string(),
             true,
             true,
             None,
             Utc::now(),
             Utc::now(),
             None,
-        );
+        )
+    }
+
+    fn event(domain: &str, code: &str) -> ClickEvent {
+        ClickEvent::new(
+            domain.to_string(),
+            code.to_string(),
+            Some("192.168.1.1".to_string()),
+            Some("Mozilla/5.0"),
+            None,
+        )
+    }
+
+    /// Queues `events`, closes the channel and runs the worker until it drains.
+    async fn run(
+        events: Vec<ClickEvent>,
+        stats_repo: MockStatsRepository,
+        domain_repo: MockDomainRepository,
+        link_repo: MockLinkRepository,
+        config: ClickWorkerConfig,
+    ) {
+        let (tx, rx) = mpsc::channel(100);
+        for event in events {
+            tx.send(event).await.unwrap();
+        }
+        drop(tx);
+
+        run_click_worker(
+            rx,
+            Arc::new(stats_repo),
+            Arc::new(domain_repo),
+            Arc::new(link_repo),
+            config,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_click_worker_successful_processing() {
+        let mut mock_domain_repo = MockDomainRepository::new();
+        let mut mock_link_repo = MockLinkRepository::new();
+        let mut mock_stats_repo = MockStatsRepository::new();
+
         mock_domain_repo
             .expect_find_by_name()
             .withf(|name| name == "s.example.com")
             .times(1)
-            .returning(move |_| Ok(Some(domain.clone())));
-
-        let link = Link::new(
-            10,
-            "abc123".to_string(),
-            "https://example.com".to_string(),
-            Some("s.example.com".to_string()),
-            Utc::now(),
-            None,
-            false,
-            None,
-        );
+            .returning(|_| Ok(Some(domain(1, "s.example.com"))));
         mock_link_repo
-            .expect_find_by_code()
-            .withf(|code, domain_id| code == "abc123" && *domain_id == 1)
+            .expect_find_ids_by_codes()
+            .withf(|keys| keys == [(1, "abc123".to_string())])
             .times(1)
-            .returning(move |_, _| Ok(Some(link.clone())));
-
-        let click = Click::new(1, 10, Utc::now(), None, None, None);
+            .returning(|_| Ok(HashMap::from([((1, "abc123".to_string()), 10)])));
         mock_stats_repo
-            .expect_record_click()
+            .expect_record_clicks()
+            .withf(|clicks| {
+                clicks.len() == 1
+                    && clicks[0].link_id == 10
+                    && clicks[0].ip.as_deref() == Some("192.168.1.1")
+                    && clicks[0].user_agent.as_deref() == Some("Mozilla/5.0")
+            })
             .times(1)
-            .returning(move |_| Ok(click.clone()));
-
-        let (tx, rx) = mpsc::channel(10);
-
-        let stats_repo = Arc::new(mock_stats_repo);
-        let domain_repo = Arc::new(mock_domain_repo);
-        let link_repo = Arc::new(mock_link_repo);
-
-        let worker_handle =
-            tokio::spawn(run_click_worker(rx, stats_repo, domain_repo, link_repo, 4));
+            .returning(|clicks| Ok(clicks.len() as u64));
+
+        run(
+            vec![event("s.example.com", "abc123")],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
+    }
 
-        let event = ClickEvent::new(
-            "s.example.com".to_string(),
-            "abc123".to_string(),
-            Some("192.168.1.1".to_string()),
-            Some("Mozilla/5.0"),
-            None,
-        );
-        tx.send(event).await.unwrap();
+    #[tokio::test]
+    async fn test_click_worker_uses_event_link_id() {
+        // No expectations: any domain or link lookup fails the test.
+        let mock_domain_repo = MockDomainRepository::new();
+        let mock_link_repo = MockLinkRepository::new();
+        let mut mock_stats_repo = MockStatsRepository::new();
 
-        drop(tx);
-        worker_handle.await.unwrap();
+        mock_stats_repo
+            .expect_record_clicks()
+            .withf(|clicks| clicks.len() == 1 && clicks[0].link_id == 10)
+            .times(1)
+            .returning(|clicks| Ok(clicks.len() as u64));
+
+        run(
+            vec![event("s.example.com", "abc123").with_link_id(Some(10))],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
     }
 
     #[tokio::test]
     async fn test_click_worker_domain_not_found() {
         let mut mock_domain_repo = MockDomainRepository::new();
+        // Nothing to look up or record: any call fails the test.
         let mock_link_repo = MockLinkRepository::new();
         let mock_stats_repo = MockStatsRepository::new();
 
@@ -275,139 +658,308 @@ mod tests {
             .times(1)
             .returning(|_| Ok(None));
 
-        let (tx, rx) = mpsc::channel(10);
-
-        let stats_repo = Arc::new(mock_stats_repo);
-        let domain_repo = Arc::new(mock_domain_repo);
-        let link_repo = Arc::new(mock_link_repo);
-
-        let worker_handle =
-            tokio::spawn(run_click_worker(rx, stats_repo, domain_repo, link_repo, 4));
-
-        let event = ClickEvent::new(
-            "nonexistent.com".to_string(),
-            "abc123".to_string(),
-            None,
-            None,
-            None,
-        );
-        tx.send(event).await.unwrap();
-
-        drop(tx);
-        worker_handle.await.unwrap();
+        run(
+            vec![event("nonexistent.com", "abc123")],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
     }
 
     #[tokio::test]
-    async fn test_click_worker_link_not_found() {
+    async fn test_click_worker_link_not_found_keeps_rest_of_batch() {
         let mut mock_domain_repo = MockDomainRepository::new();
         let mut mock_link_repo = MockLinkRepository::new();
-        let mock_stats_repo = MockStatsRepository::new();
+        let mut mock_stats_repo = MockStatsRepository::new();
 
-        let domain = Domain::new(
-            1,
-            "s.example.com".to_string(),
-            true,
-            true,
-            None,
-            Utc::now(),
-            Utc::now(),
-            None,
-        );
         mock_domain_repo
             .expect_find_by_name()
             .times(1)
-            .returning(move |_| Ok(Some(domain.clone())));
-
+            .returning(|_| Ok(Some(domain(1, "s.example.com"))));
         mock_link_repo
-            .expect_find_by_code()
+            .expect_find_ids_by_codes()
             .times(1)
-            .returning(|_, _| Ok(None));
-
-        let (tx, rx) = mpsc::channel(10);
-
-        let stats_repo = Arc::new(mock_stats_repo);
-        let domain_repo = Arc::new(mock_domain_repo);
-        let link_repo = Arc::new(mock_link_repo);
-
-        let worker_handle =
-            tokio::spawn(run_click_worker(rx, stats_repo, domain_repo, link_repo, 4));
-
-        let event = ClickEvent::new(
-            "s.example.com".to_string(),
-            "nonexistent".to_string(),
-            None,
-            None,
-            None,
-        );
-        tx.send(event).await.unwrap();
-
-        drop(tx);
-        worker_handle.await.unwrap();
+            .returning(|_| Ok(HashMap::from([((1, "abc123".to_string()), 10)])));
+        mock_stats_repo
+            .expect_record_clicks()
+            .withf(|clicks| clicks.len() == 1 && clicks[0].link_id == 10)
+            .times(1)
+            .returning(|clicks| Ok(clicks.len() as u64));
+
+        run(
+            vec![
+                event("s.example.com", "nonexistent"),
+                event("s.example.com", "abc123"),
+            ],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
     }
 
     #[tokio::test]
-    async fn test_click_worker_processes_multiple_events() {
+    async fn test_click_worker_batches_events_with_one_lookup_per_domain() {
         let mut mock_domain_repo = MockDomainRepository::new();
         let mut mock_link_repo = MockLinkRepository::new();
         let mut mock_stats_repo = MockStatsRepository::new();
 
-        let domain = Domain::new(
-            1,
-            "s.example.com".to_string(),
-            true,
-            true,
-            None,
-            Utc::now(),
-            Utc::now(),
-            None,
-        );
         mock_domain_repo
             .expect_find_by_name()
+            .times(2)
+            .returning(|name| {
+                let id = if name == "a.example.com" { 1 } else { 2 };
+                Ok(Some(domain(id, name)))
+            });
+        mock_link_repo
+            .expect_find_ids_by_codes()
+            .withf(|keys| keys.len() == 2)
+            .times(1)
+            .returning(|_| {
+                Ok(HashMap::from([
+                    ((1, "abc".to_string()), 10),
+                    ((2, "abc".to_string()), 20),
+                ]))
+            });
+        mock_stats_repo
+            .expect_record_clicks()
+            .withf(|clicks| {
+                clicks.iter().map(|c| c.link_id).collect::<Vec<_>>() == [10, 20, 10, 30]
+            })
+            .times(1)
+            .returning(|clicks| Ok(clicks.len() as u64));
+
+        run(
+            vec![
+                event("a.example.com", "abc"),
+                event("b.example.com", "abc"),
+                event("a.example.com", "abc"),
+                event("a.example.com", "def").with_link_id(Some(30)),
+            ],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_click_worker_splits_batches_at_batch_size() {
+        let mock_domain_repo = MockDomainRepository::new();
+        let mock_link_repo = MockLinkRepository::new();
+        let mut mock_stats_repo = MockStatsRepository::new();
+
+        let sizes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = sizes.clone();
+        mock_stats_repo
+            .expect_record_clicks()
             .times(3)
-            .returning(move |_| Ok(Some(domain.clone())));
+            .returning(move |clicks| {
+                recorded.lock().unwrap().push(clicks.len());
+                Ok(clicks.len() as u64)
+            });
+
+        let events = (0..5)
+            .map(|i| event("s.example.com", "abc").with_link_id(Some(i)))
+            .collect();
+        run(
+            events,
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(1).with_batching(2, Duration::from_millis(10)),
+        )
+        .await;
+
+        assert_eq!(*sizes.lock().unwrap(), [2, 2, 1]);
+    }
 
-        let link = Link::new(
-            10,
-            "abc123".to_string(),
-            "https://example.com".to_string(),
-            Some("s.example.com".to_string()),
-            Utc::now(),
-            None,
-            false,
-            None,
+    /// A journal in a fresh temporary directory, removed when dropped.
+    fn journal() -> (tempfile::TempDir, Arc<ClickJournal>) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = ClickJournal::open(dir.path().join("clicks.journal"), 1 << 20).unwrap();
+        (dir, Arc::new(journal))
+    }
+
+    fn failing_stats_repo(times: usize) -> MockStatsRepository {
+        let mut mock_stats_repo = MockStatsRepository::new();
+        mock_stats_repo
+            .expect_record_clicks()
+            .times(times)
+            .returning(|_| Err(AppError::internal("connection refused", json!({}))));
+        mock_stats_repo
+    }
+
+    #[tokio::test]
+    async fn test_click_worker_spills_batch_when_database_is_down() {
+        let (_dir, journal) = journal();
+        let mut config =
+            ClickWorkerConfig::new(4).with_journal(journal.clone(), Duration::from_secs(1));
+        config.max_retries = 0;
+
+        run(
+            vec![
+                event("s.example.com", "abc").with_link_id(Some(1)),
+                event("s.example.com", "def").with_link_id(Some(2)),
+            ],
+            failing_stats_repo(1),
+            MockDomainRepository::new(),
+            MockLinkRepository::new(),
+            config,
+        )
+        .await;
+        journal.flush().await;
+
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        let (events, _) = pending.next_batch(10).unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.link_id).collect::<Vec<_>>(),
+            [Some(1), Some(2)]
         );
-        mock_link_repo
-            .expect_find_by_code()
-            .times(3)
-            .returning(move |_, _| Ok(Some(link.clone())));
+    }
 
-        let click = Click::new(1, 10, Utc::now(), None, None, None);
+    #[tokio::test]
+    async fn test_replay_journal_records_and_clears_journal() {
+        let (_dir, journal) = journal();
+        journal
+            .append(&[
+                event("s.example.com", "abc").with_link_id(Some(1)),
+                event("s.example.com", "def").with_link_id(Some(2)),
+                event("s.example.com", "ghi").with_link_id(Some(3)),
+            ])
+            .unwrap();
+
+        let mut mock_stats_repo = MockStatsRepository::new();
         mock_stats_repo
-            .expect_record_click()
-            .times(3)
-            .returning(move |_| Ok(click.clone()));
+            .expect_record_clicks()
+            .times(2)
+            .returning(|clicks| Ok(clicks.len() as u64));
+        let config = ClickWorkerConfig::new(1).with_batching(2, Duration::ZERO);
+
+        let recorded = replay_journal(
+            &journal,
+            &mock_stats_repo,
+            &MockDomainRepository::new(),
+            &MockLinkRepository::new(),
+            &config,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(recorded, 3);
+        assert!(journal.take_pending().unwrap().is_none());
+    }
 
-        let (tx, rx) = mpsc::channel(10);
+    #[tokio::test]
+    async fn test_replay_journal_keeps_events_while_database_is_down() {
+        let (_dir, journal) = journal();
+        journal
+            .append(&[event("s.example.com", "abc").with_link_id(Some(1))])
+            .unwrap();
+        let mut config = ClickWorkerConfig::new(1);
+        config.max_retries = 0;
+
+        let result = replay_journal(
+            &journal,
+            &failing_stats_repo(1),
+            &MockDomainRepository::new(),
+            &MockLinkRepository::new(),
+            &config,
+        )
+        .await;
+
+        assert!(result.is_err());
+        let mut pending = journal.take_pending().unwrap().unwrap();
+        assert_eq!(pending.next_batch(10).unwrap().0.len(), 1);
+    }
 
-        let stats_repo = Arc::new(mock_stats_repo);
-        let domain_repo = Arc::new(mock_domain_repo);
-        let link_repo = Arc::new(mock_link_repo);
+    #[tokio::test]
+    async fn test_click_worker_retries_batch_on_transient_error() {
+        let mock_domain_repo = MockDomainRepository::new();
+        let mock_link_repo = MockLinkRepository::new();
+        let mut mock_stats_repo = MockStatsRepository::new();
 
-        let worker_handle =
-            tokio::spawn(run_click_worker(rx, stats_repo, domain_repo, link_repo, 4));
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        mock_stats_repo
+            .expect_record_clicks()
+            .times(2)
+            .returning(move |clicks| {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(AppError::internal("connection reset", json!({})))
+                } else {
+                    Ok(clicks.len() as u64)
+                }
+            });
+
+        run(
+            vec![
+                event("s.example.com", "abc").with_link_id(Some(1)),
+                event("s.example.com", "def").with_link_id(Some(2)),
+            ],
+            mock_stats_repo,
+            mock_domain_repo,
+            mock_link_repo,
+            ClickWorkerConfig::new(4),
+        )
+        .await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
 
-        for _ in 0..3 {
-            let event = ClickEvent::new(
-                "s.example.com".to_string(),
-                "abc123".to_string(),
-                Some("192.168.1.1".to_string()),
-                None,
-                None,
-            );
-            tx.send(event).await.unwrap();
-        }
+    #[tokio::test]
+    async fn test_click_worker_counts_recorded_clicks_once() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let mut mock_stats_repo = MockStatsRepository::new();
+        mock_stats_repo
+            .expect_record_clicks()
+            .times(2)
+            .returning(move |clicks| {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(AppError::internal("connection reset", json!({})))
+                } else {
+                    Ok(clicks.len() as u64)
+                }
+            });
+
+        let unflushed = Arc::new(UnflushedClicks::new());
+        run(
+            vec![
+                event("s.example.com", "abc").with_link_id(Some(1)),
+                event("s.example.com", "def").with_link_id(Some(2)),
+                event("s.example.com", "abc").with_link_id(Some(1)),
+            ],
+            mock_stats_repo,
+            MockDomainRepository::new(),
+            MockLinkRepository::new(),
+            ClickWorkerConfig::new(4).with_unflushed_clicks(unflushed.clone()),
+        )
+        .await;
+
+        // The failed attempt is not counted.
+        assert_eq!(unflushed.len(), 3);
+    }
 
-        drop(tx);
-        worker_handle.await.unwrap();
+    #[tokio::test]
+    async fn test_click_worker_does_not_count_failed_batch() {
+        let unflushed = Arc::new(UnflushedClicks::new());
+        let mut config = ClickWorkerConfig::new(4).with_unflushed_clicks(unflushed.clone());
+        config.max_retries = 0;
+
+        run(
+            vec![event("s.example.com", "abc").with_link_id(Some(1))],
+            failing_stats_repo(1),
+            MockDomainRepository::new(),
+            MockLinkRepository::new(),
+            config,
+        )
+        .await;
+
+        assert!(unflushed.is_empty());
     }
 }
@@ -0,0 +1,395 @@
+//! Audit log of changes to links, domains and tokens.
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Actor, Domain, Link, Principal};
+
+/// Actor name recorded for changes made with the admin CLI.
+pub const CLI_ACTOR: &str = "admin-cli";
+
+/// Kind of record an audit event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditEntity {
+    Link,
+    Domain,
+    Token,
+}
+
+impl AuditEntity {
+    /// Every entity kind.
+    pub const ALL: [AuditEntity; 3] = [AuditEntity::Link, AuditEntity::Domain, AuditEntity::Token];
+
+    /// Returns the entity name as stored in `audit_events.entity`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Link => "link",
+            AuditEntity::Domain => "domain",
+            AuditEntity::Token => "token",
+        }
+    }
+}
+
+impl fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AuditEntity::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == s)
+            .ok_or_else(|| format!("unknown entity '{s}' (expected one of: link, domain, token)"))
+    }
+}
+
+/// What happened to the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditAction {
+    Create,
+    Update,
+    /// Soft-deleting a link or domain, or revoking a token.
+    Delete,
+    /// Un-deleting a soft-deleted link.
+    Restore,
+}
+
+impl AuditAction {
+    /// Every action.
+    pub const ALL: [AuditAction; 4] = [
+        AuditAction::Create,
+        AuditAction::Update,
+        AuditAction::Delete,
+        AuditAction::Restore,
+    ];
+
+    /// Returns the action name as stored in `audit_events.action`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| {
+                format!("unknown action '{s}' (expected one of: create, update, delete, restore)")
+            })
+    }
+}
+
+/// Who made a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditActor {
+    /// API token used for the change; `None` for single sign-on users and the CLI.
+    pub token_id: Option<i64>,
+    /// User the change was made for, if any.
+    pub user_id: Option<i64>,
+    /// Token name, user email (`user:<id>` for users without one) or [`CLI_ACTOR`].
+    pub name: String,
+}
+
+impl AuditActor {
+    /// The admin CLI, which runs without a token.
+    pub fn cli() -> Self {
+        Self {
+            token_id: None,
+            user_id: None,
+            name: CLI_ACTOR.to_string(),
+        }
+    }
+}
+
+impl From<&Principal> for AuditActor {
+    fn from(principal: &Principal) -> Self {
+        let user_id = principal.user.map(|user| user.id);
+        match &principal.actor {
+            Actor::Token { id, name } => Self {
+                token_id: Some(*id),
+                user_id,
+                name: name.clone(),
+            },
+            Actor::User { id, email } => Self {
+                token_id: None,
+                user_id: Some(*id),
+                name: email.clone().unwrap_or_else(|| format!("user:{id}")),
+            },
+        }
+    }
+}
+
+/// Who makes a change and from where.
+///
+/// Repositories record the change in the audit log with it, in the same
+/// transaction as the change, so a change is never left without its event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditContext {
+    pub actor: AuditActor,
+    /// Client IP of the request; `None` for the CLI.
+    pub ip: Option<String>,
+}
+
+impl AuditContext {
+    /// A change made by `actor` from `ip`.
+    pub fn new(actor: AuditActor, ip: Option<String>) -> Self {
+        Self { actor, ip }
+    }
+
+    /// A change made with the admin CLI.
+    pub fn cli() -> Self {
+        Self::new(AuditActor::cli(), None)
+    }
+}
+
+/// A record whose changes are audited.
+pub trait Auditable {
+    /// Kind of the record.
+    const ENTITY: AuditEntity;
+
+    /// Database ID of the record.
+    fn audit_id(&self) -> i64;
+
+    /// Workspace the record belongs to.
+    fn audit_workspace_id(&self) -> i64;
+
+    /// JSON snapshot of the record; never contains secrets.
+    fn audit_snapshot(&self) -> Value;
+}
+
+impl Auditable for Link {
+    const ENTITY: AuditEntity = AuditEntity::Link;
+
+    fn audit_id(&self) -> i64 {
+        self.id
+    }
+
+    fn audit_workspace_id(&self) -> i64 {
+        self.workspace_id
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({
+            "code": self.code,
+            "long_url": self.long_url,
+            "domain": self.domain,
+            "permanent": self.permanent,
+            "expires_at": self.expires_at,
+            "deleted_at": self.deleted_at,
+            "owner_id": self.owner_id,
+            "team_id": self.team_id,
+        })
+    }
+}
+
+impl Auditable for Domain {
+    const ENTITY: AuditEntity = AuditEntity::Domain;
+
+    fn audit_id(&self) -> i64 {
+        self.id
+    }
+
+    fn audit_workspace_id(&self) -> i64 {
+        self.workspace_id
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({
+            "domain": self.domain,
+            "is_default": self.is_default,
+            "is_active": self.is_active,
+            "description": self.description,
+            "deleted_at": self.deleted_at,
+        })
+    }
+}
+
+/// A recorded change.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub entity: AuditEntity,
+    pub entity_id: i64,
+    pub action: AuditAction,
+    pub actor: AuditActor,
+    /// Client IP of the request; `None` for the CLI.
+    pub ip: Option<String>,
+    /// The record before the change; `None` for `create`.
+    pub before: Option<Value>,
+    /// The record after the change; `None` for `delete`.
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A change to record.
+///
+/// Built with [`NewAuditEvent::created`], [`NewAuditEvent::updated`],
+/// [`NewAuditEvent::deleted`] or [`NewAuditEvent::restored`], which take the
+/// entity, its ID and workspace from the record.
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent {
+    pub workspace_id: i64,
+    pub entity: AuditEntity,
+    pub entity_id: i64,
+    pub action: AuditAction,
+    pub actor: AuditActor,
+    pub ip: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl NewAuditEvent {
+    fn new<T: Auditable>(
+        action: AuditAction,
+        context: &AuditContext,
+        record: &T,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Self {
+        Self {
+            workspace_id: record.audit_workspace_id(),
+            entity: T::ENTITY,
+            entity_id: record.audit_id(),
+            action,
+            actor: context.actor.clone(),
+            ip: context.ip.clone(),
+            before: before.map(Auditable::audit_snapshot),
+            after: after.map(Auditable::audit_snapshot),
+        }
+    }
+
+    /// A record was created.
+    pub fn created<T: Auditable>(context: &AuditContext, after: &T) -> Self {
+        Self::new(AuditAction::Create, context, after, None, Some(after))
+    }
+
+    /// A record was changed.
+    pub fn updated<T: Auditable>(context: &AuditContext, before: &T, after: &T) -> Self {
+        Self::new(
+            AuditAction::Update,
+            context,
+            after,
+            Some(before),
+            Some(after),
+        )
+    }
+
+    /// A record was deleted or revoked.
+    pub fn deleted<T: Auditable>(context: &AuditContext, before: &T) -> Self {
+        Self::new(AuditAction::Delete, context, before, Some(before), None)
+    }
+
+    /// A deleted record was restored.
+    pub fn restored<T: Auditable>(context: &AuditContext, before: &T, after: &T) -> Self {
+        Self::new(
+            AuditAction::Restore,
+            context,
+            after,
+            Some(before),
+            Some(after),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{Role, Scope, UserRef};
+
+    fn link(deleted: bool) -> Link {
+        Link::new(
+            42,
+            "abc".to_string(),
+            "https://example.com".to_string(),
+            Some("s.example.com".to_string()),
+            Utc::now(),
+            None,
+            false,
+            deleted.then(Utc::now),
+        )
+        .with_workspace(3)
+    }
+
+    #[test]
+    fn test_entity_and_action_round_trip() {
+        for entity in AuditEntity::ALL {
+            assert_eq!(entity.as_str().parse::<AuditEntity>(), Ok(entity));
+        }
+        for action in AuditAction::ALL {
+            assert_eq!(action.as_str().parse::<AuditAction>(), Ok(action));
+        }
+        assert!("user".parse::<AuditEntity>().is_err());
+        assert!("purge".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn test_actor_from_principal() {
+        let principal = Principal {
+            actor: Actor::Token {
+                id: 7,
+                name: "ci".to_string(),
+            },
+            scopes: Scope::ALL.to_vec(),
+            domain_ids: None,
+            user: Some(UserRef {
+                id: 3,
+                team_id: None,
+                role: Role::Editor,
+            }),
+            workspace_id: 1,
+        };
+
+        assert_eq!(
+            AuditActor::from(&principal),
+            AuditActor {
+                token_id: Some(7),
+                user_id: Some(3),
+                name: "ci".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_restored_event_keeps_both_snapshots() {
+        let context = AuditContext::new(AuditActor::cli(), Some("10.0.0.1".to_string()));
+        let event = NewAuditEvent::restored(&context, &link(true), &link(false));
+
+        assert_eq!(event.entity, AuditEntity::Link);
+        assert_eq!(event.entity_id, 42);
+        assert_eq!(event.workspace_id, 3);
+        assert_eq!(event.action, AuditAction::Restore);
+        assert!(!event.before.unwrap()["deleted_at"].is_null());
+        assert!(event.after.unwrap()["deleted_at"].is_null());
+        assert_eq!(event.ip.as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_created_and_deleted_events_have_one_snapshot() {
+        let created = NewAuditEvent::created(&AuditContext::cli(), &link(false));
+        let deleted = NewAuditEvent::deleted(&AuditContext::cli(), &link(false));
+
+        assert!(created.before.is_none() && created.after.is_some());
+        assert!(deleted.before.is_some() && deleted.after.is_none());
+    }
+}
@@ -58,13 +58,15 @@ impl Click {
 /// Input data for recording a new click event.
 ///
 /// Used when logging a redirect. The `link_id` must reference an existing link,
-/// and the timestamp is automatically set by the database.
+/// and the timestamp is set by the database unless `clicked_at` is given.
 #[derive(Debug, Clone)]
 pub struct NewClick {
     pub link_id: i64,
     pub user_agent: Option<String>,
     pub referer: Option<String>,
     pub ip: Option<String>,
+    /// When the click happened; `None` records the time of the insert.
+    pub clicked_at: Option<DateTime<Utc>>,
 }
 
 #[cfg(test)]
@@ -110,6 +112,7 @@ mod tests {
             user_agent: Some("Chrome/120".to_string()),
             referer: None,
             ip: Some("10.0.0.1".to_string()),
+            clicked_at: None,
         };
 
         assert_eq!(new_click.link_id, 99);
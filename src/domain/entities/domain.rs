@@ -2,11 +2,13 @@
 
 use chrono::{DateTime, Utc};
 
+use super::DEFAULT_WORKSPACE_ID;
+
 /// A domain that serves shortened URLs.
 ///
 /// Each domain acts as a namespace for short links, allowing multiple short codes
-/// with the same value across different domains. Only one domain can be marked as
-/// the system default at a time.
+/// with the same value across different domains. Domain names are unique across
+/// workspaces; each workspace has at most one default domain.
 #[derive(Debug, Clone)]
 pub struct Domain {
     pub id: i64,
@@ -17,6 +19,8 @@ pub struct Domain {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Workspace owning the domain and its links.
+    pub workspace_id: i64,
 }
 
 impl Domain {
@@ -41,9 +45,16 @@ impl Domain {
             created_at,
             updated_at,
             deleted_at,
+            workspace_id: DEFAULT_WORKSPACE_ID,
         }
     }
 
+    /// Sets the workspace owning the domain.
+    pub fn with_workspace(mut self, workspace_id: i64) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
+
     /// Returns true if the domain has been soft-deleted.
     pub fn is_deleted(&self) -> bool {
         self.deleted_at.is_some()
@@ -58,6 +69,7 @@ pub struct NewDomain {
     pub domain: String,
     pub is_default: bool,
     pub description: Option<String>,
+    pub workspace_id: i64,
 }
 
 /// Input data for updating an existing domain.
@@ -72,7 +84,7 @@ pub struct NewDomain {
 ///
 /// # `is_default` semantics
 ///
-/// - `Some(true)` → make this domain the system default (handled by service via transaction)
+/// - `Some(true)` → make this domain its workspace's default (handled by service via transaction)
 /// - `Some(false)` → error; use `Some(true)` on another domain instead
 /// - `None` → leave unchanged
 #[derive(Debug, Clone, Default)]
@@ -130,6 +142,8 @@ mod tests {
         assert!(!domain.is_default);
         assert!(!domain.is_active);
         assert!(domain.description.is_none());
+        assert_eq!(domain.workspace_id, DEFAULT_WORKSPACE_ID);
+        assert_eq!(domain.with_workspace(5).workspace_id, 5);
     }
 
     #[test]
@@ -153,6 +167,7 @@ mod tests {
             domain: "new.short.link".to_string(),
             is_default: false,
             description: Some("Secondary domain".to_string()),
+            workspace_id: DEFAULT_WORKSPACE_ID,
         };
 
         assert_eq!(new_domain.domain, "new.short.link");
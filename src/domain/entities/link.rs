@@ -2,6 +2,8 @@
 
 use chrono::{DateTime, Utc};
 
+use super::DEFAULT_WORKSPACE_ID;
+
 /// A shortened URL link with metadata.
 ///
 /// Represents the mapping between a short code and a long URL within a specific domain.
@@ -16,6 +18,12 @@ pub struct Link {
     pub expires_at: Option<DateTime<Utc>>,
     pub permanent: bool,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// User who created the link; `None` for links without an owner.
+    pub owner_id: Option<i64>,
+    /// Team the link belongs to: the owner's team when it was created.
+    pub team_id: Option<i64>,
+    /// Workspace of the link's domain.
+    pub workspace_id: i64,
 }
 
 impl Link {
@@ -40,9 +48,25 @@ impl Link {
             expires_at,
             permanent,
             deleted_at,
+            owner_id: None,
+            team_id: None,
+            workspace_id: DEFAULT_WORKSPACE_ID,
         }
     }
 
+    /// Sets the owning user and team.
+    pub fn with_owner(mut self, owner_id: Option<i64>, team_id: Option<i64>) -> Self {
+        self.owner_id = owner_id;
+        self.team_id = team_id;
+        self
+    }
+
+    /// Sets the workspace the link belongs to.
+    pub fn with_workspace(mut self, workspace_id: i64) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
+
     /// Returns true if the link has been soft-deleted.
     pub fn is_deleted(&self) -> bool {
         self.deleted_at.is_some()
@@ -62,6 +86,22 @@ pub struct NewLink {
     pub domain_id: i64,
     pub expires_at: Option<DateTime<Utc>>,
     pub permanent: bool,
+    /// User creating the link; `None` for tokens not bound to a user.
+    pub owner_id: Option<i64>,
+    pub team_id: Option<i64>,
+}
+
+/// What to do when a live link for the same normalized URL and domain already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupePolicy {
+    /// Return an existing link with the same code (if one was requested), expiry and
+    /// redirect type; create a new link when none matches.
+    #[default]
+    Reuse,
+    /// Always create a new link, even if the URL is already shortened.
+    AlwaysNew,
+    /// Fail with a conflict if the URL is already shortened.
+    Error,
 }
 
 /// Partial update for an existing link.
@@ -75,6 +115,8 @@ pub struct LinkPatch {
     pub permanent: Option<bool>,
     /// When `true`, clears `deleted_at` to restore a soft-deleted link.
     pub restore: bool,
+    /// Version the patch restores, recorded on the new version it creates.
+    pub reverted_from: Option<i32>,
 }
 
 #[cfg(test)]
@@ -123,6 +165,26 @@ mod tests {
         assert_eq!(link.domain.unwrap(), "s.example.com");
     }
 
+    #[test]
+    fn test_link_with_owner() {
+        let link = Link::new(
+            1,
+            "code".to_string(),
+            "https://example.com".to_string(),
+            None,
+            Utc::now(),
+            None,
+            false,
+            None,
+        );
+        assert_eq!((link.owner_id, link.team_id), (None, None));
+
+        let link = link.with_owner(Some(7), Some(3));
+        assert_eq!((link.owner_id, link.team_id), (Some(7), Some(3)));
+        assert_eq!(link.workspace_id, DEFAULT_WORKSPACE_ID);
+        assert_eq!(link.with_workspace(4).workspace_id, 4);
+    }
+
     #[test]
     fn test_link_is_deleted() {
         let link = Link::new(
@@ -162,6 +224,8 @@ mod tests {
             domain_id: 42,
             expires_at: None,
             permanent: false,
+            owner_id: None,
+            team_id: None,
         };
 
         assert_eq!(new_link.code, "xyz789");
@@ -0,0 +1,92 @@
+//! Versions of a link's destination, expiry and redirect type.
+
+use chrono::{DateTime, Utc};
+
+use super::{AuditActor, Link};
+
+/// A past or current state of a link.
+#[derive(Debug, Clone)]
+pub struct LinkVersion {
+    pub link_id: i64,
+    /// 1 for the link as created, increasing by one per change.
+    pub version: i32,
+    pub long_url: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub permanent: bool,
+    /// Version this one restored, if it was created by a revert.
+    pub reverted_from: Option<i32>,
+    /// Who made the change; `None` for versions from before history was kept.
+    pub actor: Option<AuditActor>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A version to record; its number is assigned by the repository.
+#[derive(Debug, Clone)]
+pub struct NewLinkVersion {
+    pub link_id: i64,
+    pub long_url: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub permanent: bool,
+    pub reverted_from: Option<i32>,
+    pub actor: AuditActor,
+}
+
+impl NewLinkVersion {
+    /// The current state of `link`, changed by `actor`.
+    pub fn of(link: &Link, actor: AuditActor) -> Self {
+        Self {
+            link_id: link.id,
+            long_url: link.long_url.clone(),
+            expires_at: link.expires_at,
+            permanent: link.permanent,
+            reverted_from: None,
+            actor,
+        }
+    }
+}
+
+impl Link {
+    /// Returns `true` if `other` differs in a versioned field: destination,
+    /// expiry or redirect type. Deleting and restoring do not create versions.
+    pub fn version_changed(&self, other: &Link) -> bool {
+        self.long_url != other.long_url
+            || self.expires_at != other.expires_at
+            || self.permanent != other.permanent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(long_url: &str, deleted: bool) -> Link {
+        Link::new(
+            7,
+            "abc".to_string(),
+            long_url.to_string(),
+            None,
+            Utc::now(),
+            None,
+            false,
+            deleted.then(Utc::now),
+        )
+    }
+
+    #[test]
+    fn test_version_changed_ignores_deletion() {
+        let old = link("https://example.com/a", false);
+
+        assert!(old.version_changed(&link("https://example.com/b", false)));
+        assert!(!old.version_changed(&link("https://example.com/a", true)));
+    }
+
+    #[test]
+    fn test_new_version_of_link() {
+        let version = NewLinkVersion::of(&link("https://example.com/a", false), AuditActor::cli());
+
+        assert_eq!(version.link_id, 7);
+        assert_eq!(version.long_url, "https://example.com/a");
+        assert_eq!(version.reverted_from, None);
+        assert_eq!(version.actor.name, "admin-cli");
+    }
+}
@@ -9,19 +9,42 @@
 //! - [`Link`] - A shortened URL mapping
 //! - [`Click`] - A click event on a shortened link
 //! - [`Domain`] - A domain that serves shortened URLs
+//! - [`Principal`] - The authenticated caller ([`Actor`]) and its [`Scope`]s
+//! - [`Role`] - Dashboard role of a user
+//! - [`User`], [`Team`] - Dashboard users and the teams sharing their links
+//! - [`Visibility`] - Which links a caller may see and change
+//! - [`Workspace`] - A tenant owning domains, links, tokens and users, with its quotas
+//! - [`AuditEvent`] - A recorded change to a link, domain or token
+//! - [`LinkVersion`] - A past or current destination, expiry and redirect type of a link
 //!
 //! # Design Pattern
 //!
 //! Entities follow the "New Type" pattern with separate structs for creation:
 //! - `NewLink`, `NewClick`, `NewDomain` - For creating new records
 //! - `UpdateDomain` - For partial updates
+//! - `DedupePolicy` - How link creation treats an already shortened URL
 //!
 //! All entities include unit tests demonstrating their construction and usage.
 
+pub mod audit;
 pub mod click;
 pub mod domain;
 pub mod link;
+pub mod link_version;
+pub mod principal;
+pub mod user;
+pub mod workspace;
 
+pub use audit::{
+    AuditAction, AuditActor, AuditContext, AuditEntity, AuditEvent, Auditable, CLI_ACTOR,
+    NewAuditEvent,
+};
 pub use click::{Click, NewClick};
 pub use domain::{Domain, NewDomain, UpdateDomain};
-pub use link::{Link, LinkPatch, NewLink};
+pub use link::{DedupePolicy, Link, LinkPatch, NewLink};
+pub use link_version::{LinkVersion, NewLinkVersion};
+pub use principal::{Actor, Principal, Role, Scope, UserRef, Visibility};
+pub use user::{Team, User};
+pub use workspace::{
+    DEFAULT_WORKSPACE_ID, Workspace, WorkspaceQuotas, WorkspaceUsage, month_start,
+};
@@ -0,0 +1,502 @@
+//! Authenticated caller and the permissions of its API token or dashboard role.
+
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Domain, Link, User};
+use crate::error::AppError;
+
+/// Permission granted to an API token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// List domains available for link creation.
+    LinksRead,
+    /// Create, update and delete links.
+    LinksWrite,
+    /// Read click statistics.
+    StatsRead,
+    /// Create, update and delete domains.
+    DomainsAdmin,
+    /// Create, list and revoke API tokens.
+    TokensAdmin,
+    /// Read the audit log.
+    AuditRead,
+    /// Inspect and purge the redirect cache.
+    CacheAdmin,
+}
+
+impl Scope {
+    /// Every scope; granted to tokens created without an explicit scope list.
+    pub const ALL: [Scope; 7] = [
+        Scope::LinksRead,
+        Scope::LinksWrite,
+        Scope::StatsRead,
+        Scope::DomainsAdmin,
+        Scope::TokensAdmin,
+        Scope::AuditRead,
+        Scope::CacheAdmin,
+    ];
+
+    /// Returns the scope name as stored in `api_tokens.scopes`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::LinksRead => "links:read",
+            Scope::LinksWrite => "links:write",
+            Scope::StatsRead => "stats:read",
+            Scope::DomainsAdmin => "domains:admin",
+            Scope::TokensAdmin => "tokens:admin",
+            Scope::AuditRead => "audit:read",
+            Scope::CacheAdmin => "cache:admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| {
+                let known: Vec<_> = Scope::ALL.iter().map(Scope::as_str).collect();
+                format!(
+                    "unknown scope '{s}' (expected one of: {})",
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+/// Role of a user; each role grants a fixed set of scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Browse links and statistics.
+    Viewer,
+    /// Viewer, plus create, update and delete links.
+    Editor,
+    /// Every scope, including domain and token administration; sees every link of the workspace.
+    Admin,
+}
+
+impl Role {
+    /// Every role, from least to most privileged.
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Editor, Role::Admin];
+
+    /// Returns the role name as stored in `users.role`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Returns the scopes granted by the role.
+    pub fn scopes(&self) -> Vec<Scope> {
+        match self {
+            Role::Viewer => vec![Scope::LinksRead, Scope::StatsRead],
+            Role::Editor => vec![Scope::LinksRead, Scope::LinksWrite, Scope::StatsRead],
+            Role::Admin => Scope::ALL.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str() == s)
+            .ok_or_else(|| format!("unknown role '{s}' (expected one of: viewer, editor, admin)"))
+    }
+}
+
+/// Who made a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Actor {
+    /// An API token, used directly or to open a dashboard session.
+    Token { id: i64, name: String },
+    /// A dashboard user signed in through single sign-on.
+    User { id: i64, email: Option<String> },
+}
+
+/// The user a request acts for: a signed-in user or the user a token is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserRef {
+    pub id: i64,
+    pub team_id: Option<i64>,
+    pub role: Role,
+}
+
+/// Which links a caller may see and change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    /// Every link of every workspace: the admin CLI.
+    All,
+    /// Every link of a workspace: admins and API tokens not bound to a user.
+    Workspace(i64),
+    /// Links the user owns, and those of their team if they have one.
+    Owned {
+        workspace_id: i64,
+        user_id: i64,
+        team_id: Option<i64>,
+    },
+}
+
+impl Visibility {
+    /// Returns true if the link is visible.
+    pub fn can_see(&self, link: &Link) -> bool {
+        match self {
+            Visibility::All => true,
+            Visibility::Workspace(workspace_id) => link.workspace_id == *workspace_id,
+            Visibility::Owned {
+                workspace_id,
+                user_id,
+                team_id,
+            } => {
+                link.workspace_id == *workspace_id
+                    && (link.owner_id == Some(*user_id)
+                        || (team_id.is_some() && link.team_id == *team_id))
+            }
+        }
+    }
+
+    /// Workspace to filter queries by; `None` means no filter.
+    pub fn workspace_id(&self) -> Option<i64> {
+        match self {
+            Visibility::All => None,
+            Visibility::Workspace(workspace_id) | Visibility::Owned { workspace_id, .. } => {
+                Some(*workspace_id)
+            }
+        }
+    }
+
+    /// Owner to filter queries by; `None` means no filter.
+    pub fn owner_id(&self) -> Option<i64> {
+        match self {
+            Visibility::All | Visibility::Workspace(_) => None,
+            Visibility::Owned { user_id, .. } => Some(*user_id),
+        }
+    }
+
+    /// Team whose links are visible besides the owner's.
+    pub fn team_id(&self) -> Option<i64> {
+        match self {
+            Visibility::All | Visibility::Workspace(_) => None,
+            Visibility::Owned { team_id, .. } => *team_id,
+        }
+    }
+}
+
+/// The authenticated caller of an API request.
+///
+/// Produced by [`AuthService::authenticate`](crate::application::services::AuthService::authenticate)
+/// or a dashboard session and stored in the request extensions by the API auth middleware.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub actor: Actor,
+    pub scopes: Vec<Scope>,
+    /// Domains the token may act on; `None` means every domain.
+    pub domain_ids: Option<Vec<i64>>,
+    /// User the request acts for; `None` for API tokens not bound to a user.
+    pub user: Option<UserRef>,
+    /// Workspace of the token or user; the caller never sees other workspaces.
+    pub workspace_id: i64,
+}
+
+impl Principal {
+    /// Builds the principal of a single sign-on user with the scopes of their role.
+    pub fn for_user(user: &User) -> Self {
+        Self {
+            actor: Actor::User {
+                id: user.id,
+                email: user.email.clone(),
+            },
+            scopes: user.role.scopes(),
+            domain_ids: None,
+            user: Some(UserRef {
+                id: user.id,
+                team_id: user.team_id,
+                role: user.role,
+            }),
+            workspace_id: user.workspace_id,
+        }
+    }
+
+    /// Returns the links the caller may see and change.
+    ///
+    /// Admins and tokens not bound to a user see every link of their workspace;
+    /// other users see their own links and those of their team.
+    pub fn visibility(&self) -> Visibility {
+        match self.user {
+            None => Visibility::Workspace(self.workspace_id),
+            Some(user) if user.role == Role::Admin => Visibility::Workspace(self.workspace_id),
+            Some(user) => Visibility::Owned {
+                workspace_id: self.workspace_id,
+                user_id: user.id,
+                team_id: user.team_id,
+            },
+        }
+    }
+
+    /// Returns the ID of the API token behind the request, if any.
+    pub fn token_id(&self) -> Option<i64> {
+        match &self.actor {
+            Actor::Token { id, .. } => Some(*id),
+            Actor::User { .. } => None,
+        }
+    }
+
+    /// Returns a stable key identifying the actor, e.g. `token:7` or `user:3`.
+    pub fn actor_key(&self) -> String {
+        match &self.actor {
+            Actor::Token { id, .. } => format!("token:{id}"),
+            Actor::User { id, .. } => format!("user:{id}"),
+        }
+    }
+
+    /// Returns true if the token was granted `scope`.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Returns true if the token may act on the domain.
+    pub fn can_access_domain(&self, domain_id: i64) -> bool {
+        self.domain_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&domain_id))
+    }
+
+    /// Returns true if the token is limited to a set of domains.
+    pub fn is_domain_restricted(&self) -> bool {
+        self.domain_ids.is_some()
+    }
+
+    /// Fails with [`AppError::Forbidden`] unless the token was granted `scope`.
+    pub fn require_scope(&self, scope: Scope) -> Result<(), AppError> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(AppError::forbidden(
+                "Token lacks the required scope",
+                json!({ "required_scope": scope.as_str() }),
+            ))
+        }
+    }
+
+    /// Fails unless the caller may act on the domain.
+    ///
+    /// A domain of another workspace is reported as [`AppError::NotFound`], so
+    /// workspaces cannot probe each other's domains; a domain outside the token's
+    /// domain list fails with [`AppError::Forbidden`].
+    pub fn require_domain(&self, domain: &Domain) -> Result<(), AppError> {
+        if domain.workspace_id != self.workspace_id {
+            return Err(AppError::not_found(
+                "Domain not found",
+                json!({ "domain": domain.domain }),
+            ));
+        }
+
+        if self.can_access_domain(domain.id) {
+            Ok(())
+        } else {
+            Err(AppError::forbidden(
+                "Token is not allowed to use this domain",
+                json!({ "domain_id": domain.id }),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(scopes: Vec<Scope>, domain_ids: Option<Vec<i64>>) -> Principal {
+        Principal {
+            actor: Actor::Token {
+                id: 1,
+                name: "test".to_string(),
+            },
+            scopes,
+            domain_ids,
+            user: None,
+            workspace_id: 1,
+        }
+    }
+
+    fn user(id: i64, team_id: Option<i64>, role: Role) -> User {
+        User {
+            id,
+            email: Some(format!("user{id}@example.com")),
+            sso_subject: None,
+            role,
+            team_id,
+            workspace_id: 1,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn domain(id: i64, workspace_id: i64) -> Domain {
+        Domain::new(
+            id,
+            format!("d{id}.example.com"),
+            false,
+            true,
+            None,
+            chrono::Utc::now(),
+            chrono::Utc::now(),
+            None,
+        )
+        .with_workspace(workspace_id)
+    }
+
+    fn link(owner_id: Option<i64>, team_id: Option<i64>) -> Link {
+        Link::new(
+            1,
+            "code".to_string(),
+            "https://example.com".to_string(),
+            None,
+            chrono::Utc::now(),
+            None,
+            false,
+            None,
+        )
+        .with_owner(owner_id, team_id)
+    }
+
+    #[test]
+    fn test_scope_round_trip() {
+        for scope in Scope::ALL {
+            assert_eq!(scope.as_str().parse::<Scope>().unwrap(), scope);
+        }
+        assert!("links:delete".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn test_role_round_trip_and_order() {
+        for role in Role::ALL {
+            assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+        }
+        assert!("owner".parse::<Role>().is_err());
+        assert!(Role::Viewer < Role::Editor && Role::Editor < Role::Admin);
+    }
+
+    #[test]
+    fn test_role_scopes() {
+        let viewer = Principal::for_user(&user(3, None, Role::Viewer));
+        assert!(viewer.has_scope(Scope::StatsRead));
+        assert!(!viewer.has_scope(Scope::LinksWrite));
+
+        let editor = Principal::for_user(&user(3, None, Role::Editor));
+        assert!(editor.has_scope(Scope::LinksWrite));
+        assert!(!editor.has_scope(Scope::DomainsAdmin));
+
+        let admin = Principal::for_user(&user(3, None, Role::Admin));
+        assert_eq!(admin.scopes, Scope::ALL.to_vec());
+        assert_eq!(admin.token_id(), None);
+        assert_eq!(admin.actor_key(), "user:3");
+    }
+
+    #[test]
+    fn test_visibility_by_role_and_team() {
+        assert_eq!(
+            principal(Scope::ALL.to_vec(), None).visibility(),
+            Visibility::Workspace(1)
+        );
+        assert_eq!(
+            Principal::for_user(&user(3, Some(9), Role::Admin)).visibility(),
+            Visibility::Workspace(1)
+        );
+        assert_eq!(
+            Principal::for_user(&user(3, Some(9), Role::Editor)).visibility(),
+            Visibility::Owned {
+                workspace_id: 1,
+                user_id: 3,
+                team_id: Some(9)
+            }
+        );
+    }
+
+    #[test]
+    fn test_visibility_can_see() {
+        let team = Visibility::Owned {
+            workspace_id: 1,
+            user_id: 3,
+            team_id: Some(9),
+        };
+        assert!(team.can_see(&link(Some(3), None)));
+        assert!(team.can_see(&link(Some(4), Some(9))));
+        assert!(!team.can_see(&link(Some(4), Some(8))));
+        assert!(!team.can_see(&link(None, None)));
+        assert!(!team.can_see(&link(Some(3), None).with_workspace(2)));
+
+        let own = Visibility::Owned {
+            workspace_id: 1,
+            user_id: 3,
+            team_id: None,
+        };
+        assert!(own.can_see(&link(Some(3), Some(9))));
+        assert!(!own.can_see(&link(Some(4), None)));
+
+        assert!(Visibility::Workspace(1).can_see(&link(None, None)));
+        assert!(!Visibility::Workspace(2).can_see(&link(None, None)));
+        assert!(Visibility::All.can_see(&link(None, None).with_workspace(2)));
+    }
+
+    #[test]
+    fn test_require_scope() {
+        let p = principal(vec![Scope::LinksRead], None);
+
+        assert!(p.require_scope(Scope::LinksRead).is_ok());
+        assert!(matches!(
+            p.require_scope(Scope::DomainsAdmin),
+            Err(AppError::Forbidden { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unrestricted_token_can_access_any_domain() {
+        let p = principal(Scope::ALL.to_vec(), None);
+
+        assert!(!p.is_domain_restricted());
+        assert!(p.require_domain(&domain(42, 1)).is_ok());
+    }
+
+    #[test]
+    fn test_domain_of_other_workspace_is_not_found() {
+        let p = principal(Scope::ALL.to_vec(), None);
+
+        assert!(matches!(
+            p.require_domain(&domain(42, 2)),
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_restricted_token_domains() {
+        let p = principal(Scope::ALL.to_vec(), Some(vec![1, 2]));
+
+        assert!(p.is_domain_restricted());
+        assert!(p.require_domain(&domain(2, 1)).is_ok());
+        assert!(matches!(
+            p.require_domain(&domain(3, 1)),
+            Err(AppError::Forbidden { .. })
+        ));
+    }
+}
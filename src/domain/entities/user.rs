@@ -0,0 +1,34 @@
+//! Dashboard users and the teams they belong to.
+
+use chrono::{DateTime, Utc};
+
+use super::Role;
+
+/// A person using the dashboard or the API through a token bound to them.
+///
+/// Single sign-on users are created on first sign-in; others are created with
+/// the admin CLI so that tokens can be bound to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub id: i64,
+    /// Verified email; `None` for single sign-on users whose provider did not verify one.
+    pub email: Option<String>,
+    /// OpenID Connect subject; `None` until the user signs in through single sign-on.
+    pub sso_subject: Option<String>,
+    pub role: Role,
+    /// Team whose links the user sees; `None` limits a non-admin to their own links.
+    pub team_id: Option<i64>,
+    /// Workspace the user works in; they never see other workspaces.
+    pub workspace_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A group of users sharing their links, e.g. a department.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub id: i64,
+    /// Workspace the team belongs to; names are unique within a workspace.
+    pub workspace_id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
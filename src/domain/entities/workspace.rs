@@ -0,0 +1,103 @@
+//! Workspace (tenant) entity and its quotas.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+
+/// ID of the workspace created by the migrations; owns everything created before
+/// workspaces existed and every user created by single sign-on.
+pub const DEFAULT_WORKSPACE_ID: i64 = 1;
+
+/// A tenant owning domains, links, tokens and users, e.g. a business unit.
+///
+/// Callers only see the domains, links and tokens of their own workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+    pub quotas: WorkspaceQuotas,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Limits of a workspace; `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkspaceQuotas {
+    /// Maximum number of non-deleted links.
+    pub max_links: Option<i64>,
+    /// Maximum number of non-deleted domains.
+    pub max_domains: Option<i64>,
+    /// Redirects served per calendar month (UTC).
+    pub monthly_click_allowance: Option<i64>,
+}
+
+impl WorkspaceQuotas {
+    /// Returns true if no more links may be created with `used` links in the workspace.
+    pub fn links_exhausted(&self, used: i64) -> bool {
+        self.max_links.is_some_and(|max| used >= max)
+    }
+
+    /// Returns true if no more domains may be created with `used` domains in the workspace.
+    pub fn domains_exhausted(&self, used: i64) -> bool {
+        self.max_domains.is_some_and(|max| used >= max)
+    }
+
+    /// Returns true if `clicks` this month used up the click allowance.
+    pub fn clicks_exhausted(&self, clicks: i64) -> bool {
+        self.monthly_click_allowance
+            .is_some_and(|max| clicks >= max)
+    }
+}
+
+/// Current consumption of a workspace's quotas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkspaceUsage {
+    pub links: i64,
+    pub domains: i64,
+    pub clicks_this_month: i64,
+}
+
+/// Returns the start of the calendar month (UTC) containing `now`.
+///
+/// The monthly click allowance counts clicks from this instant.
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .expect("the first of a month at midnight UTC always exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_quotas_never_exhaust() {
+        let quotas = WorkspaceQuotas::default();
+
+        assert!(!quotas.links_exhausted(i64::MAX));
+        assert!(!quotas.domains_exhausted(i64::MAX));
+        assert!(!quotas.clicks_exhausted(i64::MAX));
+    }
+
+    #[test]
+    fn test_quotas_exhaust_at_limit() {
+        let quotas = WorkspaceQuotas {
+            max_links: Some(2),
+            max_domains: Some(0),
+            monthly_click_allowance: Some(100),
+        };
+
+        assert!(!quotas.links_exhausted(1));
+        assert!(quotas.links_exhausted(2));
+        assert!(quotas.domains_exhausted(0));
+        assert!(!quotas.clicks_exhausted(99));
+        assert!(quotas.clicks_exhausted(100));
+    }
+
+    #[test]
+    fn test_month_start() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 13, 45, 7).unwrap();
+
+        assert_eq!(
+            month_start(now),
+            Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
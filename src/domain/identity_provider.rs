@@ -0,0 +1,63 @@
+//! Interface to the identity provider behind dashboard single sign-on.
+
+use crate::error::AppError;
+use async_trait::async_trait;
+
+/// Parameters of one sign-in attempt, sent to the identity provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationRequest {
+    /// Opaque value echoed back to the callback; ties it to the browser that started the login.
+    pub state: String,
+    /// Value the ID token must carry; prevents replaying an ID token.
+    pub nonce: String,
+    /// PKCE `S256` challenge of the code verifier.
+    pub code_challenge: String,
+}
+
+/// Verified claims about the signed-in user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityClaims {
+    /// Stable user identifier at the identity provider (`sub`).
+    pub subject: String,
+    pub email: Option<String>,
+    /// Whether the provider verified that the user owns `email`.
+    pub email_verified: bool,
+    pub groups: Vec<String>,
+}
+
+/// An OpenID Connect identity provider using the authorization code flow with PKCE.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::oidc::OidcClient`] - discovery-based OpenID Connect client
+/// - Test mocks available with `cfg(test)`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Returns the URL to send the browser to for signing in.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] if the provider cannot be reached.
+    async fn authorization_url(&self, request: &AuthorizationRequest) -> Result<String, AppError>;
+
+    /// Redeems an authorization code and returns the claims of the verified ID token.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - code passed to the callback
+    /// - `code_verifier` - PKCE verifier of the [`AuthorizationRequest::code_challenge`]
+    /// - `nonce` - expected nonce of the ID token
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Unauthorized`] if the provider rejects the code or the
+    /// ID token is invalid (signature, issuer, audience, expiry or nonce).
+    /// Returns [`AppError::Internal`] if the provider cannot be reached.
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdentityClaims, AppError>;
+}
@@ -10,21 +10,27 @@
 //! - [`repositories`] - Data access trait definitions
 //! - [`click_event`] - Click tracking event model
 //! - [`click_worker`] - Asynchronous click processing worker
+//! - [`click_journal`] - Local journal for clicks the queue or database could not take
+//! - [`click_counters`] - Flushes of recorded clicks to the `links.clicks` counters
+//! - [`identity_provider`] - Single sign-on identity provider interface
 //!
 //! # Design Principles
 //!
 //! - Domain layer has no dependencies on infrastructure or presentation layers
-//! - Repository traits define contracts implemented by infrastructure layer
+//! - Repository and identity provider traits define contracts implemented by infrastructure layer
 //! - Business logic is encapsulated in services (see [`crate::application::services`])
 //!
 //! # Click Processing Flow
 //!
 //! 1. HTTP handler receives redirect request
 //! 2. [`click_event::ClickEvent`] is sent to async channel
-//! 3. [`click_worker::run_click_worker`] processes events with retry logic
+//! 3. [`click_worker::run_click_worker`] writes events in batches with retry logic
 //! 4. Click data is persisted via [`repositories::StatsRepository`]
 
+pub mod click_counters;
 pub mod click_event;
+pub mod click_journal;
 pub mod click_worker;
 pub mod entities;
+pub mod identity_provider;
 pub mod repositories;
@@ -0,0 +1,96 @@
+//! Repository trait for the audit log.
+
+use crate::domain::entities::{AuditEntity, AuditEvent};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Filter criteria for audit log queries.
+#[derive(Debug, Clone)]
+pub struct AuditFilter {
+    /// Workspace to list; `None` means every workspace.
+    pub workspace_id: Option<i64>,
+    pub entity: Option<AuditEntity>,
+    /// Actor name (token name or user email), or token ID.
+    pub actor: Option<String>,
+    pub from_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl AuditFilter {
+    /// Creates a new filter with pagination parameters.
+    pub fn new(offset: i64, limit: i64) -> Self {
+        Self {
+            workspace_id: None,
+            entity: None,
+            actor: None,
+            from_date: None,
+            to_date: None,
+            offset,
+            limit,
+        }
+    }
+
+    /// Limits the query to one workspace.
+    pub fn with_workspace(mut self, workspace_id: Option<i64>) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
+
+    /// Limits the query to one kind of record.
+    pub fn with_entity(mut self, entity: Option<AuditEntity>) -> Self {
+        self.entity = entity;
+        self
+    }
+
+    /// Limits the query to one actor, by name or token ID.
+    pub fn with_actor(mut self, actor: Option<String>) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// Adds date range filtering to the query.
+    pub fn with_date_range(
+        mut self,
+        from_date: Option<DateTime<Utc>>,
+        to_date: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.from_date = from_date;
+        self.to_date = to_date;
+        self
+    }
+}
+
+/// Repository interface for the append-only audit log.
+///
+/// Events can only be added; the table rejects updates and deletes. They are
+/// added by the repositories of links, domains and tokens, in the transaction
+/// of the change they record.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgAuditRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_audit.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// Lists events matching the filter, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn list(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AppError>;
+
+    /// Counts events matching the filter, ignoring its pagination.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn count(&self, filter: &AuditFilter) -> Result<i64, AppError>;
+}
@@ -1,13 +1,13 @@
 //! Repository trait for domain management.
 
-use crate::domain::entities::{Domain, NewDomain, UpdateDomain};
+use crate::domain::entities::{AuditContext, Domain, NewDomain, UpdateDomain};
 use crate::error::AppError;
 use async_trait::async_trait;
 
 /// Repository interface for managing domains.
 ///
 /// Handles CRUD operations for domains that serve as namespaces for short links.
-/// Each domain can have one default domain for the system.
+/// Each workspace can have one default domain.
 ///
 /// # Implementations
 ///
@@ -20,13 +20,18 @@ use async_trait::async_trait;
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait DomainRepository: Send + Sync {
-    /// Creates a new domain.
+    /// Creates a new domain, recording it in the audit log for `context` in the
+    /// same transaction.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Conflict`] if a domain with the same name already exists.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn create(&self, new_domain: NewDomain) -> Result<Domain, AppError>;
+    async fn create(
+        &self,
+        new_domain: NewDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError>;
 
     /// Finds a domain by its database ID.
     ///
@@ -42,45 +47,59 @@ pub trait DomainRepository: Send + Sync {
     /// Returns [`AppError::Internal`] on database errors.
     async fn find_by_name(&self, domain: &str) -> Result<Option<Domain>, AppError>;
 
-    /// Retrieves the default domain for the system.
+    /// Retrieves the default domain of a workspace.
     ///
     /// # Errors
     ///
-    /// Returns [`AppError::NotFound`] if no default domain is configured.
-    /// Returns [`AppError::Internal`] on database errors.
-    async fn get_default(&self) -> Result<Domain, AppError>;
+    /// Returns [`AppError::Internal`] if the workspace has no default domain or on
+    /// database errors.
+    async fn get_default(&self, workspace_id: i64) -> Result<Domain, AppError>;
 
-    /// Lists all domains, optionally filtered by active status.
+    /// Lists all domains, optionally filtered by workspace and active status.
     ///
     /// # Arguments
     ///
+    /// - `workspace_id` - If set, returns only the workspace's domains
     /// - `only_active` - If true, returns only active domains
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn list(&self, only_active: bool) -> Result<Vec<Domain>, AppError>;
+    async fn list(
+        &self,
+        workspace_id: Option<i64>,
+        only_active: bool,
+    ) -> Result<Vec<Domain>, AppError>;
 
-    /// Updates an existing domain.
+    /// Updates an existing domain, recording the change in the audit log for
+    /// `context` in the same transaction.
+    ///
+    /// `is_default: Some(true)` makes the domain its workspace's default, like
+    /// [`DomainRepository::set_default`]; `Some(false)` is ignored.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::NotFound`] if the domain does not exist.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn update(&self, id: i64, update: UpdateDomain) -> Result<Domain, AppError>;
+    async fn update(
+        &self,
+        id: i64,
+        update: UpdateDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError>;
 
-    /// Deletes a domain.
+    /// Soft-deletes a domain, recording it in the audit log for `context` in the
+    /// same transaction.
     ///
     /// # Errors
     ///
-    /// Returns [`AppError::NotFound`] if the domain does not exist.
-    /// Returns [`AppError::Conflict`] if the domain has associated links.
+    /// Returns [`AppError::NotFound`] if the domain does not exist or is already deleted.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn delete(&self, id: i64) -> Result<(), AppError>;
+    async fn delete(&self, id: i64, context: &AuditContext) -> Result<(), AppError>;
 
-    /// Sets a domain as the system default.
+    /// Sets a domain as the default of its workspace.
     ///
-    /// Only one domain can be marked as default at a time.
+    /// Only one domain per workspace can be marked as default at a time.
     ///
     /// # Errors
     ///
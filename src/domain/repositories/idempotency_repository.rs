@@ -0,0 +1,86 @@
+//! Repository trait for idempotency key storage.
+
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Stored state of an idempotency key.
+#[derive(Debug, Clone)]
+pub struct IdempotencyRecord {
+    /// Hash of the request body the key was first used with.
+    pub request_hash: String,
+    /// Serialized response; `None` while the first request is still in flight.
+    pub response: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Repository interface for idempotency keys.
+///
+/// Keys are namespaced by `scope`, the caller's actor key such as `token:7` or
+/// `user:3` (see [`Principal::actor_key`](crate::domain::entities::Principal::actor_key)),
+/// so two clients can use the same key without seeing each other's responses.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgIdempotencyRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_idempotency.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    /// Reserves a key for a new request.
+    ///
+    /// Succeeds when the key is unused, has expired, or is still in flight but was
+    /// reserved before `stale_before` (the original request was abandoned).
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the key was reserved for this request
+    /// - `Ok(false)` if the key is held by another request
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn try_reserve(
+        &self,
+        scope: &str,
+        key: &str,
+        request_hash: &str,
+        expires_at: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<bool, AppError>;
+
+    /// Finds a key within a scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find(&self, scope: &str, key: &str) -> Result<Option<IdempotencyRecord>, AppError>;
+
+    /// Stores the response of a reserved key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn complete(&self, scope: &str, key: &str, response: &Value) -> Result<(), AppError>;
+
+    /// Deletes a reserved key that has no response yet, so the next request
+    /// with it is processed instead of waiting for the in-flight timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn release(&self, scope: &str, key: &str) -> Result<(), AppError>;
+
+    /// Deletes all expired keys and returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn delete_expired(&self) -> Result<u64, AppError>;
+}
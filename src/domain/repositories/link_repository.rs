@@ -1,8 +1,9 @@
 //! Repository trait for short link data access.
 
-use crate::domain::entities::{Link, LinkPatch, NewLink};
+use crate::domain::entities::{AuditContext, Link, LinkPatch, NewLink, Visibility};
 use crate::error::AppError;
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 /// Repository interface for managing short links.
 ///
@@ -22,14 +23,17 @@ use async_trait::async_trait;
 pub trait LinkRepository: Send + Sync {
     /// Creates a new short link.
     ///
+    /// The workspace's link quota is checked, and the creation recorded in the
+    /// audit log for `context`, in the same transaction as the insert.
+    ///
     /// # Errors
     ///
-    /// Returns [`AppError::Conflict`] if:
-    /// - The short code already exists for the given domain
-    /// - The long URL is already shortened for the given domain
+    /// Returns [`AppError::Conflict`] if the short code already exists for the given domain.
+    ///
+    /// Returns [`AppError::Forbidden`] if the workspace's link quota is used up.
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn create(&self, new_link: NewLink) -> Result<Link, AppError>;
+    async fn create(&self, new_link: NewLink, context: &AuditContext) -> Result<Link, AppError>;
 
     /// Finds a link by its short code and domain.
     ///
@@ -43,18 +47,29 @@ pub trait LinkRepository: Send + Sync {
     /// Returns [`AppError::Internal`] on database errors.
     async fn find_by_code(&self, code: &str, domain_id: i64) -> Result<Option<Link>, AppError>;
 
-    /// Finds a link by its original long URL and domain.
+    /// Looks up the IDs of many links at once by `(domain_id, code)`.
     ///
-    /// Used to check if a URL has already been shortened for a specific domain.
+    /// Like [`LinkRepository::find_by_code`], deleted links are included. Keys
+    /// without a link are missing from the returned map.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn find_by_long_url(
+    async fn find_ids_by_codes(
         &self,
-        long_url: &str,
-        domain_id: i64,
-    ) -> Result<Option<Link>, AppError>;
+        keys: &[(i64, String)],
+    ) -> Result<HashMap<(i64, String), i64>, AppError>;
+
+    /// Finds the non-deleted links for an original long URL and domain, oldest first.
+    ///
+    /// Used to check if a URL has already been shortened for a specific domain.
+    /// Several links may exist for one URL (see [`DedupePolicy::AlwaysNew`](crate::domain::entities::DedupePolicy::AlwaysNew)).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_by_long_url(&self, long_url: &str, domain_id: i64)
+    -> Result<Vec<Link>, AppError>;
 
     /// Lists links with pagination support.
     ///
@@ -63,6 +78,7 @@ pub trait LinkRepository: Send + Sync {
     /// - `page` - Page number (1-indexed)
     /// - `page_size` - Number of items per page
     /// - `domain_id` - Optional domain filter
+    /// - `visibility` - Links the caller may see
     ///
     /// # Errors
     ///
@@ -72,16 +88,19 @@ pub trait LinkRepository: Send + Sync {
         page: i64,
         page_size: i64,
         domain_id: Option<i64>,
+        visibility: &Visibility,
     ) -> Result<Vec<Link>, AppError>;
 
-    /// Counts total links, optionally filtered by domain.
+    /// Counts the links the caller may see, optionally filtered by domain.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn count(&self, domain_id: Option<i64>) -> Result<i64, AppError>;
+    async fn count(&self, domain_id: Option<i64>, visibility: &Visibility)
+    -> Result<i64, AppError>;
 
-    /// Soft-deletes a link by setting `deleted_at = now()`.
+    /// Soft-deletes a link by setting `deleted_at = now()`, recording the deletion
+    /// in the audit log for `context` in the same transaction.
     ///
     /// Returns `Ok(true)` if the link was found and deleted, `Ok(false)` if not found
     /// or already deleted.
@@ -89,16 +108,31 @@ pub trait LinkRepository: Send + Sync {
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn soft_delete(&self, code: &str, domain_id: i64) -> Result<bool, AppError>;
+    async fn soft_delete(
+        &self,
+        code: &str,
+        domain_id: i64,
+        context: &AuditContext,
+    ) -> Result<bool, AppError>;
 
     /// Partially updates a link.
     ///
     /// Only fields present in [`LinkPatch`] are modified. `None` fields are unchanged.
-    /// When `patch.restore` is `true`, `deleted_at` is cleared.
+    /// When `patch.restore` is `true`, `deleted_at` is cleared; restoring a deleted
+    /// link is limited by the workspace's link quota like creating one. The change
+    /// is recorded in the audit log for `context` in the same transaction, as
+    /// `restore` if it restored the link and `update` otherwise.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::NotFound`] if no link matches `code` + `domain_id`.
+    /// Returns [`AppError::Forbidden`] if restoring would exceed the workspace's link quota.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn update(&self, code: &str, domain_id: i64, patch: LinkPatch) -> Result<Link, AppError>;
+    async fn update(
+        &self,
+        code: &str,
+        domain_id: i64,
+        patch: LinkPatch,
+        context: &AuditContext,
+    ) -> Result<Link, AppError>;
 }
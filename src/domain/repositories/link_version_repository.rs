@@ -0,0 +1,36 @@
+//! Repository trait for link version history.
+
+use crate::domain::entities::LinkVersion;
+use crate::error::AppError;
+use async_trait::async_trait;
+
+/// Repository interface for the versions of links.
+///
+/// Versions are added by [`LinkRepository`](super::LinkRepository) in the
+/// transaction that creates or changes the link.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgLinkVersionRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_link_version.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait LinkVersionRepository: Send + Sync {
+    /// Lists a link's versions, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn list(&self, link_id: i64) -> Result<Vec<LinkVersion>, AppError>;
+
+    /// Finds one version of a link.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find(&self, link_id: i64, version: i32) -> Result<Option<LinkVersion>, AppError>;
+}
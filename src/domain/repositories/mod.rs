@@ -13,29 +13,59 @@
 //! # Available Repositories
 //!
 //! - [`LinkRepository`] - Short link CRUD operations
+//! - [`LinkVersionRepository`] - Version history of links
 //! - [`StatsRepository`] - Click tracking and statistics
 //! - [`DomainRepository`] - Domain management
 //! - [`TokenRepository`] - API token authentication
+//! - [`IdempotencyRepository`] - Idempotency keys for retried requests
+//! - [`SessionRepository`] - Dashboard sessions
+//! - [`UserRepository`] - Users and teams
+//! - [`WorkspaceRepository`] - Workspaces and their quota usage
+//! - [`AuditRepository`] - Append-only audit log of changes
 //!
 //! # Testing
 //!
 //! See integration tests in `tests/repository_*.rs` for usage examples.
 
+pub mod audit_repository;
 pub mod domain_repository;
+pub mod idempotency_repository;
 pub mod link_repository;
+pub mod link_version_repository;
+pub mod session_repository;
 pub mod stats_repository;
 pub mod token_repository;
+pub mod user_repository;
+pub mod workspace_repository;
 
+pub use audit_repository::{AuditFilter, AuditRepository};
 pub use domain_repository::DomainRepository;
+pub use idempotency_repository::{IdempotencyRecord, IdempotencyRepository};
 pub use link_repository::LinkRepository;
-pub use stats_repository::{DetailedStats, LinkStats, StatsFilter, StatsRepository};
-pub use token_repository::{ApiToken, TokenRepository};
+pub use link_version_repository::LinkVersionRepository;
+pub use session_repository::{DashboardSession, SessionOwner, SessionRepository};
+pub use stats_repository::{DetailedStats, LinkStats, PopularLink, StatsFilter, StatsRepository};
+pub use token_repository::{ApiToken, NewApiToken, TokenRepository};
+pub use user_repository::UserRepository;
+pub use workspace_repository::WorkspaceRepository;
 
+#[cfg(test)]
+pub use audit_repository::MockAuditRepository;
 #[cfg(test)]
 pub use domain_repository::MockDomainRepository;
 #[cfg(test)]
+pub use idempotency_repository::MockIdempotencyRepository;
+#[cfg(test)]
 pub use link_repository::MockLinkRepository;
 #[cfg(test)]
+pub use link_version_repository::MockLinkVersionRepository;
+#[cfg(test)]
+pub use session_repository::MockSessionRepository;
+#[cfg(test)]
 pub use stats_repository::MockStatsRepository;
 #[cfg(test)]
 pub use token_repository::MockTokenRepository;
+#[cfg(test)]
+pub use user_repository::MockUserRepository;
+#[cfg(test)]
+pub use workspace_repository::MockWorkspaceRepository;
@@ -0,0 +1,83 @@
+//! Repository trait for dashboard session storage.
+
+use crate::domain::entities::Principal;
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// A logged-in dashboard session.
+#[derive(Debug, Clone)]
+pub struct DashboardSession {
+    /// SHA-256 of the session ID; the ID itself only lives in the cookie.
+    pub id_hash: String,
+    /// Permissions of the API token or single sign-on user that logged in.
+    pub principal: Principal,
+    /// Value the `X-CSRF-Token` header must carry on state-changing requests.
+    pub csrf_token: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Who a new session belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionOwner {
+    /// Logged in with an API token; the session has the token's permissions.
+    Token(i64),
+    /// Signed in through single sign-on; the session has the user's role and
+    /// sees the user's links.
+    User(i64),
+}
+
+/// Repository interface for dashboard sessions.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgSessionRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_session.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    /// Stores a new session.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn create_session(
+        &self,
+        id_hash: &str,
+        owner: &SessionOwner,
+        csrf_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError>;
+
+    /// Finds a session by the hash of its ID.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(session))` if the session exists, has not expired, and its token
+    ///   (if any) is neither revoked nor expired
+    /// - `Ok(None)` otherwise
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_session(&self, id_hash: &str) -> Result<Option<DashboardSession>, AppError>;
+
+    /// Deletes a session. Deleting a missing session is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn delete_session(&self, id_hash: &str) -> Result<(), AppError>;
+
+    /// Deletes all expired sessions and returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn delete_expired(&self) -> Result<u64, AppError>;
+}
@@ -1,6 +1,6 @@
 //! Repository trait for click statistics and analytics.
 
-use crate::domain::entities::{Click, NewClick};
+use crate::domain::entities::{Click, NewClick, Visibility};
 use crate::error::AppError;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -19,6 +19,22 @@ pub struct LinkStats {
     pub created_at: DateTime<Utc>,
 }
 
+/// A link that redirects, ranked by its recent clicks.
+///
+/// Holds what is needed to cache the link's redirect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopularLink {
+    pub link_id: i64,
+    pub domain_id: i64,
+    pub domain: String,
+    pub code: String,
+    pub long_url: String,
+    pub permanent: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Clicks since the start of the ranking window.
+    pub clicks: i64,
+}
+
 /// Detailed statistics with individual click records.
 ///
 /// Includes full link information, total count, and paginated click events.
@@ -31,7 +47,7 @@ pub struct DetailedStats {
 
 /// Filter criteria for statistics queries.
 ///
-/// Supports date range filtering, pagination, and domain scoping.
+/// Supports date range filtering, pagination, and domain and ownership scoping.
 #[derive(Debug, Clone)]
 pub struct StatsFilter {
     pub from_date: Option<DateTime<Utc>>,
@@ -39,17 +55,24 @@ pub struct StatsFilter {
     pub offset: i64,
     pub limit: i64,
     pub domain_id: Option<i64>,
+    /// Domains the caller may see; `None` means every domain.
+    pub domain_ids: Option<Vec<i64>>,
+    /// Links the caller may see.
+    pub visibility: Visibility,
 }
 
 impl StatsFilter {
-    /// Creates a new filter with pagination parameters.
-    pub fn new(offset: i64, limit: i64) -> Self {
+    /// Creates a new filter with pagination parameters over the links in
+    /// `visibility`.
+    pub fn new(offset: i64, limit: i64, visibility: Visibility) -> Self {
         Self {
             from_date: None,
             to_date: None,
             offset,
             limit,
             domain_id: None,
+            domain_ids: None,
+            visibility,
         }
     }
 
@@ -59,6 +82,12 @@ impl StatsFilter {
         self
     }
 
+    /// Limits the query to a set of domains (e.g. a domain-restricted token).
+    pub fn with_allowed_domains(mut self, domain_ids: Option<Vec<i64>>) -> Self {
+        self.domain_ids = domain_ids;
+        self
+    }
+
     /// Adds date range filtering to the query.
     pub fn with_date_range(
         mut self,
@@ -97,6 +126,38 @@ pub trait StatsRepository: Send + Sync {
     /// Returns [`AppError::Internal`] on database errors.
     async fn record_click(&self, new_click: NewClick) -> Result<Click, AppError>;
 
+    /// Records many click events with a single statement.
+    ///
+    /// Clicks of links that no longer exist are skipped instead of failing the
+    /// whole batch. Returns the number of clicks recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn record_clicks(&self, clicks: &[NewClick]) -> Result<u64, AppError>;
+
+    /// Adds the clicks recorded since the last flush to the denormalized
+    /// `links.clicks` counters.
+    ///
+    /// Counts the `link_clicks` rows above the stored watermark and moves the
+    /// watermark past them in the same transaction, so concurrent or repeated
+    /// flushes never count a click twice. Returns the number of links updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn flush_link_clicks(&self) -> Result<u64, AppError>;
+
+    /// Recounts `links.clicks` of every link from `link_clicks` and moves the
+    /// flush watermark past every click it counted.
+    ///
+    /// Returns the number of links whose counter was wrong.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn reconcile_link_clicks(&self) -> Result<u64, AppError>;
+
     /// Retrieves detailed statistics for a specific short code.
     ///
     /// Includes individual click records with pagination and optional filtering.
@@ -117,19 +178,25 @@ pub trait StatsRepository: Send + Sync {
 
     /// Retrieves aggregated statistics for all links.
     ///
-    /// Returns a paginated list with total click counts per link.
+    /// Returns a paginated list with total click counts per link. Without a
+    /// date range the totals come from the `links.clicks` counters, which lag
+    /// behind `link_clicks` until the next counter flush.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
     async fn get_all_stats(&self, filter: StatsFilter) -> Result<Vec<LinkStats>, AppError>;
 
-    /// Counts the total number of links in the system.
+    /// Counts the links the caller may see, optionally limited to a set of domains.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn count_all_links(&self) -> Result<i64, AppError>;
+    async fn count_all_links(
+        &self,
+        domain_ids: Option<Vec<i64>>,
+        visibility: &Visibility,
+    ) -> Result<i64, AppError>;
 
     /// Counts clicks for a specific link within an optional date range.
     ///
@@ -142,4 +209,18 @@ pub trait StatsRepository: Send + Sync {
         from_date: Option<DateTime<Utc>>,
         to_date: Option<DateTime<Utc>>,
     ) -> Result<i64, AppError>;
+
+    /// Returns the links with the most clicks since `since`, most clicked first.
+    ///
+    /// Only links that currently redirect are included: deleted and expired
+    /// links and links of deleted domains are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn most_clicked(
+        &self,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<PopularLink>, AppError>;
 }
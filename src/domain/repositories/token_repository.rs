@@ -1,25 +1,165 @@
 //! Repository trait for API token authentication.
 
+use crate::domain::entities::{
+    Actor, AuditContext, AuditEntity, Auditable, DEFAULT_WORKSPACE_ID, Principal, Scope, UserRef,
+};
 use crate::error::AppError;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
 
 /// API token entity with metadata.
 ///
-/// Tokens are stored as SHA-256 hashes for security.
+/// Tokens are stored as HMAC-SHA256 hashes for security.
 #[derive(Debug, Clone)]
 pub struct ApiToken {
     pub id: i64,
     pub name: String,
     pub token_hash: String,
+    pub scopes: Vec<Scope>,
+    /// Domains the token may act on; `None` means every domain.
+    pub domain_ids: Option<Vec<i64>>,
     pub created_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
+    /// When the token stops authenticating; `None` never expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// User the token acts for; `None` sees every link of the workspace.
+    pub user: Option<UserRef>,
+    /// Workspace the token acts in.
+    pub workspace_id: i64,
+}
+
+impl ApiToken {
+    /// Returns true if the token has an expiry that is not after `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Returns the ID of the signing key that produced `token_hash`.
+    ///
+    /// `None` for hashes stored before key IDs were introduced.
+    pub fn key_id(&self) -> Option<&str> {
+        self.token_hash.split_once(':').map(|(id, _)| id)
+    }
+
+    /// Builds the request principal for this token.
+    ///
+    /// A token bound to a user never has more scopes than the user's role.
+    pub fn principal(&self) -> Principal {
+        let scopes = match self.user {
+            Some(user) => {
+                let allowed = user.role.scopes();
+                self.scopes
+                    .iter()
+                    .copied()
+                    .filter(|scope| allowed.contains(scope))
+                    .collect()
+            }
+            None => self.scopes.clone(),
+        };
+
+        Principal {
+            actor: Actor::Token {
+                id: self.id,
+                name: self.name.clone(),
+            },
+            scopes,
+            domain_ids: self.domain_ids.clone(),
+            user: self.user,
+            workspace_id: self.workspace_id,
+        }
+    }
+}
+
+impl Auditable for ApiToken {
+    const ENTITY: AuditEntity = AuditEntity::Token;
+
+    fn audit_id(&self) -> i64 {
+        self.id
+    }
+
+    fn audit_workspace_id(&self) -> i64 {
+        self.workspace_id
+    }
+
+    /// Token metadata; the hash is left out.
+    fn audit_snapshot(&self) -> Value {
+        let scopes: Vec<_> = self.scopes.iter().map(Scope::as_str).collect();
+        json!({
+            "name": self.name,
+            "scopes": scopes,
+            "domain_ids": self.domain_ids,
+            "expires_at": self.expires_at,
+            "revoked_at": self.revoked_at,
+            "user_id": self.user.map(|user| user.id),
+        })
+    }
+}
+
+/// Data for creating a new API token.
+///
+/// Starts with every scope, no domain restriction, no expiry and no user, in the
+/// default workspace.
+#[derive(Debug, Clone)]
+pub struct NewApiToken {
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<Scope>,
+    pub domain_ids: Option<Vec<i64>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i64>,
+    pub workspace_id: i64,
+}
+
+impl NewApiToken {
+    /// Creates a full-access token that never expires.
+    pub fn new(name: impl Into<String>, token_hash: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            token_hash: token_hash.into(),
+            scopes: Scope::ALL.to_vec(),
+            domain_ids: None,
+            expires_at: None,
+            user_id: None,
+            workspace_id: DEFAULT_WORKSPACE_ID,
+        }
+    }
+
+    /// Limits the token to `scopes`.
+    pub fn with_scopes(mut self, scopes: Vec<Scope>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Limits the token to domains; `None` allows every domain.
+    pub fn with_domains(mut self, domain_ids: Option<Vec<i64>>) -> Self {
+        self.domain_ids = domain_ids;
+        self
+    }
+
+    /// Sets when the token stops authenticating.
+    pub fn with_expiry(mut self, expires_at: Option<DateTime<Utc>>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
+
+    /// Binds the token to a user; `None` leaves it unbound.
+    pub fn with_user(mut self, user_id: Option<i64>) -> Self {
+        self.user_id = user_id;
+        self
+    }
+
+    /// Places the token in a workspace.
+    pub fn with_workspace(mut self, workspace_id: i64) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
 }
 
 /// Repository interface for API token management.
 ///
 /// Handles token validation, creation, and revocation for API authentication.
-/// Tokens are hashed using SHA-256 before storage.
+/// Tokens are hashed using HMAC-SHA256 before storage.
 ///
 /// # Implementations
 ///
@@ -32,17 +172,20 @@ pub struct ApiToken {
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait TokenRepository: Send + Sync {
-    /// Validates a token hash against stored credentials.
+    /// Validates a token against stored credentials.
+    ///
+    /// `token_hashes` holds every form the token's hash may be stored in (one per
+    /// signing key); they are matched in a single indexed lookup.
     ///
     /// # Returns
     ///
-    /// - `Ok(true)` if the token is valid and not revoked
-    /// - `Ok(false)` if the token is invalid or revoked
+    /// - `Ok(Some(token))` if the token is valid, not revoked and not expired
+    /// - `Ok(None)` if the token is invalid, revoked or expired
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn validate_token(&self, token_hash: &str) -> Result<bool, AppError>;
+    async fn validate_token(&self, token_hashes: &[String]) -> Result<Option<ApiToken>, AppError>;
 
     /// Updates the last_used timestamp for a token.
     ///
@@ -53,25 +196,63 @@ pub trait TokenRepository: Send + Sync {
     /// Returns [`AppError::Internal`] on database errors.
     async fn update_last_used(&self, token_hash: &str) -> Result<(), AppError>;
 
-    /// Creates a new API token.
+    /// Replaces a token's stored hash, e.g. with one made by a newer signing key.
     ///
-    /// # Arguments
+    /// Does nothing if the stored hash is no longer `old_hash`, so concurrent
+    /// re-hashes of the same token are harmless.
+    ///
+    /// # Errors
     ///
-    /// - `name` - Human-readable token identifier
-    /// - `token_hash` - SHA-256 hash of the raw token
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn rehash_token(&self, id: i64, old_hash: &str, new_hash: &str) -> Result<(), AppError>;
+
+    /// Creates a new API token, recording it in the audit log for `context` in the
+    /// same transaction.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Conflict`] if a token with the same hash already exists.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn create_token(&self, name: &str, token_hash: &str) -> Result<ApiToken, AppError>;
+    async fn create_token(
+        &self,
+        new_token: NewApiToken,
+        context: &AuditContext,
+    ) -> Result<ApiToken, AppError>;
 
-    /// Lists all tokens in the system.
+    /// Replaces a token with a new one that has the same name, scopes, domains and user.
+    ///
+    /// The old token keeps working until `grace_until` (or its own earlier expiry),
+    /// so clients can switch over without downtime. The new token and the old
+    /// token's new expiry are recorded in the audit log for `context` in the same
+    /// transaction.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` - token to replace
+    /// - `token_hash` - hash of the replacement token
+    /// - `expires_at` - expiry of the replacement token, `None` for no expiry
+    /// - `grace_until` - when the old token stops working
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the token does not exist, is revoked or expired.
+    /// Returns [`AppError::Conflict`] if a token with the new hash already exists.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn rotate_token(
+        &self,
+        id: i64,
+        token_hash: &str,
+        expires_at: Option<DateTime<Utc>>,
+        grace_until: DateTime<Utc>,
+        context: &AuditContext,
+    ) -> Result<ApiToken, AppError>;
+
+    /// Lists the tokens of a workspace, or of every workspace if `None`.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
-    async fn list_tokens(&self) -> Result<Vec<ApiToken>, AppError>;
+    async fn list_tokens(&self, workspace_id: Option<i64>) -> Result<Vec<ApiToken>, AppError>;
 
     /// Finds a token by its database ID.
     ///
@@ -82,6 +263,8 @@ pub trait TokenRepository: Send + Sync {
 
     /// Finds a token by its name.
     ///
+    /// Rotated tokens share a name; the most recently created one is returned.
+    ///
     /// # Errors
     ///
     /// Returns [`AppError::Internal`] on database errors.
@@ -89,11 +272,13 @@ pub trait TokenRepository: Send + Sync {
 
     /// Revokes a token, preventing further authentication.
     ///
-    /// Sets the `revoked_at` timestamp to the current time.
+    /// Sets the `revoked_at` timestamp to the current time and records the
+    /// revocation in the audit log for `context` in the same transaction.
+    /// Revoking an already revoked token changes and records nothing.
     ///
     /// # Errors
     ///
     /// Returns [`AppError::NotFound`] if the token does not exist.
     /// Returns [`AppError::Internal`] on database errors.
-    async fn revoke_token(&self, id: i64) -> Result<(), AppError>;
+    async fn revoke_token(&self, id: i64, context: &AuditContext) -> Result<(), AppError>;
 }
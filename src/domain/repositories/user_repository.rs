@@ -0,0 +1,113 @@
+//! Repository trait for users and teams.
+
+use crate::domain::entities::{Role, Team, User};
+use crate::error::AppError;
+use async_trait::async_trait;
+
+/// Repository interface for dashboard users and their teams.
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgUserRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_user.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Creates or updates the user signed in through single sign-on.
+    ///
+    /// The user is matched by `subject`, or else by email if they were created
+    /// with the CLI and have not signed in yet. `email` must be verified by the
+    /// identity provider, or `None` to match by `subject` alone. Their email and
+    /// role are replaced with the ones from the identity provider, which is
+    /// authoritative for the role; their team and workspace are kept.
+    /// New users join the default workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Conflict`] if another user already has the email.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn upsert_sso_user<'a>(
+        &self,
+        subject: &str,
+        email: Option<&'a str>,
+        role: Role,
+    ) -> Result<User, AppError>;
+
+    /// Creates a user in a workspace that is not (yet) signed in through single sign-on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Conflict`] if a user with the email already exists.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn create_user(
+        &self,
+        email: &str,
+        role: Role,
+        team_id: Option<i64>,
+        workspace_id: i64,
+    ) -> Result<User, AppError>;
+
+    /// Finds a user by email (case-insensitive).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError>;
+
+    /// Lists all users, ordered by email.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn list_users(&self) -> Result<Vec<User>, AppError>;
+
+    /// Changes the role of a user who has not signed in through single sign-on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the user does not exist.
+    /// Returns [`AppError::Conflict`] if the user signs in through single sign-on,
+    /// since their role comes from the identity provider.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn set_role(&self, id: i64, role: Role) -> Result<User, AppError>;
+
+    /// Moves a user to a team, or out of any team with `None`.
+    ///
+    /// Links keep the team they were created in.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the user does not exist.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn set_team(&self, id: i64, team_id: Option<i64>) -> Result<User, AppError>;
+
+    /// Creates a team in a workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Conflict`] if the workspace already has a team with the name.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn create_team(&self, name: &str, workspace_id: i64) -> Result<Team, AppError>;
+
+    /// Finds a team of a workspace by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_team_by_name(
+        &self,
+        name: &str,
+        workspace_id: i64,
+    ) -> Result<Option<Team>, AppError>;
+
+    /// Lists the teams of all workspaces, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn list_teams(&self) -> Result<Vec<Team>, AppError>;
+}
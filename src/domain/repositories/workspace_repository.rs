@@ -0,0 +1,81 @@
+//! Repository trait for workspaces and their quota usage.
+
+use crate::domain::entities::{Workspace, WorkspaceQuotas};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Repository interface for workspaces (tenants).
+///
+/// # Implementations
+///
+/// - [`crate::infrastructure::persistence::PgWorkspaceRepository`] - PostgreSQL implementation
+/// - Test mocks available with `cfg(test)`
+///
+/// # Examples
+///
+/// See integration tests: `tests/repository_workspace.rs`
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait WorkspaceRepository: Send + Sync {
+    /// Creates a workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Conflict`] if a workspace with the name already exists.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn create(&self, name: &str, quotas: WorkspaceQuotas) -> Result<Workspace, AppError>;
+
+    /// Finds a workspace by its database ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_by_id(&self, id: i64) -> Result<Option<Workspace>, AppError>;
+
+    /// Finds a workspace by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn find_by_name(&self, name: &str) -> Result<Option<Workspace>, AppError>;
+
+    /// Lists all workspaces, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn list(&self) -> Result<Vec<Workspace>, AppError>;
+
+    /// Replaces a workspace's quotas.
+    ///
+    /// Lowering a quota below the current usage keeps existing links and domains
+    /// but blocks new ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] if the workspace does not exist.
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn set_quotas(&self, id: i64, quotas: WorkspaceQuotas) -> Result<Workspace, AppError>;
+
+    /// Counts the non-deleted links of a workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn count_links(&self, id: i64) -> Result<i64, AppError>;
+
+    /// Counts the non-deleted domains of a workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn count_domains(&self, id: i64) -> Result<i64, AppError>;
+
+    /// Counts the clicks on the workspace's links since `since`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors.
+    async fn count_clicks_since(&self, id: i64, since: DateTime<Utc>) -> Result<i64, AppError>;
+}
@@ -8,7 +8,9 @@
 //! - [`AppError::Validation`] - Invalid input (400 Bad Request)
 //! - [`AppError::NotFound`] - Resource not found (404 Not Found)
 //! - [`AppError::Conflict`] - Duplicate resource (409 Conflict)
+//! - [`AppError::Unprocessable`] - Well-formed but unacceptable request (422 Unprocessable Entity)
 //! - [`AppError::Unauthorized`] - Authentication failed (401 Unauthorized)
+//! - [`AppError::Forbidden`] - Authenticated but not permitted (403 Forbidden)
 //! - [`AppError::Internal`] - Server error (500 Internal Server Error)
 //!
 //! ## Database Error Mapping
@@ -19,6 +21,9 @@
 //! - Row not found → [`AppError::NotFound`]
 //! - Connection pool issues → [`AppError::Internal`] with retry hints
 //!
+//! Cache errors of admin operations convert via [`From<CacheError>`] to
+//! [`AppError::Internal`].
+//!
 //! ## Observability
 //!
 //! All database errors emit metrics via `metrics::counter!` for monitoring.
@@ -31,19 +36,30 @@ use axum::{
 use serde::Serialize;
 use serde_json::{Value, json};
 use sqlx::Error as SqlxError;
+use utoipa::ToSchema;
 use validator::ValidationErrors;
 
-/// Internal structure for JSON error response body.
-#[derive(Serialize)]
-struct ErrorBody {
-    error: ErrorInfo,
+use crate::infrastructure::cache::CacheError;
+
+/// JSON error envelope returned by every failing API request.
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({
+    "error": { "code": "not_found", "message": "Link not found", "details": { "code": "abc123" } }
+}))]
+pub struct ErrorBody {
+    pub error: ErrorInfo,
 }
 
 /// Structured error information returned in API responses.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct ErrorInfo {
+    /// Machine-readable error code (e.g. `validation_error`, `not_found`).
+    #[schema(value_type = String, example = "not_found")]
     pub code: &'static str,
+    /// Human-readable description of the error.
     pub message: String,
+    /// Additional structured context; shape depends on the error.
+    #[schema(value_type = Object)]
     pub details: Value,
 }
 
@@ -51,13 +67,15 @@ pub struct ErrorInfo {
 ///
 /// Each variant corresponds to an HTTP status code and includes both
 /// a human-readable message and structured details for debugging.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AppError {
     Validation { message: String, details: Value },
     NotFound { message: String, details: Value },
     Gone { message: String, details: Value },
     Conflict { message: String, details: Value },
+    Unprocessable { message: String, details: Value },
     Unauthorized { message: String, details: Value },
+    Forbidden { message: String, details: Value },
     Internal { message: String, details: Value },
 }
 
@@ -86,6 +104,14 @@ impl AppError {
         }
     }
 
+    /// Creates an unprocessable entity error (422 Unprocessable Entity).
+    pub fn unprocessable(message: impl Into<String>, details: Value) -> Self {
+        Self::Unprocessable {
+            message: message.into(),
+            details,
+        }
+    }
+
     /// Creates a gone error (410 Gone) for resources that intentionally no longer exist.
     pub fn gone(message: impl Into<String>, details: Value) -> Self {
         Self::Gone {
@@ -110,6 +136,28 @@ impl AppError {
         }
     }
 
+    /// Creates a forbidden error (403 Forbidden).
+    pub fn forbidden(message: impl Into<String>, details: Value) -> Self {
+        Self::Forbidden {
+            message: message.into(),
+            details,
+        }
+    }
+
+    /// Returns the HTTP status the error is answered with.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Validation { .. } => StatusCode::BAD_REQUEST,
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Gone { .. } => StatusCode::GONE,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Unprocessable { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            AppError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
     /// Converts the error into structured error info for serialization.
     pub fn to_error_info(self) -> ErrorInfo {
         let (code, message, details) = match self {
@@ -117,7 +165,11 @@ impl AppError {
             AppError::NotFound { message, details } => ("not_found", message, details),
             AppError::Gone { message, details } => ("gone", message, details),
             AppError::Conflict { message, details } => ("conflict", message, details),
+            AppError::Unprocessable { message, details } => {
+                ("unprocessable_entity", message, details)
+            }
             AppError::Unauthorized { message, details } => ("unauthorized", message, details),
+            AppError::Forbidden { message, details } => ("forbidden", message, details),
             AppError::Internal { message, details } => ("internal_error", message, details),
         };
 
@@ -131,48 +183,12 @@ impl AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, code, message, details, add_www_authenticate) = match self {
-            AppError::Validation { message, details } => (
-                StatusCode::BAD_REQUEST,
-                "validation_error",
-                message,
-                details,
-                false,
-            ),
-            AppError::NotFound { message, details } => {
-                (StatusCode::NOT_FOUND, "not_found", message, details, false)
-            }
-            AppError::Gone { message, details } => {
-                (StatusCode::GONE, "gone", message, details, false)
-            }
-            AppError::Conflict { message, details } => {
-                (StatusCode::CONFLICT, "conflict", message, details, false)
-            }
-            AppError::Unauthorized { message, details } => (
-                StatusCode::UNAUTHORIZED,
-                "unauthorized",
-                message,
-                details,
-                true,
-            ),
-            AppError::Internal { message, details } => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "internal_error",
-                message,
-                details,
-                false,
-            ),
-        };
-
+        let status = self.status();
         let body = ErrorBody {
-            error: ErrorInfo {
-                code,
-                message,
-                details,
-            },
+            error: self.to_error_info(),
         };
 
-        if add_www_authenticate {
+        if status == StatusCode::UNAUTHORIZED {
             let mut headers = HeaderMap::new();
             headers.insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
             (status, headers, Json(body)).into_response()
@@ -188,6 +204,12 @@ impl From<SqlxError> for AppError {
     }
 }
 
+impl From<CacheError> for AppError {
+    fn from(e: CacheError) -> Self {
+        AppError::internal("Cache operation failed", json!({ "reason": e.to_string() }))
+    }
+}
+
 /// Maps SQLx errors to application errors with detailed context.
 ///
 /// Handles constraint violations, connection issues, and other database errors
@@ -205,8 +227,12 @@ pub fn map_sqlx_error(e: SqlxError) -> AppError {
                 let constraint = db_err.constraint().unwrap_or("unknown");
                 let (message, field) = match constraint {
                     "links_code_key" => ("This short code is already in use", "code"),
-                    "links_long_url_key" => ("This URL has already been shortened", "long_url"),
                     "api_tokens_token_hash_key" => ("Token already exists", "token"),
+                    "users_email_key" => ("A user with this email already exists", "email"),
+                    "teams_workspace_id_name_key" => {
+                        ("A team with this name already exists", "name")
+                    }
+                    "workspaces_name_key" => ("A workspace with this name already exists", "name"),
                     _ => {
                         tracing::warn!(
                             constraint = constraint,
@@ -330,6 +356,54 @@ pub fn map_sqlx_error(e: SqlxError) -> AppError {
     }
 }
 
+impl std::error::Error for AppError {}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Validation { message, .. } => write!(f, "Validation error: {}", message),
+            AppError::NotFound { message, .. } => write!(f, "Not found: {}", message),
+            AppError::Gone { message, .. } => write!(f, "Gone: {}", message),
+            AppError::Conflict { message, .. } => write!(f, "Conflict: {}", message),
+            AppError::Unprocessable { message, .. } => write!(f, "Unprocessable: {}", message),
+            AppError::Unauthorized { message, .. } => write!(f, "Unauthorized: {}", message),
+            AppError::Forbidden { message, .. } => write!(f, "Forbidden: {}", message),
+            AppError::Internal { message, .. } => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let details = json!({
+            "fields": errors
+                .field_errors()
+                .iter()
+                .map(|(field, errors)| {
+                    (
+                        field.to_string(),
+                        errors
+                            .iter()
+                            .map(|e| {
+                                json!({
+                                    "code": e.code,
+                                    "message": e.message.as_ref().map(|m| m.to_string()),
+                                    "params": e.params
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    )
+                })
+                .collect::<std::collections::HashMap<_, _>>()
+        });
+
+        AppError::Validation {
+            message: "Request validation failed".to_string(),
+            details,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,11 +436,21 @@ mod tests {
         assert_eq!(status(AppError::conflict("duplicate", json!({}))), StatusCode::CONFLICT);
     }
 
+    #[test]
+    fn test_unprocessable_is_422() {
+        assert_eq!(status(AppError::unprocessable("mismatch", json!({}))), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[test]
     fn test_unauthorized_is_401() {
         assert_eq!(status(AppError::unauthorized("token invalid", json!({}))), StatusCode::UNAUTHORIZED);
     }
 
+    #[test]
+    fn test_forbidden_is_403() {
+        assert_eq!(status(AppError::forbidden("missing scope", json!({}))), StatusCode::FORBIDDEN);
+    }
+
     #[test]
     fn test_internal_is_500() {
         assert_eq!(status(AppError::internal("oops", json!({}))), StatusCode::INTERNAL_SERVER_ERROR);
@@ -389,6 +473,8 @@ mod tests {
             AppError::not_found("x", json!({})),
             AppError::gone("x", json!({})),
             AppError::conflict("x", json!({})),
+            AppError::unprocessable("x", json!({})),
+            AppError::forbidden("x", json!({})),
             AppError::internal("x", json!({})),
         ] {
             let response = err.into_response();
@@ -399,6 +485,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_status_matches_response() {
+        let errors = || {
+            vec![
+                AppError::bad_request("bad", json!({})),
+                AppError::not_found("missing", json!({})),
+                AppError::gone("gone", json!({})),
+                AppError::conflict("dup", json!({})),
+                AppError::unprocessable("nope", json!({})),
+                AppError::unauthorized("who", json!({})),
+                AppError::forbidden("no", json!({})),
+                AppError::internal("oops", json!({})),
+            ]
+        };
+
+        for (err, same) in errors().into_iter().zip(errors()) {
+            assert_eq!(err.status(), status(same));
+        }
+    }
+
     // ── to_error_info codes ───────────────────────────────────────────────────
 
     #[test]
@@ -407,7 +513,9 @@ mod tests {
         assert_eq!(AppError::not_found("x", json!({})).to_error_info().code, "not_found");
         assert_eq!(AppError::gone("x", json!({})).to_error_info().code, "gone");
         assert_eq!(AppError::conflict("x", json!({})).to_error_info().code, "conflict");
+        assert_eq!(AppError::unprocessable("x", json!({})).to_error_info().code, "unprocessable_entity");
         assert_eq!(AppError::unauthorized("x", json!({})).to_error_info().code, "unauthorized");
+        assert_eq!(AppError::forbidden("x", json!({})).to_error_info().code, "forbidden");
         assert_eq!(AppError::internal("x", json!({})).to_error_info().code, "internal_error");
     }
 
@@ -423,49 +531,3 @@ mod tests {
         assert!(AppError::internal("crash", json!({})).to_string().contains("crash"));
     }
 }
-
-impl std::error::Error for AppError {}
-
-impl std::fmt::Display for AppError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AppError::Validation { message, .. } => write!(f, "Validation error: {}", message),
-            AppError::NotFound { message, .. } => write!(f, "Not found: {}", message),
-            AppError::Gone { message, .. } => write!(f, "Gone: {}", message),
-            AppError::Conflict { message, .. } => write!(f, "Conflict: {}", message),
-            AppError::Unauthorized { message, .. } => write!(f, "Unauthorized: {}", message),
-            AppError::Internal { message, .. } => write!(f, "Internal error: {}", message),
-        }
-    }
-}
-
-impl From<ValidationErrors> for AppError {
-    fn from(errors: ValidationErrors) -> Self {
-        let details = json!({
-            "fields": errors
-                .field_errors()
-                .iter()
-                .map(|(field, errors)| {
-                    (
-                        field.to_string(),
-                        errors
-                            .iter()
-                            .map(|e| {
-                                json!({
-                                    "code": e.code,
-                                    "message": e.message.as_ref().map(|m| m.to_string()),
-                                    "params": e.params
-                                })
-                            })
-                            .collect::<Vec<_>>()
-                    )
-                })
-                .collect::<std::collections::HashMap<_, _>>()
-        });
-
-        AppError::Validation {
-            message: "Request validation failed".to_string(),
-            details,
-        }
-    }
-}
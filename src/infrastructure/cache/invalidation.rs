@@ -0,0 +1,375 @@
+//! Cross-instance invalidation of in-process caches over Redis pub/sub.
+
+use super::service::{CacheError, CacheResult, CacheService};
+use super::tiered_cache::TieredCache;
+use futures_util::StreamExt;
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Redis channel carrying the keys invalidated by any instance.
+pub const INVALIDATION_CHANNEL: &str = "url:invalidate";
+
+/// Suffix of messages that evict every key starting with the rest of the message.
+const PREFIX_WILDCARD: char = '*';
+
+/// Delay before the first reconnect attempt after the subscription drops.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnect attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// What a message on [`INVALIDATION_CHANNEL`] asks instances to evict.
+#[derive(Debug, PartialEq)]
+enum Invalidation<'a> {
+    /// A single key.
+    Key(&'a str),
+    /// Every key starting with the prefix; an empty prefix evicts everything.
+    Prefix(&'a str),
+}
+
+impl<'a> Invalidation<'a> {
+    fn encode(&self) -> String {
+        match self {
+            Invalidation::Key(key) => key.to_string(),
+            Invalidation::Prefix(prefix) => format!("{}{}", prefix, PREFIX_WILDCARD),
+        }
+    }
+
+    fn decode(message: &'a str) -> Self {
+        match message.strip_suffix(PREFIX_WILDCARD) {
+            Some(prefix) => Invalidation::Prefix(prefix),
+            None => Invalidation::Key(message),
+        }
+    }
+
+    fn apply<C: CacheService>(&self, cache: &TieredCache<C>) {
+        match self {
+            Invalidation::Key(key) => cache.evict_local(key),
+            Invalidation::Prefix(prefix) => cache.evict_local_prefix(prefix),
+        }
+    }
+}
+
+/// Delay before the reconnect attempt after one that waited `backoff`.
+fn next_backoff(backoff: Duration) -> Duration {
+    (backoff * 2).min(MAX_BACKOFF)
+}
+
+/// Publishes invalidated cache keys to, and receives them from, every instance.
+///
+/// Each instance publishes the keys it invalidates on [`INVALIDATION_CHANNEL`]
+/// and runs [`InvalidationBus::listen`], which evicts received keys from its
+/// in-process cache. A message ending in `*`, such as `s.example.com:*`, evicts
+/// every key starting with the rest of it. An instance also receives its own
+/// messages; evicting a key twice is harmless.
+///
+/// # Reconnects
+///
+/// When the subscription drops, the listener reconnects with exponential backoff
+/// (1 to 30 seconds). Messages published in the meantime are lost, so the whole
+/// in-process cache is cleared on every new subscription.
+#[derive(Clone)]
+pub struct InvalidationBus {
+    client: Client,
+    publisher: ConnectionManager,
+}
+
+impl InvalidationBus {
+    /// Connects the publishing side of the bus.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CacheError::ConnectionError`] if the URL is invalid or the
+    /// connection cannot be established.
+    pub async fn connect(redis_url: &str) -> CacheResult<Self> {
+        let client = Client::open(redis_url).map_err(|e| {
+            CacheError::ConnectionError(format!("Failed to create Redis client: {}", e))
+        })?;
+
+        let publisher = ConnectionManager::new(client.clone()).await.map_err(|e| {
+            CacheError::ConnectionError(format!("Failed to connect to Redis: {}", e))
+        })?;
+
+        Ok(Self { client, publisher })
+    }
+
+    /// Tells every instance to evict `key` from its in-process cache.
+    ///
+    /// Failures are logged and counted in `cache_invalidations_failed_total`;
+    /// other instances then serve their copy until its L1 TTL runs out.
+    #[tracing::instrument(name = "cache.publish_invalidation", skip_all, fields(db.system = "redis", key = %key))]
+    pub async fn publish(&self, key: &str) {
+        let mut conn = self.publisher.clone();
+
+        match conn.publish::<_, _, i64>(INVALIDATION_CHANNEL, key).await {
+            Ok(receivers) => {
+                metrics::counter!("cache_invalidations_published_total").increment(1);
+                debug!(
+                    "Published invalidation of {} to {} instances",
+                    key, receivers
+                );
+            }
+            Err(e) => {
+                metrics::counter!("cache_invalidations_failed_total").increment(1);
+                warn!("Redis PUBLISH error for {}: {}", key, e);
+            }
+        }
+    }
+
+    /// Tells every instance to evict all keys starting with `prefix`.
+    pub async fn publish_prefix(&self, prefix: &str) {
+        self.publish(&Invalidation::Prefix(prefix).encode()).await;
+    }
+
+    /// Evicts every key received on the channel from `cache`'s in-process tier.
+    ///
+    /// Runs until the process exits, resubscribing whenever the connection drops.
+    pub async fn listen<C: CacheService>(self, cache: Arc<TieredCache<C>>) {
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            match self.subscribe().await {
+                Ok(mut pubsub) => {
+                    info!(
+                        "Subscribed to cache invalidations on {}",
+                        INVALIDATION_CHANNEL
+                    );
+                    backoff = INITIAL_BACKOFF;
+                    cache.clear_local();
+
+                    let mut messages = pubsub.on_message();
+                    while let Some(message) = messages.next().await {
+                        match message.get_payload::<String>() {
+                            Ok(key) => {
+                                metrics::counter!("cache_invalidations_received_total")
+                                    .increment(1);
+                                Invalidation::decode(&key).apply(&cache);
+                            }
+                            Err(e) => warn!("Ignoring malformed invalidation message: {}", e),
+                        }
+                    }
+                    warn!("Cache invalidation subscription dropped");
+                }
+                Err(e) => warn!("Failed to subscribe to cache invalidations: {}", e),
+            }
+
+            metrics::counter!("cache_invalidation_reconnects_total").increment(1);
+            tokio::time::sleep(backoff).await;
+            backoff = next_backoff(backoff);
+        }
+    }
+
+    /// Opens a dedicated connection subscribed to [`INVALIDATION_CHANNEL`].
+    async fn subscribe(&self) -> redis::RedisResult<redis::aio::PubSub> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(INVALIDATION_CHANNEL).await?;
+        Ok(pubsub)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::NullCache;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+    use tokio::sync::mpsc;
+
+    /// Reads one command, sent as an array of bulk strings.
+    async fn read_command(reader: &mut BufReader<OwnedReadHalf>) -> Option<Vec<String>> {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            args.push(String::from_utf8(arg).ok()?);
+        }
+        Some(args)
+    }
+
+    fn bulk(value: &str) -> String {
+        format!("${}\r\n{}\r\n", value.len(), value)
+    }
+
+    /// Stand-in for Redis that answers just enough for the bus.
+    ///
+    /// `PUBLISH` payloads are sent to `published`. When a connection subscribes,
+    /// its writing half is sent to `subscribed` so the test can push messages
+    /// and drop it to cut the subscription.
+    async fn fake_redis(
+        published: mpsc::UnboundedSender<String>,
+        subscribed: mpsc::UnboundedSender<OwnedWriteHalf>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let published = published.clone();
+                let subscribed = subscribed.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut reader = BufReader::new(reader);
+                    while let Some(args) = read_command(&mut reader).await {
+                        match args[0].to_ascii_uppercase().as_str() {
+                            "SUBSCRIBE" => {
+                                let reply =
+                                    format!("*3\r\n{}{}:1\r\n", bulk("subscribe"), bulk(&args[1]));
+                                writer.write_all(reply.as_bytes()).await.unwrap();
+                                subscribed.send(writer).unwrap();
+                                // Keep reading so the client's socket stays open.
+                                while read_command(&mut reader).await.is_some() {}
+                                return;
+                            }
+                            "PUBLISH" => {
+                                published.send(args[2].clone()).unwrap();
+                                writer.write_all(b":1\r\n").await.unwrap();
+                            }
+                            _ => writer.write_all(b"+OK\r\n").await.unwrap(),
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    async fn push(writer: &mut OwnedWriteHalf, payload: &str) {
+        let message = format!(
+            "*3\r\n{}{}{}",
+            bulk("message"),
+            bulk(INVALIDATION_CHANNEL),
+            bulk(payload)
+        );
+        writer.write_all(message.as_bytes()).await.unwrap();
+    }
+
+    async fn cached(cache: &TieredCache<NullCache>, key: &str) -> bool {
+        cache.get_url(key).await.unwrap().is_some()
+    }
+
+    async fn fill(cache: &TieredCache<NullCache>, keys: &[&str]) {
+        for key in keys {
+            cache
+                .set_url(key, "0:https://example.com", None)
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Waits until `key` has been evicted from `cache`.
+    async fn wait_evicted(cache: &TieredCache<NullCache>, key: &str) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while cached(cache, key).await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{key} was not evicted"));
+    }
+
+    #[test]
+    fn test_encode_and_decode_messages() {
+        let key = Invalidation::Key("s.example.com:abc");
+        let prefix = Invalidation::Prefix("s.example.com:");
+
+        assert_eq!(key.encode(), "s.example.com:abc");
+        assert_eq!(prefix.encode(), "s.example.com:*");
+        assert_eq!(Invalidation::decode(&key.encode()), key);
+        assert_eq!(Invalidation::decode(&prefix.encode()), prefix);
+        assert_eq!(Invalidation::decode("*"), Invalidation::Prefix(""));
+    }
+
+    #[tokio::test]
+    async fn test_apply_evicts_key_or_prefix_from_l1() {
+        let cache = TieredCache::new(NullCache::new(), 100, 60);
+        let keys = [
+            "a.example.com:abc",
+            "a.example.com:def",
+            "b.example.com:abc",
+        ];
+        fill(&cache, &keys).await;
+
+        Invalidation::Key("a.example.com:abc").apply(&cache);
+        assert!(!cached(&cache, "a.example.com:abc").await);
+        assert!(cached(&cache, "a.example.com:def").await);
+
+        Invalidation::Prefix("a.example.com:").apply(&cache);
+        assert!(!cached(&cache, "a.example.com:def").await);
+        assert!(cached(&cache, "b.example.com:abc").await);
+
+        Invalidation::Prefix("").apply(&cache);
+        assert!(!cached(&cache, "b.example.com:abc").await);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        assert_eq!(next_backoff(INITIAL_BACKOFF), Duration::from_secs(2));
+        assert_eq!(next_backoff(Duration::from_secs(16)), MAX_BACKOFF);
+        assert_eq!(next_backoff(MAX_BACKOFF), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_publish_sends_key_and_prefix() {
+        let (published_tx, mut published) = mpsc::unbounded_channel();
+        let (subscribed_tx, _subscribed) = mpsc::unbounded_channel();
+        let url = fake_redis(published_tx, subscribed_tx).await;
+        let bus = InvalidationBus::connect(&url).await.unwrap();
+
+        bus.publish("s.example.com:abc").await;
+        bus.publish_prefix("s.example.com:").await;
+
+        assert_eq!(published.recv().await.unwrap(), "s.example.com:abc");
+        assert_eq!(published.recv().await.unwrap(), "s.example.com:*");
+    }
+
+    #[tokio::test]
+    async fn test_listener_evicts_received_keys_and_clears_l1_after_reconnect() {
+        let (published_tx, _published) = mpsc::unbounded_channel();
+        let (subscribed_tx, mut subscribed) = mpsc::unbounded_channel();
+        let url = fake_redis(published_tx, subscribed_tx).await;
+        let cache = Arc::new(TieredCache::new(NullCache::new(), 100, 60));
+        let bus = InvalidationBus::connect(&url).await.unwrap();
+        tokio::spawn(bus.listen(cache.clone()));
+
+        let mut subscriber = subscribed.recv().await.unwrap();
+        // Once a message went through, the L1 clear on subscribing is done.
+        fill(&cache, &["probe"]).await;
+        push(&mut subscriber, "probe").await;
+        wait_evicted(&cache, "probe").await;
+
+        let keys = [
+            "a.example.com:abc",
+            "a.example.com:def",
+            "b.example.com:abc",
+        ];
+        fill(&cache, &keys).await;
+        push(&mut subscriber, "a.example.com:abc").await;
+        wait_evicted(&cache, "a.example.com:abc").await;
+        assert!(cached(&cache, "a.example.com:def").await);
+
+        push(&mut subscriber, "a.example.com:*").await;
+        wait_evicted(&cache, "a.example.com:def").await;
+        assert!(cached(&cache, "b.example.com:abc").await);
+
+        // Messages may be lost while disconnected, so resubscribing clears L1.
+        drop(subscriber);
+        let _resubscribed = tokio::time::timeout(Duration::from_secs(5), subscribed.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        wait_evicted(&cache, "b.example.com:abc").await;
+    }
+}
@@ -1,13 +1,23 @@
 //! Caching layer for fast redirect lookups.
 //!
-//! Provides a [`CacheService`] trait with two implementations:
+//! Provides a [`CacheService`] trait with three implementations:
 //! - [`RedisCache`] - Production Redis-backed cache
+//! - [`TieredCache`] - In-process cache in front of [`RedisCache`]
 //! - [`NullCache`] - No-op implementation for testing/disabled caching
+//!
+//! [`InvalidationBus`] keeps the in-process tiers of all instances consistent.
+//! [`CachedRedirect`] is the cached form of a redirect answer, [`CachedLink`] of a link.
 
+mod invalidation;
 mod null_cache;
+mod redirect;
 mod redis_cache;
 mod service;
+mod tiered_cache;
 
+pub use invalidation::{INVALIDATION_CHANNEL, InvalidationBus};
 pub use null_cache::NullCache;
+pub use redirect::{CachedLink, CachedRedirect, RedirectCachePolicy};
 pub use redis_cache::RedisCache;
-pub use service::{CacheError, CacheResult, CacheService};
+pub use service::{CacheEntry, CacheError, CacheResult, CacheService, CacheStats, NewCacheEntry};
+pub use tiered_cache::TieredCache;
@@ -1,7 +1,8 @@
 //! No-op cache implementation for testing or disabled caching.
 
-use super::service::{CacheResult, CacheService};
+use super::service::{CacheEntry, CacheResult, CacheService, CacheStats};
 use async_trait::async_trait;
+use std::time::Duration;
 use tracing::debug;
 
 /// A cache implementation that does nothing.
@@ -32,7 +33,7 @@ impl Default for NullCache {
 
 #[async_trait]
 impl CacheService for NullCache {
-    async fn get_url(&self, _short_code: &str) -> CacheResult<Option<String>> {
+    async fn get_entry(&self, _short_code: &str) -> CacheResult<Option<CacheEntry>> {
         Ok(None)
     }
 
@@ -49,6 +50,26 @@ impl CacheService for NullCache {
         Ok(())
     }
 
+    async fn purge_prefix(&self, _prefix: &str) -> CacheResult<u64> {
+        Ok(0)
+    }
+
+    /// Always granted: without a shared backend there is nobody to coordinate with.
+    async fn try_lock(&self, _key: &str, _ttl: Duration) -> CacheResult<Option<String>> {
+        Ok(Some(String::new()))
+    }
+
+    async fn unlock(&self, _key: &str, _token: &str) -> CacheResult<()> {
+        Ok(())
+    }
+
+    async fn stats(&self) -> CacheResult<CacheStats> {
+        Ok(CacheStats {
+            backend: "none",
+            ..Default::default()
+        })
+    }
+
     async fn health_check(&self) -> bool {
         true
     }
@@ -0,0 +1,271 @@
+//! Cached redirect answers and the settings for caching them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::AppError;
+
+/// Version of the cache entry format written by [`CachedRedirect::encode`].
+///
+/// Fields may be added within a version as long as they have a default, since
+/// entries written without them must still parse. Any other change needs a new
+/// version; entries of an unknown version are treated as cache misses.
+const FORMAT_VERSION: u8 = 1;
+
+/// Prefix of legacy entries for permanent (301) links.
+const LEGACY_PERMANENT_PREFIX: &str = "1:";
+/// Prefix of legacy entries for temporary (307) links.
+const LEGACY_TEMPORARY_PREFIX: &str = "0:";
+
+/// A link as cached for redirects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedLink {
+    pub url: String,
+    pub permanent: bool,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// `None` for legacy entries, which only hold the URL and redirect type.
+    #[serde(default)]
+    pub link_id: Option<i64>,
+    /// `None` for legacy entries.
+    #[serde(default)]
+    pub domain_id: Option<i64>,
+}
+
+impl CachedLink {
+    /// Returns `true` if the link has expired since it was cached.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Returns the TTL to cache the link for: until it expires, or `None` for
+    /// the default TTL if it never does.
+    pub fn ttl_seconds(&self) -> Option<usize> {
+        self.expires_at.map(|exp| {
+            let secs = (exp - Utc::now()).num_seconds();
+            secs.max(1) as usize
+        })
+    }
+
+    /// Returns `true` for entries parsed from the legacy string encoding.
+    pub fn is_legacy(&self) -> bool {
+        self.link_id.is_none()
+    }
+}
+
+/// What a redirect resolves to, as cached under `domain:code`.
+///
+/// # Encoding
+///
+/// Entries are JSON objects holding the format version `v` and a `status`:
+///
+/// ```json
+/// {"v":1,"status":"found","url":"https://example.com","permanent":false,"expires_at":null,"link_id":7,"domain_id":1}
+/// {"v":1,"status":"not_found","domain_id":1}
+/// {"v":1,"status":"deleted"}
+/// ```
+///
+/// Legacy string entries still parse as links, without link and domain IDs:
+///
+/// - `"1:{url}"` → 301 Permanent Redirect
+/// - `"0:{url}"` or no prefix → 307 Temporary Redirect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CachedRedirect {
+    Found(CachedLink),
+    /// No link with this code on the domain.
+    NotFound {
+        domain_id: i64,
+    },
+    Deleted,
+    Expired,
+}
+
+/// A cache entry: a [`CachedRedirect`] tagged with its format version.
+#[derive(Serialize, Deserialize)]
+struct VersionedEntry<T> {
+    v: u8,
+    #[serde(flatten)]
+    redirect: T,
+}
+
+impl CachedRedirect {
+    /// Returns the cache key of `code` on `domain`.
+    pub fn key(domain: &str, code: &str) -> String {
+        format!("{}{}", Self::domain_prefix(domain), code)
+    }
+
+    /// Returns the prefix shared by the cache keys of all codes on `domain`.
+    pub fn domain_prefix(domain: &str) -> String {
+        format!("{}:", domain)
+    }
+
+    /// Encodes the outcome for caching.
+    pub fn encode(&self) -> String {
+        serde_json::to_string(&VersionedEntry {
+            v: FORMAT_VERSION,
+            redirect: self,
+        })
+        .expect("cached redirects serialize")
+    }
+
+    /// Parses a cached value.
+    ///
+    /// Returns `None` for entries of another format version and for malformed
+    /// entries; callers treat them as cache misses.
+    pub fn parse(value: &str) -> Option<Self> {
+        if !value.starts_with('{') {
+            return Some(Self::parse_legacy(value));
+        }
+
+        match serde_json::from_str::<VersionedEntry<Self>>(value) {
+            Ok(entry) if entry.v == FORMAT_VERSION => Some(entry.redirect),
+            _ => None,
+        }
+    }
+
+    /// Parses a link entry written before the versioned format.
+    fn parse_legacy(value: &str) -> Self {
+        let (url, permanent) = match value.strip_prefix(LEGACY_PERMANENT_PREFIX) {
+            Some(url) => (url, true),
+            None => (
+                value.strip_prefix(LEGACY_TEMPORARY_PREFIX).unwrap_or(value),
+                false,
+            ),
+        };
+
+        Self::Found(CachedLink {
+            url: url.to_string(),
+            permanent,
+            expires_at: None,
+            link_id: None,
+            domain_id: None,
+        })
+    }
+
+    /// Returns the link to redirect to, or the error answered for `code`.
+    ///
+    /// A cached link that has expired since it was cached answers 410 Gone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::NotFound`] for unknown codes and [`AppError::Gone`]
+    /// for deleted and expired links.
+    pub fn into_result(self, code: &str) -> Result<CachedLink, AppError> {
+        match self {
+            Self::Found(link) if link.is_expired() => Self::Expired.into_result(code),
+            Self::Found(link) => Ok(link),
+            Self::NotFound { domain_id } => Err(AppError::not_found(
+                "Short link not found",
+                json!({ "code": code, "domain_id": domain_id }),
+            )),
+            // Deleted takes precedence over expired in the error message.
+            Self::Deleted => Err(AppError::gone(
+                "This link has been deleted",
+                json!({ "code": code }),
+            )),
+            Self::Expired => Err(AppError::gone(
+                "This link has expired",
+                json!({ "code": code }),
+            )),
+        }
+    }
+}
+
+/// How redirect answers are cached; `None` disables a feature.
+#[derive(Debug, Clone, Default)]
+pub struct RedirectCachePolicy {
+    /// TTL of cached 404 and 410 answers, in seconds.
+    pub negative_ttl: Option<usize>,
+    /// Redirects found in the cache with less TTL left are renewed in the background.
+    pub refresh_ahead: Option<Duration>,
+    /// How long a cache miss waits for another instance that holds the lookup
+    /// lock of the same key before querying the database itself.
+    pub lock_wait: Option<Duration>,
+}
+
+impl From<&Config> for RedirectCachePolicy {
+    fn from(config: &Config) -> Self {
+        Self {
+            negative_ttl: (config.negative_cache_ttl_seconds > 0)
+                .then_some(config.negative_cache_ttl_seconds as usize),
+            refresh_ahead: (config.cache_refresh_ahead_seconds > 0)
+                .then(|| Duration::from_secs(config.cache_refresh_ahead_seconds)),
+            lock_wait: (config.cache_lock_wait_ms > 0)
+                .then(|| Duration::from_millis(config.cache_lock_wait_ms)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link() -> CachedLink {
+        CachedLink {
+            url: "https://example.com/a".to_string(),
+            permanent: true,
+            expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
+            link_id: Some(7),
+            domain_id: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_encoding_round_trip() {
+        for redirect in [
+            CachedRedirect::Found(link()),
+            CachedRedirect::NotFound { domain_id: 7 },
+            CachedRedirect::Deleted,
+            CachedRedirect::Expired,
+        ] {
+            assert_eq!(CachedRedirect::parse(&redirect.encode()), Some(redirect));
+        }
+    }
+
+    #[test]
+    fn test_legacy_values_parse_without_ids() {
+        let Some(CachedRedirect::Found(permanent)) = CachedRedirect::parse("1:https://a.example")
+        else {
+            panic!("expected a found entry");
+        };
+        assert!(permanent.permanent && permanent.is_legacy());
+        assert_eq!(permanent.url, "https://a.example");
+
+        let Some(CachedRedirect::Found(unprefixed)) = CachedRedirect::parse("https://b.example")
+        else {
+            panic!("expected a found entry");
+        };
+        assert!(!unprefixed.permanent);
+        assert_eq!(unprefixed.url, "https://b.example");
+    }
+
+    #[test]
+    fn test_unknown_version_is_a_miss() {
+        assert_eq!(CachedRedirect::parse(r#"{"v":2,"status":"deleted"}"#), None);
+        assert_eq!(CachedRedirect::parse("{not json"), None);
+    }
+
+    #[test]
+    fn test_missing_optional_fields_default() {
+        let parsed = CachedRedirect::parse(
+            r#"{"v":1,"status":"found","url":"https://c.example","permanent":false}"#,
+        );
+        assert!(matches!(parsed, Some(CachedRedirect::Found(link)) if link.expires_at.is_none()));
+    }
+
+    #[test]
+    fn test_link_expired_since_cached_is_gone() {
+        let mut expired = link();
+        expired.expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+
+        let err = CachedRedirect::Found(expired)
+            .into_result("abc")
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Gone { .. }));
+    }
+}
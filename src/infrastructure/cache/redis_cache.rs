@@ -1,10 +1,25 @@
 //! Redis-backed cache implementation.
 
-use super::service::{CacheError, CacheResult, CacheService};
+use super::service::{
+    CacheEntry, CacheError, CacheResult, CacheService, CacheStats, NewCacheEntry,
+};
 use async_trait::async_trait;
-use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use rand::RngCore;
+use redis::{AsyncCommands, Client, Script, aio::ConnectionManager};
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
+/// Deletes a lock only if it still holds the caller's token.
+const UNLOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// Keys asked for per SCAN round trip.
+const SCAN_COUNT: usize = 500;
+
 /// Redis cache implementation for fast URL lookups.
 ///
 /// Uses connection pooling via `ConnectionManager` for efficient connection reuse.
@@ -58,20 +73,73 @@ impl RedisCache {
     fn build_key(&self, short_code: &str) -> String {
         format!("{}{}", self.key_prefix, short_code)
     }
+
+    /// Constructs a SCAN pattern matching every key starting with `prefix`.
+    fn build_pattern(&self, prefix: &str) -> String {
+        let mut pattern = self.key_prefix.clone();
+        for c in prefix.chars() {
+            if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('*');
+        pattern
+    }
+
+    /// Fetches one page of the keys matching `pattern`, without lock keys.
+    ///
+    /// Returns the cursor of the next page, `0` after the last one.
+    async fn scan_page(
+        &self,
+        cursor: u64,
+        pattern: &str,
+    ) -> redis::RedisResult<(u64, Vec<String>)> {
+        let mut conn = self.client.clone();
+        let (next, keys) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async::<(u64, Vec<String>)>(&mut conn)
+            .await?;
+
+        let lock_prefix = self.build_lock_key("");
+        let keys = keys
+            .into_iter()
+            .filter(|key| !key.starts_with(&lock_prefix))
+            .collect();
+        Ok((next, keys))
+    }
+
+    /// Constructs the Redis key of a lock, e.g. `url:lock:s.example.com:abc`.
+    fn build_lock_key(&self, key: &str) -> String {
+        format!("{}lock:{}", self.key_prefix, key)
+    }
 }
 
 #[async_trait]
 impl CacheService for RedisCache {
-    async fn get_url(&self, short_code: &str) -> CacheResult<Option<String>> {
+    #[tracing::instrument(name = "cache.get_url", skip_all, fields(db.system = "redis", key = %short_code))]
+    async fn get_entry(&self, short_code: &str) -> CacheResult<Option<CacheEntry>> {
         let key = self.build_key(short_code);
         let mut conn = self.client.clone();
 
-        match conn.get::<_, Option<String>>(&key).await {
-            Ok(Some(url)) => {
+        // One round trip for the value and its remaining TTL (-1 without expiry).
+        let result = redis::pipe()
+            .get(&key)
+            .pttl(&key)
+            .query_async::<(Option<String>, i64)>(&mut conn)
+            .await;
+
+        match result {
+            Ok((Some(url), pttl)) => {
                 debug!("Cache HIT: {} -> {}", short_code, url);
-                Ok(Some(url))
+                let ttl = (pttl > 0).then(|| Duration::from_millis(pttl as u64));
+                Ok(Some(CacheEntry { value: url, ttl }))
             }
-            Ok(None) => {
+            Ok((None, _)) => {
                 debug!("Cache MISS: {}", short_code);
                 Ok(None)
             }
@@ -82,6 +150,7 @@ impl CacheService for RedisCache {
         }
     }
 
+    #[tracing::instrument(name = "cache.set_url", skip_all, fields(db.system = "redis", key = %short_code))]
     async fn set_url(
         &self,
         short_code: &str,
@@ -110,6 +179,25 @@ impl CacheService for RedisCache {
         }
     }
 
+    #[tracing::instrument(name = "cache.set_many", skip_all, fields(db.system = "redis", count = entries.len()))]
+    async fn set_many(&self, entries: &[NewCacheEntry]) -> CacheResult<()> {
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            let ttl_seconds = entry.ttl_seconds.unwrap_or(self.default_ttl);
+            pipe.set_ex(self.build_key(&entry.key), &entry.value, ttl_seconds as u64)
+                .ignore();
+        }
+
+        let mut conn = self.client.clone();
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            warn!("Redis SET error for {} entries: {}", entries.len(), e);
+        } else {
+            debug!("Cache SET: {} entries", entries.len());
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "cache.invalidate", skip_all, fields(db.system = "redis", key = %short_code))]
     async fn invalidate(&self, short_code: &str) -> CacheResult<()> {
         let key = self.build_key(short_code);
         let mut conn = self.client.clone();
@@ -128,8 +216,128 @@ impl CacheService for RedisCache {
         }
     }
 
+    #[tracing::instrument(name = "cache.purge_prefix", skip_all, fields(db.system = "redis", prefix = %prefix))]
+    async fn purge_prefix(&self, prefix: &str) -> CacheResult<u64> {
+        let pattern = self.build_pattern(prefix);
+        let mut conn = self.client.clone();
+        let mut cursor = 0;
+        let mut removed = 0;
+
+        loop {
+            let (next, keys) = self
+                .scan_page(cursor, &pattern)
+                .await
+                .map_err(|e| CacheError::OperationError(format!("Redis SCAN failed: {}", e)))?;
+            if !keys.is_empty() {
+                removed += conn.unlink::<_, u64>(keys).await.map_err(|e| {
+                    CacheError::OperationError(format!("Redis UNLINK failed: {}", e))
+                })?;
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        debug!("Cache PURGE: {} keys under {}", removed, prefix);
+        Ok(removed)
+    }
+
+    #[tracing::instrument(name = "cache.try_lock", skip_all, fields(db.system = "redis", key = %key))]
+    async fn try_lock(&self, key: &str, ttl: Duration) -> CacheResult<Option<String>> {
+        let lock_key = self.build_lock_key(key);
+        let mut conn = self.client.clone();
+
+        let mut bytes = [0u8; 16];
+        rand::rng().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+
+        let result = redis::cmd("SET")
+            .arg(&lock_key)
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async::<Option<String>>(&mut conn)
+            .await;
+
+        match result {
+            Ok(Some(_)) => Ok(Some(token)),
+            Ok(None) => {
+                debug!("Cache lock busy: {}", key);
+                Ok(None)
+            }
+            Err(e) => {
+                warn!("Redis SET NX error for lock {}: {}", key, e);
+                Ok(Some(token))
+            }
+        }
+    }
+
+    #[tracing::instrument(name = "cache.unlock", skip_all, fields(db.system = "redis", key = %key))]
+    async fn unlock(&self, key: &str, token: &str) -> CacheResult<()> {
+        let lock_key = self.build_lock_key(key);
+        let mut conn = self.client.clone();
+
+        if let Err(e) = Script::new(UNLOCK_SCRIPT)
+            .key(&lock_key)
+            .arg(token)
+            .invoke_async::<i32>(&mut conn)
+            .await
+        {
+            warn!("Redis unlock error for {}: {}", key, e);
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "cache.stats", skip_all, fields(db.system = "redis"))]
+    async fn stats(&self) -> CacheResult<CacheStats> {
+        let mut conn = self.client.clone();
+        let info = redis::cmd("INFO")
+            .query_async::<String>(&mut conn)
+            .await
+            .map_err(|e| CacheError::OperationError(format!("Redis INFO failed: {}", e)))?;
+
+        let pattern = self.build_pattern("");
+        let mut cursor = 0;
+        let mut keys = 0;
+        loop {
+            let (next, page) = self
+                .scan_page(cursor, &pattern)
+                .await
+                .map_err(|e| CacheError::OperationError(format!("Redis SCAN failed: {}", e)))?;
+            keys += page.len() as u64;
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(CacheStats {
+            backend: "redis",
+            keys: Some(keys),
+            local_entries: None,
+            hits: info_field(&info, "keyspace_hits"),
+            misses: info_field(&info, "keyspace_misses"),
+            used_memory_bytes: info_field(&info, "used_memory"),
+        })
+    }
+
+    #[tracing::instrument(name = "cache.health_check", skip_all, fields(db.system = "redis"))]
     async fn health_check(&self) -> bool {
         let mut conn = self.client.clone();
         conn.ping::<()>().await.is_ok()
     }
+
+    fn default_ttl(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.default_ttl as u64))
+    }
+}
+
+/// Reads a numeric field such as `used_memory:1024` from an INFO reply.
+fn info_field(info: &str, name: &str) -> Option<u64> {
+    info.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key == name).then(|| value.trim().parse().ok()).flatten()
+    })
 }
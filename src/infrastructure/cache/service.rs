@@ -2,6 +2,7 @@
 
 use async_trait::async_trait;
 use std::fmt;
+use std::time::Duration;
 
 /// Errors that can occur during cache operations.
 #[derive(Debug)]
@@ -24,6 +25,40 @@ impl std::error::Error for CacheError {}
 /// Result type for cache operations.
 pub type CacheResult<T> = Result<T, CacheError>;
 
+/// A cached value with the time it has left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub value: String,
+    /// Remaining TTL; `None` if unknown or the entry never expires.
+    pub ttl: Option<Duration>,
+}
+
+/// A value to store with [`CacheService::set_many`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewCacheEntry {
+    pub key: String,
+    pub value: String,
+    /// TTL in seconds; `None` for the implementation's default.
+    pub ttl_seconds: Option<usize>,
+}
+
+/// What a cache backend reports about itself; `None` where it can't tell.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheStats {
+    /// `redis`, `tiered` or `none`.
+    pub backend: &'static str,
+    /// Cached redirect keys in the shared backend.
+    pub keys: Option<u64>,
+    /// Entries in this instance's in-process tier.
+    pub local_entries: Option<u64>,
+    /// Lookups answered by the shared backend since it started.
+    pub hits: Option<u64>,
+    /// Lookups the shared backend could not answer since it started.
+    pub misses: Option<u64>,
+    /// Memory used by the shared backend.
+    pub used_memory_bytes: Option<u64>,
+}
+
 /// Trait for caching short URL mappings.
 ///
 /// Implementations must be thread-safe and handle errors gracefully without
@@ -32,21 +67,29 @@ pub type CacheResult<T> = Result<T, CacheError>;
 /// # Implementations
 ///
 /// - [`crate::infrastructure::cache::RedisCache`] - Redis-backed cache with TTL support
+/// - [`crate::infrastructure::cache::TieredCache`] - In-process cache in front of Redis
 /// - [`crate::infrastructure::cache::NullCache`] - No-op implementation for disabled caching
 #[async_trait]
 pub trait CacheService: Send + Sync {
-    /// Retrieves the original URL for a short code from cache.
+    /// Retrieves the cached value for a key with its remaining TTL.
     ///
     /// # Returns
     ///
-    /// - `Ok(Some(url))` on cache hit
+    /// - `Ok(Some(entry))` on cache hit
     /// - `Ok(None)` on cache miss or error (fail-open behavior)
     ///
     /// # Errors
     ///
     /// Should not return errors in production implementations. Errors are logged
     /// and treated as cache misses.
-    async fn get_url(&self, short_code: &str) -> CacheResult<Option<String>>;
+    async fn get_entry(&self, short_code: &str) -> CacheResult<Option<CacheEntry>>;
+
+    /// Retrieves the original URL for a short code from cache.
+    ///
+    /// Same as [`CacheService::get_entry`] without the TTL.
+    async fn get_url(&self, short_code: &str) -> CacheResult<Option<String>> {
+        Ok(self.get_entry(short_code).await?.map(|entry| entry.value))
+    }
 
     /// Stores a URL mapping in cache with optional TTL.
     ///
@@ -67,6 +110,21 @@ pub trait CacheService: Send + Sync {
         ttl_seconds: Option<usize>,
     ) -> CacheResult<()>;
 
+    /// Stores many values at once, e.g. when warming the cache up.
+    ///
+    /// The default implementation calls [`CacheService::set_url`] for each entry.
+    ///
+    /// # Errors
+    ///
+    /// Should not propagate errors to callers, like [`CacheService::set_url`].
+    async fn set_many(&self, entries: &[NewCacheEntry]) -> CacheResult<()> {
+        for entry in entries {
+            self.set_url(&entry.key, &entry.value, entry.ttl_seconds)
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Removes a cached URL mapping.
     ///
     /// Used when a link is deleted or modified.
@@ -76,8 +134,53 @@ pub trait CacheService: Send + Sync {
     /// Should not propagate errors to callers.
     async fn invalidate(&self, short_code: &str) -> CacheResult<()>;
 
+    /// Removes every key starting with `prefix`, e.g. all codes of a domain
+    /// with `"s.example.com:"`.
+    ///
+    /// Returns the number of keys removed from the shared backend.
+    ///
+    /// # Errors
+    ///
+    /// Unlike lookups, returns backend errors: this is an admin operation and
+    /// the caller must know whether the keys are gone.
+    async fn purge_prefix(&self, prefix: &str) -> CacheResult<u64>;
+
+    /// Tries to take a lock on `key` shared by all instances using the backend,
+    /// held for at most `ttl`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(token))` if the lock was taken; pass the token to [`CacheService::unlock`]
+    /// - `Ok(None)` if another holder has it
+    ///
+    /// # Errors
+    ///
+    /// Should not propagate errors to callers. Implementations should log errors
+    /// and grant the lock, so a backend failure never blocks a lookup.
+    async fn try_lock(&self, key: &str, ttl: Duration) -> CacheResult<Option<String>>;
+
+    /// Releases a lock taken with [`CacheService::try_lock`] if `token` still holds it.
+    ///
+    /// # Errors
+    ///
+    /// Should not propagate errors to callers; the lock then expires on its own.
+    async fn unlock(&self, key: &str, token: &str) -> CacheResult<()>;
+
+    /// Reports key counts, hit rates and memory use.
+    ///
+    /// # Errors
+    ///
+    /// Returns backend errors, like [`CacheService::purge_prefix`].
+    async fn stats(&self) -> CacheResult<CacheStats>;
+
     /// Checks if the cache backend is healthy.
     ///
     /// Used by health check endpoints to report cache status.
     async fn health_check(&self) -> bool;
+
+    /// TTL applied by [`CacheService::set_url`] when called without one;
+    /// `None` if entries don't expire or the backend can't tell.
+    fn default_ttl(&self) -> Option<Duration> {
+        None
+    }
 }
@@ -0,0 +1,440 @@
+//! In-process cache tier in front of Redis.
+
+use super::invalidation::InvalidationBus;
+use super::redis_cache::RedisCache;
+use super::service::{CacheEntry, CacheResult, CacheService, CacheStats, NewCacheEntry};
+use async_trait::async_trait;
+use moka::sync::Cache;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A cached value and the moment it stops being served.
+#[derive(Clone)]
+struct L1Entry {
+    value: String,
+    expires_at: Instant,
+    /// When the L2 copy expires, if known; reported as the entry's TTL.
+    l2_expires_at: Option<Instant>,
+}
+
+/// Two-tier cache: a bounded in-process cache (L1) in front of another cache (L2),
+/// by default [`RedisCache`].
+///
+/// L1 hits are answered from memory without any network I/O. Eviction uses
+/// TinyLFU, so the most requested codes stay in L1 while one-off lookups don't
+/// displace them. L1 entries live for a short TTL, or less if the TTL passed to
+/// [`CacheService::set_url`] is shorter.
+///
+/// # Consistency
+///
+/// [`CacheService::invalidate`] and [`CacheService::purge_prefix`] remove keys
+/// from both tiers of this instance and, with
+/// [`TieredCache::with_invalidation_bus`], publish them so other instances
+/// evict their L1 copies too. Without a bus, or while it is
+/// disconnected, other instances keep serving their copy until it expires, so
+/// the L1 TTL bounds how long a changed link may still redirect to its old
+/// destination.
+///
+/// # Metrics
+///
+/// Every lookup increments `cache_lookups_total{tier, result}`, with `tier` `l1`
+/// or `l2` and `result` `hit` or `miss`. L2 is only asked on an L1 miss.
+pub struct TieredCache<C: CacheService = RedisCache> {
+    l1: Cache<String, L1Entry>,
+    l1_ttl: Duration,
+    l2: C,
+    bus: Option<InvalidationBus>,
+}
+
+impl<C: CacheService> TieredCache<C> {
+    /// Wraps `l2` with an in-process cache of at most `capacity` entries, each
+    /// kept for at most `ttl_seconds`.
+    pub fn new(l2: C, capacity: u64, ttl_seconds: u64) -> Self {
+        let l1_ttl = Duration::from_secs(ttl_seconds);
+
+        Self {
+            l1: Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(l1_ttl)
+                .support_invalidation_closures()
+                .build(),
+            l1_ttl,
+            l2,
+            bus: None,
+        }
+    }
+
+    /// Publishes invalidations to other instances through `bus`.
+    ///
+    /// The instances must run [`InvalidationBus::listen`] to act on them.
+    pub fn with_invalidation_bus(mut self, bus: InvalidationBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Removes a key from the in-process tier only.
+    pub fn evict_local(&self, key: &str) {
+        self.l1.invalidate(key);
+    }
+
+    /// Removes every key starting with `prefix` from the in-process tier only.
+    pub fn evict_local_prefix(&self, prefix: &str) {
+        let prefix = prefix.to_string();
+        if let Err(e) = self
+            .l1
+            .invalidate_entries_if(move |key, _| key.starts_with(&prefix))
+        {
+            // Only fails without `support_invalidation_closures`.
+            warn!("Failed to evict local cache entries: {}", e);
+            self.l1.invalidate_all();
+        }
+    }
+
+    /// Removes every key from the in-process tier.
+    pub fn clear_local(&self) {
+        self.l1.invalidate_all();
+    }
+
+    /// TTL of a value stored in L2 with `ttl_seconds`, falling back to L2's
+    /// default, so L1 hits report when the L2 copy expires.
+    fn l2_ttl(&self, ttl_seconds: Option<usize>) -> Option<Duration> {
+        ttl_seconds
+            .map(|s| Duration::from_secs(s as u64))
+            .or_else(|| self.l2.default_ttl())
+    }
+
+    /// Stores a value in L1 for the L1 TTL, or the L2 TTL if shorter.
+    fn set_l1(&self, key: &str, value: &str, l2_ttl: Option<Duration>) {
+        let now = Instant::now();
+        let ttl = l2_ttl.map(|t| t.min(self.l1_ttl)).unwrap_or(self.l1_ttl);
+
+        self.l1.insert(
+            key.to_string(),
+            L1Entry {
+                value: value.to_string(),
+                expires_at: now + ttl,
+                l2_expires_at: l2_ttl.map(|t| now + t),
+            },
+        );
+    }
+}
+
+/// Counts one lookup in `tier`.
+fn record_lookup(tier: &'static str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    metrics::counter!("cache_lookups_total", "tier" => tier, "result" => result).increment(1);
+}
+
+#[async_trait]
+impl<C: CacheService> CacheService for TieredCache<C> {
+    async fn get_entry(&self, short_code: &str) -> CacheResult<Option<CacheEntry>> {
+        if let Some(entry) = self.l1.get(short_code) {
+            let now = Instant::now();
+            if entry.expires_at > now {
+                record_lookup("l1", true);
+                debug!("L1 cache HIT: {}", short_code);
+                return Ok(Some(CacheEntry {
+                    value: entry.value,
+                    ttl: entry
+                        .l2_expires_at
+                        .map(|at| at.saturating_duration_since(now)),
+                }));
+            }
+            self.l1.invalidate(short_code);
+        }
+        record_lookup("l1", false);
+
+        let entry = self.l2.get_entry(short_code).await?;
+        record_lookup("l2", entry.is_some());
+
+        if let Some(ref entry) = entry {
+            self.set_l1(short_code, &entry.value, entry.ttl);
+        }
+        Ok(entry)
+    }
+
+    async fn set_url(
+        &self,
+        short_code: &str,
+        original_url: &str,
+        ttl_seconds: Option<usize>,
+    ) -> CacheResult<()> {
+        let l2_ttl = self.l2_ttl(ttl_seconds);
+        self.set_l1(short_code, original_url, l2_ttl);
+        self.l2.set_url(short_code, original_url, ttl_seconds).await
+    }
+
+    async fn set_many(&self, entries: &[NewCacheEntry]) -> CacheResult<()> {
+        for entry in entries {
+            let l2_ttl = self.l2_ttl(entry.ttl_seconds);
+            self.set_l1(&entry.key, &entry.value, l2_ttl);
+        }
+        self.l2.set_many(entries).await
+    }
+
+    async fn invalidate(&self, short_code: &str) -> CacheResult<()> {
+        self.l1.invalidate(short_code);
+        self.l2.invalidate(short_code).await?;
+
+        if let Some(bus) = &self.bus {
+            bus.publish(short_code).await;
+        }
+        Ok(())
+    }
+
+    async fn purge_prefix(&self, prefix: &str) -> CacheResult<u64> {
+        self.evict_local_prefix(prefix);
+        let removed = self.l2.purge_prefix(prefix).await?;
+
+        if let Some(bus) = &self.bus {
+            bus.publish_prefix(prefix).await;
+        }
+        Ok(removed)
+    }
+
+    async fn try_lock(&self, key: &str, ttl: Duration) -> CacheResult<Option<String>> {
+        self.l2.try_lock(key, ttl).await
+    }
+
+    async fn unlock(&self, key: &str, token: &str) -> CacheResult<()> {
+        self.l2.unlock(key, token).await
+    }
+
+    async fn stats(&self) -> CacheResult<CacheStats> {
+        let stats = self.l2.stats().await?;
+        self.l1.run_pending_tasks();
+
+        Ok(CacheStats {
+            backend: "tiered",
+            local_entries: Some(self.l1.entry_count()),
+            ..stats
+        })
+    }
+
+    async fn health_check(&self) -> bool {
+        self.l2.health_check().await
+    }
+
+    fn default_ttl(&self) -> Option<Duration> {
+        self.l2.default_ttl()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// In-memory L2 that counts lookups.
+    #[derive(Default)]
+    struct CountingCache {
+        values: Mutex<HashMap<String, String>>,
+        gets: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl CacheService for CountingCache {
+        async fn get_entry(&self, short_code: &str) -> CacheResult<Option<CacheEntry>> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            let value = self.values.lock().unwrap().get(short_code).cloned();
+            Ok(value.map(|value| CacheEntry {
+                value,
+                ttl: Some(Duration::from_secs(100)),
+            }))
+        }
+
+        async fn set_url(&self, short_code: &str, url: &str, _: Option<usize>) -> CacheResult<()> {
+            self.values
+                .lock()
+                .unwrap()
+                .insert(short_code.to_string(), url.to_string());
+            Ok(())
+        }
+
+        async fn invalidate(&self, short_code: &str) -> CacheResult<()> {
+            self.values.lock().unwrap().remove(short_code);
+            Ok(())
+        }
+
+        async fn purge_prefix(&self, prefix: &str) -> CacheResult<u64> {
+            let mut values = self.values.lock().unwrap();
+            let before = values.len();
+            values.retain(|key, _| !key.starts_with(prefix));
+            Ok((before - values.len()) as u64)
+        }
+
+        async fn try_lock(&self, _: &str, _: Duration) -> CacheResult<Option<String>> {
+            Ok(Some(String::new()))
+        }
+
+        async fn unlock(&self, _: &str, _: &str) -> CacheResult<()> {
+            Ok(())
+        }
+
+        async fn stats(&self) -> CacheResult<CacheStats> {
+            Ok(CacheStats {
+                backend: "memory",
+                keys: Some(self.values.lock().unwrap().len() as u64),
+                ..Default::default()
+            })
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+
+        fn default_ttl(&self) -> Option<Duration> {
+            Some(Duration::from_secs(100))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_l1_hit_skips_l2() {
+        let l2 = CountingCache::default();
+        l2.set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+        let cache = TieredCache::new(l2, 100, 60);
+
+        let first = cache.get_url("s.example.com:abc").await.unwrap();
+        let second = cache.get_url("s.example.com:abc").await.unwrap();
+
+        assert_eq!(first.as_deref(), Some("0:https://example.com"));
+        assert_eq!(second, first);
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_l1_hit_reports_l2_ttl() {
+        let l2 = CountingCache::default();
+        l2.set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+        let cache = TieredCache::new(l2, 100, 60);
+
+        cache.get_entry("s.example.com:abc").await.unwrap();
+        let entry = cache.get_entry("s.example.com:abc").await.unwrap().unwrap();
+
+        let ttl = entry.ttl.unwrap();
+        assert!(ttl <= Duration::from_secs(100) && ttl > Duration::from_secs(90));
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_l1_entry_stored_with_default_ttl_reports_l2_default() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        cache
+            .set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+
+        let entry = cache.get_entry("s.example.com:abc").await.unwrap().unwrap();
+
+        // Refresh-ahead needs the remaining L2 TTL on L1 hits too.
+        let ttl = entry.ttl.unwrap();
+        assert!(ttl <= Duration::from_secs(100) && ttl > Duration::from_secs(90));
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_clears_both_tiers() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        cache
+            .set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+
+        cache.invalidate("s.example.com:abc").await.unwrap();
+
+        assert_eq!(cache.get_url("s.example.com:abc").await.unwrap(), None);
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_evict_local_keeps_l2() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        cache
+            .set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+
+        cache.evict_local("s.example.com:abc");
+        let value = cache.get_url("s.example.com:abc").await.unwrap();
+
+        assert_eq!(value.as_deref(), Some("0:https://example.com"));
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shorter_ttl_bounds_l1_entry() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        cache
+            .set_url("s.example.com:abc", "0:https://example.com", Some(0))
+            .await
+            .unwrap();
+
+        // The L1 entry expired immediately; the value comes from L2.
+        cache.get_url("s.example.com:abc").await.unwrap();
+
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_many_fills_both_tiers() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        let entries: Vec<_> = ["abc", "def"]
+            .iter()
+            .map(|code| NewCacheEntry {
+                key: format!("s.example.com:{code}"),
+                value: "0:https://example.com".to_string(),
+                ttl_seconds: None,
+            })
+            .collect();
+
+        cache.set_many(&entries).await.unwrap();
+
+        assert!(cache.get_url("s.example.com:def").await.unwrap().is_some());
+        assert_eq!(cache.l2.values.lock().unwrap().len(), 2);
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_prefix_clears_matching_keys_of_both_tiers() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        for key in [
+            "a.example.com:abc",
+            "a.example.com:def",
+            "b.example.com:abc",
+        ] {
+            cache
+                .set_url(key, "0:https://example.com", None)
+                .await
+                .unwrap();
+        }
+
+        let removed = cache.purge_prefix("a.example.com:").await.unwrap();
+
+        assert_eq!(removed, 2);
+        assert_eq!(cache.get_url("a.example.com:abc").await.unwrap(), None);
+        assert!(cache.get_url("b.example.com:abc").await.unwrap().is_some());
+        // Only the purged key went to L2; the other one was still in L1.
+        assert_eq!(cache.l2.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_local_entries() {
+        let cache = TieredCache::new(CountingCache::default(), 100, 60);
+        cache
+            .set_url("s.example.com:abc", "0:https://example.com", None)
+            .await
+            .unwrap();
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(stats.backend, "tiered");
+        assert_eq!(stats.keys, Some(1));
+        assert_eq!(stats.local_entries, Some(1));
+    }
+}
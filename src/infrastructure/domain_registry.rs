@@ -0,0 +1,361 @@
+//! In-memory registry of domains in front of the domain repository.
+
+use async_trait::async_trait;
+use moka::sync::Cache;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::domain::entities::{AuditContext, Domain, NewDomain, UpdateDomain};
+use crate::domain::repositories::DomainRepository;
+use crate::error::AppError;
+use crate::infrastructure::persistence::PgDomainRepository;
+
+/// Most names remembered as unknown; random Host headers can't grow it further.
+const MAX_MISSING: u64 = 10_000;
+
+/// Longest a name is remembered as unknown; reloads forget it sooner.
+const MISSING_TTL: Duration = Duration::from_secs(60);
+
+/// Domains by name, and how many times the map was replaced.
+#[derive(Default)]
+struct Entries {
+    generation: u64,
+    by_name: HashMap<String, Domain>,
+}
+
+/// A [`DomainRepository`] answering lookups by name from memory.
+///
+/// Every redirect and every click resolves its Host header to a domain, so the
+/// registry keeps domains in memory and only asks the wrapped repository, by
+/// default [`PgDomainRepository`], for names it doesn't know yet. Names that
+/// don't exist are remembered too, until the next reload or for at most a
+/// minute, so requests with random Host headers don't reach the database.
+///
+/// # Consistency
+///
+/// Creating, updating or deleting a domain through the registry reloads it, so
+/// this instance sees its own changes at once. Changes made by other instances
+/// are picked up by [`DomainRegistry::reload`], which the server runs every
+/// `DOMAIN_REFRESH_SECONDS`. All other repository methods go to the database.
+///
+/// # Metrics
+///
+/// - `domain_registry_lookups_total{result}` - lookups by name: `hit`, `miss`, or
+///   `unknown` for names remembered as not existing
+/// - `domain_registry_reloads_total` - successful reloads
+/// - `domain_registry_reload_failures_total` - reloads that failed; the last
+///   loaded domains keep being served
+pub struct DomainRegistry<R: DomainRepository = PgDomainRepository> {
+    inner: Arc<R>,
+    entries: RwLock<Entries>,
+    /// Names the wrapped repository didn't find.
+    missing: Cache<String, ()>,
+}
+
+impl<R: DomainRepository> DomainRegistry<R> {
+    /// Wraps `inner` with an empty registry; domains are added as they are looked up.
+    pub fn new(inner: Arc<R>) -> Self {
+        Self {
+            inner,
+            entries: RwLock::new(Entries::default()),
+            missing: Cache::builder()
+                .max_capacity(MAX_MISSING)
+                .time_to_live(MISSING_TTL)
+                .build(),
+        }
+    }
+
+    /// Replaces the registry with all domains that aren't deleted and forgets
+    /// the names remembered as unknown.
+    ///
+    /// Returns the number of domains loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AppError::Internal`] on database errors; the registry keeps
+    /// the domains it had, so a database hiccup doesn't send every lookup to
+    /// the database.
+    pub async fn reload(&self) -> Result<usize, AppError> {
+        match self.inner.list(None, false).await {
+            Ok(domains) => {
+                let count = domains.len();
+                self.replace(
+                    domains
+                        .into_iter()
+                        .map(|domain| (domain.domain.clone(), domain))
+                        .collect(),
+                );
+                metrics::counter!("domain_registry_reloads_total").increment(1);
+                debug!(count, "Reloaded domain registry");
+                Ok(count)
+            }
+            Err(e) => {
+                metrics::counter!("domain_registry_reload_failures_total").increment(1);
+                Err(e)
+            }
+        }
+    }
+
+    /// Reloads the registry after this instance changed a domain.
+    ///
+    /// Names remembered as unknown are forgotten even if the reload fails, so
+    /// a domain created here is found at once.
+    async fn reload_after_write(&self) {
+        self.missing.invalidate_all();
+        if let Err(e) = self.reload().await {
+            warn!("Failed to reload domain registry: {}", e);
+        }
+    }
+
+    fn replace(&self, by_name: HashMap<String, Domain>) {
+        let mut entries = self.entries.write().expect("domain registry lock poisoned");
+        entries.generation += 1;
+        entries.by_name = by_name;
+        self.missing.invalidate_all();
+    }
+}
+
+#[async_trait]
+impl<R: DomainRepository> DomainRepository for DomainRegistry<R> {
+    async fn create(
+        &self,
+        new_domain: NewDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError> {
+        let domain = self.inner.create(new_domain, context).await?;
+        self.reload_after_write().await;
+        Ok(domain)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Domain>, AppError> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_name(&self, domain: &str) -> Result<Option<Domain>, AppError> {
+        let generation = {
+            let entries = self.entries.read().expect("domain registry lock poisoned");
+            if let Some(found) = entries.by_name.get(domain) {
+                metrics::counter!("domain_registry_lookups_total", "result" => "hit").increment(1);
+                return Ok(Some(found.clone()));
+            }
+            if self.missing.contains_key(domain) {
+                metrics::counter!("domain_registry_lookups_total", "result" => "unknown")
+                    .increment(1);
+                return Ok(None);
+            }
+            entries.generation
+        };
+        metrics::counter!("domain_registry_lookups_total", "result" => "miss").increment(1);
+
+        let found = self.inner.find_by_name(domain).await?;
+
+        // A reload while the query ran may have brought a newer copy, or the
+        // domain itself.
+        let mut entries = self.entries.write().expect("domain registry lock poisoned");
+        if entries.generation == generation {
+            match &found {
+                Some(found) => {
+                    entries.by_name.insert(domain.to_string(), found.clone());
+                }
+                None => self.missing.insert(domain.to_string(), ()),
+            }
+        }
+        Ok(found)
+    }
+
+    async fn get_default(&self, workspace_id: i64) -> Result<Domain, AppError> {
+        self.inner.get_default(workspace_id).await
+    }
+
+    async fn list(
+        &self,
+        workspace_id: Option<i64>,
+        only_active: bool,
+    ) -> Result<Vec<Domain>, AppError> {
+        self.inner.list(workspace_id, only_active).await
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        update: UpdateDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError> {
+        let domain = self.inner.update(id, update, context).await?;
+        self.reload_after_write().await;
+        Ok(domain)
+    }
+
+    async fn delete(&self, id: i64, context: &AuditContext) -> Result<(), AppError> {
+        self.inner.delete(id, context).await?;
+        self.reload_after_write().await;
+        Ok(())
+    }
+
+    async fn set_default(&self, id: i64) -> Result<(), AppError> {
+        self.inner.set_default(id).await?;
+        self.reload_after_write().await;
+        Ok(())
+    }
+
+    async fn count_links(&self, domain_id: i64) -> Result<i64, AppError> {
+        self.inner.count_links(domain_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::MockDomainRepository;
+    use chrono::Utc;
+
+    fn domain(id: i64, name: &str) -> Domain {
+        Domain::new(
+            id,
+            name.to_string(),
+            false,
+            true,
+            None,
+            Utc::now(),
+            Utc::now(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_known_domain_is_served_from_memory() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_find_by_name()
+            .times(1)
+            .returning(|name| Ok(Some(domain(1, name))));
+        let registry = DomainRegistry::new(Arc::new(repo));
+
+        registry.find_by_name("s.example.com").await.unwrap();
+        let found = registry.find_by_name("s.example.com").await.unwrap();
+
+        assert_eq!(found.unwrap().id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_domain_is_remembered_until_reload() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_find_by_name().times(2).returning(|_| Ok(None));
+        repo.expect_list().times(1).returning(|_, _| Ok(vec![]));
+        let registry = DomainRegistry::new(Arc::new(repo));
+
+        // The second lookup is answered from memory.
+        for _ in 0..2 {
+            let found = registry.find_by_name("x.example.com").await.unwrap();
+            assert!(found.is_none());
+        }
+
+        registry.reload().await.unwrap();
+        let found = registry.find_by_name("x.example.com").await.unwrap();
+        assert!(found.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_created_domain_is_no_longer_unknown() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_find_by_name().times(1).returning(|_| Ok(None));
+        repo.expect_create()
+            .times(1)
+            .returning(|new_domain, _| Ok(domain(1, &new_domain.domain)));
+        repo.expect_list()
+            .times(1)
+            .returning(|_, _| Ok(vec![domain(1, "x.example.com")]));
+        let registry = DomainRegistry::new(Arc::new(repo));
+        registry.find_by_name("x.example.com").await.unwrap();
+
+        registry
+            .create(
+                NewDomain {
+                    domain: "x.example.com".to_string(),
+                    is_default: false,
+                    description: None,
+                    workspace_id: crate::domain::entities::DEFAULT_WORKSPACE_ID,
+                },
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        let found = registry.find_by_name("x.example.com").await.unwrap();
+        assert_eq!(found.unwrap().id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reload_loads_all_domains() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_list()
+            .withf(|workspace_id, only_active| workspace_id.is_none() && !only_active)
+            .times(1)
+            .returning(|_, _| Ok(vec![domain(1, "a.example.com"), domain(2, "b.example.com")]));
+        let registry = DomainRegistry::new(Arc::new(repo));
+
+        assert_eq!(registry.reload().await.unwrap(), 2);
+
+        let found = registry.find_by_name("b.example.com").await.unwrap();
+        assert_eq!(found.unwrap().id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_reloads_registry() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_find_by_name()
+            .times(1)
+            .returning(|name| Ok(Some(domain(1, name))));
+        repo.expect_update()
+            .times(1)
+            .returning(|id, _, _| Ok(domain(id, "new.example.com")));
+        repo.expect_list()
+            .times(1)
+            .returning(|_, _| Ok(vec![domain(1, "new.example.com")]));
+        let registry = DomainRegistry::new(Arc::new(repo));
+        registry.find_by_name("old.example.com").await.unwrap();
+
+        registry
+            .update(
+                1,
+                UpdateDomain {
+                    domain: Some("new.example.com".to_string()),
+                    ..Default::default()
+                },
+                &AuditContext::cli(),
+            )
+            .await
+            .unwrap();
+
+        let renamed = registry.find_by_name("new.example.com").await.unwrap();
+        assert_eq!(renamed.unwrap().id, 1);
+        assert!(
+            !registry
+                .entries
+                .read()
+                .unwrap()
+                .by_name
+                .contains_key("old.example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_loaded_domains() {
+        let mut repo = MockDomainRepository::new();
+        repo.expect_find_by_name()
+            .times(1)
+            .returning(|name| Ok(Some(domain(1, name))));
+        repo.expect_list()
+            .times(1)
+            .returning(|_, _| Err(AppError::internal("boom", serde_json::json!({}))));
+        let registry = DomainRegistry::new(Arc::new(repo));
+        registry.find_by_name("s.example.com").await.unwrap();
+
+        assert!(registry.reload().await.is_err());
+
+        // Still served from memory: no second query.
+        let found = registry.find_by_name("s.example.com").await.unwrap();
+        assert_eq!(found.unwrap().id, 1);
+    }
+}
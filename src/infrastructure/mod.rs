@@ -1,12 +1,18 @@
 //! Infrastructure layer for external integrations.
 //!
 //! This layer implements interfaces defined by the domain layer, providing
-//! concrete implementations for data persistence and caching.
+//! concrete implementations for data persistence, caching and single sign-on.
 //!
 //! # Modules
 //!
 //! - [`cache`] - Caching abstractions (Redis and no-op implementations)
+//! - [`domain_registry`] - In-memory domain lookups in front of the domain repository
+//! - [`oidc`] - OpenID Connect client for dashboard single sign-on
 //! - [`persistence`] - PostgreSQL repository implementations
+//! - [`workspace_cache`] - Short-lived workspace and click count lookups in front of the workspace repository
 
 pub mod cache;
+pub mod domain_registry;
+pub mod oidc;
 pub mod persistence;
+pub mod workspace_cache;
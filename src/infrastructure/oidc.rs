@@ -0,0 +1,352 @@
+//! OpenID Connect client for dashboard single sign-on.
+
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use url::Url;
+
+use crate::config::OidcConfig;
+use crate::domain::identity_provider::{AuthorizationRequest, IdentityClaims, IdentityProvider};
+use crate::error::AppError;
+
+/// Timeout of each request to the identity provider.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ID token signature algorithms accepted from the provider.
+///
+/// Symmetric (`HS*`) algorithms are excluded: they would let anyone holding the
+/// client secret mint ID tokens.
+const ALLOWED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Endpoints from the provider's `/.well-known/openid-configuration`.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// The part of the token endpoint response we use.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// OpenID Connect client using the authorization code flow with PKCE.
+///
+/// Provider metadata is discovered from `{issuer}/.well-known/openid-configuration`
+/// on first use and cached. Signing keys are fetched from the provider's
+/// `jwks_uri` and re-fetched when an ID token names an unknown key, so key
+/// rotation at the provider needs no restart.
+///
+/// ID tokens are verified against the signing keys, the issuer, the client ID
+/// as audience, the expiry and the login's nonce.
+pub struct OidcClient {
+    http: reqwest::Client,
+    issuer_url: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: String,
+    scopes: Vec<String>,
+    groups_claim: String,
+    metadata: RwLock<Option<Arc<ProviderMetadata>>>,
+    jwks: RwLock<Option<Arc<JwkSet>>>,
+}
+
+impl OidcClient {
+    /// Creates a client; the provider is not contacted until the first login.
+    pub fn new(config: &OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            http,
+            issuer_url: config.issuer_url.clone(),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
+            redirect_url: config.redirect_url.clone(),
+            scopes: config.scopes.clone(),
+            groups_claim: config.groups_claim.clone(),
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    /// Returns the provider metadata, discovering it on first use.
+    async fn metadata(&self) -> Result<Arc<ProviderMetadata>, AppError> {
+        if let Some(metadata) = self.metadata.read().expect("lock poisoned").clone() {
+            return Ok(metadata);
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.issuer_url.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+
+        if metadata.issuer.trim_end_matches('/') != self.issuer_url.trim_end_matches('/') {
+            return Err(provider_error(format!(
+                "discovery document is for issuer '{}', expected '{}'",
+                metadata.issuer, self.issuer_url
+            )));
+        }
+
+        let metadata = Arc::new(metadata);
+        *self.metadata.write().expect("lock poisoned") = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Returns the key that signed an ID token, re-fetching the key set if
+    /// the key is not in the cached one.
+    async fn decoding_key(
+        &self,
+        metadata: &ProviderMetadata,
+        kid: Option<&str>,
+    ) -> Result<DecodingKey, AppError> {
+        let cached = self.jwks.read().expect("lock poisoned").clone();
+        if let Some(key) = cached.and_then(|jwks| find_key(&jwks, kid)) {
+            return key;
+        }
+
+        let jwks: Arc<JwkSet> = Arc::new(self.get_json(&metadata.jwks_uri).await?);
+        *self.jwks.write().expect("lock poisoned") = Some(jwks.clone());
+
+        find_key(&jwks, kid).unwrap_or_else(|| {
+            Err(invalid_id_token(format!(
+                "signing key '{}' not found",
+                kid.unwrap_or_default()
+            )))
+        })
+    }
+
+    /// Verifies an ID token and returns its claims.
+    async fn verify_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<Map<String, Value>, AppError> {
+        let header =
+            jsonwebtoken::decode_header(id_token).map_err(|e| invalid_id_token(e.to_string()))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(invalid_id_token(format!(
+                "algorithm {:?} is not allowed",
+                header.alg
+            )));
+        }
+
+        let key = self.decoding_key(metadata, header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = jsonwebtoken::decode::<Map<String, Value>>(id_token, &key, &validation)
+            .map_err(|e| invalid_id_token(e.to_string()))?
+            .claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err(invalid_id_token("nonce does not match the login"));
+        }
+
+        // With several audiences, the token must have been issued to us.
+        if claims.get("aud").is_some_and(Value::is_array)
+            && claims
+                .get("azp")
+                .is_some_and(|azp| azp.as_str() != Some(self.client_id.as_str()))
+        {
+            return Err(invalid_id_token("authorized party is another client"));
+        }
+
+        Ok(claims)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| provider_error(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| provider_error(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcClient {
+    #[tracing::instrument(name = "oidc.authorization_url", skip_all)]
+    async fn authorization_url(&self, request: &AuthorizationRequest) -> Result<String, AppError> {
+        let metadata = self.metadata().await?;
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| provider_error(format!("invalid authorization endpoint: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", &request.state)
+            .append_pair("nonce", &request.nonce)
+            .append_pair("code_challenge", &request.code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    #[tracing::instrument(name = "oidc.exchange_code", skip_all)]
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdentityClaims, AppError> {
+        let metadata = self.metadata().await?;
+
+        let mut request = self.http.post(&metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.redirect_url),
+            ("client_id", &self.client_id),
+            ("code_verifier", code_verifier),
+        ]);
+        if let Some(secret) = &self.client_secret {
+            request = request.basic_auth(&self.client_id, Some(secret));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| provider_error(e.to_string()))?;
+
+        if response.status().is_client_error() {
+            let body: Value = response.json().await.unwrap_or(Value::Null);
+            return Err(AppError::unauthorized(
+                "Identity provider rejected the login",
+                json!({ "reason": body.get("error").cloned().unwrap_or(Value::Null) }),
+            ));
+        }
+
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(|e| provider_error(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| provider_error(e.to_string()))?;
+
+        let claims = self
+            .verify_id_token(&metadata, &tokens.id_token, nonce)
+            .await?;
+
+        Ok(identity_claims(&claims, &self.groups_claim))
+    }
+}
+
+/// Finds the key `kid` (or the only key if the token names none).
+///
+/// `None` if the key is not in the set; `Some(Err(_))` if it cannot be used.
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<Result<DecodingKey, AppError>> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid)?,
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => return None,
+    };
+
+    Some(DecodingKey::from_jwk(jwk).map_err(|e| invalid_id_token(e.to_string())))
+}
+
+/// Extracts the claims we use from a verified ID token.
+///
+/// `email_verified` and the groups claim are accepted as booleans / arrays or
+/// as their string forms, which some providers send.
+fn identity_claims(claims: &Map<String, Value>, groups_claim: &str) -> IdentityClaims {
+    let string = |name: &str| claims.get(name).and_then(Value::as_str).map(String::from);
+
+    let email_verified = match claims.get("email_verified") {
+        Some(Value::Bool(verified)) => *verified,
+        Some(Value::String(verified)) => verified == "true",
+        _ => false,
+    };
+
+    let groups = match claims.get(groups_claim) {
+        Some(Value::Array(groups)) => groups
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        Some(Value::String(group)) => vec![group.clone()],
+        _ => Vec::new(),
+    };
+
+    IdentityClaims {
+        subject: string("sub").unwrap_or_default(),
+        email: string("email"),
+        email_verified,
+        groups,
+    }
+}
+
+fn provider_error(reason: impl Into<String>) -> AppError {
+    let reason = reason.into();
+    tracing::warn!(%reason, "Identity provider request failed");
+    AppError::internal(
+        "Identity provider request failed",
+        json!({ "reason": reason }),
+    )
+}
+
+fn invalid_id_token(reason: impl Into<String>) -> AppError {
+    AppError::unauthorized("Invalid ID token", json!({ "reason": reason.into() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_claims_accept_string_forms() {
+        let claims = json!({
+            "sub": "user-1",
+            "email": "ada@example.com",
+            "email_verified": "true",
+            "roles": "sre"
+        });
+
+        let identity = identity_claims(claims.as_object().unwrap(), "roles");
+
+        assert_eq!(identity.subject, "user-1");
+        assert_eq!(identity.email.as_deref(), Some("ada@example.com"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.groups, vec!["sre"]);
+    }
+
+    #[test]
+    fn test_identity_claims_defaults() {
+        let claims = json!({ "sub": "user-1", "groups": ["a", 1, "b"] });
+
+        let identity = identity_claims(claims.as_object().unwrap(), "groups");
+
+        assert_eq!(identity.email, None);
+        assert!(!identity.email_verified);
+        assert_eq!(identity.groups, vec!["a", "b"]);
+    }
+}
@@ -6,16 +6,34 @@
 //! # Repositories
 //!
 //! - [`PgLinkRepository`] - Link storage and retrieval
+//! - [`PgLinkVersionRepository`] - Link version history
 //! - [`PgStatsRepository`] - Click tracking and analytics queries
 //! - [`PgDomainRepository`] - Domain management
 //! - [`PgTokenRepository`] - API token storage and validation
+//! - [`PgIdempotencyRepository`] - Idempotency key storage
+//! - [`PgSessionRepository`] - Dashboard session storage
+//! - [`PgUserRepository`] - Users and teams
+//! - [`PgWorkspaceRepository`] - Workspaces and their quota usage
+//! - [`PgAuditRepository`] - Append-only audit log
 
+pub mod pg_audit_repository;
 pub mod pg_domain_repository;
+pub mod pg_idempotency_repository;
 pub mod pg_link_repository;
+pub mod pg_link_version_repository;
+pub mod pg_session_repository;
 pub mod pg_stats_repository;
 pub mod pg_token_repository;
+pub mod pg_user_repository;
+pub mod pg_workspace_repository;
 
+pub use pg_audit_repository::PgAuditRepository;
 pub use pg_domain_repository::PgDomainRepository;
+pub use pg_idempotency_repository::PgIdempotencyRepository;
 pub use pg_link_repository::PgLinkRepository;
+pub use pg_link_version_repository::PgLinkVersionRepository;
+pub use pg_session_repository::PgSessionRepository;
 pub use pg_stats_repository::PgStatsRepository;
 pub use pg_token_repository::PgTokenRepository;
+pub use pg_user_repository::PgUserRepository;
+pub use pg_workspace_repository::PgWorkspaceRepository;
@@ -0,0 +1,161 @@
+//! PostgreSQL implementation of audit repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+
+use crate::domain::entities::{AuditActor, AuditEvent, NewAuditEvent};
+use crate::domain::repositories::{AuditFilter, AuditRepository};
+use crate::error::AppError;
+
+/// PostgreSQL repository for the append-only `audit_events` table.
+pub struct PgAuditRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgAuditRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgAuditRepository {
+    #[tracing::instrument(name = "audit_repository.list", skip_all, fields(db.system = "postgresql"))]
+    async fn list(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, workspace_id, entity, entity_id, action,
+                   actor_token_id, actor_user_id, actor_name, ip, before, after, created_at
+            FROM audit_events
+            WHERE ($1::bigint IS NULL OR workspace_id = $1)
+              AND ($2::text IS NULL OR entity = $2)
+              AND ($3::text IS NULL OR actor_name = $3 OR actor_token_id::text = $3)
+              AND ($4::timestamptz IS NULL OR created_at >= $4)
+              AND ($5::timestamptz IS NULL OR created_at <= $5)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $6 OFFSET $7
+            "#,
+            filter.workspace_id,
+            filter.entity.map(|entity| entity.as_str()),
+            filter.actor,
+            filter.from_date,
+            filter.to_date,
+            filter.limit,
+            filter.offset
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        rows.into_iter()
+            .map(|r| {
+                audit_event(
+                    r.id,
+                    r.workspace_id,
+                    &r.entity,
+                    r.entity_id,
+                    &r.action,
+                    AuditActor {
+                        token_id: r.actor_token_id,
+                        user_id: r.actor_user_id,
+                        name: r.actor_name,
+                    },
+                    r.ip,
+                    r.before,
+                    r.after,
+                    r.created_at,
+                )
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "audit_repository.count", skip_all, fields(db.system = "postgresql"))]
+    async fn count(&self, filter: &AuditFilter) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM audit_events
+            WHERE ($1::bigint IS NULL OR workspace_id = $1)
+              AND ($2::text IS NULL OR entity = $2)
+              AND ($3::text IS NULL OR actor_name = $3 OR actor_token_id::text = $3)
+              AND ($4::timestamptz IS NULL OR created_at >= $4)
+              AND ($5::timestamptz IS NULL OR created_at <= $5)
+            "#,
+            filter.workspace_id,
+            filter.entity.map(|entity| entity.as_str()),
+            filter.actor,
+            filter.from_date,
+            filter.to_date
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+}
+
+/// Appends an event in the transaction of the change it records, so the change
+/// fails if its event cannot be written.
+pub(super) async fn record_audit_event(
+    conn: &mut PgConnection,
+    event: &NewAuditEvent,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_events (
+            workspace_id, entity, entity_id, action,
+            actor_token_id, actor_user_id, actor_name, ip, before, after
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+        event.workspace_id,
+        event.entity.as_str(),
+        event.entity_id,
+        event.action.as_str(),
+        event.actor.token_id,
+        event.actor.user_id,
+        event.actor.name,
+        event.ip,
+        event.before,
+        event.after
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Builds an audit event from its row.
+#[allow(clippy::too_many_arguments)]
+fn audit_event(
+    id: i64,
+    workspace_id: i64,
+    entity: &str,
+    entity_id: i64,
+    action: &str,
+    actor: AuditActor,
+    ip: Option<String>,
+    before: Option<Value>,
+    after: Option<Value>,
+    created_at: DateTime<Utc>,
+) -> Result<AuditEvent, AppError> {
+    Ok(AuditEvent {
+        id,
+        workspace_id,
+        entity: entity.parse().map_err(|e: String| {
+            AppError::internal("Invalid audit entity", json!({ "reason": e }))
+        })?,
+        entity_id,
+        action: action.parse().map_err(|e: String| {
+            AppError::internal("Invalid audit action", json!({ "reason": e }))
+        })?,
+        actor,
+        ip,
+        before,
+        after,
+        created_at,
+    })
+}
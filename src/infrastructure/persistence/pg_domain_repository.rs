@@ -1,10 +1,11 @@
 //! PostgreSQL implementation of domain repository.
 
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::sync::Arc;
 
-use crate::domain::entities::{Domain, NewDomain, UpdateDomain};
+use super::pg_audit_repository::record_audit_event;
+use crate::domain::entities::{AuditContext, Domain, NewAuditEvent, NewDomain, UpdateDomain};
 use crate::domain::repositories::DomainRepository;
 use crate::error::AppError;
 use serde_json::json;
@@ -26,21 +27,29 @@ impl PgDomainRepository {
 
 #[async_trait]
 impl DomainRepository for PgDomainRepository {
-    async fn create(&self, new_domain: NewDomain) -> Result<Domain, AppError> {
+    #[tracing::instrument(name = "domain_repository.create", skip_all, fields(db.system = "postgresql"))]
+    async fn create(
+        &self,
+        new_domain: NewDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError> {
+        let mut tx = self.pool.begin().await?;
+
         let row = sqlx::query!(
             r#"
-            INSERT INTO domains (domain, is_default, description)
-            VALUES ($1, $2, $3)
-            RETURNING id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            INSERT INTO domains (domain, is_default, description, workspace_id)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             "#,
             new_domain.domain,
             new_domain.is_default,
-            new_domain.description
+            new_domain.description,
+            new_domain.workspace_id
         )
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(Domain::new(
+        let domain = Domain::new(
             row.id,
             row.domain,
             row.is_default,
@@ -49,14 +58,22 @@ impl DomainRepository for PgDomainRepository {
             row.created_at,
             row.updated_at,
             row.deleted_at,
-        ))
+        )
+        .with_workspace(row.workspace_id);
+
+        record_audit_event(&mut tx, &NewAuditEvent::created(context, &domain)).await?;
+
+        tx.commit().await?;
+
+        Ok(domain)
     }
 
+    #[tracing::instrument(name = "domain_repository.find_by_id", skip_all, fields(db.system = "postgresql", id = id))]
     async fn find_by_id(&self, id: i64) -> Result<Option<Domain>, AppError> {
         // Does NOT filter deleted_at — service decides what to do with deleted domains.
         let row = sqlx::query!(
             r#"
-            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             FROM domains
             WHERE id = $1
             "#,
@@ -76,14 +93,16 @@ impl DomainRepository for PgDomainRepository {
                 r.updated_at,
                 r.deleted_at,
             )
+            .with_workspace(r.workspace_id)
         }))
     }
 
+    #[tracing::instrument(name = "domain_repository.find_by_name", skip_all, fields(db.system = "postgresql", domain = %domain))]
     async fn find_by_name(&self, domain: &str) -> Result<Option<Domain>, AppError> {
         // Does NOT filter deleted_at — service checks is_deleted() to return 410 Gone.
         let row = sqlx::query!(
             r#"
-            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             FROM domains
             WHERE domain = $1
             "#,
@@ -103,17 +122,20 @@ impl DomainRepository for PgDomainRepository {
                 r.updated_at,
                 r.deleted_at,
             )
+            .with_workspace(r.workspace_id)
         }))
     }
 
-    async fn get_default(&self) -> Result<Domain, AppError> {
+    #[tracing::instrument(name = "domain_repository.get_default", skip_all, fields(db.system = "postgresql"))]
+    async fn get_default(&self, workspace_id: i64) -> Result<Domain, AppError> {
         let row = sqlx::query!(
             r#"
-            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             FROM domains
-            WHERE is_default = TRUE AND deleted_at IS NULL
+            WHERE is_default = TRUE AND deleted_at IS NULL AND workspace_id = $1
             LIMIT 1
-            "#
+            "#,
+            workspace_id
         )
         .fetch_optional(self.pool.as_ref())
         .await?;
@@ -129,26 +151,37 @@ impl DomainRepository for PgDomainRepository {
                 r.updated_at,
                 r.deleted_at,
             )
+            .with_workspace(r.workspace_id)
         })
         .ok_or_else(|| {
             AppError::internal(
                 "No default domain configured",
-                json!({"hint": "Run migrations or create a default domain"}),
+                json!({
+                    "workspace_id": workspace_id,
+                    "hint": "Run migrations or create a default domain"
+                }),
             )
         })
     }
 
-    async fn list(&self, only_active: bool) -> Result<Vec<Domain>, AppError> {
+    #[tracing::instrument(name = "domain_repository.list", skip_all, fields(db.system = "postgresql"))]
+    async fn list(
+        &self,
+        workspace_id: Option<i64>,
+        only_active: bool,
+    ) -> Result<Vec<Domain>, AppError> {
         // Never shows soft-deleted domains.
         let rows = sqlx::query!(
             r#"
-            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             FROM domains
             WHERE deleted_at IS NULL
               AND ($1::boolean IS NULL OR is_active = $1)
+              AND ($2::bigint IS NULL OR workspace_id = $2)
             ORDER BY is_default DESC, domain
             "#,
-            if only_active { Some(true) } else { None }
+            if only_active { Some(true) } else { None },
+            workspace_id
         )
         .fetch_all(self.pool.as_ref())
         .await?;
@@ -166,14 +199,31 @@ impl DomainRepository for PgDomainRepository {
                     r.updated_at,
                     r.deleted_at,
                 )
+                .with_workspace(r.workspace_id)
             })
             .collect())
     }
 
-    async fn update(&self, id: i64, update: UpdateDomain) -> Result<Domain, AppError> {
+    #[tracing::instrument(name = "domain_repository.update", skip_all, fields(db.system = "postgresql"))]
+    async fn update(
+        &self,
+        id: i64,
+        update: UpdateDomain,
+        context: &AuditContext,
+    ) -> Result<Domain, AppError> {
         let update_description = update.description.is_some();
         let new_description = update.description.and_then(|v| v);
 
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_domain(&mut tx, id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Domain not found", json!({"id": id})))?;
+
+        if update.is_default == Some(true) {
+            make_default(&mut tx, id).await?;
+        }
+
         let row = sqlx::query!(
             r#"
             UPDATE domains SET
@@ -182,7 +232,7 @@ impl DomainRepository for PgDomainRepository {
                 description = CASE WHEN $4 THEN $5::TEXT ELSE description END,
                 updated_at  = NOW()
             WHERE id = $1
-            RETURNING id, domain, is_default, is_active, description, created_at, updated_at, deleted_at
+            RETURNING id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
             "#,
             id,
             update.domain,
@@ -190,10 +240,10 @@ impl DomainRepository for PgDomainRepository {
             update_description,
             new_description,
         )
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(Domain::new(
+        let after = Domain::new(
             row.id,
             row.domain,
             row.is_default,
@@ -202,50 +252,49 @@ impl DomainRepository for PgDomainRepository {
             row.created_at,
             row.updated_at,
             row.deleted_at,
-        ))
-    }
-
-    async fn delete(&self, id: i64) -> Result<(), AppError> {
-        let result = sqlx::query!(
-            r#"
-            UPDATE domains SET deleted_at = NOW()
-            WHERE id = $1 AND deleted_at IS NULL
-            "#,
-            id
         )
-        .execute(self.pool.as_ref())
-        .await?;
+        .with_workspace(row.workspace_id);
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::not_found(
-                "Domain not found or already deleted",
-                json!({"id": id}),
-            ));
-        }
+        record_audit_event(&mut tx, &NewAuditEvent::updated(context, &before, &after)).await?;
 
-        Ok(())
+        tx.commit().await?;
+
+        Ok(after)
     }
 
-    async fn set_default(&self, id: i64) -> Result<(), AppError> {
+    #[tracing::instrument(name = "domain_repository.delete", skip_all, fields(db.system = "postgresql"))]
+    async fn delete(&self, id: i64, context: &AuditContext) -> Result<(), AppError> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query!("UPDATE domains SET is_default = FALSE WHERE id >= 0")
-            .execute(&mut *tx)
-            .await?;
+        let before = lock_domain(&mut tx, id)
+            .await?
+            .filter(|domain| !domain.is_deleted())
+            .ok_or_else(|| {
+                AppError::not_found("Domain not found or already deleted", json!({"id": id}))
+            })?;
 
-        let result = sqlx::query!("UPDATE domains SET is_default = TRUE WHERE id = $1", id)
+        sqlx::query!("UPDATE domains SET deleted_at = NOW() WHERE id = $1", id)
             .execute(&mut *tx)
             .await?;
 
-        if result.rows_affected() == 0 {
-            tx.rollback().await?;
-            return Err(AppError::not_found("Domain not found", json!({"id": id})));
-        }
+        record_audit_event(&mut tx, &NewAuditEvent::deleted(context, &before)).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "domain_repository.set_default", skip_all, fields(db.system = "postgresql"))]
+    async fn set_default(&self, id: i64) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        make_default(&mut tx, id).await?;
 
         tx.commit().await?;
         Ok(())
     }
 
+    #[tracing::instrument(name = "domain_repository.count_links", skip_all, fields(db.system = "postgresql"))]
     async fn count_links(&self, domain_id: i64) -> Result<i64, AppError> {
         let count =
             sqlx::query_scalar!("SELECT COUNT(*) FROM links WHERE domain_id = $1", domain_id)
@@ -255,3 +304,59 @@ impl DomainRepository for PgDomainRepository {
         Ok(count.unwrap_or(0))
     }
 }
+
+/// Loads a domain and locks it until the transaction ends.
+async fn lock_domain(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<Domain>, AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id, domain, is_default, is_active, description, created_at, updated_at, deleted_at, workspace_id
+        FROM domains
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(|r| {
+        Domain::new(
+            r.id,
+            r.domain,
+            r.is_default,
+            r.is_active,
+            r.description,
+            r.created_at,
+            r.updated_at,
+            r.deleted_at,
+        )
+        .with_workspace(r.workspace_id)
+    }))
+}
+
+/// Makes a domain the default of its workspace.
+async fn make_default(tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<(), AppError> {
+    // Only the domain's own workspace loses its previous default.
+    sqlx::query!(
+        r#"
+        UPDATE domains SET is_default = FALSE
+        WHERE workspace_id = (SELECT workspace_id FROM domains WHERE id = $1)
+        "#,
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let result = sqlx::query!("UPDATE domains SET is_default = TRUE WHERE id = $1", id)
+        .execute(&mut **tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("Domain not found", json!({"id": id})));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! PostgreSQL implementation of idempotency repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::domain::repositories::{IdempotencyRecord, IdempotencyRepository};
+use crate::error::AppError;
+
+/// PostgreSQL repository for idempotency keys.
+///
+/// Reservation is a single `INSERT ... ON CONFLICT DO UPDATE ... WHERE`, so two
+/// concurrent requests with the same key cannot both reserve it.
+pub struct PgIdempotencyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgIdempotencyRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for PgIdempotencyRepository {
+    #[tracing::instrument(name = "idempotency_repository.try_reserve", skip_all, fields(db.system = "postgresql"))]
+    async fn try_reserve(
+        &self,
+        scope: &str,
+        key: &str,
+        request_hash: &str,
+        expires_at: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<bool, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (scope, key, request_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (scope, key) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                response = NULL,
+                created_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            WHERE idempotency_keys.expires_at <= NOW()
+               OR (idempotency_keys.response IS NULL AND idempotency_keys.created_at < $5)
+            RETURNING key
+            "#,
+            scope,
+            key,
+            request_hash,
+            expires_at,
+            stale_before
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    #[tracing::instrument(name = "idempotency_repository.find", skip_all, fields(db.system = "postgresql"))]
+    async fn find(&self, scope: &str, key: &str) -> Result<Option<IdempotencyRecord>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT request_hash, response, created_at, expires_at
+            FROM idempotency_keys
+            WHERE scope = $1 AND key = $2
+            "#,
+            scope,
+            key
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.map(|r| IdempotencyRecord {
+            request_hash: r.request_hash,
+            response: r.response,
+            created_at: r.created_at,
+            expires_at: r.expires_at,
+        }))
+    }
+
+    #[tracing::instrument(name = "idempotency_repository.complete", skip_all, fields(db.system = "postgresql"))]
+    async fn complete(&self, scope: &str, key: &str, response: &Value) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET response = $3
+            WHERE scope = $1 AND key = $2
+            "#,
+            scope,
+            key,
+            response
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "idempotency_repository.release", skip_all, fields(db.system = "postgresql"))]
+    async fn release(&self, scope: &str, key: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE scope = $1 AND key = $2 AND response IS NULL
+            "#,
+            scope,
+            key
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "idempotency_repository.delete_expired", skip_all, fields(db.system = "postgresql"))]
+    async fn delete_expired(&self) -> Result<u64, AppError> {
+        let result = sqlx::query!("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
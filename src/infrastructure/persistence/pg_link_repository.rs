@@ -1,10 +1,15 @@
 //! PostgreSQL implementation of link repository.
 
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::domain::entities::{Link, LinkPatch, NewLink};
+use super::pg_audit_repository::record_audit_event;
+use super::pg_link_version_repository::record_link_version;
+use crate::domain::entities::{
+    AuditContext, Link, LinkPatch, NewAuditEvent, NewLink, NewLinkVersion, Visibility,
+};
 use crate::domain::repositories::LinkRepository;
 use crate::error::AppError;
 use serde_json::json;
@@ -25,13 +30,20 @@ impl PgLinkRepository {
 
 #[async_trait]
 impl LinkRepository for PgLinkRepository {
-    async fn create(&self, new_link: NewLink) -> Result<Link, AppError> {
+    #[tracing::instrument(name = "link_repository.create", skip_all, fields(db.system = "postgresql"))]
+    async fn create(&self, new_link: NewLink, context: &AuditContext) -> Result<Link, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        check_link_quota(&mut tx, new_link.domain_id).await?;
+
         let row = sqlx::query!(
             r#"
             WITH inserted AS (
-                INSERT INTO links (code, long_url, domain_id, expires_at, permanent)
-                VALUES ($1, $2, $3, $4, $5)
-                RETURNING id, code, long_url, domain_id, expires_at, permanent, deleted_at, created_at
+                INSERT INTO links (code, long_url, domain_id, expires_at, permanent, owner_id, team_id,
+                                   workspace_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, (SELECT workspace_id FROM domains WHERE id = $3))
+                RETURNING id, code, long_url, domain_id, expires_at, permanent, deleted_at, created_at,
+                          owner_id, team_id, workspace_id
             )
             SELECT
                 i.id,
@@ -41,7 +53,10 @@ impl LinkRepository for PgLinkRepository {
                 i.expires_at,
                 i.permanent,
                 i.deleted_at,
-                i.created_at
+                i.created_at,
+                i.owner_id,
+                i.team_id,
+                i.workspace_id
             FROM inserted i
             LEFT JOIN domains d ON d.id = i.domain_id
             "#,
@@ -50,11 +65,13 @@ impl LinkRepository for PgLinkRepository {
             new_link.domain_id,
             new_link.expires_at,
             new_link.permanent,
+            new_link.owner_id,
+            new_link.team_id,
         )
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(Link::new(
+        let link = Link::new(
             row.id,
             row.code,
             row.long_url,
@@ -63,9 +80,19 @@ impl LinkRepository for PgLinkRepository {
             row.expires_at,
             row.permanent,
             row.deleted_at,
-        ))
+        )
+        .with_owner(row.owner_id, row.team_id)
+        .with_workspace(row.workspace_id);
+
+        record_link_version(&mut tx, &NewLinkVersion::of(&link, context.actor.clone())).await?;
+        record_audit_event(&mut tx, &NewAuditEvent::created(context, &link)).await?;
+
+        tx.commit().await?;
+
+        Ok(link)
     }
 
+    #[tracing::instrument(name = "link_repository.find_by_code", skip_all, fields(db.system = "postgresql", code = %code, domain_id = domain_id))]
     async fn find_by_code(&self, code: &str, domain_id: i64) -> Result<Option<Link>, AppError> {
         // Does NOT filter deleted_at — caller decides what to do with deleted links.
         let row = sqlx::query!(
@@ -73,7 +100,8 @@ impl LinkRepository for PgLinkRepository {
             SELECT
                 l.id, l.code, l.long_url,
                 d.domain as "domain?",
-                l.expires_at, l.permanent, l.deleted_at, l.created_at
+                l.expires_at, l.permanent, l.deleted_at, l.created_at,
+                l.owner_id, l.team_id, l.workspace_id
             FROM links l
             LEFT JOIN domains d ON d.id = l.domain_id
             WHERE l.code = $1 AND l.domain_id = $2
@@ -95,50 +123,87 @@ impl LinkRepository for PgLinkRepository {
                 r.permanent,
                 r.deleted_at,
             )
+            .with_owner(r.owner_id, r.team_id)
+            .with_workspace(r.workspace_id)
         }))
     }
 
+    #[tracing::instrument(name = "link_repository.find_ids_by_codes", skip_all, fields(db.system = "postgresql", keys = keys.len()))]
+    async fn find_ids_by_codes(
+        &self,
+        keys: &[(i64, String)],
+    ) -> Result<HashMap<(i64, String), i64>, AppError> {
+        let (domain_ids, codes): (Vec<i64>, Vec<String>) = keys.iter().cloned().unzip();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT l.id, k.domain_id AS "domain_id!", k.code AS "code!"
+            FROM UNNEST($1::bigint[], $2::text[]) AS k(domain_id, code)
+            JOIN links l ON l.domain_id = k.domain_id AND l.code = k.code
+            "#,
+            &domain_ids,
+            &codes
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| ((r.domain_id, r.code), r.id))
+            .collect())
+    }
+
+    #[tracing::instrument(name = "link_repository.find_by_long_url", skip_all, fields(db.system = "postgresql"))]
     async fn find_by_long_url(
         &self,
         long_url: &str,
         domain_id: i64,
-    ) -> Result<Option<Link>, AppError> {
+    ) -> Result<Vec<Link>, AppError> {
         // Filters out deleted links so a new link can be created for the same URL after delete.
-        let row = sqlx::query!(
+        let rows = sqlx::query!(
             r#"
             SELECT
                 l.id, l.code, l.long_url,
                 d.domain as "domain?",
-                l.expires_at, l.permanent, l.deleted_at, l.created_at
+                l.expires_at, l.permanent, l.deleted_at, l.created_at,
+                l.owner_id, l.team_id, l.workspace_id
             FROM links l
             LEFT JOIN domains d ON d.id = l.domain_id
             WHERE l.long_url = $1 AND l.domain_id = $2 AND l.deleted_at IS NULL
+            ORDER BY l.created_at, l.id
             "#,
             long_url,
             domain_id
         )
-        .fetch_optional(self.pool.as_ref())
+        .fetch_all(self.pool.as_ref())
         .await?;
 
-        Ok(row.map(|r| {
-            Link::new(
-                r.id,
-                r.code,
-                r.long_url,
-                r.domain,
-                r.created_at,
-                r.expires_at,
-                r.permanent,
-                r.deleted_at,
-            )
-        }))
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                Link::new(
+                    r.id,
+                    r.code,
+                    r.long_url,
+                    r.domain,
+                    r.created_at,
+                    r.expires_at,
+                    r.permanent,
+                    r.deleted_at,
+                )
+                .with_owner(r.owner_id, r.team_id)
+                .with_workspace(r.workspace_id)
+            })
+            .collect())
     }
 
+    #[tracing::instrument(name = "link_repository.list", skip_all, fields(db.system = "postgresql"))]
     async fn list(
         &self,
         page: i64,
         page_size: i64,
         domain_id: Option<i64>,
+        visibility: &Visibility,
     ) -> Result<Vec<Link>, AppError> {
         let offset = (page - 1) * page_size;
 
@@ -148,16 +213,22 @@ impl LinkRepository for PgLinkRepository {
             SELECT
                 l.id, l.code, l.long_url,
                 d.domain as "domain?",
-                l.expires_at, l.permanent, l.deleted_at, l.created_at
+                l.expires_at, l.permanent, l.deleted_at, l.created_at,
+                l.owner_id, l.team_id, l.workspace_id
             FROM links l
             LEFT JOIN domains d ON d.id = l.domain_id
             WHERE ($1::bigint IS NULL OR l.domain_id = $1)
+              AND ($4::bigint IS NULL OR l.owner_id = $4 OR l.team_id = $5)
+              AND ($6::bigint IS NULL OR l.workspace_id = $6)
             ORDER BY l.created_at DESC
             LIMIT $2 OFFSET $3
             "#,
             domain_id,
             page_size,
-            offset
+            offset,
+            visibility.owner_id(),
+            visibility.team_id(),
+            visibility.workspace_id()
         )
         .fetch_all(self.pool.as_ref())
         .await?;
@@ -175,44 +246,98 @@ impl LinkRepository for PgLinkRepository {
                     r.permanent,
                     r.deleted_at,
                 )
+                .with_owner(r.owner_id, r.team_id)
+                .with_workspace(r.workspace_id)
             })
             .collect())
     }
 
-    async fn count(&self, domain_id: Option<i64>) -> Result<i64, AppError> {
-        let count = if let Some(domain_id) = domain_id {
-            sqlx::query_scalar!("SELECT COUNT(*) FROM links WHERE domain_id = $1", domain_id)
-                .fetch_one(self.pool.as_ref())
-                .await?
-        } else {
-            sqlx::query_scalar!("SELECT COUNT(*) FROM links")
-                .fetch_one(self.pool.as_ref())
-                .await?
-        };
+    #[tracing::instrument(name = "link_repository.count", skip_all, fields(db.system = "postgresql"))]
+    async fn count(
+        &self,
+        domain_id: Option<i64>,
+        visibility: &Visibility,
+    ) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM links
+            WHERE ($1::bigint IS NULL OR domain_id = $1)
+              AND ($2::bigint IS NULL OR owner_id = $2 OR team_id = $3)
+              AND ($4::bigint IS NULL OR workspace_id = $4)
+            "#,
+            domain_id,
+            visibility.owner_id(),
+            visibility.team_id(),
+            visibility.workspace_id()
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
 
         Ok(count.unwrap_or(0))
     }
 
-    async fn soft_delete(&self, code: &str, domain_id: i64) -> Result<bool, AppError> {
-        let result = sqlx::query!(
-            r#"
-            UPDATE links
-            SET deleted_at = now()
-            WHERE code = $1 AND domain_id = $2 AND deleted_at IS NULL
-            "#,
-            code,
-            domain_id
+    #[tracing::instrument(name = "link_repository.soft_delete", skip_all, fields(db.system = "postgresql", code = %code, domain_id = domain_id))]
+    async fn soft_delete(
+        &self,
+        code: &str,
+        domain_id: i64,
+        context: &AuditContext,
+    ) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = lock_link(&mut tx, code, domain_id)
+            .await?
+            .filter(|link| !link.is_deleted())
+        else {
+            return Ok(false);
+        };
+
+        sqlx::query!(
+            "UPDATE links SET deleted_at = now() WHERE id = $1",
+            before.id
         )
-        .execute(self.pool.as_ref())
+        .execute(&mut *tx)
         .await?;
 
-        Ok(result.rows_affected() > 0)
+        record_audit_event(&mut tx, &NewAuditEvent::deleted(context, &before)).await?;
+
+        tx.commit().await?;
+
+        Ok(true)
     }
 
-    async fn update(&self, code: &str, domain_id: i64, patch: LinkPatch) -> Result<Link, AppError> {
+    #[tracing::instrument(name = "link_repository.update", skip_all, fields(db.system = "postgresql", code = %code, domain_id = domain_id))]
+    async fn update(
+        &self,
+        code: &str,
+        domain_id: i64,
+        patch: LinkPatch,
+        context: &AuditContext,
+    ) -> Result<Link, AppError> {
         let update_expires = patch.expires_at.is_some();
         let new_expires = patch.expires_at.and_then(|v| v);
 
+        let mut tx = self.pool.begin().await?;
+
+        // Restoring a deleted link counts against the quota like creating one. The
+        // workspace is locked before the link, like when creating one.
+        let quota = if patch.restore {
+            lock_link_quota(&mut tx, domain_id).await?
+        } else {
+            None
+        };
+
+        let before = lock_link(&mut tx, code, domain_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Link not found", json!({ "code": code })))?;
+
+        if let Some(quota) = quota
+            && before.is_deleted()
+        {
+            quota.check()?;
+        }
+
         let row = sqlx::query!(
             r#"
             WITH updated AS (
@@ -222,12 +347,14 @@ impl LinkRepository for PgLinkRepository {
                     permanent  = COALESCE($6::BOOLEAN, permanent),
                     deleted_at = CASE WHEN $7 THEN NULL ELSE deleted_at END
                 WHERE code = $1 AND domain_id = $2
-                RETURNING id, code, long_url, domain_id, expires_at, permanent, deleted_at, created_at
+                RETURNING id, code, long_url, domain_id, expires_at, permanent, deleted_at, created_at,
+                          owner_id, team_id, workspace_id
             )
             SELECT
                 u.id, u.code, u.long_url,
                 d.domain,
-                u.expires_at, u.permanent, u.deleted_at, u.created_at
+                u.expires_at, u.permanent, u.deleted_at, u.created_at,
+                u.owner_id, u.team_id, u.workspace_id
             FROM updated u
             LEFT JOIN domains d ON d.id = u.domain_id
             "#,
@@ -239,11 +366,10 @@ impl LinkRepository for PgLinkRepository {
             patch.permanent,
             patch.restore,
         )
-        .fetch_optional(self.pool.as_ref())
-        .await?
-        .ok_or_else(|| AppError::not_found("Link not found", json!({ "code": code })))?;
+        .fetch_one(&mut *tx)
+        .await?;
 
-        Ok(Link::new(
+        let after = Link::new(
             row.id,
             row.code,
             row.long_url,
@@ -252,6 +378,137 @@ impl LinkRepository for PgLinkRepository {
             row.expires_at,
             row.permanent,
             row.deleted_at,
-        ))
+        )
+        .with_owner(row.owner_id, row.team_id)
+        .with_workspace(row.workspace_id);
+
+        if before.version_changed(&after) {
+            let mut version = NewLinkVersion::of(&after, context.actor.clone());
+            version.reverted_from = patch.reverted_from;
+            record_link_version(&mut tx, &version).await?;
+        }
+
+        let event = if before.is_deleted() && !after.is_deleted() {
+            NewAuditEvent::restored(context, &before, &after)
+        } else {
+            NewAuditEvent::updated(context, &before, &after)
+        };
+        record_audit_event(&mut tx, &event).await?;
+
+        tx.commit().await?;
+
+        Ok(after)
+    }
+}
+
+/// Loads a link and locks it until the transaction ends, so concurrent changes
+/// of the link are applied and recorded one after the other.
+async fn lock_link(
+    tx: &mut Transaction<'_, Postgres>,
+    code: &str,
+    domain_id: i64,
+) -> Result<Option<Link>, AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            l.id, l.code, l.long_url,
+            d.domain as "domain?",
+            l.expires_at, l.permanent, l.deleted_at, l.created_at,
+            l.owner_id, l.team_id, l.workspace_id
+        FROM links l
+        LEFT JOIN domains d ON d.id = l.domain_id
+        WHERE l.code = $1 AND l.domain_id = $2
+        FOR UPDATE OF l
+        "#,
+        code,
+        domain_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(|r| {
+        Link::new(
+            r.id,
+            r.code,
+            r.long_url,
+            r.domain,
+            r.created_at,
+            r.expires_at,
+            r.permanent,
+            r.deleted_at,
+        )
+        .with_owner(r.owner_id, r.team_id)
+        .with_workspace(r.workspace_id)
+    }))
+}
+
+/// Link quota of a workspace and its non-deleted links.
+struct LinkQuota {
+    max_links: i64,
+    used: i64,
+}
+
+impl LinkQuota {
+    /// Fails with [`AppError::Forbidden`] if the workspace may not have another link.
+    fn check(&self) -> Result<(), AppError> {
+        if self.used >= self.max_links {
+            return Err(AppError::forbidden(
+                "Workspace link quota exceeded",
+                json!({ "quota": "max_links", "limit": self.max_links }),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Locks the workspace of a domain until the transaction ends and counts its links.
+///
+/// Returns `None`, without locking, if the workspace has no link quota. The lock
+/// serializes link creation and restores in the workspace, so concurrent
+/// requests cannot go over the quota.
+async fn lock_link_quota(
+    tx: &mut Transaction<'_, Postgres>,
+    domain_id: i64,
+) -> Result<Option<LinkQuota>, AppError> {
+    let workspace = sqlx::query!(
+        r#"
+        SELECT w.id, w.max_links AS "max_links!"
+        FROM workspaces w
+        JOIN domains d ON d.workspace_id = w.id
+        WHERE d.id = $1 AND w.max_links IS NOT NULL
+        FOR UPDATE OF w
+        "#,
+        domain_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(workspace) = workspace else {
+        return Ok(None);
+    };
+
+    let used = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM links WHERE workspace_id = $1 AND deleted_at IS NULL"#,
+        workspace.id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(Some(LinkQuota {
+        max_links: workspace.max_links,
+        used,
+    }))
+}
+
+/// Fails with [`AppError::Forbidden`] if the workspace of a domain may not have
+/// another link, holding its lock until the transaction ends.
+async fn check_link_quota(
+    tx: &mut Transaction<'_, Postgres>,
+    domain_id: i64,
+) -> Result<(), AppError> {
+    match lock_link_quota(tx, domain_id).await? {
+        Some(quota) => quota.check(),
+        None => Ok(()),
     }
 }
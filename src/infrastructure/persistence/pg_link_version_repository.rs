@@ -0,0 +1,153 @@
+//! PostgreSQL implementation of link version repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+
+use crate::domain::entities::{AuditActor, LinkVersion, NewLinkVersion};
+use crate::domain::repositories::LinkVersionRepository;
+use crate::error::AppError;
+
+/// PostgreSQL repository for the `link_versions` table.
+pub struct PgLinkVersionRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgLinkVersionRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LinkVersionRepository for PgLinkVersionRepository {
+    #[tracing::instrument(name = "link_version_repository.list", skip_all, fields(db.system = "postgresql", link_id = link_id))]
+    async fn list(&self, link_id: i64) -> Result<Vec<LinkVersion>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT link_id, version, long_url, expires_at, permanent, reverted_from,
+                   actor_token_id, actor_user_id, actor_name, created_at
+            FROM link_versions
+            WHERE link_id = $1
+            ORDER BY version DESC
+            "#,
+            link_id
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                link_version(
+                    r.link_id,
+                    r.version,
+                    r.long_url,
+                    r.expires_at,
+                    r.permanent,
+                    r.reverted_from,
+                    actor(r.actor_token_id, r.actor_user_id, r.actor_name),
+                    r.created_at,
+                )
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "link_version_repository.find", skip_all, fields(db.system = "postgresql", link_id = link_id, version = version))]
+    async fn find(&self, link_id: i64, version: i32) -> Result<Option<LinkVersion>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT link_id, version, long_url, expires_at, permanent, reverted_from,
+                   actor_token_id, actor_user_id, actor_name, created_at
+            FROM link_versions
+            WHERE link_id = $1 AND version = $2
+            "#,
+            link_id,
+            version
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.map(|r| {
+            link_version(
+                r.link_id,
+                r.version,
+                r.long_url,
+                r.expires_at,
+                r.permanent,
+                r.reverted_from,
+                actor(r.actor_token_id, r.actor_user_id, r.actor_name),
+                r.created_at,
+            )
+        }))
+    }
+}
+
+/// Appends `version` as the link's next version.
+///
+/// Called by [`PgLinkRepository`](super::PgLinkRepository) in the transaction that
+/// creates or changes the link, with the link row locked, so concurrent changes
+/// of a link get consecutive numbers.
+pub(super) async fn record_link_version(
+    conn: &mut PgConnection,
+    version: &NewLinkVersion,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO link_versions (
+            link_id, version, long_url, expires_at, permanent, reverted_from,
+            actor_token_id, actor_user_id, actor_name
+        )
+        SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4, $5, $6, $7, $8
+        FROM link_versions
+        WHERE link_id = $1
+        "#,
+        version.link_id,
+        version.long_url,
+        version.expires_at,
+        version.permanent,
+        version.reverted_from,
+        version.actor.token_id,
+        version.actor.user_id,
+        version.actor.name
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Builds the actor of a version; versions without an actor name have none.
+fn actor(token_id: Option<i64>, user_id: Option<i64>, name: Option<String>) -> Option<AuditActor> {
+    name.map(|name| AuditActor {
+        token_id,
+        user_id,
+        name,
+    })
+}
+
+/// Builds a link version from its row.
+#[allow(clippy::too_many_arguments)]
+fn link_version(
+    link_id: i64,
+    version: i32,
+    long_url: String,
+    expires_at: Option<DateTime<Utc>>,
+    permanent: bool,
+    reverted_from: Option<i32>,
+    actor: Option<AuditActor>,
+    created_at: DateTime<Utc>,
+) -> LinkVersion {
+    LinkVersion {
+        link_id,
+        version,
+        long_url,
+        expires_at,
+        permanent,
+        reverted_from,
+        actor,
+        created_at,
+    }
+}
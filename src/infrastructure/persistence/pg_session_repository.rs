@@ -0,0 +1,154 @@
+//! PostgreSQL implementation of session repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use super::pg_token_repository::parse_scopes;
+use super::pg_user_repository::user_ref;
+use crate::domain::entities::{DEFAULT_WORKSPACE_ID, Principal, User};
+use crate::domain::repositories::{ApiToken, DashboardSession, SessionOwner, SessionRepository};
+use crate::error::AppError;
+use serde_json::json;
+
+/// PostgreSQL repository for dashboard sessions.
+///
+/// Token sessions are joined with their API token on lookup, so revoking or
+/// expiring the token ends its sessions immediately. Single sign-on sessions
+/// are joined with their user, so role and team changes apply immediately.
+pub struct PgSessionRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgSessionRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PgSessionRepository {
+    #[tracing::instrument(name = "session_repository.create_session", skip_all, fields(db.system = "postgresql"))]
+    async fn create_session(
+        &self,
+        id_hash: &str,
+        owner: &SessionOwner,
+        csrf_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let (token_id, user_id) = match owner {
+            SessionOwner::Token(id) => (Some(*id), None),
+            SessionOwner::User(id) => (None, Some(*id)),
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO dashboard_sessions (id_hash, token_id, user_id, csrf_token, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id_hash,
+            token_id,
+            user_id,
+            csrf_token,
+            expires_at
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "session_repository.find_session", skip_all, fields(db.system = "postgresql"))]
+    async fn find_session(&self, id_hash: &str) -> Result<Option<DashboardSession>, AppError> {
+        // A token session acts for the token's user, if it is bound to one.
+        let row = sqlx::query!(
+            r#"
+            SELECT s.id_hash, s.csrf_token, s.created_at, s.expires_at,
+                   t.id AS "token_id?", t.name AS "name?", t.token_hash AS "token_hash?",
+                   t.scopes AS "scopes?", t.domain_ids,
+                   t.created_at AS "token_created_at?", t.revoked_at,
+                   t.expires_at AS token_expires_at, t.workspace_id AS "token_workspace_id?",
+                   u.id AS "user_id?", u.email AS "email?", u.sso_subject,
+                   u.role AS "role?", u.team_id, u.workspace_id AS "user_workspace_id?",
+                   u.created_at AS "user_created_at?"
+            FROM dashboard_sessions s
+            LEFT JOIN api_tokens t ON t.id = s.token_id
+            LEFT JOIN users u ON u.id = COALESCE(s.user_id, t.user_id)
+            WHERE s.id_hash = $1
+              AND s.expires_at > NOW()
+              AND (
+                  s.token_id IS NULL
+                  OR (t.revoked_at IS NULL AND (t.expires_at IS NULL OR t.expires_at > NOW()))
+              )
+            "#,
+            id_hash
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        let Some(r) = row else {
+            return Ok(None);
+        };
+
+        let principal = match (r.token_id, r.user_id, r.email, r.role) {
+            (Some(token_id), user_id, _, role) => ApiToken {
+                id: token_id,
+                name: r.name.unwrap_or_default(),
+                token_hash: r.token_hash.unwrap_or_default(),
+                scopes: parse_scopes(r.scopes.unwrap_or_default()),
+                domain_ids: r.domain_ids,
+                created_at: r.token_created_at.unwrap_or(r.created_at),
+                revoked_at: r.revoked_at,
+                expires_at: r.token_expires_at,
+                user: user_ref(user_id, r.team_id, role)?,
+                workspace_id: r.token_workspace_id.unwrap_or(DEFAULT_WORKSPACE_ID),
+            }
+            .principal(),
+            (None, Some(user_id), email, Some(role)) => Principal::for_user(&User {
+                id: user_id,
+                email,
+                sso_subject: r.sso_subject,
+                role: role.parse().map_err(|e: String| {
+                    AppError::internal("Invalid user role", json!({ "reason": e }))
+                })?,
+                team_id: r.team_id,
+                workspace_id: r.user_workspace_id.unwrap_or(DEFAULT_WORKSPACE_ID),
+                created_at: r.user_created_at.unwrap_or(r.created_at),
+            }),
+            _ => {
+                return Err(AppError::internal(
+                    "Session has no owner",
+                    json!({ "reason": "neither token nor user is set" }),
+                ));
+            }
+        };
+
+        Ok(Some(DashboardSession {
+            id_hash: r.id_hash,
+            principal,
+            csrf_token: r.csrf_token,
+            created_at: r.created_at,
+            expires_at: r.expires_at,
+        }))
+    }
+
+    #[tracing::instrument(name = "session_repository.delete_session", skip_all, fields(db.system = "postgresql"))]
+    async fn delete_session(&self, id_hash: &str) -> Result<(), AppError> {
+        sqlx::query!("DELETE FROM dashboard_sessions WHERE id_hash = $1", id_hash)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "session_repository.delete_expired", skip_all, fields(db.system = "postgresql"))]
+    async fn delete_expired(&self) -> Result<u64, AppError> {
+        let result = sqlx::query!("DELETE FROM dashboard_sessions WHERE expires_at <= NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
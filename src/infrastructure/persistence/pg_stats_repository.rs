@@ -1,11 +1,13 @@
 //! PostgreSQL implementation of statistics repository.
 
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::sync::Arc;
 
-use crate::domain::entities::{Click, Link, NewClick};
-use crate::domain::repositories::{DetailedStats, LinkStats, StatsFilter, StatsRepository};
+use crate::domain::entities::{Click, Link, NewClick, Visibility};
+use crate::domain::repositories::{
+    DetailedStats, LinkStats, PopularLink, StatsFilter, StatsRepository,
+};
 use crate::error::AppError;
 
 /// PostgreSQL repository for click tracking and analytics.
@@ -25,17 +27,19 @@ impl PgStatsRepository {
 
 #[async_trait]
 impl StatsRepository for PgStatsRepository {
+    #[tracing::instrument(name = "stats_repository.record_click", skip_all, fields(db.system = "postgresql", link_id = new_click.link_id))]
     async fn record_click(&self, new_click: NewClick) -> Result<Click, AppError> {
         let row = sqlx::query!(
             r#"
-            INSERT INTO link_clicks (link_id, user_agent, referer, ip)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO link_clicks (link_id, user_agent, referer, ip, clicked_at)
+            VALUES ($1, $2, $3, $4, COALESCE($5, NOW()))
             RETURNING id, link_id, clicked_at, user_agent, referer, ip
             "#,
             new_click.link_id,
             new_click.user_agent,
             new_click.referer,
-            new_click.ip
+            new_click.ip,
+            new_click.clicked_at
         )
         .fetch_one(self.pool.as_ref())
         .await?;
@@ -50,6 +54,109 @@ impl StatsRepository for PgStatsRepository {
         ))
     }
 
+    #[tracing::instrument(name = "stats_repository.record_clicks", skip_all, fields(db.system = "postgresql", clicks = clicks.len()))]
+    async fn record_clicks(&self, clicks: &[NewClick]) -> Result<u64, AppError> {
+        let mut link_ids = Vec::with_capacity(clicks.len());
+        let mut user_agents = Vec::with_capacity(clicks.len());
+        let mut referers = Vec::with_capacity(clicks.len());
+        let mut ips = Vec::with_capacity(clicks.len());
+        let mut clicked_ats = Vec::with_capacity(clicks.len());
+        for click in clicks {
+            link_ids.push(click.link_id);
+            user_agents.push(click.user_agent.clone());
+            referers.push(click.referer.clone());
+            ips.push(click.ip.clone());
+            clicked_ats.push(click.clicked_at);
+        }
+
+        // The EXISTS check skips clicks of links removed since the redirect,
+        // which would otherwise fail the foreign key for the whole batch.
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO link_clicks (link_id, user_agent, referer, ip, clicked_at)
+            SELECT c.link_id, c.user_agent, c.referer, c.ip, COALESCE(c.clicked_at, NOW())
+            FROM UNNEST($1::bigint[], $2::text[], $3::text[], $4::text[], $5::timestamptz[])
+                AS c(link_id, user_agent, referer, ip, clicked_at)
+            WHERE EXISTS (SELECT 1 FROM links l WHERE l.id = c.link_id)
+            "#,
+            &link_ids,
+            &user_agents as &[Option<String>],
+            &referers as &[Option<String>],
+            &ips as &[Option<String>],
+            &clicked_ats as &[Option<chrono::DateTime<chrono::Utc>>]
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(name = "stats_repository.flush_link_clicks", skip_all, fields(db.system = "postgresql"))]
+    async fn flush_link_clicks(&self) -> Result<u64, AppError> {
+        let horizon = committed_click_horizon(&self.pool).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let counted_up_to = lock_click_watermark(&mut tx).await?;
+        let horizon = horizon.max(counted_up_to);
+
+        let result = sqlx::query!(
+            r#"
+            WITH new_clicks AS (
+                SELECT link_id, COUNT(*) AS clicks
+                FROM link_clicks
+                WHERE id > $1 AND id <= $2
+                GROUP BY link_id
+            )
+            UPDATE links
+            SET clicks = links.clicks + new_clicks.clicks
+            FROM new_clicks
+            WHERE links.id = new_clicks.link_id
+            "#,
+            counted_up_to,
+            horizon
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        set_click_watermark(&mut tx, horizon).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(name = "stats_repository.reconcile_link_clicks", skip_all, fields(db.system = "postgresql"))]
+    async fn reconcile_link_clicks(&self) -> Result<u64, AppError> {
+        let horizon = committed_click_horizon(&self.pool).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let counted_up_to = lock_click_watermark(&mut tx).await?;
+        let horizon = horizon.max(counted_up_to);
+
+        let result = sqlx::query!(
+            r#"
+            WITH counts AS (
+                SELECT l.id, COUNT(lc.id) AS clicks
+                FROM links l
+                LEFT JOIN link_clicks lc ON lc.link_id = l.id AND lc.id <= $1
+                GROUP BY l.id
+            )
+            UPDATE links
+            SET clicks = counts.clicks
+            FROM counts
+            WHERE links.id = counts.id AND links.clicks <> counts.clicks
+            "#,
+            horizon
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        set_click_watermark(&mut tx, horizon).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(name = "stats_repository.get_stats_by_code", skip_all, fields(db.system = "postgresql"))]
     async fn get_stats_by_code(
         &self,
         code: &str,
@@ -57,13 +164,22 @@ impl StatsRepository for PgStatsRepository {
     ) -> Result<Option<DetailedStats>, AppError> {
         let link_row = sqlx::query!(
             r#"
-            SELECT l.id, l.code, l.long_url, d.domain as "domain?", l.created_at
+            SELECT l.id, l.code, l.long_url, d.domain as "domain?", l.created_at,
+                   l.owner_id, l.team_id, l.workspace_id
             FROM links l
             LEFT JOIN domains d ON d.id = l.domain_id
-            WHERE code = $1 AND ($2::bigint IS NULL OR domain_id = $2)
+            WHERE code = $1
+              AND ($2::bigint IS NULL OR domain_id = $2)
+              AND ($3::bigint[] IS NULL OR domain_id = ANY($3))
+              AND ($4::bigint IS NULL OR l.owner_id = $4 OR l.team_id = $5)
+              AND ($6::bigint IS NULL OR l.workspace_id = $6)
             "#,
             code,
             filter.domain_id,
+            filter.domain_ids.as_deref(),
+            filter.visibility.owner_id(),
+            filter.visibility.team_id(),
+            filter.visibility.workspace_id(),
         )
         .fetch_optional(self.pool.as_ref())
         .await?;
@@ -82,7 +198,9 @@ impl StatsRepository for PgStatsRepository {
             None,
             false,
             None,
-        );
+        )
+        .with_owner(link_row.owner_id, link_row.team_id)
+        .with_workspace(link_row.workspace_id);
 
         let total = self
             .count_clicks_by_link_id(link.id, filter.from_date, filter.to_date)
@@ -115,7 +233,47 @@ impl StatsRepository for PgStatsRepository {
         Ok(Some(DetailedStats { link, total, items }))
     }
 
+    #[tracing::instrument(name = "stats_repository.get_all_stats", skip_all, fields(db.system = "postgresql"))]
     async fn get_all_stats(&self, filter: StatsFilter) -> Result<Vec<LinkStats>, AppError> {
+        // All-time totals come from the counters; only a date range needs the
+        // join over link_clicks.
+        if filter.from_date.is_none() && filter.to_date.is_none() {
+            let rows = sqlx::query!(
+                r#"
+                SELECT l.id, l.code, l.long_url, l.created_at, l.clicks, d.domain as "domain?"
+                FROM links l
+                LEFT JOIN domains d ON d.id = l.domain_id
+                WHERE ($3::bigint IS NULL OR l.domain_id = $3)
+                  AND ($4::bigint[] IS NULL OR l.domain_id = ANY($4))
+                  AND ($5::bigint IS NULL OR l.owner_id = $5 OR l.team_id = $6)
+                  AND ($7::bigint IS NULL OR l.workspace_id = $7)
+                ORDER BY l.created_at DESC
+                LIMIT $1 OFFSET $2
+                "#,
+                filter.limit,
+                filter.offset,
+                filter.domain_id,
+                filter.domain_ids.as_deref(),
+                filter.visibility.owner_id(),
+                filter.visibility.team_id(),
+                filter.visibility.workspace_id(),
+            )
+            .fetch_all(self.pool.as_ref())
+            .await?;
+
+            return Ok(rows
+                .into_iter()
+                .map(|r| LinkStats {
+                    link_id: r.id,
+                    code: r.code,
+                    domain: r.domain,
+                    long_url: r.long_url,
+                    total: r.clicks,
+                    created_at: r.created_at,
+                })
+                .collect());
+        }
+
         let rows = sqlx::query!(
             r#"
             SELECT
@@ -131,6 +289,9 @@ impl StatsRepository for PgStatsRepository {
                 AND ($2::timestamptz IS NULL OR lc.clicked_at <= $2)
             LEFT JOIN domains d ON d.id = l.domain_id
             WHERE ($5::bigint IS NULL OR l.domain_id = $5)
+              AND ($6::bigint[] IS NULL OR l.domain_id = ANY($6))
+              AND ($7::bigint IS NULL OR l.owner_id = $7 OR l.team_id = $8)
+              AND ($9::bigint IS NULL OR l.workspace_id = $9)
             GROUP BY l.id, l.code, l.long_url, l.created_at, d.domain
             ORDER BY l.created_at DESC
             LIMIT $3 OFFSET $4
@@ -140,6 +301,10 @@ impl StatsRepository for PgStatsRepository {
             filter.limit,
             filter.offset,
             filter.domain_id,
+            filter.domain_ids.as_deref(),
+            filter.visibility.owner_id(),
+            filter.visibility.team_id(),
+            filter.visibility.workspace_id(),
         )
         .fetch_all(self.pool.as_ref())
         .await?;
@@ -157,12 +322,24 @@ impl StatsRepository for PgStatsRepository {
             .collect())
     }
 
-    async fn count_all_links(&self) -> Result<i64, AppError> {
+    #[tracing::instrument(name = "stats_repository.count_all_links", skip_all, fields(db.system = "postgresql"))]
+    async fn count_all_links(
+        &self,
+        domain_ids: Option<Vec<i64>>,
+        visibility: &Visibility,
+    ) -> Result<i64, AppError> {
         let row = sqlx::query!(
             r#"
             SELECT COUNT(*) as count
             FROM links
-            "#
+            WHERE ($1::bigint[] IS NULL OR domain_id = ANY($1))
+              AND ($2::bigint IS NULL OR owner_id = $2 OR team_id = $3)
+              AND ($4::bigint IS NULL OR workspace_id = $4)
+            "#,
+            domain_ids.as_deref(),
+            visibility.owner_id(),
+            visibility.team_id(),
+            visibility.workspace_id()
         )
         .fetch_one(self.pool.as_ref())
         .await?;
@@ -170,6 +347,7 @@ impl StatsRepository for PgStatsRepository {
         Ok(row.count.unwrap_or(0))
     }
 
+    #[tracing::instrument(name = "stats_repository.count_clicks_by_link_id", skip_all, fields(db.system = "postgresql"))]
     async fn count_clicks_by_link_id(
         &self,
         link_id: i64,
@@ -193,4 +371,101 @@ impl StatsRepository for PgStatsRepository {
 
         Ok(row.count.unwrap_or(0))
     }
+
+    #[tracing::instrument(name = "stats_repository.most_clicked", skip_all, fields(db.system = "postgresql", limit))]
+    async fn most_clicked(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<PopularLink>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                l.id,
+                d.id as domain_id,
+                d.domain,
+                l.code,
+                l.long_url,
+                l.permanent,
+                l.expires_at,
+                COUNT(*) as "clicks!"
+            FROM link_clicks lc
+            JOIN links l ON l.id = lc.link_id
+            JOIN domains d ON d.id = l.domain_id
+            WHERE lc.clicked_at >= $1
+              AND l.deleted_at IS NULL
+              AND (l.expires_at IS NULL OR l.expires_at > NOW())
+              AND d.deleted_at IS NULL
+            GROUP BY l.id, d.id
+            ORDER BY COUNT(*) DESC, l.id
+            LIMIT $2
+            "#,
+            since,
+            limit
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| PopularLink {
+                link_id: r.id,
+                domain_id: r.domain_id,
+                domain: r.domain,
+                code: r.code,
+                long_url: r.long_url,
+                permanent: r.permanent,
+                expires_at: r.expires_at,
+                clicks: r.clicks,
+            })
+            .collect())
+    }
+}
+
+/// Returns the highest `link_clicks` ID up to which every click is committed.
+///
+/// IDs are assigned before commit, so a click committed late could otherwise
+/// land below the watermark and never be counted. The table lock waits for
+/// clicks being inserted and is released as soon as the ID has been read, so
+/// new clicks are only held back for that moment; they get higher IDs.
+async fn committed_click_horizon(pool: &PgPool) -> Result<i64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("LOCK TABLE link_clicks IN SHARE MODE")
+        .execute(&mut *tx)
+        .await?;
+    let horizon =
+        sqlx::query_scalar!(r#"SELECT COALESCE(MAX(id), 0) AS "horizon!" FROM link_clicks"#)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+
+    Ok(horizon)
+}
+
+/// Locks the click counter watermark, serializing flushes and recounts;
+/// returns the highest `link_clicks` ID already counted.
+async fn lock_click_watermark(tx: &mut Transaction<'_, Postgres>) -> Result<i64, AppError> {
+    let counted_up_to =
+        sqlx::query_scalar!("SELECT counted_up_to FROM click_counter_watermark FOR UPDATE")
+            .fetch_one(&mut **tx)
+            .await?;
+
+    Ok(counted_up_to)
+}
+
+/// Moves the watermark to `counted_up_to`.
+async fn set_click_watermark(
+    tx: &mut Transaction<'_, Postgres>,
+    counted_up_to: i64,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        "UPDATE click_counter_watermark SET counted_up_to = $1",
+        counted_up_to
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
 }
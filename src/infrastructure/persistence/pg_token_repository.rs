@@ -1,15 +1,20 @@
 //! PostgreSQL implementation of token repository.
 
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::sync::Arc;
 
-use crate::domain::repositories::{ApiToken, TokenRepository};
+use super::pg_audit_repository::record_audit_event;
+use super::pg_user_repository::user_ref;
+use crate::domain::entities::{AuditContext, NewAuditEvent, Scope};
+use crate::domain::repositories::{ApiToken, NewApiToken, TokenRepository};
 use crate::error::AppError;
 
 /// PostgreSQL repository for API token storage and validation.
 ///
-/// Stores hashed tokens (SHA-256) for security. Raw tokens are never persisted.
+/// Stores hashed tokens (HMAC-SHA256) for security. Raw tokens are never persisted.
 pub struct PgTokenRepository {
     pool: Arc<PgPool>,
 }
@@ -23,22 +28,43 @@ impl PgTokenRepository {
 
 #[async_trait]
 impl TokenRepository for PgTokenRepository {
-    async fn validate_token(&self, token_hash: &str) -> Result<bool, AppError> {
+    #[tracing::instrument(name = "token_repository.validate_token", skip_all, fields(db.system = "postgresql"))]
+    async fn validate_token(&self, token_hashes: &[String]) -> Result<Option<ApiToken>, AppError> {
         let row = sqlx::query!(
             r#"
-            SELECT id
-            FROM api_tokens
-            WHERE token_hash = $1
-              AND revoked_at IS NULL
+            SELECT t.id, t.name, t.token_hash, t.scopes, t.domain_ids, t.created_at, t.revoked_at,
+                   t.expires_at, t.workspace_id, u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM api_tokens t
+            LEFT JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = ANY($1)
+              AND t.revoked_at IS NULL
+              AND (t.expires_at IS NULL OR t.expires_at > NOW())
+            LIMIT 1
             "#,
-            token_hash
+            token_hashes
         )
         .fetch_optional(self.pool.as_ref())
         .await?;
 
-        Ok(row.is_some())
+        row.map(|r| {
+            Ok(ApiToken {
+                id: r.id,
+                name: r.name,
+                token_hash: r.token_hash,
+                scopes: parse_scopes(r.scopes),
+                domain_ids: r.domain_ids,
+                created_at: r.created_at,
+                revoked_at: r.revoked_at,
+                expires_at: r.expires_at,
+                user: user_ref(r.user_id, r.user_team_id, r.user_role)?,
+                workspace_id: r.workspace_id,
+            })
+        })
+        .transpose()
     }
 
+    #[tracing::instrument(name = "token_repository.update_last_used", skip_all, fields(db.system = "postgresql"))]
     async fn update_last_used(&self, token_hash: &str) -> Result<(), AppError> {
         sqlx::query!(
             r#"
@@ -55,105 +81,343 @@ impl TokenRepository for PgTokenRepository {
         Ok(())
     }
 
-    async fn create_token(&self, name: &str, token_hash: &str) -> Result<ApiToken, AppError> {
+    #[tracing::instrument(name = "token_repository.rehash_token", skip_all, fields(db.system = "postgresql", token_id = id))]
+    async fn rehash_token(&self, id: i64, old_hash: &str, new_hash: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE api_tokens
+            SET token_hash = $3
+            WHERE id = $1
+              AND token_hash = $2
+            "#,
+            id,
+            old_hash,
+            new_hash
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "token_repository.create_token", skip_all, fields(db.system = "postgresql"))]
+    async fn create_token(
+        &self,
+        new_token: NewApiToken,
+        context: &AuditContext,
+    ) -> Result<ApiToken, AppError> {
+        let scopes = scope_names(&new_token.scopes);
+
+        let mut tx = self.pool.begin().await?;
+
         let row = sqlx::query!(
             r#"
-            INSERT INTO api_tokens (name, token_hash)
-            VALUES ($1, $2)
-            RETURNING id, name, token_hash, created_at, revoked_at
+            WITH t AS (
+                INSERT INTO api_tokens (name, token_hash, scopes, domain_ids, expires_at, user_id,
+                                        workspace_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+            )
+            SELECT t.id AS "id!", t.name AS "name!", t.token_hash AS "token_hash!",
+                   t.scopes AS "scopes!", t.domain_ids, t.created_at AS "created_at!",
+                   t.revoked_at, t.expires_at, t.workspace_id AS "workspace_id!",
+                   u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM t
+            LEFT JOIN users u ON u.id = t.user_id
             "#,
-            name,
-            token_hash
+            new_token.name,
+            new_token.token_hash,
+            &scopes,
+            new_token.domain_ids.as_deref(),
+            new_token.expires_at,
+            new_token.user_id,
+            new_token.workspace_id
         )
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(ApiToken {
+        let token = ApiToken {
             id: row.id,
             name: row.name,
             token_hash: row.token_hash,
+            scopes: parse_scopes(row.scopes),
+            domain_ids: row.domain_ids,
             created_at: row.created_at,
             revoked_at: row.revoked_at,
-        })
+            expires_at: row.expires_at,
+            user: user_ref(row.user_id, row.user_team_id, row.user_role)?,
+            workspace_id: row.workspace_id,
+        };
+
+        record_audit_event(&mut tx, &NewAuditEvent::created(context, &token)).await?;
+
+        tx.commit().await?;
+
+        Ok(token)
     }
 
-    async fn list_tokens(&self) -> Result<Vec<ApiToken>, AppError> {
+    #[tracing::instrument(name = "token_repository.rotate_token", skip_all, fields(db.system = "postgresql", token_id = id))]
+    async fn rotate_token(
+        &self,
+        id: i64,
+        token_hash: &str,
+        expires_at: Option<DateTime<Utc>>,
+        grace_until: DateTime<Utc>,
+        context: &AuditContext,
+    ) -> Result<ApiToken, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let not_found = || AppError::not_found("Active token not found", json!({ "id": id }));
+        let before = lock_token(&mut tx, id).await?.ok_or_else(not_found)?;
+
+        // LEAST ignores NULL: a token without expiry gets `grace_until`,
+        // one that already expires earlier keeps its own expiry.
+        let old = sqlx::query!(
+            r#"
+            UPDATE api_tokens
+            SET expires_at = LEAST(expires_at, $2)
+            WHERE id = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING name, scopes, domain_ids, user_id, workspace_id, expires_at
+            "#,
+            id,
+            grace_until
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(not_found)?;
+
+        let row = sqlx::query!(
+            r#"
+            WITH t AS (
+                INSERT INTO api_tokens (name, token_hash, scopes, domain_ids, expires_at, user_id,
+                                        workspace_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+            )
+            SELECT t.id AS "id!", t.name AS "name!", t.token_hash AS "token_hash!",
+                   t.scopes AS "scopes!", t.domain_ids, t.created_at AS "created_at!",
+                   t.revoked_at, t.expires_at, t.workspace_id AS "workspace_id!",
+                   u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM t
+            LEFT JOIN users u ON u.id = t.user_id
+            "#,
+            old.name,
+            token_hash,
+            &old.scopes,
+            old.domain_ids.as_deref(),
+            expires_at,
+            old.user_id,
+            old.workspace_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let token = ApiToken {
+            id: row.id,
+            name: row.name,
+            token_hash: row.token_hash,
+            scopes: parse_scopes(row.scopes),
+            domain_ids: row.domain_ids,
+            created_at: row.created_at,
+            revoked_at: row.revoked_at,
+            expires_at: row.expires_at,
+            user: user_ref(row.user_id, row.user_team_id, row.user_role)?,
+            workspace_id: row.workspace_id,
+        };
+        let after = ApiToken {
+            expires_at: old.expires_at,
+            ..before.clone()
+        };
+
+        record_audit_event(&mut tx, &NewAuditEvent::created(context, &token)).await?;
+        record_audit_event(&mut tx, &NewAuditEvent::updated(context, &before, &after)).await?;
+
+        tx.commit().await?;
+
+        Ok(token)
+    }
+
+    #[tracing::instrument(name = "token_repository.list_tokens", skip_all, fields(db.system = "postgresql"))]
+    async fn list_tokens(&self, workspace_id: Option<i64>) -> Result<Vec<ApiToken>, AppError> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, name, token_hash, created_at, revoked_at
-            FROM api_tokens
-            ORDER BY created_at DESC
-            "#
+            SELECT t.id, t.name, t.token_hash, t.scopes, t.domain_ids, t.created_at, t.revoked_at,
+                   t.expires_at, t.workspace_id, u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM api_tokens t
+            LEFT JOIN users u ON u.id = t.user_id
+            WHERE $1::bigint IS NULL OR t.workspace_id = $1
+            ORDER BY t.created_at DESC
+            "#,
+            workspace_id
         )
         .fetch_all(self.pool.as_ref())
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|row| ApiToken {
-                id: row.id,
-                name: row.name,
-                token_hash: row.token_hash,
-                created_at: row.created_at,
-                revoked_at: row.revoked_at,
+        rows.into_iter()
+            .map(|row| {
+                Ok(ApiToken {
+                    id: row.id,
+                    name: row.name,
+                    token_hash: row.token_hash,
+                    scopes: parse_scopes(row.scopes),
+                    domain_ids: row.domain_ids,
+                    created_at: row.created_at,
+                    revoked_at: row.revoked_at,
+                    expires_at: row.expires_at,
+                    user: user_ref(row.user_id, row.user_team_id, row.user_role)?,
+                    workspace_id: row.workspace_id,
+                })
             })
-            .collect())
+            .collect()
     }
 
+    #[tracing::instrument(name = "token_repository.find_by_id", skip_all, fields(db.system = "postgresql"))]
     async fn find_by_id(&self, id: i64) -> Result<Option<ApiToken>, AppError> {
         let row = sqlx::query!(
             r#"
-            SELECT id, name, token_hash, created_at, revoked_at
-            FROM api_tokens
-            WHERE id = $1
+            SELECT t.id, t.name, t.token_hash, t.scopes, t.domain_ids, t.created_at, t.revoked_at,
+                   t.expires_at, t.workspace_id, u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM api_tokens t
+            LEFT JOIN users u ON u.id = t.user_id
+            WHERE t.id = $1
             "#,
             id
         )
         .fetch_optional(self.pool.as_ref())
         .await?;
 
-        Ok(row.map(|r| ApiToken {
-            id: r.id,
-            name: r.name,
-            token_hash: r.token_hash,
-            created_at: r.created_at,
-            revoked_at: r.revoked_at,
-        }))
+        row.map(|r| {
+            Ok(ApiToken {
+                id: r.id,
+                name: r.name,
+                token_hash: r.token_hash,
+                scopes: parse_scopes(r.scopes),
+                domain_ids: r.domain_ids,
+                created_at: r.created_at,
+                revoked_at: r.revoked_at,
+                expires_at: r.expires_at,
+                user: user_ref(r.user_id, r.user_team_id, r.user_role)?,
+                workspace_id: r.workspace_id,
+            })
+        })
+        .transpose()
     }
 
+    #[tracing::instrument(name = "token_repository.find_by_name", skip_all, fields(db.system = "postgresql"))]
     async fn find_by_name(&self, name: &str) -> Result<Option<ApiToken>, AppError> {
         let row = sqlx::query!(
             r#"
-            SELECT id, name, token_hash, created_at, revoked_at
-            FROM api_tokens
-            WHERE name = $1
+            SELECT t.id, t.name, t.token_hash, t.scopes, t.domain_ids, t.created_at, t.revoked_at,
+                   t.expires_at, t.workspace_id, u.id AS "user_id?", u.team_id AS user_team_id,
+                   u.role AS "user_role?"
+            FROM api_tokens t
+            LEFT JOIN users u ON u.id = t.user_id
+            WHERE t.name = $1
+            ORDER BY t.created_at DESC, t.id DESC
+            LIMIT 1
             "#,
             name
         )
         .fetch_optional(self.pool.as_ref())
         .await?;
 
-        Ok(row.map(|r| ApiToken {
+        row.map(|r| {
+            Ok(ApiToken {
+                id: r.id,
+                name: r.name,
+                token_hash: r.token_hash,
+                scopes: parse_scopes(r.scopes),
+                domain_ids: r.domain_ids,
+                created_at: r.created_at,
+                revoked_at: r.revoked_at,
+                expires_at: r.expires_at,
+                user: user_ref(r.user_id, r.user_team_id, r.user_role)?,
+                workspace_id: r.workspace_id,
+            })
+        })
+        .transpose()
+    }
+
+    #[tracing::instrument(name = "token_repository.revoke_token", skip_all, fields(db.system = "postgresql"))]
+    async fn revoke_token(&self, id: i64, context: &AuditContext) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_token(&mut tx, id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Token not found", json!({ "id": id })))?;
+        if before.revoked_at.is_some() {
+            return Ok(());
+        }
+
+        sqlx::query!("UPDATE api_tokens SET revoked_at = NOW() WHERE id = $1", id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(&mut tx, &NewAuditEvent::deleted(context, &before)).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// Loads a token and locks it until the transaction ends.
+async fn lock_token(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<ApiToken>, AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT t.id, t.name, t.token_hash, t.scopes, t.domain_ids, t.created_at, t.revoked_at,
+               t.expires_at, t.workspace_id, u.id AS "user_id?", u.team_id AS user_team_id,
+               u.role AS "user_role?"
+        FROM api_tokens t
+        LEFT JOIN users u ON u.id = t.user_id
+        WHERE t.id = $1
+        FOR UPDATE OF t
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    row.map(|r| {
+        Ok(ApiToken {
             id: r.id,
             name: r.name,
             token_hash: r.token_hash,
+            scopes: parse_scopes(r.scopes),
+            domain_ids: r.domain_ids,
             created_at: r.created_at,
             revoked_at: r.revoked_at,
-        }))
-    }
+            expires_at: r.expires_at,
+            user: user_ref(r.user_id, r.user_team_id, r.user_role)?,
+            workspace_id: r.workspace_id,
+        })
+    })
+    .transpose()
+}
 
-    async fn revoke_token(&self, id: i64) -> Result<(), AppError> {
-        sqlx::query!(
-            r#"
-            UPDATE api_tokens
-            SET revoked_at = NOW()
-            WHERE id = $1 AND revoked_at IS NULL
-            "#,
-            id
-        )
-        .execute(self.pool.as_ref())
-        .await?;
+/// Converts scopes to the names stored in `api_tokens.scopes`.
+fn scope_names(scopes: &[Scope]) -> Vec<String> {
+    scopes.iter().map(|s| s.as_str().to_string()).collect()
+}
 
-        Ok(())
-    }
+/// Parses stored scope names, skipping any this build does not know.
+pub(super) fn parse_scopes(raw: Vec<String>) -> Vec<Scope> {
+    raw.iter()
+        .filter_map(|s| match s.parse() {
+            Ok(scope) => Some(scope),
+            Err(_) => {
+                tracing::warn!(scope = %s, "Ignoring unknown token scope");
+                None
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,373 @@
+//! PostgreSQL implementation of user repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::domain::entities::{Role, Team, User, UserRef};
+use crate::domain::repositories::UserRepository;
+use crate::error::AppError;
+
+/// PostgreSQL repository for users and teams.
+pub struct PgUserRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgUserRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepository {
+    #[tracing::instrument(name = "user_repository.upsert_sso_user", skip_all, fields(db.system = "postgresql"))]
+    async fn upsert_sso_user<'a>(
+        &self,
+        subject: &str,
+        email: Option<&'a str>,
+        role: Role,
+    ) -> Result<User, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // A user created with the CLI is claimed on their first sign-in by email,
+        // but only by an email the identity provider verified.
+        let existing = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM users
+            WHERE sso_subject = $1
+               OR ($2::text IS NOT NULL AND sso_subject IS NULL AND lower(email) = lower($2))
+            ORDER BY sso_subject IS NULL
+            LIMIT 1
+            FOR UPDATE
+            "#,
+            subject,
+            email
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = match existing {
+            Some(id) => sqlx::query!(
+                r#"
+                    UPDATE users
+                    SET sso_subject = $2, email = $3, role = $4
+                    WHERE id = $1
+                    RETURNING id, email, sso_subject, role, team_id, workspace_id, created_at
+                    "#,
+                id,
+                subject,
+                email,
+                role.as_str()
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map(|r| {
+                user(
+                    r.id,
+                    r.email,
+                    r.sso_subject,
+                    &r.role,
+                    r.team_id,
+                    r.workspace_id,
+                    r.created_at,
+                )
+            })?,
+            None => sqlx::query!(
+                r#"
+                    INSERT INTO users (email, sso_subject, role)
+                    VALUES ($1, $2, $3)
+                    RETURNING id, email, sso_subject, role, team_id, workspace_id, created_at
+                    "#,
+                email,
+                subject,
+                role.as_str()
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map(|r| {
+                user(
+                    r.id,
+                    r.email,
+                    r.sso_subject,
+                    &r.role,
+                    r.team_id,
+                    r.workspace_id,
+                    r.created_at,
+                )
+            })?,
+        }?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    #[tracing::instrument(name = "user_repository.create_user", skip_all, fields(db.system = "postgresql"))]
+    async fn create_user(
+        &self,
+        email: &str,
+        role: Role,
+        team_id: Option<i64>,
+        workspace_id: i64,
+    ) -> Result<User, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO users (email, role, team_id, workspace_id)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, email, sso_subject, role, team_id, workspace_id, created_at
+            "#,
+            email,
+            role.as_str(),
+            team_id,
+            workspace_id
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        user(
+            row.id,
+            row.email,
+            row.sso_subject,
+            &row.role,
+            row.team_id,
+            row.workspace_id,
+            row.created_at,
+        )
+    }
+
+    #[tracing::instrument(name = "user_repository.find_by_email", skip_all, fields(db.system = "postgresql"))]
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, email, sso_subject, role, team_id, workspace_id, created_at
+            FROM users
+            WHERE lower(email) = lower($1)
+            "#,
+            email
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        row.map(|r| {
+            user(
+                r.id,
+                r.email,
+                r.sso_subject,
+                &r.role,
+                r.team_id,
+                r.workspace_id,
+                r.created_at,
+            )
+        })
+        .transpose()
+    }
+
+    #[tracing::instrument(name = "user_repository.list_users", skip_all, fields(db.system = "postgresql"))]
+    async fn list_users(&self) -> Result<Vec<User>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, email, sso_subject, role, team_id, workspace_id, created_at
+            FROM users
+            ORDER BY email
+            "#
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        rows.into_iter()
+            .map(|r| {
+                user(
+                    r.id,
+                    r.email,
+                    r.sso_subject,
+                    &r.role,
+                    r.team_id,
+                    r.workspace_id,
+                    r.created_at,
+                )
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "user_repository.set_role", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn set_role(&self, id: i64, role: Role) -> Result<User, AppError> {
+        // Checked in the same statement, so a concurrent first sign-in cannot slip in.
+        let row = sqlx::query!(
+            r#"
+            UPDATE users
+            SET role = CASE WHEN sso_subject IS NULL THEN $2 ELSE role END
+            WHERE id = $1
+            RETURNING id, email, sso_subject, role, team_id, workspace_id, created_at
+            "#,
+            id,
+            role.as_str()
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?
+        .ok_or_else(|| AppError::not_found("User not found", json!({ "id": id })))?;
+
+        if row.sso_subject.is_some() {
+            return Err(AppError::conflict(
+                "The role of a single sign-on user comes from the identity provider",
+                json!({ "id": id }),
+            ));
+        }
+
+        user(
+            row.id,
+            row.email,
+            row.sso_subject,
+            &row.role,
+            row.team_id,
+            row.workspace_id,
+            row.created_at,
+        )
+    }
+
+    #[tracing::instrument(name = "user_repository.set_team", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn set_team(&self, id: i64, team_id: Option<i64>) -> Result<User, AppError> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE users
+            SET team_id = $2
+            WHERE id = $1
+            RETURNING id, email, sso_subject, role, team_id, workspace_id, created_at
+            "#,
+            id,
+            team_id
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?
+        .ok_or_else(|| AppError::not_found("User not found", json!({ "id": id })))?;
+
+        user(
+            row.id,
+            row.email,
+            row.sso_subject,
+            &row.role,
+            row.team_id,
+            row.workspace_id,
+            row.created_at,
+        )
+    }
+
+    #[tracing::instrument(name = "user_repository.create_team", skip_all, fields(db.system = "postgresql"))]
+    async fn create_team(&self, name: &str, workspace_id: i64) -> Result<Team, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO teams (name, workspace_id)
+            VALUES ($1, $2)
+            RETURNING id, workspace_id, name, created_at
+            "#,
+            name,
+            workspace_id
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(Team {
+            id: row.id,
+            workspace_id: row.workspace_id,
+            name: row.name,
+            created_at: row.created_at,
+        })
+    }
+
+    #[tracing::instrument(name = "user_repository.find_team_by_name", skip_all, fields(db.system = "postgresql"))]
+    async fn find_team_by_name(
+        &self,
+        name: &str,
+        workspace_id: i64,
+    ) -> Result<Option<Team>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, workspace_id, name, created_at
+            FROM teams
+            WHERE name = $1 AND workspace_id = $2
+            "#,
+            name,
+            workspace_id
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.map(|r| Team {
+            id: r.id,
+            workspace_id: r.workspace_id,
+            name: r.name,
+            created_at: r.created_at,
+        }))
+    }
+
+    #[tracing::instrument(name = "user_repository.list_teams", skip_all, fields(db.system = "postgresql"))]
+    async fn list_teams(&self) -> Result<Vec<Team>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, workspace_id, name, created_at
+            FROM teams
+            ORDER BY name
+            "#
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Team {
+                id: r.id,
+                workspace_id: r.workspace_id,
+                name: r.name,
+                created_at: r.created_at,
+            })
+            .collect())
+    }
+}
+
+/// Builds a user from its row.
+fn user(
+    id: i64,
+    email: Option<String>,
+    sso_subject: Option<String>,
+    role: &str,
+    team_id: Option<i64>,
+    workspace_id: i64,
+    created_at: DateTime<Utc>,
+) -> Result<User, AppError> {
+    Ok(User {
+        id,
+        email,
+        sso_subject,
+        role: parse_role(role)?,
+        team_id,
+        workspace_id,
+        created_at,
+    })
+}
+
+/// Builds the user a token or session acts for from the joined `users` columns.
+///
+/// `id` is `None` when no user is joined.
+pub(super) fn user_ref(
+    id: Option<i64>,
+    team_id: Option<i64>,
+    role: Option<String>,
+) -> Result<Option<UserRef>, AppError> {
+    match (id, role) {
+        (Some(id), Some(role)) => Ok(Some(UserRef {
+            id,
+            team_id,
+            role: parse_role(&role)?,
+        })),
+        _ => Ok(None),
+    }
+}
+
+fn parse_role(raw: &str) -> Result<Role, AppError> {
+    raw.parse()
+        .map_err(|e: String| AppError::internal("Invalid user role", json!({ "reason": e })))
+}
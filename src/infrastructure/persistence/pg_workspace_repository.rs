@@ -0,0 +1,221 @@
+//! PostgreSQL implementation of workspace repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::domain::entities::{Workspace, WorkspaceQuotas};
+use crate::domain::repositories::WorkspaceRepository;
+use crate::error::AppError;
+
+/// PostgreSQL repository for workspaces and their quota usage.
+pub struct PgWorkspaceRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgWorkspaceRepository {
+    /// Creates a new repository with a database connection pool.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkspaceRepository for PgWorkspaceRepository {
+    #[tracing::instrument(name = "workspace_repository.create", skip_all, fields(db.system = "postgresql"))]
+    async fn create(&self, name: &str, quotas: WorkspaceQuotas) -> Result<Workspace, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO workspaces (name, max_links, max_domains, monthly_click_allowance)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, name, max_links, max_domains, monthly_click_allowance, created_at
+            "#,
+            name,
+            quotas.max_links,
+            quotas.max_domains,
+            quotas.monthly_click_allowance
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(workspace(
+            row.id,
+            row.name,
+            row.max_links,
+            row.max_domains,
+            row.monthly_click_allowance,
+            row.created_at,
+        ))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.find_by_id", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn find_by_id(&self, id: i64) -> Result<Option<Workspace>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, name, max_links, max_domains, monthly_click_allowance, created_at
+            FROM workspaces
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.map(|r| {
+            workspace(
+                r.id,
+                r.name,
+                r.max_links,
+                r.max_domains,
+                r.monthly_click_allowance,
+                r.created_at,
+            )
+        }))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.find_by_name", skip_all, fields(db.system = "postgresql"))]
+    async fn find_by_name(&self, name: &str) -> Result<Option<Workspace>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, name, max_links, max_domains, monthly_click_allowance, created_at
+            FROM workspaces
+            WHERE name = $1
+            "#,
+            name
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(row.map(|r| {
+            workspace(
+                r.id,
+                r.name,
+                r.max_links,
+                r.max_domains,
+                r.monthly_click_allowance,
+                r.created_at,
+            )
+        }))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.list", skip_all, fields(db.system = "postgresql"))]
+    async fn list(&self) -> Result<Vec<Workspace>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, max_links, max_domains, monthly_click_allowance, created_at
+            FROM workspaces
+            ORDER BY name
+            "#
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                workspace(
+                    r.id,
+                    r.name,
+                    r.max_links,
+                    r.max_domains,
+                    r.monthly_click_allowance,
+                    r.created_at,
+                )
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "workspace_repository.set_quotas", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn set_quotas(&self, id: i64, quotas: WorkspaceQuotas) -> Result<Workspace, AppError> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE workspaces
+            SET max_links = $2, max_domains = $3, monthly_click_allowance = $4
+            WHERE id = $1
+            RETURNING id, name, max_links, max_domains, monthly_click_allowance, created_at
+            "#,
+            id,
+            quotas.max_links,
+            quotas.max_domains,
+            quotas.monthly_click_allowance
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?
+        .ok_or_else(|| AppError::not_found("Workspace not found", json!({ "id": id })))?;
+
+        Ok(workspace(
+            row.id,
+            row.name,
+            row.max_links,
+            row.max_domains,
+            row.monthly_click_allowance,
+            row.created_at,
+        ))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.count_links", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn count_links(&self, id: i64) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM links WHERE workspace_id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.count_domains", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn count_domains(&self, id: i64) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM domains WHERE workspace_id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    #[tracing::instrument(name = "workspace_repository.count_clicks_since", skip_all, fields(db.system = "postgresql", id = id))]
+    async fn count_clicks_since(&self, id: i64, since: DateTime<Utc>) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM link_clicks lc
+            JOIN links l ON l.id = lc.link_id
+            WHERE l.workspace_id = $1
+              AND lc.clicked_at >= $2
+            "#,
+            id,
+            since
+        )
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+}
+
+/// Builds a workspace from its row.
+fn workspace(
+    id: i64,
+    name: String,
+    max_links: Option<i64>,
+    max_domains: Option<i64>,
+    monthly_click_allowance: Option<i64>,
+    created_at: DateTime<Utc>,
+) -> Workspace {
+    Workspace {
+        id,
+        name,
+        quotas: WorkspaceQuotas {
+            max_links,
+            max_domains,
+            monthly_click_allowance,
+        },
+        created_at,
+    }
+}
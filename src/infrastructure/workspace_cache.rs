@@ -0,0 +1,197 @@
+//! Short-lived cache of workspaces and their monthly clicks in front of the
+//! workspace repository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::domain::entities::{Workspace, WorkspaceQuotas};
+use crate::domain::repositories::WorkspaceRepository;
+use crate::error::AppError;
+use crate::infrastructure::persistence::PgWorkspaceRepository;
+
+/// Most workspaces kept in memory.
+const MAX_WORKSPACES: u64 = 10_000;
+
+/// Longest a workspace, and so its quotas, is served from memory.
+const WORKSPACE_TTL: Duration = Duration::from_secs(30);
+
+/// Longest a monthly click count is served from memory.
+const CLICK_COUNT_TTL: Duration = Duration::from_secs(10);
+
+/// A [`WorkspaceRepository`] answering workspace and click count lookups from memory.
+///
+/// Redirect cache misses check the workspace's monthly click allowance, which
+/// needs the workspace's quotas and a count over `link_clicks`. Both are kept
+/// for a few seconds, so a busy workspace costs at most one query of each per
+/// interval instead of two per redirect.
+///
+/// # Consistency
+///
+/// Changing quotas through the cache forgets the workspace at once. Quotas
+/// changed elsewhere, e.g. by `admin workspace set-quota`, apply within 30
+/// seconds. Click counts lag by up to 10 seconds, so a workspace may exceed its
+/// allowance by the redirects of that interval. All other repository methods go
+/// to the database.
+pub struct WorkspaceCache<R: WorkspaceRepository = PgWorkspaceRepository> {
+    inner: Arc<R>,
+    workspaces: Cache<i64, Workspace>,
+    /// Clicks by workspace ID and start of the counted period.
+    clicks: Cache<(i64, DateTime<Utc>), i64>,
+}
+
+impl<R: WorkspaceRepository> WorkspaceCache<R> {
+    /// Wraps `inner` with an empty cache.
+    pub fn new(inner: Arc<R>) -> Self {
+        Self {
+            inner,
+            workspaces: Cache::builder()
+                .max_capacity(MAX_WORKSPACES)
+                .time_to_live(WORKSPACE_TTL)
+                .build(),
+            clicks: Cache::builder()
+                .max_capacity(MAX_WORKSPACES)
+                .time_to_live(CLICK_COUNT_TTL)
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: WorkspaceRepository> WorkspaceRepository for WorkspaceCache<R> {
+    async fn create(&self, name: &str, quotas: WorkspaceQuotas) -> Result<Workspace, AppError> {
+        self.inner.create(name, quotas).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Workspace>, AppError> {
+        if let Some(workspace) = self.workspaces.get(&id) {
+            return Ok(Some(workspace));
+        }
+
+        let found = self.inner.find_by_id(id).await?;
+        if let Some(workspace) = &found {
+            self.workspaces.insert(id, workspace.clone());
+        }
+        Ok(found)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Workspace>, AppError> {
+        self.inner.find_by_name(name).await
+    }
+
+    async fn list(&self) -> Result<Vec<Workspace>, AppError> {
+        self.inner.list().await
+    }
+
+    async fn set_quotas(&self, id: i64, quotas: WorkspaceQuotas) -> Result<Workspace, AppError> {
+        let workspace = self.inner.set_quotas(id, quotas).await?;
+        self.workspaces.invalidate(&id);
+        Ok(workspace)
+    }
+
+    async fn count_links(&self, id: i64) -> Result<i64, AppError> {
+        self.inner.count_links(id).await
+    }
+
+    async fn count_domains(&self, id: i64) -> Result<i64, AppError> {
+        self.inner.count_domains(id).await
+    }
+
+    async fn count_clicks_since(&self, id: i64, since: DateTime<Utc>) -> Result<i64, AppError> {
+        if let Some(clicks) = self.clicks.get(&(id, since)) {
+            return Ok(clicks);
+        }
+
+        let clicks = self.inner.count_clicks_since(id, since).await?;
+        self.clicks.insert((id, since), clicks);
+        Ok(clicks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::month_start;
+    use crate::domain::repositories::MockWorkspaceRepository;
+
+    fn workspace(id: i64, monthly_click_allowance: Option<i64>) -> Workspace {
+        Workspace {
+            id,
+            name: "acme".to_string(),
+            quotas: WorkspaceQuotas {
+                monthly_click_allowance,
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workspace_is_served_from_memory() {
+        let mut repo = MockWorkspaceRepository::new();
+        repo.expect_find_by_id()
+            .times(1)
+            .returning(|id| Ok(Some(workspace(id, Some(10)))));
+        let cache = WorkspaceCache::new(Arc::new(repo));
+
+        cache.find_by_id(2).await.unwrap();
+        let found = cache.find_by_id(2).await.unwrap();
+
+        assert_eq!(found.unwrap().quotas.monthly_click_allowance, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_missing_workspace_is_not_remembered() {
+        let mut repo = MockWorkspaceRepository::new();
+        repo.expect_find_by_id().times(2).returning(|_| Ok(None));
+        let cache = WorkspaceCache::new(Arc::new(repo));
+
+        for _ in 0..2 {
+            assert!(cache.find_by_id(2).await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_quotas_forgets_workspace() {
+        let mut repo = MockWorkspaceRepository::new();
+        let mut loads = 0;
+        repo.expect_find_by_id().times(2).returning(move |id| {
+            loads += 1;
+            Ok(Some(workspace(id, Some(loads))))
+        });
+        repo.expect_set_quotas().times(1).returning(|id, quotas| {
+            Ok(Workspace {
+                quotas,
+                ..workspace(id, None)
+            })
+        });
+        let cache = WorkspaceCache::new(Arc::new(repo));
+        cache.find_by_id(2).await.unwrap();
+
+        cache
+            .set_quotas(2, WorkspaceQuotas::default())
+            .await
+            .unwrap();
+
+        let found = cache.find_by_id(2).await.unwrap();
+        assert_eq!(found.unwrap().quotas.monthly_click_allowance, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_click_count_is_served_from_memory_per_period() {
+        let since = month_start(Utc::now());
+        let earlier = since - chrono::Duration::days(40);
+        let mut repo = MockWorkspaceRepository::new();
+        repo.expect_count_clicks_since()
+            .times(2)
+            .returning(move |_, from| Ok(if from == since { 5 } else { 7 }));
+        let cache = WorkspaceCache::new(Arc::new(repo));
+
+        assert_eq!(cache.count_clicks_since(2, since).await.unwrap(), 5);
+        assert_eq!(cache.count_clicks_since(2, since).await.unwrap(), 5);
+        // Another period is counted separately.
+        assert_eq!(cache.count_clicks_since(2, earlier).await.unwrap(), 7);
+    }
+}
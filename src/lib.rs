@@ -50,6 +50,7 @@ pub mod domain;
 pub mod error;
 pub mod infrastructure;
 pub mod state;
+pub mod telemetry;
 pub mod utils;
 
 pub mod config;
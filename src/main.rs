@@ -1,10 +1,9 @@
 //! Binary entry point for the URL shortener service.
 //!
-//! Initializes logging, loads configuration, and starts the HTTP server.
+//! Loads configuration, initializes logging and tracing export, and starts the HTTP server.
 
 use anyhow::Result;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
-use url_shortener::{config, server};
+use url_shortener::{config, server, telemetry};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -12,29 +11,9 @@ async fn main() -> Result<()> {
         eprintln!("Failed to load .env: {} (using system environment)", e);
     }
 
-    let env_filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
-
     let cfg = config::load_from_env()?;
 
-    match cfg.log_format.as_str() {
-        "json" => {
-            tracing_subscriber::registry()
-                .with(env_filter)
-                .with(
-                    tracing_subscriber::fmt::layer()
-                        .json()
-                        .with_current_span(true)
-                        .with_span_list(false),
-                )
-                .init();
-        }
-        _ => {
-            tracing_subscriber::registry()
-                .with(env_filter)
-                .with(tracing_subscriber::fmt::layer())
-                .init();
-        }
-    }
+    let _telemetry = telemetry::init(&cfg)?;
 
     tracing::info!(
         listen = %cfg.listen_addr,
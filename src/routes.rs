@@ -4,25 +4,35 @@
 //!
 //! - `GET  /{code}`      - Short link redirect (public)
 //! - `GET  /health`      - Health check: DB, cache, click queue (public)
-//! - `/api/*`            - REST API (Bearer token required)
-//! - `/dashboard/*`      - Web UI (cookie session required)
+//! - `/api/v1/*`         - REST API version 1 (Bearer token required)
+//! - `GET  /api/v1/openapi.json`, `GET /api/v1/docs` - API documentation (public)
+//! - `/api/*`            - Deprecated alias of `/api/v1/*` (adds `Deprecation`/`Sunset` headers)
+//! - `POST /dashboard/login`, `POST /dashboard/logout` - Start / end a dashboard session
+//! - `GET  /dashboard/oidc/login`, `GET /dashboard/oidc/callback` - Single sign-on (if configured)
+//! - `/dashboard/*`      - Web UI (session cookie required)
 //! - `/static/*`         - Static assets
 //!
 //! # Middleware
 //!
 //! - **Tracing** - Structured request/response logging
+//! - **Deprecation** - `Deprecation`, `Sunset` and `Link` headers on the `/api` alias
 //! - **Rate limiting** - Per-IP token bucket (configurable for proxy deployments)
-//! - **Authentication** - Bearer token (API) or cookie session (web)
+//! - **Client IP** - Caller address recorded in the audit log, resolved like rate limiting
+//! - **Authentication** - Bearer token or session cookie + CSRF header (API), session cookie (web)
 //! - **Path normalization** - Trailing slash handling
 
 use crate::api;
 use crate::api::handlers::{health_handler, redirect_handler};
-use crate::api::middleware::{auth, rate_limit, tracing};
+use crate::api::middleware::deprecation::{self, DeprecationHeaders};
+use crate::api::middleware::rate_limit::SmartIpExtractor;
+use crate::api::middleware::{auth, client_ip, rate_limit, tracing};
+use crate::api::routes::{LEGACY_PREFIX, V1_PREFIX};
 use crate::state::AppState;
 use crate::web;
 use crate::web::middleware::web_auth;
 use axum::routing::get;
 use axum::{Router, middleware};
+use chrono::{DateTime, Utc};
 use tower::Layer;
 use tower_http::normalize_path::{NormalizePath, NormalizePathLayer};
 use tower_http::services::ServeDir;
@@ -35,10 +45,27 @@ use tower_http::services::ServeDir;
 /// - `behind_proxy` - when `true`, rate limiting reads client IP from
 ///   `X-Forwarded-For` / `X-Real-IP` headers instead of the peer socket address;
 ///   enable only when the service runs behind a trusted reverse proxy
-pub fn app_router(state: AppState, behind_proxy: bool) -> NormalizePath<Router> {
-    let api_router = api::routes::protected_routes()
+/// - `legacy_sunset` - date announced in the `Sunset` header of the deprecated
+///   `/api` alias
+pub fn app_router(
+    state: AppState,
+    behind_proxy: bool,
+    legacy_sunset: DateTime<Utc>,
+) -> NormalizePath<Router> {
+    let api_v1 = api::routes::v1::protected_routes()
         .route_layer(middleware::from_fn_with_state(state.clone(), auth::layer))
-        .layer(rate_limit::secure_layer(behind_proxy));
+        .layer(middleware::from_fn_with_state(
+            SmartIpExtractor { behind_proxy },
+            client_ip::layer,
+        ))
+        .layer(rate_limit::secure_layer(behind_proxy))
+        .merge(api::routes::v1::public_routes().layer(rate_limit::layer(behind_proxy)));
+
+    // The alias shares the v1 router (and its rate limiter buckets)
+    let api_legacy = api_v1.clone().layer(middleware::from_fn_with_state(
+        DeprecationHeaders::new(legacy_sunset),
+        deprecation::layer,
+    ));
 
     let web_protected = web::routes::protected_routes()
         .route_layer(middleware::from_fn_with_state(
@@ -49,12 +76,18 @@ pub fn app_router(state: AppState, behind_proxy: bool) -> NormalizePath<Router>
 
     let web_public = web::routes::public_routes().layer(rate_limit::layer(behind_proxy));
 
-    let web_router = Router::new().merge(web_protected).merge(web_public);
+    let web_session = web::routes::session_routes().layer(rate_limit::secure_layer(behind_proxy));
+
+    let web_router = Router::new()
+        .merge(web_protected)
+        .merge(web_public)
+        .merge(web_session);
 
     let router = Router::new()
         .route("/{code}", get(redirect_handler))
         .route("/health", get(health_handler))
-        .nest("/api", api_router)
+        .nest(V1_PREFIX, api_v1)
+        .nest(LEGACY_PREFIX, api_legacy)
         .nest("/dashboard", web_router)
         .nest_service("/static", ServeDir::new("static"))
         .with_state(state)
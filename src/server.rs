@@ -2,16 +2,24 @@
 //!
 //! Handles database connections, cache setup, worker spawning, and Axum server lifecycle.
 
+use crate::application::services::{IdempotencyService, SessionService};
 use crate::config::Config;
-use crate::domain::click_worker::run_click_worker;
-use crate::infrastructure::cache::{CacheService, NullCache, RedisCache};
+use crate::domain::click_counters::{UnflushedClicks, run_counter_flush};
+use crate::domain::click_journal::ClickJournal;
+use crate::domain::click_worker::{ClickWorkerConfig, run_click_worker, run_journal_replay};
+use crate::infrastructure::cache::{
+    CacheService, InvalidationBus, NullCache, RedirectCachePolicy, RedisCache, TieredCache,
+};
+use crate::infrastructure::domain_registry::DomainRegistry;
 use crate::infrastructure::persistence::{
-    PgDomainRepository, PgLinkRepository, PgStatsRepository, PgTokenRepository,
+    PgAuditRepository, PgDomainRepository, PgIdempotencyRepository, PgLinkRepository,
+    PgLinkVersionRepository, PgSessionRepository, PgStatsRepository, PgTokenRepository,
+    PgUserRepository, PgWorkspaceRepository,
 };
 use crate::routes::app_router;
 use crate::state::AppState;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::ServiceExt;
 use axum::extract::Request;
 use sqlx::postgres::PgPoolOptions;
@@ -24,16 +32,24 @@ use tokio::sync::mpsc;
 ///
 /// Initializes:
 /// - PostgreSQL connection pool and runs pending migrations
-/// - Redis cache (or [`NullCache`] fallback if Redis is unavailable or unconfigured)
+/// - Redis cache, behind an in-process [`TieredCache`] unless `L1_CACHE_CAPACITY` is 0
+///   (or [`NullCache`] fallback if Redis is unavailable or unconfigured), with an
+///   [`InvalidationBus`] listener keeping the in-process tier consistent across instances
 /// - Shared repositories passed to both the click worker and [`AppState`]
 /// - Background click worker for asynchronous click persistence
+/// - Click journal replay when `CLICK_JOURNAL_PATH` is set
+/// - Periodic flush of recorded clicks to the `links.clicks` counters
+/// - Cache warm-up with the most clicked links when `CACHE_WARMUP_LINKS` is set
+/// - Hourly purge of expired idempotency keys and dashboard sessions
 /// - Axum HTTP server with graceful shutdown on `SIGTERM` / `Ctrl-C`
 ///
 /// # Shutdown
 ///
 /// On shutdown signal the HTTP server stops accepting new connections and waits
 /// for in-flight requests to complete. Afterwards the click worker drains the
-/// remaining events from its channel before exiting.
+/// remaining events from its channel before exiting; with a click journal, batches
+/// the database can't take are spilled to it and replayed on the next start.
+/// The click counters are flushed one last time once the worker has stopped.
 ///
 /// # Errors
 ///
@@ -55,6 +71,28 @@ pub async fn run(config: Config) -> Result<()> {
 
     let cache: Arc<dyn CacheService> = if let Some(redis_url) = &config.redis_url {
         match RedisCache::connect(redis_url, config.cache_ttl_seconds).await {
+            Ok(redis) if config.l1_cache_capacity > 0 => {
+                tracing::info!("Cache enabled (in-process L1 + Redis)");
+                let mut tiered =
+                    TieredCache::new(redis, config.l1_cache_capacity, config.l1_cache_ttl_seconds);
+                let bus = InvalidationBus::connect(redis_url).await;
+                if let Ok(bus) = &bus {
+                    tiered = tiered.with_invalidation_bus(bus.clone());
+                }
+                let tiered = Arc::new(tiered);
+
+                match bus {
+                    Ok(bus) => {
+                        tokio::spawn(bus.listen(tiered.clone()));
+                    }
+                    Err(e) => tracing::warn!(
+                        "Failed to connect cache invalidation bus: {}. \
+                         Other instances will not see this instance's invalidations.",
+                        e
+                    ),
+                }
+                tiered
+            }
             Ok(redis) => {
                 tracing::info!("Cache enabled (Redis)");
                 Arc::new(redis)
@@ -76,28 +114,108 @@ pub async fn run(config: Config) -> Result<()> {
     let link_repo = Arc::new(PgLinkRepository::new(pool_arc.clone()));
     let stats_repo = Arc::new(PgStatsRepository::new(pool_arc.clone()));
     let token_repo = Arc::new(PgTokenRepository::new(pool_arc.clone()));
-    let domain_repo = Arc::new(PgDomainRepository::new(pool_arc.clone()));
+    let domain_repo = Arc::new(DomainRegistry::new(Arc::new(PgDomainRepository::new(
+        pool_arc.clone(),
+    ))));
+    match domain_repo.reload().await {
+        Ok(count) => tracing::info!("Loaded {} domains", count),
+        Err(e) => tracing::warn!("Failed to load domains: {}", e),
+    }
+    let idempotency_repo = Arc::new(PgIdempotencyRepository::new(pool_arc.clone()));
+    let session_repo = Arc::new(PgSessionRepository::new(pool_arc.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(pool_arc.clone()));
+    let workspace_repo = Arc::new(PgWorkspaceRepository::new(pool_arc.clone()));
+    let audit_repo = Arc::new(PgAuditRepository::new(pool_arc.clone()));
+    let link_version_repo = Arc::new(PgLinkVersionRepository::new(pool_arc.clone()));
+
+    let click_journal = match &config.click_journal_path {
+        Some(path) => Some(Arc::new(
+            ClickJournal::open(path, config.click_journal_max_mb * 1024 * 1024)
+                .with_context(|| format!("Failed to open click journal {path}"))?,
+        )),
+        None => None,
+    };
+
+    let unflushed_clicks = Arc::new(UnflushedClicks::new());
+    tokio::spawn(run_counter_flush(
+        unflushed_clicks.clone(),
+        stats_repo.clone(),
+        Duration::from_secs(config.click_counter_flush_seconds),
+    ));
+
+    let mut worker_config = ClickWorkerConfig::new(config.click_worker_concurrency)
+        .with_batching(
+            config.click_batch_size,
+            Duration::from_millis(config.click_batch_interval_ms),
+        )
+        .with_unflushed_clicks(unflushed_clicks.clone());
+    if let Some(journal) = &click_journal {
+        worker_config = worker_config.with_journal(
+            journal.clone(),
+            Duration::from_secs(config.click_journal_replay_seconds),
+        );
+        tokio::spawn(run_journal_replay(
+            click_tx.downgrade(),
+            stats_repo.clone(),
+            domain_repo.clone(),
+            link_repo.clone(),
+            worker_config.clone(),
+        ));
+    }
 
     let worker_handle = tokio::spawn(run_click_worker(
         click_rx,
         stats_repo.clone(),
         domain_repo.clone(),
         link_repo.clone(),
-        config.click_worker_concurrency,
+        worker_config,
     ));
     tracing::info!("Click worker started");
 
+    let domain_registry = domain_repo.clone();
+    let counter_repo = stats_repo.clone();
+    let journal = click_journal.clone();
     let state = AppState::new(
         link_repo,
         stats_repo,
         token_repo,
         domain_repo,
+        idempotency_repo,
+        session_repo,
+        user_repo,
+        workspace_repo,
+        audit_repo,
+        link_version_repo,
         click_tx,
         cache,
-        config.token_signing_secret.clone(),
-    );
+        RedirectCachePolicy::from(&config),
+        config.token_signing_secrets.clone(),
+        chrono::Duration::hours(config.session_ttl_hours as i64),
+        config.oidc.as_ref(),
+    )
+    .with_click_journal(click_journal);
 
-    let app = app_router(state, config.behind_proxy);
+    if config.redis_url.is_some() && config.cache_warmup_links > 0 {
+        let service = state.cache_admin_service.clone();
+        let limit = config.cache_warmup_links as i64;
+        let since = chrono::Utc::now() - chrono::Duration::days(config.cache_warmup_days as i64);
+        tokio::spawn(async move {
+            if let Err(e) = service.warm_up(limit, since).await {
+                tracing::warn!("Failed to warm up cache: {}", e);
+            }
+        });
+    }
+
+    tokio::spawn(purge_idempotency_keys(state.idempotency_service.clone()));
+    tokio::spawn(purge_sessions(state.session_service.clone()));
+    if config.domain_refresh_seconds > 0 {
+        tokio::spawn(refresh_domains(
+            domain_registry,
+            Duration::from_secs(config.domain_refresh_seconds),
+        ));
+    }
+
+    let app = app_router(state, config.behind_proxy, config.api_legacy_sunset);
 
     let addr: SocketAddr = config.listen_addr.parse()?;
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -114,11 +232,56 @@ pub async fn run(config: Config) -> Result<()> {
     // The worker's channel will drain and then close naturally.
     tracing::info!("HTTP server stopped, draining click queue...");
     worker_handle.await.ok();
+    if let Some(journal) = journal {
+        journal.flush().await;
+    }
+    if let Err(e) = unflushed_clicks.flush(counter_repo.as_ref()).await {
+        tracing::warn!("Failed to flush click counters: {}", e);
+    }
     tracing::info!("Click worker stopped, shutdown complete");
 
     Ok(())
 }
 
+/// Deletes expired idempotency keys once an hour.
+async fn purge_idempotency_keys(service: Arc<IdempotencyService<PgIdempotencyRepository>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    loop {
+        interval.tick().await;
+        match service.purge_expired().await {
+            Ok(0) => {}
+            Ok(removed) => tracing::debug!(removed, "Purged expired idempotency keys"),
+            Err(e) => tracing::warn!("Failed to purge idempotency keys: {}", e),
+        }
+    }
+}
+
+/// Deletes expired dashboard sessions once an hour.
+async fn purge_sessions(service: Arc<SessionService<PgSessionRepository>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    loop {
+        interval.tick().await;
+        match service.purge_expired().await {
+            Ok(0) => {}
+            Ok(removed) => tracing::debug!(removed, "Purged expired dashboard sessions"),
+            Err(e) => tracing::warn!("Failed to purge dashboard sessions: {}", e),
+        }
+    }
+}
+
+/// Reloads the domain registry, picking up changes made by other instances.
+async fn refresh_domains(registry: Arc<DomainRegistry>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    // The first tick completes at once; domains were just loaded at startup.
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(e) = registry.reload().await {
+            tracing::warn!("Failed to reload domains: {}", e);
+        }
+    }
+}
+
 /// Resolves on Ctrl-C (all platforms) or SIGTERM (Unix).
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -7,12 +7,24 @@
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
-use crate::application::services::{AuthService, DomainService, LinkService, StatsService};
+use crate::application::services::{
+    AuditService, AuthService, CacheAdminService, DomainService, IdempotencyService,
+    LinkHistoryService, LinkService, SessionService, SsoPolicy, SsoService, StatsService,
+};
+use crate::config::OidcConfig;
 use crate::domain::click_event::ClickEvent;
-use crate::infrastructure::cache::CacheService;
+use crate::domain::click_journal::ClickJournal;
+use crate::error::AppError;
+use crate::infrastructure::cache::{CacheService, CachedRedirect, RedirectCachePolicy};
+use crate::infrastructure::domain_registry::DomainRegistry;
+use crate::infrastructure::oidc::OidcClient;
 use crate::infrastructure::persistence::{
-    PgDomainRepository, PgLinkRepository, PgStatsRepository, PgTokenRepository,
+    PgAuditRepository, PgIdempotencyRepository, PgLinkRepository, PgLinkVersionRepository,
+    PgSessionRepository, PgStatsRepository, PgTokenRepository, PgUserRepository,
+    PgWorkspaceRepository,
 };
+use crate::infrastructure::workspace_cache::WorkspaceCache;
+use crate::utils::single_flight::SingleFlight;
 
 /// Shared application state injected into HTTP handlers.
 ///
@@ -20,14 +32,27 @@ use crate::infrastructure::persistence::{
 /// Cheap to clone due to `Arc` wrapping.
 #[derive(Clone)]
 pub struct AppState {
-    pub link_service: Arc<LinkService<PgLinkRepository, PgDomainRepository>>,
+    pub link_service: Arc<LinkService<PgLinkRepository, DomainRegistry, WorkspaceCache>>,
     pub stats_service: Arc<StatsService<PgStatsRepository>>,
     pub auth_service: Arc<AuthService<PgTokenRepository>>,
-    pub domain_service: Arc<DomainService<PgDomainRepository>>,
+    pub domain_service: Arc<DomainService<DomainRegistry, PgWorkspaceRepository>>,
+    pub idempotency_service: Arc<IdempotencyService<PgIdempotencyRepository>>,
+    pub session_service: Arc<SessionService<PgSessionRepository>>,
+    pub audit_service: Arc<AuditService<PgAuditRepository>>,
+    pub link_history_service: Arc<LinkHistoryService<PgLinkVersionRepository>>,
+    pub cache_admin_service: Arc<CacheAdminService<PgStatsRepository>>,
+    /// Dashboard single sign-on; `None` unless OpenID Connect is configured.
+    pub sso_service: Option<Arc<SsoService<OidcClient, PgUserRepository>>>,
 
     pub cache: Arc<dyn CacheService>,
+    /// How redirect answers are cached.
+    pub redirect_cache: RedirectCachePolicy,
+    /// Database lookups of cache misses in flight, one per `domain:code` key.
+    pub redirect_lookups: Arc<SingleFlight<Result<CachedRedirect, AppError>>>,
 
     pub click_sender: mpsc::Sender<ClickEvent>,
+    /// Journal clicks spill into when the queue is full; `None` drops them.
+    pub click_journal: Option<Arc<ClickJournal>>,
 }
 
 impl AppState {
@@ -38,31 +63,82 @@ impl AppState {
     ///
     /// # Arguments
     ///
-    /// - `link_repo` / `stats_repo` / `token_repo` / `domain_repo` - pre-built repositories
+    /// - `link_repo` / `stats_repo` / `token_repo` / `domain_repo` / `idempotency_repo` / `session_repo` / `user_repo` / `workspace_repo` / `audit_repo` / `link_version_repo` - pre-built repositories
+    /// - `domain_repo` is the [`DomainRegistry`] also used by the click worker, so both share its domains
+    /// - `workspace_repo` is wrapped in a [`WorkspaceCache`] for the link service, whose redirect checks read quotas and click counts
     /// - `click_sender` - channel sender for asynchronous click event processing
     /// - `cache` - cache implementation ([`RedisCache`](crate::infrastructure::cache::RedisCache) or [`NullCache`](crate::infrastructure::cache::NullCache))
-    /// - `token_signing_secret` - HMAC key for token hashing; must match `TOKEN_SIGNING_SECRET`
+    /// - `redirect_cache` - negative caching, refresh-ahead and lookup lock settings
+    /// - `token_signing_secrets` - HMAC keys for token hashing and session cookies, newest first; from `TOKEN_SIGNING_SECRET`
+    /// - `session_ttl` - lifetime of a dashboard login session
+    /// - `oidc` - single sign-on settings; `None` disables single sign-on
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         link_repo: Arc<PgLinkRepository>,
         stats_repo: Arc<PgStatsRepository>,
         token_repo: Arc<PgTokenRepository>,
-        domain_repo: Arc<PgDomainRepository>,
+        domain_repo: Arc<DomainRegistry>,
+        idempotency_repo: Arc<PgIdempotencyRepository>,
+        session_repo: Arc<PgSessionRepository>,
+        user_repo: Arc<PgUserRepository>,
+        workspace_repo: Arc<PgWorkspaceRepository>,
+        audit_repo: Arc<PgAuditRepository>,
+        link_version_repo: Arc<PgLinkVersionRepository>,
         click_sender: mpsc::Sender<ClickEvent>,
         cache: Arc<dyn CacheService>,
-        token_signing_secret: String,
+        redirect_cache: RedirectCachePolicy,
+        token_signing_secrets: Vec<String>,
+        session_ttl: chrono::Duration,
+        oidc: Option<&OidcConfig>,
     ) -> Self {
-        let link_service = Arc::new(LinkService::new(link_repo, domain_repo.clone()));
+        let link_service = Arc::new(LinkService::new(
+            link_repo,
+            domain_repo.clone(),
+            Arc::new(WorkspaceCache::new(workspace_repo.clone())),
+        ));
+        let cache_admin_service =
+            Arc::new(CacheAdminService::new(cache.clone(), stats_repo.clone()));
         let stats_service = Arc::new(StatsService::new(stats_repo));
-        let auth_service = Arc::new(AuthService::new(token_repo, token_signing_secret));
-        let domain_service = Arc::new(DomainService::new(domain_repo));
+        let session_service = Arc::new(SessionService::new(
+            session_repo,
+            token_signing_secrets.clone(),
+            session_ttl,
+        ));
+        let auth_service = Arc::new(AuthService::new(token_repo, token_signing_secrets));
+        let domain_service = Arc::new(DomainService::new(domain_repo, workspace_repo));
+        let sso_service = oidc.map(|config| {
+            Arc::new(SsoService::new(
+                Arc::new(OidcClient::new(config)),
+                user_repo.clone(),
+                SsoPolicy::from(config),
+            ))
+        });
+        let idempotency_service = Arc::new(IdempotencyService::new(idempotency_repo));
+        let audit_service = Arc::new(AuditService::new(audit_repo));
+        let link_history_service = Arc::new(LinkHistoryService::new(link_version_repo));
 
         Self {
             link_service,
             stats_service,
             auth_service,
             domain_service,
+            idempotency_service,
+            session_service,
+            audit_service,
+            link_history_service,
+            cache_admin_service,
+            sso_service,
             cache,
+            redirect_cache,
+            redirect_lookups: Arc::new(SingleFlight::new("redirect")),
             click_sender,
+            click_journal: None,
         }
     }
+
+    /// Spills clicks that don't fit in the queue to `journal`.
+    pub fn with_click_journal(mut self, journal: Option<Arc<ClickJournal>>) -> Self {
+        self.click_journal = journal;
+        self
+    }
 }
@@ -0,0 +1,257 @@
+//! Logging and distributed tracing setup.
+//!
+//! Installs the global `tracing` subscriber with:
+//! - a `fmt` layer (`text` or `json`, see `LOG_FORMAT`)
+//! - an optional OpenTelemetry layer exporting spans over OTLP/HTTP when
+//!   `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+//!
+//! # Context Propagation
+//!
+//! The W3C Trace Context propagator (`traceparent` / `tracestate` headers) is
+//! registered globally. Incoming requests continue the caller's trace (see
+//! [`crate::api::middleware::tracing`]), and click events carry the request's
+//! `traceparent` into the background worker so a redirect can be followed end to
+//! end: HTTP span → cache/repository spans, and the click batch span that
+//! recorded the click links back to the request.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use axum::http::HeaderMap;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{TraceContextExt as _, TracerProvider as _};
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config::Config;
+
+/// Name of the W3C Trace Context header carrying the parent span.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Keeps the OTLP tracer provider alive and flushes pending spans on drop.
+///
+/// Hold this value for the whole lifetime of the process (e.g. in `main`).
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to shut down OpenTelemetry tracer provider: {e}");
+        }
+    }
+}
+
+/// Installs the global `tracing` subscriber according to the configuration.
+///
+/// When [`Config::otlp_endpoint`] is set, spans are additionally exported to an
+/// OTLP collector via [`init_tracer_provider`]. The W3C Trace Context
+/// propagator is registered either way, so `traceparent` headers are still
+/// honoured and forwarded when this instance exports nothing itself.
+///
+/// # Errors
+///
+/// Returns an error if `RUST_LOG` is invalid or the OTLP exporter cannot be built.
+pub fn init(config: &Config) -> Result<TelemetryGuard> {
+    let env_filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
+
+    install_propagator();
+
+    let provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| init_tracer_provider(endpoint, &config.otel_service_name))
+        .transpose()?;
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+    });
+
+    let fmt_layer = match config.log_format.as_str() {
+        "json" => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+        _ => tracing_subscriber::fmt::layer().boxed(),
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    Ok(TelemetryGuard { provider })
+}
+
+/// Builds a tracer provider that batches spans and exports them over OTLP/HTTP
+/// (protobuf) to `{endpoint}/v1/traces`.
+///
+/// The W3C Trace Context propagator is registered by [`init`], not here.
+///
+/// # Arguments
+///
+/// - `endpoint` - collector base URL, e.g. `http://localhost:4318`
+/// - `service_name` - reported as the `service.name` resource attribute
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be constructed (e.g. invalid URL).
+pub fn init_tracer_provider(endpoint: &str, service_name: &str) -> Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpBinary)
+        .with_endpoint(traces_url(endpoint))
+        .build()
+        .context("Failed to build OTLP span exporter")?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(service_name.to_string())
+                .build(),
+        )
+        .build();
+
+    Ok(provider)
+}
+
+/// Registers the W3C Trace Context propagator as the global text map propagator.
+pub fn install_propagator() {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+}
+
+/// Appends the OTLP traces path unless the endpoint already points at it.
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{endpoint}/v1/traces")
+    }
+}
+
+/// Sets the parent of `span` from the `traceparent` / `tracestate` request headers.
+///
+/// Does nothing when the headers are absent or malformed.
+pub fn set_parent_from_headers(span: &tracing::Span, headers: &HeaderMap) {
+    let cx = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    });
+    let _ = span.set_parent(cx);
+}
+
+/// Returns the `traceparent` value of the current span, if it is being traced.
+///
+/// Used to carry trace context across the click queue into the background worker.
+pub fn current_traceparent() -> Option<String> {
+    let cx = tracing::Span::current().context();
+    let mut carrier = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut MapInjector(&mut carrier))
+    });
+    carrier.remove(TRACEPARENT_HEADER)
+}
+
+/// Sets the parent of `span` from a previously captured `traceparent` value.
+pub fn set_parent_from_traceparent(span: &tracing::Span, traceparent: &str) {
+    let _ = span.set_parent(extract_traceparent(traceparent));
+}
+
+/// Links `span` to the span of a previously captured `traceparent` value.
+///
+/// Used where one span handles work of several traces, such as a batch of
+/// click events, so none of them becomes its parent.
+pub fn add_link_from_traceparent(span: &tracing::Span, traceparent: &str) {
+    let span_context = extract_traceparent(traceparent)
+        .span()
+        .span_context()
+        .clone();
+    if span_context.is_valid() {
+        span.add_link(span_context);
+    }
+}
+
+/// Reads the context of a `traceparent` value with the global propagator.
+fn extract_traceparent(traceparent: &str) -> opentelemetry::Context {
+    let mut carrier = HashMap::new();
+    carrier.insert(TRACEPARENT_HEADER.to_string(), traceparent.to_string());
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MapExtractor(&carrier))
+    })
+}
+
+/// Reads propagation fields from HTTP request headers.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// Reads propagation fields from an in-memory map.
+struct MapExtractor<'a>(&'a HashMap<String, String>);
+
+impl Extractor for MapExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|v| v.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// Writes propagation fields into an in-memory map.
+struct MapInjector<'a>(&'a mut HashMap<String, String>);
+
+impl Injector for MapInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traces_url_appends_path() {
+        assert_eq!(
+            traces_url("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_traces_url_keeps_explicit_path() {
+        assert_eq!(
+            traces_url("http://collector:4318/v1/traces"),
+            "http://collector:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_current_traceparent_without_active_trace() {
+        install_propagator();
+        assert!(current_traceparent().is_none());
+    }
+}
@@ -0,0 +1,71 @@
+//! Cookie header parsing and `Set-Cookie` value construction.
+
+use axum::http::{HeaderMap, header::COOKIE};
+
+/// Returns the value of the cookie `name` from the request's `Cookie` headers.
+///
+/// Handles multiple cookies per header (`a=1; b=2`) and multiple headers.
+pub fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|cookie| {
+            let (key, value) = cookie.trim().split_once('=')?;
+            (key == name).then(|| value.to_string())
+        })
+}
+
+/// Builds a site-wide `Set-Cookie` value that is only sent over HTTPS and never
+/// on cross-site requests.
+///
+/// `http_only` hides the cookie from scripts. A `max_age_seconds` of `0`
+/// deletes the cookie.
+pub fn set_cookie(name: &str, value: &str, max_age_seconds: i64, http_only: bool) -> String {
+    let mut cookie =
+        format!("{name}={value}; Path=/; Max-Age={max_age_seconds}; Secure; SameSite=Strict");
+    if http_only {
+        cookie.push_str("; HttpOnly");
+    }
+    cookie
+}
+
+/// Builds an HttpOnly, HTTPS-only `Set-Cookie` value that is also sent on
+/// top-level navigations from other sites.
+///
+/// For cookies that must survive the redirect back from an identity provider,
+/// which `SameSite=Strict` cookies do not.
+pub fn set_lax_cookie(name: &str, value: &str, max_age_seconds: i64) -> String {
+    format!("{name}={value}; Path=/; Max-Age={max_age_seconds}; Secure; SameSite=Lax; HttpOnly")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_read_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("a=1; session=abc.def"));
+        headers.append(COOKIE, HeaderValue::from_static("csrf_token=xyz"));
+
+        assert_eq!(read_cookie(&headers, "session").as_deref(), Some("abc.def"));
+        assert_eq!(read_cookie(&headers, "csrf_token").as_deref(), Some("xyz"));
+        assert_eq!(read_cookie(&headers, "missing"), None);
+    }
+
+    #[test]
+    fn test_set_cookie_flags() {
+        assert_eq!(
+            set_cookie("session", "v", 60, true),
+            "session=v; Path=/; Max-Age=60; Secure; SameSite=Strict; HttpOnly"
+        );
+        assert!(!set_cookie("csrf_token", "v", 60, false).contains("HttpOnly"));
+        assert_eq!(
+            set_lax_cookie("oidc_login", "v", 60),
+            "oidc_login=v; Path=/; Max-Age=60; Secure; SameSite=Lax; HttpOnly"
+        );
+    }
+}
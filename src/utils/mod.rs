@@ -3,9 +3,13 @@
 //! This module provides helper functions used across the application:
 //!
 //! - [`code_generator`] - Short code generation and validation
+//! - [`cookies`] - Cookie parsing and `Set-Cookie` values
 //! - [`url_normalizer`] - URL normalization and sanitization
 //! - [`extract_domain`] - Domain extraction from HTTP headers
+//! - [`single_flight`] - Coalescing of concurrent calls for the same key
 
 pub mod code_generator;
+pub mod cookies;
 pub mod extract_domain;
+pub mod single_flight;
 pub mod url_normalizer;
@@ -0,0 +1,148 @@
+//! Coalescing of concurrent calls for the same key.
+
+use futures_util::FutureExt;
+use futures_util::future::{BoxFuture, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+type Call<T> = Shared<BoxFuture<'static, T>>;
+
+/// Runs at most one call per key at a time; concurrent callers for the same
+/// key wait for that call and share its result.
+///
+/// A key is forgotten as soon as its call finishes, so results are never
+/// reused by later callers; caching them is up to the work itself.
+///
+/// # Metrics
+///
+/// Each call increments `single_flight_calls_total{flight, role}`, with `role`
+/// `leader` for the caller whose work runs and `follower` for callers that
+/// joined it.
+pub struct SingleFlight<T> {
+    name: &'static str,
+    calls: Mutex<HashMap<String, Call<T>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    /// Creates an empty flight map; `name` labels its metrics.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the result of `work`, or of the call already running for `key`.
+    ///
+    /// `work` is dropped without running if another call for `key` is in flight.
+    /// Followers keep driving the call if the leader is cancelled.
+    pub async fn run<F>(&self, key: &str, work: F) -> T
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let (call, leader) = {
+            let mut calls = self.calls.lock().expect("single flight lock poisoned");
+            match calls.get(key) {
+                Some(call) => (call.clone(), false),
+                None => {
+                    let call = work.boxed().shared();
+                    calls.insert(key.to_string(), call.clone());
+                    (call, true)
+                }
+            }
+        };
+
+        let role = if leader { "leader" } else { "follower" };
+        metrics::counter!("single_flight_calls_total", "flight" => self.name, "role" => role)
+            .increment(1);
+
+        // Removes the key even if the leader is cancelled mid-call.
+        let _forget = leader.then(|| Forget {
+            calls: &self.calls,
+            key,
+        });
+        call.await
+    }
+
+    /// Returns `true` if a call for `key` is running.
+    pub fn in_flight(&self, key: &str) -> bool {
+        self.calls
+            .lock()
+            .expect("single flight lock poisoned")
+            .contains_key(key)
+    }
+}
+
+/// Removes a finished or cancelled leader's call from the map.
+struct Forget<'a, T> {
+    calls: &'a Mutex<HashMap<String, Call<T>>>,
+    key: &'a str,
+}
+
+impl<T> Drop for Forget<'_, T> {
+    fn drop(&mut self) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.remove(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_run() {
+        let flight = Arc::new(SingleFlight::new("test"));
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let flight = flight.clone();
+                let runs = runs.clone();
+                tokio::spawn(async move {
+                    flight
+                        .run("key", async move {
+                            runs.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            42
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), 42);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(!flight.in_flight("key"));
+    }
+
+    #[tokio::test]
+    async fn test_finished_calls_are_forgotten() {
+        let flight = SingleFlight::new("test");
+
+        assert_eq!(flight.run("key", async { 1 }).await, 1);
+        assert_eq!(flight.run("key", async { 2 }).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_releases_key() {
+        let flight = SingleFlight::new("test");
+
+        let pending = flight.run("key", std::future::pending::<i32>());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), pending)
+                .await
+                .is_err()
+        );
+
+        assert!(!flight.in_flight("key"));
+        assert_eq!(flight.run("key", async { 3 }).await, 3);
+    }
+}
@@ -0,0 +1,22 @@
+//! Audit log page handler.
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::response::IntoResponse;
+
+/// Template for the audit log page.
+///
+/// Renders `templates/audit.html` with filters and a paginated list of changes.
+/// Data is fetched client-side via Alpine.js from `/api/v1/audit`.
+#[derive(Template, WebTemplate)]
+#[template(path = "audit.html")]
+pub struct AuditTemplate {}
+
+/// Renders the audit log page.
+///
+/// # Endpoint
+///
+/// `GET /audit`
+pub async fn audit_handler() -> impl IntoResponse {
+    AuditTemplate {}
+}
@@ -7,7 +7,7 @@ use axum::response::IntoResponse;
 /// Template for the domain management page.
 ///
 /// Renders `templates/domains.html` with a full CRUD interface for domains.
-/// Data is fetched client-side via Alpine.js from `/api/domains`.
+/// Data is fetched client-side via Alpine.js from `/api/v1/domains`.
 #[derive(Template, WebTemplate)]
 #[template(path = "domains.html")]
 pub struct DomainsTemplate {}
@@ -23,7 +23,7 @@ pub struct LinksTemplate {}
 /// # Template
 ///
 /// Uses `templates/links.html` for server-side rendering.
-/// The template fetches data via JavaScript from `/api/stats`.
+/// The template fetches data via JavaScript from `/api/v1/stats`.
 pub async fn links_handler() -> impl IntoResponse {
     LinksTemplate {}
 }
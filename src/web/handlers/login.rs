@@ -1,17 +1,48 @@
-//! Login page handler.
+//! Login page, login and logout handlers.
 
 use askama::Template;
 use askama_web::WebTemplate;
-use axum::response::IntoResponse;
+use axum::{
+    Json,
+    extract::State,
+    http::{
+        HeaderMap, StatusCode,
+        header::{CACHE_CONTROL, SET_COOKIE},
+    },
+    response::{AppendHeaders, IntoResponse},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    application::services::session_service::{
+        CSRF_COOKIE, CSRF_HEADER, SESSION_COOKIE, verify_csrf,
+    },
+    domain::repositories::SessionOwner,
+    error::AppError,
+    state::AppState,
+    utils::cookies::{read_cookie, set_cookie},
+};
 
 /// Template for the login page.
 ///
 /// Renders `templates/login.html` with:
 /// - Token input form
+/// - Single sign-on button, if configured
 /// - Authentication instructions
 #[derive(Template, WebTemplate)]
 #[template(path = "login.html")]
-struct LoginTemplate {}
+pub struct LoginTemplate {
+    pub sso_enabled: bool,
+    /// Why the last single sign-on attempt failed.
+    pub error: Option<String>,
+}
+
+/// Body of `POST /login`.
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub token: String,
+}
 
 /// Renders the login page.
 ///
@@ -21,12 +52,101 @@ struct LoginTemplate {}
 ///
 /// # Authentication
 ///
-/// Users enter their API token which is stored in a cookie or session
-/// for subsequent requests to protected dashboard routes.
+/// The form posts the entered API token to `POST /login`, which exchanges it
+/// for a session cookie; the token itself is never stored in the browser.
+/// With single sign-on configured, the page also links to `GET /oidc/login`.
 ///
 /// # Template
 ///
 /// Uses `templates/login.html` for server-side rendering.
-pub async fn login_handler() -> impl IntoResponse {
-    LoginTemplate {}
+pub async fn login_handler(State(st): State<AppState>) -> impl IntoResponse {
+    LoginTemplate {
+        sso_enabled: st.sso_service.is_some(),
+        error: None,
+    }
+}
+
+/// Exchanges an API token for a dashboard session.
+///
+/// # Endpoint
+///
+/// `POST /login` with a JSON body `{"token": "..."}`
+///
+/// # Response
+///
+/// `204 No Content` with two cookies, both `Secure`, `SameSite=Strict` and
+/// expiring with the session:
+/// - `session` - signed session ID, `HttpOnly`
+/// - `csrf_token` - readable by the dashboard scripts, which echo it in the
+///   `X-CSRF-Token` header of state-changing requests
+///
+/// The session has the permissions of the token and ends when the token is
+/// revoked or expires.
+///
+/// # Errors
+///
+/// Returns `401 Unauthorized` if the token is invalid, revoked or expired.
+pub async fn login_submit_handler(
+    State(st): State<AppState>,
+    Json(req): Json<LoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let principal = st.auth_service.authenticate(req.token.trim()).await?;
+    let id = principal.token_id().ok_or_else(|| {
+        AppError::internal(
+            "Authenticated token has no token actor",
+            json!({ "actor": principal.actor_key() }),
+        )
+    })?;
+    let session = st.session_service.start(SessionOwner::Token(id)).await?;
+
+    let max_age = st.session_service.ttl().num_seconds();
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        AppendHeaders([
+            (
+                SET_COOKIE,
+                set_cookie(SESSION_COOKIE, &session.cookie, max_age, true),
+            ),
+            (
+                SET_COOKIE,
+                set_cookie(CSRF_COOKIE, &session.csrf_token, max_age, false),
+            ),
+            (CACHE_CONTROL, "no-store".to_string()),
+        ]),
+    ))
+}
+
+/// Ends the dashboard session and clears its cookies.
+///
+/// # Endpoint
+///
+/// `POST /logout`
+///
+/// # Errors
+///
+/// Returns `403 Forbidden` if the session is valid but the `X-CSRF-Token`
+/// header does not match it. Requests without a valid session just clear the
+/// cookies.
+pub async fn logout_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(cookie) = read_cookie(&headers, SESSION_COOKIE)
+        && let Ok(session) = st.session_service.authenticate(&cookie).await
+    {
+        let provided = headers
+            .get(CSRF_HEADER)
+            .and_then(|value| value.to_str().ok());
+        verify_csrf(&session, provided)?;
+        st.session_service.end(&cookie).await?;
+    }
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        AppendHeaders([
+            (SET_COOKIE, set_cookie(SESSION_COOKIE, "", 0, true)),
+            (SET_COOKIE, set_cookie(CSRF_COOKIE, "", 0, false)),
+        ]),
+    ))
 }
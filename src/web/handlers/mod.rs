@@ -1,13 +1,19 @@
 //! HTML template rendering handlers for the web dashboard.
 
+mod audit;
 mod dashboard;
 mod domains;
 mod links;
 mod login;
+mod sso;
 mod stats;
+mod tokens;
 
+pub use audit::audit_handler;
 pub use dashboard::dashboard_handler;
 pub use domains::domains_handler;
 pub use links::links_handler;
-pub use login::login_handler;
+pub use login::{LoginRequest, login_handler, login_submit_handler, logout_handler};
+pub use sso::{CallbackQuery, sso_callback_handler, sso_login_handler};
 pub use stats::stats_handler;
+pub use tokens::tokens_handler;
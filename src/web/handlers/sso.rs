@@ -0,0 +1,179 @@
+//! Dashboard single sign-on handlers.
+
+use axum::{
+    extract::{Query, State},
+    http::{
+        HeaderMap, StatusCode,
+        header::{CACHE_CONTROL, SET_COOKIE},
+    },
+    response::{AppendHeaders, Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    application::services::{
+        PendingLogin,
+        session_service::{CSRF_COOKIE, SESSION_COOKIE},
+        sso_service::LOGIN_TIMEOUT,
+    },
+    error::AppError,
+    state::AppState,
+    utils::cookies::{read_cookie, set_cookie, set_lax_cookie},
+};
+
+use super::login::LoginTemplate;
+
+/// Cookie holding the signed [`PendingLogin`] while the user is at the identity provider.
+const LOGIN_COOKIE: &str = "oidc_login";
+
+/// Signature context of the [`LOGIN_COOKIE`].
+const LOGIN_CONTEXT: &[u8] = b"oidc-login:";
+
+/// Query of the redirect back from the identity provider.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set instead of `code` when the provider refused the login.
+    pub error: Option<String>,
+}
+
+/// Starts a single sign-on login.
+///
+/// # Endpoint
+///
+/// `GET /oidc/login`
+///
+/// # Response
+///
+/// `303 See Other` to the identity provider, with an HttpOnly
+/// `SameSite=Lax` cookie holding the signed state, nonce and PKCE verifier
+/// for [`sso_callback_handler`].
+///
+/// # Errors
+///
+/// Returns `404 Not Found` if single sign-on is not configured.
+pub async fn sso_login_handler(State(st): State<AppState>) -> Result<Response, AppError> {
+    let Some(sso) = &st.sso_service else {
+        return Err(AppError::not_found(
+            "Single sign-on is not configured",
+            json!({}),
+        ));
+    };
+
+    let (url, pending) = sso.begin().await?;
+    let cookie = st.session_service.seal(LOGIN_CONTEXT, &pending.encode());
+
+    Ok((
+        AppendHeaders([(
+            SET_COOKIE,
+            set_lax_cookie(LOGIN_COOKIE, &cookie, LOGIN_TIMEOUT.num_seconds()),
+        )]),
+        Redirect::to(&url),
+    )
+        .into_response())
+}
+
+/// Finishes a single sign-on login and starts a dashboard session.
+///
+/// # Endpoint
+///
+/// `GET /oidc/callback?code=...&state=...`
+///
+/// # Response
+///
+/// `200 OK` with the `session` and `csrf_token` cookies (see
+/// [`login_submit_handler`](super::login_submit_handler)) and a page that
+/// forwards to the dashboard. The page is needed because this request comes
+/// from the identity provider's site: a redirect would not carry the new
+/// `SameSite=Strict` session cookie.
+///
+/// # Errors
+///
+/// Renders the login page with the reason:
+/// - `401 Unauthorized` if the login expired, was started in another browser,
+///   or the identity provider rejected it
+/// - `403 Forbidden` if the user's email domain and groups are not allowed
+pub async fn sso_callback_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let clear_login = (SET_COOKIE, set_lax_cookie(LOGIN_COOKIE, "", 0));
+
+    let started = async {
+        let sso = st
+            .sso_service
+            .as_ref()
+            .ok_or_else(|| AppError::not_found("Single sign-on is not configured", json!({})))?;
+
+        if let Some(error) = query.error {
+            return Err(AppError::unauthorized(
+                "Identity provider refused the login",
+                json!({ "reason": error }),
+            ));
+        }
+
+        let pending = read_cookie(&headers, LOGIN_COOKIE)
+            .and_then(|cookie| {
+                st.session_service
+                    .unseal(LOGIN_CONTEXT, &cookie)
+                    .and_then(PendingLogin::decode)
+            })
+            .ok_or_else(|| {
+                AppError::unauthorized(
+                    "Login expired or was started elsewhere",
+                    json!({ "reason": "login cookie missing or invalid" }),
+                )
+            })?;
+
+        let (Some(code), Some(state)) = (query.code, query.state) else {
+            return Err(AppError::bad_request("Missing code or state", json!({})));
+        };
+
+        let owner = sso.complete(&code, &state, &pending).await?;
+        st.session_service.start(owner).await
+    }
+    .await;
+
+    match started {
+        Ok(session) => {
+            let max_age = st.session_service.ttl().num_seconds();
+            (
+                AppendHeaders([
+                    (
+                        SET_COOKIE,
+                        set_cookie(SESSION_COOKIE, &session.cookie, max_age, true),
+                    ),
+                    (
+                        SET_COOKIE,
+                        set_cookie(CSRF_COOKIE, &session.csrf_token, max_age, false),
+                    ),
+                    clear_login,
+                    (CACHE_CONTROL, "no-store".to_string()),
+                ]),
+                Html(
+                    r#"<!doctype html><meta http-equiv="refresh" content="0;url=/dashboard"><a href="/dashboard">Continue to the dashboard</a>"#,
+                ),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            let status = match e.status() {
+                StatusCode::INTERNAL_SERVER_ERROR => StatusCode::BAD_GATEWAY,
+                status => status,
+            };
+            let message = e.to_error_info().message;
+            (
+                status,
+                AppendHeaders([clear_login]),
+                LoginTemplate {
+                    sso_enabled: st.sso_service.is_some(),
+                    error: Some(message),
+                },
+            )
+                .into_response()
+        }
+    }
+}
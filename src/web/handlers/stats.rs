@@ -25,7 +25,7 @@ pub struct StatsTemplate {
 /// # Template
 ///
 /// Uses `templates/stats.html` for server-side rendering.
-/// The template fetches detailed statistics via JavaScript from `/api/stats/{code}`.
+/// The template fetches detailed statistics via JavaScript from `/api/v1/stats/{code}`.
 pub async fn stats_handler(Path(code): Path<String>) -> impl IntoResponse {
     StatsTemplate { code }
 }
@@ -0,0 +1,22 @@
+//! API token management page handler.
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::response::IntoResponse;
+
+/// Template for the API token management page.
+///
+/// Renders `templates/tokens.html` with token listing, creation and revocation.
+/// Data is fetched client-side via Alpine.js from `/api/v1/tokens`.
+#[derive(Template, WebTemplate)]
+#[template(path = "tokens.html")]
+pub struct TokensTemplate {}
+
+/// Renders the API token management page.
+///
+/// # Endpoint
+///
+/// `GET /tokens`
+pub async fn tokens_handler() -> impl IntoResponse {
+    TokensTemplate {}
+}
@@ -1,28 +1,34 @@
-//! Cookie-based authentication middleware for web dashboard.
+//! Session cookie authentication middleware for web dashboard.
 
 use axum::{
     extract::{Request, State},
-    http::header::COOKIE,
     middleware::Next,
     response::{Redirect, Response},
 };
 
-use crate::state::AppState;
+use crate::{
+    application::services::session_service::SESSION_COOKIE, state::AppState,
+    utils::cookies::read_cookie,
+};
 
-/// Authenticates dashboard requests using cookie-based tokens.
+/// Authenticates dashboard requests using the session cookie.
 ///
 /// # Cookie Format
 ///
 /// ```text
-/// Cookie: auth_token=<token>
+/// Cookie: session=<session id>.<signature>
 /// ```
 ///
+/// The cookie is issued by `POST /dashboard/login` as `HttpOnly` and
+/// `SameSite=Strict`, so it is never exposed to scripts or sent cross-site.
+///
 /// # Authentication Flow
 ///
-/// 1. Extract `auth_token` cookie from request
-/// 2. Validate token via [`crate::application::services::auth_service::AuthService`]
+/// 1. Extract `session` cookie from request
+/// 2. Verify its signature and look up the session via
+///    [`crate::application::services::session_service::SessionService`]
 /// 3. On success, continue to handler
-/// 4. On failure or missing token, redirect to `/dashboard/login`
+/// 4. On failure or missing cookie, redirect to `/dashboard/login`
 ///
 /// # Differences from API Auth
 ///
@@ -30,13 +36,6 @@ use crate::state::AppState;
 /// this middleware redirects to the login page for a better user experience
 /// in a browser context.
 ///
-/// # Cookie Parsing
-///
-/// Handles multiple cookies in the `Cookie` header by:
-/// - Splitting on semicolons
-/// - Extracting `auth_token` key-value pair
-/// - Ignoring other cookies
-///
 /// # Example
 ///
 /// ```rust,ignore
@@ -51,33 +50,20 @@ use crate::state::AppState;
 /// # Errors
 ///
 /// Returns `Redirect` to `/dashboard/login` if:
-/// - `auth_token` cookie is missing
-/// - Token format is invalid
-/// - Token validation fails (invalid/revoked)
+/// - `session` cookie is missing
+/// - Cookie signature is invalid
+/// - Session is unknown, expired, logged out, or its token was revoked
 pub async fn layer(
     State(st): State<AppState>,
     req: Request,
     next: Next,
 ) -> Result<Response, Redirect> {
-    let token = req
-        .headers()
-        .get(COOKIE)
-        .and_then(|cookie_header| cookie_header.to_str().ok())
-        .and_then(|cookie_str| {
-            cookie_str.split(';').find_map(|cookie| {
-                let mut parts = cookie.trim().splitn(2, '=');
-                match (parts.next(), parts.next()) {
-                    (Some("auth_token"), Some(value)) => Some(value.to_string()),
-                    _ => None,
-                }
-            })
-        });
+    let Some(cookie) = read_cookie(req.headers(), SESSION_COOKIE) else {
+        return Err(Redirect::to("/dashboard/login"));
+    };
 
-    match token {
-        Some(token) => match st.auth_service.authenticate(&token).await {
-            Ok(_) => Ok(next.run(req).await),
-            Err(_) => Err(Redirect::to("/dashboard/login")),
-        },
-        None => Err(Redirect::to("/dashboard/login")),
+    match st.session_service.authenticate(&cookie).await {
+        Ok(_) => Ok(next.run(req).await),
+        Err(_) => Err(Redirect::to("/dashboard/login")),
     }
 }
@@ -1,12 +1,19 @@
 //! Web dashboard route configuration.
 
 use crate::state::AppState;
-use crate::web::handlers::{dashboard_handler, domains_handler, links_handler, login_handler, stats_handler};
-use axum::{Router, routing::get};
+use crate::web::handlers::{
+    audit_handler, dashboard_handler, domains_handler, links_handler, login_handler,
+    login_submit_handler, logout_handler, sso_callback_handler, sso_login_handler, stats_handler,
+    tokens_handler,
+};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 
 /// Protected dashboard routes requiring authentication.
 ///
-/// Protected via [`crate::web::middleware::web_auth`] (cookie-based or similar).
+/// Protected via [`crate::web::middleware::web_auth`] (session cookie).
 ///
 /// # Endpoints
 ///
@@ -14,12 +21,16 @@ use axum::{Router, routing::get};
 /// - `GET /links` - Link management page
 /// - `GET /stats/{code}` - Detailed statistics page for a specific link
 /// - `GET /domains` - Domain management page
+/// - `GET /tokens` - API token management page
+/// - `GET /audit` - Audit log of link, domain and token changes
 pub fn protected_routes() -> Router<AppState> {
     Router::new()
         .route("/", get(dashboard_handler))
         .route("/links", get(links_handler))
         .route("/stats/{code}", get(stats_handler))
         .route("/domains", get(domains_handler))
+        .route("/tokens", get(tokens_handler))
+        .route("/audit", get(audit_handler))
 }
 
 /// Public dashboard routes without authentication.
@@ -30,3 +41,21 @@ pub fn protected_routes() -> Router<AppState> {
 pub fn public_routes() -> Router<AppState> {
     Router::new().route("/login", get(login_handler))
 }
+
+/// Session routes that exchange tokens for session cookies and back.
+///
+/// Kept apart from [`public_routes`] so they can get the stricter rate limit.
+///
+/// # Endpoints
+///
+/// - `POST /login` - Exchange an API token for a session cookie
+/// - `POST /logout` - End the session and clear its cookies
+/// - `GET /oidc/login` - Start a single sign-on login at the identity provider
+/// - `GET /oidc/callback` - Finish a single sign-on login
+pub fn session_routes() -> Router<AppState> {
+    Router::new()
+        .route("/login", post(login_submit_handler))
+        .route("/logout", post(logout_handler))
+        .route("/oidc/login", get(sso_login_handler))
+        .route("/oidc/callback", get(sso_callback_handler))
+}
@@ -1,14 +1,21 @@
 // =============================================================================
-// Auth — cookie-based token management
+// Auth — server-side session (HttpOnly cookie) + CSRF token
 // =============================================================================
 const Auth = {
-  getToken() {
-    const m = document.cookie.match(/(?:^|;\s*)auth_token=([^;]+)/);
+  csrfToken() {
+    const m = document.cookie.match(/(?:^|;\s*)csrf_token=([^;]+)/);
     return m ? decodeURIComponent(m[1]) : null;
   },
-  logout() {
-    document.cookie = 'auth_token=; path=/; max-age=0';
-    window.location.href = '/dashboard/login';
+  async logout() {
+    const csrf = Auth.csrfToken();
+    try {
+      await fetch('/dashboard/logout', {
+        method: 'POST',
+        headers: csrf ? { 'X-CSRF-Token': csrf } : {},
+      });
+    } finally {
+      window.location.href = '/dashboard/login';
+    }
   },
   redirectToLogin() {
     window.location.href = '/dashboard/login';
@@ -21,12 +28,13 @@ const Auth = {
 // =============================================================================
 const Api = {
   async request(endpoint, options = {}) {
-    const token = Auth.getToken();
+    const method = (options.method || 'GET').toUpperCase();
+    const csrf = method === 'GET' ? null : Auth.csrfToken();
     const res = await fetch(endpoint, {
       ...options,
       headers: {
         'Content-Type': 'application/json',
-        ...(token ? { Authorization: `Bearer ${token}` } : {}),
+        ...(csrf ? { 'X-CSRF-Token': csrf } : {}),
         ...(options.headers || {}),
       },
     });
@@ -37,31 +45,43 @@ const Api = {
   },
 
   shorten(urls) {
-    return Api.request('/api/shorten', { method: 'POST', body: JSON.stringify({ urls }) });
+    return Api.request('/api/v1/shorten', { method: 'POST', body: JSON.stringify({ urls }) });
   },
   getLinks(params) {
-    return Api.request(`/api/stats?${new URLSearchParams(clean(params))}`);
+    return Api.request(`/api/v1/stats?${new URLSearchParams(clean(params))}`);
   },
   updateLink(code, patch) {
-    return Api.request(`/api/links/${code}`, { method: 'PATCH', body: JSON.stringify(patch) });
+    return Api.request(`/api/v1/links/${code}`, { method: 'PATCH', body: JSON.stringify(patch) });
   },
   deleteLink(code) {
-    return Api.request(`/api/links/${code}`, { method: 'DELETE' });
+    return Api.request(`/api/v1/links/${code}`, { method: 'DELETE' });
   },
   getLinkStats(code, params) {
-    return Api.request(`/api/stats/${code}?${new URLSearchParams(clean(params))}`);
+    return Api.request(`/api/v1/stats/${code}?${new URLSearchParams(clean(params))}`);
   },
   getDomains() {
-    return Api.request('/api/domains');
+    return Api.request('/api/v1/domains');
   },
   createDomain(data) {
-    return Api.request('/api/domains', { method: 'POST', body: JSON.stringify(data) });
+    return Api.request('/api/v1/domains', { method: 'POST', body: JSON.stringify(data) });
   },
   updateDomain(id, data) {
-    return Api.request(`/api/domains/${id}`, { method: 'PATCH', body: JSON.stringify(data) });
+    return Api.request(`/api/v1/domains/${id}`, { method: 'PATCH', body: JSON.stringify(data) });
   },
   deleteDomain(id) {
-    return Api.request(`/api/domains/${id}`, { method: 'DELETE' });
+    return Api.request(`/api/v1/domains/${id}`, { method: 'DELETE' });
+  },
+  getTokens() {
+    return Api.request('/api/v1/tokens');
+  },
+  createToken(data) {
+    return Api.request('/api/v1/tokens', { method: 'POST', body: JSON.stringify(data) });
+  },
+  revokeToken(id) {
+    return Api.request(`/api/v1/tokens/${id}`, { method: 'DELETE' });
+  },
+  getAudit(params) {
+    return Api.request(`/api/v1/audit?${new URLSearchParams(clean(params))}`);
   },
 };
 
@@ -119,14 +139,13 @@ function loginPage() {
       this.error = '';
       this.loading = true;
       try {
-        // Validate token against a protected endpoint; /health is public and always 200
-        const res = await fetch('/api/domains', {
-          headers: { Authorization: `Bearer ${this.token}` },
+        // Exchanges the token for an HttpOnly session cookie; the token is not kept
+        const res = await fetch('/dashboard/login', {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify({ token: this.token }),
         });
         if (res.ok) {
-          const exp = new Date();
-          exp.setDate(exp.getDate() + 30);
-          document.cookie = `auth_token=${encodeURIComponent(this.token)}; path=/; expires=${exp.toUTCString()}`;
           window.location.href = '/dashboard';
         } else {
           this.error = res.status === 401 ? 'Invalid token' : 'Authentication failed';
@@ -522,3 +541,159 @@ function domainsPage() {
     },
   };
 }
+
+// =============================================================================
+// tokensPage() — Alpine data for /dashboard/tokens
+// =============================================================================
+const TOKEN_SCOPES = ['links:read', 'links:write', 'stats:read', 'domains:admin', 'tokens:admin', 'audit:read', 'cache:admin'];
+
+function tokensPage() {
+  return {
+    tokens: [],
+    scopeOptions: TOKEN_SCOPES,
+    loading: false,
+    loadError: '',
+    showAddForm: false,
+    newToken: { name: '', scopes: [...TOKEN_SCOPES], expiresAt: '' },
+    addError: '',
+    created: null,
+    copied: false,
+    revokeConfirm: null,
+    revokeError: '',
+
+    async init() {
+      await this.load();
+    },
+
+    async load() {
+      this.loading = true;
+      const res = await Api.getTokens();
+      if (res?.ok) {
+        this.tokens = res.data.items;
+        this.loadError = '';
+      } else if (res) {
+        this.loadError = res.data?.error?.message || 'Failed to load tokens';
+      }
+      this.loading = false;
+    },
+
+    status(t) {
+      if (t.revokedAt) return 'Revoked';
+      if (t.expiresAt && new Date(t.expiresAt) <= new Date()) return 'Expired';
+      return 'Active';
+    },
+
+    async create() {
+      this.addError = '';
+      const data = { name: this.newToken.name, scopes: this.newToken.scopes };
+      if (this.newToken.expiresAt) data.expires_at = new Date(this.newToken.expiresAt).toISOString();
+      const res = await Api.createToken(data);
+      if (res?.status === 201) {
+        // The plaintext is returned only once; keep it on screen until dismissed
+        this.created = res.data;
+        this.copied = false;
+        this.newToken = { name: '', scopes: [...TOKEN_SCOPES], expiresAt: '' };
+        this.showAddForm = false;
+        await this.load();
+      } else {
+        this.addError = res?.data?.error?.message || 'Failed to create token';
+      }
+    },
+
+    async copyCreated() {
+      await navigator.clipboard.writeText(this.created.plaintext);
+      this.copied = true;
+    },
+
+    confirmRevoke(id) { this.revokeConfirm = id; this.revokeError = ''; },
+    cancelRevoke() { this.revokeConfirm = null; },
+
+    async doRevoke(id) {
+      this.revokeError = '';
+      const res = await Api.revokeToken(id);
+      if (res === null) {
+        this.revokeConfirm = null;
+        await this.load();
+      } else if (res?.ok === false) {
+        this.revokeError = res?.data?.error?.message || 'Cannot revoke token';
+        this.revokeConfirm = null;
+      }
+    },
+  };
+}
+
+// =============================================================================
+// auditPage() — Alpine data for /dashboard/audit
+// =============================================================================
+function auditPage() {
+  return {
+    events: [],
+    entity: '',
+    actor: '',
+    fromDate: '',
+    toDate: '',
+    page: 1,
+    totalPages: 1,
+    totalItems: 0,
+    loading: false,
+    loadError: '',
+    expanded: null,
+
+    get pages() {
+      const delta = 2, arr = [];
+      for (let i = Math.max(1, this.page - delta); i <= Math.min(this.totalPages, this.page + delta); i++) arr.push(i);
+      return arr;
+    },
+
+    async init() {
+      await this.load();
+    },
+
+    async load() {
+      this.loading = true;
+      const res = await Api.getAudit({
+        page: this.page,
+        page_size: 25,
+        entity: this.entity,
+        actor: this.actor.trim(),
+        from: this.fromDate ? new Date(this.fromDate).toISOString() : '',
+        to: this.toDate ? new Date(this.toDate).toISOString() : '',
+      });
+      if (res?.ok) {
+        this.events = res.data.items;
+        this.totalPages = res.data.pagination.totalPages;
+        this.totalItems = res.data.pagination.totalItems;
+        this.loadError = '';
+      } else if (res) {
+        this.loadError = res.data?.error?.message || 'Failed to load audit log';
+      }
+      this.loading = false;
+    },
+
+    applyFilters() { this.page = 1; this.load(); },
+    resetFilters() {
+      this.entity = ''; this.actor = ''; this.fromDate = ''; this.toDate = '';
+      this.page = 1; this.load();
+    },
+    goToPage(p) { this.page = p; this.load(); },
+    toggle(id) { this.expanded = this.expanded === id ? null : id; },
+
+    actionBadge(action) {
+      if (action === 'create' || action === 'restore') return 'bg-green-100 text-green-700';
+      if (action === 'delete') return 'bg-red-100 text-red-700';
+      return 'bg-yellow-100 text-yellow-700';
+    },
+
+    // Fields that differ between the before and after values
+    changes(e) {
+      const before = e.before || {}, after = e.after || {};
+      return [...new Set([...Object.keys(before), ...Object.keys(after)])]
+        .filter((k) => JSON.stringify(before[k]) !== JSON.stringify(after[k]))
+        .map((k) => ({ field: k, before: before[k], after: after[k] }));
+    },
+
+    formatValue(v) {
+      return v === undefined || v === null ? '—' : typeof v === 'string' ? v : JSON.stringify(v);
+    },
+  };
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::domain::entities::Scope;
+
+const ADMIN: &str = "audit-admin-token";
+const CLIENT_IP: &str = "203.0.113.9";
+
+async fn setup(pool: PgPool) -> TestServer {
+    common::create_test_api_token(&pool, "admin", ADMIN).await;
+
+    let (state, _rx) = common::create_test_state(pool);
+    common::test_server(state)
+}
+
+async fn audit(server: &TestServer, token: &str, query: &str) -> Value {
+    let response = server
+        .get(&format!("/api/v1/audit{query}"))
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(token)
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()
+}
+
+/// `(entity, action)` of every listed event, oldest first.
+fn actions(body: &Value) -> Vec<(String, String)> {
+    let mut actions: Vec<_> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["entity"].as_str().unwrap().to_string(),
+                e["action"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    actions.reverse();
+    actions
+}
+
+#[sqlx::test]
+async fn test_link_changes_are_recorded(pool: PgPool) {
+    let server = setup(pool).await;
+
+    let shortened = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "urls": [{ "url": "https://example.com/old", "custom_code": "audit1" }] }))
+        .await;
+    shortened.assert_status_ok();
+
+    for request in [
+        server
+            .patch("/api/v1/links/audit1")
+            .json(&json!({ "url": "https://example.com/new" })),
+        server.delete("/api/v1/links/audit1"),
+        server
+            .patch("/api/v1/links/audit1")
+            .json(&json!({ "restore": true })),
+    ] {
+        let response = request
+            .add_header("Host", "s.example.com")
+            .add_header("X-Forwarded-For", CLIENT_IP)
+            .authorization_bearer(ADMIN)
+            .await;
+        assert!(response.status_code().is_success());
+    }
+
+    let body = audit(&server, ADMIN, "?entity=link").await;
+
+    assert_eq!(
+        actions(&body),
+        vec![
+            ("link".to_string(), "create".to_string()),
+            ("link".to_string(), "update".to_string()),
+            ("link".to_string(), "delete".to_string()),
+            ("link".to_string(), "restore".to_string()),
+        ]
+    );
+    assert_eq!(body["pagination"]["total_items"], 4);
+
+    let update = &body["items"][2];
+    assert_eq!(update["actor"]["name"], "admin");
+    assert!(update["actor"]["token_id"].is_i64());
+    assert_eq!(update["ip"], CLIENT_IP);
+    assert_eq!(update["before"]["long_url"], "https://example.com/old");
+    assert_eq!(update["after"]["long_url"], "https://example.com/new");
+
+    let create = &body["items"][3];
+    assert!(create["before"].is_null());
+    assert_eq!(create["after"]["code"], "audit1");
+
+    let delete = &body["items"][1];
+    assert!(delete["after"].is_null());
+    assert!(delete["before"]["deleted_at"].is_null());
+}
+
+#[sqlx::test]
+async fn test_domain_and_token_changes_are_recorded(pool: PgPool) {
+    let server = setup(pool).await;
+
+    let domain = server
+        .post("/api/v1/domains")
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "domain": "audit.example.com" }))
+        .await
+        .json::<Value>();
+    server
+        .patch(&format!("/api/v1/domains/{}", domain["id"]))
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "description": "Campaigns" }))
+        .await
+        .assert_status_ok();
+    server
+        .delete(&format!("/api/v1/domains/{}", domain["id"]))
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let token = server
+        .post("/api/v1/tokens")
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "name": "ci" }))
+        .await
+        .json::<Value>();
+    for _ in 0..2 {
+        server
+            .delete(&format!("/api/v1/tokens/{}", token["token"]["id"]))
+            .add_header("X-Forwarded-For", CLIENT_IP)
+            .authorization_bearer(ADMIN)
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    let body = audit(&server, ADMIN, "").await;
+
+    // Revoking the already revoked token again changes nothing and is not recorded.
+    assert_eq!(
+        actions(&body),
+        vec![
+            ("domain".to_string(), "create".to_string()),
+            ("domain".to_string(), "update".to_string()),
+            ("domain".to_string(), "delete".to_string()),
+            ("token".to_string(), "create".to_string()),
+            ("token".to_string(), "delete".to_string()),
+        ]
+    );
+    assert_eq!(body["items"][3]["after"]["description"], "Campaigns");
+    assert_eq!(body["items"][0]["before"]["name"], "ci");
+    assert!(body["items"][0]["before"].get("token_hash").is_none());
+
+    let tokens = audit(&server, ADMIN, "?entity=token&actor=admin").await;
+    assert_eq!(tokens["items"].as_array().unwrap().len(), 2);
+
+    let nobody = audit(&server, ADMIN, "?actor=nobody").await;
+    assert!(nobody["items"].as_array().unwrap().is_empty());
+
+    let future = audit(&server, ADMIN, "?from=2999-01-01T00:00:00Z").await;
+    assert!(future["items"].as_array().unwrap().is_empty());
+}
+
+#[sqlx::test]
+async fn test_audit_requires_scope_and_unrestricted_token(pool: PgPool) {
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_scoped_api_token(&pool, "reader", "audit-reader", &[Scope::LinksRead], None)
+        .await;
+    common::create_scoped_api_token(
+        &pool,
+        "restricted",
+        "audit-restricted",
+        &[Scope::AuditRead],
+        Some(&[domain_id]),
+    )
+    .await;
+    let server = setup(pool).await;
+
+    for token in ["audit-reader", "audit-restricted"] {
+        let response = server
+            .get("/api/v1/audit")
+            .add_header("X-Forwarded-For", CLIENT_IP)
+            .authorization_bearer(token)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    }
+
+    let invalid = server
+        .get("/api/v1/audit?entity=user")
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer(ADMIN)
+        .await;
+    assert_eq!(invalid.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn test_workspaces_only_see_their_own_events(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    common::create_workspace_domain(&pool, "go.acme.test", workspace_id).await;
+    common::create_workspace_api_token(&pool, "acme", "audit-acme", workspace_id).await;
+    let server = setup(pool).await;
+
+    server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", CLIENT_IP)
+        .authorization_bearer("audit-acme")
+        .json(&json!({ "urls": [{ "url": "https://example.com/acme" }] }))
+        .await
+        .assert_status_ok();
+
+    let acme = audit(&server, "audit-acme", "").await;
+    let admin = audit(&server, ADMIN, "").await;
+
+    assert_eq!(acme["items"].as_array().unwrap().len(), 1);
+    assert_eq!(acme["items"][0]["actor"]["name"], "acme");
+    assert!(admin["items"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::Scope;
+use url_shortener::infrastructure::cache::{NullCache, TieredCache};
+use url_shortener::routes::app_router;
+use url_shortener::state::AppState;
+
+const ADMIN: &str = "cache-admin-token";
+
+/// State with an in-process cache, so entries survive between requests.
+async fn setup(pool: PgPool) -> (TestServer, AppState) {
+    common::create_test_api_token(&pool, "admin", ADMIN).await;
+
+    let cache = Arc::new(TieredCache::new(NullCache::new(), 1000, 300));
+    let (state, _rx) = common::create_test_state_with_cache(pool, cache);
+    let server = common::serve(app_router(state.clone(), false, Utc::now()));
+    (server, state)
+}
+
+async fn create_clicked_link(pool: &PgPool, code: &str, domain_id: i64, clicks: usize) {
+    common::create_test_link(
+        pool,
+        code,
+        &format!("https://example.com/{code}"),
+        domain_id,
+    )
+    .await;
+    let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", code)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    for i in 0..clicks {
+        common::create_test_click(pool, link_id, &format!("10.0.0.{i}")).await;
+    }
+}
+
+async fn warm_up(state: &AppState) -> usize {
+    state
+        .cache_admin_service
+        .warm_up(100, Utc::now() - chrono::Duration::days(7))
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn test_warm_up_serves_redirects_from_cache(pool: PgPool) {
+    let (server, state) = setup(pool.clone()).await;
+    let domain_id = common::get_default_domain(&pool).await;
+    create_clicked_link(&pool, "warm1", domain_id, 3).await;
+    create_clicked_link(&pool, "cold1", domain_id, 0).await;
+
+    assert_eq!(warm_up(&state).await, 1);
+
+    // The database changes, but the warmed entry still answers.
+    sqlx::query!("UPDATE links SET long_url = 'https://example.com/changed' WHERE code = 'warm1'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = server
+        .get("/warm1")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(response.header("location"), "https://example.com/warm1");
+
+    let missing = server
+        .get("/api/v1/cache/keys/s.example.com/cold1")
+        .authorization_bearer(ADMIN)
+        .await;
+    missing.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_inspect_and_purge_key(pool: PgPool) {
+    let (server, state) = setup(pool.clone()).await;
+    let domain_id = common::get_default_domain(&pool).await;
+    create_clicked_link(&pool, "key1", domain_id, 1).await;
+    warm_up(&state).await;
+
+    let inspected = server
+        .get("/api/v1/cache/keys/s.example.com/key1")
+        .authorization_bearer(ADMIN)
+        .await;
+    inspected.assert_status_ok();
+    let body = inspected.json::<Value>();
+    assert_eq!(body["key"], "s.example.com:key1");
+    assert_eq!(body["status"], "found");
+    assert!(
+        body["value"]
+            .as_str()
+            .unwrap()
+            .contains("https://example.com/key1")
+    );
+
+    let purged = server
+        .delete("/api/v1/cache/keys/s.example.com/key1")
+        .authorization_bearer(ADMIN)
+        .await;
+    purged.assert_status(StatusCode::NO_CONTENT);
+
+    let gone = server
+        .get("/api/v1/cache/keys/s.example.com/key1")
+        .authorization_bearer(ADMIN)
+        .await;
+    gone.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_purge_domain_leaves_other_domains(pool: PgPool) {
+    let (server, state) = setup(pool.clone()).await;
+    let default_id = common::get_default_domain(&pool).await;
+    let other_id = common::create_test_domain(&pool, "other.example.com").await;
+    create_clicked_link(&pool, "dom1", default_id, 1).await;
+    create_clicked_link(&pool, "dom2", other_id, 1).await;
+    warm_up(&state).await;
+
+    let purged = server
+        .delete("/api/v1/cache/domains/s.example.com")
+        .authorization_bearer(ADMIN)
+        .await;
+    purged.assert_status_ok();
+    assert_eq!(purged.json::<Value>()["domain"], "s.example.com");
+
+    server
+        .get("/api/v1/cache/keys/s.example.com/dom1")
+        .authorization_bearer(ADMIN)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/api/v1/cache/keys/other.example.com/dom2")
+        .authorization_bearer(ADMIN)
+        .await
+        .assert_status_ok();
+}
+
+#[sqlx::test]
+async fn test_stats_report_local_entries(pool: PgPool) {
+    let (server, state) = setup(pool.clone()).await;
+    let domain_id = common::get_default_domain(&pool).await;
+    create_clicked_link(&pool, "stat1", domain_id, 1).await;
+    create_clicked_link(&pool, "stat2", domain_id, 2).await;
+    warm_up(&state).await;
+
+    let response = server
+        .get("/api/v1/cache/stats")
+        .authorization_bearer(ADMIN)
+        .await;
+
+    response.assert_status_ok();
+    let body = response.json::<Value>();
+    assert_eq!(body["backend"], "tiered");
+    assert_eq!(body["local_entries"], 2);
+    assert_eq!(body["keys"], Value::Null);
+}
+
+#[sqlx::test]
+async fn test_cache_admin_requires_scope(pool: PgPool) {
+    let (server, _state) = setup(pool.clone()).await;
+    common::create_scoped_api_token(&pool, "reader", "reader-token", &[Scope::StatsRead], None)
+        .await;
+
+    for response in [
+        server
+            .get("/api/v1/cache/stats")
+            .authorization_bearer("reader-token")
+            .await,
+        server
+            .delete("/api/v1/cache/domains/s.example.com")
+            .authorization_bearer("reader-token")
+            .await,
+    ] {
+        response.assert_status(StatusCode::FORBIDDEN);
+    }
+}
+
+#[sqlx::test]
+async fn test_domain_restricted_token_is_limited_to_its_domains(pool: PgPool) {
+    let (server, _state) = setup(pool.clone()).await;
+    let default_id = common::get_default_domain(&pool).await;
+    common::create_test_domain(&pool, "other.example.com").await;
+    common::create_scoped_api_token(
+        &pool,
+        "restricted",
+        "restricted-token",
+        &[Scope::CacheAdmin],
+        Some(&[default_id]),
+    )
+    .await;
+
+    server
+        .delete("/api/v1/cache/domains/s.example.com")
+        .authorization_bearer("restricted-token")
+        .await
+        .assert_status_ok();
+    server
+        .delete("/api/v1/cache/domains/other.example.com")
+        .authorization_bearer("restricted-token")
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    server
+        .get("/api/v1/cache/stats")
+        .authorization_bearer("restricted-token")
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
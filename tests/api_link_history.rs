@@ -0,0 +1,154 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::domain::entities::{Role, Scope};
+
+const ADMIN: &str = "history-admin-token";
+const HOST: &str = "s.example.com";
+
+async fn setup(pool: PgPool) -> TestServer {
+    common::create_test_api_token(&pool, "admin", ADMIN).await;
+
+    let (state, _rx) = common::create_test_state(pool);
+    common::test_server(state)
+}
+
+async fn shorten(server: &TestServer, code: &str, url: &str) {
+    server
+        .post("/api/v1/shorten")
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "urls": [{ "url": url, "custom_code": code }] }))
+        .await
+        .assert_status_ok();
+}
+
+async fn history(server: &TestServer, token: &str, code: &str) -> Value {
+    let response = server
+        .get(&format!("/api/v1/links/{code}/history"))
+        .add_header("Host", HOST)
+        .authorization_bearer(token)
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()
+}
+
+#[sqlx::test]
+async fn test_updates_are_versioned(pool: PgPool) {
+    let server = setup(pool).await;
+    shorten(&server, "hist1", "https://example.com/v1").await;
+
+    for body in [
+        json!({ "url": "https://example.com/v2" }),
+        json!({ "permanent": true }),
+        // Deleting and restoring do not change versioned fields.
+        json!({ "restore": true }),
+    ] {
+        server
+            .patch("/api/v1/links/hist1")
+            .add_header("Host", HOST)
+            .authorization_bearer(ADMIN)
+            .json(&body)
+            .await
+            .assert_status_ok();
+    }
+
+    let body = history(&server, ADMIN, "hist1").await;
+    let items = body["items"].as_array().unwrap();
+
+    assert_eq!(body["code"], "hist1");
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0]["version"], 3);
+    assert_eq!(items[0]["permanent"], true);
+    assert_eq!(items[1]["long_url"], "https://example.com/v2");
+    assert_eq!(items[2]["version"], 1);
+    assert_eq!(items[2]["long_url"], "https://example.com/v1");
+    assert_eq!(items[2]["actor"]["name"], "admin");
+    assert!(items[2]["reverted_from"].is_null());
+}
+
+#[sqlx::test]
+async fn test_revert_restores_version(pool: PgPool) {
+    let server = setup(pool).await;
+    shorten(&server, "hist2", "https://example.com/old").await;
+    server
+        .patch("/api/v1/links/hist2")
+        .add_header("Host", HOST)
+        .authorization_bearer(ADMIN)
+        .json(&json!({ "url": "https://example.com/new", "permanent": true }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/v1/links/hist2/revert/1")
+        .add_header("Host", HOST)
+        .authorization_bearer(ADMIN)
+        .await;
+    response.assert_status_ok();
+    let link = response.json::<Value>();
+    assert_eq!(link["long_url"], "https://example.com/old");
+    assert_eq!(link["permanent"], false);
+
+    let body = history(&server, ADMIN, "hist2").await;
+    let latest = &body["items"][0];
+    assert_eq!(latest["version"], 3);
+    assert_eq!(latest["reverted_from"], 1);
+    assert_eq!(latest["long_url"], "https://example.com/old");
+
+    let redirect = server.get("/hist2").add_header("Host", HOST).await;
+    redirect.assert_status(StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(redirect.header("location"), "https://example.com/old");
+}
+
+#[sqlx::test]
+async fn test_revert_to_unknown_version_is_not_found(pool: PgPool) {
+    let server = setup(pool).await;
+    shorten(&server, "hist3", "https://example.com/a").await;
+
+    server
+        .post("/api/v1/links/hist3/revert/7")
+        .add_header("Host", HOST)
+        .authorization_bearer(ADMIN)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .post("/api/v1/links/missing/revert/1")
+        .add_header("Host", HOST)
+        .authorization_bearer(ADMIN)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_history_requires_scope_and_visibility(pool: PgPool) {
+    common::create_scoped_api_token(&pool, "stats", "stats-only", &[Scope::StatsRead], None).await;
+    common::create_scoped_api_token(&pool, "reader", "read-only", &[Scope::LinksRead], None).await;
+    let owner = common::create_test_user(&pool, "owner@example.com", Role::Editor, None).await;
+    let other = common::create_test_user(&pool, "other@example.com", Role::Editor, None).await;
+    common::create_user_api_token(&pool, "other", "other-token", other).await;
+    let server = setup(pool.clone()).await;
+    shorten(&server, "hist4", "https://example.com/a").await;
+    common::set_link_owner(&pool, "hist4", owner, None).await;
+
+    server
+        .get("/api/v1/links/hist4/history")
+        .add_header("Host", HOST)
+        .authorization_bearer("stats-only")
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    history(&server, "read-only", "hist4").await;
+    server
+        .post("/api/v1/links/hist4/revert/1")
+        .add_header("Host", HOST)
+        .authorization_bearer("read-only")
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    server
+        .get("/api/v1/links/hist4/history")
+        .add_header("Host", HOST)
+        .authorization_bearer("other-token")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::domain::entities::Role;
+
+const ADA: &str = "ownership-ada-token";
+const BOB: &str = "ownership-bob-token";
+const EVE: &str = "ownership-eve-token";
+const ADMIN: &str = "ownership-admin-token";
+
+/// Ada and Bob are editors in the same team; Eve is an editor without a team.
+/// The admin token is not bound to a user.
+async fn setup(pool: PgPool) -> TestServer {
+    let team_id = common::create_test_team(&pool, "marketing").await;
+    for (email, token, team) in [
+        ("ada@example.com", ADA, Some(team_id)),
+        ("bob@example.com", BOB, Some(team_id)),
+        ("eve@example.com", EVE, None),
+    ] {
+        let user_id = common::create_test_user(&pool, email, Role::Editor, team).await;
+        common::create_user_api_token(&pool, email, token, user_id).await;
+    }
+    common::create_test_api_token(&pool, "admin", ADMIN).await;
+
+    let (state, _rx) = common::create_test_state(pool);
+    common::test_server(state)
+}
+
+async fn shorten(server: &TestServer, token: &str, url: &str) -> Value {
+    let response = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(token)
+        .json(&json!({ "urls": [{ "url": url }] }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["items"][0].clone()
+}
+
+async fn stats_codes(server: &TestServer, token: &str) -> (Vec<String>, i64) {
+    let body = server
+        .get("/api/v1/stats")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(token)
+        .await
+        .json::<Value>();
+
+    let mut codes: Vec<String> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["code"].as_str().unwrap().to_string())
+        .collect();
+    codes.sort();
+    (codes, body["pagination"]["total_items"].as_i64().unwrap())
+}
+
+async fn delete(server: &TestServer, token: &str, code: &str) -> TestResponse {
+    server
+        .delete(&format!("/api/v1/links/{code}"))
+        .add_header("Host", "s.example.com")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(token)
+        .await
+}
+
+#[sqlx::test]
+async fn test_stats_show_own_and_team_links(pool: PgPool) {
+    let server = setup(pool).await;
+
+    let ada = shorten(&server, ADA, "https://example.com/ada").await;
+    let eve = shorten(&server, EVE, "https://example.com/eve").await;
+    let ada_code = ada["code"].as_str().unwrap().to_string();
+    let eve_code = eve["code"].as_str().unwrap().to_string();
+
+    assert_eq!(stats_codes(&server, ADA).await, (vec![ada_code.clone()], 1));
+    assert_eq!(stats_codes(&server, BOB).await, (vec![ada_code.clone()], 1));
+    assert_eq!(stats_codes(&server, EVE).await, (vec![eve_code.clone()], 1));
+
+    let mut all = vec![ada_code, eve_code];
+    all.sort();
+    assert_eq!(stats_codes(&server, ADMIN).await, (all, 2));
+
+    let detail = server
+        .get(&format!("/api/v1/stats/{}", eve["code"].as_str().unwrap()))
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(ADA)
+        .await;
+    assert_eq!(detail.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_links_of_other_teams_cannot_be_changed(pool: PgPool) {
+    let server = setup(pool).await;
+
+    let code = shorten(&server, ADA, "https://example.com/owned").await["code"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let patch = server
+        .patch(&format!("/api/v1/links/{code}"))
+        .add_header("Host", "s.example.com")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(EVE)
+        .json(&json!({ "url": "https://evil.example" }))
+        .await;
+    assert_eq!(patch.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        delete(&server, EVE, &code).await.status_code(),
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(
+        delete(&server, BOB, &code).await.status_code(),
+        StatusCode::NO_CONTENT
+    );
+}
+
+#[sqlx::test]
+async fn test_dedupe_does_not_reuse_links_of_other_teams(pool: PgPool) {
+    let server = setup(pool).await;
+
+    let ada = shorten(&server, ADA, "https://example.com/shared").await;
+    let bob = shorten(&server, BOB, "https://example.com/shared").await;
+    let eve = shorten(&server, EVE, "https://example.com/shared").await;
+
+    assert_eq!(bob["code"], ada["code"]);
+    assert_eq!(bob["reused"], true);
+    assert_ne!(eve["code"], ada["code"]);
+    assert_eq!(eve["reused"], false);
+}
+
+#[sqlx::test]
+async fn test_user_token_is_limited_to_role_scopes(pool: PgPool) {
+    let user_id = common::create_test_user(&pool, "val@example.com", Role::Viewer, None).await;
+    common::create_user_api_token(&pool, "viewer", "ownership-viewer-token", user_id).await;
+    let server = setup(pool).await;
+
+    let shorten = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer("ownership-viewer-token")
+        .json(&json!({ "urls": [{ "url": "https://example.com" }] }))
+        .await;
+
+    assert_eq!(shorten.status_code(), StatusCode::FORBIDDEN);
+}
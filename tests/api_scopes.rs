@@ -0,0 +1,119 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::domain::entities::Scope;
+
+const TOKEN: &str = "api-scopes-token";
+
+#[sqlx::test]
+async fn test_missing_scope_is_forbidden(pool: PgPool) {
+    common::create_scoped_api_token(&pool, "reader", TOKEN, &[Scope::StatsRead], None).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let shorten = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.2.1")
+        .authorization_bearer(TOKEN)
+        .json(&json!({ "urls": [{ "url": "https://example.com" }] }))
+        .await;
+    let stats = server
+        .get("/api/v1/stats")
+        .add_header("X-Forwarded-For", "10.0.2.2")
+        .authorization_bearer(TOKEN)
+        .await;
+
+    assert_eq!(shorten.status_code(), StatusCode::FORBIDDEN);
+    let body = shorten.json::<Value>();
+    assert_eq!(body["error"]["code"], "forbidden");
+    assert_eq!(body["error"]["details"]["required_scope"], "links:write");
+    stats.assert_status_ok();
+}
+
+#[sqlx::test]
+async fn test_domain_restricted_token_only_uses_its_domains(pool: PgPool) {
+    let allowed = common::create_test_domain(&pool, "allowed.example").await;
+    common::create_test_domain(&pool, "other.example").await;
+    common::create_scoped_api_token(&pool, "tenant", TOKEN, &Scope::ALL, Some(&[allowed])).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let shorten = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.2.3")
+        .authorization_bearer(TOKEN)
+        .json(&json!({ "urls": [
+            { "url": "https://example.com/a", "domain": "allowed.example" },
+            { "url": "https://example.com/b", "domain": "other.example" }
+        ] }))
+        .await;
+    let domains = server
+        .get("/api/v1/domains")
+        .add_header("X-Forwarded-For", "10.0.2.4")
+        .authorization_bearer(TOKEN)
+        .await;
+    let stats = server
+        .get("/api/v1/stats")
+        .add_query_param("domain", "other.example")
+        .add_header("X-Forwarded-For", "10.0.2.5")
+        .authorization_bearer(TOKEN)
+        .await;
+
+    shorten.assert_status_ok();
+    let items = shorten.json::<Value>()["items"].clone();
+    assert!(items[0]["code"].is_string());
+    assert_eq!(items[1]["error"]["code"], "forbidden");
+
+    let domains = domains.json::<Value>();
+    let names: Vec<_> = domains["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["domain"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["allowed.example"]);
+
+    assert_eq!(stats.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_domain_admin_scope_required_for_domain_changes(pool: PgPool) {
+    let allowed = common::create_test_domain(&pool, "tenant.example").await;
+    common::create_scoped_api_token(
+        &pool,
+        "tenant-admin",
+        TOKEN,
+        &[Scope::DomainsAdmin],
+        Some(&[allowed]),
+    )
+    .await;
+    common::create_scoped_api_token(&pool, "writer", "writer-token", &[Scope::LinksWrite], None)
+        .await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let create = server
+        .post("/api/v1/domains")
+        .add_header("X-Forwarded-For", "10.0.2.6")
+        .authorization_bearer(TOKEN)
+        .json(&json!({ "domain": "new.example" }))
+        .await;
+    let update_own = server
+        .patch(&format!("/api/v1/domains/{allowed}"))
+        .add_header("X-Forwarded-For", "10.0.2.7")
+        .authorization_bearer(TOKEN)
+        .json(&json!({ "description": "Tenant domain" }))
+        .await;
+    let update_by_writer = server
+        .patch(&format!("/api/v1/domains/{allowed}"))
+        .add_header("X-Forwarded-For", "10.0.2.8")
+        .authorization_bearer("writer-token")
+        .json(&json!({ "description": "Hijacked" }))
+        .await;
+
+    assert_eq!(create.status_code(), StatusCode::FORBIDDEN);
+    update_own.assert_status_ok();
+    assert_eq!(update_by_writer.status_code(), StatusCode::FORBIDDEN);
+}
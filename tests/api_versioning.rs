@@ -0,0 +1,76 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::routes::app_router;
+
+const TOKEN: &str = "api-versioning-token";
+
+fn server(state: url_shortener::AppState) -> TestServer {
+    let sunset = Utc.with_ymd_and_hms(2027, 4, 30, 0, 0, 0).unwrap();
+    common::serve(app_router(state, true, sunset))
+}
+
+#[sqlx::test]
+async fn test_v1_routes_are_not_deprecated(pool: PgPool) {
+    common::create_test_api_token(&pool, "versioning", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = server(state);
+
+    let response = server
+        .get("/api/v1/domains")
+        .add_header("X-Forwarded-For", "10.0.1.1")
+        .authorization_bearer(TOKEN)
+        .await;
+
+    response.assert_status_ok();
+    assert!(response.maybe_header("deprecation").is_none());
+    assert!(response.maybe_header("sunset").is_none());
+}
+
+#[sqlx::test]
+async fn test_legacy_alias_serves_v1_with_deprecation_headers(pool: PgPool) {
+    common::create_test_api_token(&pool, "versioning", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = server(state);
+
+    let legacy = server
+        .get("/api/domains")
+        .add_query_param("page", 1)
+        .add_header("X-Forwarded-For", "10.0.1.2")
+        .authorization_bearer(TOKEN)
+        .await;
+    let v1 = server
+        .get("/api/v1/domains")
+        .add_query_param("page", 1)
+        .add_header("X-Forwarded-For", "10.0.1.3")
+        .authorization_bearer(TOKEN)
+        .await;
+
+    legacy.assert_status_ok();
+    assert_eq!(legacy.json::<Value>(), v1.json::<Value>());
+    assert_eq!(legacy.header("deprecation"), "@1792281600");
+    assert_eq!(legacy.header("sunset"), "Fri, 30 Apr 2027 00:00:00 GMT");
+    assert_eq!(
+        legacy.header("link"),
+        "</api/v1/domains?page=1>; rel=\"successor-version\""
+    );
+}
+
+#[sqlx::test]
+async fn test_legacy_alias_keeps_auth_and_errors(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let server = server(state);
+
+    let response = server
+        .post("/api/shorten")
+        .add_header("X-Forwarded-For", "10.0.1.4")
+        .json(&json!({ "urls": [{ "url": "https://example.com" }] }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header("deprecation"), "@1792281600");
+}
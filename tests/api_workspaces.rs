@@ -0,0 +1,185 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::infrastructure::cache::{CachedLink, CachedRedirect, NullCache, TieredCache};
+
+const ADMIN: &str = "workspaces-admin-token";
+const ACME: &str = "workspaces-acme-token";
+const ACME_HOST: &str = "go.acme.test";
+
+/// The admin token belongs to the default workspace; the acme token belongs
+/// to the `acme` workspace, whose default domain is [`ACME_HOST`].
+async fn setup(pool: PgPool) -> (TestServer, i64) {
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    common::create_workspace_domain(&pool, ACME_HOST, workspace_id).await;
+    common::create_test_api_token(&pool, "admin", ADMIN).await;
+    common::create_workspace_api_token(&pool, "acme", ACME, workspace_id).await;
+
+    let (state, _rx) = common::create_test_state(pool);
+    (common::test_server(state), workspace_id)
+}
+
+async fn shorten(server: &TestServer, token: &str, url: &str) -> Value {
+    let response = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(token)
+        .json(&json!({ "urls": [{ "url": url }] }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()
+}
+
+async fn list(server: &TestServer, token: &str, path: &str, field: &str) -> Vec<String> {
+    let body = server
+        .get(path)
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(token)
+        .await
+        .json::<Value>();
+
+    body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item[field].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[sqlx::test]
+async fn test_workspaces_do_not_see_each_other(pool: PgPool) {
+    let (server, _) = setup(pool).await;
+
+    let acme = shorten(&server, ACME, "https://example.com/acme").await["items"][0].clone();
+    let admin = shorten(&server, ADMIN, "https://example.com/admin").await["items"][0].clone();
+    let acme_code = acme["code"].as_str().unwrap().to_string();
+    let admin_code = admin["code"].as_str().unwrap().to_string();
+
+    assert!(acme["short_url"].as_str().unwrap().contains(ACME_HOST));
+
+    assert_eq!(
+        list(&server, ACME, "/api/v1/stats", "code").await,
+        vec![acme_code.clone()]
+    );
+    assert_eq!(
+        list(&server, ADMIN, "/api/v1/stats", "code").await,
+        vec![admin_code]
+    );
+    assert_eq!(
+        list(&server, ACME, "/api/v1/domains", "domain").await,
+        vec![ACME_HOST.to_string()]
+    );
+    assert!(
+        !list(&server, ADMIN, "/api/v1/domains", "domain")
+            .await
+            .contains(&ACME_HOST.to_string())
+    );
+    assert_eq!(
+        list(&server, ACME, "/api/v1/tokens", "name").await,
+        vec!["acme".to_string()]
+    );
+
+    let detail = server
+        .get(&format!("/api/v1/stats/{acme_code}"))
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(ADMIN)
+        .await;
+    assert_eq!(detail.status_code(), StatusCode::NOT_FOUND);
+
+    let delete = server
+        .delete(&format!("/api/v1/links/{acme_code}"))
+        .add_header("Host", ACME_HOST)
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(ADMIN)
+        .await;
+    assert_eq!(delete.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_link_quota_blocks_new_links(pool: PgPool) {
+    let (server, workspace_id) = setup(pool.clone()).await;
+    common::set_workspace_quotas(&pool, workspace_id, Some(1), None, None).await;
+
+    let first = shorten(&server, ACME, "https://example.com/one").await;
+    assert_eq!(first["summary"]["successful"], 1);
+
+    let second = shorten(&server, ACME, "https://example.com/two").await;
+    assert_eq!(second["summary"]["failed"], 1);
+
+    // The default workspace is not affected
+    let other = shorten(&server, ADMIN, "https://example.com/two").await;
+    assert_eq!(other["summary"]["successful"], 1);
+}
+
+#[sqlx::test]
+async fn test_domain_quota_blocks_new_domains(pool: PgPool) {
+    let (server, workspace_id) = setup(pool.clone()).await;
+    common::set_workspace_quotas(&pool, workspace_id, None, Some(1), None).await;
+
+    let response = server
+        .post("/api/v1/domains")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .authorization_bearer(ACME)
+        .json(&json!({ "domain": "links.acme.test" }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_exhausted_click_allowance_stops_redirects(pool: PgPool) {
+    let (server, workspace_id) = setup(pool.clone()).await;
+    // Quotas are cached once the workspace is used, so set them first.
+    common::set_workspace_quotas(&pool, workspace_id, None, None, Some(0)).await;
+
+    let code = shorten(&server, ACME, "https://example.com/clicks").await["items"][0]["code"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .get(&format!("/{code}"))
+        .add_header("Host", ACME_HOST)
+        .add_header("X-Forwarded-For", common::client_ip())
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_exhausted_click_allowance_stops_cached_redirects(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    common::create_workspace_domain(&pool, ACME_HOST, workspace_id).await;
+    common::set_workspace_quotas(&pool, workspace_id, None, None, Some(0)).await;
+    let cache = Arc::new(TieredCache::new(NullCache::new(), 1000, 300));
+    let (state, _rx) = common::create_test_state_with_cache(pool, cache);
+    let cached = CachedRedirect::Found(CachedLink {
+        url: "https://example.com/cached".to_string(),
+        permanent: false,
+        expires_at: None,
+        link_id: None,
+        domain_id: None,
+    });
+    state
+        .cache
+        .set_url(
+            &CachedRedirect::key(ACME_HOST, "cached"),
+            &cached.encode(),
+            Some(300),
+        )
+        .await
+        .unwrap();
+    let server = common::test_server(state);
+
+    let response = server
+        .get("/cached")
+        .add_header("Host", ACME_HOST)
+        .add_header("X-Forwarded-For", common::client_ip())
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
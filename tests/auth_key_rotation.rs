@@ -0,0 +1,84 @@
+mod common;
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::application::services::{AuthService, signing_key_id};
+use url_shortener::domain::entities::AuditContext;
+use url_shortener::domain::repositories::TokenRepository;
+use url_shortener::infrastructure::persistence::PgTokenRepository;
+
+fn service(pool: &PgPool, secrets: &[&str]) -> AuthService<PgTokenRepository> {
+    AuthService::new(
+        Arc::new(PgTokenRepository::new(Arc::new(pool.clone()))),
+        secrets.iter().map(|s| s.to_string()).collect(),
+    )
+}
+
+#[sqlx::test]
+async fn test_token_of_previous_secret_is_rehashed_on_use(pool: PgPool) {
+    let old = service(&pool, &["old-secret"]);
+    let issued = old
+        .create_token(
+            "ci",
+            None,
+            vec![],
+            None,
+            None,
+            None,
+            1,
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        issued.token.key_id(),
+        Some(signing_key_id("old-secret").as_str())
+    );
+
+    let rotated = service(&pool, &["new-secret", "old-secret"]);
+    rotated.authenticate(&issued.plaintext).await.unwrap();
+
+    let repo = PgTokenRepository::new(Arc::new(pool.clone()));
+    let stored = repo.find_by_id(issued.token.id).await.unwrap().unwrap();
+    assert_eq!(stored.token_hash, rotated.hash_token(&issued.plaintext));
+
+    // Once re-hashed, the old secret is no longer needed.
+    let new_only = service(&pool, &["new-secret"]);
+    assert!(new_only.authenticate(&issued.plaintext).await.is_ok());
+    assert!(old.authenticate(&issued.plaintext).await.is_err());
+}
+
+#[sqlx::test]
+async fn test_legacy_hash_without_key_id_is_rehashed_on_use(pool: PgPool) {
+    let id = common::create_test_api_token(&pool, "legacy", "legacy-token").await;
+    let service = service(&pool, &[common::TEST_SIGNING_SECRET]);
+
+    service.authenticate("legacy-token").await.unwrap();
+
+    let repo = PgTokenRepository::new(Arc::new(pool.clone()));
+    let stored = repo.find_by_id(id).await.unwrap().unwrap();
+    assert_eq!(stored.key_id(), Some(service.primary_key_id()));
+}
+
+#[sqlx::test]
+async fn test_removed_secret_no_longer_authenticates(pool: PgPool) {
+    let issued = service(&pool, &["old-secret"])
+        .create_token(
+            "ci",
+            None,
+            vec![],
+            None,
+            None,
+            None,
+            1,
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+
+    let result = service(&pool, &["new-secret"])
+        .authenticate(&issued.plaintext)
+        .await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use url_shortener::domain::click_event::ClickEvent;
+use url_shortener::domain::click_journal::ClickJournal;
+use url_shortener::domain::click_worker::{ClickWorkerConfig, replay_journal};
+use url_shortener::infrastructure::persistence::{
+    PgDomainRepository, PgLinkRepository, PgStatsRepository,
+};
+
+/// A journal path in a fresh temporary directory, removed when dropped.
+fn journal_path() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("clicks.journal");
+    (dir, path)
+}
+
+async fn replay(pool: &PgPool, journal: &ClickJournal) -> u64 {
+    let pool = Arc::new(pool.clone());
+    replay_journal(
+        journal,
+        &PgStatsRepository::new(pool.clone()),
+        &PgDomainRepository::new(pool.clone()),
+        &PgLinkRepository::new(pool),
+        &ClickWorkerConfig::new(1).with_batching(2, std::time::Duration::ZERO),
+    )
+    .await
+    .unwrap()
+}
+
+#[sqlx::test]
+async fn test_replay_records_spilled_clicks_with_their_time(pool: PgPool) {
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "journaled", "https://example.com", domain_id).await;
+
+    let clicked_at = Utc::now() - Duration::days(3);
+    let (_dir, path) = journal_path();
+    let journal = ClickJournal::open(path, 1 << 20).unwrap();
+    let events: Vec<ClickEvent> = (0..3)
+        .map(|i| {
+            let mut event = ClickEvent::new(
+                "s.example.com".to_string(),
+                "journaled".to_string(),
+                Some(format!("10.0.0.{i}")),
+                None,
+                None,
+            );
+            event.clicked_at = clicked_at;
+            event
+        })
+        .chain([ClickEvent::new(
+            "s.example.com".to_string(),
+            "unknown".to_string(),
+            None,
+            None,
+            None,
+        )])
+        .collect();
+    assert!(journal.spill(&events, "db_unavailable"));
+    journal.flush().await;
+
+    let recorded = replay(&pool, &journal).await;
+
+    assert_eq!(recorded, 3);
+    let times: Vec<chrono::DateTime<Utc>> = sqlx::query_scalar!(
+        "SELECT lc.clicked_at FROM link_clicks lc JOIN links l ON l.id = lc.link_id WHERE l.code = 'journaled'"
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(times.len(), 3);
+    assert!(
+        times
+            .iter()
+            .all(|t| (*t - clicked_at).num_milliseconds().abs() < 1)
+    );
+
+    // Nothing is left to replay, so nothing is recorded twice.
+    assert_eq!(replay(&pool, &journal).await, 0);
+}
@@ -1,14 +1,54 @@
 #![allow(dead_code)]
 
+use axum::extract::Request;
+use axum::{Router, ServiceExt};
+use axum_test::TestServer;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
 use tokio::sync::mpsc;
-use url_shortener::application::services::{AuthService, DomainService, LinkService, StatsService};
-use url_shortener::infrastructure::cache::NullCache;
+use tower_http::normalize_path::NormalizePath;
+use url_shortener::application::services::{
+    AuditService, AuthService, CacheAdminService, DomainService, IdempotencyService,
+    LinkHistoryService, LinkService, SessionService, StatsService,
+};
+use url_shortener::domain::entities::{Actor, Principal, Role, Scope};
+use url_shortener::infrastructure::cache::{CacheService, NullCache, RedirectCachePolicy};
+use url_shortener::infrastructure::domain_registry::DomainRegistry;
 use url_shortener::infrastructure::persistence::{
-    PgDomainRepository, PgLinkRepository, PgStatsRepository, PgTokenRepository,
+    PgAuditRepository, PgDomainRepository, PgIdempotencyRepository, PgLinkRepository,
+    PgLinkVersionRepository, PgSessionRepository, PgStatsRepository, PgTokenRepository,
+    PgWorkspaceRepository,
 };
+use url_shortener::infrastructure::workspace_cache::WorkspaceCache;
+use url_shortener::routes::app_router;
 use url_shortener::state::AppState;
+use url_shortener::utils::single_flight::SingleFlight;
+
+/// Signing secret used by [`create_test_state`]'s `AuthService`.
+pub const TEST_SIGNING_SECRET: &str = "test-signing-secret";
+
+/// Serves the application router for `state` behind a proxy, as deployed.
+pub fn test_server(state: AppState) -> TestServer {
+    serve(app_router(state, true, Utc::now()))
+}
+
+/// Serves `app` with the peer address available, as `axum::serve` does.
+pub fn serve(app: NormalizePath<Router>) -> TestServer {
+    TestServer::new(ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(app))
+        .unwrap()
+}
+
+/// A distinct client address per request, so rate limits never interfere.
+pub fn client_ip() -> String {
+    static NEXT: AtomicU16 = AtomicU16::new(1);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    format!("10.1.{}.{}", n >> 8, n & 0xff)
+}
 
 pub async fn create_test_domain(pool: &PgPool, name: &str) -> i64 {
     sqlx::query_scalar!(
@@ -20,8 +60,70 @@ pub async fn create_test_domain(pool: &PgPool, name: &str) -> i64 {
     .unwrap()
 }
 
+pub async fn create_test_workspace(pool: &PgPool, name: &str) -> i64 {
+    sqlx::query_scalar!(
+        "INSERT INTO workspaces (name) VALUES ($1) RETURNING id",
+        name
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Creates the default domain of a workspace.
+pub async fn create_workspace_domain(pool: &PgPool, name: &str, workspace_id: i64) -> i64 {
+    sqlx::query_scalar!(
+        "INSERT INTO domains (domain, is_default, workspace_id) VALUES ($1, true, $2) RETURNING id",
+        name,
+        workspace_id
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+pub async fn set_workspace_quotas(
+    pool: &PgPool,
+    workspace_id: i64,
+    max_links: Option<i64>,
+    max_domains: Option<i64>,
+    monthly_click_allowance: Option<i64>,
+) {
+    sqlx::query!(
+        "UPDATE workspaces SET max_links = $2, max_domains = $3, monthly_click_allowance = $4 WHERE id = $1",
+        workspace_id,
+        max_links,
+        max_domains,
+        monthly_click_allowance
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Stores an API token whose raw value is `token`, in a workspace.
+pub async fn create_workspace_api_token(
+    pool: &PgPool,
+    name: &str,
+    token: &str,
+    workspace_id: i64,
+) -> i64 {
+    let id = create_test_api_token(pool, name, token).await;
+
+    sqlx::query!(
+        "UPDATE api_tokens SET workspace_id = $2 WHERE id = $1",
+        id,
+        workspace_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
 pub async fn get_default_domain(pool: &PgPool) -> i64 {
-    sqlx::query_scalar!("SELECT id FROM domains WHERE is_default = true LIMIT 1")
+    sqlx::query_scalar!("SELECT id FROM domains WHERE is_default = true AND workspace_id = 1")
         .fetch_one(pool)
         .await
         .unwrap()
@@ -29,7 +131,7 @@ pub async fn get_default_domain(pool: &PgPool) -> i64 {
 
 pub async fn create_test_link(pool: &PgPool, code: &str, url: &str, domain_id: i64) {
     sqlx::query!(
-        "INSERT INTO links (code, long_url, domain_id) VALUES ($1, $2, $3)",
+        "INSERT INTO links (code, long_url, domain_id, workspace_id) VALUES ($1, $2, $3, (SELECT workspace_id FROM domains WHERE id = $3))",
         code,
         url,
         domain_id
@@ -41,7 +143,7 @@ pub async fn create_test_link(pool: &PgPool, code: &str, url: &str, domain_id: i
 
 pub async fn create_deleted_link(pool: &PgPool, code: &str, url: &str, domain_id: i64) {
     sqlx::query!(
-        "INSERT INTO links (code, long_url, domain_id, deleted_at) VALUES ($1, $2, $3, NOW())",
+        "INSERT INTO links (code, long_url, domain_id, deleted_at, workspace_id) VALUES ($1, $2, $3, NOW(), (SELECT workspace_id FROM domains WHERE id = $3))",
         code,
         url,
         domain_id
@@ -53,7 +155,7 @@ pub async fn create_deleted_link(pool: &PgPool, code: &str, url: &str, domain_id
 
 pub async fn create_expired_link(pool: &PgPool, code: &str, url: &str, domain_id: i64) {
     sqlx::query!(
-        "INSERT INTO links (code, long_url, domain_id, expires_at) VALUES ($1, $2, $3, NOW() - INTERVAL '1 hour')",
+        "INSERT INTO links (code, long_url, domain_id, expires_at, workspace_id) VALUES ($1, $2, $3, NOW() - INTERVAL '1 hour', (SELECT workspace_id FROM domains WHERE id = $3))",
         code,
         url,
         domain_id
@@ -65,7 +167,7 @@ pub async fn create_expired_link(pool: &PgPool, code: &str, url: &str, domain_id
 
 pub async fn create_permanent_link(pool: &PgPool, code: &str, url: &str, domain_id: i64) {
     sqlx::query!(
-        "INSERT INTO links (code, long_url, domain_id, permanent) VALUES ($1, $2, $3, TRUE)",
+        "INSERT INTO links (code, long_url, domain_id, permanent, workspace_id) VALUES ($1, $2, $3, TRUE, (SELECT workspace_id FROM domains WHERE id = $3))",
         code,
         url,
         domain_id
@@ -75,46 +177,206 @@ pub async fn create_permanent_link(pool: &PgPool, code: &str, url: &str, domain_
     .unwrap();
 }
 
+/// Records a click and counts it in `links.clicks`, like a click whose
+/// counter has been flushed.
 pub async fn create_test_click(pool: &PgPool, link_id: i64, ip: &str) {
-    sqlx::query!(
-        "INSERT INTO link_clicks (link_id, ip) VALUES ($1, $2)",
+    let click_id = sqlx::query_scalar!(
+        "INSERT INTO link_clicks (link_id, ip) VALUES ($1, $2) RETURNING id",
         link_id,
         ip
     )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE links SET clicks = clicks + 1 WHERE id = $1",
+        link_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE click_counter_watermark SET counted_up_to = GREATEST(counted_up_to, $1)",
+        click_id
+    )
     .execute(pool)
     .await
     .unwrap();
 }
 
+/// Stores an API token whose raw value is `token`, hashed like `AuthService` does.
+pub async fn create_test_api_token(pool: &PgPool, name: &str, token: &str) -> i64 {
+    let mut mac = Hmac::<Sha256>::new_from_slice(TEST_SIGNING_SECRET.as_bytes()).unwrap();
+    mac.update(token.as_bytes());
+    let token_hash = hex::encode(mac.finalize().into_bytes());
+
+    sqlx::query_scalar!(
+        "INSERT INTO api_tokens (name, token_hash) VALUES ($1, $2) RETURNING id",
+        name,
+        token_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Stores an API token limited to `scopes` and, optionally, `domain_ids`.
+pub async fn create_scoped_api_token(
+    pool: &PgPool,
+    name: &str,
+    token: &str,
+    scopes: &[Scope],
+    domain_ids: Option<&[i64]>,
+) -> i64 {
+    let id = create_test_api_token(pool, name, token).await;
+    let scopes: Vec<String> = scopes.iter().map(|s| s.as_str().to_string()).collect();
+
+    sqlx::query!(
+        "UPDATE api_tokens SET scopes = $2, domain_ids = $3 WHERE id = $1",
+        id,
+        &scopes,
+        domain_ids
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
+pub async fn create_test_team(pool: &PgPool, name: &str) -> i64 {
+    sqlx::query_scalar!("INSERT INTO teams (name) VALUES ($1) RETURNING id", name)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+pub async fn create_test_user(pool: &PgPool, email: &str, role: Role, team_id: Option<i64>) -> i64 {
+    sqlx::query_scalar!(
+        "INSERT INTO users (email, role, team_id) VALUES ($1, $2, $3) RETURNING id",
+        email,
+        role.as_str(),
+        team_id
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+pub async fn set_link_owner(pool: &PgPool, code: &str, owner_id: i64, team_id: Option<i64>) {
+    sqlx::query!(
+        "UPDATE links SET owner_id = $2, team_id = $3 WHERE code = $1",
+        code,
+        owner_id,
+        team_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Stores an API token whose raw value is `token`, bound to a user.
+pub async fn create_user_api_token(pool: &PgPool, name: &str, token: &str, user_id: i64) -> i64 {
+    let id = create_test_api_token(pool, name, token).await;
+
+    sqlx::query!(
+        "UPDATE api_tokens SET user_id = $2 WHERE id = $1",
+        id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
+/// Principal with every scope, for handler tests mounted without the auth layer.
+pub fn full_access_principal() -> Principal {
+    Principal {
+        actor: Actor::Token {
+            id: 1,
+            name: "test".to_string(),
+        },
+        scopes: Scope::ALL.to_vec(),
+        domain_ids: None,
+        user: None,
+        workspace_id: 1,
+    }
+}
+
 pub fn create_test_state(
     pool: PgPool,
 ) -> (
     AppState,
     mpsc::Receiver<url_shortener::domain::click_event::ClickEvent>,
+) {
+    create_test_state_with_cache(pool, Arc::new(NullCache))
+}
+
+/// Like [`create_test_state`], with `cache` used by redirects and cache administration.
+pub fn create_test_state_with_cache(
+    pool: PgPool,
+    cache: Arc<dyn CacheService>,
+) -> (
+    AppState,
+    mpsc::Receiver<url_shortener::domain::click_event::ClickEvent>,
 ) {
     let pool = Arc::new(pool);
     let (tx, rx) = mpsc::channel(100);
 
     let link_repo = Arc::new(PgLinkRepository::new(pool.clone()));
-    let domain_repo = Arc::new(PgDomainRepository::new(pool.clone()));
+    let domain_repo = Arc::new(DomainRegistry::new(Arc::new(PgDomainRepository::new(
+        pool.clone(),
+    ))));
     let stats_repo = Arc::new(PgStatsRepository::new(pool.clone()));
     let token_repo = Arc::new(PgTokenRepository::new(pool.clone()));
+    let idempotency_repo = Arc::new(PgIdempotencyRepository::new(pool.clone()));
+    let session_repo = Arc::new(PgSessionRepository::new(pool.clone()));
+    let workspace_repo = Arc::new(PgWorkspaceRepository::new(pool.clone()));
+    let audit_repo = Arc::new(PgAuditRepository::new(pool.clone()));
+    let link_version_repo = Arc::new(PgLinkVersionRepository::new(pool.clone()));
 
-    let link_service = Arc::new(LinkService::new(link_repo, domain_repo.clone()));
-    let domain_service = Arc::new(DomainService::new(domain_repo));
+    let link_service = Arc::new(LinkService::new(
+        link_repo,
+        domain_repo.clone(),
+        Arc::new(WorkspaceCache::new(workspace_repo.clone())),
+    ));
+    let domain_service = Arc::new(DomainService::new(domain_repo, workspace_repo));
+    let cache_admin_service = Arc::new(CacheAdminService::new(cache.clone(), stats_repo.clone()));
     let stats_service = Arc::new(StatsService::new(stats_repo));
+    let idempotency_service = Arc::new(IdempotencyService::new(idempotency_repo));
     let auth_service = Arc::new(AuthService::new(
         token_repo,
-        "test-signing-secret".to_string(),
+        vec![TEST_SIGNING_SECRET.to_string()],
+    ));
+    let session_service = Arc::new(SessionService::new(
+        session_repo,
+        vec![TEST_SIGNING_SECRET.to_string()],
+        chrono::Duration::hours(12),
     ));
+    let audit_service = Arc::new(AuditService::new(audit_repo));
+    let link_history_service = Arc::new(LinkHistoryService::new(link_version_repo));
 
     let state = AppState {
         link_service,
         stats_service,
         auth_service,
         domain_service,
-        cache: Arc::new(NullCache),
+        idempotency_service,
+        session_service,
+        audit_service,
+        link_history_service,
+        cache_admin_service,
+        sso_service: None,
+        cache,
+        redirect_cache: RedirectCachePolicy {
+            negative_ttl: Some(30),
+            ..Default::default()
+        },
+        redirect_lookups: Arc::new(SingleFlight::new("redirect")),
         click_sender: tx,
+        click_journal: None,
     };
 
     (state, rx)
@@ -0,0 +1,139 @@
+mod common;
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::{
+    AuditContext, DEFAULT_WORKSPACE_ID, NewDomain, UpdateDomain,
+};
+use url_shortener::domain::repositories::DomainRepository;
+use url_shortener::infrastructure::domain_registry::DomainRegistry;
+use url_shortener::infrastructure::persistence::PgDomainRepository;
+
+fn registry(pool: PgPool) -> DomainRegistry {
+    DomainRegistry::new(Arc::new(PgDomainRepository::new(Arc::new(pool))))
+}
+
+fn new_domain(name: &str) -> NewDomain {
+    NewDomain {
+        domain: name.to_string(),
+        is_default: false,
+        description: None,
+        workspace_id: DEFAULT_WORKSPACE_ID,
+    }
+}
+
+#[sqlx::test]
+async fn test_reload_loads_existing_domains(pool: PgPool) {
+    common::create_test_domain(&pool, "loaded.example.com").await;
+    let registry = registry(pool);
+
+    let count = registry.reload().await.unwrap();
+
+    assert!(count >= 2);
+    let found = registry.find_by_name("loaded.example.com").await.unwrap();
+    assert!(found.is_some());
+}
+
+#[sqlx::test]
+async fn test_rename_is_visible_at_once(pool: PgPool) {
+    let registry = registry(pool);
+    let domain = registry
+        .create(new_domain("old.example.com"), &AuditContext::cli())
+        .await
+        .unwrap();
+    registry.find_by_name("old.example.com").await.unwrap();
+
+    registry
+        .update(
+            domain.id,
+            UpdateDomain {
+                domain: Some("new.example.com".to_string()),
+                ..Default::default()
+            },
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        registry
+            .find_by_name("old.example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let renamed = registry.find_by_name("new.example.com").await.unwrap();
+    assert_eq!(renamed.unwrap().id, domain.id);
+}
+
+#[sqlx::test]
+async fn test_deleted_domain_is_reported_deleted(pool: PgPool) {
+    let registry = registry(pool);
+    let domain = registry
+        .create(new_domain("gone.example.com"), &AuditContext::cli())
+        .await
+        .unwrap();
+    registry.find_by_name("gone.example.com").await.unwrap();
+
+    registry
+        .delete(domain.id, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    let found = registry.find_by_name("gone.example.com").await.unwrap();
+    assert!(found.unwrap().is_deleted());
+}
+
+#[sqlx::test]
+async fn test_external_change_is_visible_after_reload(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "shared.example.com").await;
+    let registry = registry(pool.clone());
+    registry.find_by_name("shared.example.com").await.unwrap();
+
+    // Another instance deactivates the domain.
+    sqlx::query!(
+        "UPDATE domains SET is_active = FALSE WHERE id = $1",
+        domain_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let stale = registry.find_by_name("shared.example.com").await.unwrap();
+    assert!(stale.unwrap().is_active);
+
+    registry.reload().await.unwrap();
+    let fresh = registry.find_by_name("shared.example.com").await.unwrap();
+    assert!(!fresh.unwrap().is_active);
+}
+
+#[sqlx::test]
+async fn test_domain_created_elsewhere_is_found_after_reload(pool: PgPool) {
+    let registry = registry(pool.clone());
+    assert!(
+        registry
+            .find_by_name("later.example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Another instance creates the domain; this one still remembers it as unknown.
+    common::create_test_domain(&pool, "later.example.com").await;
+    assert!(
+        registry
+            .find_by_name("later.example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    registry.reload().await.unwrap();
+    assert!(
+        registry
+            .find_by_name("later.example.com")
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
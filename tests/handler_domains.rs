@@ -1,7 +1,7 @@
 mod common;
 
 use axum::{
-    Router,
+    Extension, Router,
     routing::{delete, get, patch, post},
 };
 use axum_test::TestServer;
@@ -18,6 +18,7 @@ fn make_server(pool: PgPool) -> TestServer {
         .route("/api/domains", post(create_domain_handler))
         .route("/api/domains/{id}", patch(update_domain_handler))
         .route("/api/domains/{id}", delete(delete_domain_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
     TestServer::new(app).unwrap()
 }
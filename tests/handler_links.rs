@@ -1,7 +1,7 @@
 mod common;
 
 use axum::{
-    Router,
+    Extension, Router,
     routing::{delete, patch},
 };
 use axum_test::TestServer;
@@ -19,6 +19,7 @@ fn make_server(pool: PgPool) -> TestServer {
     let app = Router::new()
         .route("/api/links/{code}", patch(update_link_handler))
         .route("/api/links/{code}", delete(delete_link_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
     TestServer::new(app).unwrap()
 }
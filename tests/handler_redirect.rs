@@ -1,12 +1,24 @@
 mod common;
 
+use async_trait::async_trait;
+use axum::http::StatusCode;
 use axum::{Router, extract::ConnectInfo, routing::get};
 use axum_test::TestServer;
-use axum::http::StatusCode;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Barrier;
 use tower::Layer;
 use url_shortener::api::handlers::redirect_handler;
+use url_shortener::domain::click_event::ClickEvent;
+use url_shortener::domain::click_journal::ClickJournal;
+use url_shortener::infrastructure::cache::{
+    CacheEntry, CacheResult, CacheService, CacheStats, CachedLink, CachedRedirect, NullCache,
+    TieredCache,
+};
 
 #[derive(Clone)]
 struct MockConnectInfoLayer;
@@ -116,6 +128,47 @@ async fn test_redirect_records_click(pool: PgPool) {
     assert_eq!(click_event.unwrap().code, "clickme");
 }
 
+#[sqlx::test]
+async fn test_redirect_spills_click_when_queue_is_full(pool: PgPool) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let journal = Arc::new(ClickJournal::open(dir.path().join("clicks.journal"), 1 << 20).unwrap());
+    let (state, _rx) = common::create_test_state(pool.clone());
+    let state = state.with_click_journal(Some(journal.clone()));
+    while state
+        .click_sender
+        .try_send(ClickEvent::new(
+            "s.example.com".to_string(),
+            "filler".to_string(),
+            None,
+            None,
+            None,
+        ))
+        .is_ok()
+    {}
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "spillme", "https://example.com", domain_id).await;
+
+    let response = server
+        .get("/spillme")
+        .add_header("Host", "s.example.com")
+        .await;
+
+    assert_eq!(response.status_code(), 307);
+    journal.flush().await;
+    let mut pending = journal.take_pending().unwrap().unwrap();
+    let (events, _) = pending.next_batch(10).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].code, "spillme");
+    assert!(events[0].link_id.is_some());
+    pending.finish().unwrap();
+}
+
 #[sqlx::test]
 async fn test_redirect_with_user_agent_and_referer(pool: PgPool) {
     let (state, mut rx) = common::create_test_state(pool.clone());
@@ -223,3 +276,284 @@ async fn test_redirect_permanent_link_returns_308(pool: PgPool) {
     let location = response.header("location");
     assert_eq!(location, "https://example.com/dest");
 }
+
+/// State whose cache keeps entries in memory.
+fn caching_state(pool: PgPool) -> url_shortener::state::AppState {
+    let (mut state, _rx) = common::create_test_state(pool);
+    state.cache = Arc::new(TieredCache::new(NullCache::new(), 100, 60));
+    state
+}
+
+/// Waits for the redirect handler's background cache write of `key`.
+async fn wait_for_cached(state: &url_shortener::state::AppState, key: &str) -> String {
+    for _ in 0..100 {
+        if let Some(value) = state.cache.get_url(key).await.unwrap() {
+            return value;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("{key} was not cached");
+}
+
+#[sqlx::test]
+async fn test_redirect_caches_unknown_code(pool: PgPool) {
+    let state = caching_state(pool.clone());
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    let first = server
+        .get("/ghost")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(first.status_code(), StatusCode::NOT_FOUND);
+    assert!(matches!(
+        CachedRedirect::parse(&wait_for_cached(&state, "s.example.com:ghost").await),
+        Some(CachedRedirect::NotFound { .. })
+    ));
+
+    // Inserted behind the service's back, so the cached 404 still answers.
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "ghost", "https://example.com/ghost", domain_id).await;
+    let cached = server
+        .get("/ghost")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(cached.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(cached.text(), first.text());
+
+    state.cache.invalidate("s.example.com:ghost").await.unwrap();
+    let found = server
+        .get("/ghost")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(found.status_code(), StatusCode::TEMPORARY_REDIRECT);
+}
+
+#[sqlx::test]
+async fn test_redirect_caches_deleted_link(pool: PgPool) {
+    let state = caching_state(pool.clone());
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_deleted_link(&pool, "gone1", "https://example.com/gone", domain_id).await;
+
+    let first = server
+        .get("/gone1")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(first.status_code(), StatusCode::GONE);
+    assert_eq!(
+        CachedRedirect::parse(&wait_for_cached(&state, "s.example.com:gone1").await),
+        Some(CachedRedirect::Deleted)
+    );
+
+    sqlx::query!("UPDATE links SET deleted_at = NULL WHERE code = 'gone1'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let cached = server
+        .get("/gone1")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(cached.status_code(), StatusCode::GONE);
+    assert_eq!(cached.text(), first.text());
+}
+
+/// In-memory cache reporting a fixed TTL and counting writes.
+///
+/// The first `gate` lookups wait for each other, so that many requests miss
+/// the cache at the same moment.
+struct MemoryCache {
+    values: Mutex<HashMap<String, String>>,
+    ttl: Duration,
+    sets: AtomicUsize,
+    gets: AtomicUsize,
+    gate: Barrier,
+    gate_size: usize,
+}
+
+impl MemoryCache {
+    fn new(ttl: Duration, gate: usize) -> Self {
+        Self {
+            values: Mutex::new(HashMap::new()),
+            ttl,
+            sets: AtomicUsize::new(0),
+            gets: AtomicUsize::new(0),
+            gate: Barrier::new(gate),
+            gate_size: gate,
+        }
+    }
+}
+
+#[async_trait]
+impl CacheService for MemoryCache {
+    async fn get_entry(&self, key: &str) -> CacheResult<Option<CacheEntry>> {
+        if self.gets.fetch_add(1, Ordering::SeqCst) < self.gate_size {
+            self.gate.wait().await;
+        }
+        let value = self.values.lock().unwrap().get(key).cloned();
+        Ok(value.map(|value| CacheEntry {
+            value,
+            ttl: Some(self.ttl),
+        }))
+    }
+
+    async fn set_url(&self, key: &str, value: &str, _: Option<usize>) -> CacheResult<()> {
+        self.sets.fetch_add(1, Ordering::SeqCst);
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn invalidate(&self, key: &str) -> CacheResult<()> {
+        self.values.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn purge_prefix(&self, prefix: &str) -> CacheResult<u64> {
+        let mut values = self.values.lock().unwrap();
+        let before = values.len();
+        values.retain(|key, _| !key.starts_with(prefix));
+        Ok((before - values.len()) as u64)
+    }
+
+    async fn try_lock(&self, _: &str, _: Duration) -> CacheResult<Option<String>> {
+        Ok(Some(String::new()))
+    }
+
+    async fn unlock(&self, _: &str, _: &str) -> CacheResult<()> {
+        Ok(())
+    }
+
+    async fn stats(&self) -> CacheResult<CacheStats> {
+        Ok(CacheStats {
+            backend: "memory",
+            keys: Some(self.values.lock().unwrap().len() as u64),
+            ..Default::default()
+        })
+    }
+
+    async fn health_check(&self) -> bool {
+        true
+    }
+}
+
+#[sqlx::test]
+async fn test_concurrent_misses_share_one_lookup(pool: PgPool) {
+    let cache = Arc::new(MemoryCache::new(Duration::from_secs(3600), 10));
+    let (mut state, _rx) = common::create_test_state(pool.clone());
+    state.cache = cache.clone();
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "hot", "https://example.com/hot", domain_id).await;
+
+    let responses = futures_util::future::join_all((0..10).map(|_| {
+        server
+            .get("/hot")
+            .add_header("Host", "s.example.com")
+            .into_future()
+    }))
+    .await;
+
+    for response in responses {
+        assert_eq!(response.status_code(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(response.header("location"), "https://example.com/hot");
+    }
+    wait_for_cached(&state, "s.example.com:hot").await;
+    assert_eq!(cache.sets.load(Ordering::SeqCst), 1);
+}
+
+/// Waits until `key` holds a current-format entry redirecting to `url`.
+async fn wait_for_url(cache: &MemoryCache, key: &str, url: &str) -> CachedLink {
+    for _ in 0..100 {
+        let value = cache.values.lock().unwrap().get(key).cloned();
+        if let Some(CachedRedirect::Found(link)) = value.as_deref().and_then(CachedRedirect::parse)
+            && link.url == url
+            && !link.is_legacy()
+        {
+            return link;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("{key} was not cached with {url}");
+}
+
+#[sqlx::test]
+async fn test_redirect_refreshes_entry_ahead_of_expiry(pool: PgPool) {
+    let cache = Arc::new(MemoryCache::new(Duration::from_secs(5), 0));
+    let (mut state, _rx) = common::create_test_state(pool.clone());
+    state.cache = cache.clone();
+    state.redirect_cache.refresh_ahead = Some(Duration::from_secs(60));
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "warm", "https://example.com/new", domain_id).await;
+    let stale = CachedRedirect::Found(CachedLink {
+        url: "https://example.com/old".to_string(),
+        permanent: false,
+        expires_at: None,
+        link_id: Some(1),
+        domain_id: Some(domain_id),
+    });
+    cache
+        .set_url("s.example.com:warm", &stale.encode(), None)
+        .await
+        .unwrap();
+
+    // The entry is about to expire: it still answers, and is renewed behind it.
+    let response = server
+        .get("/warm")
+        .add_header("Host", "s.example.com")
+        .await;
+    assert_eq!(response.header("location"), "https://example.com/old");
+
+    wait_for_url(&cache, "s.example.com:warm", "https://example.com/new").await;
+}
+
+#[sqlx::test]
+async fn test_redirect_upgrades_legacy_entry(pool: PgPool) {
+    let cache = Arc::new(MemoryCache::new(Duration::from_secs(3600), 0));
+    let (mut state, mut rx) = common::create_test_state(pool.clone());
+    state.cache = cache.clone();
+    let app = Router::new()
+        .route("/{code}", get(redirect_handler))
+        .layer(MockConnectInfoLayer)
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "old", "https://example.com/old", domain_id).await;
+    cache
+        .set_url("s.example.com:old", "1:https://example.com/old", None)
+        .await
+        .unwrap();
+
+    let response = server.get("/old").add_header("Host", "s.example.com").await;
+    assert_eq!(response.status_code(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(rx.recv().await.unwrap().link_id, None);
+
+    let link = wait_for_url(&cache, "s.example.com:old", "https://example.com/old").await;
+    let link_id = link.link_id.expect("entry upgraded with the link ID");
+    assert_eq!(link.domain_id, Some(domain_id));
+
+    server.get("/old").add_header("Host", "s.example.com").await;
+    assert_eq!(rx.recv().await.unwrap().link_id, Some(link_id));
+}
@@ -1,16 +1,21 @@
 mod common;
 
-use axum::{Router, routing::post};
+use axum::http::StatusCode;
+use axum::{Extension, Router, routing::post};
 use axum_test::TestServer;
 use serde_json::json;
 use sqlx::PgPool;
+use std::sync::Arc;
 use url_shortener::api::handlers::shorten_handler;
+use url_shortener::domain::entities::{Actor, Principal};
+use url_shortener::infrastructure::cache::{CachedRedirect, NullCache, TieredCache};
 
 #[sqlx::test]
 async fn test_shorten_single_url_success(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -45,6 +50,7 @@ async fn test_shorten_with_custom_code(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -75,6 +81,7 @@ async fn test_shorten_multiple_urls(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -106,6 +113,7 @@ async fn test_shorten_deduplication(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -129,6 +137,123 @@ async fn test_shorten_deduplication(pool: PgPool) {
     let json2 = response2.json::<serde_json::Value>();
     let code2 = json2["items"][0]["code"].as_str().unwrap();
     assert_eq!(code1, code2);
+    assert_eq!(json1["items"][0]["reused"], false);
+    assert_eq!(json2["items"][0]["reused"], true);
+}
+
+#[sqlx::test]
+async fn test_shorten_reuse_respects_requested_settings(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+    let first = server
+        .post("/api/shorten")
+        .json(&json!({ "urls": [{ "url": "https://settings.com" }] }))
+        .await
+        .json::<serde_json::Value>();
+
+    let response = server
+        .post("/api/shorten")
+        .json(&json!({
+            "urls": [
+                { "url": "https://settings.com", "permanent": true },
+                { "url": "https://settings.com", "custom_code": "settings-code" }
+            ]
+        }))
+        .await;
+
+    let json = response.json::<serde_json::Value>();
+    assert_eq!(json["summary"]["successful"], 2);
+    assert_eq!(json["items"][0]["reused"], false);
+    assert_ne!(json["items"][0]["code"], first["items"][0]["code"]);
+    assert_eq!(json["items"][1]["reused"], false);
+    assert_eq!(json["items"][1]["code"], "settings-code");
+}
+
+#[sqlx::test]
+async fn test_shorten_dedupe_always_new(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+    let body = json!({ "urls": [{ "url": "https://always-new.com", "dedupe": "always_new" }] });
+
+    let json1 = server
+        .post("/api/shorten")
+        .json(&body)
+        .await
+        .json::<serde_json::Value>();
+    let json2 = server
+        .post("/api/shorten")
+        .json(&body)
+        .await
+        .json::<serde_json::Value>();
+
+    assert_eq!(json2["summary"]["successful"], 1);
+    assert_ne!(json1["items"][0]["code"], json2["items"][0]["code"]);
+    assert_eq!(json2["items"][0]["reused"], false);
+}
+
+#[sqlx::test]
+async fn test_shorten_dedupe_error(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+    server
+        .post("/api/shorten")
+        .json(&json!({ "urls": [{ "url": "https://strict.com" }] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/shorten")
+        .json(&json!({ "urls": [{ "url": "https://strict.com", "dedupe": "error" }] }))
+        .await;
+
+    let json = response.json::<serde_json::Value>();
+    assert_eq!(json["summary"]["failed"], 1);
+    assert_eq!(json["items"][0]["error"]["code"], "conflict");
+}
+
+#[sqlx::test]
+async fn test_shorten_same_url_on_two_domains(pool: PgPool) {
+    common::create_test_domain(&pool, "other.example.com").await;
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+    let response = server
+        .post("/api/shorten")
+        .json(&json!({
+            "urls": [
+                { "url": "https://two-domains.com" },
+                { "url": "https://two-domains.com", "domain": "other.example.com" }
+            ]
+        }))
+        .await;
+
+    let json = response.json::<serde_json::Value>();
+    assert_eq!(json["summary"]["successful"], 2);
+    assert!(
+        json["items"][1]["short_url"]
+            .as_str()
+            .unwrap()
+            .contains("other.example.com")
+    );
 }
 
 #[sqlx::test]
@@ -136,6 +261,7 @@ async fn test_shorten_invalid_url(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -156,6 +282,7 @@ async fn test_shorten_custom_code_conflict(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -198,6 +325,7 @@ async fn test_shorten_mixed_success_and_failure(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -222,6 +350,7 @@ async fn test_shorten_url_normalization(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -249,6 +378,7 @@ async fn test_shorten_partial_failure_reserved_custom_code(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -277,3 +407,140 @@ async fn test_shorten_partial_failure_reserved_custom_code(pool: PgPool) {
     assert!(items[1].get("error").is_some());
     assert_eq!(items[1]["error"]["code"], "validation_error");
 }
+
+#[sqlx::test]
+async fn test_shorten_idempotency_key_replays_response(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool.clone());
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+    let body = json!({
+        "urls": [{ "url": "https://example.com/retried", "custom_code": "retried" }]
+    });
+
+    let first = server
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "job-42")
+        .json(&body)
+        .await;
+    first.assert_status_ok();
+    assert!(first.maybe_header("idempotent-replayed").is_none());
+
+    let retry = server
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "job-42")
+        .json(&body)
+        .await;
+    retry.assert_status_ok();
+    assert_eq!(retry.header("idempotent-replayed"), "true");
+    assert_eq!(
+        retry.json::<serde_json::Value>(),
+        first.json::<serde_json::Value>()
+    );
+
+    let links = sqlx::query_scalar!("SELECT COUNT(*) FROM links WHERE code = 'retried'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(links, Some(1));
+}
+
+#[sqlx::test]
+async fn test_shorten_idempotency_key_with_different_body_is_422(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state);
+
+    let server = TestServer::new(app).unwrap();
+
+    server
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "job-43")
+        .json(&json!({ "urls": [{ "url": "https://example.com/a" }] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "job-43")
+        .json(&json!({ "urls": [{ "url": "https://example.com/b" }] }))
+        .await;
+
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let json = response.json::<serde_json::Value>();
+    assert_eq!(json["error"]["code"], "unprocessable_entity");
+}
+
+#[sqlx::test]
+async fn test_shorten_idempotency_keys_are_scoped_per_token(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let server_for = |principal: Principal| {
+        let app = Router::new()
+            .route("/api/shorten", post(shorten_handler))
+            .layer(Extension(principal))
+            .with_state(state.clone());
+        TestServer::new(app).unwrap()
+    };
+    let token_a = common::full_access_principal();
+    let token_b = Principal {
+        actor: Actor::Token {
+            id: 2,
+            name: "other".to_string(),
+        },
+        ..token_a.clone()
+    };
+
+    server_for(token_a)
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "shared")
+        .json(&json!({ "urls": [{ "url": "https://example.com/a" }] }))
+        .await
+        .assert_status_ok();
+
+    let other = server_for(token_b)
+        .post("/api/shorten")
+        .add_header("Idempotency-Key", "shared")
+        .json(&json!({ "urls": [{ "url": "https://example.com/b" }] }))
+        .await;
+
+    other.assert_status_ok();
+    assert!(other.maybe_header("idempotent-replayed").is_none());
+    assert_eq!(
+        other.json::<serde_json::Value>()["items"][0]["long_url"],
+        "https://example.com/b"
+    );
+}
+
+#[sqlx::test]
+async fn test_shorten_clears_cached_not_found(pool: PgPool) {
+    let (mut state, _rx) = common::create_test_state(pool);
+    state.cache = Arc::new(TieredCache::new(NullCache::new(), 100, 60));
+    state
+        .cache
+        .set_url(
+            "s.example.com:fresh",
+            &CachedRedirect::NotFound { domain_id: 1 }.encode(),
+            Some(30),
+        )
+        .await
+        .unwrap();
+    let app = Router::new()
+        .route("/api/shorten", post(shorten_handler))
+        .layer(Extension(common::full_access_principal()))
+        .with_state(state.clone());
+    let server = TestServer::new(app).unwrap();
+
+    server
+        .post("/api/shorten")
+        .json(&json!({ "urls": [{ "url": "https://example.com/fresh", "custom_code": "fresh" }] }))
+        .await
+        .assert_status_ok();
+
+    let cached = state.cache.get_url("s.example.com:fresh").await.unwrap();
+    assert_eq!(cached, None);
+}
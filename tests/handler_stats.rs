@@ -1,6 +1,6 @@
 mod common;
 
-use axum::{Router, routing::get};
+use axum::{Extension, Router, routing::get};
 use axum_test::TestServer;
 use sqlx::PgPool;
 use url_shortener::api::handlers::{stats_handler, stats_list_handler};
@@ -10,6 +10,7 @@ async fn test_stats_by_code_success(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool.clone());
     let app = Router::new()
         .route("/api/stats/{code}", get(stats_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -42,6 +43,7 @@ async fn test_stats_by_code_not_found(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool);
     let app = Router::new()
         .route("/api/stats/{code}", get(stats_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -58,6 +60,7 @@ async fn test_stats_with_pagination(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool.clone());
     let app = Router::new()
         .route("/api/stats/{code}", get(stats_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -91,6 +94,7 @@ async fn test_stats_list_all(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool.clone());
     let app = Router::new()
         .route("/api/stats", get(stats_list_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -121,6 +125,7 @@ async fn test_stats_list_with_clicks(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool.clone());
     let app = Router::new()
         .route("/api/stats", get(stats_list_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -155,6 +160,7 @@ async fn test_stats_list_pagination(pool: PgPool) {
     let (state, _rx) = common::create_test_state(pool.clone());
     let app = Router::new()
         .route("/api/stats", get(stats_list_handler))
+        .layer(Extension(common::full_access_principal()))
         .with_state(state);
 
     let server = TestServer::new(app).unwrap();
@@ -0,0 +1,221 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum::{
+    Extension, Router,
+    routing::{delete, get},
+};
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use url_shortener::api::handlers::{
+    create_token_handler, list_tokens_handler, revoke_token_handler,
+};
+use url_shortener::domain::entities::{Principal, Scope};
+
+fn make_server(pool: PgPool, principal: Principal) -> (TestServer, url_shortener::AppState) {
+    let (state, _rx) = common::create_test_state(pool);
+    let app = Router::new()
+        .route(
+            "/api/tokens",
+            get(list_tokens_handler).post(create_token_handler),
+        )
+        .route("/api/tokens/{id}", delete(revoke_token_handler))
+        .layer(Extension(principal))
+        .with_state(state.clone());
+    (TestServer::new(app).unwrap(), state)
+}
+
+fn principal(scopes: &[Scope], domain_ids: Option<Vec<i64>>) -> Principal {
+    Principal {
+        domain_ids,
+        scopes: scopes.to_vec(),
+        ..common::full_access_principal()
+    }
+}
+
+// ─── CREATE ──────────────────────────────────────────────────────────────────
+
+#[sqlx::test]
+async fn test_create_token_returns_plaintext_once(pool: PgPool) {
+    let (server, state) = make_server(pool, common::full_access_principal());
+
+    let response = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "CI pipeline", "scopes": ["links:write"] }))
+        .await;
+
+    response.assert_status(StatusCode::CREATED);
+    let body = response.json::<Value>();
+    let plaintext = body["plaintext"].as_str().unwrap();
+    assert_eq!(body["token"]["name"], "CI pipeline");
+    assert_eq!(body["token"]["scopes"], json!(["links:write"]));
+
+    let principal = state.auth_service.authenticate(plaintext).await.unwrap();
+    assert_eq!(principal.scopes, vec![Scope::LinksWrite]);
+
+    let list = server.get("/api/tokens").await.json::<Value>();
+    let listed = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "CI pipeline")
+        .unwrap();
+    assert!(listed.get("plaintext").is_none());
+    assert!(listed.get("token_hash").is_none());
+}
+
+#[sqlx::test]
+async fn test_create_token_defaults_to_caller_scopes(pool: PgPool) {
+    let caller = principal(&[Scope::StatsRead, Scope::TokensAdmin], None);
+    let (server, _state) = make_server(pool, caller);
+
+    let response = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "reporting" }))
+        .await;
+
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(
+        response.json::<Value>()["token"]["scopes"],
+        json!(["stats:read", "tokens:admin"])
+    );
+}
+
+#[sqlx::test]
+async fn test_create_token_cannot_escalate_scopes(pool: PgPool) {
+    let caller = principal(&[Scope::TokensAdmin, Scope::StatsRead], None);
+    let (server, _state) = make_server(pool, caller);
+
+    let response = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "escalate", "scopes": ["links:write"] }))
+        .await;
+
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.json::<Value>()["error"]["details"]["scope"],
+        "links:write"
+    );
+}
+
+#[sqlx::test]
+async fn test_create_token_validates_input(pool: PgPool) {
+    let (server, _state) = make_server(pool, common::full_access_principal());
+
+    let empty_name = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "" }))
+        .await;
+    let unknown_domain = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "tenant", "domain_ids": [999999] }))
+        .await;
+    let past_expiry = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "old", "expires_at": "2020-01-01T00:00:00Z" }))
+        .await;
+    let unknown_scope = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "bad", "scopes": ["links:delete"] }))
+        .await;
+
+    empty_name.assert_status(StatusCode::BAD_REQUEST);
+    unknown_domain.assert_status(StatusCode::BAD_REQUEST);
+    past_expiry.assert_status(StatusCode::BAD_REQUEST);
+    assert!(unknown_scope.status_code().is_client_error());
+}
+
+#[sqlx::test]
+async fn test_create_domain_restricted_token(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "tenant.example").await;
+    let (server, state) = make_server(pool, common::full_access_principal());
+
+    let response = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "tenant", "domain_ids": [domain_id] }))
+        .await;
+
+    response.assert_status(StatusCode::CREATED);
+    let body = response.json::<Value>();
+    assert_eq!(body["token"]["domain_ids"], json!([domain_id]));
+
+    let principal = state
+        .auth_service
+        .authenticate(body["plaintext"].as_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(principal.domain_ids, Some(vec![domain_id]));
+}
+
+// ─── AUTHORIZATION ───────────────────────────────────────────────────────────
+
+#[sqlx::test]
+async fn test_tokens_admin_scope_required(pool: PgPool) {
+    let caller = principal(&[Scope::LinksRead, Scope::LinksWrite], None);
+    let (server, _state) = make_server(pool, caller);
+
+    let list = server.get("/api/tokens").await;
+    let create = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "x" }))
+        .await;
+    let revoke = server.delete("/api/tokens/1").await;
+
+    list.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(
+        list.json::<Value>()["error"]["details"]["required_scope"],
+        "tokens:admin"
+    );
+    create.assert_status(StatusCode::FORBIDDEN);
+    revoke.assert_status(StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_domain_restricted_caller_cannot_manage_tokens(pool: PgPool) {
+    let (server, _state) = make_server(pool, principal(&Scope::ALL, Some(vec![1])));
+
+    let response = server.get("/api/tokens").await;
+
+    response.assert_status(StatusCode::FORBIDDEN);
+}
+
+// ─── REVOKE ──────────────────────────────────────────────────────────────────
+
+#[sqlx::test]
+async fn test_revoke_token(pool: PgPool) {
+    let (server, state) = make_server(pool, common::full_access_principal());
+
+    let created = server
+        .post("/api/tokens")
+        .json(&json!({ "name": "short-lived" }))
+        .await
+        .json::<Value>();
+    let id = created["token"]["id"].as_i64().unwrap();
+    let plaintext = created["plaintext"].as_str().unwrap();
+
+    let response = server.delete(&format!("/api/tokens/{id}")).await;
+    let again = server.delete(&format!("/api/tokens/{id}")).await;
+
+    response.assert_status(StatusCode::NO_CONTENT);
+    again.assert_status(StatusCode::NO_CONTENT);
+    assert!(state.auth_service.authenticate(plaintext).await.is_err());
+
+    let list = server.get("/api/tokens").await.json::<Value>();
+    let revoked = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["id"] == id)
+        .unwrap();
+    assert!(revoked["revoked_at"].is_string());
+}
+
+#[sqlx::test]
+async fn test_revoke_unknown_token_is_not_found(pool: PgPool) {
+    let (server, _state) = make_server(pool, common::full_access_principal());
+
+    let response = server.delete("/api/tokens/999999").await;
+
+    response.assert_status(StatusCode::NOT_FOUND);
+}
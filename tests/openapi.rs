@@ -0,0 +1,223 @@
+mod common;
+
+use axum::http::Method;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use url_shortener::api::openapi::ApiDoc;
+use utoipa::OpenApi;
+
+const TOKEN: &str = "openapi-drift-token";
+const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+fn spec() -> Value {
+    serde_json::to_value(ApiDoc::openapi()).unwrap()
+}
+
+fn committed_spec_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("docs/openapi.json")
+}
+
+/// Follows a local `#/components/schemas/...` reference.
+fn resolve<'a>(doc: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => {
+            let pointer = reference.trim_start_matches('#');
+            resolve(doc, doc.pointer(pointer).expect("dangling $ref"))
+        }
+        None => schema,
+    }
+}
+
+fn type_matches(ty: &str, value: &Value) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// Checks `value` against the subset of JSON Schema that utoipa emits for our DTOs.
+fn conforms(doc: &Value, schema: &Value, value: &Value, at: &str) -> Result<(), String> {
+    let schema = resolve(doc, schema);
+
+    if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        return variants
+            .iter()
+            .find_map(|v| conforms(doc, v, value, at).ok())
+            .ok_or_else(|| format!("{at}: matches no oneOf variant"));
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+        return Err(format!("{at}: expected {types:?}, got {value}"));
+    }
+
+    if let (Some(items), Some(values)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in values.iter().enumerate() {
+            conforms(doc, items, item, &format!("{at}[{i}]"))?;
+        }
+    }
+
+    if let (Some(properties), Some(object)) = (
+        schema.get("properties").and_then(Value::as_object),
+        value.as_object(),
+    ) {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let key = required.as_str().unwrap();
+            if !object.contains_key(key) {
+                return Err(format!("{at}: missing required field `{key}`"));
+            }
+        }
+        for (key, field) in object {
+            let field_schema = properties
+                .get(key)
+                .ok_or_else(|| format!("{at}: undocumented field `{key}`"))?;
+            conforms(doc, field_schema, field, &format!("{at}.{key}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Substitutes every `{param}` in the path with the parameter's documented example.
+fn fill_path(path: &str, operation: &Value) -> String {
+    let mut url = path.to_string();
+    for param in operation["parameters"].as_array().into_iter().flatten() {
+        if param["in"] == "path" {
+            let name = param["name"].as_str().unwrap();
+            let example = match &param["example"] {
+                Value::String(s) => s.clone(),
+                Value::Null => panic!("path parameter `{name}` of {path} has no example"),
+                other => other.to_string(),
+            };
+            url = url.replace(&format!("{{{name}}}"), &example);
+        }
+    }
+    url
+}
+
+/// Returns the documented example of an operation's JSON request body, if it has one.
+fn request_example(doc: &Value, operation: &Value, label: &str) -> Option<Value> {
+    let schema = operation.pointer("/requestBody/content/application~1json/schema")?;
+    let example = resolve(doc, schema).get("example").cloned();
+    Some(example.unwrap_or_else(|| panic!("{label}: request body schema has no example")))
+}
+
+#[test]
+fn test_openapi_document_matches_committed_spec() {
+    let generated = spec();
+    let path = committed_spec_path();
+
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        let pretty = serde_json::to_string_pretty(&generated).unwrap();
+        std::fs::write(&path, pretty + "\n").unwrap();
+        return;
+    }
+
+    let committed: Value = serde_json::from_str(
+        &std::fs::read_to_string(&path).expect("docs/openapi.json is missing"),
+    )
+    .unwrap();
+
+    assert!(
+        committed == generated,
+        "The API no longer matches docs/openapi.json. If the change is intended, \
+         regenerate it with `UPDATE_OPENAPI=1 cargo test --test openapi` and review the diff."
+    );
+}
+
+/// Calls every documented operation against the real router with its documented
+/// example request, and checks that the handler answers with a documented status
+/// and a body matching the documented schema.
+///
+/// Fails when a handler stops accepting its documented request body (axum rejects it
+/// with an undocumented plain-text 4xx), when a documented route is not mounted, or
+/// when a handler's response type no longer matches the spec.
+#[sqlx::test]
+async fn test_handlers_match_documented_operations(pool: PgPool) {
+    common::create_test_api_token(&pool, "openapi", TOKEN).await;
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "abc123", "https://example.com/docs", domain_id).await;
+
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let doc = spec();
+    let mut checked = 0;
+
+    for (path, item) in doc["paths"].as_object().unwrap() {
+        for method in METHODS {
+            let Some(operation) = item.get(method) else {
+                continue;
+            };
+            let label = format!("{} {path}", method.to_uppercase());
+
+            let mut request = server
+                .method(
+                    Method::from_bytes(method.to_uppercase().as_bytes()).unwrap(),
+                    &fill_path(path, operation),
+                )
+                .add_header("Host", "s.example.com")
+                .add_header("X-Forwarded-For", format!("10.0.0.{checked}"))
+                .authorization_bearer(TOKEN);
+            if let Some(body) = request_example(&doc, operation, &label) {
+                request = request.json(&body);
+            }
+            let response = request.await;
+
+            let status = response.status_code().as_u16().to_string();
+            let documented = operation["responses"].get(&status).unwrap_or_else(|| {
+                panic!(
+                    "{label} returned undocumented status {status}: {}",
+                    response.text()
+                )
+            });
+
+            if let Some(schema) = documented.pointer("/content/application~1json/schema") {
+                let body: Value = serde_json::from_str(&response.text()).unwrap_or_else(|_| {
+                    panic!(
+                        "{label} ({status}) did not return JSON: {}",
+                        response.text()
+                    )
+                });
+                if let Err(e) = conforms(&doc, schema, &body, "body") {
+                    panic!("{label} ({status}) response does not match the spec: {e}\n{body}");
+                }
+            }
+
+            checked += 1;
+        }
+    }
+
+    assert_eq!(checked, 20, "unexpected number of documented operations");
+}
+
+#[sqlx::test]
+async fn test_openapi_json_is_public(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let response = server.get("/api/v1/openapi.json").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), spec());
+
+    let docs = server.get("/api/v1/docs").await;
+    docs.assert_status_ok();
+    assert!(docs.text().contains(r#"<redoc spec-url="openapi.json">"#));
+    let csp = docs.header("content-security-policy");
+    assert!(
+        csp.to_str()
+            .unwrap()
+            .contains("script-src https://cdn.redoc.ly;")
+    );
+}
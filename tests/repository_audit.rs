@@ -0,0 +1,147 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::{
+    AuditAction, AuditActor, AuditEntity, DEFAULT_WORKSPACE_ID, NewAuditEvent,
+};
+use url_shortener::domain::repositories::{AuditFilter, AuditRepository};
+use url_shortener::infrastructure::persistence::PgAuditRepository;
+
+fn event(entity: AuditEntity, entity_id: i64, actor: AuditActor) -> NewAuditEvent {
+    NewAuditEvent {
+        workspace_id: DEFAULT_WORKSPACE_ID,
+        entity,
+        entity_id,
+        action: AuditAction::Update,
+        actor,
+        ip: Some("203.0.113.9".to_string()),
+        before: Some(json!({ "long_url": "https://example.com/old" })),
+        after: Some(json!({ "long_url": "https://example.com/new" })),
+    }
+}
+
+/// Inserts `event` as the repositories do inside a change's transaction.
+async fn record(pool: &PgPool, event: NewAuditEvent) -> i64 {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO audit_events (
+            workspace_id, entity, entity_id, action,
+            actor_token_id, actor_user_id, actor_name, ip, before, after
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id
+        "#,
+    )
+    .bind(event.workspace_id)
+    .bind(event.entity.as_str())
+    .bind(event.entity_id)
+    .bind(event.action.as_str())
+    .bind(event.actor.token_id)
+    .bind(event.actor.user_id)
+    .bind(&event.actor.name)
+    .bind(&event.ip)
+    .bind(&event.before)
+    .bind(&event.after)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+fn token_actor(id: i64, name: &str) -> AuditActor {
+    AuditActor {
+        token_id: Some(id),
+        user_id: None,
+        name: name.to_string(),
+    }
+}
+
+#[sqlx::test]
+async fn test_record_and_list(pool: PgPool) {
+    let repo = PgAuditRepository::new(Arc::new(pool.clone()));
+
+    let id = record(&pool, event(AuditEntity::Link, 42, token_actor(7, "ci"))).await;
+    let events = repo.list(&AuditFilter::new(0, 10)).await.unwrap();
+
+    assert_eq!(events.len(), 1);
+    let found = &events[0];
+    assert_eq!(found.id, id);
+    assert_eq!(found.entity, AuditEntity::Link);
+    assert_eq!(found.entity_id, 42);
+    assert_eq!(found.action, AuditAction::Update);
+    assert_eq!(found.actor, token_actor(7, "ci"));
+    assert_eq!(found.ip.as_deref(), Some("203.0.113.9"));
+    assert_eq!(
+        found.after.as_ref().unwrap()["long_url"],
+        "https://example.com/new"
+    );
+}
+
+#[sqlx::test]
+async fn test_filters(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    let repo = PgAuditRepository::new(Arc::new(pool.clone()));
+
+    record(&pool, event(AuditEntity::Link, 1, token_actor(7, "ci"))).await;
+    record(&pool, event(AuditEntity::Domain, 2, AuditActor::cli())).await;
+    record(
+        &pool,
+        NewAuditEvent {
+            workspace_id,
+            ..event(AuditEntity::Token, 3, token_actor(8, "acme"))
+        },
+    )
+    .await;
+
+    let ids = |events: Vec<url_shortener::domain::entities::AuditEvent>| {
+        events.iter().map(|e| e.entity_id).collect::<Vec<_>>()
+    };
+
+    let all = AuditFilter::new(0, 10);
+    assert_eq!(ids(repo.list(&all).await.unwrap()), vec![3, 2, 1]);
+    assert_eq!(repo.count(&all).await.unwrap(), 3);
+
+    let default_ws = AuditFilter::new(0, 10).with_workspace(Some(DEFAULT_WORKSPACE_ID));
+    assert_eq!(ids(repo.list(&default_ws).await.unwrap()), vec![2, 1]);
+
+    let domains = default_ws.clone().with_entity(Some(AuditEntity::Domain));
+    assert_eq!(ids(repo.list(&domains).await.unwrap()), vec![2]);
+
+    let by_name = default_ws.clone().with_actor(Some("ci".to_string()));
+    assert_eq!(ids(repo.list(&by_name).await.unwrap()), vec![1]);
+
+    let by_token_id = AuditFilter::new(0, 10).with_actor(Some("8".to_string()));
+    assert_eq!(ids(repo.list(&by_token_id).await.unwrap()), vec![3]);
+    assert_eq!(repo.count(&by_token_id).await.unwrap(), 1);
+
+    let future =
+        AuditFilter::new(0, 10).with_date_range(Some(Utc::now() + Duration::hours(1)), None);
+    assert!(repo.list(&future).await.unwrap().is_empty());
+    assert_eq!(repo.count(&future).await.unwrap(), 0);
+
+    let page = AuditFilter::new(1, 1);
+    assert_eq!(ids(repo.list(&page).await.unwrap()), vec![2]);
+}
+
+#[sqlx::test]
+async fn test_events_cannot_be_changed_or_deleted(pool: PgPool) {
+    let repo = PgAuditRepository::new(Arc::new(pool.clone()));
+    let id = record(&pool, event(AuditEntity::Link, 1, AuditActor::cli())).await;
+
+    let update = sqlx::query("UPDATE audit_events SET actor_name = 'someone' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await;
+    let delete = sqlx::query("DELETE FROM audit_events WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await;
+    let truncate = sqlx::query("TRUNCATE audit_events").execute(&pool).await;
+
+    assert!(update.unwrap_err().to_string().contains("append-only"));
+    assert!(delete.unwrap_err().to_string().contains("append-only"));
+    assert!(truncate.unwrap_err().to_string().contains("append-only"));
+    assert_eq!(repo.count(&AuditFilter::new(0, 10)).await.unwrap(), 1);
+}
@@ -2,7 +2,9 @@ mod common;
 
 use sqlx::PgPool;
 use std::sync::Arc;
-use url_shortener::domain::entities::{NewDomain, UpdateDomain};
+use url_shortener::domain::entities::{
+    AuditContext, DEFAULT_WORKSPACE_ID, NewDomain, UpdateDomain,
+};
 use url_shortener::domain::repositories::DomainRepository;
 use url_shortener::infrastructure::persistence::PgDomainRepository;
 
@@ -14,9 +16,10 @@ async fn test_create_domain(pool: PgPool) {
         domain: "new-test.com".to_string(),
         is_default: false,
         description: Some("Test domain".to_string()),
+        workspace_id: DEFAULT_WORKSPACE_ID,
     };
 
-    let result = repo.create(new_domain).await;
+    let result = repo.create(new_domain, &AuditContext::cli()).await;
 
     assert!(result.is_ok());
     let domain = result.unwrap();
@@ -33,8 +36,9 @@ async fn test_find_by_name(pool: PgPool) {
         domain: "find-me.com".to_string(),
         is_default: false,
         description: None,
+        workspace_id: DEFAULT_WORKSPACE_ID,
     };
-    repo.create(new_domain).await.unwrap();
+    repo.create(new_domain, &AuditContext::cli()).await.unwrap();
 
     let result = repo.find_by_name("find-me.com").await;
 
@@ -48,7 +52,7 @@ async fn test_find_by_name(pool: PgPool) {
 async fn test_get_default_domain(pool: PgPool) {
     let repo = PgDomainRepository::new(Arc::new(pool));
 
-    let result = repo.get_default().await;
+    let result = repo.get_default(DEFAULT_WORKSPACE_ID).await;
 
     assert!(result.is_ok());
     let domain = result.unwrap();
@@ -64,11 +68,12 @@ async fn test_list_domains(pool: PgPool) {
             domain: format!("list-test-{}.com", i),
             is_default: false,
             description: None,
+            workspace_id: DEFAULT_WORKSPACE_ID,
         };
-        repo.create(new_domain).await.unwrap();
+        repo.create(new_domain, &AuditContext::cli()).await.unwrap();
     }
 
-    let result = repo.list(false).await;
+    let result = repo.list(Some(DEFAULT_WORKSPACE_ID), false).await;
 
     assert!(result.is_ok());
     let domains = result.unwrap();
@@ -83,15 +88,16 @@ async fn test_update_domain(pool: PgPool) {
         domain: "update-test.com".to_string(),
         is_default: false,
         description: Some("Old description".to_string()),
+        workspace_id: DEFAULT_WORKSPACE_ID,
     };
-    let created = repo.create(new_domain).await.unwrap();
+    let created = repo.create(new_domain, &AuditContext::cli()).await.unwrap();
 
     let update = UpdateDomain {
         is_active: Some(false),
         description: Some(Some("New description".to_string())),
         ..Default::default()
     };
-    let result = repo.update(created.id, update).await;
+    let result = repo.update(created.id, update, &AuditContext::cli()).await;
 
     assert!(result.is_ok());
     let updated = result.unwrap();
@@ -107,8 +113,9 @@ async fn test_count_links(pool: PgPool) {
         domain: "count-links.com".to_string(),
         is_default: false,
         description: None,
+        workspace_id: DEFAULT_WORKSPACE_ID,
     };
-    let domain = repo.create(new_domain).await.unwrap();
+    let domain = repo.create(new_domain, &AuditContext::cli()).await.unwrap();
 
     for i in 1..=3 {
         common::create_test_link(
@@ -125,3 +132,70 @@ async fn test_count_links(pool: PgPool) {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), 3);
 }
+
+#[sqlx::test]
+async fn test_domains_are_scoped_to_their_workspace(pool: PgPool) {
+    let repo = PgDomainRepository::new(Arc::new(pool.clone()));
+    let default_id = common::get_default_domain(&pool).await;
+    let workspace = common::create_test_workspace(&pool, "acme").await;
+
+    let acme = repo
+        .create(
+            NewDomain {
+                domain: "acme.example.com".to_string(),
+                is_default: true,
+                description: None,
+                workspace_id: workspace,
+            },
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(acme.workspace_id, workspace);
+
+    // Each workspace keeps its own default.
+    assert_eq!(repo.get_default(workspace).await.unwrap().id, acme.id);
+    assert_eq!(
+        repo.get_default(DEFAULT_WORKSPACE_ID).await.unwrap().id,
+        default_id
+    );
+
+    let listed = repo.list(Some(workspace), false).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(
+        repo.list(Some(DEFAULT_WORKSPACE_ID), false)
+            .await
+            .unwrap()
+            .iter()
+            .all(|d| d.workspace_id == DEFAULT_WORKSPACE_ID)
+    );
+    assert!(repo.list(None, false).await.unwrap().len() > listed.len());
+}
+
+#[sqlx::test]
+async fn test_set_default_keeps_other_workspaces_default(pool: PgPool) {
+    let repo = PgDomainRepository::new(Arc::new(pool.clone()));
+    let default_id = common::get_default_domain(&pool).await;
+    let workspace = common::create_test_workspace(&pool, "acme").await;
+    common::create_workspace_domain(&pool, "acme.example.com", workspace).await;
+
+    let second = repo
+        .create(
+            NewDomain {
+                domain: "second.acme.example.com".to_string(),
+                is_default: false,
+                description: None,
+                workspace_id: workspace,
+            },
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    repo.set_default(second.id).await.unwrap();
+
+    assert_eq!(repo.get_default(workspace).await.unwrap().id, second.id);
+    assert_eq!(
+        repo.get_default(DEFAULT_WORKSPACE_ID).await.unwrap().id,
+        default_id
+    );
+}
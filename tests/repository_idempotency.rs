@@ -0,0 +1,160 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::repositories::IdempotencyRepository;
+use url_shortener::infrastructure::persistence::PgIdempotencyRepository;
+
+fn expires() -> chrono::DateTime<Utc> {
+    Utc::now() + Duration::hours(24)
+}
+
+fn stale_before() -> chrono::DateTime<Utc> {
+    Utc::now() - Duration::seconds(60)
+}
+
+#[sqlx::test]
+async fn test_reserve_new_key(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    let reserved = repo
+        .try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    assert!(reserved);
+
+    let record = repo.find("scope", "key-1").await.unwrap().unwrap();
+    assert_eq!(record.request_hash, "hash");
+    assert!(record.response.is_none());
+}
+
+#[sqlx::test]
+async fn test_reserve_held_key_fails(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    let again = repo
+        .try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+
+    assert!(!again);
+}
+
+#[sqlx::test]
+async fn test_keys_are_scoped(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope-a", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    let other_scope = repo
+        .try_reserve("scope-b", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+
+    assert!(other_scope);
+    assert!(repo.find("scope-c", "key-1").await.unwrap().is_none());
+}
+
+#[sqlx::test]
+async fn test_complete_stores_response(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    repo.complete("scope", "key-1", &json!({"summary": {"total": 1}}))
+        .await
+        .unwrap();
+
+    let record = repo.find("scope", "key-1").await.unwrap().unwrap();
+    assert_eq!(record.response, Some(json!({"summary": {"total": 1}})));
+}
+
+#[sqlx::test]
+async fn test_release_frees_only_keys_without_response(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    repo.try_reserve("scope", "key-2", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    repo.complete("scope", "key-2", &json!({})).await.unwrap();
+
+    repo.release("scope", "key-1").await.unwrap();
+    repo.release("scope", "key-2").await.unwrap();
+
+    assert!(repo.find("scope", "key-1").await.unwrap().is_none());
+    assert!(repo.find("scope", "key-2").await.unwrap().is_some());
+}
+
+#[sqlx::test]
+async fn test_reserve_takes_over_abandoned_key(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope", "key-1", "old", expires(), stale_before())
+        .await
+        .unwrap();
+
+    // Everything reserved before "now + 1s" counts as abandoned.
+    let taken_over = repo
+        .try_reserve(
+            "scope",
+            "key-1",
+            "new",
+            expires(),
+            Utc::now() + Duration::seconds(1),
+        )
+        .await
+        .unwrap();
+
+    assert!(taken_over);
+    let record = repo.find("scope", "key-1").await.unwrap().unwrap();
+    assert_eq!(record.request_hash, "new");
+}
+
+#[sqlx::test]
+async fn test_completed_key_is_never_taken_over_before_expiry(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+
+    repo.try_reserve("scope", "key-1", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+    repo.complete("scope", "key-1", &json!({})).await.unwrap();
+
+    let taken_over = repo
+        .try_reserve(
+            "scope",
+            "key-1",
+            "hash",
+            expires(),
+            Utc::now() + Duration::seconds(1),
+        )
+        .await
+        .unwrap();
+
+    assert!(!taken_over);
+}
+
+#[sqlx::test]
+async fn test_delete_expired_keeps_live_keys(pool: PgPool) {
+    let repo = PgIdempotencyRepository::new(Arc::new(pool));
+    let past = Utc::now() - Duration::seconds(1);
+
+    repo.try_reserve("scope", "expired", "hash", past, stale_before())
+        .await
+        .unwrap();
+    repo.try_reserve("scope", "live", "hash", expires(), stale_before())
+        .await
+        .unwrap();
+
+    let removed = repo.delete_expired().await.unwrap();
+    assert_eq!(removed, 1);
+    assert!(repo.find("scope", "expired").await.unwrap().is_none());
+    assert!(repo.find("scope", "live").await.unwrap().is_some());
+}
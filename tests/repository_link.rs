@@ -2,8 +2,9 @@ mod common;
 
 use sqlx::PgPool;
 use std::sync::Arc;
-use url_shortener::domain::entities::NewLink;
+use url_shortener::domain::entities::{AuditContext, LinkPatch, NewLink, Role, Visibility};
 use url_shortener::domain::repositories::LinkRepository;
+use url_shortener::error::AppError;
 use url_shortener::infrastructure::persistence::PgLinkRepository;
 
 #[sqlx::test]
@@ -17,9 +18,11 @@ async fn test_create_link(pool: PgPool) {
         domain_id,
         expires_at: None,
         permanent: false,
+        owner_id: None,
+        team_id: None,
     };
 
-    let result = repo.create(new_link).await;
+    let result = repo.create(new_link, &AuditContext::cli()).await;
 
     assert!(result.is_ok());
     let link = result.unwrap();
@@ -61,6 +64,28 @@ async fn test_find_by_code_not_found(pool: PgPool) {
     assert!(result.unwrap().is_none());
 }
 
+#[sqlx::test]
+async fn test_find_ids_by_codes(pool: PgPool) {
+    let first = common::create_test_domain(&pool, "ids-a.com").await;
+    let second = common::create_test_domain(&pool, "ids-b.com").await;
+    common::create_test_link(&pool, "same", "https://example.com/a", first).await;
+    common::create_test_link(&pool, "same", "https://example.com/b", second).await;
+    let repo = PgLinkRepository::new(Arc::new(pool));
+
+    let keys = [
+        (first, "same".to_string()),
+        (second, "same".to_string()),
+        (first, "missing".to_string()),
+    ];
+    let ids = repo.find_ids_by_codes(&keys).await.unwrap();
+
+    assert_eq!(ids.len(), 2);
+    let first_link = repo.find_by_code("same", first).await.unwrap().unwrap();
+    let second_link = repo.find_by_code("same", second).await.unwrap().unwrap();
+    assert_eq!(ids[&(first, "same".to_string())], first_link.id);
+    assert_eq!(ids[&(second, "same".to_string())], second_link.id);
+}
+
 #[sqlx::test]
 async fn test_find_by_long_url(pool: PgPool) {
     let domain_id = common::create_test_domain(&pool, "test4.com").await;
@@ -81,7 +106,217 @@ async fn test_find_by_long_url(pool: PgPool) {
         .await;
 
     assert!(result.is_ok());
-    let link = result.unwrap();
-    assert!(link.is_some());
-    assert_eq!(link.unwrap().code, "xyz789");
+    let links = result.unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].code, "xyz789");
+}
+
+#[sqlx::test]
+async fn test_find_by_long_url_returns_live_links_oldest_first(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "test4b.com").await;
+    let other_domain_id = common::create_test_domain(&pool, "test4c.com").await;
+
+    common::create_test_link(&pool, "first", "https://dup-url.com", domain_id).await;
+    common::create_test_link(&pool, "second", "https://dup-url.com", domain_id).await;
+    common::create_deleted_link(&pool, "deleted", "https://dup-url.com", domain_id).await;
+    common::create_test_link(&pool, "other", "https://dup-url.com", other_domain_id).await;
+
+    let repo = PgLinkRepository::new(Arc::new(pool));
+    let links = repo
+        .find_by_long_url("https://dup-url.com", domain_id)
+        .await
+        .unwrap();
+
+    let codes: Vec<_> = links.iter().map(|l| l.code.as_str()).collect();
+    assert_eq!(codes, ["first", "second"]);
+}
+
+#[sqlx::test]
+async fn test_list_and_count_by_visibility(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "visibility.com").await;
+    let team_id = common::create_test_team(&pool, "sales").await;
+    let ada = common::create_test_user(&pool, "ada@example.com", Role::Editor, Some(team_id)).await;
+    let bob = common::create_test_user(&pool, "bob@example.com", Role::Editor, Some(team_id)).await;
+    let repo = PgLinkRepository::new(Arc::new(pool.clone()));
+
+    for (code, owner_id, team) in [
+        ("ada1", Some(ada), Some(team_id)),
+        ("bob1", Some(bob), Some(team_id)),
+        ("bob2", Some(bob), None),
+        ("legacy", None, None),
+    ] {
+        repo.create(
+            NewLink {
+                code: code.to_string(),
+                long_url: format!("https://example.com/{code}"),
+                domain_id,
+                expires_at: None,
+                permanent: false,
+                owner_id,
+                team_id: team,
+            },
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let own = Visibility::Owned {
+        workspace_id: 1,
+        user_id: ada,
+        team_id: None,
+    };
+    let team = Visibility::Owned {
+        workspace_id: 1,
+        user_id: ada,
+        team_id: Some(team_id),
+    };
+
+    let listed = repo.list(1, 10, Some(domain_id), &team).await.unwrap();
+    let mut codes: Vec<_> = listed.iter().map(|l| l.code.as_str()).collect();
+    codes.sort();
+    assert_eq!(codes, ["ada1", "bob1"]);
+    assert_eq!(listed[0].team_id, Some(team_id));
+
+    assert_eq!(repo.count(Some(domain_id), &own).await.unwrap(), 1);
+    assert_eq!(repo.count(Some(domain_id), &team).await.unwrap(), 2);
+    assert_eq!(
+        repo.count(Some(domain_id), &Visibility::All).await.unwrap(),
+        4
+    );
+}
+
+fn new_link(code: &str, domain_id: i64) -> NewLink {
+    NewLink {
+        code: code.to_string(),
+        long_url: format!("https://example.com/{code}"),
+        domain_id,
+        expires_at: None,
+        permanent: false,
+        owner_id: None,
+        team_id: None,
+    }
+}
+
+#[sqlx::test]
+async fn test_create_enforces_link_quota_under_concurrency(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "quota").await;
+    let domain_id = common::create_workspace_domain(&pool, "quota.test", workspace_id).await;
+    common::set_workspace_quotas(&pool, workspace_id, Some(3), None, None).await;
+    let repo = Arc::new(PgLinkRepository::new(Arc::new(pool)));
+
+    let creates = (0..10).map(|i| {
+        let repo = repo.clone();
+        tokio::spawn(async move {
+            repo.create(new_link(&format!("q{i}"), domain_id), &AuditContext::cli())
+                .await
+        })
+    });
+    let results: Vec<_> = futures_util::future::join_all(creates)
+        .await
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 3);
+    assert!(
+        results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .all(|e| matches!(e, AppError::Forbidden { .. }))
+    );
+}
+
+#[sqlx::test]
+async fn test_restore_enforces_link_quota(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "quota").await;
+    let domain_id = common::create_workspace_domain(&pool, "quota.test", workspace_id).await;
+    common::create_deleted_link(&pool, "gone", "https://example.com/gone", domain_id).await;
+    common::set_workspace_quotas(&pool, workspace_id, Some(1), None, None).await;
+    let repo = PgLinkRepository::new(Arc::new(pool));
+    let restore = LinkPatch {
+        url: None,
+        expires_at: None,
+        permanent: None,
+        restore: true,
+        reverted_from: None,
+    };
+
+    repo.create(new_link("live", domain_id), &AuditContext::cli())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        repo.update("gone", domain_id, restore.clone(), &AuditContext::cli())
+            .await,
+        Err(AppError::Forbidden { .. })
+    ));
+    // Restoring a link that is not deleted does not need room in the quota.
+    assert!(
+        repo.update("live", domain_id, restore, &AuditContext::cli())
+            .await
+            .is_ok()
+    );
+}
+
+#[sqlx::test]
+async fn test_changes_are_recorded_in_the_audit_log(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "audited.test").await;
+    let repo = PgLinkRepository::new(Arc::new(pool.clone()));
+    let patch = LinkPatch {
+        url: Some("https://example.com/new".to_string()),
+        expires_at: None,
+        permanent: None,
+        restore: false,
+        reverted_from: None,
+    };
+
+    repo.create(new_link("audited", domain_id), &AuditContext::cli())
+        .await
+        .unwrap();
+    repo.update("audited", domain_id, patch, &AuditContext::cli())
+        .await
+        .unwrap();
+    repo.soft_delete("audited", domain_id, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    let actions: Vec<String> =
+        sqlx::query_scalar("SELECT action FROM audit_events WHERE entity = 'link' ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(actions, vec!["create", "update", "delete"]);
+}
+
+#[sqlx::test]
+async fn test_change_fails_when_audit_event_cannot_be_written(pool: PgPool) {
+    let domain_id = common::create_test_domain(&pool, "unaudited.test").await;
+    sqlx::raw_sql(
+        r#"
+        CREATE FUNCTION reject_audit_event() RETURNS trigger AS $$
+        BEGIN
+            RAISE EXCEPTION 'audit log unavailable';
+        END;
+        $$ LANGUAGE plpgsql;
+        CREATE TRIGGER reject_audit_event BEFORE INSERT ON audit_events
+            FOR EACH ROW EXECUTE FUNCTION reject_audit_event();
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let repo = PgLinkRepository::new(Arc::new(pool));
+
+    let result = repo
+        .create(new_link("unaudited", domain_id), &AuditContext::cli())
+        .await;
+
+    assert!(result.is_err());
+    assert!(
+        repo.find_by_code("unaudited", domain_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
 }
@@ -0,0 +1,130 @@
+mod common;
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::{AuditContext, Link, LinkPatch, NewLink};
+use url_shortener::domain::repositories::{LinkRepository, LinkVersionRepository};
+use url_shortener::infrastructure::persistence::{PgLinkRepository, PgLinkVersionRepository};
+
+async fn create_link(links: &PgLinkRepository, code: &str, domain_id: i64) -> Link {
+    let new_link = NewLink {
+        code: code.to_string(),
+        long_url: format!("https://example.com/{code}"),
+        domain_id,
+        expires_at: None,
+        permanent: false,
+        owner_id: None,
+        team_id: None,
+    };
+
+    links.create(new_link, &AuditContext::cli()).await.unwrap()
+}
+
+fn url_patch(long_url: &str, reverted_from: Option<i32>) -> LinkPatch {
+    LinkPatch {
+        url: Some(long_url.to_string()),
+        expires_at: None,
+        permanent: None,
+        restore: false,
+        reverted_from,
+    }
+}
+
+#[sqlx::test]
+async fn test_changes_number_versions_per_link(pool: PgPool) {
+    let domain_id = common::get_default_domain(&pool).await;
+    let links = PgLinkRepository::new(Arc::new(pool.clone()));
+    let repo = PgLinkVersionRepository::new(Arc::new(pool));
+
+    let one = create_link(&links, "one", domain_id).await;
+    let two = create_link(&links, "two", domain_id).await;
+    links
+        .update(
+            "one",
+            domain_id,
+            url_patch("https://example.com/1b", Some(1)),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    // Patches that leave the destination, expiry and redirect type alone add no version.
+    links
+        .update(
+            "two",
+            domain_id,
+            url_patch("https://example.com/two", None),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+
+    let versions = repo.list(one.id).await.unwrap();
+    let numbers: Vec<i32> = versions.iter().map(|v| v.version).collect();
+    assert_eq!(numbers, vec![2, 1]);
+    assert_eq!(versions[0].long_url, "https://example.com/1b");
+    assert_eq!(versions[0].reverted_from, Some(1));
+    assert_eq!(versions[0].actor.as_ref().unwrap().name, "admin-cli");
+    assert_eq!(repo.list(two.id).await.unwrap().len(), 1);
+}
+
+#[sqlx::test]
+async fn test_concurrent_changes_get_consecutive_versions(pool: PgPool) {
+    let domain_id = common::get_default_domain(&pool).await;
+    let links = Arc::new(PgLinkRepository::new(Arc::new(pool.clone())));
+    let repo = PgLinkVersionRepository::new(Arc::new(pool));
+    let link = create_link(&links, "busy", domain_id).await;
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let links = links.clone();
+            tokio::spawn(async move {
+                links
+                    .update(
+                        "busy",
+                        domain_id,
+                        url_patch(&format!("https://example.com/busy/{i}"), None),
+                        &AuditContext::cli(),
+                    )
+                    .await
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    let numbers: Vec<i32> = repo
+        .list(link.id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|v| v.version)
+        .collect();
+    assert_eq!(numbers, (1..=9).rev().collect::<Vec<_>>());
+}
+
+#[sqlx::test]
+async fn test_list_and_find(pool: PgPool) {
+    let domain_id = common::get_default_domain(&pool).await;
+    let links = PgLinkRepository::new(Arc::new(pool.clone()));
+    let repo = PgLinkVersionRepository::new(Arc::new(pool));
+
+    let link = create_link(&links, "hist", domain_id).await;
+    links
+        .update(
+            "hist",
+            domain_id,
+            url_patch("https://example.com/b", None),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+
+    let versions = repo.list(link.id).await.unwrap();
+    let numbers: Vec<i32> = versions.iter().map(|v| v.version).collect();
+    assert_eq!(numbers, vec![2, 1]);
+
+    let found = repo.find(link.id, 1).await.unwrap().unwrap();
+    assert_eq!(found.long_url, "https://example.com/hist");
+    assert!(repo.find(link.id, 3).await.unwrap().is_none());
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::{Actor, AuditContext, Role, Visibility};
+use url_shortener::domain::repositories::{SessionOwner, SessionRepository, TokenRepository};
+use url_shortener::infrastructure::persistence::{PgSessionRepository, PgTokenRepository};
+
+async fn setup(pool: &PgPool) -> (PgSessionRepository, i64) {
+    let token_id = common::create_test_api_token(pool, "web", "session-repo-token").await;
+    (PgSessionRepository::new(Arc::new(pool.clone())), token_id)
+}
+
+#[sqlx::test]
+async fn test_create_and_find_session(pool: PgPool) {
+    let (repo, token_id) = setup(&pool).await;
+
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::Token(token_id),
+        "csrf-1",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    let session = repo.find_session("hash-1").await.unwrap().unwrap();
+    assert_eq!(session.csrf_token, "csrf-1");
+    assert_eq!(session.principal.token_id(), Some(token_id));
+    assert!(repo.find_session("unknown").await.unwrap().is_none());
+}
+
+#[sqlx::test]
+async fn test_expired_session_is_not_found(pool: PgPool) {
+    let (repo, token_id) = setup(&pool).await;
+
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() - Duration::seconds(1),
+    )
+    .await
+    .unwrap();
+
+    assert!(repo.find_session("hash-1").await.unwrap().is_none());
+}
+
+#[sqlx::test]
+async fn test_session_of_revoked_token_is_not_found(pool: PgPool) {
+    let (repo, token_id) = setup(&pool).await;
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    PgTokenRepository::new(Arc::new(pool.clone()))
+        .revoke_token(token_id, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    assert!(repo.find_session("hash-1").await.unwrap().is_none());
+}
+
+#[sqlx::test]
+async fn test_delete_session(pool: PgPool) {
+    let (repo, token_id) = setup(&pool).await;
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    repo.delete_session("hash-1").await.unwrap();
+
+    assert!(repo.find_session("hash-1").await.unwrap().is_none());
+}
+
+#[sqlx::test]
+async fn test_delete_expired_keeps_live_sessions(pool: PgPool) {
+    let (repo, token_id) = setup(&pool).await;
+    repo.create_session(
+        "old",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() - Duration::hours(1),
+    )
+    .await
+    .unwrap();
+    repo.create_session(
+        "live",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(repo.delete_expired().await.unwrap(), 1);
+    assert!(repo.find_session("live").await.unwrap().is_some());
+}
+
+#[sqlx::test]
+async fn test_single_sign_on_session_has_role_permissions(pool: PgPool) {
+    let team_id = common::create_test_team(&pool, "marketing").await;
+    let user_id =
+        common::create_test_user(&pool, "ada@example.com", Role::Editor, Some(team_id)).await;
+    let repo = PgSessionRepository::new(Arc::new(pool));
+
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::User(user_id),
+        "csrf",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    let session = repo.find_session("hash-1").await.unwrap().unwrap();
+    assert_eq!(
+        session.principal.actor,
+        Actor::User {
+            id: user_id,
+            email: Some("ada@example.com".to_string())
+        }
+    );
+    assert_eq!(session.principal.scopes, Role::Editor.scopes());
+    assert_eq!(session.principal.token_id(), None);
+    assert_eq!(
+        session.principal.visibility(),
+        Visibility::Owned {
+            workspace_id: 1,
+            user_id,
+            team_id: Some(team_id)
+        }
+    );
+}
+
+#[sqlx::test]
+async fn test_token_session_acts_for_the_tokens_user(pool: PgPool) {
+    let user_id = common::create_test_user(&pool, "bob@example.com", Role::Viewer, None).await;
+    let token_id = common::create_user_api_token(&pool, "bob", "bob-token", user_id).await;
+    let repo = PgSessionRepository::new(Arc::new(pool));
+
+    repo.create_session(
+        "hash-1",
+        &SessionOwner::Token(token_id),
+        "csrf",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    let session = repo.find_session("hash-1").await.unwrap().unwrap();
+    assert_eq!(session.principal.token_id(), Some(token_id));
+    assert_eq!(session.principal.scopes, Role::Viewer.scopes());
+    assert_eq!(session.principal.user.map(|u| u.id), Some(user_id));
+}
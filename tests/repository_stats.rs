@@ -2,8 +2,8 @@ mod common;
 
 use sqlx::PgPool;
 use std::sync::Arc;
-use url_shortener::domain::entities::NewClick;
-use url_shortener::domain::repositories::{StatsFilter, StatsRepository};
+use url_shortener::domain::entities::{NewClick, Role, Visibility};
+use url_shortener::domain::repositories::{LinkStats, StatsFilter, StatsRepository};
 use url_shortener::infrastructure::persistence::PgStatsRepository;
 
 #[sqlx::test]
@@ -23,6 +23,7 @@ async fn test_record_click(pool: PgPool) {
         user_agent: Some("Mozilla/5.0".to_string()),
         referer: None,
         ip: Some("192.168.1.1".to_string()),
+        clicked_at: None,
     };
 
     let result = repo.record_click(new_click).await;
@@ -33,6 +34,47 @@ async fn test_record_click(pool: PgPool) {
     assert_eq!(click.user_agent, Some("Mozilla/5.0".to_string()));
 }
 
+#[sqlx::test]
+async fn test_record_clicks_skips_missing_links(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let domain_id = common::create_test_domain(&pool, "stats-batch.com").await;
+    common::create_test_link(&pool, "batch1", "https://example.com", domain_id).await;
+    let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "batch1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let click = |link_id, ip: &str| NewClick {
+        link_id,
+        user_agent: None,
+        referer: Some("https://example.org".to_string()),
+        ip: Some(ip.to_string()),
+        clicked_at: None,
+    };
+    let recorded = repo
+        .record_clicks(&[
+            click(link_id, "10.0.0.1"),
+            click(-1, "10.0.0.2"),
+            click(link_id, "10.0.0.3"),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(recorded, 2);
+    let ips: Vec<Option<String>> = sqlx::query_scalar!(
+        "SELECT ip FROM link_clicks WHERE link_id = $1 ORDER BY ip",
+        link_id
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        ips,
+        [Some("10.0.0.1".to_string()), Some("10.0.0.3".to_string())]
+    );
+}
+
 #[sqlx::test]
 async fn test_get_stats_by_code(pool: PgPool) {
     let repo = PgStatsRepository::new(Arc::new(pool.clone()));
@@ -49,7 +91,7 @@ async fn test_get_stats_by_code(pool: PgPool) {
         common::create_test_click(&pool, link_id, &format!("192.168.1.{}", i)).await;
     }
 
-    let filter = StatsFilter::new(0, 10).with_domain(Some(domain_id));
+    let filter = StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id));
     let result = repo.get_stats_by_code("stats456", filter).await;
 
     assert!(result.is_ok());
@@ -78,7 +120,7 @@ async fn test_get_all_stats(pool: PgPool) {
         common::create_test_click(&pool, link1_id, &format!("192.168.1.{}", i)).await;
     }
 
-    let filter = StatsFilter::new(0, 10).with_domain(Some(domain_id));
+    let filter = StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id));
     let result = repo.get_all_stats(filter).await;
 
     assert!(result.is_ok());
@@ -90,6 +132,189 @@ async fn test_get_all_stats(pool: PgPool) {
     assert_eq!(link1_stats.unwrap().total, 3);
 }
 
+#[sqlx::test]
+async fn test_get_all_stats_reads_counters_without_date_range(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let domain_id = common::create_test_domain(&pool, "counters.com").await;
+    common::create_test_link(&pool, "counted", "https://example.com", domain_id).await;
+    let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "counted")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    // Recorded, but not yet flushed to the counter.
+    let clicks: Vec<NewClick> = (0..2)
+        .map(|_| NewClick {
+            link_id,
+            user_agent: None,
+            referer: None,
+            ip: None,
+            clicked_at: None,
+        })
+        .collect();
+    repo.record_clicks(&clicks).await.unwrap();
+
+    let total = |stats: Vec<LinkStats>| stats.iter().find(|s| s.code == "counted").unwrap().total;
+    let all_time = StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id));
+    let ranged = all_time
+        .clone()
+        .with_date_range(Some(chrono::Utc::now() - chrono::Duration::hours(1)), None);
+
+    assert_eq!(
+        total(repo.get_all_stats(all_time.clone()).await.unwrap()),
+        0
+    );
+    assert_eq!(total(repo.get_all_stats(ranged).await.unwrap()), 2);
+
+    assert_eq!(repo.flush_link_clicks().await.unwrap(), 1);
+    assert_eq!(
+        total(repo.get_all_stats(all_time.clone()).await.unwrap()),
+        2
+    );
+
+    // Flushing again, as another server would, counts nothing twice.
+    assert_eq!(repo.flush_link_clicks().await.unwrap(), 0);
+    assert_eq!(total(repo.get_all_stats(all_time).await.unwrap()), 2);
+}
+
+#[sqlx::test]
+async fn test_reconcile_link_clicks(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let domain_id = common::create_test_domain(&pool, "reconcile.com").await;
+    common::create_test_link(&pool, "drifted", "https://example.com/1", domain_id).await;
+    common::create_test_link(&pool, "exact", "https://example.com/2", domain_id).await;
+    let drifted_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "drifted")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let exact_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "exact")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    for i in 1..=3 {
+        common::create_test_click(&pool, drifted_id, &format!("10.0.0.{}", i)).await;
+    }
+    common::create_test_click(&pool, exact_id, "10.0.1.1").await;
+    sqlx::query!("UPDATE links SET clicks = 7 WHERE id = $1", drifted_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    // Recorded, but not yet flushed.
+    repo.record_clicks(&[NewClick {
+        link_id: exact_id,
+        user_agent: None,
+        referer: None,
+        ip: None,
+        clicked_at: None,
+    }])
+    .await
+    .unwrap();
+
+    assert_eq!(repo.reconcile_link_clicks().await.unwrap(), 2);
+    // The recount covered the unflushed click; a flush doesn't add it again.
+    assert_eq!(repo.flush_link_clicks().await.unwrap(), 0);
+
+    let stats = repo
+        .get_all_stats(StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id)))
+        .await
+        .unwrap();
+    let total = |code: &str| stats.iter().find(|s| s.code == code).unwrap().total;
+    assert_eq!(total("drifted"), 3);
+    assert_eq!(total("exact"), 2);
+
+    assert_eq!(repo.reconcile_link_clicks().await.unwrap(), 0);
+}
+
+#[sqlx::test]
+async fn test_reconcile_link_clicks_leaves_clicks_inserted_meanwhile_to_the_flush(pool: PgPool) {
+    let repo = Arc::new(PgStatsRepository::new(Arc::new(pool.clone())));
+
+    let domain_id = common::create_test_domain(&pool, "meanwhile.com").await;
+    common::create_test_link(&pool, "meanwhile", "https://example.com", domain_id).await;
+    let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "meanwhile")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    // A flush in progress keeps the recount waiting.
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query!("SELECT counted_up_to FROM click_counter_watermark FOR UPDATE")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+    let reconcile = tokio::spawn({
+        let repo = repo.clone();
+        async move { repo.reconcile_link_clicks().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!reconcile.is_finished());
+
+    // Clicks are not held back by the waiting recount.
+    let click = NewClick {
+        link_id,
+        user_agent: None,
+        referer: None,
+        ip: None,
+        clicked_at: None,
+    };
+    tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        repo.record_clicks(&[click]),
+    )
+    .await
+    .expect("click insert waited for the recount")
+    .unwrap();
+
+    tx.rollback().await.unwrap();
+    assert_eq!(reconcile.await.unwrap().unwrap(), 0);
+    assert_eq!(repo.flush_link_clicks().await.unwrap(), 1);
+
+    let stats = repo
+        .get_all_stats(StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id)))
+        .await
+        .unwrap();
+    assert_eq!(stats[0].total, 1);
+}
+
+#[sqlx::test]
+async fn test_flush_link_clicks_waits_for_clicks_being_inserted(pool: PgPool) {
+    let repo = Arc::new(PgStatsRepository::new(Arc::new(pool.clone())));
+
+    let domain_id = common::create_test_domain(&pool, "inflight.com").await;
+    common::create_test_link(&pool, "inflight", "https://example.com", domain_id).await;
+    let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", "inflight")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    // A click whose ID is taken but not yet committed.
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query!("INSERT INTO link_clicks (link_id) VALUES ($1)", link_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+    let flush = tokio::spawn({
+        let repo = repo.clone();
+        async move { repo.flush_link_clicks().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!flush.is_finished());
+
+    tx.commit().await.unwrap();
+    assert_eq!(flush.await.unwrap().unwrap(), 1);
+
+    let stats = repo
+        .get_all_stats(StatsFilter::new(0, 10, Visibility::All).with_domain(Some(domain_id)))
+        .await
+        .unwrap();
+    assert_eq!(stats[0].total, 1);
+}
+
 #[sqlx::test]
 async fn test_count_all_links(pool: PgPool) {
     let repo = PgStatsRepository::new(Arc::new(pool.clone()));
@@ -106,12 +331,83 @@ async fn test_count_all_links(pool: PgPool) {
         .await;
     }
 
-    let result = repo.count_all_links().await;
+    let result = repo.count_all_links(None, &Visibility::All).await;
 
     assert!(result.is_ok());
     assert!(result.unwrap() >= 4);
 }
 
+#[sqlx::test]
+async fn test_stats_limited_to_allowed_domains(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let allowed = common::create_test_domain(&pool, "allowed.example").await;
+    let hidden = common::create_test_domain(&pool, "hidden.example").await;
+    common::create_test_link(&pool, "seen1", "https://example.com/seen", allowed).await;
+    common::create_test_link(&pool, "hidden1", "https://example.com/hidden", hidden).await;
+
+    let filter =
+        StatsFilter::new(0, 100, Visibility::All).with_allowed_domains(Some(vec![allowed]));
+    let stats = repo.get_all_stats(filter.clone()).await.unwrap();
+    let count = repo
+        .count_all_links(Some(vec![allowed]), &Visibility::All)
+        .await
+        .unwrap();
+
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].code, "seen1");
+    assert_eq!(count, 1);
+    assert!(
+        repo.get_stats_by_code("hidden1", filter)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[sqlx::test]
+async fn test_stats_limited_to_visible_links(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let domain_id = common::create_test_domain(&pool, "owned.example").await;
+    let team_id = common::create_test_team(&pool, "growth").await;
+    let ada = common::create_test_user(&pool, "ada@example.com", Role::Viewer, Some(team_id)).await;
+    let bob = common::create_test_user(&pool, "bob@example.com", Role::Editor, Some(team_id)).await;
+    let eve = common::create_test_user(&pool, "eve@example.com", Role::Editor, None).await;
+    for (code, owner, team) in [
+        ("mine", ada, None),
+        ("team", bob, Some(team_id)),
+        ("other", eve, None),
+    ] {
+        common::create_test_link(&pool, code, "https://example.com", domain_id).await;
+        common::set_link_owner(&pool, code, owner, team).await;
+    }
+
+    let visibility = Visibility::Owned {
+        workspace_id: 1,
+        user_id: ada,
+        team_id: Some(team_id),
+    };
+    let filter = StatsFilter::new(0, 100, visibility.clone());
+    let mut codes: Vec<_> = repo
+        .get_all_stats(filter.clone())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.code)
+        .collect();
+    codes.sort();
+
+    assert_eq!(codes, ["mine", "team"]);
+    assert_eq!(repo.count_all_links(None, &visibility).await.unwrap(), 2);
+    assert!(
+        repo.get_stats_by_code("other", filter)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
 #[sqlx::test]
 async fn test_count_clicks_by_link_id(pool: PgPool) {
     let repo = PgStatsRepository::new(Arc::new(pool.clone()));
@@ -133,3 +429,49 @@ async fn test_count_clicks_by_link_id(pool: PgPool) {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), 7);
 }
+
+#[sqlx::test]
+async fn test_most_clicked_ranks_recent_clicks_of_live_links(pool: PgPool) {
+    let repo = PgStatsRepository::new(Arc::new(pool.clone()));
+
+    let domain_id = common::create_test_domain(&pool, "popular.com").await;
+    common::create_test_link(&pool, "top", "https://example.com/top", domain_id).await;
+    common::create_test_link(&pool, "second", "https://example.com/second", domain_id).await;
+    common::create_test_link(&pool, "stale", "https://example.com/stale", domain_id).await;
+    common::create_deleted_link(&pool, "deleted", "https://example.com/deleted", domain_id).await;
+    common::create_expired_link(&pool, "expired", "https://example.com/expired", domain_id).await;
+
+    for (code, clicks) in [
+        ("top", 5),
+        ("second", 3),
+        ("stale", 9),
+        ("deleted", 9),
+        ("expired", 9),
+    ] {
+        let link_id: i64 = sqlx::query_scalar!("SELECT id FROM links WHERE code = $1", code)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        for i in 1..=clicks {
+            common::create_test_click(&pool, link_id, &format!("10.0.0.{}", i)).await;
+        }
+    }
+    sqlx::query!(
+        "UPDATE link_clicks SET clicked_at = NOW() - INTERVAL '30 days'
+         WHERE link_id = (SELECT id FROM links WHERE code = 'stale')"
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let since = chrono::Utc::now() - chrono::Duration::days(7);
+    let links = repo.most_clicked(since, 10).await.unwrap();
+
+    let ranked: Vec<_> = links.iter().map(|l| (l.code.as_str(), l.clicks)).collect();
+    assert_eq!(ranked, vec![("top", 5), ("second", 3)]);
+    assert_eq!(links[0].domain, "popular.com");
+    assert_eq!(links[0].domain_id, domain_id);
+
+    let limited = repo.most_clicked(since, 1).await.unwrap();
+    assert_eq!(limited.len(), 1);
+}
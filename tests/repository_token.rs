@@ -1,13 +1,21 @@
+use chrono::{Duration, Utc};
 use sqlx::PgPool;
 use std::sync::Arc;
-use url_shortener::domain::repositories::TokenRepository;
+use url_shortener::domain::entities::{AuditContext, Scope};
+use url_shortener::domain::repositories::{NewApiToken, TokenRepository};
+use url_shortener::error::AppError;
 use url_shortener::infrastructure::persistence::PgTokenRepository;
 
 #[sqlx::test]
 async fn test_create_token(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    let result = repo.create_token("test-token", "hash123").await;
+    let result = repo
+        .create_token(
+            NewApiToken::new("test-token", "hash123"),
+            &AuditContext::cli(),
+        )
+        .await;
 
     assert!(result.is_ok());
     let token = result.unwrap();
@@ -20,22 +28,27 @@ async fn test_create_token(pool: PgPool) {
 async fn test_validate_token_valid(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    repo.create_token("valid-token", "validhash").await.unwrap();
+    repo.create_token(
+        NewApiToken::new("valid-token", "validhash"),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
 
-    let result = repo.validate_token("validhash").await;
+    let result = repo.validate_token(&["validhash".into()]).await;
 
     assert!(result.is_ok());
-    assert!(result.unwrap());
+    assert!(result.unwrap().is_some());
 }
 
 #[sqlx::test]
 async fn test_validate_token_invalid(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    let result = repo.validate_token("nonexistent").await;
+    let result = repo.validate_token(&["nonexistent".into()]).await;
 
     assert!(result.is_ok());
-    assert!(!result.unwrap());
+    assert!(result.unwrap().is_none());
 }
 
 #[sqlx::test]
@@ -43,15 +56,20 @@ async fn test_validate_token_revoked(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
     let token = repo
-        .create_token("revoked-token", "revokedhash")
+        .create_token(
+            NewApiToken::new("revoked-token", "revokedhash"),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    repo.revoke_token(token.id, &AuditContext::cli())
         .await
         .unwrap();
-    repo.revoke_token(token.id).await.unwrap();
 
-    let result = repo.validate_token("revokedhash").await;
+    let result = repo.validate_token(&["revokedhash".into()]).await;
 
     assert!(result.is_ok());
-    assert!(!result.unwrap());
+    assert!(result.unwrap().is_none());
 }
 
 #[sqlx::test]
@@ -59,7 +77,10 @@ async fn test_update_last_used(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool.clone()));
 
     let token = repo
-        .create_token("update-token", "updatehash")
+        .create_token(
+            NewApiToken::new("update-token", "updatehash"),
+            &AuditContext::cli(),
+        )
         .await
         .unwrap();
 
@@ -81,11 +102,17 @@ async fn test_update_last_used(pool: PgPool) {
 async fn test_list_tokens(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    repo.create_token("token1", "hash1").await.unwrap();
-    repo.create_token("token2", "hash2").await.unwrap();
-    repo.create_token("token3", "hash3").await.unwrap();
+    repo.create_token(NewApiToken::new("token1", "hash1"), &AuditContext::cli())
+        .await
+        .unwrap();
+    repo.create_token(NewApiToken::new("token2", "hash2"), &AuditContext::cli())
+        .await
+        .unwrap();
+    repo.create_token(NewApiToken::new("token3", "hash3"), &AuditContext::cli())
+        .await
+        .unwrap();
 
-    let result = repo.list_tokens().await;
+    let result = repo.list_tokens(None).await;
 
     assert!(result.is_ok());
     let tokens = result.unwrap();
@@ -96,7 +123,13 @@ async fn test_list_tokens(pool: PgPool) {
 async fn test_find_by_id(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    let created = repo.create_token("find-by-id", "findhash").await.unwrap();
+    let created = repo
+        .create_token(
+            NewApiToken::new("find-by-id", "findhash"),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
 
     let result = repo.find_by_id(created.id).await;
 
@@ -110,7 +143,12 @@ async fn test_find_by_id(pool: PgPool) {
 async fn test_find_by_name(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
-    repo.create_token("unique-name", "namehash").await.unwrap();
+    repo.create_token(
+        NewApiToken::new("unique-name", "namehash"),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
 
     let result = repo.find_by_name("unique-name").await;
 
@@ -125,11 +163,14 @@ async fn test_revoke_token(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool.clone()));
 
     let token = repo
-        .create_token("revoke-test", "revokehash")
+        .create_token(
+            NewApiToken::new("revoke-test", "revokehash"),
+            &AuditContext::cli(),
+        )
         .await
         .unwrap();
 
-    let result = repo.revoke_token(token.id).await;
+    let result = repo.revoke_token(token.id, &AuditContext::cli()).await;
     assert!(result.is_ok());
 
     let revoked_at =
@@ -146,12 +187,258 @@ async fn test_revoke_already_revoked(pool: PgPool) {
     let repo = PgTokenRepository::new(Arc::new(pool));
 
     let token = repo
-        .create_token("double-revoke", "doublehash")
+        .create_token(
+            NewApiToken::new("double-revoke", "doublehash"),
+            &AuditContext::cli(),
+        )
         .await
         .unwrap();
 
-    repo.revoke_token(token.id).await.unwrap();
-    let result = repo.revoke_token(token.id).await;
+    repo.revoke_token(token.id, &AuditContext::cli())
+        .await
+        .unwrap();
+    let result = repo.revoke_token(token.id, &AuditContext::cli()).await;
 
     assert!(result.is_ok());
 }
+
+#[sqlx::test]
+async fn test_validate_token_returns_scopes_and_domains(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    repo.create_token(
+        NewApiToken::new("scoped", "scopedhash")
+            .with_scopes(vec![Scope::LinksRead, Scope::StatsRead])
+            .with_domains(Some(vec![4, 5])),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
+
+    let token = repo
+        .validate_token(&["scopedhash".into()])
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(token.scopes, vec![Scope::LinksRead, Scope::StatsRead]);
+    assert_eq!(token.domain_ids, Some(vec![4, 5]));
+}
+
+#[sqlx::test]
+async fn test_validate_token_skips_unknown_scopes(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool.clone()));
+
+    let token = repo
+        .create_token(
+            NewApiToken::new("legacy", "legacyhash"),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    sqlx::query!(
+        "UPDATE api_tokens SET scopes = ARRAY['links:read', 'links:purge'] WHERE id = $1",
+        token.id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let token = repo
+        .validate_token(&["legacyhash".into()])
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(token.scopes, vec![Scope::LinksRead]);
+    assert!(token.domain_ids.is_none());
+}
+
+#[sqlx::test]
+async fn test_validate_token_expired(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    repo.create_token(
+        NewApiToken::new("expired", "expiredhash")
+            .with_expiry(Some(Utc::now() - Duration::minutes(1))),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
+    repo.create_token(
+        NewApiToken::new("expiring", "expiringhash")
+            .with_expiry(Some(Utc::now() + Duration::hours(1))),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
+
+    assert!(
+        repo.validate_token(&["expiredhash".into()])
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repo.validate_token(&["expiringhash".into()])
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[sqlx::test]
+async fn test_rotate_token_keeps_old_token_during_grace_period(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    let old = repo
+        .create_token(
+            NewApiToken::new("rotating", "oldhash")
+                .with_scopes(vec![Scope::StatsRead])
+                .with_domains(Some(vec![9])),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    let grace_until = Utc::now() + Duration::hours(24);
+
+    let new = repo
+        .rotate_token(old.id, "newhash", None, grace_until, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    assert_ne!(new.id, old.id);
+    assert_eq!(new.name, "rotating");
+    assert_eq!(new.scopes, vec![Scope::StatsRead]);
+    assert_eq!(new.domain_ids, Some(vec![9]));
+    assert!(new.expires_at.is_none());
+
+    let old = repo
+        .validate_token(&["oldhash".into()])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        old.expires_at.unwrap().timestamp_micros(),
+        grace_until.timestamp_micros()
+    );
+    assert!(
+        repo.validate_token(&["newhash".into()])
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let by_name = repo.find_by_name("rotating").await.unwrap().unwrap();
+    assert_eq!(by_name.id, new.id);
+}
+
+#[sqlx::test]
+async fn test_rotate_token_keeps_earlier_expiry(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    let soon = Utc::now() + Duration::hours(1);
+    let old = repo
+        .create_token(
+            NewApiToken::new("short-lived", "shorthash").with_expiry(Some(soon)),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+
+    repo.rotate_token(
+        old.id,
+        "replacementhash",
+        None,
+        Utc::now() + Duration::days(2),
+        &AuditContext::cli(),
+    )
+    .await
+    .unwrap();
+
+    let old = repo.find_by_id(old.id).await.unwrap().unwrap();
+    assert_eq!(
+        old.expires_at.unwrap().timestamp_micros(),
+        soon.timestamp_micros()
+    );
+}
+
+#[sqlx::test]
+async fn test_rotate_revoked_token_fails(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    let token = repo
+        .create_token(NewApiToken::new("gone", "gonehash"), &AuditContext::cli())
+        .await
+        .unwrap();
+    repo.revoke_token(token.id, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    let result = repo
+        .rotate_token(
+            token.id,
+            "unusedhash",
+            None,
+            Utc::now() + Duration::hours(1),
+            &AuditContext::cli(),
+        )
+        .await;
+
+    assert!(matches!(result, Err(AppError::NotFound { .. })));
+    assert!(
+        repo.validate_token(&["unusedhash".into()])
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[sqlx::test]
+async fn test_validate_token_matches_any_candidate_hash(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    repo.create_token(NewApiToken::new("multi", "k2:newmac"), &AuditContext::cli())
+        .await
+        .unwrap();
+
+    let token = repo
+        .validate_token(&["k1:oldmac".into(), "k2:newmac".into(), "oldmac".into()])
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(token.token_hash, "k2:newmac");
+    assert_eq!(token.key_id(), Some("k2"));
+}
+
+#[sqlx::test]
+async fn test_rehash_token(pool: PgPool) {
+    let repo = PgTokenRepository::new(Arc::new(pool));
+
+    let token = repo
+        .create_token(
+            NewApiToken::new("legacy", "legacymac"),
+            &AuditContext::cli(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(token.key_id(), None);
+
+    repo.rehash_token(token.id, "legacymac", "k1:newmac")
+        .await
+        .unwrap();
+    // A stale re-hash does not overwrite the current hash.
+    repo.rehash_token(token.id, "legacymac", "k0:othermac")
+        .await
+        .unwrap();
+
+    let token = repo.find_by_id(token.id).await.unwrap().unwrap();
+    assert_eq!(token.token_hash, "k1:newmac");
+    assert!(
+        repo.validate_token(&["legacymac".into()])
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::Role;
+use url_shortener::domain::repositories::UserRepository;
+use url_shortener::error::AppError;
+use url_shortener::infrastructure::persistence::PgUserRepository;
+
+#[sqlx::test]
+async fn test_upsert_sso_user_creates_then_updates(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+
+    let created = repo
+        .upsert_sso_user("sub-1", Some("ada@example.com"), Role::Viewer)
+        .await
+        .unwrap();
+    let updated = repo
+        .upsert_sso_user("sub-1", Some("ada@new.example.com"), Role::Admin)
+        .await
+        .unwrap();
+
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.email.as_deref(), Some("ada@new.example.com"));
+    assert_eq!(updated.role, Role::Admin);
+    assert_eq!(repo.list_users().await.unwrap().len(), 1);
+}
+
+#[sqlx::test]
+async fn test_upsert_sso_user_claims_user_created_by_email(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+    let team = repo.create_team("marketing", 1).await.unwrap();
+    let created = repo
+        .create_user("Ada@Example.com", Role::Editor, Some(team.id), 1)
+        .await
+        .unwrap();
+
+    let signed_in = repo
+        .upsert_sso_user("sub-1", Some("ada@example.com"), Role::Viewer)
+        .await
+        .unwrap();
+
+    assert_eq!(signed_in.id, created.id);
+    assert_eq!(signed_in.sso_subject.as_deref(), Some("sub-1"));
+    assert_eq!(signed_in.role, Role::Viewer);
+    assert_eq!(signed_in.team_id, Some(team.id));
+}
+
+#[sqlx::test]
+async fn test_upsert_sso_user_without_email(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+
+    let first = repo
+        .upsert_sso_user("sub-1", None, Role::Viewer)
+        .await
+        .unwrap();
+    let second = repo
+        .upsert_sso_user("sub-2", None, Role::Viewer)
+        .await
+        .unwrap();
+
+    assert_eq!(first.email, None);
+    assert_ne!(first.id, second.id);
+}
+
+#[sqlx::test]
+async fn test_set_role_and_team(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+    let team = repo.create_team("sales", 1).await.unwrap();
+    let user = repo
+        .create_user("bob@example.com", Role::Viewer, None, 1)
+        .await
+        .unwrap();
+
+    repo.set_role(user.id, Role::Editor).await.unwrap();
+    let moved = repo.set_team(user.id, Some(team.id)).await.unwrap();
+
+    assert_eq!(moved.role, Role::Editor);
+    assert_eq!(moved.team_id, Some(team.id));
+    assert_eq!(
+        repo.find_by_email("BOB@example.com").await.unwrap(),
+        Some(moved)
+    );
+    assert!(matches!(
+        repo.set_role(user.id + 1, Role::Admin).await,
+        Err(AppError::NotFound { .. })
+    ));
+}
+
+#[sqlx::test]
+async fn test_set_role_rejects_single_sign_on_users(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+    let user = repo
+        .upsert_sso_user("sub-1", Some("ada@example.com"), Role::Viewer)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        repo.set_role(user.id, Role::Admin).await,
+        Err(AppError::Conflict { .. })
+    ));
+    assert_eq!(
+        repo.find_by_email("ada@example.com")
+            .await
+            .unwrap()
+            .map(|u| u.role),
+        Some(Role::Viewer)
+    );
+}
+
+#[sqlx::test]
+async fn test_duplicate_email_and_team_name_conflict(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+
+    repo.create_user("eve@example.com", Role::Viewer, None, 1)
+        .await
+        .unwrap();
+    repo.create_team("ops", 1).await.unwrap();
+
+    assert!(matches!(
+        repo.create_user("eve@example.com", Role::Admin, None, 1)
+            .await,
+        Err(AppError::Conflict { .. })
+    ));
+    assert!(matches!(
+        repo.create_team("ops", 1).await,
+        Err(AppError::Conflict { .. })
+    ));
+    assert_eq!(
+        repo.find_team_by_name("ops", 1).await.unwrap().map(|t| t.name),
+        Some("ops".to_string())
+    );
+}
+
+#[sqlx::test]
+async fn test_duplicate_team_reports_its_name(pool: PgPool) {
+    let repo = PgUserRepository::new(Arc::new(pool));
+
+    repo.create_team("support", 1).await.unwrap();
+    let result = repo.create_team("support", 1).await;
+
+    let Err(AppError::Conflict { message, details }) = result else {
+        panic!("expected a conflict, got {result:?}");
+    };
+    assert_eq!(message, "A team with this name already exists");
+    assert_eq!(details["field"], "name");
+}
+
+#[sqlx::test]
+async fn test_team_names_are_unique_per_workspace(pool: PgPool) {
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    let repo = PgUserRepository::new(Arc::new(pool));
+
+    let default_team = repo.create_team("marketing", 1).await.unwrap();
+    let acme_team = repo.create_team("marketing", workspace_id).await.unwrap();
+
+    assert_ne!(default_team.id, acme_team.id);
+    assert_eq!(
+        repo.find_team_by_name("marketing", workspace_id)
+            .await
+            .unwrap(),
+        Some(acme_team)
+    );
+    assert!(matches!(
+        repo.create_team("marketing", workspace_id).await,
+        Err(AppError::Conflict { .. })
+    ));
+}
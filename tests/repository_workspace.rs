@@ -0,0 +1,114 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use url_shortener::domain::entities::{DEFAULT_WORKSPACE_ID, WorkspaceQuotas};
+use url_shortener::domain::repositories::WorkspaceRepository;
+use url_shortener::error::AppError;
+use url_shortener::infrastructure::persistence::PgWorkspaceRepository;
+
+#[sqlx::test]
+async fn test_default_workspace_exists(pool: PgPool) {
+    let repo = PgWorkspaceRepository::new(Arc::new(pool));
+
+    let workspace = repo
+        .find_by_id(DEFAULT_WORKSPACE_ID)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(workspace.name, "default");
+    assert_eq!(workspace.quotas, WorkspaceQuotas::default());
+}
+
+#[sqlx::test]
+async fn test_create_and_find_workspace(pool: PgPool) {
+    let repo = PgWorkspaceRepository::new(Arc::new(pool));
+    let quotas = WorkspaceQuotas {
+        max_links: Some(10),
+        max_domains: Some(2),
+        monthly_click_allowance: None,
+    };
+
+    let created = repo.create("acme", quotas).await.unwrap();
+    let found = repo.find_by_name("acme").await.unwrap().unwrap();
+
+    assert_eq!(found.id, created.id);
+    assert_eq!(found.quotas, quotas);
+    assert!(repo.find_by_name("missing").await.unwrap().is_none());
+
+    let names: Vec<_> = repo
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|w| w.name)
+        .collect();
+    assert_eq!(names, vec!["acme", "default"]);
+}
+
+#[sqlx::test]
+async fn test_create_duplicate_workspace_conflicts(pool: PgPool) {
+    let repo = PgWorkspaceRepository::new(Arc::new(pool));
+
+    let result = repo.create("default", WorkspaceQuotas::default()).await;
+
+    assert!(matches!(result, Err(AppError::Conflict { .. })));
+}
+
+#[sqlx::test]
+async fn test_set_quotas(pool: PgPool) {
+    let repo = PgWorkspaceRepository::new(Arc::new(pool));
+    let workspace = repo
+        .create("acme", WorkspaceQuotas::default())
+        .await
+        .unwrap();
+    let quotas = WorkspaceQuotas {
+        max_links: None,
+        max_domains: Some(1),
+        monthly_click_allowance: Some(1000),
+    };
+
+    let updated = repo.set_quotas(workspace.id, quotas).await.unwrap();
+
+    assert_eq!(updated.quotas, quotas);
+
+    let missing = repo.set_quotas(999_999, quotas).await;
+    assert!(matches!(missing, Err(AppError::NotFound { .. })));
+}
+
+#[sqlx::test]
+async fn test_usage_counts_only_own_resources(pool: PgPool) {
+    let repo = PgWorkspaceRepository::new(Arc::new(pool.clone()));
+    let workspace_id = common::create_test_workspace(&pool, "acme").await;
+    let domain_id = common::create_workspace_domain(&pool, "go.acme.test", workspace_id).await;
+    common::create_test_link(&pool, "acme1", "https://example.com/1", domain_id).await;
+    common::create_deleted_link(&pool, "acme2", "https://example.com/2", domain_id).await;
+
+    let default_domain = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "other", "https://example.com/3", default_domain).await;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO link_clicks (link_id, clicked_at)
+        SELECT id, NOW() FROM links WHERE code = 'acme1'
+        UNION ALL
+        SELECT id, NOW() - INTERVAL '40 days' FROM links WHERE code = 'acme1'
+        UNION ALL
+        SELECT id, NOW() FROM links WHERE code = 'other'
+        "#
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(repo.count_links(workspace_id).await.unwrap(), 1);
+    assert_eq!(repo.count_domains(workspace_id).await.unwrap(), 1);
+    assert_eq!(
+        repo.count_clicks_since(workspace_id, Utc::now() - Duration::days(30))
+            .await
+            .unwrap(),
+        1
+    );
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{Router, routing::post};
+use opentelemetry::trace::TracerProvider as _;
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing_subscriber::layer::SubscriberExt;
+use url_shortener::domain::click_worker::{ClickWorkerConfig, run_click_worker};
+use url_shortener::infrastructure::persistence::{
+    PgDomainRepository, PgLinkRepository, PgStatsRepository,
+};
+use url_shortener::telemetry;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+type Received = Arc<Mutex<Vec<Bytes>>>;
+
+/// Minimal OTLP/HTTP collector: records every `POST /v1/traces` payload.
+async fn start_collector() -> (SocketAddr, Received) {
+    let received: Received = Arc::default();
+
+    let app = Router::new()
+        .route(
+            "/v1/traces",
+            post(|State(received): State<Received>, body: Bytes| async move {
+                received.lock().unwrap().push(body);
+                StatusCode::OK
+            }),
+        )
+        .with_state(received.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, received)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[sqlx::test]
+async fn test_redirect_and_click_spans_exported_with_incoming_trace(pool: PgPool) {
+    let (addr, received) = start_collector().await;
+
+    telemetry::install_propagator();
+    let provider =
+        telemetry::init_tracer_provider(&format!("http://{addr}"), "url-shortener-test").unwrap();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let domain_id = common::get_default_domain(&pool).await;
+    common::create_test_link(&pool, "traced", "https://example.com/traced", domain_id).await;
+
+    let (state, mut rx) = common::create_test_state(pool.clone());
+    let server = common::test_server(state);
+
+    let response = server
+        .get("/traced")
+        .add_header("Host", "s.example.com")
+        .add_header("X-Forwarded-For", common::client_ip())
+        .add_header("traceparent", TRACEPARENT)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::TEMPORARY_REDIRECT);
+
+    // The click event carries the request's trace into the worker.
+    let event = rx.try_recv().unwrap();
+    let trace_context = event.trace_context.clone().unwrap();
+    assert!(trace_context.contains(TRACE_ID));
+
+    let pool = Arc::new(pool);
+    let (tx, worker_rx) = mpsc::channel(1);
+    tx.send(event).await.unwrap();
+    drop(tx);
+    run_click_worker(
+        worker_rx,
+        Arc::new(PgStatsRepository::new(pool.clone())),
+        Arc::new(PgDomainRepository::new(pool.clone())),
+        Arc::new(PgLinkRepository::new(pool.clone())),
+        ClickWorkerConfig::new(1),
+    )
+    .await;
+
+    let flush_provider = provider.clone();
+    tokio::task::spawn_blocking(move || flush_provider.force_flush())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let payload: Vec<u8> = received
+        .lock()
+        .unwrap()
+        .iter()
+        .flat_map(|b| b.to_vec())
+        .collect();
+    assert!(!payload.is_empty(), "collector received no spans");
+
+    assert!(contains(&payload, &hex::decode(TRACE_ID).unwrap()));
+    assert!(contains(&payload, b"url-shortener-test"));
+    for span_name in [
+        "request",
+        "link_repository.find_by_code",
+        "click.batch",
+        "stats_repository.record_clicks",
+    ] {
+        assert!(
+            contains(&payload, span_name.as_bytes()),
+            "span {span_name} was not exported"
+        );
+    }
+
+    tokio::task::spawn_blocking(move || provider.shutdown())
+        .await
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,252 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use serde_json::json;
+use sqlx::PgPool;
+use url_shortener::domain::entities::AuditContext;
+
+const TOKEN: &str = "web-session-token";
+
+/// Returns the `Set-Cookie` header that sets `name`.
+fn set_cookie_header(response: &TestResponse, name: &str) -> String {
+    response
+        .iter_headers_by_name("set-cookie")
+        .map(|value| value.to_str().unwrap().to_string())
+        .find(|value| value.starts_with(&format!("{name}=")))
+        .unwrap_or_else(|| panic!("no Set-Cookie for {name}"))
+}
+
+/// Returns the value set for the cookie `name`.
+fn cookie_value(response: &TestResponse, name: &str) -> String {
+    let header = set_cookie_header(response, name);
+    let pair = header.split(';').next().unwrap();
+    pair.split_once('=').unwrap().1.to_string()
+}
+
+/// Logs in and returns the `Cookie` header value and the CSRF token.
+async fn login(server: &TestServer, ip: &str) -> (String, String) {
+    let response = server
+        .post("/dashboard/login")
+        .add_header("X-Forwarded-For", ip)
+        .json(&json!({ "token": TOKEN }))
+        .await;
+    response.assert_status(StatusCode::NO_CONTENT);
+
+    let session = cookie_value(&response, "session");
+    let csrf = cookie_value(&response, "csrf_token");
+    (format!("session={session}; csrf_token={csrf}"), csrf)
+}
+
+#[sqlx::test]
+async fn test_login_sets_session_and_csrf_cookies(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let response = server
+        .post("/dashboard/login")
+        .add_header("X-Forwarded-For", "10.0.3.1")
+        .json(&json!({ "token": TOKEN }))
+        .await;
+
+    response.assert_status(StatusCode::NO_CONTENT);
+    let session = set_cookie_header(&response, "session");
+    assert!(!session.contains(TOKEN));
+    assert!(session.contains("HttpOnly"));
+    assert!(session.contains("SameSite=Strict"));
+    assert!(session.contains("Secure"));
+    assert!(session.contains(&format!("Max-Age={}", 12 * 3600)));
+
+    let csrf = set_cookie_header(&response, "csrf_token");
+    assert!(!csrf.contains("HttpOnly"));
+    assert!(csrf.contains("SameSite=Strict"));
+}
+
+#[sqlx::test]
+async fn test_login_with_invalid_token_is_unauthorized(pool: PgPool) {
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let response = server
+        .post("/dashboard/login")
+        .add_header("X-Forwarded-For", "10.0.3.2")
+        .json(&json!({ "token": "wrong" }))
+        .await;
+
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(response.maybe_header("set-cookie").is_none());
+}
+
+#[sqlx::test]
+async fn test_session_opens_dashboard_and_api(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+    let (cookies, _) = login(&server, "10.0.3.3").await;
+
+    let page = server
+        .get("/dashboard/links")
+        .add_header("X-Forwarded-For", "10.0.3.3")
+        .add_header("Cookie", &cookies)
+        .await;
+    page.assert_status_ok();
+
+    let api = server
+        .get("/api/v1/domains")
+        .add_header("X-Forwarded-For", "10.0.3.3")
+        .add_header("Cookie", &cookies)
+        .await;
+    api.assert_status_ok();
+}
+
+#[sqlx::test]
+async fn test_raw_token_cookie_no_longer_authenticates(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let response = server
+        .get("/dashboard")
+        .add_header("X-Forwarded-For", "10.0.3.4")
+        .add_header("Cookie", format!("auth_token={TOKEN}"))
+        .await;
+
+    response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(response.header("location"), "/dashboard/login");
+}
+
+#[sqlx::test]
+async fn test_tampered_session_cookie_redirects_to_login(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+    let (cookies, _) = login(&server, "10.0.3.5").await;
+
+    let (session_id, _signature) = cookies
+        .trim_start_matches("session=")
+        .split_once('.')
+        .unwrap();
+    let forged = format!("session={session_id}.{}", "0".repeat(64));
+
+    let response = server
+        .get("/dashboard")
+        .add_header("X-Forwarded-For", "10.0.3.5")
+        .add_header("Cookie", forged)
+        .await;
+
+    response.assert_status(StatusCode::SEE_OTHER);
+}
+
+#[sqlx::test]
+async fn test_state_changing_api_call_requires_csrf_token(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+    let (cookies, csrf) = login(&server, "10.0.3.6").await;
+    let body = json!({ "urls": [{ "url": "https://example.com/csrf" }] });
+
+    let missing = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.3.6")
+        .add_header("Cookie", &cookies)
+        .json(&body)
+        .await;
+    missing.assert_status(StatusCode::FORBIDDEN);
+
+    let wrong = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.3.6")
+        .add_header("Cookie", &cookies)
+        .add_header("X-CSRF-Token", "not-the-token")
+        .json(&body)
+        .await;
+    wrong.assert_status(StatusCode::FORBIDDEN);
+
+    let valid = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.3.6")
+        .add_header("Cookie", &cookies)
+        .add_header("X-CSRF-Token", &csrf)
+        .json(&body)
+        .await;
+    valid.assert_status_ok();
+}
+
+#[sqlx::test]
+async fn test_bearer_token_needs_no_csrf_token(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+
+    let response = server
+        .post("/api/v1/shorten")
+        .add_header("X-Forwarded-For", "10.0.3.7")
+        .authorization_bearer(TOKEN)
+        .json(&json!({ "urls": [{ "url": "https://example.com/bearer" }] }))
+        .await;
+
+    response.assert_status_ok();
+}
+
+#[sqlx::test]
+async fn test_logout_invalidates_session(pool: PgPool) {
+    common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let server = common::test_server(state);
+    let (cookies, csrf) = login(&server, "10.0.3.8").await;
+
+    let without_csrf = server
+        .post("/dashboard/logout")
+        .add_header("X-Forwarded-For", "10.0.3.8")
+        .add_header("Cookie", &cookies)
+        .await;
+    without_csrf.assert_status(StatusCode::FORBIDDEN);
+
+    let logout = server
+        .post("/dashboard/logout")
+        .add_header("X-Forwarded-For", "10.0.3.8")
+        .add_header("Cookie", &cookies)
+        .add_header("X-CSRF-Token", &csrf)
+        .await;
+    logout.assert_status(StatusCode::NO_CONTENT);
+    assert!(set_cookie_header(&logout, "session").contains("Max-Age=0"));
+    assert!(set_cookie_header(&logout, "csrf_token").contains("Max-Age=0"));
+
+    // The old cookie is dead even if the browser kept it.
+    let page = server
+        .get("/dashboard")
+        .add_header("X-Forwarded-For", "10.0.3.8")
+        .add_header("Cookie", &cookies)
+        .await;
+    page.assert_status(StatusCode::SEE_OTHER);
+
+    let api = server
+        .get("/api/v1/domains")
+        .add_header("X-Forwarded-For", "10.0.3.8")
+        .add_header("Cookie", &cookies)
+        .await;
+    api.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test]
+async fn test_revoking_token_ends_its_sessions(pool: PgPool) {
+    let id = common::create_test_api_token(&pool, "web", TOKEN).await;
+    let (state, _rx) = common::create_test_state(pool);
+    let auth_service = state.auth_service.clone();
+    let server = common::test_server(state);
+    let (cookies, _) = login(&server, "10.0.3.9").await;
+
+    auth_service
+        .revoke_token(id, None, &AuditContext::cli())
+        .await
+        .unwrap();
+
+    let response = server
+        .get("/dashboard")
+        .add_header("X-Forwarded-For", "10.0.3.9")
+        .add_header("Cookie", &cookies)
+        .await;
+
+    response.assert_status(StatusCode::SEE_OTHER);
+}